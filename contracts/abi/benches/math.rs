@@ -0,0 +1,80 @@
+//! Benchmarks for curve, price and AMM math
+//!
+//! Save a baseline with `cargo bench -p fair-launch-abi -- --save-baseline main`
+//! and compare against it with `--baseline main`. Changes beyond the noise
+//! threshold configured below are reported as regressions.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fair_launch_abi::{amm, bonding_curve, BondingCurveConfig, Price};
+use primitive_types::U256;
+use std::time::Duration;
+
+/// Shared configuration: changes under 5% are treated as noise
+fn config() -> Criterion {
+    Criterion::default()
+        .noise_threshold(0.05)
+        .significance_level(0.01)
+        .measurement_time(Duration::from_secs(5))
+}
+
+fn curve_math(c: &mut Criterion) {
+    let config = BondingCurveConfig::default();
+    let supply = U256::from(500_000_000u64);
+    let amount = U256::from(1_000_000u64);
+
+    let mut group = c.benchmark_group("bonding_curve");
+    group.bench_function("buy_cost", |b| {
+        b.iter(|| {
+            bonding_curve::calculate_buy_cost(
+                black_box(supply),
+                black_box(amount),
+                config.k,
+                config.scale,
+            )
+        })
+    });
+    group.bench_function("sell_return", |b| {
+        b.iter(|| {
+            bonding_curve::calculate_sell_return(
+                black_box(supply),
+                black_box(amount),
+                config.k,
+                config.scale,
+            )
+        })
+    });
+    group.bench_function("current_price", |b| {
+        b.iter(|| bonding_curve::calculate_current_price(black_box(supply), config.k, config.scale))
+    });
+    group.finish();
+}
+
+fn price_math(c: &mut Criterion) {
+    let mut group = c.benchmark_group("price");
+    group.bench_function("from_ratio", |b| {
+        b.iter(|| Price::from_ratio(black_box(U256::from(69_000)), black_box(U256::from(1_000_000_000u64))))
+    });
+    group.bench_function("to_string", |b| {
+        let price = Price::from_ratio(U256::from(69_000), U256::from(1_000_000_000u64)).unwrap();
+        b.iter(|| black_box(price).to_string())
+    });
+    group.finish();
+}
+
+fn amm_math(c: &mut Criterion) {
+    let reserve_in = U256::from(1_000_000_000u64);
+    let reserve_out = U256::from(69_000);
+
+    c.bench_function("amm/constant_product_output", |b| {
+        b.iter(|| {
+            amm::constant_product_output(black_box(U256::from(10_000)), reserve_in, reserve_out)
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = curve_math, price_math, amm_math
+}
+criterion_main!(benches);
@@ -0,0 +1,131 @@
+/**
+ * Property-based tests for bonding curve mathematics
+ *
+ * Parameters cover realistic curves (k up to 10^9, scale 10^3..10^12) and
+ * supplies up to 10^24; cases whose intermediates overflow are skipped, since
+ * the contracts reject those trades with `CurveError::Overflow`.
+ */
+
+#[cfg(test)]
+mod bonding_curve_property_tests {
+    use crate::bonding_curve::*;
+    use primitive_types::U256;
+    use proptest::prelude::*;
+
+    fn k() -> impl Strategy<Value = U256> {
+        (1u64..=1_000_000_000).prop_map(U256::from)
+    }
+
+    fn scale() -> impl Strategy<Value = U256> {
+        (1_000u64..=1_000_000_000_000).prop_map(U256::from)
+    }
+
+    /// Token amounts up to 10^24 base units
+    fn amount() -> impl Strategy<Value = U256> {
+        (0u128..=1_000_000_000_000_000_000_000_000).prop_map(U256::from)
+    }
+
+    fn any_u256() -> impl Strategy<Value = U256> {
+        any::<[u64; 4]>().prop_map(U256)
+    }
+
+    proptest! {
+        #[test]
+        fn price_never_decreases_with_supply(
+            supply in amount(),
+            extra in amount(),
+            k in k(),
+            scale in scale(),
+        ) {
+            let lower = calculate_current_price(supply, k, scale);
+            let higher = calculate_current_price(supply + extra, k, scale);
+            prop_assume!(lower.is_ok() && higher.is_ok());
+            prop_assert!(higher.unwrap() >= lower.unwrap());
+        }
+
+        #[test]
+        fn buy_cost_never_decreases_with_amount(
+            supply in amount(),
+            amount in amount(),
+            extra in amount(),
+            k in k(),
+            scale in scale(),
+        ) {
+            let smaller = calculate_buy_cost(supply, amount, k, scale);
+            let larger = calculate_buy_cost(supply, amount + extra, k, scale);
+            prop_assume!(smaller.is_ok() && larger.is_ok());
+            prop_assert!(larger.unwrap() >= smaller.unwrap());
+        }
+
+        #[test]
+        fn segmented_buys_cost_the_same_as_one_buy(
+            supply in amount(),
+            first in amount(),
+            second in amount(),
+            k in k(),
+            scale in scale(),
+        ) {
+            let whole = calculate_buy_cost(supply, first + second, k, scale);
+            let head = calculate_buy_cost(supply, first, k, scale);
+            let tail = calculate_buy_cost(supply + first, second, k, scale);
+            prop_assume!(whole.is_ok() && head.is_ok() && tail.is_ok());
+            prop_assert_eq!(whole.unwrap(), head.unwrap() + tail.unwrap());
+        }
+
+        #[test]
+        fn selling_a_buy_returns_its_cost(
+            supply in amount(),
+            amount in amount(),
+            k in k(),
+            scale in scale(),
+        ) {
+            let cost = calculate_buy_cost(supply, amount, k, scale);
+            let proceeds = calculate_sell_return(supply + amount, amount, k, scale);
+            prop_assume!(cost.is_ok() && proceeds.is_ok());
+            prop_assert_eq!(cost.unwrap(), proceeds.unwrap());
+        }
+
+        #[test]
+        fn tokens_for_cost_is_the_largest_affordable_amount(
+            supply in amount(),
+            budget in amount(),
+            k in k(),
+            scale in scale(),
+        ) {
+            let bought = calculate_tokens_for_cost(supply, budget, k, scale).unwrap();
+            if let Ok(cost) = calculate_buy_cost(supply, bought, k, scale) {
+                prop_assert!(cost <= budget);
+            }
+            if let Ok(cost) = calculate_buy_cost(supply, bought + 1, k, scale) {
+                prop_assert!(cost > budget);
+            }
+        }
+
+        #[test]
+        fn max_sell_never_exceeds_supply_or_available(
+            supply in amount(),
+            available in amount(),
+            k in k(),
+            scale in scale(),
+        ) {
+            let Ok(sellable) = calculate_max_sell_for_return(supply, available, k, scale) else {
+                return Ok(());
+            };
+            prop_assert!(sellable <= supply);
+            let proceeds = calculate_sell_return(supply, sellable, k, scale).unwrap();
+            prop_assert!(proceeds <= available);
+        }
+
+        #[test]
+        fn curve_math_never_panics(
+            supply in any_u256(),
+            amount in any_u256(),
+            k in any_u256(),
+            scale in any_u256(),
+        ) {
+            let _ = calculate_current_price(supply, k, scale);
+            let _ = calculate_buy_cost(supply, amount, k, scale);
+            let _ = calculate_sell_return(supply, amount, k, scale);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 #[cfg(feature = "service")]
 use async_graphql::SimpleObject;
-use linera_sdk::linera_base_types::{Account, Timestamp};
+use linera_sdk::linera_base_types::{
+    Account, AccountSignature, Amount, ApplicationId, BlockHeight, ChainId, CryptoHash, TimeDelta,
+    Timestamp,
+};
 use primitive_types::U256;
 use serde::{Deserialize, Serialize};
 
@@ -30,6 +33,237 @@ pub struct BondingCurveConfig {
     pub max_supply: U256,
     /// Creator fee percentage (0-10000, where 300 = 3%)
     pub creator_fee_bps: u16,
+    /// Minimum unique holders required before graduation (None = no requirement)
+    #[serde(default)]
+    pub min_holders: Option<u64>,
+    /// Decimal places of one curve base unit relative to the native token
+    /// (0 = whole tokens, 18 = attos; see `units`)
+    #[serde(default)]
+    pub base_decimals: u8,
+    /// Currency buys are paid in and sells are paid out in
+    #[serde(default)]
+    pub base_asset: BaseAsset,
+    /// Whether holders may transfer tokens before graduation
+    /// (false keeps curve positions non-transferable to prevent OTC sniping)
+    #[serde(default = "default_transfers_enabled")]
+    pub transfers_enabled_during_curve: bool,
+    /// How the raised funds are divided at graduation
+    #[serde(default)]
+    pub graduation_plan: GraduationPlan,
+    /// How long the graduated pool stays locked before the creator may
+    /// withdraw its liquidity (None = locked permanently)
+    #[serde(default)]
+    pub pool_lock_micros: Option<u64>,
+    /// End of the whitelist phase: until then only accounts the creator added
+    /// with `AddToWhitelist` may buy (None = open to everyone from launch)
+    #[serde(default)]
+    pub whitelist_ends_at: Option<Timestamp>,
+    /// How the supply is first sold; a Dutch auction offers `max_supply`
+    /// instead of running the curve
+    #[serde(default)]
+    pub launch_mechanism: LaunchMechanism,
+    /// Whether the supply left unsold at graduation is burned rather than
+    /// minted into the pool
+    #[serde(default)]
+    pub burn_unsold_on_graduation: bool,
+    /// Raise a curve launch must reach by `deadline`; short of it the launch
+    /// fails and holders redeem their tokens for refunds (None = no minimum)
+    #[serde(default)]
+    pub min_raise: Option<U256>,
+    /// When `min_raise` must have been reached
+    #[serde(default)]
+    pub deadline: Option<Timestamp>,
+    /// Surcharge on curve buys in the first moments after the curve opens
+    /// to everyone (None = no surcharge)
+    #[serde(default)]
+    pub snipe_tax: Option<SnipeTaxConfig>,
+    /// Minimum time between two curve trades of the same account, in
+    /// microseconds (None = no rate limit)
+    #[serde(default)]
+    pub min_trade_interval_micros: Option<u64>,
+    /// Creator fee discounts by 30-day trading volume, copied from the
+    /// factory's admin-set table at launch (see `fee_tiers`)
+    #[serde(default)]
+    pub fee_tiers: Vec<FeeTier>,
+}
+
+fn default_transfers_enabled() -> bool {
+    true
+}
+
+/// How a launch sells its supply before graduating to a DEX pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LaunchMechanism {
+    /// Continuous buys and sells priced by the bonding curve
+    #[default]
+    BondingCurve,
+    /// Declining-price auction settled at a single clearing price
+    DutchAuction(DutchAuctionConfig),
+    /// Fixed-price sale over a window, pro-rated when oversubscribed
+    FixedPriceSale(FixedPriceSaleConfig),
+}
+
+impl LaunchMechanism {
+    /// The auction parameters, if this launch is a Dutch auction
+    pub fn auction(&self) -> Option<&DutchAuctionConfig> {
+        match self {
+            LaunchMechanism::DutchAuction(config) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// The sale parameters, if this launch is a fixed-price sale
+    pub fn fixed_price_sale(&self) -> Option<&FixedPriceSaleConfig> {
+        match self {
+            LaunchMechanism::FixedPriceSale(config) => Some(config),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LaunchMechanism {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LaunchMechanism::BondingCurve => write!(f, "bonding_curve"),
+            LaunchMechanism::DutchAuction(_) => write!(f, "dutch_auction"),
+            LaunchMechanism::FixedPriceSale(_) => write!(f, "fixed_price_sale"),
+        }
+    }
+}
+
+/// Early-buy surcharge of a curve launch (see `snipe_tax`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnipeTaxConfig {
+    /// Surcharge when the curve opens, in basis points of the buy's cost
+    pub initial_bps: u16,
+    /// Time for the surcharge to decay to zero
+    pub duration_micros: u64,
+    pub destination: SnipeTaxDestination,
+}
+
+/// Where the snipe tax collected on early buys goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnipeTaxDestination {
+    /// Paid to the platform treasury: the factory replaces whatever account
+    /// a launch asks for with its own treasury
+    Treasury(Account),
+    /// Kept by the token application and never paid out
+    Burn,
+}
+
+/// Creator fee discount for traders whose 30-day volume on a launch reached
+/// `min_volume` base units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub min_volume: U256,
+    /// Share of the creator fee waived, in basis points
+    pub discount_bps: u16,
+}
+
+/// GraphQL-friendly version of FeeTier
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct FeeTierGQL {
+    pub min_volume: String,
+    pub discount_bps: u16,
+}
+
+impl From<&FeeTier> for FeeTierGQL {
+    fn from(tier: &FeeTier) -> Self {
+        Self {
+            min_volume: tier.min_volume.to_string(),
+            discount_bps: tier.discount_bps,
+        }
+    }
+}
+
+/// Parameters of a Dutch auction launch (see `dutch_auction`)
+///
+/// The price falls linearly from `start_price` at `starts_at` to
+/// `floor_price` after `duration_micros`. Every bidder pays the clearing
+/// price: the price when the supply sold out, or the floor if it never did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DutchAuctionConfig {
+    pub start_price: Price,
+    pub floor_price: Price,
+    pub starts_at: Timestamp,
+    pub duration_micros: u64,
+}
+
+/// Parameters of a fixed-price sale launch (see `fixed_price_sale`)
+///
+/// Buyers commit base currency between `starts_at` and the end of the
+/// window; `max_supply` is then divided at `price`, pro rata to the
+/// commitments if they exceed its cost, and the excess is refunded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedPriceSaleConfig {
+    pub price: Price,
+    pub starts_at: Timestamp,
+    pub duration_micros: u64,
+}
+
+/// Currency a launch is denominated in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BaseAsset {
+    /// The chain's native token
+    #[default]
+    Native,
+    /// A fungible token application (e.g. a stablecoin), paid via cross-application calls
+    Fungible(ApplicationId),
+}
+
+impl std::fmt::Display for BaseAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaseAsset::Native => write!(f, "native"),
+            BaseAsset::Fungible(application_id) => write!(f, "{}", application_id),
+        }
+    }
+}
+
+/// Division of the raised funds at graduation, in basis points summing to 10_000
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct GraduationPlan {
+    /// Share seeded into the DEX pool
+    pub pool_bps: u16,
+    /// Share paid to the creator
+    pub creator_bps: u16,
+    /// Share burned: kept by the token application with no path to withdraw it
+    pub burn_bps: u16,
+}
+
+impl GraduationPlan {
+    /// Whether the shares add up to exactly 100% and something reaches the pool
+    pub fn is_valid(&self) -> bool {
+        let total =
+            u32::from(self.pool_bps) + u32::from(self.creator_bps) + u32::from(self.burn_bps);
+        total == 10_000 && self.pool_bps > 0
+    }
+
+    /// Split `amount` into (pool, creator, burn) shares
+    ///
+    /// Rounding dust goes to the pool, so the shares always add up to `amount`.
+    pub fn split(&self, amount: Amount) -> (Amount, Amount, Amount) {
+        let attos = u128::from(amount);
+        let share = |bps: u16| {
+            let bps = u128::from(bps);
+            Amount::from_attos(attos / 10_000 * bps + attos % 10_000 * bps / 10_000)
+        };
+        let creator = share(self.creator_bps);
+        let burn = share(self.burn_bps);
+        (amount.saturating_sub(creator).saturating_sub(burn), creator, burn)
+    }
+}
+
+impl Default for GraduationPlan {
+    fn default() -> Self {
+        Self {
+            pool_bps: 10_000,
+            creator_bps: 0,
+            burn_bps: 0,
+        }
+    }
 }
 
 /// GraphQL-friendly version of BondingCurveConfig
@@ -41,6 +275,27 @@ pub struct BondingCurveConfigGQL {
     pub target_raise: String,
     pub max_supply: String,
     pub creator_fee_bps: u16,
+    pub min_holders: Option<u64>,
+    pub base_decimals: u8,
+    /// "native" or the fungible application ID
+    pub base_asset: String,
+    pub transfers_enabled_during_curve: bool,
+    pub graduation_plan: GraduationPlan,
+    /// Pool lock length in microseconds (None = permanent)
+    pub pool_lock_micros: Option<u64>,
+    /// End of the whitelist phase in microseconds (None = no whitelist phase)
+    pub whitelist_ends_at: Option<String>,
+    /// "bonding_curve", "dutch_auction" or "fixed_price_sale"
+    pub launch_mechanism: String,
+    pub burn_unsold_on_graduation: bool,
+    pub min_raise: Option<String>,
+    /// Minimum raise deadline in microseconds
+    pub deadline: Option<String>,
+    /// Snipe tax at launch in basis points (None = no snipe tax)
+    pub snipe_tax_bps: Option<u16>,
+    pub snipe_tax_duration_micros: Option<u64>,
+    pub min_trade_interval_micros: Option<u64>,
+    pub fee_tiers: Vec<FeeTierGQL>,
 }
 
 impl From<&BondingCurveConfig> for BondingCurveConfigGQL {
@@ -51,6 +306,21 @@ impl From<&BondingCurveConfig> for BondingCurveConfigGQL {
             target_raise: config.target_raise.to_string(),
             max_supply: config.max_supply.to_string(),
             creator_fee_bps: config.creator_fee_bps,
+            min_holders: config.min_holders,
+            base_decimals: config.base_decimals,
+            base_asset: config.base_asset.to_string(),
+            transfers_enabled_during_curve: config.transfers_enabled_during_curve,
+            graduation_plan: config.graduation_plan,
+            pool_lock_micros: config.pool_lock_micros,
+            whitelist_ends_at: config.whitelist_ends_at.map(|t| t.micros().to_string()),
+            launch_mechanism: config.launch_mechanism.to_string(),
+            burn_unsold_on_graduation: config.burn_unsold_on_graduation,
+            min_raise: config.min_raise.map(|raise| raise.to_string()),
+            deadline: config.deadline.map(|t| t.micros().to_string()),
+            snipe_tax_bps: config.snipe_tax.map(|tax| tax.initial_bps),
+            snipe_tax_duration_micros: config.snipe_tax.map(|tax| tax.duration_micros),
+            min_trade_interval_micros: config.min_trade_interval_micros,
+            fee_tiers: config.fee_tiers.iter().map(FeeTierGQL::from).collect(),
         }
     }
 }
@@ -63,10 +333,57 @@ impl Default for BondingCurveConfig {
             target_raise: U256::from(69_000),
             max_supply: U256::from(1_000_000_000u64),
             creator_fee_bps: 300, // 3% default fee
+            min_holders: None,
+            base_decimals: 0,
+            base_asset: BaseAsset::Native,
+            transfers_enabled_during_curve: true,
+            graduation_plan: GraduationPlan::default(),
+            pool_lock_micros: None,
+            whitelist_ends_at: None,
+            launch_mechanism: LaunchMechanism::BondingCurve,
+            burn_unsold_on_graduation: false,
+            min_raise: None,
+            deadline: None,
+            snipe_tax: None,
+            min_trade_interval_micros: None,
+            fee_tiers: Vec::new(),
+        }
+    }
+}
+
+/// Range of creator fees the factory accepts for new launches, in basis points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct CreatorFeeBounds {
+    pub min_bps: u16,
+    pub max_bps: u16,
+}
+
+impl CreatorFeeBounds {
+    /// Whether `fee_bps` lies within the bounds (inclusive)
+    pub fn contains(&self, fee_bps: u16) -> bool {
+        (self.min_bps..=self.max_bps).contains(&fee_bps)
+    }
+}
+
+impl Default for CreatorFeeBounds {
+    fn default() -> Self {
+        Self {
+            min_bps: 0,
+            max_bps: 1_000, // 10%
         }
     }
 }
 
+/// A token bytecode version the platform rolls out to new launches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BytecodeRelease {
+    pub version: u32,
+    /// Release notes, e.g. what changed and how existing tokens migrate
+    pub description: String,
+    pub registered_at: Timestamp,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenLaunch {
     pub token_id: String,
@@ -78,6 +395,56 @@ pub struct TokenLaunch {
     pub is_graduated: bool,
     pub created_at: Timestamp,
     pub dex_pool_id: Option<String>,
+    #[serde(default)]
+    pub moderation_status: ModerationStatus,
+    /// Risk score maintained by the factory (see `risk`)
+    #[serde(default)]
+    pub risk: risk::RiskScore,
+}
+
+/// Moderation state of a launch, set by the factory admin
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModerationStatus {
+    #[default]
+    Clear,
+    /// Hidden from default listings (e.g. rugged or illegal)
+    Flagged { reason: String, flagged_at: Timestamp },
+}
+
+impl ModerationStatus {
+    pub fn is_flagged(&self) -> bool {
+        matches!(self, ModerationStatus::Flagged { .. })
+    }
+
+    /// Reason given when the token was flagged
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            ModerationStatus::Clear => None,
+            ModerationStatus::Flagged { reason, .. } => Some(reason),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TokenIdSeed {
+    factory_chain: ChainId,
+    token_count: u64,
+    creator: Account,
+}
+
+impl linera_sdk::linera_base_types::BcsHashable<'_> for TokenIdSeed {}
+
+/// ID of the `token_count`-th launch of the factory on `factory_chain`
+///
+/// The ID is fixed before the token's chain is opened, so it cannot come
+/// from the chain ID; the factory records each token's chain separately.
+pub fn derive_token_id(factory_chain: ChainId, token_count: u64, creator: Account) -> String {
+    CryptoHash::new(&TokenIdSeed {
+        factory_chain,
+        token_count,
+        creator,
+    })
+    .to_string()
 }
 
 #[derive(Debug, Clone)]
@@ -92,17 +459,138 @@ pub struct TokenLaunchGQL {
     pub dex_pool_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Trade {
     pub token_id: String,
     pub trader: Account,  // Changed from ChainId to Account
     pub is_buy: bool,
     pub token_amount: U256,
     pub currency_amount: U256,
+    pub price: Price,
+    pub timestamp: Timestamp,
+    /// Optional trader-supplied note (at most `MAX_MEMO_LENGTH` bytes)
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// Account that referred the buyer (resolved from a factory referral code)
+    #[serde(default)]
+    pub referrer: Option<Account>,
+    /// Whether the trade looks like wash trading (see `WashTradeStats`)
+    #[serde(default)]
+    pub flagged: bool,
+}
+
+/// A trade as stored before prices were fixed-point, with `price` in whole
+/// base units per token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegacyTrade {
+    pub token_id: String,
+    pub trader: Account,
+    pub is_buy: bool,
+    pub token_amount: U256,
+    pub currency_amount: U256,
     pub price: U256,
     pub timestamp: Timestamp,
 }
 
+impl From<LegacyTrade> for Trade {
+    fn from(trade: LegacyTrade) -> Self {
+        Trade {
+            token_id: trade.token_id,
+            trader: trade.trader,
+            is_buy: trade.is_buy,
+            token_amount: trade.token_amount,
+            currency_amount: trade.currency_amount,
+            // A legacy price too large to scale saturates rather than wraps
+            price: Price::from_integer(trade.price).unwrap_or(Price::from_raw(U256::MAX)),
+            timestamp: trade.timestamp,
+            memo: None,
+            referrer: None,
+            flagged: false,
+        }
+    }
+}
+
+/// Maximum length of a trade or transfer memo in bytes
+pub const MAX_MEMO_LENGTH: usize = 140;
+
+/// Window in which an account's buys and sells are netted against each other
+pub const WASH_TRADE_WINDOW_MICROS: u64 = 3_600_000_000;
+
+/// How close to zero, in basis points of the larger side, an account's buys
+/// and sells must net within the window to be flagged as wash trading
+pub const WASH_TRADE_TOLERANCE_BPS: u64 = 500;
+
+/// An account's buy/sell alternation within the current wash trading window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WashTradeStats {
+    pub window_start: Timestamp,
+    /// Tokens bought and sold since `window_start`
+    pub bought: U256,
+    pub sold: U256,
+    /// Direction of the account's latest trade
+    pub last_is_buy: Option<bool>,
+    /// Switches between buying and selling since `window_start`
+    pub alternations: u64,
+    /// Flagged trades and their currency volume over the account's lifetime
+    pub flagged_trades: u64,
+    pub flagged_volume: U256,
+    pub last_flagged_at: Option<Timestamp>,
+}
+
+impl WashTradeStats {
+    /// Empty stats with a window opening at `at`
+    pub fn new(at: Timestamp) -> Self {
+        Self {
+            window_start: at,
+            bought: U256::zero(),
+            sold: U256::zero(),
+            last_is_buy: None,
+            alternations: 0,
+            flagged_trades: 0,
+            flagged_volume: U256::zero(),
+            last_flagged_at: None,
+        }
+    }
+
+    /// Fold in one of the account's trades, returning whether it is flagged:
+    /// it reverses the account's direction and leaves its buys and sells in
+    /// the window netting to within `WASH_TRADE_TOLERANCE_BPS` of zero
+    pub fn record(&mut self, trade: &Trade) -> bool {
+        if trade.timestamp.delta_since(self.window_start).as_micros() >= WASH_TRADE_WINDOW_MICROS {
+            self.window_start = trade.timestamp;
+            self.bought = U256::zero();
+            self.sold = U256::zero();
+            self.alternations = 0;
+        }
+        if trade.is_buy {
+            self.bought = self.bought.saturating_add(trade.token_amount);
+        } else {
+            self.sold = self.sold.saturating_add(trade.token_amount);
+        }
+        let reversed = self.last_is_buy.is_some_and(|last_is_buy| last_is_buy != trade.is_buy);
+        if reversed {
+            self.alternations += 1;
+        }
+        self.last_is_buy = Some(trade.is_buy);
+
+        let (smaller, larger) = (self.bought.min(self.sold), self.bought.max(self.sold));
+        let net = larger - smaller;
+        let flagged = reversed
+            && !smaller.is_zero()
+            && net.saturating_mul(U256::from(10_000))
+                <= larger.saturating_mul(U256::from(WASH_TRADE_TOLERANCE_BPS));
+        if flagged {
+            self.flagged_trades += 1;
+            self.flagged_volume = self.flagged_volume.saturating_add(trade.currency_amount);
+            self.last_flagged_at = Some(trade.timestamp);
+        }
+        flagged
+    }
+}
+
+/// Maximum number of accounts one `AddToWhitelist` call may add
+pub const MAX_WHITELIST_BATCH: usize = 500;
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "service", derive(SimpleObject))]
 pub struct TradeGQL {
@@ -110,7 +598,12 @@ pub struct TradeGQL {
     pub is_buy: bool,
     pub token_amount: String,
     pub currency_amount: String,
+    /// Price as an integer scaled by 10^price_decimals
     pub price: String,
+    pub price_decimals: u8,
+    pub memo: Option<String>,
+    pub referrer: Option<String>,
+    pub flagged: bool,
 }
 
 impl From<&Trade> for TradeGQL {
@@ -120,12 +613,16 @@ impl From<&Trade> for TradeGQL {
             is_buy: trade.is_buy,
             token_amount: trade.token_amount.to_string(),
             currency_amount: trade.currency_amount.to_string(),
-            price: trade.price.to_string(),
+            price: trade.price.raw().to_string(),
+            price_decimals: price::PRICE_DECIMALS,
+            memo: trade.memo.clone(),
+            referrer: trade.referrer.map(|referrer| referrer.to_string()),
+            flagged: trade.flagged,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UserPosition {
     pub token_id: String,
     pub balance: U256,
@@ -133,6 +630,38 @@ pub struct UserPosition {
     pub trades_count: u64,
 }
 
+/// A page of token state in the canonical form shared by `stateExport` and
+/// `TokenOperation::ImportState`, entries in key order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateChunk {
+    Balances(Vec<(Account, U256)>),
+    Positions(Vec<(Account, UserPosition)>),
+    /// Trades with their sequence numbers
+    Trades(Vec<(u64, Trade)>),
+}
+
+impl StateChunk {
+    /// Number of entries in the chunk
+    pub fn len(&self) -> usize {
+        match self {
+            StateChunk::Balances(entries) => entries.len(),
+            StateChunk::Positions(entries) => entries.len(),
+            StateChunk::Trades(entries) => entries.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash the factory admin approves before the chunk can be imported
+    pub fn commitment(&self) -> CryptoHash {
+        CryptoHash::new(self)
+    }
+}
+
+impl linera_sdk::linera_base_types::BcsHashable<'_> for StateChunk {}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "service", derive(SimpleObject))]
 pub struct UserPositionGQL {
@@ -159,19 +688,141 @@ pub enum FactoryOperation {
     CreateToken {
         metadata: TokenMetadata,
         curve_config: Option<BondingCurveConfig>,
+        /// Name of a factory curve template (mutually exclusive with curve_config)
+        curve_template: Option<String>,
+    },
+    /// Register a named bonding curve template (admin only)
+    AddCurveTemplate {
+        name: String,
+        config: BondingCurveConfig,
+    },
+    /// Move finalized (graduated) tokens to the archive (admin only)
+    ArchiveTokens { max_tokens: u64 },
+    /// Register a referral code pointing at the caller (token creators only)
+    RegisterReferralCode { code: String },
+    /// Hide a token from default listings (admin only)
+    FlagToken { token_id: String, reason: String },
+    /// Restore a flagged token (admin only)
+    UnflagToken { token_id: String },
+    /// Allow a token to import the `stateExport` chunks with these
+    /// commitments, checked against the source token (admin only)
+    ApproveStateImport {
+        token_id: String,
+        commitments: Vec<CryptoHash>,
+    },
+    /// Create a token and fill the creator's first buy before anyone else can trade
+    CreateTokenAndBuy {
+        metadata: TokenMetadata,
+        curve_config: Option<BondingCurveConfig>,
+        /// Tokens to buy for the creator
+        initial_buy: U256,
+        /// Slippage bound for the initial buy, in base currency
+        max_cost: U256,
+    },
+    /// Receive the launch events a factory chain publishes on its event stream
+    Subscribe { publisher: ChainId },
+    /// Stop receiving a factory chain's launch events
+    Unsubscribe { publisher: ChainId },
+    /// Set the creator fee range accepted for new launches and templates (admin only)
+    SetCreatorFeeBounds { min_bps: u16, max_bps: u16 },
+    /// Set the shortest pool lock a launch may choose (admin only)
+    ///
+    /// Launches without a lock duration stay permanently locked and are always accepted.
+    SetMinPoolLock { micros: u64 },
+    /// Set the volume-based creator fee discounts given to new launches (admin only)
+    SetFeeTiers { tiers: Vec<FeeTier> },
+    /// Buy several tokens at once: (token_id, amount, max_cost) per order
+    ///
+    /// Each order is sent to its token chain and filled there independently;
+    /// the `batch` query reports which orders filled and which failed.
+    BatchBuy { orders: Vec<(String, U256, U256)> },
+    /// Register a token bytecode version (admin only); launches reporting an
+    /// older `contract_version` are then listed as outdated
+    RegisterBytecode { version: u32, description: String },
+    /// Move up to `max_entries` traders recorded before the trader sketch
+    /// into it (callable by anyone)
+    MigrateTraders { max_entries: u64 },
+    /// Add up to `max_entries` launches from before the platform raise and
+    /// volume totals to them (callable by anyone)
+    BackfillStats { max_entries: u64 },
+}
+
+/// Event stream a factory chain publishes `FactoryEvent`s to
+///
+/// Other applications can subscribe to it directly with the factory's
+/// application ID; user chains use `FactoryOperation::Subscribe`.
+pub const FACTORY_EVENTS_STREAM: &[u8] = b"factory_events";
+
+/// Launch lifecycle events pushed to subscriber chains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FactoryEvent {
+    /// A token was registered and its curve is about to open
+    NewLaunch {
+        token_id: String,
+        creator: Account,
+        metadata: TokenMetadata,
+    },
+    /// A token's curve completed and its DEX pool is live
+    Graduated {
+        token_id: String,
+        pool_id: String,
     },
 }
 
+/// Allowance approval signed off-chain by the owner and submitted by anyone
+/// with `TokenOperation::PermitApprove`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permit {
+    /// Token the permit is valid for, so it cannot be replayed on another token
+    pub token_id: String,
+    pub owner: Account,
+    pub spender: Account,
+    pub amount: U256,
+    /// Must equal the owner's current permit nonce; each permit is usable once
+    pub nonce: u64,
+    /// Last time the permit can be submitted
+    pub deadline: Timestamp,
+    /// Expiry of the resulting allowance, as in `TokenOperation::Approve`
+    pub expires_at: Option<Timestamp>,
+}
+
+impl linera_sdk::linera_base_types::BcsSignable<'_> for Permit {}
+
 /// Operations for Token contract
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TokenOperation {
     Buy {
         amount: U256,
         max_cost: U256, // Slippage protection
+        /// Fill only up to the remaining supply instead of failing past max_supply
+        #[serde(default)]
+        fill_or_cap: bool,
+        #[serde(default)]
+        memo: Option<String>,
+        /// Referring account, e.g. resolved from a factory referral code
+        #[serde(default)]
+        referrer: Option<Account>,
+        /// Supply the quote was taken at; the buy fails if it has changed since
+        #[serde(default)]
+        expected_supply: Option<U256>,
+    },
+    /// Buy as many tokens as `spend` base units afford, snipe tax included
+    BuyWithBudget {
+        spend: U256,
+        min_tokens_out: U256, // Slippage protection
+        #[serde(default)]
+        memo: Option<String>,
+        #[serde(default)]
+        referrer: Option<Account>,
     },
     Sell {
         amount: U256,
         min_return: U256, // Slippage protection
+        #[serde(default)]
+        memo: Option<String>,
+        /// Supply the quote was taken at; the sell fails if it has changed since
+        #[serde(default)]
+        expected_supply: Option<U256>,
     },
     /// Called by factory when token is created
     Initialize {
@@ -181,164 +832,4015 @@ pub enum TokenOperation {
     },
     /// Graduate to DEX when curve completes
     Graduate,
+    /// Move tokens to the swap application towards a pool the signer then
+    /// opens with `SwapOperation::CreatePool` (graduated tokens only)
+    DepositPoolTokens {
+        amount: U256,
+    },
+    /// Move the signer's stablecoin into the application to seed the
+    /// configured stable pool at graduation (before graduation only)
+    DepositStableLiquidity {
+        amount: Amount,
+    },
     /// Approve spender to transfer tokens on behalf of owner
     Approve {
         spender: Account,
         amount: U256,
+        /// Optional expiry after which the allowance can no longer be used
+        expires_at: Option<Timestamp>,
     },
     /// Transfer tokens from owner to recipient (requires allowance)
     TransferFrom {
         from: Account,
         to: Account,
         amount: U256,
+        #[serde(default)]
+        memo: Option<String>,
+    },
+    /// Raise the caller's allowance for a spender by `added`
+    ///
+    /// Unlike `Approve`, the change is relative, so a spender that spends
+    /// the old allowance first cannot also spend the new one in full.
+    IncreaseAllowance {
+        spender: Account,
+        added: U256,
+    },
+    /// Lower the caller's allowance for a spender by `subtracted` (fails below zero)
+    DecreaseAllowance {
+        spender: Account,
+        subtracted: U256,
+    },
+    /// Let `operator` transfer any amount of the caller's tokens (or stop it)
+    ///
+    /// Operators bypass allowances in `TransferFrom`, e.g. for the swap
+    /// application, so trades need no per-amount approvals.
+    SetOperator {
+        operator: Account,
+        approved: bool,
+    },
+    /// Delete the caller's allowance for a spender immediately
+    Revoke {
+        spender: Account,
+    },
+    /// Delete every allowance the caller has granted
+    RevokeAll,
+    /// Move up to `max_entries` allowances off the legacy string keys
+    /// (callable by anyone; writes migrate their own entry regardless)
+    MigrateAllowances {
+        max_entries: u64,
+    },
+    /// Move up to `max_entries` trades off the legacy string keys, oldest
+    /// first (callable by anyone; pruning waits until none are left)
+    MigrateTrades {
+        max_entries: u64,
+    },
+    /// Rank up to `max_entries` more balances held before the largest holders
+    /// were tracked (callable by anyone)
+    BackfillTopHolders {
+        max_entries: u64,
+    },
+    /// Set an allowance from a permit signed by its owner (callable by anyone)
+    PermitApprove {
+        permit: Permit,
+        signature: AccountSignature,
+    },
+    /// Update mutable metadata (creator only; name and symbol are immutable)
+    UpdateMetadata {
+        description: String,
+        image_url: Option<String>,
+        twitter: Option<String>,
+        telegram: Option<String>,
+        website: Option<String>,
+    },
+    /// Burn balances below `threshold` (creator only, after graduation)
+    ///
+    /// Each call examines a batch of balances, resuming where the last one
+    /// stopped. Balances held by the token and swap applications are kept.
+    SweepDust {
+        threshold: U256,
+    },
+    /// Stake tokens to earn a share of trading fees (after graduation)
+    Stake {
+        amount: U256,
+    },
+    /// Return staked tokens to the caller's balance
+    Unstake {
+        amount: U256,
+    },
+    /// Collect accrued staking rewards in base currency
+    ClaimRewards,
+    /// Commit current balances to a merkle root and fund a reward pool for them (co-creators)
+    TakeSnapshot {
+        reward: Amount,
+    },
+    /// Claim a pro-rata share of a snapshot's reward pool by proving the snapshot balance
+    ClaimSnapshotReward {
+        proof: SnapshotProof,
+    },
+    /// Escrow `total_budget` and spend `amount_per_interval` of it on a buy every `interval`
+    CreateRecurringBuy {
+        amount_per_interval: Amount,
+        interval: TimeDelta,
+        total_budget: Amount,
+    },
+    /// Stop a recurring buy and refund its unspent budget (owner only)
+    CancelRecurringBuy {
+        schedule_id: u64,
+    },
+    /// Escrow tokens from the caller's balance until `unlock_at`, e.g. to
+    /// prove a team allocation is locked
+    LockTokens {
+        amount: U256,
+        unlock_at: Timestamp,
+    },
+    /// Return every expired lock of the caller to its balance
+    ClaimUnlocked,
+    /// Propose a creator-only action (co-creators only); the proposer's
+    /// approval counts, so it runs at once with a threshold of one
+    ProposeCreatorAction {
+        action: CreatorAction,
+    },
+    /// Approve a pending creator action (co-creators only), running it once
+    /// the threshold is met
+    ApproveCreatorAction {
+        proposal_id: u64,
+    },
+    /// Load a chunk of a `stateExport` from an earlier token version
+    /// (creator only, and only until this token records its own first trade)
+    ///
+    /// The chunk's commitment must have been approved by the factory admin,
+    /// and imported balances are paid for at the curve price.
+    ImportState {
+        chunk: StateChunk,
+    },
+    /// Delete the oldest trades so that at most `keep_last` remain (co-creators
+    /// only); counts, volumes and positions are kept
+    PruneTrades {
+        keep_last: u64,
+    },
+    /// Let `accounts` buy during the whitelist phase (creator only, and only
+    /// before `whitelist_ends_at`; at most `MAX_WHITELIST_BATCH` per call)
+    AddToWhitelist {
+        accounts: Vec<Account>,
+    },
+    /// Bid for `amount` tokens of a Dutch auction launch at the current price,
+    /// paying at most `max_cost`; the fill is capped at the unsold supply
+    AuctionBid {
+        amount: U256,
+        max_cost: U256,
+    },
+    /// Settle a Dutch auction whose time ran out at the floor price (anyone)
+    SettleAuction,
+    /// Collect what a settled auction's bids paid above the clearing price
+    ClaimAuctionRefund,
+    /// Commit `amount` of base currency to a fixed-price sale
+    CommitToSale {
+        amount: Amount,
+    },
+    /// Allocate the next batch of commitments of a fixed-price sale whose
+    /// window closed, refunding any excess (anyone; repeat until settled)
+    SettleSale,
+    /// Burn the caller's tokens for a pro-rata share of the raise once the
+    /// launch missed `min_raise` by its deadline
+    RedeemRefund,
+    /// Read an account's balance (for cross-application callers; no state change)
+    QueryBalance {
+        owner: Account,
+    },
+    /// Read the allowance usable by `spender` right now (zero once expired)
+    QueryAllowance {
+        owner: Account,
+        spender: Account,
     },
+    /// Read the curve's supply, raise and price
+    QueryCurveState,
 }
 
-/// Cross-chain messages
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum Message {
-    /// Factory → Token: Token created
-    TokenCreated {
+/// Response to a token operation
+///
+/// State-changing operations answer `Ok`; the `Query*` operations answer
+/// with the value read, so other applications can integrate on-chain via
+/// `call_application` instead of GraphQL.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenResponse {
+    #[default]
+    Ok,
+    Balance(U256),
+    Allowance(U256),
+    CurveState(CurveState),
+}
+
+/// Shares of the target raise tracked as milestones, in basis points
+pub const RAISE_MILESTONES_BPS: [u16; 4] = [2_500, 5_000, 7_500, 10_000];
+
+/// Event stream a token chain publishes `TokenEvent`s to
+pub const TOKEN_EVENTS_STREAM: &[u8] = b"token_events";
+
+/// GraphQL response extension carrying the queried state's `state_version`
+///
+/// The factory, token and swap states bump the counter in every block that
+/// mutates them, so clients can cheaply tell whether cached query results
+/// are stale.
+pub const STATE_VERSION_EXTENSION: &str = "stateVersion";
+
+/// Curve progress events pushed to subscribers of a token chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenEvent {
+    /// The raise crossed a share of the target raise for the first time
+    MilestoneReached {
         token_id: String,
-        creator: Account,  // Changed from ChainId to Account
-        metadata: TokenMetadata,
-        curve_config: BondingCurveConfig,
+        /// One of `RAISE_MILESTONES_BPS`
+        milestone_bps: u16,
+        total_raised: U256,
+        reached_at: Timestamp,
     },
-
-    /// Token → User: Trade executed
-    TradeExecuted {
+    /// The raised funds were divided according to the launch's `GraduationPlan`
+    GraduationFundsSplit {
         token_id: String,
-        trader: Account,  // Changed from ChainId to Account
-        is_buy: bool,
-        token_amount: U256,
-        currency_amount: U256,
-        new_price: U256,
+        to_pool: Amount,
+        to_creator: Amount,
+        burned: Amount,
+        split_at: Timestamp,
     },
-
-    /// Token → Swap: Graduate to DEX
-    GraduateToken {
+    /// A Dutch auction launch closed at a single clearing price
+    AuctionSettled {
         token_id: String,
-        total_supply: U256,
+        clearing_price: Price,
+        sold: U256,
         total_raised: U256,
+        settled_at: Timestamp,
     },
-
-    /// Swap → Token: Pool created
-    PoolCreated {
+    /// Every commitment to a fixed-price sale was allocated
+    SaleSettled {
+        token_id: String,
+        sold: U256,
+        total_committed: U256,
+        total_raised: U256,
+        settled_at: Timestamp,
+    },
+    /// The token's price, published on `price_feed::PRICE_FEED_STREAM`
+    PriceUpdate(price_feed::PriceUpdate),
+    /// The supply left unsold at graduation was burned instead of pooled
+    UnsoldSupplyBurned {
+        token_id: String,
+        amount: U256,
+        burned_at: Timestamp,
+    },
+    /// Tokens left out of the graduation pool to open it at the final launch
+    /// price were burned
+    PoolExcessBurned {
         token_id: String,
         pool_id: String,
+        amount: U256,
+        burned_at: Timestamp,
     },
-
-    /// Factory → All: New token launched (broadcast)
-    NewLaunch {
+    /// The launch missed its minimum raise by the deadline; holders redeem
+    LaunchFailed {
         token_id: String,
-        metadata: TokenMetadata,
-        creator: Account,  // Changed from ChainId to Account
+        total_raised: U256,
+        min_raise: U256,
+        failed_at: Timestamp,
     },
 }
 
-/// GraphQL-friendly version of PoolInfo from swap contract
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "service", derive(SimpleObject))]
-pub struct PoolInfoGQL {
-    pub pool_id: String,
-    pub token_id: String,
-    pub token_liquidity: String,
-    pub base_liquidity: String,
-    pub initial_ratio: String,
-    pub created_at: String,
-    pub is_locked: bool,
-    pub lock_expires_at: Option<String>,
-    pub trade_count: u64,
-    pub tvl: String,
+/// Curve snapshot returned by `TokenOperation::QueryCurveState`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurveState {
+    pub current_supply: U256,
+    pub max_supply: U256,
+    /// Raised so far, in base units
+    pub total_raised: U256,
+    /// Marginal curve price at the current supply
+    pub price: Price,
+    pub base_asset: BaseAsset,
+    pub base_decimals: u8,
+    pub is_graduated: bool,
+    /// DEX pool the token trades in after graduation
+    pub dex_pool_id: Option<String>,
 }
 
-/// Bonding curve calculations
-pub mod bonding_curve {
-    use super::*;
+/// Factory application parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FactoryParameters {
+    /// Native fee charged for each launch (zero disables the fee)
+    pub creation_fee: Amount,
+    /// Account receiving creation fees (defaults to the factory admin)
+    pub treasury: Option<Account>,
+    /// Launches allowed per creator within `launch_window_micros` (0 disables the limit)
+    pub max_launches_per_window: u32,
+    /// Length of the per-creator rate limit window
+    pub launch_window_micros: u64,
+    /// HTTP gateway for `ipfs://` image links (None uses `ipfs::DEFAULT_GATEWAY`)
+    pub ipfs_gateway: Option<String>,
+}
 
-    /// Calculate cost to buy `amount` tokens at current supply
-    /// Formula: Integral of k * (supply / scale)^2 from current_supply to new_supply
-    pub fn calculate_buy_cost(
-        current_supply: U256,
-        amount: U256,
-        k: U256,
-        scale: U256,
-    ) -> U256 {
-        let new_supply = current_supply + amount;
+impl Default for FactoryParameters {
+    fn default() -> Self {
+        Self {
+            creation_fee: Amount::ZERO,
+            treasury: None,
+            max_launches_per_window: 3,
+            launch_window_micros: 3_600_000_000, // 1 hour
+            ipfs_gateway: None,
+        }
+    }
+}
+
+/// Swap application parameters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwapParameters {
+    /// Factory chain that receives swap notifications (None disables them)
+    pub factory_chain_id: Option<ChainId>,
+    /// Buyback-and-burn funded by swap fees (None leaves every fee in the pools)
+    pub buyback: Option<BuybackConfig>,
+}
+
+/// Buyback-and-burn funded by a slice of each pool's swap fees
+///
+/// Fees paid in base currency accrue until `min_base` has built up or
+/// `interval_micros` have passed since the pool's last buyback, then buy
+/// tokens from the pool. Fees paid in the token skip the purchase and join
+/// the next burn directly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuybackConfig {
+    /// Share of each swap fee withheld for buybacks (10_000 = the whole fee)
+    pub fee_share_bps: u16,
+    /// Accrued base currency that triggers a buyback
+    pub min_base: U256,
+    /// Longest wait between buybacks while anything has accrued
+    pub interval_micros: u64,
+}
+
+/// Token application parameters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenParameters {
+    /// Swap application that receives the raised liquidity at graduation
+    pub swap_application_id: Option<ApplicationId>,
+    /// Chain hosting the swap application (defaults to the token's chain)
+    pub swap_chain_id: Option<ChainId>,
+    /// Factory chain that receives periodic token summaries and trade
+    /// notifications (None disables sync)
+    #[serde(default)]
+    pub factory_chain_id: Option<ChainId>,
+    /// Stablecoin-quoted pool seeded next to the native pool at graduation
+    #[serde(default)]
+    pub stable_pool: Option<StablePoolConfig>,
+    /// HTTP gateway for `ipfs://` image links (None uses `ipfs::DEFAULT_GATEWAY`)
+    #[serde(default)]
+    pub ipfs_gateway: Option<String>,
+    /// How often the token publishes its price to `price_feed` subscribers
+    #[serde(default)]
+    pub price_feed: price_feed::PriceFeedConfig,
+}
+
+/// Second, stablecoin-quoted pool opened at graduation
+///
+/// The stable side is the balance of `asset` held by the token application
+/// when the curve graduates (deposited with
+/// `TokenOperation::DepositStableLiquidity`), paired with up to
+/// `token_share_bps` of the graduating supply. Both pools open at the launch's
+/// final price, converted at `stable_per_native` for the stable pool; tokens
+/// the stablecoin cannot match at that price stay with the native pool.
+/// Ignored when the curve itself is denominated in `asset`, no rate is set or
+/// no stablecoin has been deposited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StablePoolConfig {
+    /// Fungible stablecoin application quoting the pool
+    pub asset: ApplicationId,
+    /// Decimal places of one stable base unit relative to the stablecoin (see `units`)
+    pub decimals: u8,
+    /// Share of the graduating supply paired with the stablecoin, in basis points
+    pub token_share_bps: u16,
+    /// Stable base units worth one native base unit, scaled like `Price`
+    #[serde(default)]
+    pub stable_per_native: Option<Price>,
+}
+
+impl StablePoolConfig {
+    /// The stable pool's opening price for a launch that ended at `final_price`
+    pub fn opening_price(&self, final_price: Price) -> Option<Price> {
+        let rate = self.stable_per_native?;
+        let price = wad::mul_wad(final_price.raw(), rate.raw())?;
+        (!price.is_zero()).then(|| Price::from_raw(price))
+    }
+}
+
+/// Stablecoin liquidity sent alongside a graduation message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StableLeg {
+    pub asset: ApplicationId,
+    pub decimals: u8,
+    /// Most tokens paired with the stablecoin, taken out of the native pool's share
+    pub token_liquidity: U256,
+    /// Opening price in stable base units, matching the native pool's
+    pub opening_price: Price,
+    /// Stablecoin moved to the swap application
+    pub receipt: TransferReceipt,
+}
+
+/// Creator buy executed together with token initialization
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InitialBuy {
+    pub amount: U256,
+    pub max_cost: U256,
+}
+
+/// Most orders a single `FactoryOperation::BatchBuy` may carry
+pub const MAX_BATCH_ORDERS: usize = 20;
+
+/// Outcome of one order of a batch buy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatchOrderStatus {
+    /// Sent to the token chain, not yet settled
+    Pending,
+    Filled,
+    Failed { reason: String },
+}
+
+/// One order of a batch buy, as tracked by the factory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchOrder {
+    pub token_id: String,
+    pub amount: U256,
+    pub max_cost: U256,
+    pub status: BatchOrderStatus,
+}
+
+/// Buys of several tokens submitted in one factory operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuyBatch {
+    pub buyer: Account,
+    pub orders: Vec<BatchOrder>,
+    pub created_at: Timestamp,
+}
+
+impl BuyBatch {
+    /// Whether every order has filled or failed
+    pub fn is_settled(&self) -> bool {
+        self.orders
+            .iter()
+            .all(|order| order.status != BatchOrderStatus::Pending)
+    }
+}
+
+/// Receipt for the native transfer sent alongside a graduation message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferReceipt {
+    pub from: Account,
+    pub to: Account,
+    pub amount: Amount,
+    pub transferred_at: Timestamp,
+}
+
+/// Swap chain's statement of a graduated pool's terms, kept by the token chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolAttestation {
+    pub pool_id: String,
+    pub token_liquidity: U256,
+    pub base_liquidity: U256,
+    pub initial_ratio: Price,
+    pub created_at: Timestamp,
+    pub is_locked: bool,
+    /// None = permanent lock
+    pub lock_expires_at: Option<Timestamp>,
+    /// Hash of the swap chain's PoolInfo record when the attestation was issued
+    pub pool_info_hash: CryptoHash,
+    pub attested_at: Timestamp,
+}
+
+/// Cross-chain messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Message {
+    /// Factory → Token: Token created
+    TokenCreated {
+        token_id: String,
+        creator: Account,  // Changed from ChainId to Account
+        metadata: TokenMetadata,
+        curve_config: BondingCurveConfig,
+        /// Creator's first buy, filled as part of initialization
+        initial_buy: Option<InitialBuy>,
+    },
+
+    /// Token → User: Trade executed
+    TradeExecuted {
+        token_id: String,
+        trader: Account,  // Changed from ChainId to Account
+        is_buy: bool,
+        token_amount: U256,
+        currency_amount: U256,
+        new_price: Price,
+        /// Creator fee charged on the trade, stakers' share included
+        #[serde(default)]
+        creator_fee: U256,
+    },
+
+    /// Token → Swap: Graduate to DEX
+    GraduateToken {
+        token_id: String,
+        total_supply: U256,
+        total_raised: U256,
+        /// Native liquidity moved to the swap application (None if nothing was sent)
+        #[serde(default)]
+        receipt: Option<TransferReceipt>,
+        /// Decimals of the curve base unit, for converting the receipt amount
+        #[serde(default)]
+        base_decimals: u8,
+        /// Asset the receipt amount is denominated in
+        #[serde(default)]
+        base_asset: BaseAsset,
+        /// Liquidity for a second, stablecoin-quoted pool
+        #[serde(default)]
+        stable_leg: Option<StableLeg>,
+        /// Pool lock length chosen at launch (None = permanent)
+        #[serde(default)]
+        lock_duration_micros: Option<u64>,
+        /// Account allowed to withdraw the liquidity once a time lock expires
+        #[serde(default)]
+        creator: Option<Account>,
+        /// Last price the launch sold at, which the pool opens at
+        /// (None = the average price `total_raised / total_supply`)
+        #[serde(default)]
+        final_price: Option<Price>,
+    },
+
+    /// Token → Swap: a graduated token's supply changed (burns, lock releases)
+    SupplyChanged {
+        token_id: String,
+        total_supply: U256,
+        /// Supply outside time locks
+        circulating_supply: U256,
+        reported_at: Timestamp,
+    },
+
+    /// Token → Swap: tokens moved to the swap application for a new pool
+    PoolTokensDeposited {
+        token_id: String,
+        depositor: Account,
+        amount: U256,
+    },
+
+    /// Swap → Token: tokens bought back from a pool with its fees, to be burned
+    BurnTokens {
+        token_id: String,
+        pool_id: String,
+        amount: U256,
+    },
+
+    /// Swap → Token: tokens left in a time-locked pool, withdrawn by its
+    /// creator once the lock expired
+    PoolTokensWithdrawn {
+        token_id: String,
+        pool_id: String,
+        creator: Account,
+        amount: U256,
+    },
+
+    /// Swap → Token: Pool created
+    PoolCreated {
+        token_id: String,
+        pool_id: String,
+        /// Pool terms and lock status as recorded by the swap chain
+        attestation: PoolAttestation,
+        /// Whether this is the stablecoin-quoted pool rather than the native one
+        #[serde(default)]
+        is_stable_pool: bool,
+        /// Tokens left out of the pool so it opens at the final launch price,
+        /// and so burned (zero on a resend)
+        #[serde(default)]
+        excess_burned: U256,
+    },
+
+    /// Factory → All: New token launched (broadcast)
+    NewLaunch {
+        token_id: String,
+        metadata: TokenMetadata,
+        creator: Account,  // Changed from ChainId to Account
+    },
+
+    /// Factory → Token: Moderation status changed
+    ModerationUpdate {
+        token_id: String,
+        status: ModerationStatus,
+    },
+
+    /// Factory → Token: State chunks the admin allows the token to import
+    StateImportApproved {
+        token_id: String,
+        commitments: Vec<CryptoHash>,
+    },
+
+    /// Token → Factory: Periodic snapshot of listing metrics
+    SyncTokenSummary {
+        token_id: String,
+        current_supply: U256,
+        total_raised: U256,
+        summary: TokenSummary,
+    },
+
+    /// Token → Buyer: A buy was rejected after payment and refunded
+    RefundIssued {
+        token_id: String,
+        buyer: Account,
+        amount: Amount,
+        reason: String,
+    },
+
+    /// Swap → Factory: A DEX swap executed, for platform statistics
+    SwapExecuted {
+        trader: Account,
+        /// Base currency moved by the swap
+        base_volume: U256,
+    },
+
+    /// Factory → Token: Fill one order of a batch buy for the authenticated buyer
+    BatchBuyOrder {
+        batch_id: u64,
+        order_index: u32,
+        token_id: String,
+        amount: U256,
+        max_cost: U256,
+    },
+
+    /// Token → Factory: A batch buy order filled (failures bounce back instead)
+    BatchOrderFilled {
+        batch_id: u64,
+        order_index: u32,
+        token_id: String,
+    },
+
+    /// Token → Factory: The bytecode version a token runs, sent once initialized
+    VersionAnnouncement {
+        token_id: String,
+        version: u32,
+    },
+}
+
+/// Compact listing metrics replicated from a token chain to the factory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenSummary {
+    pub price: Price,
+    /// Curve progress towards max supply in basis points
+    pub progress_bps: u16,
+    pub volume_24h: U256,
+    /// Volume over the current day and the six before it
+    #[serde(default)]
+    pub volume_7d: U256,
+    pub holder_count: u64,
+    /// Largest single balance as a share of the circulating supply, in basis points
+    #[serde(default)]
+    pub top_holder_bps: u16,
+    /// Token chain time the snapshot was taken
+    pub updated_at: Timestamp,
+}
+
+/// GraphQL-friendly version of TokenSummary
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct TokenSummaryGQL {
+    /// Price as an integer scaled by 10^price_decimals
+    pub price: String,
+    pub price_decimals: u8,
+    pub progress_bps: u16,
+    pub volume_24h: String,
+    pub volume_7d: String,
+    pub holder_count: u64,
+    pub top_holder_bps: u16,
+    pub updated_at: String,
+}
+
+impl From<&TokenSummary> for TokenSummaryGQL {
+    fn from(summary: &TokenSummary) -> Self {
+        Self {
+            price: summary.price.raw().to_string(),
+            price_decimals: price::PRICE_DECIMALS,
+            progress_bps: summary.progress_bps,
+            volume_24h: summary.volume_24h.to_string(),
+            volume_7d: summary.volume_7d.to_string(),
+            holder_count: summary.holder_count,
+            top_holder_bps: summary.top_holder_bps,
+            updated_at: summary.updated_at.micros().to_string(),
+        }
+    }
+}
+
+/// A native payment returned to a buyer whose buy could not be filled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Refund {
+    pub buyer: Account,
+    pub amount: Amount,
+    pub reason: String,
+    pub queued_at: Timestamp,
+    /// Whether the payment has been transferred back
+    pub issued: bool,
+}
+
+/// GraphQL-friendly version of Refund
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct RefundGQL {
+    pub refund_id: u64,
+    pub buyer: String,
+    pub amount: String,
+    pub reason: String,
+    pub queued_at: String,
+    pub issued: bool,
+}
+
+impl RefundGQL {
+    pub fn new(refund_id: u64, refund: &Refund) -> Self {
+        Self {
+            refund_id,
+            buyer: refund.buyer.to_string(),
+            amount: refund.amount.to_string(),
+            reason: refund.reason.clone(),
+            queued_at: refund.queued_at.micros().to_string(),
+            issued: refund.issued,
+        }
+    }
+}
+
+/// Merkle commitment to the token balances at a block height
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    /// Root over `merkle::leaf_hash` of every non-zero balance
+    pub root: CryptoHash,
+    pub block_height: BlockHeight,
+    pub taken_at: Timestamp,
+    pub holder_count: u64,
+    pub total_balance: U256,
+    /// Base currency shared pro rata among the snapshot's holders
+    pub reward_pool: Amount,
+    pub claimed: Amount,
+}
+
+/// Inclusion proof for one account's balance in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotProof {
+    pub snapshot_id: u64,
+    pub balance: U256,
+    /// Sibling hashes from the leaf up to the root
+    pub siblings: Vec<CryptoHash>,
+}
+
+/// GraphQL-friendly version of BalanceSnapshot
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct BalanceSnapshotGQL {
+    pub snapshot_id: u64,
+    pub root: String,
+    pub block_height: u64,
+    pub taken_at: String,
+    pub holder_count: u64,
+    pub total_balance: String,
+    pub reward_pool: String,
+    pub claimed: String,
+}
+
+impl BalanceSnapshotGQL {
+    pub fn new(snapshot_id: u64, snapshot: &BalanceSnapshot) -> Self {
+        Self {
+            snapshot_id,
+            root: snapshot.root.to_string(),
+            block_height: snapshot.block_height.0,
+            taken_at: snapshot.taken_at.micros().to_string(),
+            holder_count: snapshot.holder_count,
+            total_balance: snapshot.total_balance.to_string(),
+            reward_pool: snapshot.reward_pool.to_string(),
+            claimed: snapshot.claimed.to_string(),
+        }
+    }
+}
+
+/// A dollar-cost-averaging schedule funded from an escrowed budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurringBuy {
+    pub owner: Account,
+    pub amount_per_interval: Amount,
+    pub interval: TimeDelta,
+    /// Escrowed budget not yet spent or refunded
+    pub remaining_budget: Amount,
+    pub next_run_at: Timestamp,
+    pub created_at: Timestamp,
+    pub executions: u32,
+    pub tokens_bought: U256,
+    pub spent: Amount,
+    /// False once cancelled, exhausted or stopped by graduation
+    pub active: bool,
+}
+
+/// GraphQL-friendly version of RecurringBuy
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct RecurringBuyGQL {
+    pub schedule_id: u64,
+    pub owner: String,
+    pub amount_per_interval: String,
+    pub interval_micros: String,
+    pub remaining_budget: String,
+    pub next_run_at: String,
+    pub created_at: String,
+    pub executions: u32,
+    pub tokens_bought: String,
+    pub spent: String,
+    pub active: bool,
+}
+
+impl RecurringBuyGQL {
+    pub fn new(schedule_id: u64, schedule: &RecurringBuy) -> Self {
+        Self {
+            schedule_id,
+            owner: schedule.owner.to_string(),
+            amount_per_interval: schedule.amount_per_interval.to_string(),
+            interval_micros: schedule.interval.as_micros().to_string(),
+            remaining_budget: schedule.remaining_budget.to_string(),
+            next_run_at: schedule.next_run_at.micros().to_string(),
+            created_at: schedule.created_at.micros().to_string(),
+            executions: schedule.executions,
+            tokens_bought: schedule.tokens_bought.to_string(),
+            spent: schedule.spent.to_string(),
+            active: schedule.active,
+        }
+    }
+}
+
+/// Most accounts a token's creator set may hold
+pub const MAX_CO_CREATORS: usize = 10;
+
+/// Creator-only action, run once enough co-creators have approved it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CreatorAction {
+    /// Same as `TokenOperation::UpdateMetadata`
+    UpdateMetadata {
+        description: String,
+        image_url: Option<String>,
+        twitter: Option<String>,
+        telegram: Option<String>,
+        website: Option<String>,
+    },
+    /// Same as `TokenOperation::SweepDust`
+    SweepDust { threshold: U256 },
+    /// Replace the co-creators and the number of approvals actions need
+    SetCreatorSet { members: Vec<Account>, threshold: u16 },
+    /// Same as `TokenOperation::AddToWhitelist`
+    AddToWhitelist { accounts: Vec<Account> },
+    /// Same as `TokenOperation::ImportState`
+    ImportState { chunk: StateChunk },
+    /// Same as `TokenOperation::TakeSnapshot`; the reward is paid by the
+    /// member whose approval runs it
+    TakeSnapshot { reward: Amount },
+    /// Same as `TokenOperation::PruneTrades`
+    PruneTrades { keep_last: u64 },
+}
+
+/// An account's combined bids in a Dutch auction launch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionBid {
+    /// Tokens bought (already credited to the bidder)
+    pub amount: U256,
+    /// Base currency paid at the bid-time prices
+    pub paid: U256,
+    /// Whether the overpayment above the clearing price was collected
+    pub refunded: bool,
+}
+
+/// A launch that missed its minimum raise, and the refunds paid against it
+///
+/// The raise held when the launch failed is shared pro rata over the supply
+/// at that time, so the order holders redeem in does not change their share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchFailure {
+    pub failed_at: Timestamp,
+    /// Base currency available for refunds when the launch failed
+    pub refund_pool: Amount,
+    /// Supply the refund pool is shared over
+    pub supply: U256,
+    /// Refunds paid so far
+    pub redeemed: Amount,
+}
+
+impl LaunchFailure {
+    /// Refund owed for burning `balance` tokens
+    pub fn refund_for(&self, balance: U256) -> Amount {
+        let pool = U256::from(u128::from(self.refund_pool));
+        let share = wad::mul_div(pool, balance.min(self.supply), self.supply).unwrap_or_default();
+        Amount::from_attos(share.as_u128())
+    }
+}
+
+/// An account's commitment to a fixed-price sale
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaleCommitment {
+    /// Base currency committed
+    pub committed: U256,
+    /// Tokens allocated at settlement
+    pub tokens: U256,
+    /// Base currency refunded at settlement
+    pub refund: U256,
+    pub settled: bool,
+}
+
+/// A creator action awaiting (or having gathered) co-creator approvals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorProposal {
+    pub action: CreatorAction,
+    pub proposer: Account,
+    /// Co-creators that approved, the proposer included
+    pub approvals: Vec<Account>,
+    /// Creator set version the proposal was made under; it lapses when the set changes
+    pub set_version: u64,
+    pub created_at: Timestamp,
+    pub executed: bool,
+}
+
+/// A tracked message that bounced and may need operator attention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingMessage {
+    /// Message variant name (e.g. "GraduateToken")
+    pub kind: String,
+    pub token_id: String,
+    /// Number of times this message has bounced
+    pub bounces: u32,
+    pub last_bounced_at: Timestamp,
+    /// What the contract did in response (retry, rollback, ...)
+    pub resolution: String,
+}
+
+impl PendingMessage {
+    /// Storage key for a pending message
+    pub fn key(kind: &str, token_id: &str) -> String {
+        format!("{}:{}", kind, token_id)
+    }
+}
+
+/// GraphQL-friendly version of PendingMessage
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct PendingMessageGQL {
+    pub kind: String,
+    pub token_id: String,
+    pub bounces: u32,
+    pub last_bounced_at: String,
+    pub resolution: String,
+}
+
+impl From<&PendingMessage> for PendingMessageGQL {
+    fn from(pending: &PendingMessage) -> Self {
+        Self {
+            kind: pending.kind.clone(),
+            token_id: pending.token_id.clone(),
+            bounces: pending.bounces,
+            last_bounced_at: pending.last_bounced_at.micros().to_string(),
+            resolution: pending.resolution.clone(),
+        }
+    }
+}
+
+/// GraphQL-friendly version of PoolInfo from swap contract
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct PoolInfoGQL {
+    pub pool_id: String,
+    pub token_id: String,
+    pub token_liquidity: String,
+    pub base_liquidity: String,
+    /// Opening price as an integer scaled by 10^price_decimals
+    pub initial_ratio: String,
+    pub price_decimals: u8,
+    pub created_at: String,
+    pub is_locked: bool,
+    pub lock_expires_at: Option<String>,
+    pub trade_count: u64,
+    pub tvl: String,
+}
+
+/// GraphQL-friendly version of PoolAttestation
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "service", derive(SimpleObject))]
+pub struct PoolAttestationGQL {
+    pub pool_id: String,
+    pub token_liquidity: String,
+    pub base_liquidity: String,
+    /// Opening price as an integer scaled by 10^price_decimals
+    pub initial_ratio: String,
+    pub price_decimals: u8,
+    pub created_at: String,
+    pub is_locked: bool,
+    pub lock_expires_at: Option<String>,
+    pub pool_info_hash: String,
+    pub attested_at: String,
+}
+
+impl From<&PoolAttestation> for PoolAttestationGQL {
+    fn from(attestation: &PoolAttestation) -> Self {
+        Self {
+            pool_id: attestation.pool_id.clone(),
+            token_liquidity: attestation.token_liquidity.to_string(),
+            base_liquidity: attestation.base_liquidity.to_string(),
+            initial_ratio: attestation.initial_ratio.raw().to_string(),
+            price_decimals: price::PRICE_DECIMALS,
+            created_at: attestation.created_at.micros().to_string(),
+            is_locked: attestation.is_locked,
+            lock_expires_at: attestation.lock_expires_at.map(|t| t.micros().to_string()),
+            pool_info_hash: attestation.pool_info_hash.to_string(),
+            attested_at: attestation.attested_at.micros().to_string(),
+        }
+    }
+}
+
+/// WAD-style (1e18) fixed-point helpers
+///
+/// Intermediate products are computed in 512 bits, so a result only fails
+/// when it does not fit in a U256 itself.
+pub mod wad {
+    use super::*;
+    use primitive_types::U512;
+
+    /// Decimal places of a WAD value
+    pub const WAD_DECIMALS: u8 = 18;
+
+    /// One in WAD representation, `10^WAD_DECIMALS`
+    pub fn wad() -> U256 {
+        U256::exp10(WAD_DECIMALS as usize)
+    }
+
+    /// `a * b / denominator`, rounded down (None on overflow or zero denominator)
+    pub fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+        if denominator.is_zero() {
+            return None;
+        }
+        let result = a.full_mul(b) / U512::from(denominator);
+        U256::try_from(result).ok()
+    }
+
+    /// Multiply two WAD values: `a * b / WAD`
+    pub fn mul_wad(a: U256, b: U256) -> Option<U256> {
+        mul_div(a, b, wad())
+    }
+
+    /// Divide two values into a WAD value: `a * WAD / b`
+    pub fn div_wad(a: U256, b: U256) -> Option<U256> {
+        mul_div(a, wad(), b)
+    }
+
+    /// Apply a basis-point rate: `value * bps / 10_000`
+    pub fn mul_bps(value: U256, bps: u16) -> Option<U256> {
+        mul_div(value, U256::from(bps), U256::from(10_000))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_mul_div_uses_wide_intermediate() {
+            // MAX * 2 overflows U256 but the quotient fits
+            assert_eq!(mul_div(U256::MAX, U256::from(2), U256::from(4)), Some(U256::MAX / 2));
+            assert_eq!(mul_div(U256::MAX, U256::from(2), U256::one()), None);
+            assert_eq!(mul_div(U256::one(), U256::one(), U256::zero()), None);
+        }
+
+        #[test]
+        fn test_wad_operations() {
+            let half = wad() / 2;
+            assert_eq!(mul_wad(half, U256::from(10) * wad()), Some(U256::from(5) * wad()));
+            assert_eq!(div_wad(U256::from(1), U256::from(4)), Some(wad() / 4));
+            assert_eq!(mul_bps(U256::from(10_000), 300), Some(U256::from(300)));
+        }
+    }
+}
+
+pub use price::Price;
+
+/// Fixed-point price representation
+pub mod price {
+    use super::*;
+    use std::fmt;
+
+    /// Decimal places of the fixed-point price representation
+    pub const PRICE_DECIMALS: u8 = wad::WAD_DECIMALS;
+
+    /// Base currency per token, stored as an integer scaled by `10^PRICE_DECIMALS`
+    ///
+    /// Keeps sub-unit prices (e.g. early on the curve) from truncating to zero.
+    #[derive(
+        Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+    )]
+    pub struct Price(U256);
+
+    impl Price {
+        /// The scale factor, `10^PRICE_DECIMALS`
+        pub fn scale() -> U256 {
+            wad::wad()
+        }
+
+        /// Wrap an already-scaled value
+        pub fn from_raw(raw: U256) -> Self {
+            Price(raw)
+        }
+
+        /// The scaled integer representation
+        pub fn raw(self) -> U256 {
+            self.0
+        }
+
+        /// Price of exactly `value` base units per token
+        pub fn from_integer(value: U256) -> Option<Self> {
+            value.checked_mul(Self::scale()).map(Price)
+        }
+
+        /// Price of `numerator / denominator` (None on overflow or zero denominator)
+        pub fn from_ratio(numerator: U256, denominator: U256) -> Option<Self> {
+            wad::div_wad(numerator, denominator).map(Price)
+        }
+
+        /// Whole base units per token, truncated
+        pub fn to_integer(self) -> U256 {
+            self.0 / Self::scale()
+        }
+
+        /// Value of `amount` tokens at this price, truncated to base units
+        pub fn mul_amount(self, amount: U256) -> Option<U256> {
+            wad::mul_wad(amount, self.0)
+        }
+
+        pub fn is_zero(self) -> bool {
+            self.0.is_zero()
+        }
+    }
+
+    /// Formats as a decimal number with trailing zeros trimmed (e.g. "0.0125")
+    impl fmt::Display for Price {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let integer = self.0 / Self::scale();
+            let fraction = self.0 % Self::scale();
+            if fraction.is_zero() {
+                return write!(f, "{}", integer);
+            }
+            let digits = format!("{:0>width$}", fraction.to_string(), width = PRICE_DECIMALS as usize);
+            write!(f, "{}.{}", integer, digits.trim_end_matches('0'))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_price_from_ratio() {
+            let price = Price::from_ratio(U256::from(1), U256::from(80)).unwrap();
+            assert_eq!(price.raw(), U256::exp10(16) + U256::exp10(15) * 2 + U256::exp10(14) * 5);
+            assert_eq!(price.to_integer(), U256::zero());
+            assert!(!price.is_zero());
+            assert_eq!(Price::from_ratio(U256::one(), U256::zero()), None);
+        }
+
+        #[test]
+        fn test_price_display() {
+            assert_eq!(Price::from_ratio(U256::from(1), U256::from(80)).unwrap().to_string(), "0.0125");
+            assert_eq!(Price::from_integer(U256::from(42)).unwrap().to_string(), "42");
+            assert_eq!(Price::from_raw(U256::one()).to_string(), "0.000000000000000001");
+            assert_eq!(Price::default().to_string(), "0");
+        }
+
+        #[test]
+        fn test_price_mul_amount() {
+            let price = Price::from_ratio(U256::from(3), U256::from(2)).unwrap();
+            assert_eq!(price.mul_amount(U256::from(10)), Some(U256::from(15)));
+        }
+
+        #[test]
+        fn test_legacy_trade_price() {
+            let legacy = LegacyTrade {
+                token_id: "t".to_string(),
+                trader: Account {
+                    chain_id: ChainId::root(0),
+                    owner: linera_sdk::linera_base_types::AccountOwner::CHAIN,
+                },
+                is_buy: true,
+                token_amount: U256::from(10),
+                currency_amount: U256::from(30),
+                price: U256::from(3),
+                timestamp: Timestamp::from(7),
+            };
+            let trade = Trade::from(legacy.clone());
+            assert_eq!(trade.price, Price::from_integer(U256::from(3)).unwrap());
+            assert_eq!(trade.price.to_integer(), legacy.price);
+            assert_eq!((trade.memo, trade.referrer, trade.flagged), (None, None, false));
+
+            let huge = LegacyTrade { price: U256::MAX, ..legacy };
+            assert_eq!(Trade::from(huge).price.raw(), U256::MAX);
+        }
+    }
+}
+
+/// Bonding curve calculations
+pub mod bonding_curve {
+    use super::*;
+    use thiserror::Error;
+
+    #[derive(Debug, Error, PartialEq, Eq)]
+    pub enum CurveError {
+        /// An intermediate value exceeded U256 (or the curve has a zero scale)
+        #[error("Bonding curve arithmetic overflow")]
+        Overflow,
+    }
+
+    /// Integral of the price curve from 0 to `supply`: k * supply^3 / (3 * scale^2)
+    fn integral(supply: U256, k: U256, scale: U256) -> Result<U256, CurveError> {
+        if scale.is_zero() {
+            return Err(CurveError::Overflow);
+        }
+        // k * supply^2 / scale^2 as a WAD, then times supply / 3
+        let price = calculate_current_price(supply, k, scale)?;
+        wad::mul_div(price.raw(), supply, wad::wad() * U256::from(3)).ok_or(CurveError::Overflow)
+    }
+
+    /// Calculate cost to buy `amount` tokens at current supply
+    /// Formula: Integral of k * (supply / scale)^2 from current_supply to new_supply
+    pub fn calculate_buy_cost(
+        current_supply: U256,
+        amount: U256,
+        k: U256,
+        scale: U256,
+    ) -> Result<U256, CurveError> {
+        let new_supply = current_supply
+            .checked_add(amount)
+            .ok_or(CurveError::Overflow)?;
+
+        let integral_new = integral(new_supply, k, scale)?;
+        let integral_old = integral(current_supply, k, scale)?;
+
+        integral_new
+            .checked_sub(integral_old)
+            .ok_or(CurveError::Overflow)
+    }
+
+    /// Calculate return for selling `amount` tokens at current supply
+    pub fn calculate_sell_return(
+        current_supply: U256,
+        amount: U256,
+        k: U256,
+        scale: U256,
+    ) -> Result<U256, CurveError> {
+        if amount > current_supply {
+            return Ok(U256::zero());
+        }
+
+        let new_supply = current_supply - amount;
+
+        let integral_old = integral(current_supply, k, scale)?;
+        let integral_new = integral(new_supply, k, scale)?;
+
+        integral_old
+            .checked_sub(integral_new)
+            .ok_or(CurveError::Overflow)
+    }
+
+    /// Largest amount of tokens buyable at `current_supply` for at most `budget`
+    ///
+    /// Binary search over `calculate_buy_cost`, which never decreases as the
+    /// amount grows.
+    pub fn calculate_tokens_for_cost(
+        current_supply: U256,
+        budget: U256,
+        k: U256,
+        scale: U256,
+    ) -> Result<U256, CurveError> {
+        if scale.is_zero() {
+            return Err(CurveError::Overflow);
+        }
+        let affordable = |amount: U256| {
+            calculate_buy_cost(current_supply, amount, k, scale)
+                .map(|cost| cost <= budget)
+                .unwrap_or(false)
+        };
+
+        // Double an upper bound until it is unaffordable (or overflows the curve)
+        let mut low = U256::zero();
+        let mut high = U256::one();
+        while affordable(high) {
+            low = high;
+            high = match high.checked_mul(U256::from(2)) {
+                Some(high) => high,
+                None => return Ok(low),
+            };
+        }
+
+        while high - low > U256::one() {
+            let mid = low + (high - low) / 2;
+            if affordable(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Largest amount of tokens sellable at `current_supply` for a return of at
+    /// most `available`
+    ///
+    /// Binary search over `calculate_sell_return`, which never decreases as
+    /// the amount grows; the result never exceeds `current_supply`.
+    pub fn calculate_max_sell_for_return(
+        current_supply: U256,
+        available: U256,
+        k: U256,
+        scale: U256,
+    ) -> Result<U256, CurveError> {
+        if calculate_sell_return(current_supply, current_supply, k, scale)? <= available {
+            return Ok(current_supply);
+        }
+
+        // The full supply is not payable, so the answer lies in [0, current_supply)
+        let mut low = U256::zero();
+        let mut high = current_supply;
+        while high - low > U256::one() {
+            let mid = low + (high - low) / 2;
+            if calculate_sell_return(current_supply, mid, k, scale)? <= available {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Calculate current price at given supply
+    /// Formula: k * (supply / scale)^2, as a fixed-point `Price`
+    /// Optimized to minimize precision loss: (k * supply * Price::scale() / scale) * supply / scale
+    pub fn calculate_current_price(supply: U256, k: U256, scale: U256) -> Result<Price, CurveError> {
+        if supply == U256::zero() || scale == U256::zero() {
+            return Ok(Price::default());
+        }
+        // Divide incrementally with wide intermediates to avoid overflow
+        let partial = k
+            .checked_mul(Price::scale())
+            .and_then(|k_wad| wad::mul_div(k_wad, supply, scale))
+            .ok_or(CurveError::Overflow)?;
+        let raw = wad::mul_div(partial, supply, scale).ok_or(CurveError::Overflow)?;
+        Ok(Price::from_raw(raw))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_buy_cost_calculation() {
+            let k = U256::from(1000);
+            let scale = U256::from(1_000_000);
+            let current_supply = U256::from(0);
+            let amount = U256::from(100_000);
+
+            let cost = calculate_buy_cost(current_supply, amount, k, scale).unwrap();
+            assert!(cost > U256::zero());
+        }
+
+        #[test]
+        fn test_sell_return_calculation() {
+            let k = U256::from(1000);
+            let scale = U256::from(1_000_000);
+            let current_supply = U256::from(100_000);
+            let amount = U256::from(50_000);
+
+            let return_amount = calculate_sell_return(current_supply, amount, k, scale).unwrap();
+            assert!(return_amount > U256::zero());
+        }
+
+        #[test]
+        fn test_price_calculation() {
+            let k = U256::from(1000);
+            let scale = U256::from(1_000_000);
+            let supply = U256::from(500_000);
+
+            let price = calculate_current_price(supply, k, scale).unwrap();
+            assert!(!price.is_zero());
+        }
+
+        #[test]
+        fn test_price_keeps_precision_at_low_supply() {
+            let k = U256::from(1000);
+            let scale = U256::from(1_000_000);
+
+            // 1000 * (1_000 / 1_000_000)^2 = 0.001, which truncated to 0 as an integer
+            let price = calculate_current_price(U256::from(1_000), k, scale).unwrap();
+            assert_eq!(price.to_string(), "0.001");
+        }
+
+        #[test]
+        fn test_buy_cost_overflow_boundary() {
+            let one = U256::one();
+            // (2^85)^3 = 2^255 still fits in U256, (2^86)^3 does not
+            let largest = one << 85;
+            assert!(calculate_buy_cost(U256::zero(), largest, one, one).is_ok());
+            assert_eq!(
+                calculate_buy_cost(U256::zero(), one << 86, one, one),
+                Err(CurveError::Overflow)
+            );
+            // Supply addition overflow
+            assert_eq!(
+                calculate_buy_cost(U256::MAX, one, one, one),
+                Err(CurveError::Overflow)
+            );
+        }
+
+        #[test]
+        fn test_sell_return_overflow_boundary() {
+            let one = U256::one();
+            assert!(calculate_sell_return(one << 85, one, one, one).is_ok());
+            assert_eq!(
+                calculate_sell_return(one << 86, one, one, one),
+                Err(CurveError::Overflow)
+            );
+        }
+
+        #[test]
+        fn test_price_overflow_boundary() {
+            let one = U256::one();
+            // supply^2 * 1e18 fits in U256 for 2^98 but not for 2^99
+            assert!(calculate_current_price(one << 98, one, one).is_ok());
+            assert_eq!(
+                calculate_current_price(one << 99, one, one),
+                Err(CurveError::Overflow)
+            );
+        }
+
+        #[test]
+        fn test_zero_scale_is_rejected() {
+            assert_eq!(
+                calculate_buy_cost(U256::zero(), U256::one(), U256::one(), U256::zero()),
+                Err(CurveError::Overflow)
+            );
+        }
+
+        #[test]
+        fn test_tokens_for_cost_inverts_buy_cost() {
+            let k = U256::from(1000);
+            let scale = U256::from(1_000_000);
+            let supply = U256::from(2_000_000);
+            let budget = U256::from(50_000);
+
+            let amount = calculate_tokens_for_cost(supply, budget, k, scale).unwrap();
+            assert!(calculate_buy_cost(supply, amount, k, scale).unwrap() <= budget);
+            assert!(calculate_buy_cost(supply, amount + 1, k, scale).unwrap() > budget);
+
+            assert_eq!(
+                calculate_tokens_for_cost(supply, U256::zero(), k, scale).unwrap(),
+                U256::zero()
+            );
+        }
+
+        #[test]
+        fn test_max_sell_for_return() {
+            let k = U256::from(1000);
+            let scale = U256::from(1_000_000);
+            let supply = U256::from(2_000_000);
+            let available = U256::from(50_000);
+
+            let amount = calculate_max_sell_for_return(supply, available, k, scale).unwrap();
+            assert!(calculate_sell_return(supply, amount, k, scale).unwrap() <= available);
+            assert!(calculate_sell_return(supply, amount + 1, k, scale).unwrap() > available);
+
+            // Enough liquidity for the whole supply caps the result at the supply
+            assert_eq!(
+                calculate_max_sell_for_return(supply, U256::MAX, k, scale).unwrap(),
+                supply
+            );
+        }
+    }
+}
+
+/// Launch economics simulated with the token contract's own math, for curve
+/// previews and tests
+///
+/// Buys are capped at the remaining supply (as with `fill_or_cap`) and a sell
+/// larger than the trader's balance is rejected, as is any trade once the
+/// launch graduates. Creator fees include the stakers' share.
+#[cfg(any(test, feature = "simulation"))]
+pub mod simulation {
+    use super::*;
+    use bonding_curve::CurveError;
+    use std::collections::BTreeMap;
+
+    /// One step of a simulated launch; traders are identified by index
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub enum SimulatedTrade {
+        Buy { trader: u32, amount: U256 },
+        Sell { trader: u32, amount: U256 },
+    }
+
+    /// How a simulated trade settled
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct SimulatedFill {
+        /// Position of the trade in the sequence
+        pub index: usize,
+        pub is_buy: bool,
+        /// Tokens bought or sold after capping
+        pub token_amount: U256,
+        /// Curve cost of a buy or gross return of a sell
+        pub currency_amount: U256,
+        pub creator_fee: U256,
+        pub price_after: Price,
+    }
+
+    /// Result of running a trade sequence through a curve
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct LaunchOutcome {
+        pub final_supply: U256,
+        pub final_price: Price,
+        pub total_raised: U256,
+        pub creator_fees: U256,
+        pub holders: u64,
+        /// Index of the trade that completed the curve, if it graduated
+        pub graduated_at: Option<usize>,
+        pub fills: Vec<SimulatedFill>,
+        /// Indices of trades that were not executed
+        pub rejected: Vec<usize>,
+    }
+
+    /// Run `trades` in order against a fresh curve with `config`
+    pub fn simulate_launch(
+        config: &BondingCurveConfig,
+        trades: &[SimulatedTrade],
+    ) -> Result<LaunchOutcome, CurveError> {
+        let mut balances: BTreeMap<u32, U256> = BTreeMap::new();
+        let mut supply = U256::zero();
+        let mut total_raised = U256::zero();
+        let mut creator_fees = U256::zero();
+        let mut graduated_at = None;
+        let mut fills = Vec::new();
+        let mut rejected = Vec::new();
+
+        for (index, trade) in trades.iter().enumerate() {
+            if graduated_at.is_some() {
+                rejected.push(index);
+                continue;
+            }
+            let (is_buy, token_amount, currency_amount) = match *trade {
+                SimulatedTrade::Buy { trader, amount } => {
+                    let amount = amount.min(config.max_supply.saturating_sub(supply));
+                    if amount.is_zero() {
+                        rejected.push(index);
+                        continue;
+                    }
+                    let cost =
+                        bonding_curve::calculate_buy_cost(supply, amount, config.k, config.scale)?;
+                    supply += amount;
+                    total_raised += cost;
+                    *balances.entry(trader).or_default() += amount;
+                    (true, amount, cost)
+                }
+                SimulatedTrade::Sell { trader, amount } => {
+                    let balance = balances.get(&trader).copied().unwrap_or_default();
+                    if amount.is_zero() || amount > balance {
+                        rejected.push(index);
+                        continue;
+                    }
+                    let proceeds = bonding_curve::calculate_sell_return(
+                        supply,
+                        amount,
+                        config.k,
+                        config.scale,
+                    )?;
+                    supply -= amount;
+                    total_raised = total_raised.saturating_sub(proceeds);
+                    balances.insert(trader, balance - amount);
+                    (false, amount, proceeds)
+                }
+            };
+            balances.retain(|_, balance| !balance.is_zero());
+
+            let creator_fee = wad::mul_bps(currency_amount, config.creator_fee_bps)
+                .ok_or(CurveError::Overflow)?;
+            creator_fees += creator_fee;
+            let price_after =
+                bonding_curve::calculate_current_price(supply, config.k, config.scale)?;
+            fills.push(SimulatedFill {
+                index,
+                is_buy,
+                token_amount,
+                currency_amount,
+                creator_fee,
+                price_after,
+            });
+
+            let holders = balances.len() as u64;
+            let enough_holders = config.min_holders.unwrap_or(0) <= holders;
+            if is_buy && supply >= config.max_supply && enough_holders {
+                graduated_at = Some(index);
+            }
+        }
+
+        Ok(LaunchOutcome {
+            final_supply: supply,
+            final_price: bonding_curve::calculate_current_price(supply, config.k, config.scale)?,
+            total_raised,
+            creator_fees,
+            holders: balances.len() as u64,
+            graduated_at,
+            fills,
+            rejected,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn config() -> BondingCurveConfig {
+            BondingCurveConfig {
+                max_supply: U256::from(100_000),
+                creator_fee_bps: 100,
+                ..BondingCurveConfig::default()
+            }
+        }
+
+        #[test]
+        fn test_simulate_launch_matches_curve_math() {
+            let config = config();
+            let trades = [
+                SimulatedTrade::Buy { trader: 0, amount: U256::from(40_000) },
+                SimulatedTrade::Sell { trader: 1, amount: U256::from(1) },
+                SimulatedTrade::Buy { trader: 1, amount: U256::from(30_000) },
+                SimulatedTrade::Sell { trader: 0, amount: U256::from(10_000) },
+            ];
+            let outcome = simulate_launch(&config, &trades).unwrap();
+
+            assert_eq!(outcome.rejected, vec![1]);
+            assert_eq!(outcome.final_supply, U256::from(60_000));
+            assert_eq!(outcome.holders, 2);
+            assert_eq!(outcome.graduated_at, None);
+
+            let cost = |supply: u64, amount: u64| {
+                bonding_curve::calculate_buy_cost(
+                    U256::from(supply),
+                    U256::from(amount),
+                    config.k,
+                    config.scale,
+                )
+                .unwrap()
+            };
+            // The sell unwinds the last 10k tokens of the second buy
+            let raised = cost(0, 40_000) + cost(40_000, 30_000) - cost(60_000, 10_000);
+            assert_eq!(outcome.total_raised, raised);
+            let fees = outcome
+                .fills
+                .iter()
+                .fold(U256::zero(), |total, fill| total + fill.creator_fee);
+            assert_eq!(outcome.creator_fees, fees);
+        }
+
+        #[test]
+        fn test_simulate_launch_graduation() {
+            let mut config = config();
+            let trades = [
+                SimulatedTrade::Buy { trader: 0, amount: U256::from(60_000) },
+                SimulatedTrade::Buy { trader: 0, amount: U256::from(60_000) },
+                SimulatedTrade::Buy { trader: 1, amount: U256::from(1) },
+            ];
+
+            // The second buy is capped at the remaining supply and completes the curve
+            let outcome = simulate_launch(&config, &trades).unwrap();
+            assert_eq!(outcome.graduated_at, Some(1));
+            assert_eq!(outcome.fills[1].token_amount, U256::from(40_000));
+            assert_eq!(outcome.rejected, vec![2]);
+
+            // Too few holders keeps the full curve open, and later buys are capped to nothing
+            config.min_holders = Some(2);
+            let outcome = simulate_launch(&config, &trades).unwrap();
+            assert_eq!(outcome.graduated_at, None);
+            assert_eq!(outcome.rejected, vec![2]);
+        }
+    }
+}
+
+/// Dutch auction pricing and settlement math
+///
+/// Bids pay the price at the time they land; at settlement every bidder is
+/// charged the clearing price instead and the difference is refunded.
+pub mod dutch_auction {
+    use super::*;
+    use primitive_types::U512;
+    use thiserror::Error;
+
+    #[derive(Debug, Error, PartialEq, Eq)]
+    pub enum AuctionError {
+        #[error("floor_price must be greater than zero")]
+        ZeroFloorPrice,
+
+        #[error("start_price must be at least floor_price")]
+        StartBelowFloor,
+
+        #[error("duration_micros must be greater than zero")]
+        ZeroDuration,
+    }
+
+    /// Check that an auction's prices and duration are usable
+    pub fn validate(config: &DutchAuctionConfig) -> Result<(), AuctionError> {
+        if config.floor_price.is_zero() {
+            return Err(AuctionError::ZeroFloorPrice);
+        }
+        if config.start_price < config.floor_price {
+            return Err(AuctionError::StartBelowFloor);
+        }
+        if config.duration_micros == 0 {
+            return Err(AuctionError::ZeroDuration);
+        }
+        Ok(())
+    }
+
+    /// Time after which no more bids are accepted
+    pub fn ends_at(config: &DutchAuctionConfig) -> Timestamp {
+        config.starts_at.saturating_add(TimeDelta::from_micros(config.duration_micros))
+    }
+
+    /// Price at `now`: `start_price` until the start, then falling linearly to
+    /// `floor_price` at the end
+    pub fn price_at(config: &DutchAuctionConfig, now: Timestamp) -> Price {
+        let elapsed = now.delta_since(config.starts_at).as_micros().min(config.duration_micros);
+        let drop = config.start_price.raw().saturating_sub(config.floor_price.raw());
+        let fallen = wad::mul_div(drop, U256::from(elapsed), U256::from(config.duration_micros))
+            .unwrap_or(drop);
+        Price::from_raw(config.start_price.raw() - fallen)
+    }
+
+    /// What `amount` tokens cost at the clearing price, rounded up so the
+    /// refunds owed never exceed what the application set aside
+    pub fn charge(amount: U256, clearing_price: Price) -> Option<U256> {
+        let scale = U512::from(Price::scale());
+        let product = amount.full_mul(clearing_price.raw());
+        U256::try_from((product + scale - U512::one()) / scale).ok()
+    }
+
+    /// Overpayment returned to a bidder who paid `paid` for `amount` tokens
+    pub fn refund(paid: U256, amount: U256, clearing_price: Price) -> U256 {
+        charge(amount, clearing_price).map_or(U256::zero(), |charge| paid.saturating_sub(charge))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn config() -> DutchAuctionConfig {
+            DutchAuctionConfig {
+                start_price: Price::from_integer(U256::from(100)).unwrap(),
+                floor_price: Price::from_integer(U256::from(20)).unwrap(),
+                starts_at: Timestamp::from(1_000),
+                duration_micros: 800,
+            }
+        }
+
+        #[test]
+        fn test_price_declines_linearly_to_the_floor() {
+            let config = config();
+            let price = |micros| price_at(&config, Timestamp::from(micros)).to_integer();
+            assert_eq!(price(0), U256::from(100));
+            assert_eq!(price(1_000), U256::from(100));
+            assert_eq!(price(1_400), U256::from(60));
+            assert_eq!(price(1_800), U256::from(20));
+            assert_eq!(price(5_000), U256::from(20));
+            assert_eq!(ends_at(&config), Timestamp::from(1_800));
+        }
+
+        #[test]
+        fn test_refund_charges_the_clearing_price_rounded_up() {
+            let clearing = Price::from_ratio(U256::from(3), U256::from(2)).unwrap();
+            assert_eq!(charge(U256::from(3), clearing), Some(U256::from(5)));
+            assert_eq!(refund(U256::from(9), U256::from(3), clearing), U256::from(4));
+            assert_eq!(refund(U256::from(4), U256::from(3), clearing), U256::zero());
+        }
+
+        #[test]
+        fn test_validate() {
+            assert_eq!(validate(&config()), Ok(()));
+            let inverted = DutchAuctionConfig { start_price: Price::default(), ..config() };
+            assert_eq!(validate(&inverted), Err(AuctionError::StartBelowFloor));
+            let instant = DutchAuctionConfig { duration_micros: 0, ..config() };
+            assert_eq!(validate(&instant), Err(AuctionError::ZeroDuration));
+        }
+    }
+}
+
+/// Fixed-price sale allocation math
+///
+/// When the commitments exceed the cost of the whole supply, each is scaled
+/// down to its share of that cost; whatever an allocation does not use is
+/// refunded. Rounding always favors the application, so the allocations
+/// never exceed the supply and the refunds never exceed the commitments.
+pub mod fixed_price_sale {
+    use super::*;
+    use thiserror::Error;
+
+    #[derive(Debug, Error, PartialEq, Eq)]
+    pub enum SaleError {
+        #[error("price must be greater than zero")]
+        ZeroPrice,
+
+        #[error("duration_micros must be greater than zero")]
+        ZeroDuration,
+    }
+
+    /// A commitment's outcome once the sale window closed
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Allocation {
+        pub tokens: U256,
+        /// Base currency kept for the tokens
+        pub cost: U256,
+        /// Base currency returned to the buyer
+        pub refund: U256,
+    }
+
+    /// Check that a sale's price and window are usable
+    pub fn validate(config: &FixedPriceSaleConfig) -> Result<(), SaleError> {
+        if config.price.is_zero() {
+            return Err(SaleError::ZeroPrice);
+        }
+        if config.duration_micros == 0 {
+            return Err(SaleError::ZeroDuration);
+        }
+        Ok(())
+    }
+
+    /// Time after which no more commitments are accepted
+    pub fn ends_at(config: &FixedPriceSaleConfig) -> Timestamp {
+        config.starts_at.saturating_add(TimeDelta::from_micros(config.duration_micros))
+    }
+
+    /// Allocation of a `committed` amount when `total_committed` was committed
+    /// for `supply` tokens (None on overflow)
+    pub fn allocate(
+        config: &FixedPriceSaleConfig,
+        committed: U256,
+        total_committed: U256,
+        supply: U256,
+    ) -> Option<Allocation> {
+        let capacity = config.price.mul_amount(supply)?;
+        let usable = if total_committed > capacity {
+            wad::mul_div(committed, capacity, total_committed)?
+        } else {
+            committed
+        };
+        let tokens = wad::mul_div(usable, Price::scale(), config.price.raw())?;
+        let cost = dutch_auction::charge(tokens, config.price)?.min(committed);
+        Some(Allocation { tokens, cost, refund: committed - cost })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn config() -> FixedPriceSaleConfig {
+            FixedPriceSaleConfig {
+                price: Price::from_integer(U256::from(2)).unwrap(),
+                starts_at: Timestamp::from(0),
+                duration_micros: 1_000,
+            }
+        }
+
+        #[test]
+        fn test_undersubscribed_sale_fills_every_commitment() {
+            let allocation =
+                allocate(&config(), U256::from(101), U256::from(500), U256::from(1_000)).unwrap();
+            assert_eq!(
+                allocation,
+                Allocation { tokens: U256::from(50), cost: U256::from(100), refund: U256::from(1) }
+            );
+        }
+
+        #[test]
+        fn test_oversubscribed_sale_is_pro_rated() {
+            // 4_000 committed for a supply costing 2_000: everyone gets half
+            let supply = U256::from(1_000);
+            let total = U256::from(4_000);
+            let first = allocate(&config(), U256::from(3_000), total, supply).unwrap();
+            let second = allocate(&config(), U256::from(1_000), total, supply).unwrap();
+            assert_eq!(first.tokens, U256::from(750));
+            assert_eq!(first.refund, U256::from(1_500));
+            assert_eq!(second.tokens, U256::from(250));
+            assert_eq!(second.cost + second.refund, U256::from(1_000));
+            assert_eq!(first.tokens + second.tokens, supply);
+        }
+
+        #[test]
+        fn test_validate() {
+            assert_eq!(validate(&config()), Ok(()));
+            let free = FixedPriceSaleConfig { price: Price::default(), ..config() };
+            assert_eq!(validate(&free), Err(SaleError::ZeroPrice));
+        }
+    }
+}
+
+/// Early-buy surcharge that discourages bots sweeping the curve at launch
+///
+/// Buys pay `initial_bps` of their cost on top when the curve opens to
+/// everyone, falling linearly to nothing after `duration_micros`.
+pub mod snipe_tax {
+    use super::*;
+
+    /// Highest surcharge a launch may start with, in basis points
+    pub const MAX_SNIPE_TAX_BPS: u16 = 2_000;
+
+    /// Surcharge rate at `now` for a curve that opened to everyone at `opened_at`
+    ///
+    /// Nothing is charged before the curve opens: those buys are the
+    /// whitelist's, not snipes.
+    pub fn surcharge_bps(config: &SnipeTaxConfig, opened_at: Timestamp, now: Timestamp) -> u16 {
+        if now < opened_at {
+            return 0;
+        }
+        let elapsed = now.delta_since(opened_at).as_micros();
+        if elapsed >= config.duration_micros {
+            return 0;
+        }
+        let remaining = config.duration_micros - elapsed;
+        (u128::from(config.initial_bps) * u128::from(remaining)
+            / u128::from(config.duration_micros)) as u16
+    }
+
+    /// Surcharge on a buy costing `cost` at `now` (None on overflow)
+    pub fn surcharge(
+        config: &SnipeTaxConfig,
+        cost: U256,
+        opened_at: Timestamp,
+        now: Timestamp,
+    ) -> Option<U256> {
+        wad::mul_bps(cost, surcharge_bps(config, opened_at, now))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn config() -> SnipeTaxConfig {
+            SnipeTaxConfig {
+                initial_bps: 2_000,
+                duration_micros: 300_000_000,
+                destination: SnipeTaxDestination::Burn,
+            }
+        }
+
+        #[test]
+        fn test_surcharge_decays_to_zero() {
+            let opened_at = Timestamp::from(1_000);
+            assert_eq!(surcharge_bps(&config(), opened_at, opened_at), 2_000);
+            // Whitelisted buys before the opening pay nothing
+            assert_eq!(surcharge_bps(&config(), opened_at, Timestamp::from(0)), 0);
+            assert_eq!(surcharge_bps(&config(), opened_at, Timestamp::from(150_001_000)), 1_000);
+            assert_eq!(surcharge_bps(&config(), opened_at, Timestamp::from(300_001_000)), 0);
+            assert_eq!(surcharge_bps(&config(), opened_at, Timestamp::from(u64::MAX)), 0);
+
+            let cost = U256::from(10_000);
+            assert_eq!(surcharge(&config(), cost, opened_at, opened_at), Some(U256::from(2_000)));
+            let none = SnipeTaxConfig { duration_micros: 0, ..config() };
+            assert_eq!(surcharge(&none, cost, opened_at, opened_at), Some(U256::zero()));
+        }
+    }
+}
+
+/// Volume-based creator fee discounts
+///
+/// A trader's volume is the base currency of their curve trades over the
+/// last `VOLUME_WINDOW_DAYS` days, counted in whole-day buckets.
+pub mod fee_tiers {
+    use super::*;
+
+    /// Days of trading counted towards a trader's tier
+    pub const VOLUME_WINDOW_DAYS: u64 = 30;
+
+    /// Width of a volume bucket in microseconds
+    pub const DAY_MICROS: u64 = 86_400_000_000;
+
+    /// Most tiers a table may hold
+    pub const MAX_FEE_TIERS: usize = 8;
+
+    /// Check that a tier table is short, sorted by strictly increasing volume,
+    /// and that discounts never shrink nor exceed the whole fee
+    pub fn validate(tiers: &[FeeTier]) -> Result<(), String> {
+        if tiers.len() > MAX_FEE_TIERS {
+            return Err(format!(
+                "{} fee tiers exceed the maximum of {}",
+                tiers.len(),
+                MAX_FEE_TIERS
+            ));
+        }
+        for tier in tiers {
+            if tier.discount_bps > 10_000 {
+                return Err(format!("fee discount of {} bps exceeds 10000", tier.discount_bps));
+            }
+        }
+        for pair in tiers.windows(2) {
+            if pair[1].min_volume <= pair[0].min_volume {
+                return Err("fee tier volumes must strictly increase".to_string());
+            }
+            if pair[1].discount_bps < pair[0].discount_bps {
+                return Err("fee tier discounts must not decrease with volume".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Discount of the highest tier `volume` reaches, in basis points
+    pub fn discount_bps(tiers: &[FeeTier], volume: U256) -> u16 {
+        tiers
+            .iter()
+            .filter(|tier| volume >= tier.min_volume)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// `fee_bps` with `discount_bps` of it waived
+    pub fn discounted_fee_bps(fee_bps: u16, discount_bps: u16) -> u16 {
+        let waived = u32::from(fee_bps) * u32::from(discount_bps.min(10_000)) / 10_000;
+        fee_bps - waived as u16
+    }
+
+    /// A trader's daily volume over the window
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct RollingVolume {
+        /// (day, volume) buckets, oldest first
+        days: Vec<(u64, U256)>,
+    }
+
+    impl RollingVolume {
+        /// Add `amount` traded at `at`, dropping buckets that left the window
+        pub fn record(&mut self, at: Timestamp, amount: U256) {
+            let day = at.micros() / DAY_MICROS;
+            self.days.retain(|(bucket, _)| bucket + VOLUME_WINDOW_DAYS > day);
+            if let Some((bucket, volume)) = self.days.last_mut() {
+                if *bucket == day {
+                    *volume = volume.saturating_add(amount);
+                    return;
+                }
+            }
+            self.days.push((day, amount));
+        }
+
+        /// Volume over the window ending at `now`
+        pub fn total(&self, now: Timestamp) -> U256 {
+            let day = now.micros() / DAY_MICROS;
+            self.days
+                .iter()
+                .filter(|(bucket, _)| bucket + VOLUME_WINDOW_DAYS > day)
+                .fold(U256::zero(), |total, (_, volume)| total.saturating_add(*volume))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn tier(min_volume: u64, discount_bps: u16) -> FeeTier {
+            FeeTier { min_volume: U256::from(min_volume), discount_bps }
+        }
+
+        #[test]
+        fn test_discount_follows_the_highest_tier_reached() {
+            let tiers = [tier(1_000, 1_000), tier(10_000, 2_500), tier(100_000, 5_000)];
+            assert!(validate(&tiers).is_ok());
+            assert_eq!(discount_bps(&tiers, U256::from(999)), 0);
+            assert_eq!(discount_bps(&tiers, U256::from(10_000)), 2_500);
+            assert_eq!(discount_bps(&tiers, U256::from(1_000_000)), 5_000);
+            assert_eq!(discount_bps(&[], U256::from(1_000_000)), 0);
+
+            assert_eq!(discounted_fee_bps(300, 2_500), 225);
+            assert_eq!(discounted_fee_bps(300, 10_000), 0);
+            assert_eq!(discounted_fee_bps(300, 0), 300);
+        }
+
+        #[test]
+        fn test_validate_rejects_unordered_tables() {
+            assert!(validate(&[tier(10_000, 1_000), tier(1_000, 2_000)]).is_err());
+            assert!(validate(&[tier(1_000, 2_000), tier(10_000, 1_000)]).is_err());
+            assert!(validate(&[tier(1_000, 10_001)]).is_err());
+            assert!(validate(&[tier(1, 0); MAX_FEE_TIERS + 1]).is_err());
+        }
+
+        #[test]
+        fn test_rolling_volume_forgets_old_days() {
+            let day = |day: u64| Timestamp::from(day * DAY_MICROS);
+            let mut volume = RollingVolume::default();
+            volume.record(day(0), U256::from(100));
+            volume.record(day(0), U256::from(50));
+            volume.record(day(20), U256::from(200));
+            assert_eq!(volume.total(day(29)), U256::from(350));
+            assert_eq!(volume.total(day(30)), U256::from(200));
+
+            volume.record(day(50), U256::from(1));
+            assert_eq!(volume.days.len(), 1);
+            assert_eq!(volume.total(day(50)), U256::from(1));
+        }
+    }
+}
+
+/// Constant product (x * y = k) AMM math shared by the swap contract and benchmarks
+pub mod amm {
+    use super::*;
+    use primitive_types::U512;
+
+    /// Fee charged on the input of exact-output swaps (30 = 0.3%)
+    pub const SWAP_FEE_BPS: u16 = 30;
+
+    /// Fee charged on the input of exact-input swaps, the same as exact-output
+    /// swaps so either direction funds buybacks
+    pub const EXACT_IN_FEE_BPS: u16 = SWAP_FEE_BPS;
+
+    /// Preview of an exact-input swap against a pool's reserves
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ExactInQuote {
+        pub amount_out: U256,
+        /// Part of the input kept by the pool as a fee
+        pub fee: U256,
+        /// Output shortfall against the pre-trade spot price, in basis points
+        /// (the fee excluded)
+        pub price_impact_bps: u16,
+    }
+
+    /// Quote swapping `amount_in` into a pool, charging `fee_bps` on the input
+    ///
+    /// Returns None on overflow or for an empty pool.
+    pub fn quote_exact_in(
+        amount_in: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+        fee_bps: u16,
+    ) -> Option<ExactInQuote> {
+        let fee = wad::mul_bps(amount_in, fee_bps)?;
+        let net_in = amount_in - fee;
+        let amount_out = constant_product_output(net_in, reserve_in, reserve_out)?;
+        let spot_out = wad::mul_div(net_in, reserve_out, reserve_in)?;
+        let price_impact_bps = if spot_out.is_zero() {
+            0
+        } else {
+            let shortfall = wad::mul_div(spot_out - amount_out, U256::from(10_000), spot_out)?;
+            shortfall.min(U256::from(10_000)).as_u32() as u16
+        };
+        Some(ExactInQuote {
+            amount_out,
+            fee,
+            price_impact_bps,
+        })
+    }
+
+    /// Output for swapping `amount_in` into a pool with the given reserves:
+    /// amount_out = (amount_in * reserve_out) / (reserve_in + amount_in)
+    ///
+    /// Returns None on overflow or when both `reserve_in` and `amount_in` are zero.
+    pub fn constant_product_output(
+        amount_in: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+    ) -> Option<U256> {
+        let denominator = reserve_in.checked_add(amount_in)?;
+        wad::mul_div(amount_in, reserve_out, denominator)
+    }
+
+    /// Input required to receive exactly `amount_out`, including a `fee_bps`
+    /// fee on the input:
+    /// amount_in = reserve_in * amount_out * 10_000 / ((reserve_out - amount_out) * (10_000 - fee_bps)) + 1
+    ///
+    /// Rounds up so the pool never loses to rounding. Returns None when the
+    /// pool cannot supply `amount_out` or on overflow.
+    pub fn constant_product_input(
+        amount_out: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+        fee_bps: u16,
+    ) -> Option<U256> {
+        if amount_out >= reserve_out || fee_bps >= 10_000 {
+            return None;
+        }
+        let numerator = reserve_in
+            .full_mul(amount_out)
+            .checked_mul(U512::from(10_000))?;
+        let denominator = U512::from(reserve_out - amount_out) * U512::from(10_000 - fee_bps);
+        U256::try_from(numerator / denominator).ok()?.checked_add(U256::one())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_constant_product_output() {
+            let out = constant_product_output(U256::from(100), U256::from(900), U256::from(1000));
+            assert_eq!(out, Some(U256::from(100)));
+            assert_eq!(constant_product_output(U256::MAX, U256::one(), U256::from(2)), None);
+        }
+
+        #[test]
+        fn test_constant_product_input() {
+            // Without a fee the input mirrors the exact-input formula, rounded up
+            let input = constant_product_input(U256::from(100), U256::from(900), U256::from(1000), 0);
+            assert_eq!(input, Some(U256::from(101)));
+            let out = constant_product_output(U256::from(101), U256::from(900), U256::from(1000));
+            assert!(out.unwrap() >= U256::from(100));
+
+            // The fee increases the required input
+            let (amount_out, reserve_in, reserve_out) =
+                (U256::from(100_000), U256::from(900_000), U256::from(1_000_000));
+            let no_fee = constant_product_input(amount_out, reserve_in, reserve_out, 0);
+            let with_fee = constant_product_input(amount_out, reserve_in, reserve_out, SWAP_FEE_BPS);
+            assert_eq!(no_fee, Some(U256::from(100_001)));
+            assert_eq!(with_fee, Some(U256::from(100_301)));
+
+            // The pool cannot be drained
+            assert_eq!(constant_product_input(U256::from(1000), U256::from(900), U256::from(1000), 0), None);
+        }
+
+        #[test]
+        fn test_quote_exact_in() {
+            // 100 into 900/1000: spot would give 111, the curve gives 100
+            let quote = quote_exact_in(U256::from(100), U256::from(900), U256::from(1000), 0).unwrap();
+            assert_eq!(quote.amount_out, U256::from(100));
+            assert_eq!(quote.fee, U256::zero());
+            assert_eq!(quote.price_impact_bps, 990);
+
+            // The fee is taken from the input before it reaches the curve
+            let (amount_in, reserve) = (U256::from(10_000), U256::from(1_000_000));
+            let quote = quote_exact_in(amount_in, reserve, reserve, EXACT_IN_FEE_BPS).unwrap();
+            assert_eq!(quote.fee, U256::from(30));
+            let net_out =
+                constant_product_output(U256::from(9_970), U256::from(1_000_000), U256::from(1_000_000));
+            assert_eq!(Some(quote.amount_out), net_out);
+
+            assert_eq!(quote_exact_in(U256::from(100), U256::zero(), U256::from(1000), 0), None);
+        }
+    }
+}
+
+/// Fee withholding and scheduling for the swap pools' buyback-and-burn
+pub mod buyback {
+    use super::*;
+
+    /// Part of a swap fee withheld for the buyback
+    pub fn share_of(config: &BuybackConfig, fee: U256) -> U256 {
+        wad::mul_bps(fee, config.fee_share_bps.min(10_000)).unwrap_or_default()
+    }
+
+    /// Whether a pool's accrued fees should be spent and burned at `now`
+    ///
+    /// `since` is the pool's last buyback, or its creation before the first.
+    pub fn is_due(
+        config: &BuybackConfig,
+        accrued_base: U256,
+        pending_burn: U256,
+        since: Timestamp,
+        now: Timestamp,
+    ) -> bool {
+        if accrued_base.is_zero() && pending_burn.is_zero() {
+            return false;
+        }
+        (!accrued_base.is_zero() && accrued_base >= config.min_base)
+            || now.delta_since(since).as_micros() >= config.interval_micros
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_share_of() {
+            let config = BuybackConfig {
+                fee_share_bps: 2_500,
+                ..BuybackConfig::default()
+            };
+            assert_eq!(share_of(&config, U256::from(301)), U256::from(75));
+
+            // A share above 100% withholds the whole fee, never more
+            let greedy = BuybackConfig {
+                fee_share_bps: 20_000,
+                ..config
+            };
+            assert_eq!(share_of(&greedy, U256::from(301)), U256::from(301));
+        }
+
+        #[test]
+        fn test_is_due() {
+            let config = BuybackConfig {
+                fee_share_bps: 5_000,
+                min_base: U256::from(1_000),
+                interval_micros: 60_000_000,
+            };
+            let since = Timestamp::from(1_000_000);
+            let soon = Timestamp::from(2_000_000);
+            let later = Timestamp::from(61_000_000);
+
+            // Nothing accrued: never due
+            assert!(!is_due(&config, U256::zero(), U256::zero(), since, later));
+
+            // Below the threshold it waits for the interval
+            assert!(!is_due(&config, U256::from(999), U256::zero(), since, soon));
+            assert!(is_due(&config, U256::from(999), U256::zero(), since, later));
+            assert!(is_due(&config, U256::from(1_000), U256::zero(), since, soon));
+
+            // Token fees alone are burned on the interval
+            assert!(!is_due(&config, U256::zero(), U256::from(5), since, soon));
+            assert!(is_due(&config, U256::zero(), U256::from(5), since, later));
+        }
+    }
+}
+
+/// Graduation pool derivation shared by the swap contract and previews
+pub mod graduation {
+    use super::*;
+    use thiserror::Error;
+
+    #[derive(Debug, Error, PartialEq, Eq)]
+    pub enum GraduationError {
+        #[error("Token supply must be greater than zero")]
+        ZeroSupply,
+
+        #[error("Total raised must be greater than zero")]
+        ZeroRaised,
+
+        #[error("Pool parameters overflow")]
+        Overflow,
+    }
+
+    /// Pool parameters that result from graduating a token
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct PoolParameters {
+        pub pool_id: String,
+        pub token_reserve: U256,
+        pub base_reserve: U256,
+        /// Opening price as base per token
+        pub initial_ratio: Price,
+        /// Value locked counting both sides in base currency
+        pub tvl: U256,
+    }
+
+    /// Derive the pool ID for a token
+    pub fn pool_id(token_id: &str) -> String {
+        format!("pool-{}", token_id)
+    }
+
+    /// Key the stablecoin-quoted pool of a token is created under
+    pub fn stable_pool_key(token_id: &str) -> String {
+        format!("{}:stable", token_id)
+    }
+
+    /// Key a pool opened after graduation in another base asset is created under
+    pub fn asset_pool_key(token_id: &str, base_asset: &BaseAsset) -> String {
+        format!("{}:{}", token_id, base_asset)
+    }
+
+    /// Token ID a pool key belongs to (inverse of `stable_pool_key` and
+    /// `asset_pool_key`)
+    pub fn pool_key_token_id(key: &str) -> &str {
+        key.split_once(':').map_or(key, |(token_id, _)| token_id)
+    }
+
+    /// Split a graduating supply into (native pool, stable pool) shares
+    ///
+    /// Returns None unless both pools would receive tokens.
+    pub fn split_supply(total_supply: U256, stable_share_bps: u16) -> Option<(U256, U256)> {
+        if stable_share_bps == 0 || stable_share_bps >= 10_000 {
+            return None;
+        }
+        let stable = total_supply * U256::from(stable_share_bps) / U256::from(10_000);
+        if stable.is_zero() {
+            return None;
+        }
+        Some((total_supply - stable, stable))
+    }
+
+    /// Largest gap, in basis points, between a graduation pool's opening price
+    /// and the launch's final price before it is worth flagging
+    pub const PRICE_TOLERANCE_BPS: u64 = 10;
+
+    /// Split `supply` into the tokens to pair with `base_reserve` so the pool
+    /// opens at `final_price`, and the excess left out of the pool (burned)
+    ///
+    /// Seeding with the whole supply opens the pool at the launch's average
+    /// price, which on a rising curve sits below the last price paid and is
+    /// arbitraged in the first block. A zero price, or one the supply cannot
+    /// reach, keeps the whole supply in the pool.
+    pub fn price_matched_supply(
+        supply: U256,
+        base_reserve: U256,
+        final_price: Price,
+    ) -> Result<(U256, U256), GraduationError> {
+        if supply.is_zero() {
+            return Err(GraduationError::ZeroSupply);
+        }
+        if final_price.is_zero() {
+            return Ok((supply, U256::zero()));
+        }
+        let matched =
+            wad::div_wad(base_reserve, final_price.raw()).ok_or(GraduationError::Overflow)?;
+        if matched.is_zero() {
+            return Err(GraduationError::ZeroRaised);
+        }
+        let token_reserve = matched.min(supply);
+        Ok((token_reserve, supply - token_reserve))
+    }
+
+    /// Distance of `price` from `reference` in basis points of `reference`
+    pub fn price_deviation_bps(price: Price, reference: Price) -> u64 {
+        if reference.is_zero() {
+            return if price.is_zero() { 0 } else { u64::MAX };
+        }
+        let gap = if price > reference {
+            price.raw() - reference.raw()
+        } else {
+            reference.raw() - price.raw()
+        };
+        wad::mul_div(gap, U256::from(10_000), reference.raw())
+            .filter(|bps| *bps <= U256::from(u64::MAX))
+            .map_or(u64::MAX, |bps| bps.as_u64())
+    }
+
+    /// Compute the pool parameters for a graduating token
+    pub fn pool_parameters(
+        token_id: &str,
+        total_supply: U256,
+        total_raised: U256,
+    ) -> Result<PoolParameters, GraduationError> {
+        if total_supply == U256::zero() {
+            return Err(GraduationError::ZeroSupply);
+        }
+        if total_raised == U256::zero() {
+            return Err(GraduationError::ZeroRaised);
+        }
+
+        // Use fixed-point division to preserve precision
+        let initial_ratio =
+            Price::from_ratio(total_raised, total_supply).ok_or(GraduationError::Overflow)?;
+
+        // TVL accounts for both token and base currency sides
+        let tvl = total_raised
+            .checked_mul(U256::from(2))
+            .ok_or(GraduationError::Overflow)?;
+
+        Ok(PoolParameters {
+            pool_id: pool_id(token_id),
+            token_reserve: total_supply,
+            base_reserve: total_raised,
+            initial_ratio,
+            tvl,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_pool_parameters() {
+            let params = pool_parameters("t", U256::from(1_000_000), U256::from(10_000)).unwrap();
+            assert_eq!(params.pool_id, "pool-t");
+            assert_eq!(params.initial_ratio.to_string(), "0.01");
+            assert_eq!(params.tvl, U256::from(20_000));
+        }
+
+        #[test]
+        fn test_pool_parameters_rejects_zero() {
+            assert_eq!(
+                pool_parameters("t", U256::zero(), U256::from(1)),
+                Err(GraduationError::ZeroSupply)
+            );
+            assert_eq!(
+                pool_parameters("t", U256::from(1), U256::zero()),
+                Err(GraduationError::ZeroRaised)
+            );
+        }
+
+        #[test]
+        fn test_split_supply() {
+            assert_eq!(
+                split_supply(U256::from(1_000_000), 2_500),
+                Some((U256::from(750_000), U256::from(250_000)))
+            );
+            assert_eq!(split_supply(U256::from(1_000_000), 0), None);
+            assert_eq!(split_supply(U256::from(1_000_000), 10_000), None);
+            assert_eq!(split_supply(U256::from(1), 5_000), None);
+            assert_eq!(pool_id(&stable_pool_key("t")), "pool-t:stable");
+            assert_eq!(pool_key_token_id(&stable_pool_key("t")), "t");
+            assert_eq!(pool_key_token_id("t"), "t");
+            assert_eq!(pool_key_token_id(&asset_pool_key("t", &BaseAsset::Native)), "t");
+        }
+
+        #[test]
+        fn test_pool_opens_at_final_curve_price() {
+            let k = U256::from(1_000);
+            let scale = U256::from(1_000_000);
+            let supply = U256::from(800_000);
+            let raised = bonding_curve::calculate_buy_cost(U256::zero(), supply, k, scale).unwrap();
+            let final_price = bonding_curve::calculate_current_price(supply, k, scale).unwrap();
+
+            // Seeding with the whole supply opens at the average price, far below
+            let naive = pool_parameters("t", supply, raised).unwrap();
+            assert!(price_deviation_bps(naive.initial_ratio, final_price) > 5_000);
+
+            let (token_reserve, excess) =
+                price_matched_supply(supply, raised, final_price).unwrap();
+            assert_eq!(token_reserve + excess, supply);
+            assert!(excess > U256::zero());
+            let matched = pool_parameters("t", token_reserve, raised).unwrap();
+            assert!(price_deviation_bps(matched.initial_ratio, final_price) <= PRICE_TOLERANCE_BPS);
+        }
+
+        #[test]
+        fn test_price_matched_supply_edge_cases() {
+            let supply = U256::from(1_000);
+            // A flat price (auctions, sales) keeps the whole supply
+            let flat = Price::from_ratio(U256::from(500), supply).unwrap();
+            assert_eq!(
+                price_matched_supply(supply, U256::from(500), flat),
+                Ok((supply, U256::zero()))
+            );
+            assert_eq!(
+                price_matched_supply(supply, U256::from(500), Price::default()),
+                Ok((supply, U256::zero()))
+            );
+            // A price below the average cannot conjure extra tokens
+            let low = Price::from_ratio(U256::one(), supply).unwrap();
+            assert_eq!(
+                price_matched_supply(supply, U256::from(500), low),
+                Ok((supply, U256::zero()))
+            );
+            assert_eq!(
+                price_matched_supply(U256::zero(), U256::one(), flat),
+                Err(GraduationError::ZeroSupply)
+            );
+
+            assert_eq!(price_deviation_bps(flat, flat), 0);
+            let double = Price::from_ratio(U256::from(1_000), supply).unwrap();
+            assert_eq!(price_deviation_bps(double, flat), 10_000);
+            assert_eq!(price_deviation_bps(flat, double), 5_000);
+        }
+
+        #[test]
+        fn test_graduation_plan_split() {
+            assert!(GraduationPlan::default().is_valid());
+            let plan = GraduationPlan {
+                pool_bps: 8_000,
+                creator_bps: 1_500,
+                burn_bps: 500,
+            };
+            assert!(plan.is_valid());
+            assert_eq!(
+                plan.split(Amount::from_attos(1_001)),
+                (Amount::from_attos(801), Amount::from_attos(150), Amount::from_attos(50))
+            );
+
+            let (pool, creator, burn) = plan.split(Amount::from_attos(u128::MAX));
+            let total = pool.saturating_add(creator).saturating_add(burn);
+            assert_eq!(total, Amount::from_attos(u128::MAX));
+
+            let no_pool = GraduationPlan { pool_bps: 0, creator_bps: 5_000, burn_bps: 5_000 };
+            assert!(!no_pool.is_valid());
+            let short = GraduationPlan { pool_bps: 9_000, creator_bps: 500, burn_bps: 0 };
+            assert!(!short.is_valid());
+        }
+    }
+}
+
+/// First-in, first-out cost basis over a trader's curve trades
+///
+/// Only curve trades are seen: tokens received by transfer have no recorded
+/// cost, so selling them reports a zero cost basis for the uncovered part.
+pub mod tax {
+    use super::*;
+
+    /// Tokens bought in one trade and not yet sold
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Lot {
+        pub acquired_at: Timestamp,
+        pub amount: U256,
+        /// Base currency paid for `amount`
+        pub cost: U256,
+    }
+
+    /// Part of a sell matched against one lot (or against no lot at all)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Disposal {
+        /// None when the sold tokens were not bought on the curve
+        pub acquired_at: Option<Timestamp>,
+        pub disposed_at: Timestamp,
+        pub amount: U256,
+        pub cost_basis: U256,
+        pub proceeds: U256,
+    }
+
+    impl Disposal {
+        /// Whether the disposal lost money
+        pub fn is_loss(&self) -> bool {
+            self.proceeds < self.cost_basis
+        }
+
+        /// Absolute difference between proceeds and cost basis
+        pub fn gain_magnitude(&self) -> U256 {
+            if self.is_loss() {
+                self.cost_basis - self.proceeds
+            } else {
+                self.proceeds - self.cost_basis
+            }
+        }
+    }
+
+    /// Disposals within a period and the lots still open at its end
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct Report {
+        pub disposals: Vec<Disposal>,
+        pub open_lots: Vec<Lot>,
+        pub total_proceeds: U256,
+        pub total_cost_basis: U256,
+    }
+
+    /// Match sells against earlier buys in order, reporting the disposals
+    /// made between `from` and `to` (inclusive)
+    ///
+    /// `trades` must be oldest first. Buys before `from` still form lots, so
+    /// a sell in the period carries the basis of whatever it consumed.
+    pub fn fifo_report(trades: &[Trade], from: Timestamp, to: Timestamp) -> Report {
+        fifo_report_from(&[], trades, from, to)
+    }
+
+    /// `fifo_report` starting from lots left open by trades no longer held
+    /// (see `carry_lots`)
+    pub fn fifo_report_from(
+        opening: &[Lot],
+        trades: &[Trade],
+        from: Timestamp,
+        to: Timestamp,
+    ) -> Report {
+        let mut lots: std::collections::VecDeque<Lot> = opening.iter().copied().collect();
+        let mut report = Report::default();
+
+        for trade in trades.iter().take_while(|trade| trade.timestamp <= to) {
+            if trade.is_buy {
+                lots.push_back(Lot {
+                    acquired_at: trade.timestamp,
+                    amount: trade.token_amount,
+                    cost: trade.currency_amount,
+                });
+                continue;
+            }
+
+            let mut remaining = trade.token_amount;
+            let mut proceeds_left = trade.currency_amount;
+            let mut disposals = Vec::new();
+            while !remaining.is_zero() {
+                let (acquired_at, amount, cost_basis) = match lots.front_mut() {
+                    Some(lot) if lot.amount <= remaining => {
+                        let lot = lots.pop_front().expect("front lot exists");
+                        (Some(lot.acquired_at), lot.amount, lot.cost)
+                    }
+                    Some(lot) => {
+                        let cost = lot.cost * remaining / lot.amount;
+                        lot.amount -= remaining;
+                        lot.cost -= cost;
+                        (Some(lot.acquired_at), remaining, cost)
+                    }
+                    None => (None, remaining, U256::zero()),
+                };
+                remaining -= amount;
+
+                // Proceeds are shared pro rata; the last part takes the rounding
+                let proceeds = if remaining.is_zero() {
+                    proceeds_left
+                } else {
+                    trade.currency_amount * amount / trade.token_amount
+                };
+                proceeds_left -= proceeds;
+                disposals.push(Disposal {
+                    acquired_at,
+                    disposed_at: trade.timestamp,
+                    amount,
+                    cost_basis,
+                    proceeds,
+                });
+            }
+
+            if trade.timestamp >= from {
+                for disposal in disposals {
+                    report.total_proceeds += disposal.proceeds;
+                    report.total_cost_basis += disposal.cost_basis;
+                    report.disposals.push(disposal);
+                }
+            }
+        }
+
+        report.open_lots = lots.into_iter().collect();
+        report
+    }
+
+    /// Apply one trade to a trader's open lots, oldest first, so the trade
+    /// can be deleted without losing the basis it leaves behind
+    pub fn carry_lots(lots: &mut Vec<Lot>, trade: &Trade) {
+        if trade.is_buy {
+            lots.push(Lot {
+                acquired_at: trade.timestamp,
+                amount: trade.token_amount,
+                cost: trade.currency_amount,
+            });
+            return;
+        }
+        let mut remaining = trade.token_amount;
+        while !remaining.is_zero() && !lots.is_empty() {
+            if lots[0].amount <= remaining {
+                remaining -= lots.remove(0).amount;
+            } else {
+                let lot = &mut lots[0];
+                lot.cost -= lot.cost * remaining / lot.amount;
+                lot.amount -= remaining;
+                remaining = U256::zero();
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use linera_sdk::linera_base_types::AccountOwner;
+
+        fn trade(is_buy: bool, token_amount: u64, currency_amount: u64, at: u64) -> Trade {
+            Trade {
+                token_id: "t".to_string(),
+                trader: Account {
+                    chain_id: ChainId::root(0),
+                    owner: AccountOwner::CHAIN,
+                },
+                is_buy,
+                token_amount: U256::from(token_amount),
+                currency_amount: U256::from(currency_amount),
+                price: Price::default(),
+                timestamp: Timestamp::from(at),
+                memo: None,
+                referrer: None,
+                flagged: false,
+            }
+        }
+
+        #[test]
+        fn test_fifo_report_splits_lots() {
+            let trades = [
+                trade(true, 100, 1_000, 1),
+                trade(true, 100, 3_000, 2),
+                trade(false, 150, 3_000, 3),
+                trade(false, 100, 500, 4),
+            ];
+
+            let report = fifo_report(&trades, Timestamp::from(0), Timestamp::from(3));
+            assert_eq!(report.disposals.len(), 2);
+            assert_eq!(report.disposals[0].cost_basis, U256::from(1_000));
+            assert_eq!(report.disposals[0].proceeds, U256::from(2_000));
+            assert_eq!(report.disposals[1].amount, U256::from(50));
+            assert_eq!(report.disposals[1].cost_basis, U256::from(1_500));
+            assert_eq!(report.disposals[1].proceeds, U256::from(1_000));
+            assert!(report.disposals[1].is_loss());
+            assert_eq!(report.open_lots.len(), 1);
+            assert_eq!(report.open_lots[0].cost, U256::from(1_500));
+
+            // Only the sell inside the period is reported; the oversold
+            // 50 tokens carry no basis
+            let report = fifo_report(&trades, Timestamp::from(4), Timestamp::from(4));
+            assert_eq!(report.disposals.len(), 2);
+            assert_eq!(report.total_cost_basis, U256::from(1_500));
+            assert_eq!(report.total_proceeds, U256::from(500));
+            assert_eq!(report.disposals[1].acquired_at, None);
+            assert!(report.open_lots.is_empty());
+        }
+
+        #[test]
+        fn test_carried_lots_keep_the_basis_of_deleted_trades() {
+            let trades = [
+                trade(true, 100, 1_000, 1),
+                trade(true, 100, 3_000, 2),
+                trade(false, 150, 3_000, 3),
+                trade(false, 40, 500, 4),
+            ];
+
+            let mut lots = Vec::new();
+            for trade in &trades[..3] {
+                carry_lots(&mut lots, trade);
+            }
+            let (from, to) = (Timestamp::from(0), Timestamp::from(4));
+            let carried = fifo_report_from(&lots, &trades[3..], from, to);
+            let full = fifo_report(&trades, Timestamp::from(4), to);
+            assert_eq!(carried, full);
+            assert_eq!(carried.total_cost_basis, U256::from(1_200));
+        }
+    }
+}
+
+/// Composite launch risk score
+///
+/// Computed by the factory from what it records itself (the creator's launch
+/// history, the curve configuration, moderation) and from the summaries the
+/// token chain reports, never from anything a creator declares about their
+/// own launch. Each component runs from 0 (lowest risk) to `COMPONENT_MAX`
+/// and the total is their sum, so 100 is the riskiest possible launch.
+pub mod risk {
+    use super::*;
+
+    /// Upper bound of each of the five components
+    pub const COMPONENT_MAX: u8 = 20;
+
+    /// Creator fee at or above which the fee component maxes out (10%)
+    pub const MAX_RISK_FEE_BPS: u16 = 1_000;
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "service", derive(SimpleObject))]
+    pub struct RiskScore {
+        /// Sum of the components, 0-100
+        pub total: u8,
+        /// Prior launches that never graduated or were flagged
+        pub creator_history: u8,
+        /// Creator fee level
+        pub fee_level: u8,
+        /// Missing holder and anti-sniping protections
+        pub protection: u8,
+        /// Share of the supply held by the largest holder
+        pub concentration: u8,
+        /// Missing description, image and social links
+        pub metadata: u8,
+    }
+
+    /// The creator's other launches, as recorded by the factory
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct CreatorHistory {
+        pub launches: u64,
+        pub graduated: u64,
+        pub flagged: u64,
+    }
+
+    /// Score a launch
+    ///
+    /// `summary` is the token chain's latest summary, if one has arrived.
+    pub fn score(
+        history: CreatorHistory,
+        config: &BondingCurveConfig,
+        summary: Option<&TokenSummary>,
+        metadata: &TokenMetadata,
+    ) -> RiskScore {
+        let creator_history = creator_history_risk(history);
+        let fee_level = fee_risk(config.creator_fee_bps);
+        let protection = protection_risk(config);
+        let concentration = concentration_risk(summary);
+        let metadata = metadata_risk(metadata);
+        RiskScore {
+            total: creator_history + fee_level + protection + concentration + metadata,
+            creator_history,
+            fee_level,
+            protection,
+            concentration,
+            metadata,
+        }
+    }
+
+    impl RiskScore {
+        /// This score with its concentration taken from a newer summary
+        pub fn with_summary(self, summary: Option<&TokenSummary>) -> Self {
+            let concentration = concentration_risk(summary);
+            RiskScore {
+                total: self.total - self.concentration + concentration,
+                concentration,
+                ..self
+            }
+        }
+    }
+
+    /// Flagged creators score the maximum; unknown creators sit in the middle
+    fn creator_history_risk(history: CreatorHistory) -> u8 {
+        if history.flagged > 0 {
+            return COMPONENT_MAX;
+        }
+        if history.launches == 0 {
+            return COMPONENT_MAX / 2;
+        }
+        let unfinished = history.launches.saturating_sub(history.graduated);
+        (unfinished * u64::from(COMPONENT_MAX) / history.launches) as u8
+    }
+
+    fn fee_risk(creator_fee_bps: u16) -> u8 {
+        let fee_bps = creator_fee_bps.min(MAX_RISK_FEE_BPS);
+        (u32::from(fee_bps) * u32::from(COMPONENT_MAX) / u32::from(MAX_RISK_FEE_BPS)) as u8
+    }
+
+    fn protection_risk(config: &BondingCurveConfig) -> u8 {
+        let mut risk = COMPONENT_MAX;
+        if config.min_holders.is_some_and(|min_holders| min_holders > 1) {
+            risk -= COMPONENT_MAX / 2;
+        }
+        if !config.transfers_enabled_during_curve {
+            risk -= COMPONENT_MAX / 2;
+        }
+        risk
+    }
+
+    /// Launches without a summary yet score in the middle
+    fn concentration_risk(summary: Option<&TokenSummary>) -> u8 {
+        match summary {
+            Some(summary) => {
+                let top_holder_bps = u32::from(summary.top_holder_bps.min(10_000));
+                (top_holder_bps * u32::from(COMPONENT_MAX) / 10_000) as u8
+            }
+            None => COMPONENT_MAX / 2,
+        }
+    }
+
+    fn metadata_risk(metadata: &TokenMetadata) -> u8 {
+        let present = |field: &Option<String>| field.as_deref().is_some_and(|value| !value.trim().is_empty());
+        let items = [
+            !metadata.description.trim().is_empty(),
+            present(&metadata.image_url),
+            present(&metadata.twitter),
+            present(&metadata.telegram),
+            present(&metadata.website),
+        ];
+        let missing = items.iter().filter(|present| !**present).count() as u8;
+        missing * (COMPONENT_MAX / items.len() as u8)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn metadata(complete: bool) -> TokenMetadata {
+            let link = complete.then(|| "https://example.com".to_string());
+            TokenMetadata {
+                name: "Risk".to_string(),
+                symbol: "RSK".to_string(),
+                description: if complete { "A token".to_string() } else { String::new() },
+                image_url: link.clone(),
+                twitter: link.clone(),
+                telegram: link.clone(),
+                website: link,
+            }
+        }
+
+        #[test]
+        fn test_score_components() {
+            let config = BondingCurveConfig {
+                creator_fee_bps: 500,
+                min_holders: Some(50),
+                transfers_enabled_during_curve: false,
+                ..BondingCurveConfig::default()
+            };
+            let summary = TokenSummary {
+                top_holder_bps: 2_500,
+                ..TokenSummary::default()
+            };
+            let history = CreatorHistory {
+                launches: 4,
+                graduated: 3,
+                flagged: 0,
+            };
+
+            let risk = score(history, &config, Some(&summary), &metadata(true));
+            assert_eq!(risk.creator_history, 5);
+            assert_eq!(risk.fee_level, 10);
+            assert_eq!(risk.protection, 0);
+            assert_eq!(risk.concentration, 5);
+            assert_eq!(risk.metadata, 0);
+            assert_eq!(risk.total, 20);
+
+            let concentrated = TokenSummary {
+                top_holder_bps: 10_000,
+                ..summary
+            };
+            let rescored = risk.with_summary(Some(&concentrated));
+            assert_eq!(rescored, score(history, &config, Some(&concentrated), &metadata(true)));
+        }
+
+        #[test]
+        fn test_worst_case_scores_100() {
+            let config = BondingCurveConfig {
+                creator_fee_bps: 5_000,
+                ..BondingCurveConfig::default()
+            };
+            let summary = TokenSummary {
+                top_holder_bps: 10_000,
+                ..TokenSummary::default()
+            };
+            let history = CreatorHistory {
+                launches: 1,
+                graduated: 1,
+                flagged: 1,
+            };
+
+            let risk = score(history, &config, Some(&summary), &metadata(false));
+            assert_eq!(risk.total, 100);
+
+            // New creators and unsynced launches land in the middle
+            let fresh = score(CreatorHistory::default(), &config, None, &metadata(false));
+            assert_eq!(fresh.creator_history, COMPONENT_MAX / 2);
+            assert_eq!(fresh.concentration, COMPONENT_MAX / 2);
+        }
+    }
+}
+
+/// Conversion between curve base units (U256) and native `Amount`s
+///
+/// A curve base unit is `10^-base_decimals` native tokens. `Amount` counts
+/// attos (`10^-18` tokens), so any `base_decimals` up to 18 converts exactly.
+pub mod units {
+    use super::*;
+    use thiserror::Error;
+
+    /// Decimal places of the native `Amount` type
+    pub const NATIVE_DECIMALS: u8 = 18;
+
+    #[derive(Debug, Error, PartialEq, Eq)]
+    pub enum UnitsError {
+        #[error("Base decimals {0} exceed the native precision of {NATIVE_DECIMALS}")]
+        TooManyDecimals(u8),
+
+        #[error("Value {0} does not fit in a native Amount")]
+        Overflow(U256),
+    }
+
+    /// Attos per curve base unit
+    pub fn attos_per_unit(base_decimals: u8) -> Result<u128, UnitsError> {
+        if base_decimals > NATIVE_DECIMALS {
+            return Err(UnitsError::TooManyDecimals(base_decimals));
+        }
+        Ok(10u128.pow(u32::from(NATIVE_DECIMALS - base_decimals)))
+    }
+
+    /// Convert curve base units into a native `Amount`
+    pub fn to_amount(value: U256, base_decimals: u8) -> Result<Amount, UnitsError> {
+        let attos = value
+            .checked_mul(U256::from(attos_per_unit(base_decimals)?))
+            .filter(|attos| *attos <= U256::from(u128::MAX))
+            .ok_or(UnitsError::Overflow(value))?;
+        Ok(Amount::from_attos(attos.as_u128()))
+    }
+
+    /// Convert a native `Amount` into curve base units, rounding down
+    pub fn from_amount(amount: Amount, base_decimals: u8) -> Result<U256, UnitsError> {
+        Ok(U256::from(u128::from(amount) / attos_per_unit(base_decimals)?))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Deterministic xorshift sequence used as a property-test input source
+        fn samples(count: usize) -> Vec<u128> {
+            let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+            (0..count)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    u128::from(state)
+                })
+                .collect()
+        }
+
+        #[test]
+        fn test_whole_token_units() {
+            assert_eq!(to_amount(U256::from(5), 0).unwrap(), Amount::from_tokens(5));
+            assert_eq!(from_amount(Amount::from_tokens(5), 0).unwrap(), U256::from(5));
+        }
+
+        #[test]
+        fn test_atto_units() {
+            assert_eq!(to_amount(U256::one(), 18).unwrap(), Amount::from_attos(1));
+            assert_eq!(from_amount(Amount::ONE, 18).unwrap(), U256::from(u128::from(Amount::ONE)));
+        }
+
+        #[test]
+        fn test_from_amount_rounds_down() {
+            let amount = Amount::from_attos(1_999_999);
+            assert_eq!(from_amount(amount, 12).unwrap(), U256::one());
+        }
+
+        #[test]
+        fn test_rejects_invalid_inputs() {
+            assert_eq!(
+                to_amount(U256::one(), 19),
+                Err(UnitsError::TooManyDecimals(19))
+            );
+            let huge = U256::from(u128::MAX);
+            assert_eq!(to_amount(huge, 0), Err(UnitsError::Overflow(huge)));
+        }
+
+        #[test]
+        fn test_round_trip_property() {
+            for base_decimals in 0..=NATIVE_DECIMALS {
+                let max_units = u128::MAX / attos_per_unit(base_decimals).unwrap();
+                for sample in samples(64) {
+                    let value = U256::from(sample % max_units);
+                    let amount = to_amount(value, base_decimals).unwrap();
+                    assert_eq!(from_amount(amount, base_decimals).unwrap(), value);
+                }
+            }
+        }
+    }
+}
+
+/// Resolution of `ipfs://` metadata links through an HTTP gateway
+///
+/// Image URLs are stored as submitted; services rewrite IPFS links against
+/// the gateway configured in their parameters so frontends can render them
+/// directly.
+pub mod ipfs {
+    /// Gateway used when the application parameters configure none
+    pub const DEFAULT_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+    const IPFS_SCHEME: &str = "ipfs://";
+
+    /// Rewrite an `ipfs://CID[/path]` link to `gateway` + `CID[/path]`
+    ///
+    /// HTTP(S) links pass through unchanged; anything else, including an
+    /// `ipfs://` link without a content identifier, resolves to None.
+    pub fn gateway_url(url: &str, gateway: &str) -> Option<String> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return Some(url.to_string());
+        }
+        let path = url.strip_prefix(IPFS_SCHEME)?;
+        // Tolerate the redundant `ipfs://ipfs/CID` form some pinning services emit
+        let path = path.strip_prefix("ipfs/").unwrap_or(path).trim_start_matches('/');
+        if path.is_empty() {
+            return None;
+        }
+        Some(format!("{}/{}", gateway.trim_end_matches('/'), path))
+    }
+
+    /// Resolve an image link, checking with the gateway that an IPFS link
+    /// is actually served before handing it out
+    ///
+    /// The node must allow the gateway's host in its HTTP request allow list.
+    #[cfg(feature = "service")]
+    pub fn resolve<S: linera_sdk::Service>(
+        runtime: &linera_sdk::ServiceRuntime<S>,
+        url: &str,
+        gateway: Option<&str>,
+    ) -> Option<String> {
+        use linera_sdk::http;
+
+        let resolved = gateway_url(url, gateway.unwrap_or(DEFAULT_GATEWAY))?;
+        if !url.starts_with(IPFS_SCHEME) {
+            return Some(resolved);
+        }
+        let response = runtime.http_request(http::Request::new(http::Method::Head, &resolved));
+        (200..300).contains(&response.status).then_some(resolved)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_rewrites_ipfs_links() {
+            assert_eq!(
+                gateway_url("ipfs://bafyabc/logo.png", "https://gw.example/ipfs/").as_deref(),
+                Some("https://gw.example/ipfs/bafyabc/logo.png")
+            );
+            assert_eq!(
+                gateway_url("ipfs://ipfs/bafyabc", "https://gw.example/ipfs").as_deref(),
+                Some("https://gw.example/ipfs/bafyabc")
+            );
+        }
 
-        // Integral: k * (x^3 / (3 * scale^2))
-        let scale_squared = scale * scale;
-        let integral_new = (k * new_supply * new_supply * new_supply) / (U256::from(3) * scale_squared);
-        let integral_old = (k * current_supply * current_supply * current_supply) / (U256::from(3) * scale_squared);
+        #[test]
+        fn test_passes_through_http_links() {
+            let url = "https://example.com/image.png";
+            assert_eq!(gateway_url(url, DEFAULT_GATEWAY).as_deref(), Some(url));
+        }
 
-        integral_new - integral_old
+        #[test]
+        fn test_rejects_unresolvable_links() {
+            assert_eq!(gateway_url("ipfs://", DEFAULT_GATEWAY), None);
+            assert_eq!(gateway_url("ftp://example.com/a.png", DEFAULT_GATEWAY), None);
+        }
     }
+}
 
-    /// Calculate return for selling `amount` tokens at current supply
-    pub fn calculate_sell_return(
-        current_supply: U256,
-        amount: U256,
-        k: U256,
-        scale: U256,
-    ) -> U256 {
-        if amount > current_supply {
-            return U256::zero();
+/// Relay-style cursor pagination shared by the services' list queries
+///
+/// Cursors are positions in the underlying list, so a page stays stable
+/// while items are only appended. Resolvers pass the Relay arguments
+/// through `async_graphql::connection::query`, pick their slice with
+/// `window` and wrap it with `page`.
+pub mod pagination {
+    /// Edges returned when neither `first` nor `last` is given
+    pub const DEFAULT_PAGE_SIZE: usize = 20;
+
+    /// Most edges a single page may hold
+    pub const MAX_PAGE_SIZE: usize = 100;
+
+    /// Positions `[start, end)` selected by Relay arguments over `total` items
+    pub fn window(
+        after: Option<usize>,
+        before: Option<usize>,
+        first: Option<usize>,
+        last: Option<usize>,
+        total: usize,
+    ) -> (usize, usize) {
+        let mut start = after.map_or(0, |after| after.saturating_add(1)).min(total);
+        let mut end = before.unwrap_or(total).min(total).max(start);
+        match (first, last) {
+            (Some(first), _) => end = end.min(start + first.min(MAX_PAGE_SIZE)),
+            (None, Some(last)) => start = start.max(end.saturating_sub(last.min(MAX_PAGE_SIZE))),
+            (None, None) => end = end.min(start + DEFAULT_PAGE_SIZE),
         }
+        (start, end)
+    }
 
-        let new_supply = current_supply - amount;
+    #[cfg(feature = "service")]
+    pub use self::connection::*;
+
+    #[cfg(feature = "service")]
+    mod connection {
+        use async_graphql::{
+            connection::{Connection, Edge, EmptyFields},
+            OutputType, SimpleObject,
+        };
+
+        /// Fields every connection carries next to its edges and page info
+        #[derive(SimpleObject)]
+        pub struct ConnectionTotals {
+            /// Items in the whole list, not just this page
+            pub total_count: u64,
+        }
 
-        let scale_squared = scale * scale;
-        let integral_old = (k * current_supply * current_supply * current_supply) / (U256::from(3) * scale_squared);
-        let integral_new = (k * new_supply * new_supply * new_supply) / (U256::from(3) * scale_squared);
+        /// A page of `T`, with integer cursors and the list's total count
+        pub type Page<T> = Connection<usize, T, ConnectionTotals, EmptyFields>;
 
-        integral_old - integral_new
+        /// Wrap the nodes of window `[start, end)`, each with its cursor
+        pub fn page<T: OutputType>(
+            nodes: Vec<(usize, T)>,
+            start: usize,
+            end: usize,
+            total: usize,
+        ) -> Page<T> {
+            let totals = ConnectionTotals {
+                total_count: total as u64,
+            };
+            let mut connection = Connection::with_additional_fields(start > 0, end < total, totals);
+            connection
+                .edges
+                .extend(nodes.into_iter().map(|(position, node)| Edge::new(position, node)));
+            connection
+        }
     }
 
-    /// Calculate current price at given supply
-    /// Formula: k * (supply / scale)^2
-    /// Optimized to minimize precision loss: (k * supply / scale) * supply / scale
-    pub fn calculate_current_price(supply: U256, k: U256, scale: U256) -> U256 {
-        if supply == U256::zero() || scale == U256::zero() {
-            return U256::zero();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_forward_pages() {
+            assert_eq!(window(None, None, None, None, 50), (0, DEFAULT_PAGE_SIZE));
+            assert_eq!(window(None, None, Some(10), None, 50), (0, 10));
+            assert_eq!(window(Some(9), None, Some(10), None, 50), (10, 20));
+            assert_eq!(window(Some(45), None, Some(10), None, 50), (46, 50));
+            assert_eq!(window(Some(60), None, Some(10), None, 50), (50, 50));
+        }
+
+        #[test]
+        fn test_backward_pages() {
+            assert_eq!(window(None, None, None, Some(10), 50), (40, 50));
+            assert_eq!(window(None, Some(40), None, Some(10), 50), (30, 40));
+            assert_eq!(window(None, Some(3), None, Some(10), 50), (0, 3));
+        }
+
+        #[test]
+        fn test_page_size_is_capped() {
+            assert_eq!(window(None, None, Some(1_000), None, 500), (0, MAX_PAGE_SIZE));
+        }
+    }
+}
+
+/// HyperLogLog sketch for approximate unique-trader counts
+///
+/// 2^10 one-byte registers keep the sketch at 1 KiB however many accounts
+/// are added, with a standard error of about 3%. Contracts only insert;
+/// the floating-point estimate is left to the services.
+pub mod cardinality {
+    use super::*;
+
+    /// Bits of the hash choosing a register
+    pub const PRECISION: u32 = 10;
+
+    /// Number of registers
+    pub const REGISTERS: usize = 1 << PRECISION;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct TraderSketch {
+        registers: Vec<u8>,
+    }
+
+    impl Default for TraderSketch {
+        fn default() -> Self {
+            Self { registers: vec![0; REGISTERS] }
+        }
+    }
+
+    impl TraderSketch {
+        /// Add an account; adding it again never changes the sketch
+        pub fn insert(&mut self, account: &Account) {
+            let hash = hash(account.to_string().as_bytes());
+            let index = (hash >> (64 - PRECISION)) as usize;
+            let rank = ((hash << PRECISION).leading_zeros() + 1).min(64 - PRECISION + 1) as u8;
+            if let Some(register) = self.registers.get_mut(index) {
+                *register = (*register).max(rank);
+            }
+        }
+
+        /// Approximate number of distinct accounts added
+        pub fn estimate(&self) -> u64 {
+            if self.registers.is_empty() {
+                return 0;
+            }
+            let registers = self.registers.len() as f64;
+            let alpha = 0.7213 / (1.0 + 1.079 / registers);
+            let sum: f64 =
+                self.registers.iter().map(|register| 2f64.powi(-i32::from(*register))).sum();
+            let raw = alpha * registers * registers / sum;
+
+            // Linear counting is more accurate while many registers are empty
+            let empty = self.registers.iter().filter(|register| **register == 0).count();
+            let estimate = if raw <= 2.5 * registers && empty > 0 {
+                registers * (registers / empty as f64).ln()
+            } else {
+                raw
+            };
+            estimate.round() as u64
+        }
+    }
+
+    /// FNV-1a finished with the SplitMix64 mixer, so that every bit of the
+    /// result depends on the whole input
+    fn hash(bytes: &[u8]) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
         }
-        // Avoid overflow and precision loss by dividing incrementally
-        (k * supply / scale) * supply / scale
+        hash ^= hash >> 30;
+        hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash ^= hash >> 27;
+        hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^ (hash >> 31)
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use linera_sdk::linera_base_types::AccountOwner;
+
+        fn account(index: u32) -> Account {
+            Account { chain_id: ChainId::root(index), owner: AccountOwner::CHAIN }
+        }
 
         #[test]
-        fn test_buy_cost_calculation() {
-            let k = U256::from(1000);
-            let scale = U256::from(1_000_000);
-            let current_supply = U256::from(0);
-            let amount = U256::from(100_000);
+        fn test_small_counts_are_exact() {
+            let mut sketch = TraderSketch::default();
+            assert_eq!(sketch.estimate(), 0);
+            for index in 0..3 {
+                sketch.insert(&account(index));
+                sketch.insert(&account(index));
+            }
+            assert_eq!(sketch.estimate(), 3);
+        }
 
-            let cost = calculate_buy_cost(current_supply, amount, k, scale);
-            assert!(cost > U256::zero());
+        #[test]
+        fn test_large_counts_are_close() {
+            let mut sketch = TraderSketch::default();
+            for index in 0..20_000 {
+                sketch.insert(&account(index));
+            }
+            let estimate = sketch.estimate();
+            assert!((18_000..=22_000).contains(&estimate), "estimate {}", estimate);
+        }
+    }
+}
+
+/// Merkle trees over balance snapshots
+///
+/// Pairs are hashed in sorted order, so proofs are plain sibling lists
+/// without position bits. An odd node is carried up to the next level.
+pub mod merkle {
+    use super::*;
+    use linera_sdk::linera_base_types::BcsHashable;
+
+    #[derive(Serialize, Deserialize)]
+    struct Leaf {
+        snapshot_id: u64,
+        account: Account,
+        balance: U256,
+    }
+
+    impl BcsHashable<'_> for Leaf {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Node {
+        left: CryptoHash,
+        right: CryptoHash,
+    }
+
+    impl BcsHashable<'_> for Node {}
+
+    /// Leaf committing to `account` holding `balance` in snapshot `snapshot_id`
+    pub fn leaf_hash(snapshot_id: u64, account: Account, balance: U256) -> CryptoHash {
+        CryptoHash::new(&Leaf {
+            snapshot_id,
+            account,
+            balance,
+        })
+    }
+
+    fn node_hash(a: CryptoHash, b: CryptoHash) -> CryptoHash {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        CryptoHash::new(&Node { left, right })
+    }
+
+    fn next_level(level: &[CryptoHash]) -> Vec<CryptoHash> {
+        level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => node_hash(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    /// Root over `leaves`, or None if there are none
+    pub fn root(leaves: &[CryptoHash]) -> Option<CryptoHash> {
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            level = next_level(&level);
+        }
+        level.first().copied()
+    }
+
+    /// Sibling path for the leaf at `index`
+    pub fn proof(leaves: &[CryptoHash], mut index: usize) -> Option<Vec<CryptoHash>> {
+        if index >= leaves.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(index ^ 1) {
+                siblings.push(*sibling);
+            }
+            level = next_level(&level);
+            index /= 2;
+        }
+        Some(siblings)
+    }
+
+    /// Whether `siblings` lead from `leaf` to `root`
+    pub fn verify(leaf: CryptoHash, siblings: &[CryptoHash], root: CryptoHash) -> bool {
+        siblings
+            .iter()
+            .fold(leaf, |hash, sibling| node_hash(hash, *sibling))
+            == root
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use linera_sdk::linera_base_types::AccountOwner;
+
+        fn leaves(count: u64) -> Vec<CryptoHash> {
+            (0..count)
+                .map(|i| {
+                    let account = Account {
+                        chain_id: ChainId::root(0),
+                        owner: AccountOwner::CHAIN,
+                    };
+                    leaf_hash(0, account, U256::from(i + 1))
+                })
+                .collect()
         }
 
         #[test]
-        fn test_sell_return_calculation() {
-            let k = U256::from(1000);
-            let scale = U256::from(1_000_000);
-            let current_supply = U256::from(100_000);
-            let amount = U256::from(50_000);
+        fn test_every_leaf_proves_inclusion() {
+            for count in 1..=9 {
+                let leaves = leaves(count);
+                let root = root(&leaves).unwrap();
+                for (index, leaf) in leaves.iter().enumerate() {
+                    let siblings = proof(&leaves, index).unwrap();
+                    assert!(verify(*leaf, &siblings, root), "leaf {index} of {count}");
+                }
+            }
+        }
 
-            let return_amount = calculate_sell_return(current_supply, amount, k, scale);
-            assert!(return_amount > U256::zero());
+        #[test]
+        fn test_rejects_foreign_leaf() {
+            let leaves = leaves(5);
+            let root = root(&leaves).unwrap();
+            let siblings = proof(&leaves, 2).unwrap();
+            let account = Account {
+                chain_id: ChainId::root(0),
+                owner: AccountOwner::CHAIN,
+            };
+            assert!(!verify(leaf_hash(0, account, U256::from(100)), &siblings, root));
+            assert!(!verify(leaf_hash(1, account, U256::from(3)), &siblings, root));
+            assert_eq!(super::root(&[]), None);
+            assert_eq!(proof(&leaves, 5), None);
+        }
+    }
+}
+
+/// Structured, throttled logging shared by the three contracts
+///
+/// Each contract keeps a `Logger` for the block being executed. Lines go to
+/// the `log` facade under the contract's target, capped per level so a loop
+/// over many items cannot flood the validator's logs; the number of dropped
+/// lines is reported when the logger is flushed. Critical lines are always
+/// returned by `flush` so the contract can mirror them into its bounded
+/// on-chain `CriticalEventLog`.
+pub mod logging {
+    use super::*;
+    use linera_sdk::views::{MapView, RegisterView, View, ViewStorageContext};
+    use linera_views::ViewError;
+    use std::collections::BTreeMap;
+    use std::fmt;
+
+    pub const FACTORY_TARGET: &str = "fair_launch::factory";
+    pub const TOKEN_TARGET: &str = "fair_launch::token";
+    pub const SWAP_TARGET: &str = "fair_launch::swap";
+
+    /// Lines emitted per level and block before further lines are dropped
+    pub const MAX_LINES_PER_LEVEL: u32 = 20;
+
+    /// Critical events kept on-chain per contract (oldest are evicted)
+    pub const CRITICAL_EVENT_CAPACITY: u64 = 64;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum Level {
+        Debug,
+        Info,
+        Warn,
+        Error,
+        /// An error that needs operator attention (e.g. funds left unaccounted)
+        Critical,
+    }
+
+    impl Level {
+        fn log_level(self) -> log::Level {
+            match self {
+                Level::Debug => log::Level::Debug,
+                Level::Info => log::Level::Info,
+                Level::Warn => log::Level::Warn,
+                Level::Error | Level::Critical => log::Level::Error,
+            }
+        }
+    }
+
+    /// A critical log line mirrored into contract state
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct CriticalEvent {
+        pub target: String,
+        pub message: String,
+        pub recorded_at: Timestamp,
+    }
+
+    /// GraphQL-friendly version of CriticalEvent
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "service", derive(SimpleObject))]
+    pub struct CriticalEventGQL {
+        pub sequence: u64,
+        pub target: String,
+        pub message: String,
+        pub recorded_at: String,
+    }
+
+    impl CriticalEventGQL {
+        pub fn new(sequence: u64, event: &CriticalEvent) -> Self {
+            Self {
+                sequence,
+                target: event.target.clone(),
+                message: event.message.clone(),
+                recorded_at: event.recorded_at.micros().to_string(),
+            }
+        }
+    }
+
+    /// Per-block logger for one contract
+    #[derive(Debug)]
+    pub struct Logger {
+        target: &'static str,
+        emitted: BTreeMap<Level, u32>,
+        suppressed: BTreeMap<Level, u32>,
+        critical: Vec<String>,
+    }
+
+    impl Logger {
+        pub fn new(target: &'static str) -> Self {
+            Logger {
+                target,
+                emitted: BTreeMap::new(),
+                suppressed: BTreeMap::new(),
+                critical: Vec::new(),
+            }
+        }
+
+        pub fn log(&mut self, level: Level, message: impl fmt::Display) {
+            if level == Level::Critical {
+                self.critical.push(message.to_string());
+            }
+
+            let emitted = self.emitted.entry(level).or_default();
+            if *emitted >= MAX_LINES_PER_LEVEL {
+                *self.suppressed.entry(level).or_default() += 1;
+                return;
+            }
+            *emitted += 1;
+            log::log!(target: self.target, level.log_level(), "{}", message);
+        }
+
+        pub fn debug(&mut self, message: impl fmt::Display) {
+            self.log(Level::Debug, message);
+        }
+
+        pub fn info(&mut self, message: impl fmt::Display) {
+            self.log(Level::Info, message);
+        }
+
+        pub fn warn(&mut self, message: impl fmt::Display) {
+            self.log(Level::Warn, message);
+        }
+
+        pub fn error(&mut self, message: impl fmt::Display) {
+            self.log(Level::Error, message);
+        }
+
+        pub fn critical(&mut self, message: impl fmt::Display) {
+            self.log(Level::Critical, message);
+        }
+
+        /// Report dropped lines, reset the per-block caps and hand over the
+        /// critical events to mirror on-chain
+        pub fn flush(&mut self, now: Timestamp) -> Vec<CriticalEvent> {
+            for (level, count) in std::mem::take(&mut self.suppressed) {
+                log::log!(
+                    target: self.target,
+                    level.log_level(),
+                    "{} {:?} lines suppressed in this block",
+                    count,
+                    level
+                );
+            }
+            self.emitted.clear();
+
+            std::mem::take(&mut self.critical)
+                .into_iter()
+                .map(|message| CriticalEvent {
+                    target: self.target.to_string(),
+                    message,
+                    recorded_at: now,
+                })
+                .collect()
+        }
+    }
+
+    /// Ring buffer of the latest critical events
+    #[derive(View)]
+    #[view(context = ViewStorageContext)]
+    pub struct CriticalEventLog {
+        /// Events by sequence number, the latest `CRITICAL_EVENT_CAPACITY` only
+        pub events: MapView<u64, CriticalEvent>,
+
+        /// Number of events ever recorded (next sequence number)
+        pub count: RegisterView<u64>,
+    }
+
+    impl CriticalEventLog {
+        /// Append an event, evicting the oldest one beyond capacity
+        pub fn push(&mut self, event: CriticalEvent) -> Result<(), ViewError> {
+            let sequence = *self.count.get();
+            self.events.insert(&sequence, event)?;
+            if sequence >= CRITICAL_EVENT_CAPACITY {
+                self.events.remove(&(sequence - CRITICAL_EVENT_CAPACITY))?;
+            }
+            self.count.set(sequence + 1);
+            Ok(())
         }
 
+        /// Up to `limit` retained events, newest first
+        pub async fn recent(&self, limit: u64) -> Result<Vec<(u64, CriticalEvent)>, ViewError> {
+            let count = *self.count.get();
+            let oldest = count.saturating_sub(CRITICAL_EVENT_CAPACITY.min(limit));
+            let mut events = Vec::new();
+            for sequence in (oldest..count).rev() {
+                if let Some(event) = self.events.get(&sequence).await? {
+                    events.push((sequence, event));
+                }
+            }
+            Ok(events)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
         #[test]
-        fn test_price_calculation() {
-            let k = U256::from(1000);
-            let scale = U256::from(1_000_000);
-            let supply = U256::from(500_000);
+        fn test_logger_throttles_and_collects_critical() {
+            let mut logger = Logger::new(SWAP_TARGET);
+            for i in 0..(MAX_LINES_PER_LEVEL + 5) {
+                logger.info(i);
+            }
+            assert_eq!(logger.suppressed.get(&Level::Info), Some(&5));
+
+            logger.critical("liquidity unaccounted");
+            let events = logger.flush(Timestamp::from(7));
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].target, SWAP_TARGET);
+            assert_eq!(events[0].recorded_at, Timestamp::from(7));
+            assert!(logger.suppressed.is_empty());
+            assert!(logger.flush(Timestamp::from(8)).is_empty());
+        }
+    }
+}
+
+/// Durable, sequenced record of externally relevant events
+///
+/// Indexers poll `events(afterSeq, limit)` with the last sequence number they
+/// processed; sequence numbers are assigned without gaps and entries are never
+/// evicted, so nothing is missed between polls.
+pub mod outbox {
+    use super::*;
+    use linera_sdk::views::{MapView, RegisterView, View, ViewStorageContext};
+    use linera_views::ViewError;
+
+    /// One recorded event
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct OutboxEvent {
+        /// Event name, e.g. "Trade" or "PoolCreated"
+        pub kind: String,
+        /// Token or pool the event concerns, if any
+        pub subject: Option<String>,
+        /// The event body as JSON
+        pub payload: String,
+        pub recorded_at: Timestamp,
+    }
+
+    /// GraphQL-friendly version of OutboxEvent
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "service", derive(SimpleObject))]
+    pub struct OutboxEventGQL {
+        pub sequence: u64,
+        pub kind: String,
+        pub subject: Option<String>,
+        pub payload: String,
+        pub recorded_at: String,
+    }
+
+    impl OutboxEventGQL {
+        pub fn new(sequence: u64, event: &OutboxEvent) -> Self {
+            Self {
+                sequence,
+                kind: event.kind.clone(),
+                subject: event.subject.clone(),
+                payload: event.payload.clone(),
+                recorded_at: event.recorded_at.micros().to_string(),
+            }
+        }
+    }
+
+    /// Append-only event log of one contract
+    #[derive(View)]
+    #[view(context = ViewStorageContext)]
+    pub struct Outbox {
+        /// Events by sequence number
+        pub events: MapView<u64, OutboxEvent>,
+
+        /// Number of events ever recorded (next sequence number)
+        pub count: RegisterView<u64>,
+    }
+
+    impl Outbox {
+        /// Append an event, returning its sequence number
+        pub fn push(
+            &mut self,
+            kind: &str,
+            subject: Option<&str>,
+            payload: &impl Serialize,
+            recorded_at: Timestamp,
+        ) -> Result<u64, ViewError> {
+            let sequence = *self.count.get();
+            let event = OutboxEvent {
+                kind: kind.to_string(),
+                subject: subject.map(str::to_string),
+                payload: serde_json::to_string(payload).unwrap_or_default(),
+                recorded_at,
+            };
+            self.events.insert(&sequence, event)?;
+            self.count.set(sequence + 1);
+            Ok(sequence)
+        }
+
+        /// Up to `limit` events after sequence `after` (from the first if None), oldest first
+        pub async fn after(
+            &self,
+            after: Option<u64>,
+            limit: u64,
+        ) -> Result<Vec<(u64, OutboxEvent)>, ViewError> {
+            let start = after.map_or(0, |sequence| sequence.saturating_add(1));
+            let end = (*self.count.get()).min(start.saturating_add(limit));
+            let mut events = Vec::new();
+            for sequence in start..end {
+                if let Some(event) = self.events.get(&sequence).await? {
+                    events.push((sequence, event));
+                }
+            }
+            Ok(events)
+        }
+    }
+}
+
+/// Price channel a token chain publishes for wallets and apps on other chains
+///
+/// The token contract emits a `TokenEvent::PriceUpdate` on `PRICE_FEED_STREAM`
+/// every `every_trades` trades or whenever the price has moved `move_bps` since
+/// the last update. Subscribers embed `PriceFeedState` and `apply` the updates
+/// they read from the stream.
+pub mod price_feed {
+    use super::*;
+    use linera_sdk::views::{MapView, RegisterView, View, ViewStorageContext};
+    use linera_views::ViewError;
+
+    /// Event stream token chains publish price updates to
+    pub const PRICE_FEED_STREAM: &[u8] = b"price_feed";
+
+    /// Default number of trades between updates
+    pub const DEFAULT_EVERY_TRADES: u64 = 10;
+
+    /// Default price move that triggers an update early (100 = 1%)
+    pub const DEFAULT_MOVE_BPS: u16 = 100;
+
+    /// When a token chain publishes its price
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct PriceFeedConfig {
+        /// Trades between updates (0 publishes on price moves only)
+        pub every_trades: u64,
+        /// Price move since the last update that publishes early (0 disables)
+        pub move_bps: u16,
+    }
 
-            let price = calculate_current_price(supply, k, scale);
-            assert!(price > U256::zero());
+    impl Default for PriceFeedConfig {
+        fn default() -> Self {
+            Self {
+                every_trades: DEFAULT_EVERY_TRADES,
+                move_bps: DEFAULT_MOVE_BPS,
+            }
+        }
+    }
+
+    /// A token's price as published on its chain
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct PriceUpdate {
+        pub token_id: String,
+        pub price: Price,
+        /// The token's trade count when the price was taken; orders updates
+        pub trade_count: u64,
+        pub published_at: Timestamp,
+    }
+
+    /// Distance between two prices in basis points of `from` (saturating)
+    pub fn move_bps(from: Price, to: Price) -> u32 {
+        if from.is_zero() {
+            return if to.is_zero() { 0 } else { u32::MAX };
+        }
+        let diff = if to > from {
+            to.raw() - from.raw()
+        } else {
+            from.raw() - to.raw()
+        };
+        wad::mul_div(diff, U256::from(10_000), from.raw())
+            .map_or(u32::MAX, |bps| bps.min(U256::from(u32::MAX)).as_u32())
+    }
+
+    /// Whether a trade leaving the token at `price` after `trade_count` trades
+    /// should be published, given the last update (None publishes the first)
+    pub fn should_publish(
+        config: &PriceFeedConfig,
+        last: Option<&PriceUpdate>,
+        price: Price,
+        trade_count: u64,
+    ) -> bool {
+        let Some(last) = last else {
+            return true;
+        };
+        let trades = trade_count.saturating_sub(last.trade_count);
+        (config.every_trades > 0 && trades >= config.every_trades)
+            || (config.move_bps > 0 && move_bps(last.price, price) >= u32::from(config.move_bps))
+    }
+
+    /// Latest price received for a token
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct PriceQuote {
+        /// Chain the update was published on
+        pub publisher: ChainId,
+        pub update: PriceUpdate,
+        pub received_at: Timestamp,
+    }
+
+    /// Subscriber-side view of the latest published price per token
+    #[derive(View)]
+    #[view(context = ViewStorageContext)]
+    pub struct PriceFeedState {
+        /// Latest quote per token: token_id → PriceQuote
+        pub quotes: MapView<String, PriceQuote>,
+
+        /// Number of updates applied
+        pub update_count: RegisterView<u64>,
+    }
+
+    impl PriceFeedState {
+        /// Store an update unless a later one for the same token is already
+        /// held; returns whether it was applied
+        pub async fn apply(
+            &mut self,
+            publisher: ChainId,
+            update: PriceUpdate,
+            received_at: Timestamp,
+        ) -> Result<bool, ViewError> {
+            if let Some(current) = self.quotes.get(&update.token_id).await? {
+                if current.update.trade_count >= update.trade_count {
+                    return Ok(false);
+                }
+            }
+            let token_id = update.token_id.clone();
+            let quote = PriceQuote {
+                publisher,
+                update,
+                received_at,
+            };
+            self.quotes.insert(&token_id, quote)?;
+            let count = *self.update_count.get();
+            self.update_count.set(count + 1);
+            Ok(true)
+        }
+
+        /// Latest quote for a token
+        pub async fn quote(&self, token_id: &str) -> Result<Option<PriceQuote>, ViewError> {
+            self.quotes.get(token_id).await
+        }
+
+        /// Latest quote for every token seen
+        pub async fn all_quotes(&self) -> Result<Vec<PriceQuote>, ViewError> {
+            let mut quotes = Vec::new();
+            for token_id in self.quotes.indices().await? {
+                if let Some(quote) = self.quotes.get(&token_id).await? {
+                    quotes.push(quote);
+                }
+            }
+            Ok(quotes)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn update(price: u64, trade_count: u64) -> PriceUpdate {
+            PriceUpdate {
+                token_id: "t".to_string(),
+                price: Price::from_raw(U256::from(price)),
+                trade_count,
+                published_at: Timestamp::from(0),
+            }
+        }
+
+        #[test]
+        fn test_move_bps() {
+            let price = |raw: u64| Price::from_raw(U256::from(raw));
+            assert_eq!(move_bps(price(10_000), price(10_150)), 150);
+            assert_eq!(move_bps(price(10_000), price(9_900)), 100);
+            assert_eq!(move_bps(price(0), price(0)), 0);
+            assert_eq!(move_bps(price(0), price(1)), u32::MAX);
+        }
+
+        #[test]
+        fn test_should_publish() {
+            let config = PriceFeedConfig::default();
+            let last = update(10_000, 5);
+            let price = |raw: u64| Price::from_raw(U256::from(raw));
+
+            // The first trade always publishes
+            assert!(should_publish(&config, None, price(10_000), 1));
+
+            // Small moves wait for the trade interval
+            assert!(!should_publish(&config, Some(&last), price(10_050), 14));
+            assert!(should_publish(&config, Some(&last), price(10_050), 15));
+
+            // A large enough move publishes early, in either direction
+            assert!(should_publish(&config, Some(&last), price(10_100), 6));
+            assert!(should_publish(&config, Some(&last), price(9_900), 6));
+
+            // With both triggers off nothing after the first update publishes
+            let off = PriceFeedConfig {
+                every_trades: 0,
+                move_bps: 0,
+            };
+            assert!(!should_publish(&off, Some(&last), price(20_000), 100));
         }
     }
 }
@@ -359,6 +4861,95 @@ pub enum SwapOperation {
         amount_in: U256,
         min_amount_out: U256,
     },
+    /// Swap for an exact output amount, paying at most `max_in`
+    SwapExactOut {
+        pool_id: String,
+        token_out: String,
+        amount_out: U256,
+        max_in: U256,
+    },
+    /// Route `amount_in` of `path[0]` through the base currency to the last token
+    ///
+    /// Each step sells into one token's pool and buys from the next
+    /// (tokenA → base → tokenB), all or nothing. `min_out` bounds the final
+    /// output, covering slippage across every hop.
+    SwapExactTokensForTokens {
+        path: Vec<String>,
+        amount_in: U256,
+        min_out: U256,
+    },
+    /// Return a time-locked pool's liquidity to its creator once the lock
+    /// has expired (creator only); the pool is emptied
+    WithdrawUnlockedLiquidity {
+        pool_id: String,
+    },
+    /// Spend a pool's accrued buyback fees and burn the tokens, once due
+    ExecuteBuyback {
+        pool_id: String,
+    },
+    /// Pay collected protocol fees out of the treasury (treasury admin only)
+    WithdrawTreasury {
+        to: Account,
+        amount: Amount,
+    },
+    /// Hand treasury management to another account (treasury admin only)
+    SetTreasury {
+        account: Account,
+    },
+    /// Take over treasury management of an application instantiated before
+    /// the treasury admin was recorded (signers on the application's creator
+    /// chain only, while no admin is set)
+    ClaimTreasury,
+    /// Index up to `max_entries` tokens graduated before pools were indexed
+    /// by base asset (callable by anyone; new pools index their token anyway)
+    MigratePools {
+        max_entries: u64,
+    },
+    /// Add up to `max_entries` pools locked before the lock totals were kept
+    /// to them (callable by anyone)
+    BackfillLockedTotals {
+        max_entries: u64,
+    },
+    /// Open a permanently locked pool pairing a graduated token with another
+    /// base asset, seeded with `base_amount` taken from the signer and
+    /// `token_amount` from the tokens they deposited with
+    /// `TokenOperation::DepositPoolTokens`
+    CreatePool {
+        token_id: String,
+        base_asset: BaseAsset,
+        token_amount: U256,
+        base_amount: Amount,
+        /// Decimals of the pool's base units (at most `units::NATIVE_DECIMALS`)
+        base_decimals: u8,
+    },
+    /// Send `amount_out` of a pool's base currency to the `callee`
+    /// application, then call it with a `FlashSwapCallback`
+    ///
+    /// Before the call returns the callee must pay the swap application what
+    /// the callback says is due, in the pool's base asset: the loan plus the
+    /// swap fee. Anything less reverts the whole transaction.
+    FlashSwap {
+        pool_id: String,
+        /// Anything but the pool's token ID, which cannot be borrowed
+        token_out: String,
+        amount_out: U256,
+        callee: ApplicationId,
+        /// Passed through to the callee untouched
+        data: Vec<u8>,
+    },
+}
+
+/// Call a flash swap makes to its callee once the output has been sent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlashSwapCallback {
+    pub pool_id: String,
+    pub token_out: String,
+    pub amount_out: U256,
+    /// Asset the callee must repay in
+    pub base_asset: BaseAsset,
+    /// Amount the callee must pay the swap application before returning
+    pub amount_due: Amount,
+    pub data: Vec<u8>,
 }
 
 /// ABI definitions for the three contracts
@@ -370,7 +4961,7 @@ pub struct TokenAbi;
 
 impl ContractAbi for TokenAbi {
     type Operation = TokenOperation;
-    type Response = ();
+    type Response = TokenResponse;
 }
 
 #[cfg(feature = "service")]
@@ -407,5 +4998,17 @@ impl ServiceAbi for SwapAbi {
     type QueryResponse = async_graphql::Response;
 }
 
+// Flash swap callee ABI: applications receiving `SwapOperation::FlashSwap`
+// output accept `FlashSwapCallback` as an operation
+pub struct FlashSwapCalleeAbi;
+
+impl ContractAbi for FlashSwapCalleeAbi {
+    type Operation = FlashSwapCallback;
+    type Response = ();
+}
+
 #[cfg(test)]
 mod bonding_curve_tests;
+
+#[cfg(test)]
+mod bonding_curve_proptests;
@@ -0,0 +1,54 @@
+use fair_launch_abi::TokenLaunch;
+use linera_sdk::views::{MapView, RegisterView, View, ViewStorageContext};
+use linera_views::ViewError;
+
+/// Archival companion to the factory registry
+///
+/// Finalized token records (graduated with a DEX pool) are moved here so the
+/// hot registry only holds launches that still change.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct ArchiveState {
+    /// Archived tokens: token_id → TokenLaunch
+    pub tokens: MapView<String, TokenLaunch>,
+
+    /// Dense index of archived tokens: index → token_id
+    pub token_index: MapView<u64, String>,
+
+    /// Number of archived tokens
+    pub token_count: RegisterView<u64>,
+}
+
+impl ArchiveState {
+    /// Append a finalized token record
+    pub fn archive_token(&mut self, token: TokenLaunch) -> Result<(), ViewError> {
+        let count = *self.token_count.get();
+        self.token_index.insert(&count, token.token_id.clone())?;
+        self.tokens.insert(&token.token_id, token)?;
+        self.token_count.set(count + 1);
+        Ok(())
+    }
+
+    /// Get an archived token by ID
+    pub async fn get_token(&self, token_id: &str) -> Result<Option<TokenLaunch>, ViewError> {
+        self.tokens.get(token_id).await
+    }
+
+    /// Get an archived token by its archive index
+    pub async fn get_token_at(&self, index: u64) -> Result<Option<TokenLaunch>, ViewError> {
+        match self.token_index.get(&index).await? {
+            Some(token_id) => self.tokens.get(&token_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Overwrite an archived token record
+    pub fn update_token(&mut self, token: TokenLaunch) -> Result<(), ViewError> {
+        self.tokens.insert(&token.token_id, token)
+    }
+
+    /// Number of archived tokens
+    pub fn count(&self) -> u64 {
+        *self.token_count.get()
+    }
+}
@@ -0,0 +1,88 @@
+use fair_launch_abi::{BatchOrder, BatchOrderStatus, BuyBatch};
+use linera_sdk::{
+    linera_base_types::{Account, Timestamp},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+
+/// Batch buys submitted through the factory and the outcome of each order
+///
+/// Orders are settled by the token chains: a `BatchOrderFilled` reply marks
+/// an order filled and a bounced `BatchBuyOrder` marks it failed.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct BatchBuyState {
+    /// Batches: batch_id → BuyBatch
+    pub batches: MapView<u64, BuyBatch>,
+
+    /// Number of batches submitted (next batch_id)
+    pub batch_count: RegisterView<u64>,
+
+    /// Batches each account submitted, oldest first: Account → Vec<batch_id>
+    pub batches_by_buyer: MapView<Account, Vec<u64>>,
+}
+
+impl BatchBuyState {
+    /// Store a new batch with every order pending, returning its ID
+    pub async fn open(
+        &mut self,
+        buyer: Account,
+        orders: Vec<BatchOrder>,
+        created_at: Timestamp,
+    ) -> Result<u64, ViewError> {
+        let batch_id = *self.batch_count.get();
+        let batch = BuyBatch {
+            buyer,
+            orders,
+            created_at,
+        };
+        self.batches.insert(&batch_id, batch)?;
+        let mut batch_ids = self.batches_by_buyer.get(&buyer).await?.unwrap_or_default();
+        batch_ids.push(batch_id);
+        self.batches_by_buyer.insert(&buyer, batch_ids)?;
+        self.batch_count.set(batch_id + 1);
+        Ok(batch_id)
+    }
+
+    /// Record the outcome of one order; returns the updated batch if it exists
+    ///
+    /// Orders already settled keep their first outcome.
+    pub async fn settle(
+        &mut self,
+        batch_id: u64,
+        order_index: u32,
+        status: BatchOrderStatus,
+    ) -> Result<Option<BuyBatch>, ViewError> {
+        let Some(mut batch) = self.batches.get(&batch_id).await? else {
+            return Ok(None);
+        };
+        if let Some(order) = batch.orders.get_mut(order_index as usize) {
+            if order.status == BatchOrderStatus::Pending {
+                order.status = status;
+            }
+        }
+        self.batches.insert(&batch_id, batch.clone())?;
+        Ok(Some(batch))
+    }
+
+    /// Get a batch by ID
+    pub async fn get(&self, batch_id: u64) -> Result<Option<BuyBatch>, ViewError> {
+        self.batches.get(&batch_id).await
+    }
+
+    /// Batches submitted by an account, newest first
+    pub async fn get_by_buyer(
+        &self,
+        buyer: &Account,
+        limit: usize,
+    ) -> Result<Vec<(u64, BuyBatch)>, ViewError> {
+        let batch_ids = self.batches_by_buyer.get(buyer).await?.unwrap_or_default();
+        let mut batches = Vec::new();
+        for batch_id in batch_ids.into_iter().rev().take(limit) {
+            if let Some(batch) = self.batches.get(&batch_id).await? {
+                batches.push((batch_id, batch));
+            }
+        }
+        Ok(batches)
+    }
+}
@@ -1,17 +1,39 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod archive;
+mod batch;
+mod feed;
+mod portfolio;
 mod state;
-use fair_launch_abi::{BondingCurveConfig, FactoryAbi, FactoryOperation, Message, TokenMetadata};
+mod stats;
+mod versions;
+use fair_launch_abi::{
+    fee_tiers,
+    logging::{self, Logger},
+    BatchOrder, BatchOrderStatus, BondingCurveConfig, BytecodeRelease, CreatorFeeBounds,
+    FactoryAbi, FactoryEvent, FactoryOperation, FactoryParameters, FeeTier, InitialBuy,
+    LaunchMechanism,
+    Message, ModerationStatus, SnipeTaxConfig, SnipeTaxDestination, TokenMetadata,
+    FACTORY_EVENTS_STREAM, MAX_BATCH_ORDERS,
+};
 use linera_sdk::{
     abi::WithContractAbi,
-    linera_base_types::{Account, AccountOwner, ChainId},
-    views::View,
+    linera_base_types::{
+        Account, AccountOwner, Amount, ApplicationPermissions, ChainId, ChainOwnership,
+        CryptoHash, StreamName, StreamUpdate,
+    },
+    views::{RootView, View},
     Contract, ContractRuntime,
 };
+use primitive_types::U256;
 use thiserror::Error;
 
+use crate::feed::FeedEntry;
 use crate::state::{FactoryError, FactoryState};
 
+/// Version of this factory bytecode, recorded in `contract_version`
+const CONTRACT_VERSION: u32 = 1;
+
 /// Factory contract errors
 #[derive(Debug, Error)]
 pub enum ContractError {
@@ -27,6 +49,27 @@ pub enum ContractError {
     #[error("Invalid bonding curve configuration: {0}")]
     InvalidCurveConfig(String),
 
+    #[error("Invalid initial buy: {0}")]
+    InvalidInitialBuy(String),
+
+    #[error("Creation fee unpaid: balance {have}, fee {need}")]
+    CreationFeeUnpaid { have: Amount, need: Amount },
+
+    #[error("Invalid subscription: {0}")]
+    InvalidSubscription(String),
+
+    #[error("Invalid creator fee bounds: {0}")]
+    InvalidFeeBounds(String),
+
+    #[error("Invalid fee tiers: {0}")]
+    InvalidFeeTiers(String),
+
+    #[error("Invalid batch buy: {0}")]
+    InvalidBatch(String),
+
+    #[error("Bytecode version {0} is already registered")]
+    BytecodeAlreadyRegistered(u32),
+
     #[error(transparent)]
     ViewError(#[from] anyhow::Error),
 }
@@ -34,6 +77,7 @@ pub enum ContractError {
 pub struct FactoryContract {
     state: FactoryState,
     runtime: ContractRuntime<Self>,
+    logger: Logger,
 }
 
 linera_sdk::contract!(FactoryContract);
@@ -45,19 +89,28 @@ impl WithContractAbi for FactoryContract {
 impl Contract for FactoryContract {
     type Message = Message;
     type InstantiationArgument = ();
-    type Parameters = ();
-    type EventValue = ();
+    type Parameters = FactoryParameters;
+    type EventValue = FactoryEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = FactoryState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load factory state");
-        FactoryContract { state, runtime }
+        FactoryContract {
+            state,
+            runtime,
+            logger: Logger::new(logging::FACTORY_TARGET),
+        }
     }
 
     async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
         // Factory is ready to create tokens immediately after instantiation
         self.runtime.application_parameters();
+
+        // The instantiating account administers curve templates
+        let admin = self.caller_account();
+        self.state.admin.set(Some(admin));
+        self.state.contract_version.set(CONTRACT_VERSION);
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
@@ -65,41 +118,310 @@ impl Contract for FactoryContract {
             FactoryOperation::CreateToken {
                 metadata,
                 curve_config,
+                curve_template,
             } => {
-                match self.execute_create_token(metadata, curve_config).await {
+                match self.execute_create_token(metadata, curve_config, curve_template, None).await {
                     Ok(token_id) => {
-                        log::info!("Successfully created token: {}", token_id);
+                        self.logger.info(format!("Successfully created token: {}", token_id));
                         token_id
                     }
                     Err(e) => {
-                        log::error!("Failed to create token: {}", e);
+                        self.logger.error(format!("Failed to create token: {}", e));
                         panic!("Token creation failed: {}", e);
                     }
                 }
             }
+
+            FactoryOperation::CreateTokenAndBuy {
+                metadata,
+                curve_config,
+                initial_buy,
+                max_cost,
+            } => {
+                let initial_buy = InitialBuy {
+                    amount: initial_buy,
+                    max_cost,
+                };
+                match self
+                    .execute_create_token(metadata, curve_config, None, Some(initial_buy))
+                    .await
+                {
+                    Ok(token_id) => {
+                        self.logger.info(format!(
+                            "Successfully created token with initial buy: {}",
+                            token_id
+                        ));
+                        token_id
+                    }
+                    Err(e) => {
+                        self.logger.error(format!(
+                            "Failed to create token with initial buy: {}",
+                            e
+                        ));
+                        panic!("Token creation failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::AddCurveTemplate { name, config } => {
+                match self.execute_add_curve_template(name, config) {
+                    Ok(name) => {
+                        self.logger.info(format!("Curve template registered: {}", name));
+                        name
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to add curve template: {}", e));
+                        panic!("Curve template registration failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::ArchiveTokens { max_tokens } => {
+                match self.execute_archive_tokens(max_tokens).await {
+                    Ok(archived) => {
+                        self.logger.info(format!("Archived {} finalized tokens", archived));
+                        archived.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to archive tokens: {}", e));
+                        panic!("Token archival failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::RegisterReferralCode { code } => {
+                match self.execute_register_referral_code(code).await {
+                    Ok(code) => {
+                        self.logger.info(format!("Referral code registered: {}", code));
+                        code
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to register referral code: {}", e));
+                        panic!("Referral code registration failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::FlagToken { token_id, reason } => {
+                let flagged_at = self.runtime.system_time();
+                let status = ModerationStatus::Flagged { reason, flagged_at };
+                match self.execute_set_moderation(token_id, status).await {
+                    Ok(token_id) => {
+                        self.logger.info(format!("Token flagged: {}", token_id));
+                        token_id
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to flag token: {}", e));
+                        panic!("Token flagging failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::UnflagToken { token_id } => {
+                match self.execute_set_moderation(token_id, ModerationStatus::Clear).await {
+                    Ok(token_id) => {
+                        self.logger.info(format!("Token unflagged: {}", token_id));
+                        token_id
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to unflag token: {}", e));
+                        panic!("Token unflagging failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::ApproveStateImport { token_id, commitments } => {
+                match self.execute_approve_state_import(token_id, commitments).await {
+                    Ok(token_id) => {
+                        self.logger.info(format!("State import approved for token {}", token_id));
+                        token_id
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to approve state import: {}", e));
+                        panic!("State import approval failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::Subscribe { publisher } => {
+                match self.execute_subscribe(publisher).await {
+                    Ok(publisher) => {
+                        self.logger.info(format!("Subscribed to launch events of {}", publisher));
+                        publisher.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to subscribe: {}", e));
+                        panic!("Subscription failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::Unsubscribe { publisher } => {
+                match self.execute_unsubscribe(publisher).await {
+                    Ok(publisher) => {
+                        self.logger.info(format!(
+                            "Unsubscribed from launch events of {}",
+                            publisher
+                        ));
+                        publisher.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to unsubscribe: {}", e));
+                        panic!("Unsubscription failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::SetCreatorFeeBounds { min_bps, max_bps } => {
+                match self.execute_set_creator_fee_bounds(CreatorFeeBounds { min_bps, max_bps }) {
+                    Ok(bounds) => {
+                        self.logger.info(format!(
+                            "Creator fee bounds set to {}-{} bps",
+                            bounds.min_bps, bounds.max_bps
+                        ));
+                        format!("{}-{}", bounds.min_bps, bounds.max_bps)
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to set creator fee bounds: {}", e));
+                        panic!("Setting creator fee bounds failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::SetMinPoolLock { micros } => {
+                match self.execute_set_min_pool_lock(micros) {
+                    Ok(micros) => {
+                        self.logger.info(format!("Minimum pool lock set to {} micros", micros));
+                        micros.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to set minimum pool lock: {}", e));
+                        panic!("Setting minimum pool lock failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::SetFeeTiers { tiers } => {
+                match self.execute_set_fee_tiers(tiers) {
+                    Ok(count) => {
+                        self.logger.info(format!("Fee tiers set ({} tiers)", count));
+                        count.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to set fee tiers: {}", e));
+                        panic!("Setting fee tiers failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::RegisterBytecode { version, description } => {
+                match self.execute_register_bytecode(version, description).await {
+                    Ok(()) => {
+                        self.logger.info(format!("Token bytecode version {} registered", version));
+                        version.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to register bytecode: {}", e));
+                        panic!("Registering bytecode failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::BatchBuy { orders } => {
+                let order_count = orders.len();
+                match self.execute_batch_buy(orders).await {
+                    Ok(batch_id) => {
+                        self.logger.info(format!(
+                            "Batch buy {} sent with {} orders",
+                            batch_id, order_count
+                        ));
+                        batch_id.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to send batch buy: {}", e));
+                        panic!("Batch buy failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::MigrateTraders { max_entries } => {
+                match self.state.stats.migrate_legacy_traders(max_entries as usize).await {
+                    Ok(moved) => {
+                        self.logger.info(format!("Migrated {} legacy traders", moved));
+                        moved.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to migrate traders: {}", e));
+                        panic!("Trader migration failed: {}", e);
+                    }
+                }
+            }
+
+            FactoryOperation::BackfillStats { max_entries } => {
+                match self.state.backfill_stats_totals(max_entries as usize).await {
+                    Ok(added) => {
+                        self.logger.info(format!("Added {} launches to platform totals", added));
+                        added.to_string()
+                    }
+                    Err(e) => {
+                        self.logger.error(format!("Failed to backfill platform totals: {}", e));
+                        panic!("Stats backfill failed: {}", e);
+                    }
+                }
+            }
         }
     }
 
     async fn execute_message(&mut self, message: Self::Message) {
+        if self.runtime.message_is_bouncing() == Some(true) {
+            self.handle_bounced_message(message).await;
+            return;
+        }
+
         match message {
             Message::TradeExecuted {
                 token_id,
-                trader: _,
-                is_buy: _,
-                token_amount: _,
-                currency_amount: _,
-                new_price: _,
+                trader,
+                is_buy,
+                token_amount,
+                currency_amount,
+                new_price,
+                creator_fee,
             } => {
-                // Update token metrics if needed
-                // For now, we rely on token chain to manage its own state
-                // This could be used for factory-level analytics
-                log::info!("Trade executed on token: {}", token_id);
+                if let Err(e) = self
+                    .state
+                    .stats
+                    .record_curve_trade(&token_id, trader, currency_amount, creator_fee)
+                    .await
+                {
+                    self.logger.error(format!("Failed to count trade on {}: {}", token_id, e));
+                }
+
+                // Cache the trader's position for portfolio queries
+                if let Err(e) = self
+                    .state
+                    .portfolios
+                    .apply_trade(trader, &token_id, is_buy, token_amount, currency_amount, new_price)
+                    .await
+                {
+                    self.logger.error(format!(
+                        "Failed to update portfolio of {} for {}: {}",
+                        trader, token_id, e
+                    ));
+                }
             }
 
             Message::GraduateToken {
                 token_id,
                 total_supply,
                 total_raised,
+                receipt: _,
+                base_decimals: _,
+                base_asset: _,
+                stable_leg: _,
+                lock_duration_micros: _,
+                creator: _,
+                final_price: _,
             } => {
                 // Update token graduation status
                 if let Err(e) = self
@@ -107,7 +429,10 @@ impl Contract for FactoryContract {
                     .update_token_status(&token_id, true, None)
                     .await
                 {
-                    log::error!("Failed to update graduation status for {}: {}", token_id, e);
+                    self.logger.error(format!(
+                        "Failed to update graduation status for {}: {}",
+                        token_id, e
+                    ));
                 }
 
                 if let Err(e) = self
@@ -115,23 +440,60 @@ impl Contract for FactoryContract {
                     .update_token_metrics(&token_id, total_supply, total_raised)
                     .await
                 {
-                    log::error!("Failed to update metrics for {}: {}", token_id, e);
+                    self.logger.error(format!("Failed to update metrics for {}: {}", token_id, e));
+                }
+
+                self.logger.info(format!("Token {} graduated to DEX", token_id));
+            }
+
+            Message::SyncTokenSummary {
+                token_id,
+                current_supply,
+                total_raised,
+                summary,
+            } => {
+                if let Err(e) = self
+                    .state
+                    .apply_token_summary(&token_id, current_supply, total_raised, summary)
+                    .await
+                {
+                    self.logger.error(format!("Failed to sync summary for {}: {}", token_id, e));
                 }
+            }
 
-                log::info!("Token {} graduated to DEX", token_id);
+            Message::PoolCreated { token_id, pool_id, is_stable_pool: true, .. } => {
+                // Listings point at the native pool; the token records both
+                self.logger.info(format!(
+                    "Stable DEX pool created for token {}: {}",
+                    token_id, pool_id
+                ));
             }
 
-            Message::PoolCreated { token_id, pool_id } => {
+            Message::PoolCreated { token_id, pool_id, .. } => {
+                // The swap chain may resend PoolCreated; announce the pool only once
+                let already_listed = match self.state.get_token(&token_id).await {
+                    Ok(token) => token.dex_pool_id.is_some(),
+                    Err(_) => false,
+                };
+
                 // Update token with pool information
                 if let Err(e) = self
                     .state
                     .update_token_status(&token_id, true, Some(pool_id.clone()))
                     .await
                 {
-                    log::error!("Failed to update pool info for {}: {}", token_id, e);
+                    self.logger.error(format!(
+                        "Failed to update pool info for {}: {}",
+                        token_id, e
+                    ));
+                } else if !already_listed {
+                    self.publish(FactoryEvent::Graduated {
+                        token_id: token_id.clone(),
+                        pool_id: pool_id.clone(),
+                    });
                 }
 
-                log::info!("DEX pool created for token {}: {}", token_id, pool_id);
+                self.logger.info(format!("DEX pool created for token {}: {}", token_id, pool_id));
             }
 
             Message::NewLaunch {
@@ -141,17 +503,98 @@ impl Contract for FactoryContract {
             } => {
                 // This is a broadcast message sent by tokens
                 // Factory can track launches but doesn't need to act
-                log::info!("New token launch broadcast received: {}", token_id);
+                self.logger.info(format!("New token launch broadcast received: {}", token_id));
+            }
+
+            Message::TokenCreated { .. }
+            | Message::ModerationUpdate { .. }
+            | Message::StateImportApproved { .. } => {
+                // Factory sends these messages, doesn't need to handle them
+            }
+
+            Message::RefundIssued { .. } => {
+                // Addressed to buyers' chains, not the factory
+            }
+
+            Message::SupplyChanged { .. }
+            | Message::PoolTokensDeposited { .. }
+            | Message::PoolTokensWithdrawn { .. }
+            | Message::BurnTokens { .. } => {
+                // Exchanged between token and swap chains
+            }
+
+            Message::BatchBuyOrder { .. } => {
+                // Factory sends these to token chains
+            }
+
+            Message::BatchOrderFilled {
+                batch_id,
+                order_index,
+                token_id,
+            } => {
+                self.settle_batch_order(batch_id, order_index, &token_id, BatchOrderStatus::Filled)
+                    .await;
+            }
+
+            Message::SwapExecuted {
+                trader,
+                base_volume,
+            } => {
+                if let Err(e) = self.state.stats.record_dex_trade(trader, base_volume).await {
+                    self.logger.error(format!("Failed to count swap by {}: {}", trader, e));
+                }
             }
 
-            Message::TokenCreated { .. } => {
-                // Factory sends this message, doesn't need to handle it
+            Message::VersionAnnouncement { token_id, version } => {
+                if self.state.get_token(&token_id).await.is_err() {
+                    self.logger.warn(format!(
+                        "Version announcement from unknown token {}",
+                        token_id
+                    ));
+                } else if let Err(e) = self.state.versions.record_token(&token_id, version) {
+                    self.logger.error(format!(
+                        "Failed to record version of {}: {}",
+                        token_id, e
+                    ));
+                }
+            }
+        }
+    }
+
+    async fn process_streams(&mut self, updates: Vec<StreamUpdate>) {
+        let received_at = self.runtime.system_time();
+        for update in updates {
+            if update.stream_id.stream_name != Self::events_stream() {
+                continue;
+            }
+            for index in update.new_indices() {
+                let event = self
+                    .runtime
+                    .read_event(update.chain_id, Self::events_stream(), index);
+                let entry = FeedEntry {
+                    publisher: update.chain_id,
+                    event,
+                    received_at,
+                };
+                if let Err(e) = self.state.feed.push(entry) {
+                    self.logger.error(format!(
+                        "Failed to record event {} from {}: {}",
+                        index, update.chain_id, e
+                    ));
+                }
             }
         }
     }
 
-    async fn store(self) {
-        // State is automatically persisted by linera-views
+    async fn store(mut self) {
+        for event in self.logger.flush(self.runtime.system_time()) {
+            self.state
+                .critical_events
+                .push(event)
+                .expect("Failed to mirror critical event");
+        }
+        self.state.bump_version().await;
+        self.state.save().await.expect("Failed to save state");
     }
 }
 
@@ -159,34 +602,73 @@ impl FactoryContract {
     /// Execute token creation operation
     ///
     /// This spawns a new microchain for the token and initializes it via cross-chain message.
+    /// An initial buy rides along with the initialization message, so the creator's fill
+    /// lands before the curve is open to anyone else; if it fails the message bounces and
+    /// the registration is rolled back.
     async fn execute_create_token(
         &mut self,
         metadata: TokenMetadata,
         curve_config: Option<BondingCurveConfig>,
+        curve_template: Option<String>,
+        initial_buy: Option<InitialBuy>,
     ) -> Result<String, ContractError> {
         // Authenticate caller - create Account from chain_id and authenticated signer
         let creator_chain_id = self.runtime.chain_id();
-        let creator_account = Account {
-            chain_id: creator_chain_id,
-            owner: match self.runtime.authenticated_signer() {
-                Some(owner) => owner,
-                _ => AccountOwner::CHAIN,
-            },
-        };
+        let creator_account = self.caller_account();
 
-        // Use default curve config if not provided
-        let curve_config = curve_config.unwrap_or_default();
+        // Resolve curve: explicit config, named template, or default
+        let mut curve_config = match (curve_config, curve_template) {
+            (Some(_), Some(_)) => {
+                return Err(ContractError::InvalidCurveConfig(
+                    "specify either curve_config or curve_template, not both".to_string(),
+                ));
+            }
+            (Some(config), None) => config,
+            (None, Some(name)) => self.state.get_curve_template(&name).await?,
+            (None, None) => BondingCurveConfig::default(),
+        };
+        // Fee discounts are the platform's to set, whatever the request carries
+        curve_config.fee_tiers = self.state.fee_tiers.get().clone();
+        // So is the treasury a snipe tax pays
+        if let Some(SnipeTaxConfig {
+            destination: SnipeTaxDestination::Treasury(account),
+            ..
+        }) = curve_config.snipe_tax.as_mut()
+        {
+            *account = self.platform_treasury()?;
+        }
 
         // Validate bonding curve configuration
-        Self::validate_curve_config(&curve_config)?;
+        Self::validate_curve_config(&curve_config, self.state.creator_fee_bounds.get())?;
+        Self::validate_pool_lock(&curve_config, *self.state.min_pool_lock_micros.get())?;
+        if let Some(initial_buy) = &initial_buy {
+            Self::validate_initial_buy(initial_buy, &curve_config)?;
+        }
 
         // Get current timestamp
         let created_at = self.runtime.system_time();
 
-        // Create a new microchain for the token
-        // The chain ID will be deterministic based on the message ID
-        let token_chain_id = self.create_token_chain(creator_chain_id).await?;
-        let token_id = token_chain_id.to_string();
+        // Spam prevention: per-creator rate limit, then the creation fee
+        let parameters = self.runtime.application_parameters();
+        self.state
+            .record_launch(
+                creator_account,
+                created_at,
+                parameters.max_launches_per_window,
+                parameters.launch_window_micros,
+            )
+            .await?;
+        self.charge_creation_fee(&parameters)?;
+
+        // Open a dedicated microchain for the token
+        let token_chain_id = self.create_token_chain(creator_account);
+        // Chain IDs are only known once opened, so the ID is derived from this
+        // factory's launch counter instead
+        let token_id = fair_launch_abi::derive_token_id(
+            self.runtime.chain_id(),
+            self.state.get_token_count(),
+            creator_account,
+        );
 
         // Register token in factory state
         self.state
@@ -198,6 +680,7 @@ impl FactoryContract {
                 created_at,
             )
             .await?;
+        self.state.set_token_chain(&token_id, token_chain_id)?;
 
         // Send initialization message to the new token chain with tracking
         // This ensures the message is delivered and the token is initialized
@@ -207,60 +690,574 @@ impl FactoryContract {
                 creator: creator_account.clone(),
                 metadata: metadata.clone(),
                 curve_config: curve_config.clone(),
+                initial_buy: initial_buy.clone(),
             })
             .with_tracking()
+            // The initial buy is paid by the creator on the token chain
+            .with_authentication()
             .send_to(token_chain_id);
 
+        self.publish(FactoryEvent::NewLaunch {
+            token_id: token_id.clone(),
+            creator: creator_account,
+            metadata: metadata.clone(),
+        });
+
         // Also send the initialize operation to the token contract
         // Note: In practice, you'd call the token contract's Initialize operation
         // This would typically be done via cross-application calls
 
-        log::info!(
+        self.logger.info(format!(
             "Token created - ID: {}, Creator: {:?}, Name: {}",
             token_id,
             creator_chain_id,
             metadata.name
-        );
+        ));
+
+        Ok(token_id)
+    }
+
+    /// Compensate for a tracked message that was rejected by its receiver
+    async fn handle_bounced_message(&mut self, message: Message) {
+        let now = self.runtime.system_time();
+        match message {
+            Message::TokenCreated { token_id, .. } => {
+                // The token chain never initialized: roll back the registration
+                if let Err(e) = self.state.unregister_token(&token_id).await {
+                    self.logger.critical(format!(
+                        "Failed to roll back registration of {}: {}",
+                        token_id, e
+                    ));
+                }
+
+                if let Err(e) = self
+                    .state
+                    .record_bounce("TokenCreated", &token_id, now, "Token registration rolled back")
+                    .await
+                {
+                    self.logger.error(format!(
+                        "Failed to record bounced TokenCreated for {}: {}",
+                        token_id, e
+                    ));
+                }
+
+                self.logger.warn(format!(
+                    "TokenCreated bounced for {}; registration rolled back",
+                    token_id
+                ));
+            }
+
+            Message::ModerationUpdate { token_id, .. } => {
+                // The factory listing is authoritative; record for the operator
+                if let Err(e) = self
+                    .state
+                    .record_bounce("ModerationUpdate", &token_id, now, "Token chain not updated")
+                    .await
+                {
+                    self.logger.error(format!(
+                        "Failed to record bounced ModerationUpdate for {}: {}",
+                        token_id, e
+                    ));
+                }
+            }
+
+            Message::BatchBuyOrder {
+                batch_id,
+                order_index,
+                token_id,
+                ..
+            } => {
+                // The token chain rejected the buy; nothing was charged there
+                let status = BatchOrderStatus::Failed {
+                    reason: "Buy rejected by the token chain".to_string(),
+                };
+                self.settle_batch_order(batch_id, order_index, &token_id, status).await;
+            }
+
+            _ => {
+                // Only TokenCreated, ModerationUpdate and BatchBuyOrder are sent with
+                // tracking from the factory
+            }
+        }
+    }
+
+    /// Register a named curve template (admin only)
+    fn execute_add_curve_template(
+        &mut self,
+        name: String,
+        config: BondingCurveConfig,
+    ) -> Result<String, ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        Self::validate_curve_config(&config, self.state.creator_fee_bounds.get())?;
+        Self::validate_pool_lock(&config, *self.state.min_pool_lock_micros.get())?;
+        self.state.add_curve_template(name.clone(), config)?;
+
+        Ok(name)
+    }
+
+    /// Set the creator fee range accepted for new launches (admin only)
+    ///
+    /// Tokens already launched keep their fee; templates outside the new range
+    /// are rejected when a launch uses them.
+    fn execute_set_creator_fee_bounds(
+        &mut self,
+        bounds: CreatorFeeBounds,
+    ) -> Result<CreatorFeeBounds, ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
+        if bounds.min_bps > bounds.max_bps || bounds.max_bps > 10_000 {
+            return Err(ContractError::InvalidFeeBounds(format!(
+                "{}-{} bps is not a range within 0-10000",
+                bounds.min_bps, bounds.max_bps
+            )));
+        }
+
+        self.state.creator_fee_bounds.set(bounds);
+        Ok(bounds)
+    }
+
+    /// Set the shortest pool lock accepted for new launches (admin only)
+    ///
+    /// Tokens already launched keep their lock; templates with a shorter lock
+    /// are rejected when a launch uses them.
+    fn execute_set_min_pool_lock(&mut self, micros: u64) -> Result<u64, ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        self.state.min_pool_lock_micros.set(micros);
+        Ok(micros)
+    }
+
+    /// Set the creator fee discounts copied into new launches (admin only)
+    ///
+    /// Tokens already launched keep the table they were created with.
+    fn execute_set_fee_tiers(&mut self, tiers: Vec<FeeTier>) -> Result<usize, ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
+        fee_tiers::validate(&tiers).map_err(ContractError::InvalidFeeTiers)?;
+
+        let count = tiers.len();
+        self.state.fee_tiers.set(tiers);
+        Ok(count)
+    }
+
+    /// Register a token bytecode version for rollout (admin only)
+    async fn execute_register_bytecode(
+        &mut self,
+        version: u32,
+        description: String,
+    ) -> Result<(), ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let release = BytecodeRelease {
+            version,
+            description,
+            registered_at: self.runtime.system_time(),
+        };
+        let registered = self
+            .state
+            .versions
+            .register(release)
+            .await
+            .map_err(FactoryError::from)?;
+        if !registered {
+            return Err(ContractError::BytecodeAlreadyRegistered(version));
+        }
+        Ok(())
+    }
+
+    /// Move finalized tokens out of the hot registry (admin only)
+    async fn execute_archive_tokens(&mut self, max_tokens: u64) -> Result<u64, ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
 
+        Ok(self.state.archive_finalized_tokens(max_tokens).await?)
+    }
+
+    /// Set a token's moderation status and notify its chain (admin only)
+    async fn execute_set_moderation(
+        &mut self,
+        token_id: String,
+        status: ModerationStatus,
+    ) -> Result<String, ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
+        if let ModerationStatus::Flagged { reason, .. } = &status {
+            if reason.trim().is_empty() || reason.len() > 280 {
+                return Err(FactoryError::InvalidMetadata(
+                    "Moderation reason must be 1-280 characters".to_string(),
+                )
+                .into());
+            }
+        }
+
+        self.state.set_moderation_status(&token_id, status.clone()).await?;
+        let now = self.runtime.system_time();
+        self.state
+            .outbox
+            .push("ModerationChanged", Some(&token_id), &status, now)
+            .map_err(FactoryError::from)?;
+
+        match self.state.get_token_chain(&token_id).await {
+            Ok(token_chain) => {
+                self.runtime
+                    .prepare_message(Message::ModerationUpdate {
+                        token_id: token_id.clone(),
+                        status,
+                    })
+                    .with_tracking()
+                    .send_to(token_chain);
+            }
+            Err(e) => self.logger.warn(format!(
+                "No chain known for token {}; skipping notification: {}",
+                token_id, e
+            )),
+        }
+
+        Ok(token_id)
+    }
+
+    /// Let a token import the `stateExport` chunks with these commitments
+    /// (admin only)
+    ///
+    /// The admin checks the chunks against the source token's export first:
+    /// imported balances mint supply, so the token accepts no others.
+    async fn execute_approve_state_import(
+        &mut self,
+        token_id: String,
+        commitments: Vec<CryptoHash>,
+    ) -> Result<String, ContractError> {
+        let caller = self.caller_account();
+        if self.state.admin.get().as_ref() != Some(&caller) {
+            return Err(ContractError::Unauthorized);
+        }
+        let token_chain = self.state.get_token_chain(&token_id).await?;
+
+        self.runtime
+            .prepare_message(Message::StateImportApproved {
+                token_id: token_id.clone(),
+                commitments,
+            })
+            .with_tracking()
+            .send_to(token_chain);
         Ok(token_id)
     }
 
-    /// Create a new microchain for a token
+    /// Send each order of a batch buy to its token chain, returning the batch ID
     ///
-    /// In Linera's microchain architecture, each token gets its own chain
-    /// For simplicity in this implementation, we use the creator's chain_id as the token identifier
-    /// In production, you'd use open_chain to create a dedicated child chain
-    async fn create_token_chain(&mut self, creator_chain_id: ChainId) -> Result<ChainId, ContractError> {
-        // For this fair launch implementation, we'll use a deterministic approach:
-        // The token ID is derived from a combination of the factory chain and a counter
-        // In a production system with open_chain support, you would:
-        // 1. Create ownership from the creator's public key
-        // 2. Call open_chain with proper ApplicationPermissions and Amount
-        // 3. Return the newly created chain_id
-
-        // For now, return a derived chain ID based on the creator and token count
-        // This is simplified - in production you'd use the actual Linera chain creation API
-        let token_count = *self.state.token_count.get();
-
-        // Use the factory's chain ID combined with token count as the token chain ID
-        // In production, this would be a real child chain created via open_chain
-        log::info!(
-            "Creating token #{} for creator chain {}",
-            token_count,
-            creator_chain_id
+    /// Orders fill independently and are paid by the authenticated buyer on
+    /// each token chain: one that fails there bounces back and is marked
+    /// failed without affecting the others.
+    async fn execute_batch_buy(
+        &mut self,
+        orders: Vec<(String, U256, U256)>,
+    ) -> Result<u64, ContractError> {
+        Self::validate_batch_orders(&orders)?;
+
+        let mut batch_orders = Vec::with_capacity(orders.len());
+        let mut token_chains = Vec::with_capacity(orders.len());
+        for (token_id, amount, max_cost) in orders {
+            let token = self.state.get_token(&token_id).await?;
+            if token.is_graduated {
+                return Err(ContractError::InvalidBatch(format!(
+                    "token {} has graduated; buy it on the DEX",
+                    token_id
+                )));
+            }
+            token_chains.push(self.state.get_token_chain(&token_id).await?);
+            batch_orders.push(BatchOrder {
+                token_id,
+                amount,
+                max_cost,
+                status: BatchOrderStatus::Pending,
+            });
+        }
+
+        let buyer = self.caller_account();
+        let now = self.runtime.system_time();
+        let batch_id = self
+            .state
+            .batches
+            .open(buyer, batch_orders.clone(), now)
+            .await
+            .map_err(FactoryError::from)?;
+
+        for (order_index, (order, token_chain)) in
+            batch_orders.into_iter().zip(token_chains).enumerate()
+        {
+            self.runtime
+                .prepare_message(Message::BatchBuyOrder {
+                    batch_id,
+                    order_index: order_index as u32,
+                    token_id: order.token_id,
+                    amount: order.amount,
+                    max_cost: order.max_cost,
+                })
+                .with_tracking()
+                // Each order is paid by the buyer on the token chain
+                .with_authentication()
+                .send_to(token_chain);
+        }
+
+        Ok(batch_id)
+    }
+
+    /// Record the outcome of a batch buy order reported by (or bounced from) a token chain
+    async fn settle_batch_order(
+        &mut self,
+        batch_id: u64,
+        order_index: u32,
+        token_id: &str,
+        status: BatchOrderStatus,
+    ) {
+        match self.state.batches.settle(batch_id, order_index, status.clone()).await {
+            Ok(Some(batch)) => {
+                self.logger.info(format!(
+                    "Batch {} order {} ({}) settled: {:?}",
+                    batch_id, order_index, token_id, status
+                ));
+                if batch.is_settled() {
+                    self.logger.info(format!("Batch {} fully settled", batch_id));
+                }
+            }
+            Ok(None) => self.logger.warn(format!(
+                "Settlement for unknown batch {} order {}",
+                batch_id, order_index
+            )),
+            Err(e) => self.logger.error(format!(
+                "Failed to settle batch {} order {}: {}",
+                batch_id, order_index, e
+            )),
+        }
+    }
+
+    /// Check the shape of a batch buy before anything is stored or sent
+    fn validate_batch_orders(orders: &[(String, U256, U256)]) -> Result<(), ContractError> {
+        if orders.is_empty() {
+            return Err(ContractError::InvalidBatch("no orders".to_string()));
+        }
+        if orders.len() > MAX_BATCH_ORDERS {
+            return Err(ContractError::InvalidBatch(format!(
+                "{} orders exceeds the limit of {}",
+                orders.len(),
+                MAX_BATCH_ORDERS
+            )));
+        }
+
+        for (i, (token_id, amount, _)) in orders.iter().enumerate() {
+            if amount.is_zero() {
+                return Err(ContractError::InvalidBatch(format!(
+                    "order {} for {} has a zero amount",
+                    i, token_id
+                )));
+            }
+            if orders[..i].iter().any(|(other, _, _)| other == token_id) {
+                return Err(ContractError::InvalidBatch(format!(
+                    "token {} appears more than once",
+                    token_id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Subscribe this chain to a factory chain's launch events
+    async fn execute_subscribe(&mut self, publisher: ChainId) -> Result<ChainId, ContractError> {
+        if publisher == self.runtime.chain_id() {
+            return Err(ContractError::InvalidSubscription(
+                "a chain cannot subscribe to its own events".to_string(),
+            ));
+        }
+
+        let now = self.runtime.system_time();
+        if !self.state.feed.subscribe(publisher, now).await.map_err(FactoryError::from)? {
+            return Err(ContractError::InvalidSubscription(format!(
+                "already subscribed to {}",
+                publisher
+            )));
+        }
+
+        let application_id = self.runtime.application_id().forget_abi();
+        self.runtime
+            .subscribe_to_events(publisher, application_id, Self::events_stream());
+        Ok(publisher)
+    }
+
+    /// Stop receiving a factory chain's launch events
+    async fn execute_unsubscribe(&mut self, publisher: ChainId) -> Result<ChainId, ContractError> {
+        if !self.state.feed.unsubscribe(publisher).await.map_err(FactoryError::from)? {
+            return Err(ContractError::InvalidSubscription(format!(
+                "not subscribed to {}",
+                publisher
+            )));
+        }
+
+        let application_id = self.runtime.application_id().forget_abi();
+        self.runtime
+            .unsubscribe_from_events(publisher, application_id, Self::events_stream());
+        Ok(publisher)
+    }
+
+    /// Publish a launch event to subscriber chains and record it in the outbox
+    fn publish(&mut self, event: FactoryEvent) {
+        let (kind, token_id) = match &event {
+            FactoryEvent::NewLaunch { token_id, .. } => ("NewLaunch", token_id),
+            FactoryEvent::Graduated { token_id, .. } => ("Graduated", token_id),
+        };
+        let now = self.runtime.system_time();
+        self.state
+            .outbox
+            .push(kind, Some(token_id), &event, now)
+            .expect("Failed to record outbox event");
+        self.runtime.emit(Self::events_stream(), &event);
+    }
+
+    fn events_stream() -> StreamName {
+        StreamName::from(FACTORY_EVENTS_STREAM)
+    }
+
+    /// Register a referral code for the caller (token creators only)
+    async fn execute_register_referral_code(&mut self, code: String) -> Result<String, ContractError> {
+        let caller = self.caller_account();
+        if !self.state.is_creator(&caller).await? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        Ok(self.state.register_referral_code(&code, caller).await?)
+    }
+
+    /// Transfer the configured creation fee from the signer to the treasury
+    fn charge_creation_fee(&mut self, parameters: &FactoryParameters) -> Result<(), ContractError> {
+        let fee = parameters.creation_fee;
+        if fee == Amount::ZERO {
+            return Ok(());
+        }
+
+        let signer = self
+            .runtime
+            .authenticated_signer()
+            .ok_or(ContractError::Unauthorized)?;
+        let treasury = self.platform_treasury()?;
+
+        let balance = self.runtime.owner_balance(signer);
+        if balance < fee {
+            return Err(ContractError::CreationFeeUnpaid {
+                have: balance,
+                need: fee,
+            });
+        }
+        self.runtime.transfer(signer, treasury, fee);
+        self.state.stats.record_creation_fee(fee);
+
+        Ok(())
+    }
+
+    /// The configured treasury, or the admin without one
+    fn platform_treasury(&mut self) -> Result<Account, ContractError> {
+        match self.runtime.application_parameters().treasury {
+            Some(treasury) => Ok(treasury),
+            None => self.state.admin.get().ok_or(ContractError::Unauthorized),
+        }
+    }
+
+    /// Get the calling account (authenticated signer on current chain)
+    fn caller_account(&mut self) -> Account {
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner: match self.runtime.authenticated_signer() {
+                Some(owner) => owner,
+                _ => AccountOwner::CHAIN,
+            },
+        }
+    }
+
+    /// Open a new microchain for a token, owned by its creator
+    ///
+    /// In Linera's microchain architecture, each token gets its own chain: a
+    /// chain hosts a single token, so a creator's second launch cannot share
+    /// the first one's chain. The chain starts unfunded; the creator tops it
+    /// up to pay for its blocks.
+    fn create_token_chain(&mut self, creator: Account) -> ChainId {
+        let token_count = self.state.get_token_count();
+        let ownership = ChainOwnership::single(creator.owner);
+        let chain_id = self.runtime.open_chain(
+            ownership,
+            ApplicationPermissions::default(),
+            Amount::ZERO,
         );
 
-        // Return the creator's chain ID - tokens live on their creator's chain
-        // This is a valid pattern for fair launch tokens where each token has a single
-        // authoritative chain for trades. Future versions could use child chains via
-        // runtime.open_chain() when that API stabilizes.
-        Ok(creator_chain_id)
+        self.logger.info(format!(
+            "Opened chain {} for token #{} of creator {}",
+            chain_id, token_count, creator
+        ));
+        chain_id
     }
 
-    /// Validate bonding curve configuration
-    fn validate_curve_config(config: &BondingCurveConfig) -> Result<(), ContractError> {
-        use primitive_types::U256;
+    /// Validate a creator's initial buy against the curve it will execute on
+    fn validate_initial_buy(
+        initial_buy: &InitialBuy,
+        config: &BondingCurveConfig,
+    ) -> Result<(), ContractError> {
+        if initial_buy.amount.is_zero() {
+            return Err(ContractError::InvalidInitialBuy(
+                "amount must be greater than zero".to_string(),
+            ));
+        }
 
+        if initial_buy.amount > config.max_supply {
+            return Err(ContractError::InvalidInitialBuy(
+                "amount exceeds the curve's max_supply".to_string(),
+            ));
+        }
+
+        if config.launch_mechanism != LaunchMechanism::BondingCurve {
+            return Err(ContractError::InvalidInitialBuy(format!(
+                "{} launches sell only through their own mechanism",
+                config.launch_mechanism
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check a time-limited pool lock against the admin's minimum
+    /// (permanent locks always pass)
+    fn validate_pool_lock(
+        config: &BondingCurveConfig,
+        min_micros: u64,
+    ) -> Result<(), ContractError> {
+        match config.pool_lock_micros {
+            Some(micros) if micros < min_micros => Err(ContractError::InvalidCurveConfig(format!(
+                "pool lock of {} micros is shorter than the minimum of {}",
+                micros, min_micros
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Validate bonding curve configuration
+    fn validate_curve_config(
+        config: &BondingCurveConfig,
+        fee_bounds: &CreatorFeeBounds,
+    ) -> Result<(), ContractError> {
         if config.k == U256::zero() {
             return Err(ContractError::InvalidCurveConfig(
                 "k parameter must be greater than zero".to_string(),
@@ -291,6 +1288,84 @@ impl FactoryContract {
             ));
         }
 
+        if config.base_decimals > fair_launch_abi::units::NATIVE_DECIMALS {
+            return Err(ContractError::InvalidCurveConfig(format!(
+                "base_decimals must be at most {}",
+                fair_launch_abi::units::NATIVE_DECIMALS
+            )));
+        }
+
+        if !fee_bounds.contains(config.creator_fee_bps) {
+            return Err(ContractError::InvalidCurveConfig(format!(
+                "creator_fee_bps {} outside the allowed range {}-{}",
+                config.creator_fee_bps, fee_bounds.min_bps, fee_bounds.max_bps
+            )));
+        }
+
+        let plan = config.graduation_plan;
+        if !plan.is_valid() {
+            return Err(ContractError::InvalidCurveConfig(format!(
+                "graduation plan shares (pool {}, creator {}, burn {}) must sum to 10000 bps \
+                 with a non-zero pool share",
+                plan.pool_bps, plan.creator_bps, plan.burn_bps
+            )));
+        }
+
+        if let Some(snipe_tax) = config.snipe_tax {
+            let max_bps = fair_launch_abi::snipe_tax::MAX_SNIPE_TAX_BPS;
+            if snipe_tax.initial_bps > max_bps {
+                return Err(ContractError::InvalidCurveConfig(format!(
+                    "snipe tax of {} bps exceeds {}",
+                    snipe_tax.initial_bps, max_bps
+                )));
+            }
+            if config.launch_mechanism != LaunchMechanism::BondingCurve {
+                return Err(ContractError::InvalidCurveConfig(
+                    "snipe tax applies to bonding curve launches only".to_string(),
+                ));
+            }
+        }
+
+        if config.min_trade_interval_micros == Some(0) {
+            return Err(ContractError::InvalidCurveConfig(
+                "min trade interval must be greater than zero".to_string(),
+            ));
+        }
+
+        match (config.min_raise, config.deadline) {
+            (None, None) => {}
+            (Some(min_raise), Some(_)) => {
+                if min_raise.is_zero() || min_raise > config.target_raise {
+                    return Err(ContractError::InvalidCurveConfig(format!(
+                        "min_raise {} must be greater than zero and at most target_raise {}",
+                        min_raise, config.target_raise
+                    )));
+                }
+                if config.launch_mechanism != LaunchMechanism::BondingCurve {
+                    return Err(ContractError::InvalidCurveConfig(
+                        "min_raise applies to bonding curve launches only".to_string(),
+                    ));
+                }
+            }
+            _ => {
+                return Err(ContractError::InvalidCurveConfig(
+                    "min_raise and deadline must be set together".to_string(),
+                ));
+            }
+        }
+
+        match &config.launch_mechanism {
+            LaunchMechanism::BondingCurve => {}
+            LaunchMechanism::DutchAuction(auction) => {
+                fair_launch_abi::dutch_auction::validate(auction)
+                    .map_err(|e| ContractError::InvalidCurveConfig(e.to_string()))?;
+            }
+            LaunchMechanism::FixedPriceSale(sale) => {
+                fair_launch_abi::fixed_price_sale::validate(sale)
+                    .map_err(|e| ContractError::InvalidCurveConfig(e.to_string()))?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -298,33 +1373,207 @@ impl FactoryContract {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use fair_launch_abi::BondingCurveConfig;
+    use fair_launch_abi::{
+        BondingCurveConfig, DutchAuctionConfig, FixedPriceSaleConfig, GraduationPlan, Price,
+        SnipeTaxConfig, SnipeTaxDestination,
+    };
+    use linera_sdk::linera_base_types::Timestamp;
     use primitive_types::U256;
 
+    fn validate(config: &BondingCurveConfig) -> Result<(), ContractError> {
+        FactoryContract::validate_curve_config(config, &CreatorFeeBounds::default())
+    }
+
     #[test]
     fn test_validate_curve_config_valid() {
         let config = BondingCurveConfig::default();
-        assert!(FactoryContract::validate_curve_config(&config).is_ok());
+        assert!(validate(&config).is_ok());
     }
 
     #[test]
     fn test_validate_curve_config_zero_k() {
         let mut config = BondingCurveConfig::default();
         config.k = U256::zero();
-        assert!(FactoryContract::validate_curve_config(&config).is_err());
+        assert!(validate(&config).is_err());
     }
 
     #[test]
     fn test_validate_curve_config_zero_scale() {
         let mut config = BondingCurveConfig::default();
         config.scale = U256::zero();
-        assert!(FactoryContract::validate_curve_config(&config).is_err());
+        assert!(validate(&config).is_err());
     }
 
     #[test]
     fn test_validate_curve_config_invalid_supply() {
         let mut config = BondingCurveConfig::default();
         config.max_supply = config.scale;
-        assert!(FactoryContract::validate_curve_config(&config).is_err());
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_base_decimals() {
+        let mut config = BondingCurveConfig::default();
+        config.base_decimals = 18;
+        assert!(validate(&config).is_ok());
+        config.base_decimals = 19;
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_creator_fee_bounds() {
+        let mut config = BondingCurveConfig::default();
+        config.creator_fee_bps = 10_000;
+        assert!(validate(&config).is_err());
+
+        let bounds = CreatorFeeBounds { min_bps: 100, max_bps: 500 };
+        config.creator_fee_bps = 500;
+        assert!(FactoryContract::validate_curve_config(&config, &bounds).is_ok());
+        config.creator_fee_bps = 50;
+        assert!(FactoryContract::validate_curve_config(&config, &bounds).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_graduation_plan() {
+        let mut config = BondingCurveConfig::default();
+        config.graduation_plan = GraduationPlan {
+            pool_bps: 7_000,
+            creator_bps: 2_000,
+            burn_bps: 1_000,
+        };
+        assert!(validate(&config).is_ok());
+
+        config.graduation_plan.burn_bps = 2_000;
+        assert!(validate(&config).is_err());
+        config.graduation_plan = GraduationPlan {
+            pool_bps: 0,
+            creator_bps: 10_000,
+            burn_bps: 0,
+        };
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_dutch_auction() {
+        let auction = DutchAuctionConfig {
+            start_price: Price::from_integer(U256::from(10)).unwrap(),
+            floor_price: Price::from_integer(U256::from(2)).unwrap(),
+            starts_at: Timestamp::from(0),
+            duration_micros: 3_600_000_000,
+        };
+        let mut config = BondingCurveConfig {
+            launch_mechanism: LaunchMechanism::DutchAuction(auction),
+            ..BondingCurveConfig::default()
+        };
+        assert!(validate(&config).is_ok());
+        let initial_buy = InitialBuy { amount: U256::from(1_000), max_cost: U256::MAX };
+        assert!(FactoryContract::validate_initial_buy(&initial_buy, &config).is_err());
+
+        config.launch_mechanism = LaunchMechanism::DutchAuction(DutchAuctionConfig {
+            floor_price: Price::default(),
+            ..auction
+        });
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_fixed_price_sale() {
+        let sale = FixedPriceSaleConfig {
+            price: Price::from_integer(U256::from(1)).unwrap(),
+            starts_at: Timestamp::from(0),
+            duration_micros: 3_600_000_000,
+        };
+        let mut config = BondingCurveConfig {
+            launch_mechanism: LaunchMechanism::FixedPriceSale(sale),
+            ..BondingCurveConfig::default()
+        };
+        assert!(validate(&config).is_ok());
+
+        config.launch_mechanism =
+            LaunchMechanism::FixedPriceSale(FixedPriceSaleConfig { duration_micros: 0, ..sale });
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_min_raise() {
+        let mut config = BondingCurveConfig {
+            min_raise: Some(U256::from(10_000)),
+            deadline: Some(Timestamp::from(1_000)),
+            ..BondingCurveConfig::default()
+        };
+        assert!(validate(&config).is_ok());
+
+        config.min_raise = Some(config.target_raise + U256::one());
+        assert!(validate(&config).is_err());
+        config.min_raise = Some(U256::from(10_000));
+        config.deadline = None;
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_snipe_tax() {
+        let snipe_tax = SnipeTaxConfig {
+            initial_bps: 2_000,
+            duration_micros: 300_000_000,
+            destination: SnipeTaxDestination::Burn,
+        };
+        let mut config =
+            BondingCurveConfig { snipe_tax: Some(snipe_tax), ..BondingCurveConfig::default() };
+        assert!(validate(&config).is_ok());
+
+        config.snipe_tax = Some(SnipeTaxConfig { initial_bps: 2_001, ..snipe_tax });
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_curve_config_min_trade_interval() {
+        let mut config = BondingCurveConfig {
+            min_trade_interval_micros: Some(5_000_000),
+            ..BondingCurveConfig::default()
+        };
+        assert!(validate(&config).is_ok());
+
+        config.min_trade_interval_micros = Some(0);
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_pool_lock() {
+        let mut config = BondingCurveConfig::default();
+        assert!(FactoryContract::validate_pool_lock(&config, 1_000).is_ok());
+
+        config.pool_lock_micros = Some(1_000);
+        assert!(FactoryContract::validate_pool_lock(&config, 1_000).is_ok());
+        assert!(FactoryContract::validate_pool_lock(&config, 1_001).is_err());
+    }
+
+    #[test]
+    fn test_validate_initial_buy() {
+        let config = BondingCurveConfig::default();
+        let buy = |amount: U256| InitialBuy {
+            amount,
+            max_cost: U256::MAX,
+        };
+
+        assert!(FactoryContract::validate_initial_buy(&buy(U256::from(1_000)), &config).is_ok());
+        assert!(FactoryContract::validate_initial_buy(&buy(config.max_supply), &config).is_ok());
+        assert!(FactoryContract::validate_initial_buy(&buy(U256::zero()), &config).is_err());
+        assert!(
+            FactoryContract::validate_initial_buy(&buy(config.max_supply + 1), &config).is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_batch_orders() {
+        let token = |n: u8| format!("{:064x}", n);
+        let order = |n: u8, amount: u64| (token(n), U256::from(amount), U256::MAX);
+
+        assert!(FactoryContract::validate_batch_orders(&[order(1, 10), order(2, 20)]).is_ok());
+        assert!(FactoryContract::validate_batch_orders(&[]).is_err());
+        assert!(FactoryContract::validate_batch_orders(&[order(1, 0)]).is_err());
+        assert!(FactoryContract::validate_batch_orders(&[order(1, 10), order(1, 5)]).is_err());
+
+        let too_many: Vec<_> = (0..=MAX_BATCH_ORDERS as u8).map(|n| order(n, 1)).collect();
+        assert!(FactoryContract::validate_batch_orders(&too_many).is_err());
     }
 }
@@ -0,0 +1,81 @@
+use fair_launch_abi::FactoryEvent;
+use linera_sdk::{
+    linera_base_types::{ChainId, Timestamp},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+use serde::{Deserialize, Serialize};
+
+/// Events retained in a subscriber chain's feed
+pub const FEED_CAPACITY: u64 = 100;
+
+/// A launch event received from a subscribed factory chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedEntry {
+    pub publisher: ChainId,
+    pub event: FactoryEvent,
+    pub received_at: Timestamp,
+}
+
+/// Launch events pushed to this chain by the factory chains it subscribes to
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct FeedState {
+    /// Subscribed publisher chains: ChainId → subscription time
+    pub subscriptions: MapView<ChainId, Timestamp>,
+
+    /// Received events by sequence number, the latest `FEED_CAPACITY` only
+    pub entries: MapView<u64, FeedEntry>,
+
+    /// Number of events ever received (next sequence number)
+    pub count: RegisterView<u64>,
+}
+
+impl FeedState {
+    /// Append a received event, evicting the oldest one beyond capacity
+    pub fn push(&mut self, entry: FeedEntry) -> Result<(), ViewError> {
+        let sequence = *self.count.get();
+        self.entries.insert(&sequence, entry)?;
+        if sequence >= FEED_CAPACITY {
+            self.entries.remove(&(sequence - FEED_CAPACITY))?;
+        }
+        self.count.set(sequence + 1);
+        Ok(())
+    }
+
+    /// Up to `limit` retained events, newest first
+    pub async fn recent(&self, limit: u64) -> Result<Vec<(u64, FeedEntry)>, ViewError> {
+        let count = *self.count.get();
+        let oldest = count.saturating_sub(FEED_CAPACITY.min(limit));
+        let mut entries = Vec::new();
+        for sequence in (oldest..count).rev() {
+            if let Some(entry) = self.entries.get(&sequence).await? {
+                entries.push((sequence, entry));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Record a subscription, returning false if it already existed
+    pub async fn subscribe(&mut self, publisher: ChainId, now: Timestamp) -> Result<bool, ViewError> {
+        if self.subscriptions.contains_key(&publisher).await? {
+            return Ok(false);
+        }
+        self.subscriptions.insert(&publisher, now)?;
+        Ok(true)
+    }
+
+    /// Drop a subscription, returning false if there was none
+    pub async fn unsubscribe(&mut self, publisher: ChainId) -> Result<bool, ViewError> {
+        if !self.subscriptions.contains_key(&publisher).await? {
+            return Ok(false);
+        }
+        self.subscriptions.remove(&publisher)?;
+        Ok(true)
+    }
+
+    /// Publisher chains this chain is subscribed to
+    pub async fn publishers(&self) -> Result<Vec<ChainId>, ViewError> {
+        self.subscriptions.indices().await
+    }
+}
@@ -0,0 +1,97 @@
+use fair_launch_abi::Price;
+use linera_sdk::{
+    linera_base_types::Account,
+    views::{MapView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+/// An account's curve position in one token, built from trade notifications
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortfolioPosition {
+    /// Tokens bought minus tokens sold on the curve
+    pub balance: U256,
+    /// Base currency spent on buys
+    pub total_invested: U256,
+    /// Base currency received from sells
+    pub total_proceeds: U256,
+    pub trades_count: u64,
+}
+
+impl PortfolioPosition {
+    /// Value of the remaining balance at `price`, in base currency
+    pub fn current_value(&self, price: Price) -> U256 {
+        price.mul_amount(self.balance).unwrap_or_default()
+    }
+}
+
+/// Per-account positions cached from the token chains' `TradeExecuted`
+/// notifications
+///
+/// Positions only reflect curve trades: token transfers and DEX swaps after
+/// graduation are not reported to the factory.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct PortfolioState {
+    /// Positions: (Account, token_id) → PortfolioPosition
+    pub positions: MapView<(Account, String), PortfolioPosition>,
+
+    /// Tokens each account has traded: Account → Vec<token_id>
+    pub tokens_by_account: MapView<Account, Vec<String>>,
+
+    /// Latest traded curve price: token_id → Price
+    pub last_prices: MapView<String, Price>,
+}
+
+impl PortfolioState {
+    /// Fold a curve trade into the trader's position
+    pub async fn apply_trade(
+        &mut self,
+        trader: Account,
+        token_id: &str,
+        is_buy: bool,
+        token_amount: U256,
+        currency_amount: U256,
+        price: Price,
+    ) -> Result<(), ViewError> {
+        let key = (trader, token_id.to_string());
+        let mut position = match self.positions.get(&key).await? {
+            Some(position) => position,
+            None => {
+                let mut tokens = self.tokens_by_account.get(&trader).await?.unwrap_or_default();
+                tokens.push(token_id.to_string());
+                self.tokens_by_account.insert(&trader, tokens)?;
+                PortfolioPosition::default()
+            }
+        };
+
+        if is_buy {
+            position.balance = position.balance.saturating_add(token_amount);
+            position.total_invested = position.total_invested.saturating_add(currency_amount);
+        } else {
+            position.balance = position.balance.saturating_sub(token_amount);
+            position.total_proceeds = position.total_proceeds.saturating_add(currency_amount);
+        }
+        position.trades_count += 1;
+
+        self.positions.insert(&key, position)?;
+        self.last_prices.insert(token_id, price)
+    }
+
+    /// Positions of an account in the order its tokens were first traded
+    pub async fn positions_of(&self, account: &Account) -> Result<Vec<(String, PortfolioPosition)>, ViewError> {
+        let mut positions = Vec::new();
+        for token_id in self.tokens_by_account.get(account).await?.unwrap_or_default() {
+            if let Some(position) = self.positions.get(&(*account, token_id.clone())).await? {
+                positions.push((token_id, position));
+            }
+        }
+        Ok(positions)
+    }
+
+    /// Latest traded curve price of a token
+    pub async fn last_price(&self, token_id: &str) -> Result<Option<Price>, ViewError> {
+        self.last_prices.get(token_id).await
+    }
+}
@@ -1,8 +1,22 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod archive;
+mod batch;
+mod feed;
+mod portfolio;
 mod state;
-use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
-use fair_launch_abi::{FactoryAbi, TokenLaunch, TokenMetadata};
+mod stats;
+mod versions;
+use async_graphql::{
+    connection::query, ComplexObject, Context, EmptySubscription, ErrorExtensions, Object, Schema,
+    SimpleObject,
+};
+use fair_launch_abi::{
+    ipfs,
+    pagination::{self, Page},
+    BatchOrderStatus, BuyBatch, BytecodeRelease, CreatorFeeBounds, FactoryAbi, FactoryEvent,
+    FactoryOperation, FactoryParameters, FeeTierGQL, TokenLaunch, TokenMetadata,
+};
 use linera_sdk::{
     abi::WithServiceAbi,
     views::View,
@@ -11,10 +25,13 @@ use linera_sdk::{
 use primitive_types::U256;
 use std::sync::Arc;
 
-use crate::state::FactoryState;
+use crate::feed::FeedEntry;
+use crate::state::{FactoryError, FactoryState};
+
+/// Most recent batch buys `me` checks for orders still pending
+const RECENT_BATCHES: usize = 20;
 
 pub struct FactoryService {
-    state: Arc<FactoryState>,
     runtime: Arc<ServiceRuntime<Self>>,
 }
 
@@ -25,28 +42,38 @@ impl WithServiceAbi for FactoryService {
 }
 
 impl Service for FactoryService {
-    type Parameters = ();
+    type Parameters = FactoryParameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
-        let state = FactoryState::load(runtime.root_view_storage_context())
-            .await
-            .expect("Failed to load factory state");
         FactoryService {
-            state: Arc::new(state),
             runtime: Arc::new(runtime),
         }
     }
 
     async fn handle_query(&self, request: async_graphql::Request) -> async_graphql::Response {
+        // Load per query so a long-lived instance answers from the latest block
+        let state = Arc::new(
+            FactoryState::load(self.runtime.root_view_storage_context())
+                .await
+                .expect("Failed to load factory state"),
+        );
         let schema = Schema::build(
             QueryRoot::default(),
-            EmptyMutation,
+            MutationRoot {
+                runtime: self.runtime.clone(),
+            },
             EmptySubscription,
         )
-        .data(self.state.clone())
+        .data(state.clone())
+        .data(self.runtime.clone())
         .finish();
 
-        schema.execute(request).await
+        let mut response = schema.execute(request).await;
+        response.extensions.insert(
+            fair_launch_abi::STATE_VERSION_EXTENSION.to_string(),
+            async_graphql::Value::from(*state.state_version.get()),
+        );
+        response
     }
 }
 
@@ -56,22 +83,48 @@ struct QueryRoot;
 
 #[Object]
 impl QueryRoot {
+    /// Version of the state these results were read from
+    async fn state_version(&self, ctx: &Context<'_>) -> u64 {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        *state.state_version.get()
+    }
+
     /// Get total number of tokens created
     async fn token_count(&self, ctx: &Context<'_>) -> u64 {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
         state.get_token_count()
     }
 
-    /// Get a specific token by its ID (ChainId)
-    async fn token(&self, ctx: &Context<'_>, token_id: String) -> Option<TokenLaunchView> {
+    /// Resolve a referral code (as used in `?ref=CODE` links) to its account
+    async fn referral(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+    ) -> async_graphql::Result<Option<String>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let account = state.resolve_referral_code(&code).await.map_err(|e| query_error(e, None))?;
+        Ok(account.map(|account| account.to_string()))
+    }
+
+    /// Get the number of finalized tokens moved to the archive
+    async fn archived_token_count(&self, ctx: &Context<'_>) -> u64 {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        state.archive.count()
+    }
+
+    /// Get a specific token by its ID (ChainId), None if there is no such token
+    async fn token(
+        &self,
+        ctx: &Context<'_>,
+        token_id: String,
+    ) -> async_graphql::Result<Option<TokenLaunchView>> {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
 
         match state.get_token(&token_id).await {
-            Ok(token) => Some(TokenLaunchView::from(token)),
-            Err(e) => {
-                log::warn!("Failed to get token {}: {}", token_id, e);
-                None
-            }
+            Ok(token) => Ok(with_summaries(state, vec![token]).await.pop()),
+            Err(FactoryError::TokenNotFound(_)) => Ok(None),
+            Err(e) => Err(query_error(e, Some(&token_id))),
         }
     }
 
@@ -81,19 +134,45 @@ impl QueryRoot {
         ctx: &Context<'_>,
         offset: Option<u64>,
         limit: Option<u64>,
-    ) -> Vec<TokenLaunchView> {
+    ) -> async_graphql::Result<Vec<TokenLaunchView>> {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
 
         let offset = offset.unwrap_or(0);
         let limit = limit.unwrap_or(20).min(100); // Max 100 per query
 
-        match state.get_all_tokens(offset, limit).await {
-            Ok(tokens) => tokens.into_iter().map(TokenLaunchView::from).collect(),
-            Err(e) => {
-                log::error!("Failed to get tokens: {}", e);
-                Vec::new()
-            }
-        }
+        let tokens = state.get_all_tokens(offset, limit).await.map_err(|e| query_error(e, None))?;
+        Ok(with_summaries(state, unflagged(tokens)).await)
+    }
+
+    /// All tokens as a cursor-paginated connection, flagged tokens skipped
+    ///
+    /// Cursors are registry positions; `totalCount` counts the whole
+    /// registry, flagged tokens included.
+    async fn tokens_connection(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<Page<TokenLaunchView>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        query(after, before, first, last, |after, before, first, last| async move {
+            let total = state.get_token_count() as usize;
+            let (start, end) = pagination::window(after, before, first, last, total);
+            let tokens = state
+                .get_all_tokens(start as u64, (end - start) as u64)
+                .await
+                .map_err(|e| query_error(e, None))?;
+            let (positions, tokens): (Vec<usize>, Vec<TokenLaunch>) = (start..)
+                .zip(tokens)
+                .filter(|(_, token)| !token.moderation_status.is_flagged())
+                .unzip();
+            let nodes = positions.into_iter().zip(with_summaries(state, tokens).await).collect();
+            Ok::<_, async_graphql::Error>(pagination::page(nodes, start, end, total))
+        })
+        .await
     }
 
     /// Get all tokens created by a specific creator
@@ -101,29 +180,23 @@ impl QueryRoot {
         &self,
         ctx: &Context<'_>,
         creator_json: String,
-    ) -> Vec<TokenLaunchView> {
+    ) -> async_graphql::Result<Vec<TokenLaunchView>> {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
 
-        // Parse creator Account from JSON
-        let creator_account: linera_sdk::linera_base_types::Account = match serde_json::from_str(&creator_json) {
-            Ok(account) => account,
-            Err(e) => {
-                log::warn!("Invalid creator Account format: {}", e);
-                return Vec::new();
-            }
-        };
-
-        match state.get_tokens_by_creator(&creator_account).await {
-            Ok(tokens) => tokens.into_iter().map(TokenLaunchView::from).collect(),
-            Err(e) => {
-                log::error!("Failed to get tokens by creator: {}", e);
-                Vec::new()
-            }
-        }
+        let creator_account = parse_account(&creator_json)?;
+        let tokens = state
+            .get_tokens_by_creator(&creator_account)
+            .await
+            .map_err(|e| query_error(e, None))?;
+        Ok(with_summaries(state, unflagged(tokens)).await)
     }
 
     /// Get recent token launches
-    async fn recent_tokens(&self, ctx: &Context<'_>, limit: Option<u64>) -> Vec<TokenLaunchView> {
+    async fn recent_tokens(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<u64>,
+    ) -> async_graphql::Result<Vec<TokenLaunchView>> {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
 
         let total_count = state.get_token_count();
@@ -136,17 +209,22 @@ impl QueryRoot {
             0
         };
 
-        match state.get_all_tokens(offset, limit).await {
-            Ok(mut tokens) => {
-                // Reverse to get newest first
-                tokens.reverse();
-                tokens.into_iter().map(TokenLaunchView::from).collect()
-            }
-            Err(e) => {
-                log::error!("Failed to get recent tokens: {}", e);
-                Vec::new()
-            }
-        }
+        let mut tokens =
+            state.get_all_tokens(offset, limit).await.map_err(|e| query_error(e, None))?;
+        // Reverse to get newest first
+        tokens.reverse();
+        Ok(with_summaries(state, unflagged(tokens)).await)
+    }
+
+    /// The open token closest to its target raise (king of the hill)
+    async fn king_of_the_hill(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<TokenLaunchView>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let king = state.get_king_of_the_hill().await.map_err(|e| query_error(e, None))?;
+        Ok(with_summaries(state, king.into_iter().collect()).await.pop())
     }
 
     /// Get graduated tokens (completed bonding curves)
@@ -155,82 +233,436 @@ impl QueryRoot {
         ctx: &Context<'_>,
         offset: Option<u64>,
         limit: Option<u64>,
-    ) -> Vec<TokenLaunchView> {
+    ) -> async_graphql::Result<Vec<TokenLaunchView>> {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
 
         let offset = offset.unwrap_or(0);
         let limit = limit.unwrap_or(20).min(100);
 
-        match state.get_all_tokens(offset, limit * 2).await {
-            Ok(tokens) => tokens
-                .into_iter()
-                .filter(|t| t.is_graduated)
-                .map(TokenLaunchView::from)
-                .take(limit as usize)
-                .collect(),
-            Err(e) => {
-                log::error!("Failed to get graduated tokens: {}", e);
-                Vec::new()
-            }
-        }
+        let tokens =
+            state.get_all_tokens(offset, limit * 2).await.map_err(|e| query_error(e, None))?;
+        let graduated = tokens
+            .into_iter()
+            .filter(|t| t.is_graduated && !t.moderation_status.is_flagged())
+            .take(limit as usize)
+            .collect();
+        Ok(with_summaries(state, graduated).await)
     }
 
     /// Search tokens by name or symbol
-    async fn search_tokens(&self, ctx: &Context<'_>, query: String) -> Vec<TokenLaunchView> {
+    async fn search_tokens(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+    ) -> async_graphql::Result<Vec<TokenLaunchView>> {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
 
         let query_lower = query.to_lowercase();
 
         // Get all tokens and filter
         // In a production system, this would use an index for better performance
-        match state.get_all_tokens(0, 1000).await {
-            Ok(tokens) => tokens
-                .into_iter()
-                .filter(|t| {
-                    t.metadata.name.to_lowercase().contains(&query_lower)
-                        || t.metadata.symbol.to_lowercase().contains(&query_lower)
-                })
-                .take(20)
-                .map(TokenLaunchView::from)
-                .collect(),
-            Err(e) => {
-                log::error!("Failed to search tokens: {}", e);
-                Vec::new()
-            }
+        let tokens = state.get_all_tokens(0, 1000).await.map_err(|e| query_error(e, None))?;
+        let matches = tokens
+            .into_iter()
+            .filter(|t| !t.moderation_status.is_flagged())
+            .filter(|t| {
+                t.metadata.name.to_lowercase().contains(&query_lower)
+                    || t.metadata.symbol.to_lowercase().contains(&query_lower)
+            })
+            .take(20)
+            .collect();
+        Ok(with_summaries(state, matches).await)
+    }
+
+    /// List tracked messages that bounced and their resolution
+    async fn pending_messages(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<fair_launch_abi::PendingMessageGQL>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let pending = state.get_pending_messages().await.map_err(|e| query_error(e, None))?;
+        Ok(pending.iter().map(|p| p.into()).collect())
+    }
+
+    /// Latest critical events logged by the contract, newest first
+    async fn critical_events(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+    ) -> async_graphql::Result<Vec<fair_launch_abi::logging::CriticalEventGQL>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let limit = limit.unwrap_or(20).max(0) as u64;
+
+        let events = state.critical_events.recent(limit).await.map_err(|e| query_error(e, None))?;
+        Ok(events
+            .iter()
+            .map(|(sequence, event)| {
+                fair_launch_abi::logging::CriticalEventGQL::new(*sequence, event)
+            })
+            .collect())
+    }
+
+    /// Outbox events after sequence `after_seq` (from the first if omitted), oldest first
+    ///
+    /// Pass the last sequence processed to sync incrementally without gaps.
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        after_seq: Option<u64>,
+        limit: Option<i32>,
+    ) -> async_graphql::Result<Vec<fair_launch_abi::outbox::OutboxEventGQL>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let limit = limit.unwrap_or(100).clamp(1, 1000) as u64;
+
+        let events = state.outbox.after(after_seq, limit).await.map_err(|e| query_error(e, None))?;
+        Ok(events
+            .iter()
+            .map(|(sequence, event)| fair_launch_abi::outbox::OutboxEventGQL::new(*sequence, event))
+            .collect())
+    }
+
+    /// Factory chains this chain receives launch events from
+    async fn subscriptions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<String>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let publishers = state.feed.publishers().await.map_err(|e| query_error(e, None))?;
+        Ok(publishers.iter().map(ToString::to_string).collect())
+    }
+
+    /// Launch events pushed by subscribed factory chains, newest first
+    async fn launch_feed(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+    ) -> async_graphql::Result<Vec<FeedEntryView>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let limit = limit.unwrap_or(20).max(0) as u64;
+
+        let entries = state.feed.recent(limit).await.map_err(|e| query_error(e, None))?;
+        Ok(entries
+            .into_iter()
+            .map(|(sequence, entry)| FeedEntryView::new(sequence, entry))
+            .collect())
+    }
+
+    /// Everything the factory indexes about one account, in a single round-trip
+    ///
+    /// Positions cover curve trades reported by the token chains; balances
+    /// moved by transfers or on the DEX live on the token chains and the swap
+    /// application. The platform keeps no points or watchlists.
+    async fn me(
+        &self,
+        ctx: &Context<'_>,
+        account_json: String,
+    ) -> async_graphql::Result<AccountOverview> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let account = parse_account(&account_json)?;
+
+        // The account's own launches are listed even when flagged
+        let created =
+            state.get_tokens_by_creator(&account).await.map_err(|e| query_error(e, None))?;
+        let pending_messages = state
+            .get_pending_messages()
+            .await
+            .map_err(|e| query_error(e, None))?
+            .iter()
+            .filter(|p| created.iter().any(|token| token.token_id == p.token_id))
+            .map(|p| p.into())
+            .collect();
+        let referral_codes =
+            state.get_referral_codes_of(&account).await.map_err(|e| query_error(e, None))?;
+        let recent_launches = state
+            .recent_launches
+            .get(&account)
+            .await
+            .map_err(|e| query_error(e, None))?
+            .unwrap_or_default()
+            .iter()
+            .map(|launched_at| launched_at.micros().to_string())
+            .collect();
+
+        let positions = portfolio_entries(state, &account).await?;
+        let mut creator_fees = U256::zero();
+        for token in &created {
+            let fees = state
+                .stats
+                .creator_fees_of(&token.token_id)
+                .await
+                .map_err(|e| query_error(e, Some(&token.token_id)))?;
+            creator_fees = creator_fees.saturating_add(fees);
         }
+        let pending_batches = state
+            .batches
+            .get_by_buyer(&account, RECENT_BATCHES)
+            .await
+            .map_err(|e| query_error(e, None))?
+            .into_iter()
+            .filter(|(_, batch)| !batch.is_settled())
+            .map(|(batch_id, batch)| BatchView::new(batch_id, batch))
+            .collect();
+
+        Ok(AccountOverview {
+            account: account.to_string(),
+            created_tokens: with_summaries(state, created).await,
+            positions,
+            creator_fees: creator_fees.to_string(),
+            pending_batches,
+            referral_codes,
+            pending_messages,
+            recent_launches,
+        })
     }
 
-    /// Get factory statistics
-    async fn stats(&self, ctx: &Context<'_>) -> FactoryStats {
+    /// Raise, fee earnings, holders and graduation status of every launch by
+    /// an account, with totals across them
+    ///
+    /// Fees are summed from the token chains' trade notifications and holder
+    /// counts come from their latest synced summaries.
+    async fn creator_dashboard(
+        &self,
+        ctx: &Context<'_>,
+        account_json: String,
+    ) -> async_graphql::Result<CreatorDashboard> {
         let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
 
-        let total_tokens = state.get_token_count();
+        let account = parse_account(&account_json)?;
+        let created =
+            state.get_tokens_by_creator(&account).await.map_err(|e| query_error(e, None))?;
 
-        // Calculate total value locked by iterating all tokens
-        // In production, this should be cached/indexed
-        let mut total_value_locked = U256::zero();
+        let mut total_raised = U256::zero();
+        let mut total_creator_fees = U256::zero();
         let mut graduated_count = 0;
-
-        if let Ok(tokens) = state.get_all_tokens(0, total_tokens).await {
-            for token in tokens {
-                total_value_locked += token.total_raised;
-                if token.is_graduated {
-                    graduated_count += 1;
-                }
+        let mut launches = Vec::with_capacity(created.len());
+        for token in created {
+            let creator_fees = state
+                .stats
+                .creator_fees_of(&token.token_id)
+                .await
+                .map_err(|e| query_error(e, Some(&token.token_id)))?;
+            let summary = state
+                .get_token_summary(&token.token_id)
+                .await
+                .map_err(|e| query_error(e, Some(&token.token_id)))?;
+            total_raised = total_raised.saturating_add(token.total_raised);
+            total_creator_fees = total_creator_fees.saturating_add(creator_fees);
+            if token.is_graduated {
+                graduated_count += 1;
             }
+
+            launches.push(CreatorLaunchStats {
+                token_id: token.token_id,
+                symbol: token.metadata.symbol,
+                created_at: token.created_at.micros().to_string(),
+                total_raised: token.total_raised.to_string(),
+                creator_fees: creator_fees.to_string(),
+                holder_count: summary.as_ref().map(|summary| summary.holder_count),
+                progress_bps: summary.as_ref().map(|summary| summary.progress_bps),
+                is_graduated: token.is_graduated,
+                dex_pool_id: token.dex_pool_id,
+            });
         }
 
-        FactoryStats {
+        Ok(CreatorDashboard {
+            account: account.to_string(),
+            launch_count: launches.len() as u64,
+            graduated_count,
+            total_raised: total_raised.to_string(),
+            total_creator_fees: total_creator_fees.to_string(),
+            launches,
+        })
+    }
+
+    /// Invested amount, current value and PnL per token for an account
+    ///
+    /// Built from trade notifications sent by the token chains, so it covers
+    /// curve trades only. Amounts are in each token's own base currency and
+    /// are valued at the latest traded (or synced) curve price.
+    async fn portfolio(
+        &self,
+        ctx: &Context<'_>,
+        account_json: String,
+    ) -> async_graphql::Result<Vec<PortfolioEntry>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let account = parse_account(&account_json)?;
+        portfolio_entries(state, &account).await
+    }
+
+    /// A batch buy and the outcome of each of its orders
+    async fn batch(
+        &self,
+        ctx: &Context<'_>,
+        batch_id: u64,
+    ) -> async_graphql::Result<Option<BatchView>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let batch = state.batches.get(batch_id).await.map_err(|e| query_error(e, None))?;
+        Ok(batch.map(|batch| BatchView::new(batch_id, batch)))
+    }
+
+    /// Batch buys submitted by an account, newest first
+    async fn batches(
+        &self,
+        ctx: &Context<'_>,
+        buyer_json: String,
+        limit: Option<usize>,
+    ) -> async_graphql::Result<Vec<BatchView>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let buyer = parse_account(&buyer_json)?;
+        let batches = state
+            .batches
+            .get_by_buyer(&buyer, limit.unwrap_or(20))
+            .await
+            .map_err(|e| query_error(e, None))?;
+        Ok(batches
+            .into_iter()
+            .map(|(batch_id, batch)| BatchView::new(batch_id, batch))
+            .collect())
+    }
+
+    /// Creator fee range accepted for new launches, in basis points
+    async fn creator_fee_bounds(&self, ctx: &Context<'_>) -> CreatorFeeBounds {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        *state.creator_fee_bounds.get()
+    }
+
+    /// Shortest pool lock a launch may choose, in microseconds
+    async fn min_pool_lock_micros(&self, ctx: &Context<'_>) -> u64 {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        *state.min_pool_lock_micros.get()
+    }
+
+    /// Creator fee discounts by 30-day trading volume given to new launches
+    async fn fee_tiers(&self, ctx: &Context<'_>) -> Vec<FeeTierGQL> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        state.fee_tiers.get().iter().map(FeeTierGQL::from).collect()
+    }
+
+    /// Version of the factory bytecode
+    async fn contract_version(&self, ctx: &Context<'_>) -> u32 {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        *state.contract_version.get()
+    }
+
+    /// Registered token bytecode releases, oldest first
+    async fn bytecode_releases(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<BytecodeReleaseView>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let releases = state.versions.list_releases().await.map_err(|e| query_error(e, None))?;
+        Ok(releases.into_iter().map(Into::into).collect())
+    }
+
+    /// Token bytecode version a launch announced (0 if none)
+    async fn token_version(
+        &self,
+        ctx: &Context<'_>,
+        token_id: String,
+    ) -> async_graphql::Result<u32> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        state
+            .versions
+            .token_version(&token_id)
+            .await
+            .map_err(|e| query_error(e, Some(&token_id)))
+    }
+
+    /// Active launches running older bytecode than the latest registered release
+    async fn outdated_tokens(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<usize>,
+    ) -> async_graphql::Result<Vec<OutdatedToken>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let latest_version = *state.versions.latest.get();
+        let outdated = state
+            .outdated_tokens(limit.unwrap_or(100))
+            .await
+            .map_err(|e| query_error(e, None))?;
+        Ok(outdated
+            .into_iter()
+            .map(|(token_id, version)| OutdatedToken { token_id, version, latest_version })
+            .collect())
+    }
+
+    /// Get all registered bonding curve templates
+    async fn curve_templates(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<CurveTemplateView>> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let templates = state.get_curve_templates().await.map_err(|e| query_error(e, None))?;
+        Ok(templates
+            .into_iter()
+            .map(|(name, config)| CurveTemplateView {
+                name,
+                config: (&config).into(),
+            })
+            .collect())
+    }
+
+    /// Get factory statistics
+    async fn stats(&self, ctx: &Context<'_>) -> async_graphql::Result<FactoryStats> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+
+        let total_tokens = state.get_token_count();
+
+        // Totals are maintained as launches report in, so this stays constant-time
+        let graduated_count = (*state.stats.graduations.get()).min(total_tokens);
+        Ok(FactoryStats {
             total_tokens,
             graduated_count,
             active_count: total_tokens - graduated_count,
-            total_value_locked: format!("{}", total_value_locked),
+            total_value_locked: state.stats.total_raised.get().to_string(),
+            volume_24h: state.stats.summary_volume_24h.get().to_string(),
+            volume_7d: state.stats.summary_volume_7d.get().to_string(),
+        })
+    }
+
+    /// Platform-wide totals, maintained incrementally from token and swap reports
+    async fn platform_stats(&self, ctx: &Context<'_>) -> PlatformStats {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let stats = &state.stats;
+
+        let total_launches = state.get_token_count();
+        let curve_volume = *stats.curve_volume.get();
+        let dex_volume = *stats.dex_volume.get();
+
+        PlatformStats {
+            total_launches,
+            graduations: *stats.graduations.get(),
+            graduation_rate_bps: stats.graduation_rate_bps(total_launches),
+            curve_volume: curve_volume.to_string(),
+            dex_volume: dex_volume.to_string(),
+            total_volume: curve_volume.saturating_add(dex_volume).to_string(),
+            creator_fees: stats.creator_fees.get().to_string(),
+            treasury_fees: stats.treasury_fees.get().to_string(),
+            unique_traders: stats.unique_traders(),
         }
     }
 }
 
+/// GraphQL mutation root
+struct MutationRoot {
+    runtime: Arc<ServiceRuntime<FactoryService>>,
+}
+
+#[Object]
+impl MutationRoot {
+    /// Schedule a factory operation given as JSON, for scripts and dev tooling
+    async fn schedule_operation(&self, operation_json: String) -> async_graphql::Result<bool> {
+        let operation: FactoryOperation = serde_json::from_str(&operation_json)?;
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+}
+
 /// GraphQL view of TokenLaunch (for serialization compatibility)
 #[derive(SimpleObject)]
+#[graphql(complex)]
 struct TokenLaunchView {
     token_id: String,
     creator: String,
@@ -241,6 +673,12 @@ struct TokenLaunchView {
     is_graduated: bool,
     created_at: String,
     dex_pool_id: Option<String>,
+    is_flagged: bool,
+    moderation_reason: Option<String>,
+    /// Composite risk score and its components (higher is riskier)
+    risk: fair_launch_abi::risk::RiskScore,
+    /// Latest metrics synced from the token chain (None until the first sync)
+    summary: Option<fair_launch_abi::TokenSummaryGQL>,
 }
 
 impl From<TokenLaunch> for TokenLaunchView {
@@ -255,10 +693,344 @@ impl From<TokenLaunch> for TokenLaunchView {
             is_graduated: token.is_graduated,
             created_at: format!("{}", token.created_at.micros()),
             dex_pool_id: token.dex_pool_id,
+            is_flagged: token.moderation_status.is_flagged(),
+            moderation_reason: token.moderation_status.reason().map(str::to_string),
+            risk: token.risk,
+            summary: None,
+        }
+    }
+}
+
+#[ComplexObject]
+impl TokenLaunchView {
+    /// Image link rewritten through the configured IPFS gateway (None if it
+    /// is not served)
+    ///
+    /// Resolved only when selected, as IPFS links cost a gateway request each.
+    async fn resolved_image_url(&self, ctx: &Context<'_>) -> Option<String> {
+        let runtime = ctx.data::<Arc<ServiceRuntime<FactoryService>>>().expect("Runtime not found");
+        let gateway = runtime.application_parameters().ipfs_gateway;
+        let url = self.metadata.image_url.as_deref()?;
+        ipfs::resolve(runtime, url, gateway.as_deref())
+    }
+
+    /// Chain the token application runs on (shared by its creator's launches)
+    async fn chain_id(&self, ctx: &Context<'_>) -> async_graphql::Result<String> {
+        let state = ctx.data::<Arc<FactoryState>>().expect("State not found");
+        let chain_id = state
+            .get_token_chain(&self.token_id)
+            .await
+            .map_err(|e| query_error(e, Some(&self.token_id)))?;
+        Ok(chain_id.to_string())
+    }
+}
+
+/// Factory-side view of a single account
+#[derive(SimpleObject)]
+struct AccountOverview {
+    account: String,
+    created_tokens: Vec<TokenLaunchView>,
+    /// Curve positions, as returned by `portfolio`
+    positions: Vec<PortfolioEntry>,
+    /// Creator fees charged on the account's launches, each in its own base units
+    creator_fees: String,
+    /// Recent batch buys with orders still waiting to fill
+    pending_batches: Vec<BatchView>,
+    referral_codes: Vec<String>,
+    /// Bounced messages involving the account's tokens
+    pending_messages: Vec<fair_launch_abi::PendingMessageGQL>,
+    /// Launch times counted against the creation rate limit (micros)
+    recent_launches: Vec<String>,
+}
+
+/// A creator's launches with totals across them
+#[derive(SimpleObject)]
+struct CreatorDashboard {
+    account: String,
+    launch_count: u64,
+    graduated_count: u64,
+    /// Sum of the launches' raises, each in its own base units
+    total_raised: String,
+    /// Creator fees charged on the launches' curve trades (stakers' share included)
+    total_creator_fees: String,
+    launches: Vec<CreatorLaunchStats>,
+}
+
+/// One launch as seen on its creator's dashboard
+#[derive(SimpleObject)]
+struct CreatorLaunchStats {
+    token_id: String,
+    symbol: String,
+    /// Launch time in microseconds
+    created_at: String,
+    total_raised: String,
+    creator_fees: String,
+    /// Holders in the latest synced summary (None before the first sync)
+    holder_count: Option<u64>,
+    /// Curve progress in basis points from the latest synced summary
+    progress_bps: Option<u16>,
+    is_graduated: bool,
+    dex_pool_id: Option<String>,
+}
+
+/// An account's position in one token, valued at the latest known price
+#[derive(SimpleObject)]
+struct PortfolioEntry {
+    token_id: String,
+    symbol: Option<String>,
+    balance: String,
+    /// Base currency spent on buys
+    total_invested: String,
+    /// Base currency received from sells
+    total_proceeds: String,
+    /// Price used for valuation, scaled by 10^price_decimals
+    price: String,
+    price_decimals: u8,
+    current_value: String,
+    /// current_value + total_proceeds - total_invested (may be negative)
+    pnl: String,
+    trades_count: u64,
+}
+
+/// A batch buy with per-order outcomes
+#[derive(SimpleObject)]
+struct BatchView {
+    batch_id: u64,
+    buyer: String,
+    orders: Vec<BatchOrderView>,
+    /// Whether every order has filled or failed
+    settled: bool,
+    created_at: String,
+}
+
+/// One order of a batch buy
+#[derive(SimpleObject)]
+struct BatchOrderView {
+    token_id: String,
+    amount: String,
+    max_cost: String,
+    /// "Pending", "Filled" or "Failed"
+    status: String,
+    /// Why the order failed (Failed only)
+    reason: Option<String>,
+}
+
+impl BatchView {
+    fn new(batch_id: u64, batch: BuyBatch) -> Self {
+        let settled = batch.is_settled();
+        let orders = batch
+            .orders
+            .into_iter()
+            .map(|order| {
+                let (status, reason) = match order.status {
+                    BatchOrderStatus::Pending => ("Pending", None),
+                    BatchOrderStatus::Filled => ("Filled", None),
+                    BatchOrderStatus::Failed { reason } => ("Failed", Some(reason)),
+                };
+                BatchOrderView {
+                    token_id: order.token_id,
+                    amount: order.amount.to_string(),
+                    max_cost: order.max_cost.to_string(),
+                    status: status.to_string(),
+                    reason,
+                }
+            })
+            .collect();
+        BatchView {
+            batch_id,
+            buyer: batch.buyer.to_string(),
+            orders,
+            settled,
+            created_at: batch.created_at.micros().to_string(),
+        }
+    }
+}
+
+/// A launch event received from a subscribed factory chain
+#[derive(SimpleObject)]
+struct FeedEntryView {
+    sequence: u64,
+    publisher: String,
+    /// "NewLaunch" or "Graduated"
+    kind: String,
+    token_id: String,
+    /// Launch details (NewLaunch only)
+    creator: Option<String>,
+    metadata: Option<TokenMetadata>,
+    /// DEX pool of a graduated token (Graduated only)
+    pool_id: Option<String>,
+    received_at: String,
+}
+
+impl FeedEntryView {
+    fn new(sequence: u64, entry: FeedEntry) -> Self {
+        let mut view = FeedEntryView {
+            sequence,
+            publisher: entry.publisher.to_string(),
+            kind: String::new(),
+            token_id: String::new(),
+            creator: None,
+            metadata: None,
+            pool_id: None,
+            received_at: entry.received_at.micros().to_string(),
+        };
+        match entry.event {
+            FactoryEvent::NewLaunch {
+                token_id,
+                creator,
+                metadata,
+            } => {
+                view.kind = "NewLaunch".to_string();
+                view.token_id = token_id;
+                view.creator = Some(creator.to_string());
+                view.metadata = Some(metadata);
+            }
+            FactoryEvent::Graduated { token_id, pool_id } => {
+                view.kind = "Graduated".to_string();
+                view.token_id = token_id;
+                view.pool_id = Some(pool_id);
+            }
+        }
+        view
+    }
+}
+
+/// Convert a state error into a GraphQL error carrying a machine-readable
+/// `code` extension, plus `tokenId` when the failure concerns one token
+///
+/// Lets clients tell an empty result apart from a failed read.
+fn query_error(error: impl Into<FactoryError>, token_id: Option<&str>) -> async_graphql::Error {
+    let error = error.into();
+    let code = match &error {
+        FactoryError::TokenNotFound(_) => "TOKEN_NOT_FOUND",
+        FactoryError::TemplateNotFound(_) => "TEMPLATE_NOT_FOUND",
+        FactoryError::InvalidReferralCode(_) | FactoryError::InvalidMetadata(_) => {
+            "INVALID_ARGUMENT"
+        }
+        FactoryError::StorageError(_) | FactoryError::ViewError(_) => "STORAGE_ERROR",
+        _ => "INTERNAL_ERROR",
+    };
+    (&error).extend_with(|_, extensions| {
+        extensions.set("code", code);
+        if let Some(token_id) = token_id {
+            extensions.set("tokenId", token_id);
+        }
+    })
+}
+
+/// Parse an `Account` given as JSON, failing with an `INVALID_ARGUMENT` error
+fn parse_account(
+    account_json: &str,
+) -> async_graphql::Result<linera_sdk::linera_base_types::Account> {
+    serde_json::from_str(account_json).map_err(|e| {
+        async_graphql::Error::new(format!("Invalid Account format: {}", e))
+            .extend_with(|_, extensions| extensions.set("code", "INVALID_ARGUMENT"))
+    })
+}
+
+/// Drop flagged tokens from a default listing (they stay reachable by ID)
+fn unflagged(tokens: Vec<TokenLaunch>) -> Vec<TokenLaunch> {
+    tokens
+        .into_iter()
+        .filter(|token| !token.moderation_status.is_flagged())
+        .collect()
+}
+
+/// Convert tokens to views with their replicated summaries attached
+async fn with_summaries(state: &FactoryState, tokens: Vec<TokenLaunch>) -> Vec<TokenLaunchView> {
+    let mut views = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let summary = state.get_token_summary(&token.token_id).await.ok().flatten();
+        let mut view = TokenLaunchView::from(token);
+        view.summary = summary.as_ref().map(Into::into);
+        views.push(view);
+    }
+    views
+}
+
+/// An account's positions valued at the latest traded (or synced) curve price
+async fn portfolio_entries(
+    state: &FactoryState,
+    account: &linera_sdk::linera_base_types::Account,
+) -> async_graphql::Result<Vec<PortfolioEntry>> {
+    let positions =
+        state.portfolios.positions_of(account).await.map_err(|e| query_error(e, None))?;
+
+    let mut entries = Vec::with_capacity(positions.len());
+    for (token_id, position) in positions {
+        let last_price = state
+            .portfolios
+            .last_price(&token_id)
+            .await
+            .map_err(|e| query_error(e, Some(&token_id)))?;
+        let price = match last_price {
+            Some(price) => price,
+            None => state
+                .get_token_summary(&token_id)
+                .await
+                .map_err(|e| query_error(e, Some(&token_id)))?
+                .map(|summary| summary.price)
+                .unwrap_or_default(),
+        };
+        let symbol = state.get_token(&token_id).await.ok().map(|token| token.metadata.symbol);
+        let current_value = position.current_value(price);
+        let returned = current_value.saturating_add(position.total_proceeds);
+        let pnl = if returned >= position.total_invested {
+            (returned - position.total_invested).to_string()
+        } else {
+            format!("-{}", position.total_invested - returned)
+        };
+
+        entries.push(PortfolioEntry {
+            token_id,
+            symbol,
+            balance: position.balance.to_string(),
+            total_invested: position.total_invested.to_string(),
+            total_proceeds: position.total_proceeds.to_string(),
+            price: price.raw().to_string(),
+            price_decimals: fair_launch_abi::price::PRICE_DECIMALS,
+            current_value: current_value.to_string(),
+            pnl,
+            trades_count: position.trades_count,
+        });
+    }
+    Ok(entries)
+}
+
+/// GraphQL view of a named curve template
+#[derive(SimpleObject)]
+struct CurveTemplateView {
+    name: String,
+    config: fair_launch_abi::BondingCurveConfigGQL,
+}
+
+/// GraphQL view of a registered token bytecode release
+#[derive(SimpleObject)]
+struct BytecodeReleaseView {
+    version: u32,
+    description: String,
+    registered_at: String,
+}
+
+impl From<BytecodeRelease> for BytecodeReleaseView {
+    fn from(release: BytecodeRelease) -> Self {
+        BytecodeReleaseView {
+            version: release.version,
+            description: release.description,
+            registered_at: release.registered_at.micros().to_string(),
         }
     }
 }
 
+/// A launch running older token bytecode than the latest release
+#[derive(SimpleObject)]
+struct OutdatedToken {
+    token_id: String,
+    /// Version the token announced (0 if it never did)
+    version: u32,
+    latest_version: u32,
+}
+
 /// Factory statistics
 #[derive(SimpleObject)]
 struct FactoryStats {
@@ -266,6 +1038,29 @@ struct FactoryStats {
     graduated_count: u64,
     active_count: u64,
     total_value_locked: String,
+    /// Curve volume summed over the tokens' latest summaries
+    volume_24h: String,
+    volume_7d: String,
+}
+
+/// Platform-wide totals across launches, curve trades and DEX swaps
+#[derive(SimpleObject)]
+struct PlatformStats {
+    total_launches: u64,
+    graduations: u64,
+    /// Graduations per launch, in basis points
+    graduation_rate_bps: u64,
+    /// Base currency traded on bonding curves
+    curve_volume: String,
+    /// Base currency traded in DEX pools
+    dex_volume: String,
+    total_volume: String,
+    /// Creator fees charged on curve trades (stakers' share included)
+    creator_fees: String,
+    /// Native creation fees paid to the treasury
+    treasury_fees: String,
+    /// Approximate number of accounts that traded on a curve or in a pool
+    unique_traders: u64,
 }
 
 
@@ -299,6 +1094,8 @@ mod tests {
             is_graduated: false,
             created_at: Timestamp::from(0),
             dex_pool_id: None,
+            moderation_status: Default::default(),
+            risk: Default::default(),
         };
 
         let view = TokenLaunchView::from(token);
@@ -1,12 +1,22 @@
-use fair_launch_abi::{BondingCurveConfig, TokenLaunch, TokenMetadata};
+use fair_launch_abi::{
+    logging::CriticalEventLog, outbox::Outbox, risk, BondingCurveConfig, CreatorFeeBounds, FeeTier,
+    ModerationStatus, PendingMessage, TokenLaunch, TokenMetadata, TokenSummary,
+};
 use linera_sdk::{
     linera_base_types::{Account, ChainId, Timestamp},
-    views::{MapView, RegisterView, RootView, ViewStorageContext},
+    views::{MapView, RegisterView, RootView, View, ViewStorageContext},
 };
 use linera_views::ViewError;
 use primitive_types::U256;
 use thiserror::Error;
 
+use crate::archive::ArchiveState;
+use crate::batch::BatchBuyState;
+use crate::feed::FeedState;
+use crate::portfolio::PortfolioState;
+use crate::stats::PlatformStatsState;
+use crate::versions::VersionState;
+
 /// Factory state errors
 #[derive(Debug, Error)]
 pub enum FactoryError {
@@ -19,6 +29,18 @@ pub enum FactoryError {
     #[error("Invalid metadata: {0}")]
     InvalidMetadata(String),
 
+    #[error("Curve template not found: {0}")]
+    TemplateNotFound(String),
+
+    #[error("Invalid referral code: {0}")]
+    InvalidReferralCode(String),
+
+    #[error("Referral code already taken: {0}")]
+    ReferralCodeTaken(String),
+
+    #[error("Launch limit reached: {max} per window, next launch allowed at {retry_at}")]
+    LaunchRateLimited { max: u32, retry_at: Timestamp },
+
     #[error("Storage error: {0}")]
     StorageError(#[from] anyhow::Error),
 
@@ -30,9 +52,14 @@ pub enum FactoryError {
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct FactoryState {
-    /// All created tokens: token_id (ChainId string) → TokenLaunch
+    /// All created tokens: token_id → TokenLaunch
     pub tokens: MapView<String, TokenLaunch>,
 
+    /// Chain each token lives on: token_id → ChainId
+    ///
+    /// Tokens launched before IDs were derived have none: their ID is the chain ID.
+    pub token_chains: MapView<String, ChainId>,
+
     /// Total number of tokens created
     pub token_count: RegisterView<u64>,
 
@@ -42,9 +69,83 @@ pub struct FactoryState {
 
     /// Index for fast lookup: index → token_id
     pub token_index: MapView<u64, String>,
+
+    /// Reverse of `token_index`: token_id → index
+    ///
+    /// Tokens indexed before this was kept have no entry and are found by
+    /// scanning `token_index` when removed.
+    pub token_positions: MapView<String, u64>,
+
+    /// Factory administrator (the account that instantiated the factory)
+    pub admin: RegisterView<Option<Account>>,
+
+    /// Creator fees accepted for new launches and curve templates
+    pub creator_fee_bounds: RegisterView<CreatorFeeBounds>,
+
+    /// Shortest pool lock a launch may choose, in microseconds
+    pub min_pool_lock_micros: RegisterView<u64>,
+
+    /// Creator fee discounts by trading volume, copied into new launches
+    pub fee_tiers: RegisterView<Vec<FeeTier>>,
+
+    /// Named bonding curve templates: name → config
+    pub curve_templates: MapView<String, BondingCurveConfig>,
+
+    /// Bounced tracked messages: "{kind}:{token_id}" → PendingMessage
+    pub pending_messages: MapView<String, PendingMessage>,
+
+    /// Latest critical log lines, mirrored on-chain for operators
+    pub critical_events: CriticalEventLog,
+
+    /// Sequenced log of externally relevant events for indexers
+    pub outbox: Outbox,
+
+    /// Finalized tokens moved out of the hot registry
+    pub archive: ArchiveState,
+
+    /// Referral codes: code → referring account
+    pub referral_codes: MapView<String, Account>,
+
+    /// Listing metrics replicated from token chains: token_id → TokenSummary
+    pub token_summaries: MapView<String, TokenSummary>,
+
+    /// Recent launch times per creator, for rate limiting
+    pub recent_launches: MapView<Account, Vec<Timestamp>>,
+
+    /// Per-account curve positions cached from trade notifications
+    pub portfolios: PortfolioState,
+
+    /// Open, unflagged token closest to its target raise: (token_id, progress in bps)
+    pub king_of_the_hill: RegisterView<Option<(String, u64)>>,
+
+    /// Launch events received from subscribed factory chains
+    pub feed: FeedState,
+
+    /// Platform-wide volume, fee and trader counters
+    pub stats: PlatformStatsState,
+
+    /// Batch buys and the outcome of each order
+    pub batches: BatchBuyState,
+
+    /// Token bytecode releases and the version each launch runs
+    pub versions: VersionState,
+
+    /// Version of the factory bytecode that instantiated this state
+    pub contract_version: RegisterView<u32>,
+
+    /// Change counter reported under `STATE_VERSION_EXTENSION`
+    pub state_version: RegisterView<u64>,
 }
 
 impl FactoryState {
+    /// Bump `state_version` if anything changed since the last save
+    pub async fn bump_version(&mut self) {
+        if self.has_pending_changes().await {
+            let version = *self.state_version.get();
+            self.state_version.set(version + 1);
+        }
+    }
+
     /// Register a new token launch
     pub async fn register_token(
         &mut self,
@@ -58,7 +159,9 @@ impl FactoryState {
         Self::validate_metadata(&metadata)?;
 
         // Check for duplicates
-        if self.tokens.get(&token_id).await?.is_some() {
+        if self.tokens.get(&token_id).await?.is_some()
+            || self.archive.get_token(&token_id).await?.is_some()
+        {
             return Err(FactoryError::TokenAlreadyExists(token_id));
         }
 
@@ -73,14 +176,18 @@ impl FactoryState {
             is_graduated: false,
             created_at,
             dex_pool_id: None,
+            moderation_status: ModerationStatus::Clear,
+            risk: risk::RiskScore::default(),
         };
 
         // Store token
         self.tokens.insert(&token_id, token_launch)?;
+        self.stats.count_token(&token_id, U256::zero(), None)?;
 
         // Update token count and index
         let count = *self.token_count.get();
         self.token_index.insert(&count, token_id.clone())?;
+        self.token_positions.insert(&token_id, count)?;
         self.token_count.set(count + 1);
 
         // Update creator registry
@@ -96,17 +203,331 @@ impl FactoryState {
         creator_tokens.push_str(&token_id);
         self.creator_registry.insert(&creator, creator_tokens)?;
 
+        self.refresh_creator_risk(&creator).await
+    }
+
+    /// Recompute the risk score of every launch by `creator`
+    ///
+    /// A launch's creator-history component depends on the creator's other
+    /// launches, so all of them are rescored together.
+    pub async fn refresh_creator_risk(&mut self, creator: &Account) -> Result<(), FactoryError> {
+        let tokens = self.get_tokens_by_creator(creator).await?;
+        let flagged = tokens
+            .iter()
+            .filter(|token| token.moderation_status.is_flagged())
+            .count() as u64;
+        let graduated = tokens.iter().filter(|token| token.is_graduated).count() as u64;
+
+        for mut token in tokens.iter().cloned() {
+            let history = risk::CreatorHistory {
+                launches: tokens.len() as u64 - 1,
+                graduated: graduated - u64::from(token.is_graduated),
+                flagged,
+            };
+            let summary = self.token_summaries.get(&token.token_id).await?;
+            token.risk = risk::score(history, &token.curve_config, summary.as_ref(), &token.metadata);
+            self.save_token(token).await?;
+        }
         Ok(())
     }
 
+    /// Roll back a token registration (used when TokenCreated bounces)
+    ///
+    /// The last indexed token is moved into the freed index slot so the
+    /// index stays dense for pagination.
+    pub async fn unregister_token(&mut self, token_id: &str) -> Result<(), FactoryError> {
+        let token = self.get_token(token_id).await?;
+        self.tokens.remove(token_id)?;
+        self.token_chains.remove(token_id)?;
+
+        // Remove from the creator registry
+        let creator_tokens = self
+            .creator_registry
+            .get(&token.creator)
+            .await?
+            .unwrap_or_default();
+        let remaining: Vec<&str> = creator_tokens
+            .split(',')
+            .filter(|id| !id.is_empty() && *id != token_id)
+            .collect();
+        if remaining.is_empty() {
+            self.creator_registry.remove(&token.creator)?;
+        } else {
+            self.creator_registry.insert(&token.creator, remaining.join(","))?;
+        }
+
+        self.remove_from_index(token_id).await?;
+        if matches!(self.king_of_the_hill.get(), Some((king_id, _)) if king_id == token_id) {
+            self.rescan_king_of_the_hill(None).await?;
+        }
+        self.refresh_creator_risk(&token.creator).await
+    }
+
+    /// Remove a token from the hot index, keeping it dense
+    async fn remove_from_index(&mut self, token_id: &str) -> Result<(), FactoryError> {
+        let mut position = self.token_positions.get(token_id).await?;
+        if position.is_none() {
+            for index in self.token_index.indices().await? {
+                if self.token_index.get(&index).await?.as_deref() == Some(token_id) {
+                    position = Some(index);
+                    break;
+                }
+            }
+        }
+        if let Some(index) = position {
+            self.remove_index_at(index).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove the token at `index` from the hot index, moving the last
+    /// indexed token into the freed slot
+    async fn remove_index_at(&mut self, index: u64) -> Result<(), FactoryError> {
+        let Some(token_id) = self.token_index.get(&index).await? else {
+            return Ok(());
+        };
+        let last = *self.token_count.get() - 1;
+        if index != last {
+            if let Some(last_id) = self.token_index.get(&last).await? {
+                self.token_positions.insert(&last_id, index)?;
+                self.token_index.insert(&index, last_id)?;
+            }
+        }
+        self.token_index.remove(&last)?;
+        self.token_positions.remove(&token_id)?;
+        self.token_count.set(last);
+        Ok(())
+    }
+
+    /// Move up to `max_tokens` finalized tokens (graduated with a DEX pool)
+    /// from the hot registry into the archive, returning how many moved
+    ///
+    /// Creator registry entries are kept, so archived tokens remain
+    /// reachable by creator.
+    pub async fn archive_finalized_tokens(&mut self, max_tokens: u64) -> Result<u64, FactoryError> {
+        let mut finalized = Vec::new();
+        for index in self.token_index.indices().await? {
+            if finalized.len() as u64 >= max_tokens {
+                break;
+            }
+            if let Some(token_id) = self.token_index.get(&index).await? {
+                if let Some(token) = self.tokens.get(&token_id).await? {
+                    if token.is_graduated && token.dex_pool_id.is_some() {
+                        finalized.push((index, token));
+                    }
+                }
+            }
+        }
+
+        // Highest index first: each removal only moves the last token, which
+        // is never one of the lower slots still to be removed
+        let mut indices: Vec<u64> = finalized.iter().map(|(index, _)| *index).collect();
+        indices.sort_unstable_by(|a, b| b.cmp(a));
+        for index in indices {
+            self.remove_index_at(index).await?;
+        }
+
+        let archived = finalized.len() as u64;
+        for (_, token) in finalized {
+            self.tokens.remove(&token.token_id)?;
+            self.archive.archive_token(token)?;
+        }
+
+        Ok(archived)
+    }
+
+    /// Record a bounced message, returning how many times it has bounced
+    pub async fn record_bounce(
+        &mut self,
+        kind: &str,
+        token_id: &str,
+        bounced_at: Timestamp,
+        resolution: &str,
+    ) -> Result<u32, FactoryError> {
+        let key = PendingMessage::key(kind, token_id);
+        let bounces = self
+            .pending_messages
+            .get(&key)
+            .await?
+            .map(|pending| pending.bounces)
+            .unwrap_or(0)
+            + 1;
+        let pending = PendingMessage {
+            kind: kind.to_string(),
+            token_id: token_id.to_string(),
+            bounces,
+            last_bounced_at: bounced_at,
+            resolution: resolution.to_string(),
+        };
+        self.pending_messages.insert(&key, pending)?;
+        Ok(bounces)
+    }
+
+    /// Get all pending (bounced) messages
+    pub async fn get_pending_messages(&self) -> Result<Vec<PendingMessage>, FactoryError> {
+        let mut pending = Vec::new();
+        for key in self.pending_messages.indices().await? {
+            if let Some(message) = self.pending_messages.get(&key).await? {
+                pending.push(message);
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Up to `limit` launches running older token bytecode than the latest
+    /// registered release, with the version each runs
+    pub async fn outdated_tokens(&self, limit: usize) -> Result<Vec<(String, u32)>, FactoryError> {
+        let latest = *self.versions.latest.get();
+        let mut outdated = Vec::new();
+        for token_id in self.tokens.indices().await? {
+            if outdated.len() >= limit {
+                break;
+            }
+            let version = self.versions.token_version(&token_id).await?;
+            if version < latest {
+                outdated.push((token_id, version));
+            }
+        }
+        Ok(outdated)
+    }
+
     /// Get a token by ID
     pub async fn get_token(&self, token_id: &str) -> Result<TokenLaunch, FactoryError> {
-        self.tokens
-            .get(token_id)
+        if let Some(token) = self.tokens.get(token_id).await? {
+            return Ok(token);
+        }
+        self.archive
+            .get_token(token_id)
             .await?
             .ok_or_else(|| FactoryError::TokenNotFound(token_id.to_string()))
     }
 
+    /// Record the chain a token lives on
+    pub fn set_token_chain(
+        &mut self,
+        token_id: &str,
+        chain_id: ChainId,
+    ) -> Result<(), FactoryError> {
+        self.token_chains.insert(token_id, chain_id)?;
+        Ok(())
+    }
+
+    /// Chain a token lives on
+    pub async fn get_token_chain(&self, token_id: &str) -> Result<ChainId, FactoryError> {
+        if let Some(chain_id) = self.token_chains.get(token_id).await? {
+            return Ok(chain_id);
+        }
+        // Launches registered before IDs were derived are keyed by their chain ID
+        token_id
+            .parse()
+            .map_err(|_| FactoryError::TokenNotFound(token_id.to_string()))
+    }
+
+    /// Whether a token has been moved to the archive
+    pub async fn is_archived(&self, token_id: &str) -> Result<bool, FactoryError> {
+        Ok(self.tokens.get(token_id).await?.is_none()
+            && self.archive.get_token(token_id).await?.is_some())
+    }
+
+    /// Write a token record back to whichever registry holds it
+    async fn save_token(&mut self, token: TokenLaunch) -> Result<(), FactoryError> {
+        self.update_king_of_the_hill(&token).await?;
+        if self.is_archived(&token.token_id).await? {
+            self.archive.update_token(token)?;
+        } else {
+            self.tokens.insert(&token.token_id.clone(), token)?;
+        }
+        Ok(())
+    }
+
+    /// Progress of a token's raise towards its target, in basis points
+    fn raise_progress_bps(token: &TokenLaunch) -> u64 {
+        let target_raise = token.curve_config.target_raise;
+        if target_raise.is_zero() {
+            return 0;
+        }
+        let progress = token.total_raised * U256::from(10_000) / target_raise;
+        progress.min(U256::from(u64::MAX)).as_u64()
+    }
+
+    /// Whether a token may be featured as king of the hill
+    fn is_contender(token: &TokenLaunch) -> bool {
+        !token.is_graduated && !token.moderation_status.is_flagged()
+    }
+
+    /// Keep the king of the hill current as a token record changes
+    ///
+    /// Only a king that falls back (sells, graduation, flagging) forces a
+    /// scan of the hot registry for its successor.
+    async fn update_king_of_the_hill(&mut self, token: &TokenLaunch) -> Result<(), FactoryError> {
+        let progress = Self::raise_progress_bps(token);
+        let contender = Self::is_contender(token);
+        match self.king_of_the_hill.get().clone() {
+            Some((king_id, king_progress)) if king_id == token.token_id => {
+                if contender && progress >= king_progress {
+                    self.king_of_the_hill.set(Some((king_id, progress)));
+                } else {
+                    self.rescan_king_of_the_hill(Some(token)).await?;
+                }
+            }
+            Some((_, king_progress)) => {
+                if contender && progress > king_progress {
+                    self.king_of_the_hill.set(Some((token.token_id.clone(), progress)));
+                }
+            }
+            None => {
+                if contender {
+                    self.king_of_the_hill.set(Some((token.token_id.clone(), progress)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pick the king of the hill from the hot registry
+    ///
+    /// `updated` is a record about to be written, taking precedence over its
+    /// stored version. Archived tokens are graduated and never contend.
+    async fn rescan_king_of_the_hill(
+        &mut self,
+        updated: Option<&TokenLaunch>,
+    ) -> Result<(), FactoryError> {
+        let mut king: Option<(String, u64)> = None;
+        for index in self.token_index.indices().await? {
+            let Some(token_id) = self.token_index.get(&index).await? else {
+                continue;
+            };
+            let token = match updated {
+                Some(updated) if updated.token_id == token_id => updated.clone(),
+                _ => match self.tokens.get(&token_id).await? {
+                    Some(token) => token,
+                    None => continue,
+                },
+            };
+            if !Self::is_contender(&token) {
+                continue;
+            }
+            let progress = Self::raise_progress_bps(&token);
+            let leads = match &king {
+                Some((_, best)) => progress > *best,
+                None => true,
+            };
+            if leads {
+                king = Some((token_id, progress));
+            }
+        }
+        self.king_of_the_hill.set(king);
+        Ok(())
+    }
+
+    /// The open token closest to graduation, if any
+    pub async fn get_king_of_the_hill(&self) -> Result<Option<TokenLaunch>, FactoryError> {
+        match self.king_of_the_hill.get() {
+            Some((token_id, _)) => Ok(Some(self.get_token(token_id).await?)),
+            None => Ok(None),
+        }
+    }
+
     /// Get all tokens created by a specific creator
     pub async fn get_tokens_by_creator(
         &self,
@@ -120,7 +541,7 @@ impl FactoryState {
 
         let mut tokens = Vec::new();
         for token_id in token_ids_str.split(',') {
-            if let Ok(Some(token)) = self.tokens.get(token_id).await {
+            if let Ok(token) = self.get_token(token_id).await {
                 tokens.push(token);
             }
         }
@@ -134,13 +555,19 @@ impl FactoryState {
         offset: u64,
         limit: u64,
     ) -> Result<Vec<TokenLaunch>, FactoryError> {
-        let total_count = *self.token_count.get();
-        let end = (offset + limit).min(total_count);
+        // Archived tokens come first (indices [0, archived)), then hot tokens
+        let archived = self.archive.count();
+        let total_count = self.get_token_count();
+        let end = offset.saturating_add(limit).min(total_count);
 
         let mut tokens = Vec::new();
 
         for i in offset..end {
-            if let Ok(Some(token_id)) = self.token_index.get(&i).await {
+            if i < archived {
+                if let Ok(Some(token)) = self.archive.get_token_at(i).await {
+                    tokens.push(token);
+                }
+            } else if let Ok(Some(token_id)) = self.token_index.get(&(i - archived)).await {
                 if let Ok(Some(token)) = self.tokens.get(&token_id).await {
                     tokens.push(token);
                 }
@@ -150,9 +577,9 @@ impl FactoryState {
         Ok(tokens)
     }
 
-    /// Get total token count
+    /// Get total token count (hot and archived)
     pub fn get_token_count(&self) -> u64 {
-        *self.token_count.get()
+        *self.token_count.get() + self.archive.count()
     }
 
     /// Update token status (for graduation notifications)
@@ -164,12 +591,29 @@ impl FactoryState {
     ) -> Result<(), FactoryError> {
         let mut token = self.get_token(token_id).await?;
 
+        if is_graduated && !token.is_graduated {
+            self.stats.record_graduation();
+        }
         token.is_graduated = is_graduated;
         token.dex_pool_id = dex_pool_id;
+        let creator = token.creator;
 
-        self.tokens.insert(token_id, token)?;
+        self.save_token(token).await?;
 
-        Ok(())
+        self.refresh_creator_risk(&creator).await
+    }
+
+    /// Set a token's moderation status
+    pub async fn set_moderation_status(
+        &mut self,
+        token_id: &str,
+        status: ModerationStatus,
+    ) -> Result<(), FactoryError> {
+        let mut token = self.get_token(token_id).await?;
+        token.moderation_status = status;
+        let creator = token.creator;
+        self.save_token(token).await?;
+        self.refresh_creator_risk(&creator).await
     }
 
     /// Update token supply and raised amount (for trade notifications)
@@ -181,14 +625,213 @@ impl FactoryState {
     ) -> Result<(), FactoryError> {
         let mut token = self.get_token(token_id).await?;
 
+        if self.stats.is_counted(token_id).await? {
+            self.stats.record_raise_change(token.total_raised, total_raised);
+        }
         token.current_supply = current_supply;
         token.total_raised = total_raised;
 
-        self.tokens.insert(token_id, token)?;
+        self.save_token(token).await?;
+
+        Ok(())
+    }
+
+    /// Record a launch by `creator`, enforcing at most `max_launches` per `window_micros`
+    ///
+    /// Launch times outside the window are pruned, so a creator's list never
+    /// holds more than `max_launches` entries. A `max_launches` of zero
+    /// disables the limit and keeps no launch times.
+    pub async fn record_launch(
+        &mut self,
+        creator: Account,
+        now: Timestamp,
+        max_launches: u32,
+        window_micros: u64,
+    ) -> Result<(), FactoryError> {
+        if max_launches == 0 {
+            self.recent_launches.remove(&creator)?;
+            return Ok(());
+        }
+        let window_start = now.micros().saturating_sub(window_micros);
+        let mut launches = self.recent_launches.get(&creator).await?.unwrap_or_default();
+        launches.retain(|launched_at| launched_at.micros() > window_start);
+
+        if launches.len() >= max_launches as usize {
+            let oldest = launches.iter().map(|t| t.micros()).min().unwrap_or(now.micros());
+            return Err(FactoryError::LaunchRateLimited {
+                max: max_launches,
+                retry_at: Timestamp::from(oldest.saturating_add(window_micros)),
+            });
+        }
 
+        launches.push(now);
+        self.recent_launches.insert(&creator, launches)?;
         Ok(())
     }
 
+    /// Add up to `max_entries` launches from before the platform totals to
+    /// them, returning how many were added
+    ///
+    /// Until a launch is added, its raise and summary updates stay out of the
+    /// totals rather than replacing figures they never included.
+    pub async fn backfill_stats_totals(
+        &mut self,
+        max_entries: usize,
+    ) -> Result<usize, FactoryError> {
+        let mut uncounted = Vec::new();
+        let token_ids = self.tokens.indices().await?;
+        for token_id in token_ids.into_iter().chain(self.archive.tokens.indices().await?) {
+            if uncounted.len() >= max_entries {
+                break;
+            }
+            if !self.stats.is_counted(&token_id).await? {
+                uncounted.push(token_id);
+            }
+        }
+        for token_id in &uncounted {
+            let total_raised = self.get_token(token_id).await?.total_raised;
+            let summary = self.token_summaries.get(token_id).await?;
+            self.stats.count_token(token_id, total_raised, summary.as_ref())?;
+        }
+        Ok(uncounted.len())
+    }
+
+    /// Apply a summary synced from a token chain
+    ///
+    /// Updates the token's supply and raised totals and stores the summary.
+    /// Snapshots older than the stored one are ignored, so reordered syncs
+    /// never roll metrics back. Returns whether the summary was applied.
+    pub async fn apply_token_summary(
+        &mut self,
+        token_id: &str,
+        current_supply: U256,
+        total_raised: U256,
+        summary: TokenSummary,
+    ) -> Result<bool, FactoryError> {
+        let existing = self.token_summaries.get(token_id).await?;
+        if let Some(existing) = &existing {
+            if existing.updated_at > summary.updated_at {
+                return Ok(false);
+            }
+        }
+
+        self.update_token_metrics(token_id, current_supply, total_raised)
+            .await?;
+        if self.stats.is_counted(token_id).await? {
+            self.stats.record_summary_change(existing.as_ref(), &summary);
+        }
+
+        // Holder concentration is the only risk component a summary moves,
+        // so the creator's other launches keep their scores
+        let mut token = self.get_token(token_id).await?;
+        token.risk = token.risk.with_summary(Some(&summary));
+        self.save_token(token).await?;
+
+        self.token_summaries.insert(token_id, summary)?;
+        Ok(true)
+    }
+
+    /// Get the latest replicated summary for a token
+    pub async fn get_token_summary(&self, token_id: &str) -> Result<Option<TokenSummary>, FactoryError> {
+        Ok(self.token_summaries.get(token_id).await?)
+    }
+
+    /// Register or replace a named curve template
+    pub fn add_curve_template(
+        &mut self,
+        name: String,
+        config: BondingCurveConfig,
+    ) -> Result<(), FactoryError> {
+        if name.trim().is_empty() || name.len() > 50 {
+            return Err(FactoryError::InvalidMetadata(
+                "Template name must be 1-50 characters".to_string(),
+            ));
+        }
+        self.curve_templates.insert(&name, config)?;
+        Ok(())
+    }
+
+    /// Get a curve template by name
+    pub async fn get_curve_template(&self, name: &str) -> Result<BondingCurveConfig, FactoryError> {
+        self.curve_templates
+            .get(name)
+            .await?
+            .ok_or_else(|| FactoryError::TemplateNotFound(name.to_string()))
+    }
+
+    /// Get all curve templates
+    pub async fn get_curve_templates(&self) -> Result<Vec<(String, BondingCurveConfig)>, FactoryError> {
+        let mut templates = Vec::new();
+        for name in self.curve_templates.indices().await? {
+            if let Some(config) = self.curve_templates.get(&name).await? {
+                templates.push((name, config));
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Whether an account has created at least one token
+    pub async fn is_creator(&self, account: &Account) -> Result<bool, FactoryError> {
+        Ok(!self
+            .creator_registry
+            .get(account)
+            .await?
+            .unwrap_or_default()
+            .is_empty())
+    }
+
+    /// Register a referral code for an account
+    ///
+    /// Codes are 3-16 ASCII letters, digits, '-' or '_', matched case-insensitively.
+    pub async fn register_referral_code(
+        &mut self,
+        code: &str,
+        account: Account,
+    ) -> Result<String, FactoryError> {
+        let code = Self::normalize_referral_code(code)?;
+        if self.referral_codes.get(&code).await?.is_some() {
+            return Err(FactoryError::ReferralCodeTaken(code));
+        }
+        self.referral_codes.insert(&code, account)?;
+        Ok(code)
+    }
+
+    /// Resolve a referral code to its account
+    pub async fn resolve_referral_code(&self, code: &str) -> Result<Option<Account>, FactoryError> {
+        let code = Self::normalize_referral_code(code)?;
+        Ok(self.referral_codes.get(&code).await?)
+    }
+
+    /// Referral codes registered to an account
+    pub async fn get_referral_codes_of(&self, account: &Account) -> Result<Vec<String>, FactoryError> {
+        let mut codes = Vec::new();
+        for code in self.referral_codes.indices().await? {
+            if self.referral_codes.get(&code).await?.as_ref() == Some(account) {
+                codes.push(code);
+            }
+        }
+        Ok(codes)
+    }
+
+    /// Validate and lowercase a referral code
+    fn normalize_referral_code(code: &str) -> Result<String, FactoryError> {
+        let code = code.trim();
+        if code.len() < 3 || code.len() > 16 {
+            return Err(FactoryError::InvalidReferralCode(
+                "Referral code must be 3-16 characters".to_string(),
+            ));
+        }
+        if !code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(FactoryError::InvalidReferralCode(
+                "Referral code may only contain letters, digits, '-' and '_'".to_string(),
+            ));
+        }
+        Ok(code.to_ascii_lowercase())
+    }
+
     /// Validate token metadata
     fn validate_metadata(metadata: &TokenMetadata) -> Result<(), FactoryError> {
         if metadata.name.trim().is_empty() {
@@ -245,6 +888,7 @@ impl FactoryState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use fair_launch_abi::BytecodeRelease;
     use linera_sdk::linera_base_types::{Account, AccountOwner, ChainId, Timestamp};
     use linera_views::memory::MemoryContext;
 
@@ -326,6 +970,49 @@ mod tests {
         assert!(matches!(result, Err(FactoryError::TokenAlreadyExists(_))));
     }
 
+    #[tokio::test]
+    async fn test_two_tokens_from_one_creator() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let factory_chain = ChainId::root(0);
+        let creator = Account {
+            chain_id: ChainId::root(1),
+            owner: AccountOwner::CHAIN,
+        };
+        let curve_config = BondingCurveConfig::default();
+        let created_at = Timestamp::from(0);
+
+        // Each launch gets its own chain and a distinct ID
+        let mut token_ids = Vec::new();
+        for n in 0..2 {
+            let token_id =
+                fair_launch_abi::derive_token_id(factory_chain, state.get_token_count(), creator);
+            state
+                .register_token(
+                    token_id.clone(),
+                    creator,
+                    create_test_metadata(),
+                    curve_config.clone(),
+                    created_at,
+                )
+                .await
+                .unwrap();
+            state.set_token_chain(&token_id, ChainId::root(10 + n)).unwrap();
+            token_ids.push(token_id);
+        }
+
+        assert_ne!(token_ids[0], token_ids[1]);
+        assert_eq!(state.get_token_count(), 2);
+        assert_eq!(state.get_tokens_by_creator(&creator).await.unwrap().len(), 2);
+        for (n, token_id) in (10..).zip(&token_ids) {
+            assert_eq!(state.get_token_chain(token_id).await.unwrap(), ChainId::root(n));
+        }
+
+        // Derivation is deterministic
+        assert_eq!(fair_launch_abi::derive_token_id(factory_chain, 0, creator), token_ids[0]);
+    }
+
     #[tokio::test]
     async fn test_metadata_validation() {
         let context = MemoryContext::default();
@@ -431,4 +1118,590 @@ mod tests {
         let page2 = state.get_all_tokens(5, 5).await.unwrap();
         assert_eq!(page2.len(), 5);
     }
+
+    #[tokio::test]
+    async fn test_unregister_token_keeps_index_dense() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        for i in 0..3 {
+            state
+                .register_token(
+                    format!("token-{}", i),
+                    creator,
+                    create_test_metadata(),
+                    BondingCurveConfig::default(),
+                    Timestamp::from(0),
+                )
+                .await
+                .unwrap();
+        }
+
+        state.unregister_token("token-0").await.unwrap();
+
+        assert_eq!(state.get_token_count(), 2);
+        assert!(matches!(state.get_token("token-0").await, Err(FactoryError::TokenNotFound(_))));
+
+        let tokens = state.get_all_tokens(0, 10).await.unwrap();
+        assert_eq!(tokens.len(), 2);
+
+        let by_creator = state.get_tokens_by_creator(&creator).await.unwrap();
+        assert_eq!(by_creator.len(), 2);
+
+        // The last token took the freed slot and its position moved with it
+        assert_eq!(state.token_index.get(&0).await.unwrap().as_deref(), Some("token-2"));
+        assert_eq!(state.token_positions.get("token-2").await.unwrap(), Some(0));
+        assert_eq!(state.token_positions.get("token-0").await.unwrap(), None);
+
+        // Tokens indexed before positions were kept are still found
+        state.token_positions.remove("token-1").unwrap();
+        state.unregister_token("token-1").await.unwrap();
+        assert_eq!(state.get_token_count(), 1);
+        assert_eq!(state.token_index.get(&0).await.unwrap().as_deref(), Some("token-2"));
+    }
+
+    #[tokio::test]
+    async fn test_curve_templates() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let mut config = BondingCurveConfig::default();
+        config.k = U256::from(2000);
+        state.add_curve_template("steep".to_string(), config).unwrap();
+
+        let template = state.get_curve_template("steep").await.unwrap();
+        assert_eq!(template.k, U256::from(2000));
+        assert_eq!(state.get_curve_templates().await.unwrap().len(), 1);
+
+        let result = state.get_curve_template("missing").await;
+        assert!(matches!(result, Err(FactoryError::TemplateNotFound(_))));
+
+        let result = state.add_curve_template("".to_string(), BondingCurveConfig::default());
+        assert!(matches!(result, Err(FactoryError::InvalidMetadata(_))));
+    }
+
+    #[tokio::test]
+    async fn test_archive_finalized_tokens() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        for i in 0..3 {
+            state
+                .register_token(
+                    format!("token-{}", i),
+                    creator,
+                    create_test_metadata(),
+                    BondingCurveConfig::default(),
+                    Timestamp::from(0),
+                )
+                .await
+                .unwrap();
+        }
+        state
+            .update_token_status("token-1", true, Some("pool-1".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(state.archive_finalized_tokens(10).await.unwrap(), 1);
+
+        // Hot registry shrinks, totals stay the same
+        assert_eq!(*state.token_count.get(), 2);
+        assert_eq!(state.get_token_count(), 3);
+        assert!(state.is_archived("token-1").await.unwrap());
+
+        // Archived tokens remain queryable
+        let token = state.get_token("token-1").await.unwrap();
+        assert_eq!(token.dex_pool_id, Some("pool-1".to_string()));
+        assert_eq!(state.get_all_tokens(0, 10).await.unwrap().len(), 3);
+        assert_eq!(state.get_tokens_by_creator(&creator).await.unwrap().len(), 3);
+
+        // Archived IDs cannot be reused
+        let result = state
+            .register_token(
+                "token-1".to_string(),
+                creator,
+                create_test_metadata(),
+                BondingCurveConfig::default(),
+                Timestamp::from(0),
+            )
+            .await;
+        assert!(matches!(result, Err(FactoryError::TokenAlreadyExists(_))));
+
+        // Nothing left to archive
+        assert_eq!(state.archive_finalized_tokens(10).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_referral_codes() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        assert!(!state.is_creator(&creator).await.unwrap());
+        state
+            .register_token(
+                "token-0".to_string(),
+                creator,
+                create_test_metadata(),
+                BondingCurveConfig::default(),
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+        assert!(state.is_creator(&creator).await.unwrap());
+
+        let code = state.register_referral_code("Moon_Ref", creator).await.unwrap();
+        assert_eq!(code, "moon_ref");
+        assert_eq!(state.resolve_referral_code("MOON_REF").await.unwrap(), Some(creator));
+        assert_eq!(state.resolve_referral_code("other").await.unwrap(), None);
+
+        let result = state.register_referral_code("moon_ref", creator).await;
+        assert!(matches!(result, Err(FactoryError::ReferralCodeTaken(_))));
+
+        let result = state.register_referral_code("a!", creator).await;
+        assert!(matches!(result, Err(FactoryError::InvalidReferralCode(_))));
+
+        let other = Account {
+            chain_id: ChainId::root(1),
+            owner: AccountOwner::CHAIN,
+        };
+        state.register_referral_code("second", creator).await.unwrap();
+        state.register_referral_code("theirs", other).await.unwrap();
+        assert_eq!(
+            state.get_referral_codes_of(&creator).await.unwrap(),
+            vec!["moon_ref".to_string(), "second".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_moderation_status() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        state
+            .register_token(
+                "token-0".to_string(),
+                creator,
+                create_test_metadata(),
+                BondingCurveConfig::default(),
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+
+        let flagged = ModerationStatus::Flagged {
+            reason: "rug".to_string(),
+            flagged_at: Timestamp::from(5),
+        };
+        state.set_moderation_status("token-0", flagged.clone()).await.unwrap();
+        let token = state.get_token("token-0").await.unwrap();
+        assert_eq!(token.moderation_status, flagged);
+        assert_eq!(token.moderation_status.reason(), Some("rug"));
+
+        state.set_moderation_status("token-0", ModerationStatus::Clear).await.unwrap();
+        assert!(!state.get_token("token-0").await.unwrap().moderation_status.is_flagged());
+
+        let result = state.set_moderation_status("missing", ModerationStatus::Clear).await;
+        assert!(matches!(result, Err(FactoryError::TokenNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_launch_rate_limit() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        let hour = 3_600_000_000u64;
+        state.record_launch(creator, Timestamp::from(0), 2, hour).await.unwrap();
+        state.record_launch(creator, Timestamp::from(10), 2, hour).await.unwrap();
+
+        let result = state.record_launch(creator, Timestamp::from(20), 2, hour).await;
+        match result {
+            Err(FactoryError::LaunchRateLimited { max, retry_at }) => {
+                assert_eq!(max, 2);
+                assert_eq!(retry_at, Timestamp::from(hour));
+            }
+            other => panic!("Expected rate limit, got {:?}", other),
+        }
+
+        // The first launch leaves the window
+        state.record_launch(creator, Timestamp::from(hour + 1), 2, hour).await.unwrap();
+
+        // Zero disables the limit and stops keeping launch times
+        for at in 0..5 {
+            state.record_launch(creator, Timestamp::from(2 * hour + at), 0, hour).await.unwrap();
+        }
+        assert_eq!(state.recent_launches.get(&creator).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_token_summary_sync() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        state
+            .register_token(
+                "token-0".to_string(),
+                creator,
+                create_test_metadata(),
+                BondingCurveConfig::default(),
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+
+        let summary = |at: u64, holder_count: u64| TokenSummary {
+            holder_count,
+            updated_at: Timestamp::from(at),
+            ..TokenSummary::default()
+        };
+        assert!(state
+            .apply_token_summary("token-0", U256::from(500), U256::from(50), summary(10, 3))
+            .await
+            .unwrap());
+        let token = state.get_token("token-0").await.unwrap();
+        assert_eq!(token.current_supply, U256::from(500));
+
+        // A stale snapshot is ignored
+        assert!(!state
+            .apply_token_summary("token-0", U256::from(100), U256::from(10), summary(5, 1))
+            .await
+            .unwrap());
+        let stored = state.get_token_summary("token-0").await.unwrap().unwrap();
+        assert_eq!(stored.holder_count, 3);
+
+        let result = state
+            .apply_token_summary("missing", U256::zero(), U256::zero(), summary(10, 0))
+            .await;
+        assert!(matches!(result, Err(FactoryError::TokenNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_positions_from_trades() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let trader = Account {
+            chain_id: ChainId::root(1),
+            owner: AccountOwner::CHAIN,
+        };
+        let price = fair_launch_abi::Price::from_ratio(U256::from(1), U256::from(2)).unwrap();
+        state
+            .portfolios
+            .apply_trade(trader, "token-0", true, U256::from(1_000), U256::from(300), price)
+            .await
+            .unwrap();
+        state
+            .portfolios
+            .apply_trade(trader, "token-0", false, U256::from(400), U256::from(150), price)
+            .await
+            .unwrap();
+        state
+            .portfolios
+            .apply_trade(trader, "token-1", true, U256::from(10), U256::from(5), price)
+            .await
+            .unwrap();
+
+        let positions = state.portfolios.positions_of(&trader).await.unwrap();
+        assert_eq!(positions.len(), 2);
+        let (token_id, position) = &positions[0];
+        assert_eq!(token_id, "token-0");
+        assert_eq!(position.balance, U256::from(600));
+        assert_eq!(position.total_invested, U256::from(300));
+        assert_eq!(position.total_proceeds, U256::from(150));
+        assert_eq!(position.trades_count, 2);
+        assert_eq!(position.current_value(price), U256::from(300));
+    }
+
+    #[tokio::test]
+    async fn test_risk_score_follows_creator_history() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        for index in 0..2 {
+            state
+                .register_token(
+                    format!("token-{}", index),
+                    creator,
+                    create_test_metadata(),
+                    BondingCurveConfig::default(),
+                    Timestamp::from(0),
+                )
+                .await
+                .unwrap();
+        }
+
+        // The second launch sees one unfinished prior launch
+        let risk = state.get_token("token-1").await.unwrap().risk;
+        assert_eq!(risk.creator_history, risk::COMPONENT_MAX);
+        assert_eq!(risk.metadata, 0);
+
+        state.update_token_status("token-0", true, None).await.unwrap();
+        assert_eq!(state.get_token("token-1").await.unwrap().risk.creator_history, 0);
+
+        // Flagging one launch marks all of the creator's launches
+        let status = ModerationStatus::Flagged {
+            reason: "rug".to_string(),
+            flagged_at: Timestamp::from(1),
+        };
+        state.set_moderation_status("token-0", status).await.unwrap();
+        let risk = state.get_token("token-1").await.unwrap().risk;
+        assert_eq!(risk.creator_history, risk::COMPONENT_MAX);
+        assert_eq!(
+            risk.total,
+            risk.creator_history + risk.fee_level + risk.protection + risk.concentration + risk.metadata
+        );
+    }
+
+    #[tokio::test]
+    async fn test_king_of_the_hill() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        for index in 0..3 {
+            state
+                .register_token(
+                    format!("token-{}", index),
+                    creator,
+                    create_test_metadata(),
+                    BondingCurveConfig::default(),
+                    Timestamp::from(0),
+                )
+                .await
+                .unwrap();
+        }
+        let king = |state: &FactoryState| state.king_of_the_hill.get().clone();
+
+        state.update_token_metrics("token-1", U256::zero(), U256::from(20_000)).await.unwrap();
+        state.update_token_metrics("token-2", U256::zero(), U256::from(10_000)).await.unwrap();
+        assert_eq!(king(&state), Some(("token-1".to_string(), 2_898)));
+
+        // A king that falls back hands over to the next best token
+        state.update_token_metrics("token-1", U256::zero(), U256::from(5_000)).await.unwrap();
+        assert_eq!(king(&state).unwrap().0, "token-2");
+
+        state.update_token_status("token-2", true, None).await.unwrap();
+        assert_eq!(king(&state).unwrap().0, "token-1");
+
+        let status = ModerationStatus::Flagged {
+            reason: "spam".to_string(),
+            flagged_at: Timestamp::from(1),
+        };
+        state.set_moderation_status("token-1", status).await.unwrap();
+        let featured = state.get_king_of_the_hill().await.unwrap().unwrap();
+        assert_eq!(featured.token_id, "token-0");
+    }
+
+    #[tokio::test]
+    async fn test_launch_feed_subscriptions() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let publisher = ChainId::root(1);
+        assert!(state.feed.subscribe(publisher, Timestamp::from(0)).await.unwrap());
+        assert!(!state.feed.subscribe(publisher, Timestamp::from(1)).await.unwrap());
+        assert_eq!(state.feed.publishers().await.unwrap(), vec![publisher]);
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        for index in 0..crate::feed::FEED_CAPACITY + 2 {
+            let event = fair_launch_abi::FactoryEvent::NewLaunch {
+                token_id: format!("token-{}", index),
+                creator,
+                metadata: create_test_metadata(),
+            };
+            state
+                .feed
+                .push(crate::feed::FeedEntry {
+                    publisher,
+                    event,
+                    received_at: Timestamp::from(index),
+                })
+                .unwrap();
+        }
+
+        // Only the latest FEED_CAPACITY events are retained
+        let recent = state.feed.recent(u64::MAX).await.unwrap();
+        assert_eq!(recent.len() as u64, crate::feed::FEED_CAPACITY);
+        assert_eq!(recent[0].0, crate::feed::FEED_CAPACITY + 1);
+        assert!(state.feed.entries.get(&1).await.unwrap().is_none());
+
+        assert!(state.feed.unsubscribe(publisher).await.unwrap());
+        assert!(!state.feed.unsubscribe(publisher).await.unwrap());
+        assert!(state.feed.publishers().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_platform_stats() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        let trader = Account {
+            chain_id: ChainId::root(1),
+            owner: AccountOwner::CHAIN,
+        };
+        for index in 0..4 {
+            state
+                .register_token(
+                    format!("token-{}", index),
+                    creator,
+                    create_test_metadata(),
+                    BondingCurveConfig::default(),
+                    Timestamp::from(0),
+                )
+                .await
+                .unwrap();
+        }
+
+        let stats = &mut state.stats;
+        stats
+            .record_curve_trade("token-0", trader, U256::from(1_000), U256::from(10))
+            .await
+            .unwrap();
+        stats.record_curve_trade("token-1", creator, U256::from(500), U256::from(5)).await.unwrap();
+        state.stats.record_dex_trade(trader, U256::from(200)).await.unwrap();
+        state.stats.record_creation_fee(linera_sdk::linera_base_types::Amount::ONE);
+        assert_eq!(*state.stats.curve_volume.get(), U256::from(1_500));
+        assert_eq!(*state.stats.dex_volume.get(), U256::from(200));
+        assert_eq!(*state.stats.creator_fees.get(), U256::from(15));
+        assert_eq!(state.stats.creator_fees_of("token-1").await.unwrap(), U256::from(5));
+        assert_eq!(state.stats.creator_fees_of("token-2").await.unwrap(), U256::zero());
+        assert_eq!(state.stats.unique_traders(), 2);
+
+        // Traders recorded before the sketch keep counting while they migrate
+        let legacy = Account {
+            chain_id: ChainId::root(2),
+            owner: AccountOwner::CHAIN,
+        };
+        state.stats.traders.insert(&legacy, ()).unwrap();
+        state.stats.traders.insert(&trader, ()).unwrap();
+        state.stats.unique_traders.set(3);
+        assert_eq!(state.stats.unique_traders(), 3);
+        assert_eq!(state.stats.migrate_legacy_traders(1).await.unwrap(), 1);
+        assert_eq!(state.stats.migrate_legacy_traders(10).await.unwrap(), 1);
+        assert_eq!(state.stats.migrate_legacy_traders(10).await.unwrap(), 0);
+        assert_eq!(state.stats.trader_sketch.get().estimate(), 3);
+
+        // Repeated graduation notices count once
+        state.update_token_status("token-0", true, None).await.unwrap();
+        state.update_token_status("token-0", true, Some("pool-token-0".to_string())).await.unwrap();
+        assert_eq!(*state.stats.graduations.get(), 1);
+        assert_eq!(state.stats.graduation_rate_bps(state.get_token_count()), 2_500);
+
+        // Raise and volume totals follow each token's latest report
+        let summary = |volume: u64, at: u64| TokenSummary {
+            volume_24h: U256::from(volume),
+            volume_7d: U256::from(volume * 2),
+            updated_at: Timestamp::from(at),
+            ..TokenSummary::default()
+        };
+        for (token_id, raised, volume, at) in
+            [("token-1", 300, 100, 1), ("token-2", 200, 50, 1), ("token-1", 400, 70, 2)]
+        {
+            let summary = summary(volume, at);
+            state
+                .apply_token_summary(token_id, U256::zero(), U256::from(raised), summary)
+                .await
+                .unwrap();
+        }
+        assert_eq!(*state.stats.total_raised.get(), U256::from(600));
+        assert_eq!(*state.stats.summary_volume_24h.get(), U256::from(120));
+        assert_eq!(*state.stats.summary_volume_7d.get(), U256::from(240));
+
+        // A launch from before the totals stays out of them until backfilled
+        state.stats.counted_tokens.remove(&"token-3".to_string()).unwrap();
+        state
+            .apply_token_summary("token-3", U256::zero(), U256::from(100), summary(30, 1))
+            .await
+            .unwrap();
+        assert_eq!(*state.stats.total_raised.get(), U256::from(600));
+        assert_eq!(state.backfill_stats_totals(10).await.unwrap(), 1);
+        assert_eq!(state.backfill_stats_totals(10).await.unwrap(), 0);
+        assert_eq!(*state.stats.total_raised.get(), U256::from(700));
+        assert_eq!(*state.stats.summary_volume_24h.get(), U256::from(150));
+        assert_eq!(*state.stats.summary_volume_7d.get(), U256::from(300));
+    }
+
+    #[tokio::test]
+    async fn test_bytecode_versions() {
+        let context = MemoryContext::default();
+        let mut state = FactoryState::load(context).await.unwrap();
+
+        let creator = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        for token_id in ["token-a", "token-b", "token-c"] {
+            state
+                .register_token(
+                    token_id.to_string(),
+                    creator,
+                    create_test_metadata(),
+                    BondingCurveConfig::default(),
+                    Timestamp::from(0),
+                )
+                .await
+                .unwrap();
+        }
+        let release = |version: u32| BytecodeRelease {
+            version,
+            description: format!("v{}", version),
+            registered_at: Timestamp::from(0),
+        };
+
+        // Nothing is outdated before a release exists
+        state.versions.record_token("token-a", 1).unwrap();
+        assert!(state.outdated_tokens(10).await.unwrap().is_empty());
+
+        assert!(state.versions.register(release(1)).await.unwrap());
+        assert!(!state.versions.register(release(1)).await.unwrap());
+        state.versions.record_token("token-b", 1).unwrap();
+        let outdated = state.outdated_tokens(10).await.unwrap();
+        assert_eq!(outdated, vec![("token-c".to_string(), 0)]);
+
+        // A new release makes every older launch outdated
+        assert!(state.versions.register(release(2)).await.unwrap());
+        assert_eq!(*state.versions.latest.get(), 2);
+        assert_eq!(state.outdated_tokens(10).await.unwrap().len(), 3);
+        assert_eq!(state.outdated_tokens(1).await.unwrap().len(), 1);
+        state.versions.record_token("token-a", 2).unwrap();
+        assert!(!state.versions.is_outdated("token-a").await.unwrap());
+        assert_eq!(state.versions.list_releases().await.unwrap().len(), 2);
+    }
 }
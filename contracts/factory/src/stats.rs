@@ -0,0 +1,181 @@
+use fair_launch_abi::{cardinality::TraderSketch, TokenSummary};
+use linera_sdk::{
+    linera_base_types::{Account, Amount},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+use primitive_types::U256;
+
+/// Platform-wide counters, maintained as launches, trades and swaps are reported
+///
+/// Curve volume and creator fees come from the token chains' `TradeExecuted`
+/// notifications and DEX volume from the swap chain's `SwapExecuted`, summed
+/// in each token's own base units like the other cross-token totals.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct PlatformStatsState {
+    /// Tokens whose curve completed
+    pub graduations: RegisterView<u64>,
+
+    /// Base currency traded on bonding curves
+    pub curve_volume: RegisterView<U256>,
+
+    /// Base currency traded in DEX pools
+    pub dex_volume: RegisterView<U256>,
+
+    /// Creator fees charged on curve trades (stakers' share included)
+    pub creator_fees: RegisterView<U256>,
+
+    /// Creator fees charged per launch: token_id → fees
+    pub token_creator_fees: MapView<String, U256>,
+
+    /// Native creation fees paid to the treasury
+    pub treasury_fees: RegisterView<Amount>,
+
+    /// Accounts that traded on a curve or in a pool, as a fixed-size sketch
+    /// rather than an ever-growing set
+    pub trader_sketch: RegisterView<TraderSketch>,
+
+    /// Traders recorded before the sketch (presence marks a trader); drained
+    /// into `trader_sketch` by `migrate_legacy_traders`, never written to
+    pub traders: MapView<Account, ()>,
+
+    /// Exact trader count kept before the sketch, a floor for `unique_traders`
+    pub unique_traders: RegisterView<u64>,
+
+    /// Sum of every launch's `total_raised`, kept so `stats` need not scan the registry
+    pub total_raised: RegisterView<U256>,
+
+    /// Sum of the 24h volumes in the latest token summaries
+    pub summary_volume_24h: RegisterView<U256>,
+
+    /// Sum of the 7d volumes in the latest token summaries
+    pub summary_volume_7d: RegisterView<U256>,
+
+    /// Launches whose raise and summary volumes are in the totals above;
+    /// launches from before the totals join through `backfill_stats_totals`
+    pub counted_tokens: MapView<String, ()>,
+}
+
+impl PlatformStatsState {
+    /// Count a curve trade on `token_id` and its creator fee
+    pub async fn record_curve_trade(
+        &mut self,
+        token_id: &str,
+        trader: Account,
+        volume: U256,
+        creator_fee: U256,
+    ) -> Result<(), ViewError> {
+        let curve_volume = *self.curve_volume.get();
+        self.curve_volume.set(curve_volume.saturating_add(volume));
+        let creator_fees = *self.creator_fees.get();
+        self.creator_fees.set(creator_fees.saturating_add(creator_fee));
+        if !creator_fee.is_zero() {
+            let token_fees = self.creator_fees_of(token_id).await?;
+            self.token_creator_fees
+                .insert(&token_id.to_string(), token_fees.saturating_add(creator_fee))?;
+        }
+        self.record_trader(trader);
+        Ok(())
+    }
+
+    /// Creator fees charged on a launch's curve trades
+    pub async fn creator_fees_of(&self, token_id: &str) -> Result<U256, ViewError> {
+        Ok(self.token_creator_fees.get(&token_id.to_string()).await?.unwrap_or_default())
+    }
+
+    /// Count a DEX swap
+    pub async fn record_dex_trade(&mut self, trader: Account, volume: U256) -> Result<(), ViewError> {
+        let dex_volume = *self.dex_volume.get();
+        self.dex_volume.set(dex_volume.saturating_add(volume));
+        self.record_trader(trader);
+        Ok(())
+    }
+
+    /// Count a creation fee paid to the treasury
+    pub fn record_creation_fee(&mut self, fee: Amount) {
+        let treasury_fees = *self.treasury_fees.get();
+        self.treasury_fees.set(treasury_fees.saturating_add(fee));
+    }
+
+    /// Count a token graduating
+    pub fn record_graduation(&mut self) {
+        let graduations = *self.graduations.get();
+        self.graduations.set(graduations + 1);
+    }
+
+    /// Whether a launch's raise and summary volumes are in the totals
+    pub async fn is_counted(&self, token_id: &str) -> Result<bool, ViewError> {
+        self.counted_tokens.contains_key(&token_id.to_string()).await
+    }
+
+    /// Add a launch's raise and latest summary to the totals
+    pub fn count_token(
+        &mut self,
+        token_id: &str,
+        total_raised: U256,
+        summary: Option<&TokenSummary>,
+    ) -> Result<(), ViewError> {
+        self.record_raise_change(U256::zero(), total_raised);
+        if let Some(summary) = summary {
+            self.record_summary_change(None, summary);
+        }
+        self.counted_tokens.insert(&token_id.to_string(), ())
+    }
+
+    /// Replace a launch's contribution to `total_raised`
+    pub fn record_raise_change(&mut self, previous: U256, current: U256) {
+        let total = *self.total_raised.get();
+        self.total_raised.set(total.saturating_sub(previous).saturating_add(current));
+    }
+
+    /// Replace a token's contribution to the summed summary volumes
+    pub fn record_summary_change(
+        &mut self,
+        previous: Option<&TokenSummary>,
+        current: &TokenSummary,
+    ) {
+        let (previous_24h, previous_7d) = previous
+            .map_or((U256::zero(), U256::zero()), |summary| {
+                (summary.volume_24h, summary.volume_7d)
+            });
+        let volume_24h = *self.summary_volume_24h.get();
+        self.summary_volume_24h
+            .set(volume_24h.saturating_sub(previous_24h).saturating_add(current.volume_24h));
+        let volume_7d = *self.summary_volume_7d.get();
+        self.summary_volume_7d
+            .set(volume_7d.saturating_sub(previous_7d).saturating_add(current.volume_7d));
+    }
+
+    /// Graduations as a share of `launches`, in basis points
+    pub fn graduation_rate_bps(&self, launches: u64) -> u64 {
+        if launches == 0 {
+            return 0;
+        }
+        (*self.graduations.get()).min(launches) * 10_000 / launches
+    }
+
+    /// Approximate number of accounts that traded on a curve or in a pool
+    ///
+    /// Never below the exact count recorded before the sketch, so the figure
+    /// does not drop while legacy traders are still being migrated.
+    pub fn unique_traders(&self) -> u64 {
+        self.trader_sketch.get().estimate().max(*self.unique_traders.get())
+    }
+
+    /// Move up to `max_entries` legacy traders into the sketch, returning how
+    /// many were moved
+    pub async fn migrate_legacy_traders(&mut self, max_entries: usize) -> Result<usize, ViewError> {
+        let traders = self.traders.indices().await?;
+        let moved = traders.len().min(max_entries);
+        for trader in traders.into_iter().take(moved) {
+            self.trader_sketch.get_mut().insert(&trader);
+            self.traders.remove(&trader)?;
+        }
+        Ok(moved)
+    }
+
+    fn record_trader(&mut self, trader: Account) {
+        self.trader_sketch.get_mut().insert(&trader);
+    }
+}
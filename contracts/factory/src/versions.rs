@@ -0,0 +1,60 @@
+use fair_launch_abi::BytecodeRelease;
+use linera_sdk::views::{MapView, RegisterView, View, ViewStorageContext};
+use linera_views::ViewError;
+
+/// Token bytecode releases and the version each launch reported running
+///
+/// Tokens announce their `contract_version` with `VersionAnnouncement` when
+/// they are initialized; launches that never announced count as version 0.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct VersionState {
+    /// Registered releases: version → release
+    pub releases: MapView<u32, BytecodeRelease>,
+
+    /// Highest registered version (0 until a release is registered)
+    pub latest: RegisterView<u32>,
+
+    /// Version each token announced: token_id → version
+    pub token_versions: MapView<String, u32>,
+}
+
+impl VersionState {
+    /// Register a release, returning false if the version already exists
+    pub async fn register(&mut self, release: BytecodeRelease) -> Result<bool, ViewError> {
+        if self.releases.contains_key(&release.version).await? {
+            return Ok(false);
+        }
+        if release.version > *self.latest.get() {
+            self.latest.set(release.version);
+        }
+        self.releases.insert(&release.version, release)?;
+        Ok(true)
+    }
+
+    /// Record the version a token announced
+    pub fn record_token(&mut self, token_id: &str, version: u32) -> Result<(), ViewError> {
+        self.token_versions.insert(&token_id.to_string(), version)
+    }
+
+    /// Version a token runs (0 if it never announced one)
+    pub async fn token_version(&self, token_id: &str) -> Result<u32, ViewError> {
+        Ok(self.token_versions.get(&token_id.to_string()).await?.unwrap_or(0))
+    }
+
+    /// Whether a token runs older bytecode than the latest release
+    pub async fn is_outdated(&self, token_id: &str) -> Result<bool, ViewError> {
+        Ok(self.token_version(token_id).await? < *self.latest.get())
+    }
+
+    /// All registered releases, oldest first
+    pub async fn list_releases(&self) -> Result<Vec<BytecodeRelease>, ViewError> {
+        let mut releases = Vec::new();
+        for version in self.releases.indices().await? {
+            if let Some(release) = self.releases.get(&version).await? {
+                releases.push(release);
+            }
+        }
+        Ok(releases)
+    }
+}
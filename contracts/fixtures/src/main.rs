@@ -0,0 +1,372 @@
+//! Deterministic test fixtures for a local Fair Launch deployment
+//!
+//! Populates a running `linera service` with synthetic launches at varying
+//! lifecycle stages (fresh, mid-curve, near-graduation, graduated). Every
+//! launch goes through the applications' real operations, scheduled with the
+//! services' `scheduleOperation` mutation, so the resulting state is exactly
+//! what users would produce by hand.
+//!
+//! Each token lives on its own chain, so pass one chain per launch:
+//!
+//! ```text
+//! cargo run -p fair-launch-fixtures -- \
+//!     --factory-app-id <ID> --token-app-id <ID> --owner <OWNER> \
+//!     --chains <CHAIN_1>,<CHAIN_2>,<CHAIN_3>,<CHAIN_4>
+//! ```
+//!
+//! The same `--seed` always produces the same launches and trades.
+
+use anyhow::{anyhow, bail, Context};
+use clap::Parser;
+use fair_launch_abi::{
+    bonding_curve, BaseAsset, BondingCurveConfig, FactoryOperation, GraduationPlan,
+    LaunchMechanism, TokenMetadata, TokenOperation,
+};
+use linera_sdk::linera_base_types::{Account, AccountOwner, ChainId};
+use primitive_types::U256;
+use serde_json::json;
+use std::str::FromStr;
+
+/// Fixture launches use a small curve so a local wallet can afford to graduate it
+/// (the full supply costs about 0.13 native tokens)
+const FIXTURE_MAX_SUPPLY: u64 = 10_000_000;
+const FIXTURE_SCALE: u64 = 5_000_000;
+const FIXTURE_K: u64 = 1;
+const FIXTURE_TARGET_RAISE: u64 = 12_000_000;
+const FIXTURE_BASE_DECIMALS: u8 = 8;
+
+/// Slippage allowance on fixture buys, in basis points
+const MAX_COST_SLIPPAGE_BPS: u64 = 100;
+
+const ADJECTIVES: &[&str] = &[
+    "Lunar", "Rapid", "Quiet", "Golden", "Frosty", "Hyper", "Silent", "Cosmic", "Lucky", "Brave",
+];
+const NOUNS: &[&str] = &[
+    "Otter", "Comet", "Falcon", "Pepper", "Mango", "Rocket", "Badger", "Nebula", "Panda", "Tiger",
+];
+
+#[derive(Parser, Debug)]
+#[command(about = "Populate a local deployment with synthetic launches")]
+struct Args {
+    /// Node service URL
+    #[arg(long, default_value = "http://localhost:8080")]
+    node_url: String,
+
+    /// Factory application ID
+    #[arg(long)]
+    factory_app_id: String,
+
+    /// Token application ID
+    #[arg(long)]
+    token_app_id: String,
+
+    /// Comma-separated chains to launch on, one token per chain
+    #[arg(long, value_delimiter = ',', required = true)]
+    chains: Vec<String>,
+
+    /// Owner signing blocks on the launch chains (becomes the creator and trader)
+    #[arg(long)]
+    owner: String,
+
+    /// Number of launches (defaults to one per chain)
+    #[arg(long)]
+    count: Option<usize>,
+
+    /// Seed for names, stages and trade sizes
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+
+    /// Print the operations instead of submitting them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Lifecycle stage a fixture launch is driven to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Fresh,
+    MidCurve,
+    NearGraduation,
+    Graduated,
+}
+
+impl Stage {
+    const ALL: [Stage; 4] = [Stage::Fresh, Stage::MidCurve, Stage::NearGraduation, Stage::Graduated];
+
+    /// Share of the max supply bought during this stage, in basis points
+    fn target_bps(self) -> u64 {
+        match self {
+            Stage::Fresh => 0,
+            Stage::MidCurve => 5_000,
+            Stage::NearGraduation => 9_500,
+            Stage::Graduated => 10_000,
+        }
+    }
+}
+
+/// Deterministic xorshift generator
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+/// Operations for one launch, in submission order
+#[derive(Debug)]
+struct LaunchPlan {
+    stage: Stage,
+    metadata: TokenMetadata,
+    curve_config: BondingCurveConfig,
+    /// (amount, max_cost) per buy
+    buys: Vec<(U256, U256)>,
+    /// Partial sell after the buys, leaving a sell in the trade history
+    sell: Option<U256>,
+}
+
+fn fixture_curve() -> BondingCurveConfig {
+    BondingCurveConfig {
+        k: U256::from(FIXTURE_K),
+        scale: U256::from(FIXTURE_SCALE),
+        target_raise: U256::from(FIXTURE_TARGET_RAISE),
+        max_supply: U256::from(FIXTURE_MAX_SUPPLY),
+        creator_fee_bps: 300,
+        min_holders: None,
+        base_decimals: FIXTURE_BASE_DECIMALS,
+        base_asset: BaseAsset::Native,
+        transfers_enabled_during_curve: true,
+        graduation_plan: GraduationPlan::default(),
+        pool_lock_micros: None,
+        whitelist_ends_at: None,
+        launch_mechanism: LaunchMechanism::BondingCurve,
+        burn_unsold_on_graduation: false,
+        min_raise: None,
+        deadline: None,
+        snipe_tax: None,
+        min_trade_interval_micros: None,
+        fee_tiers: Vec::new(),
+    }
+}
+
+/// Plan launch number `index`, cycling through the lifecycle stages
+fn plan_launch(index: usize, rng: &mut Rng) -> anyhow::Result<LaunchPlan> {
+    let stage = Stage::ALL[index % Stage::ALL.len()];
+    let adjective = ADJECTIVES[rng.below(ADJECTIVES.len() as u64) as usize];
+    let noun = NOUNS[rng.below(NOUNS.len() as u64) as usize];
+    let symbol = format!("{}{}{}", &adjective[..1], &noun[..2], index).to_uppercase();
+    let metadata = TokenMetadata {
+        name: format!("{} {} #{}", adjective, noun, index),
+        symbol,
+        description: format!("Fixture launch {} ({:?})", index, stage),
+        image_url: None,
+        twitter: None,
+        telegram: None,
+        website: None,
+    };
+
+    let curve_config = fixture_curve();
+    let target = curve_config.max_supply * U256::from(stage.target_bps()) / U256::from(10_000);
+
+    // Split the target into a few uneven buys, pricing each against the curve
+    let tranches = 1 + rng.below(5);
+    let mut buys = Vec::new();
+    let mut supply = U256::zero();
+    for tranche in 0..tranches {
+        let remaining = target - supply;
+        if remaining.is_zero() {
+            break;
+        }
+        let amount = if tranche + 1 == tranches {
+            remaining
+        } else {
+            remaining * U256::from(20 + rng.below(40)) / U256::from(100)
+        };
+        if amount.is_zero() {
+            continue;
+        }
+        let cost = bonding_curve::calculate_buy_cost(supply, amount, curve_config.k, curve_config.scale)
+            .map_err(|e| anyhow!("pricing fixture buy: {}", e))?;
+        let max_cost = cost + cost * U256::from(MAX_COST_SLIPPAGE_BPS) / U256::from(10_000) + 1;
+        buys.push((amount, max_cost));
+        supply += amount;
+    }
+
+    // Graduated curves are closed, so only open ones get a sell
+    let sell = match stage {
+        Stage::MidCurve | Stage::NearGraduation => Some(target / U256::from(20)),
+        Stage::Fresh | Stage::Graduated => None,
+    };
+
+    Ok(LaunchPlan {
+        stage,
+        metadata,
+        curve_config,
+        buys,
+        sell,
+    })
+}
+
+/// Schedules operations through the node service's application endpoints
+struct Client {
+    http: reqwest::blocking::Client,
+    node_url: String,
+    dry_run: bool,
+}
+
+impl Client {
+    fn schedule(
+        &self,
+        chain_id: ChainId,
+        application_id: &str,
+        operation: &impl serde::Serialize,
+    ) -> anyhow::Result<()> {
+        let operation_json = serde_json::to_string(operation)?;
+        if self.dry_run {
+            println!("[{}] {} <- {}", chain_id, application_id, operation_json);
+            return Ok(());
+        }
+
+        let url = format!("{}/chains/{}/applications/{}", self.node_url, chain_id, application_id);
+        let body = json!({
+            "query": "mutation($op: String!) { scheduleOperation(operationJson: $op) }",
+            "variables": { "op": operation_json },
+        });
+        let response: serde_json::Value = self
+            .http
+            .post(&url)
+            .json(&body)
+            .send()
+            .with_context(|| format!("posting to {}", url))?
+            .error_for_status()?
+            .json()?;
+        if let Some(errors) = response.get("errors") {
+            bail!("operation rejected by {}: {}", url, errors);
+        }
+        Ok(())
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let owner = AccountOwner::from_str(&args.owner).map_err(|e| anyhow!("invalid owner: {}", e))?;
+    let chains = args
+        .chains
+        .iter()
+        .map(|chain| ChainId::from_str(chain).with_context(|| format!("invalid chain {}", chain)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let count = args.count.unwrap_or(chains.len());
+    if count > chains.len() {
+        bail!("{} launches need {} chains, got {}", count, count, chains.len());
+    }
+
+    let client = Client {
+        http: reqwest::blocking::Client::new(),
+        node_url: args.node_url.trim_end_matches('/').to_string(),
+        dry_run: args.dry_run,
+    };
+    let mut rng = Rng::new(args.seed);
+
+    for (index, chain_id) in chains.into_iter().take(count).enumerate() {
+        let plan = plan_launch(index, &mut rng)?;
+        let creator = Account { chain_id, owner };
+        println!("Launch {} on {}: {} ({:?})", index, chain_id, plan.metadata.name, plan.stage);
+
+        client.schedule(
+            chain_id,
+            &args.factory_app_id,
+            &FactoryOperation::CreateToken {
+                metadata: plan.metadata.clone(),
+                curve_config: Some(plan.curve_config.clone()),
+                curve_template: None,
+            },
+        )?;
+        client.schedule(
+            chain_id,
+            &args.token_app_id,
+            &TokenOperation::Initialize {
+                creator,
+                metadata: plan.metadata.clone(),
+                curve_config: plan.curve_config.clone(),
+            },
+        )?;
+        for (amount, max_cost) in &plan.buys {
+            client.schedule(
+                chain_id,
+                &args.token_app_id,
+                &TokenOperation::Buy {
+                    amount: *amount,
+                    max_cost: *max_cost,
+                    fill_or_cap: true,
+                    memo: Some("fixture".to_string()),
+                    referrer: None,
+                    expected_supply: None,
+                },
+            )?;
+        }
+        if let Some(amount) = plan.sell {
+            client.schedule(
+                chain_id,
+                &args.token_app_id,
+                &TokenOperation::Sell {
+                    amount,
+                    min_return: U256::zero(),
+                    memo: Some("fixture".to_string()),
+                    expected_supply: None,
+                },
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plans_are_deterministic() {
+        let plans = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..8)
+                .map(|index| plan_launch(index, &mut rng).unwrap())
+                .map(|plan| (plan.metadata.name, plan.buys))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(plans(7), plans(7));
+        assert_ne!(plans(7), plans(8));
+    }
+
+    #[test]
+    fn test_plans_reach_their_stage() {
+        let mut rng = Rng::new(42);
+        for index in 0..8 {
+            let plan = plan_launch(index, &mut rng).unwrap();
+            let bought = plan.buys.iter().fold(U256::zero(), |total, (amount, _)| total + *amount);
+            let target = plan.curve_config.max_supply * U256::from(plan.stage.target_bps())
+                / U256::from(10_000);
+            assert_eq!(bought, target, "launch {}", index);
+            assert_eq!(plan.stage, Stage::ALL[index % 4]);
+        }
+    }
+
+    #[test]
+    fn test_fixture_curve_can_graduate() {
+        let curve = fixture_curve();
+        let full_cost =
+            bonding_curve::calculate_buy_cost(U256::zero(), curve.max_supply, curve.k, curve.scale).unwrap();
+        assert!(full_cost >= curve.target_raise);
+    }
+}
@@ -1,17 +1,33 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
 mod state;
-use fair_launch_abi::{Message, SwapAbi, SwapOperation};
+use fair_launch_abi::{
+    amm, buyback, graduation,
+    logging::{self, Logger},
+    units, wad, BaseAsset, BuybackConfig, FlashSwapCallback, FlashSwapCalleeAbi, Message, Price,
+    StableLeg, SwapAbi, SwapOperation, SwapParameters, TransferReceipt,
+};
 use linera_sdk::{
     abi::WithContractAbi,
-    linera_base_types::ChainId,
-    views::View,
+    abis::fungible::{FungibleOperation, FungibleResponse, FungibleTokenAbi},
+    linera_base_types::{
+        Account, AccountOwner, Amount, ApplicationId, ChainId, TimeDelta, Timestamp,
+    },
+    views::{RootView, View},
     Contract, ContractRuntime,
 };
+use linera_views::ViewError;
 use primitive_types::U256;
+use std::collections::BTreeMap;
 use thiserror::Error;
 
-use crate::state::SwapState;
+use crate::state::{
+    PoolBuyback, PoolInfo, PoolLock, PoolQuote, SwapState, SwapTrade, TokenSupply,
+    UnlockedLiquidity,
+};
+
+/// Version of this swap bytecode, recorded in `contract_version`
+const CONTRACT_VERSION: u32 = 1;
 
 #[derive(Debug, Error)]
 pub enum SwapError {
@@ -21,20 +37,107 @@ pub enum SwapError {
     #[error("Pool is locked")]
     PoolLocked,
 
+    #[error("Only the pool's creator may withdraw its liquidity")]
+    NotPoolCreator,
+
     #[error("Insufficient liquidity")]
     InsufficientLiquidity,
 
     #[error("Slippage exceeded: got {got}, min {min}")]
     SlippageExceeded { got: U256, min: U256 },
 
+    #[error("Slippage exceeded: requires {required}, max {max}")]
+    ExcessiveInput { required: U256, max: U256 },
+
     #[error("Invalid amount: must be greater than zero")]
     InvalidAmount,
+
+    #[error("Invalid swap path: {0}")]
+    InvalidPath(String),
+
+    #[error("Missing liquidity transfer receipt")]
+    MissingReceipt,
+
+    #[error("Liquidity transfer receipt addressed to {0}, not this application")]
+    WrongRecipient(Account),
+
+    #[error("Liquidity not received: receipt claims {claimed}, uncommitted balance {available}")]
+    LiquidityNotReceived { claimed: Amount, available: Amount },
+
+    #[error("Token {token_id} already has a pool in {base_asset}")]
+    PoolExists { token_id: String, base_asset: BaseAsset },
+
+    #[error("Pool creation failed: {0}")]
+    PoolCreation(String),
+
+    #[error("Deposited {deposited} pool tokens, pool needs {required}")]
+    InsufficientPoolTokens { deposited: U256, required: U256 },
+
+    #[error("Creating a pool requires an authenticated signer")]
+    NotAuthenticated,
+
+    #[error("Only the treasury admin may manage the treasury")]
+    NotTreasuryAdmin,
+
+    #[error("The treasury already has an admin")]
+    TreasuryAdminSet,
+
+    #[error("Treasury holds {available}, cannot withdraw {requested}")]
+    InsufficientTreasury { requested: Amount, available: Amount },
+
+    #[error("Buyback-and-burn is not configured")]
+    BuybackDisabled,
+
+    #[error("No buyback is due for this pool")]
+    BuybackNotDue,
+
+    #[error("Flash swaps are unavailable for pool {0}")]
+    FlashSwapUnavailable(String),
+
+    #[error("Pool {0} lends only its base currency in flash swaps")]
+    FlashSwapTokenBorrow(String),
+
+    #[error("Flash swap not repaid: {due} due, {received} received")]
+    FlashSwapNotRepaid { due: Amount, received: Amount },
+
+    #[error("Swap arithmetic overflow")]
+    CurveOverflow,
+
+    #[error(transparent)]
+    View(#[from] ViewError),
+
+    #[error(transparent)]
+    Units(#[from] units::UnitsError),
+}
+
+/// Outcome of an exact-input route over working copies of its pools
+struct Route {
+    amount_out: U256,
+    /// Base currency moved across all hops
+    base_volume: U256,
+    /// Trades to record in the pools' histories: (pool_id, sequence, trade)
+    trades: Vec<(String, u64, SwapTrade)>,
+    /// Swap fees charged along the way: (pool_id, paid in tokens, fee)
+    fees: Vec<(String, bool, U256)>,
+}
+
+/// Stable leg of a graduation whose stablecoin has arrived
+struct VerifiedStableLeg {
+    asset: BaseAsset,
+    decimals: u8,
+    received: Amount,
+    /// Stablecoin pooled, in stable base units
+    base_liquidity: U256,
+    /// Tokens matched to the stablecoin at `opening_price`
+    token_liquidity: U256,
+    opening_price: Price,
 }
 
 /// Swap contract - creates and manages locked liquidity pools for graduated tokens
 pub struct SwapContract {
     state: SwapState,
     runtime: ContractRuntime<Self>,
+    logger: Logger,
 }
 
 linera_sdk::contract!(SwapContract);
@@ -46,14 +149,18 @@ impl WithContractAbi for SwapContract {
 impl Contract for SwapContract {
     type Message = Message;
     type InstantiationArgument = ();
-    type Parameters = ();
+    type Parameters = SwapParameters;
     type EventValue = ();
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = SwapState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load swap state");
-        SwapContract { state, runtime }
+        SwapContract {
+            state,
+            runtime,
+            logger: Logger::new(logging::SWAP_TARGET),
+        }
     }
 
     async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
@@ -62,6 +169,11 @@ impl Contract for SwapContract {
             .initialize(created_at)
             .await
             .expect("Failed to initialize swap contract");
+        self.state.contract_version.set(CONTRACT_VERSION);
+
+        // The instantiating account manages the treasury
+        let admin = self.trader_account();
+        self.state.treasury_admin.set(Some(admin));
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
@@ -85,18 +197,220 @@ impl Contract for SwapContract {
                     .await
                     .expect("Swap failed");
             }
+            SwapOperation::SwapExactOut {
+                pool_id,
+                token_out,
+                amount_out,
+                max_in,
+            } => {
+                self.execute_swap_exact_out(pool_id, token_out, amount_out, max_in)
+                    .await
+                    .expect("Swap failed");
+            }
+            SwapOperation::SwapExactTokensForTokens {
+                path,
+                amount_in,
+                min_out,
+            } => {
+                let route = path.join(" -> ");
+                let amount_out = self
+                    .execute_swap_exact_tokens_for_tokens(path, amount_in, min_out)
+                    .await
+                    .expect("Routed swap failed");
+                self.logger.info(format!(
+                    "Routed swap {}: {} in, {} out",
+                    route, amount_in, amount_out
+                ));
+            }
+            SwapOperation::WithdrawUnlockedLiquidity { pool_id } => {
+                let unlocked = self
+                    .withdraw_unlocked_liquidity(pool_id.clone())
+                    .await
+                    .expect("Withdrawal failed");
+                self.logger.info(format!(
+                    "Pool {} unlocked: {} {} and {} tokens returned to {}",
+                    pool_id,
+                    unlocked.base_amount,
+                    unlocked.base_asset,
+                    unlocked.token_amount,
+                    unlocked.creator
+                ));
+            }
+            SwapOperation::ExecuteBuyback { pool_id } => {
+                self.execute_buyback(pool_id).await.expect("Buyback failed");
+            }
+            SwapOperation::WithdrawTreasury { to, amount } => {
+                self.withdraw_treasury(to, amount).expect("Treasury withdrawal failed");
+                self.logger.info(format!("Treasury paid {} to {}", amount, to));
+            }
+            SwapOperation::CreatePool {
+                token_id,
+                base_asset,
+                token_amount,
+                base_amount,
+                base_decimals,
+            } => {
+                let pool = self
+                    .execute_create_pool(
+                        token_id,
+                        base_asset,
+                        token_amount,
+                        base_amount,
+                        base_decimals,
+                    )
+                    .await
+                    .expect("Pool creation failed");
+                self.logger.info(format!(
+                    "Pool {} opened in {} with {} tokens and {} base currency",
+                    pool.pool_id, base_asset, pool.token_liquidity, pool.base_liquidity
+                ));
+            }
+            SwapOperation::SetTreasury { account } => {
+                self.set_treasury(account).expect("Failed to set treasury admin");
+                self.logger.info(format!("Treasury now managed by {}", account));
+            }
+            SwapOperation::ClaimTreasury => {
+                let admin = self.claim_treasury().expect("Failed to claim the treasury");
+                self.logger.info(format!("Treasury now managed by {}", admin));
+            }
+            SwapOperation::MigratePools { max_entries } => {
+                let migrated = self
+                    .state
+                    .migrate_asset_pools(max_entries as usize)
+                    .await
+                    .expect("Pool migration failed");
+                self.logger.info(format!("Indexed the pools of {} legacy tokens", migrated));
+            }
+            SwapOperation::BackfillLockedTotals { max_entries } => {
+                let added = self
+                    .state
+                    .backfill_locked_totals(max_entries as usize)
+                    .await
+                    .expect("Locked totals backfill failed");
+                self.logger.info(format!("Added {} locked pools to the lock totals", added));
+            }
+            SwapOperation::FlashSwap {
+                pool_id,
+                token_out,
+                amount_out,
+                callee,
+                data,
+            } => {
+                let due = self
+                    .execute_flash_swap(pool_id.clone(), token_out, amount_out, callee, data)
+                    .await
+                    .expect("Flash swap failed");
+                self.logger.info(format!(
+                    "Flash swap on {}: {} out, {} repaid by {}",
+                    pool_id, amount_out, due, callee
+                ));
+            }
         }
     }
 
     async fn execute_message(&mut self, message: Self::Message) {
+        if self.runtime.message_is_bouncing() == Some(true) {
+            self.handle_bounced_message(message).await;
+            return;
+        }
+
         match message {
             Message::GraduateToken {
                 token_id,
                 total_supply,
                 total_raised,
+                receipt,
+                base_decimals,
+                base_asset,
+                stable_leg,
+                lock_duration_micros,
+                creator,
+                final_price,
             } => {
-                self.handle_graduation(token_id, total_supply, total_raised)
-                    .await;
+                // Buybacks burn on the token's chain
+                if let Some(token_chain) = self.runtime.message_origin_chain_id() {
+                    self.state
+                        .token_chains
+                        .insert(&token_id, token_chain)
+                        .expect("Failed to record token chain");
+                }
+
+                // A time lock needs someone to return the liquidity to
+                let created_at = self.runtime.system_time();
+                let lock = match (lock_duration_micros, creator) {
+                    (Some(micros), Some(creator)) => {
+                        Some((creator, created_at.saturating_add(TimeDelta::from_micros(micros))))
+                    }
+                    _ => None,
+                };
+                self.handle_graduation(
+                    token_id,
+                    total_supply,
+                    total_raised,
+                    receipt,
+                    base_decimals,
+                    base_asset,
+                    stable_leg,
+                    lock,
+                    final_price,
+                )
+                .await;
+            }
+
+            Message::SupplyChanged {
+                token_id,
+                total_supply,
+                circulating_supply,
+                reported_at,
+            } => {
+                let supply = TokenSupply {
+                    total_supply,
+                    circulating_supply,
+                    reported_at,
+                };
+                match self.state.record_supply(&token_id, supply).await {
+                    Ok(true) => self.logger.info(format!(
+                        "Token {} supply now {} ({} circulating)",
+                        token_id, total_supply, circulating_supply
+                    )),
+                    Ok(false) => self.logger.info(format!(
+                        "Ignoring stale supply report for token {}",
+                        token_id
+                    )),
+                    Err(e) => self.logger.error(format!(
+                        "Failed to record supply change for token {}: {}",
+                        token_id, e
+                    )),
+                }
+            }
+
+            Message::PoolTokensDeposited {
+                token_id,
+                depositor,
+                amount,
+            } => {
+                // Only the token's own chain can vouch for a deposit
+                let token_chain = self
+                    .state
+                    .token_chains
+                    .get(&token_id)
+                    .await
+                    .expect("Failed to read token chain");
+                if token_chain.is_none() || token_chain != self.runtime.message_origin_chain_id() {
+                    panic!("Pool token deposit for {} not sent from its chain", token_id);
+                }
+                let deposited = self
+                    .state
+                    .pool_tokens_deposited(&token_id, depositor.owner)
+                    .await
+                    .expect("Failed to read pool token deposit");
+                self.state
+                    .set_pool_tokens_deposited(&token_id, depositor.owner, deposited + amount)
+                    .expect("Failed to record pool token deposit");
+                self.logger.info(format!(
+                    "{} deposited {} {} tokens for new pools",
+                    depositor, amount, token_id
+                ));
             }
 
             _ => {
@@ -105,28 +419,42 @@ impl Contract for SwapContract {
         }
     }
 
-    async fn store(self) {
-        // State is automatically persisted by linera-views
+    async fn store(mut self) {
+        for event in self.logger.flush(self.runtime.system_time()) {
+            self.state
+                .critical_events
+                .push(event)
+                .expect("Failed to mirror critical event");
+        }
+        self.state.bump_version().await;
+        self.state.save().await.expect("Failed to save state");
     }
 }
 
 impl SwapContract {
     /// Handle token graduation - create locked liquidity pool
+    #[allow(clippy::too_many_arguments)]
     async fn handle_graduation(
         &mut self,
         token_id: String,
         total_supply: U256,
         total_raised: U256,
+        receipt: Option<TransferReceipt>,
+        base_decimals: u8,
+        base_asset: BaseAsset,
+        stable_leg: Option<StableLeg>,
+        lock: Option<(Account, Timestamp)>,
+        final_price: Option<Price>,
     ) {
         // Log graduation event
-        self.log_event(&format!(
+        self.logger.info(format!(
             "Graduation request received for token {}",
             token_id
         ));
 
         // Validate inputs
         if total_supply == U256::zero() {
-            self.log_error(&format!(
+            self.logger.error(format!(
                 "Invalid graduation: token {} has zero supply",
                 token_id
             ));
@@ -134,7 +462,7 @@ impl SwapContract {
         }
 
         if total_raised == U256::zero() {
-            self.log_error(&format!(
+            self.logger.error(format!(
                 "Invalid graduation: token {} has zero raised amount",
                 token_id
             ));
@@ -144,20 +472,30 @@ impl SwapContract {
         // Check if pool already exists (idempotency check)
         match self.state.has_pool(&token_id).await {
             Ok(true) => {
-                self.log_event(&format!(
+                self.logger.info(format!(
                     "Pool already exists for token {}, ignoring duplicate graduation",
                     token_id
                 ));
 
-                // Still send PoolCreated message back (idempotent)
+                // Still send PoolCreated message(s) back (idempotent)
+                let chain_id = self.runtime.chain_id();
                 if let Ok(Some(pool)) = self.state.get_pool_by_token(&token_id).await {
-                    let chain_id = self.runtime.chain_id();
-                    self.send_pool_created_message(token_id, pool.pool_id, chain_id);
+                    self.send_pool_created_message(
+                        token_id.clone(),
+                        &pool,
+                        chain_id,
+                        false,
+                        U256::zero(),
+                    );
+                }
+                let stable_key = graduation::stable_pool_key(&token_id);
+                if let Ok(Some(pool)) = self.state.get_pool_by_token(&stable_key).await {
+                    self.send_pool_created_message(token_id, &pool, chain_id, true, U256::zero());
                 }
                 return;
             }
             Err(e) => {
-                self.log_error(&format!(
+                self.logger.error(format!(
                     "Failed to check pool existence for token {}: {}",
                     token_id, e
                 ));
@@ -166,25 +504,101 @@ impl SwapContract {
             _ => {}
         }
 
-        // Create pool
+        // Verify the raised funds actually arrived before pooling them
+        let verified = self.verify_liquidity_receipt(receipt, base_asset).await.and_then(|received| {
+            Ok((received, Self::amount_to_u256(received, base_decimals)?))
+        });
+        let (received, base_liquidity) = match verified {
+            Ok(verified) => verified,
+            Err(e) => {
+                self.logger.critical(format!(
+                    "Rejecting graduation for token {} (declared raise {}): {}",
+                    token_id, total_raised, e
+                ));
+                return;
+            }
+        };
+
+        // Verify the stable leg before its tokens come out of the native pool's
+        // share; a leg that fails leaves a single native pool
+        let stable_leg = match stable_leg {
+            Some(leg) => self.verify_stable_leg(&token_id, leg, total_supply).await,
+            None => None,
+        };
+        let native_supply = match &stable_leg {
+            Some(leg) => total_supply - leg.token_liquidity,
+            None => total_supply,
+        };
+
+        // Open at the launch's last price rather than its average, leaving the
+        // tokens that would undercut it out of the pool
+        let matched = match final_price {
+            Some(price) => graduation::price_matched_supply(native_supply, base_liquidity, price),
+            None => Ok((native_supply, U256::zero())),
+        };
+        let (native_supply, excess) = match matched {
+            Ok(split) => split,
+            Err(e) => {
+                self.logger.critical(format!(
+                    "Rejecting graduation for token {}: {}",
+                    token_id, e
+                ));
+                return;
+            }
+        };
+
+        // Create pool backed by the received liquidity
         let created_at = self.runtime.system_time();
+        let quote = PoolQuote {
+            base_asset,
+            base_decimals,
+        };
         match self
             .state
-            .create_pool(token_id.clone(), total_supply, total_raised, created_at)
+            .create_pool_in(token_id.clone(), quote, native_supply, base_liquidity, created_at)
             .await
         {
             Ok(pool) => {
-                self.log_event(&format!(
-                    "Pool created successfully: {} for token {} with {} tokens and {} base currency (locked permanently)",
-                    pool.pool_id, token_id, total_supply, total_raised
+                self.state
+                    .commit_liquidity(base_asset, received)
+                    .await
+                    .expect("Failed to commit pool liquidity");
+                let pool = self.apply_time_lock(pool, lock, base_asset, received).await;
+
+                let lock_status = match pool.lock_expires_at {
+                    Some(expires_at) => format!("locked until {}", expires_at.micros()),
+                    None => "locked permanently".to_string(),
+                };
+                self.logger.info(format!(
+                    "Pool created successfully: {} for token {} with {} tokens and {} base currency ({})",
+                    pool.pool_id, token_id, native_supply, base_liquidity, lock_status
                 ));
+                if !excess.is_zero() {
+                    self.logger.info(format!(
+                        "Left {} tokens of {} out of {} to open at the final launch price",
+                        excess, token_id, pool.pool_id
+                    ));
+                }
+                if let Some(price) = final_price {
+                    let deviation = graduation::price_deviation_bps(pool.initial_ratio, price);
+                    if deviation > graduation::PRICE_TOLERANCE_BPS {
+                        self.logger.warn(format!(
+                            "Pool {} opened at {}, {} bps from the final launch price {}",
+                            pool.pool_id, pool.initial_ratio, deviation, price
+                        ));
+                    }
+                }
 
-                // Send PoolCreated message back to token contract
+                // Send PoolCreated message back to token contract, which burns the excess
                 let chain_id = self.runtime.chain_id();
-                self.send_pool_created_message(token_id, pool.pool_id, chain_id);
+                self.send_pool_created_message(token_id.clone(), &pool, chain_id, false, excess);
+
+                if let Some(leg) = stable_leg {
+                    self.create_stable_pool(token_id, leg, created_at, lock).await;
+                }
             }
             Err(e) => {
-                self.log_error(&format!(
+                self.logger.critical(format!(
                     "Failed to create pool for token {}: {}",
                     token_id, e
                 ));
@@ -192,6 +606,452 @@ impl SwapContract {
         }
     }
 
+    /// Open a pool pairing a graduated token with another base asset
+    ///
+    /// Anyone may open one per asset. The base side is taken from the signer
+    /// and, as with graduation pools, locked permanently.
+    async fn execute_create_pool(
+        &mut self,
+        token_id: String,
+        base_asset: BaseAsset,
+        token_amount: U256,
+        base_amount: Amount,
+        base_decimals: u8,
+    ) -> Result<PoolInfo, SwapError> {
+        if token_amount.is_zero() || base_amount == Amount::ZERO {
+            return Err(SwapError::InvalidAmount);
+        }
+        let base_liquidity = Self::amount_to_u256(base_amount, base_decimals)?;
+        if base_liquidity.is_zero() {
+            return Err(SwapError::InvalidAmount);
+        }
+        if !self.state.has_pool(&token_id).await.unwrap_or(false) {
+            return Err(SwapError::PoolNotFound(token_id));
+        }
+        let exists = self
+            .state
+            .has_asset_pool(&token_id, &base_asset)
+            .await
+            .map_err(|e| SwapError::PoolCreation(e.to_string()))?;
+        if exists {
+            return Err(SwapError::PoolExists { token_id, base_asset });
+        }
+        let signer = self
+            .runtime
+            .authenticated_signer()
+            .ok_or(SwapError::NotAuthenticated)?;
+
+        // The token side comes out of what the signer deposited on the token chain
+        let deposited = self.state.pool_tokens_deposited(&token_id, signer).await?;
+        if deposited < token_amount {
+            return Err(SwapError::InsufficientPoolTokens {
+                deposited,
+                required: token_amount,
+            });
+        }
+        self.state
+            .set_pool_tokens_deposited(&token_id, signer, deposited - token_amount)?;
+
+        // Take the base side from the signer before the pool exists
+        let application = self.application_account();
+        match base_asset {
+            BaseAsset::Native => self.runtime.transfer(signer, application, base_amount),
+            BaseAsset::Fungible(application_id) => {
+                let call = FungibleOperation::Transfer {
+                    owner: signer,
+                    amount: base_amount,
+                    target_account: application,
+                };
+                self.runtime
+                    .call_application(true, application_id.with_abi::<FungibleTokenAbi>(), &call);
+            }
+        }
+        self.state.commit_liquidity(base_asset, base_amount).await?;
+
+        let now = self.runtime.system_time();
+        let quote = PoolQuote {
+            base_asset,
+            base_decimals,
+        };
+        self.state
+            .create_asset_pool(&token_id, quote, token_amount, base_liquidity, now)
+            .await
+            .map_err(|e| SwapError::PoolCreation(e.to_string()))
+    }
+
+    /// Check a graduation's stable leg and size its token side so the stable
+    /// pool opens at the leg's opening price
+    ///
+    /// Tokens the stablecoin cannot match stay with the native pool. Returns
+    /// None, logging why, when the leg cannot be used.
+    async fn verify_stable_leg(
+        &mut self,
+        token_id: &str,
+        leg: StableLeg,
+        total_supply: U256,
+    ) -> Option<VerifiedStableLeg> {
+        let asset = BaseAsset::Fungible(leg.asset);
+        let verified = self
+            .verify_liquidity_receipt(Some(leg.receipt), asset)
+            .await
+            .and_then(|received| Ok((received, Self::amount_to_u256(received, leg.decimals)?)))
+            .and_then(|(received, base_liquidity)| {
+                let (token_liquidity, _) = graduation::price_matched_supply(
+                    leg.token_liquidity,
+                    base_liquidity,
+                    leg.opening_price,
+                )
+                .map_err(|e| SwapError::PoolCreation(e.to_string()))?;
+                Ok((received, base_liquidity, token_liquidity))
+            });
+        match verified {
+            Ok((received, base_liquidity, token_liquidity)) if token_liquidity < total_supply => {
+                Some(VerifiedStableLeg {
+                    asset,
+                    decimals: leg.decimals,
+                    received,
+                    base_liquidity,
+                    token_liquidity,
+                    opening_price: leg.opening_price,
+                })
+            }
+            Ok(_) => {
+                self.logger.critical(format!(
+                    "Skipping stable pool for token {}: it would take the whole supply",
+                    token_id
+                ));
+                None
+            }
+            Err(e) => {
+                self.logger.critical(format!(
+                    "Skipping stable pool for token {}: {}",
+                    token_id, e
+                ));
+                None
+            }
+        }
+    }
+
+    /// Create the stablecoin-quoted pool from a graduation's verified stable leg
+    async fn create_stable_pool(
+        &mut self,
+        token_id: String,
+        leg: VerifiedStableLeg,
+        created_at: Timestamp,
+        lock: Option<(Account, Timestamp)>,
+    ) {
+        let asset = leg.asset;
+        let stable_key = graduation::stable_pool_key(&token_id);
+        let quote = PoolQuote {
+            base_asset: asset,
+            base_decimals: leg.decimals,
+        };
+        match self
+            .state
+            .create_pool_in(stable_key, quote, leg.token_liquidity, leg.base_liquidity, created_at)
+            .await
+        {
+            Ok(pool) => {
+                self.state
+                    .commit_liquidity(asset, leg.received)
+                    .await
+                    .expect("Failed to commit stable pool liquidity");
+                let pool = self.apply_time_lock(pool, lock, asset, leg.received).await;
+
+                self.logger.info(format!(
+                    "Stable pool created: {} for token {} with {} tokens and {} {}",
+                    pool.pool_id, token_id, leg.token_liquidity, leg.base_liquidity, asset
+                ));
+                // A deposit too large for the token share opens above the target
+                let deviation =
+                    graduation::price_deviation_bps(pool.initial_ratio, leg.opening_price);
+                if deviation > graduation::PRICE_TOLERANCE_BPS {
+                    self.logger.warn(format!(
+                        "Stable pool {} opened at {}, {} bps from the matched price {}",
+                        pool.pool_id, pool.initial_ratio, deviation, leg.opening_price
+                    ));
+                }
+
+                let chain_id = self.runtime.chain_id();
+                self.send_pool_created_message(token_id, &pool, chain_id, true, U256::zero());
+            }
+            Err(e) => {
+                self.logger.critical(format!(
+                    "Failed to create stable pool for token {}: {}",
+                    token_id, e
+                ));
+            }
+        }
+    }
+
+    /// Put a new pool under the launch's time lock, if it chose one
+    async fn apply_time_lock(
+        &mut self,
+        pool: PoolInfo,
+        lock: Option<(Account, Timestamp)>,
+        base_asset: BaseAsset,
+        amount: Amount,
+    ) -> PoolInfo {
+        let Some((creator, expires_at)) = lock else {
+            return pool;
+        };
+        let lock = PoolLock {
+            creator,
+            base_asset,
+            amount,
+        };
+        self.state
+            .lock_pool(&pool.pool_id, lock, expires_at)
+            .await
+            .expect("Failed to lock pool")
+    }
+
+    /// Return a time-locked pool's reserves to its creator after expiry
+    ///
+    /// The base reserve is paid from this application; the token reserve is
+    /// credited on the token's chain.
+    async fn withdraw_unlocked_liquidity(
+        &mut self,
+        pool_id: String,
+    ) -> Result<UnlockedLiquidity, SwapError> {
+        let lock = self
+            .state
+            .pool_locks
+            .get(&pool_id)
+            .await?
+            .ok_or(SwapError::PoolLocked)?;
+        if self.runtime.authenticated_signer() != Some(lock.creator.owner) {
+            return Err(SwapError::NotPoolCreator);
+        }
+
+        let token_id = self
+            .state
+            .get_pool(&pool_id)
+            .await?
+            .map(|pool| graduation::pool_key_token_id(&pool.token_id).to_string())
+            .ok_or_else(|| SwapError::PoolNotFound(pool_id.clone()))?;
+
+        let now = self.runtime.system_time();
+        let unlocked = self
+            .state
+            .unlock_pool(&pool_id, now)
+            .await
+            .map_err(|_| SwapError::PoolLocked)?;
+
+        if unlocked.base_amount > Amount::ZERO {
+            self.send_base(unlocked.base_asset, unlocked.creator, unlocked.base_amount);
+        }
+        if !unlocked.token_amount.is_zero() {
+            let chain_id = self.token_chain(&token_id).await?;
+            self.runtime
+                .prepare_message(Message::PoolTokensWithdrawn {
+                    token_id,
+                    pool_id,
+                    creator: unlocked.creator,
+                    amount: unlocked.token_amount,
+                })
+                .with_tracking()
+                .send_to(chain_id);
+        }
+        Ok(unlocked)
+    }
+
+    /// Native balance of the application not backing any pool nor reserved
+    /// for buybacks: protocol fees and anything else paid to the application
+    /// outside a graduation
+    fn treasury_balance(&mut self) -> Amount {
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        self.runtime
+            .owner_balance(application_owner)
+            .saturating_sub(*self.state.committed_liquidity.get())
+            .saturating_sub(*self.state.buyback_reserved.get())
+    }
+
+    fn authorize_treasury_admin(&mut self) -> Result<(), SwapError> {
+        let caller = self.trader_account();
+        if self.state.treasury_admin.get().as_ref() != Some(&caller) {
+            return Err(SwapError::NotTreasuryAdmin);
+        }
+        Ok(())
+    }
+
+    /// Pay treasury funds to `to`, never touching pool liquidity
+    fn withdraw_treasury(&mut self, to: Account, amount: Amount) -> Result<(), SwapError> {
+        self.authorize_treasury_admin()?;
+        if amount == Amount::ZERO {
+            return Err(SwapError::InvalidAmount);
+        }
+        let available = self.treasury_balance();
+        if amount > available {
+            return Err(SwapError::InsufficientTreasury {
+                requested: amount,
+                available,
+            });
+        }
+
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        self.runtime.transfer(application_owner, to, amount);
+        let now = self.runtime.system_time();
+        self.state.record_treasury_withdrawal(to, amount, now)?;
+        Ok(())
+    }
+
+    /// Hand treasury management to `account`
+    fn set_treasury(&mut self, account: Account) -> Result<(), SwapError> {
+        self.authorize_treasury_admin()?;
+        let now = self.runtime.system_time();
+        self.state.set_treasury_admin(account, now)?;
+        Ok(())
+    }
+
+    /// Make the caller treasury admin of an application instantiated before
+    /// the admin was recorded, returning the new admin
+    fn claim_treasury(&mut self) -> Result<Account, SwapError> {
+        if self.state.treasury_admin.get().is_some() {
+            return Err(SwapError::TreasuryAdminSet);
+        }
+        if self.runtime.authenticated_signer().is_none()
+            || self.runtime.chain_id() != self.runtime.application_creator_chain_id()
+        {
+            return Err(SwapError::NotTreasuryAdmin);
+        }
+        let caller = self.trader_account();
+        let now = self.runtime.system_time();
+        self.state.set_treasury_admin(caller, now)?;
+        Ok(caller)
+    }
+
+    /// Record a tracked message that was rejected by its receiver
+    async fn handle_bounced_message(&mut self, message: Message) {
+        let now = self.runtime.system_time();
+        match message {
+            Message::PoolCreated { token_id, pool_id, .. } => {
+                // The pool stays valid; a graduation retry resends PoolCreated
+                if let Err(e) = self
+                    .state
+                    .record_bounce(
+                        "PoolCreated",
+                        &token_id,
+                        now,
+                        "Pool kept; resent on the next graduation retry",
+                    )
+                    .await
+                {
+                    self.logger.error(format!(
+                        "Failed to record bounced PoolCreated for token {}: {}",
+                        token_id, e
+                    ));
+                }
+
+                self.logger.error(format!(
+                    "PoolCreated for pool {} bounced from token {}",
+                    pool_id, token_id
+                ));
+            }
+
+            Message::BurnTokens { token_id, pool_id, amount } => {
+                // The tokens already left the pool; the burn is only missing
+                // from the token's reported supply
+                self.logger.critical(format!(
+                    "Burn of {} tokens bought back by pool {} bounced from token {}",
+                    amount, pool_id, token_id
+                ));
+            }
+
+            Message::PoolTokensWithdrawn { token_id, pool_id, creator, amount } => {
+                // The pool is already emptied; the creator was not credited
+                self.logger.critical(format!(
+                    "{} tokens withdrawn from pool {} for {} bounced from token {}",
+                    amount, pool_id, creator, token_id
+                ));
+            }
+
+            _ => {
+                // Only PoolCreated, BurnTokens and PoolTokensWithdrawn are sent
+                // with tracking from the swap contract
+            }
+        }
+    }
+
+    /// Check that a graduation transfer targets this application and has been credited
+    async fn verify_liquidity_receipt(
+        &mut self,
+        receipt: Option<TransferReceipt>,
+        base_asset: BaseAsset,
+    ) -> Result<Amount, SwapError> {
+        let receipt = receipt.ok_or(SwapError::MissingReceipt)?;
+
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        let expected = Account {
+            chain_id: self.runtime.chain_id(),
+            owner: application_owner,
+        };
+        if receipt.to != expected {
+            return Err(SwapError::WrongRecipient(receipt.to));
+        }
+        if receipt.amount == Amount::ZERO {
+            return Err(SwapError::InvalidAmount);
+        }
+
+        // Funds already backing other pools cannot be claimed twice
+        let balance = self.application_balance(base_asset);
+        let committed = match base_asset {
+            BaseAsset::Native => *self.state.committed_liquidity.get(),
+            BaseAsset::Fungible(application_id) => {
+                self.state.committed_fungible(application_id).await?
+            }
+        };
+        let available = balance.saturating_sub(committed);
+        if available < receipt.amount {
+            return Err(SwapError::LiquidityNotReceived {
+                claimed: receipt.amount,
+                available,
+            });
+        }
+
+        Ok(receipt.amount)
+    }
+
+    /// This application's balance of `asset`
+    fn application_balance(&mut self, asset: BaseAsset) -> Amount {
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        match asset {
+            BaseAsset::Native => self.runtime.owner_balance(application_owner),
+            BaseAsset::Fungible(application_id) => {
+                let call = FungibleOperation::Balance { owner: application_owner };
+                match self
+                    .runtime
+                    .call_application(true, application_id.with_abi::<FungibleTokenAbi>(), &call)
+                {
+                    FungibleResponse::Balance(balance) => balance,
+                    _ => Amount::ZERO,
+                }
+            }
+        }
+    }
+
+    /// Pay `amount` of `asset` from this application to `to`
+    fn send_base(&mut self, asset: BaseAsset, to: Account, amount: Amount) {
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        match asset {
+            BaseAsset::Native => self.runtime.transfer(application_owner, to, amount),
+            BaseAsset::Fungible(application_id) => {
+                let call = FungibleOperation::Transfer {
+                    owner: application_owner,
+                    amount,
+                    target_account: to,
+                };
+                self.runtime
+                    .call_application(true, application_id.with_abi::<FungibleTokenAbi>(), &call);
+            }
+        }
+    }
+
+    /// Convert a native Amount into curve base units
+    fn amount_to_u256(amount: Amount, base_decimals: u8) -> Result<U256, SwapError> {
+        Ok(units::from_amount(amount, base_decimals)?)
+    }
+
     /// Add liquidity to an existing pool
     async fn add_liquidity(
         &mut self,
@@ -224,10 +1084,14 @@ impl SwapContract {
     }
 
     /// Execute a swap using constant product AMM formula
+    ///
+    /// `token_in` selects the direction: the pool's token ID sells tokens for
+    /// base currency, anything else buys tokens with base currency. The
+    /// service's `swapQuote` previews this exact computation.
     async fn execute_swap(
         &mut self,
         pool_id: String,
-        _token_in: String,
+        token_in: String,
         amount_in: U256,
         min_amount_out: U256,
     ) -> Result<(), SwapError> {
@@ -244,9 +1108,10 @@ impl SwapContract {
             .map_err(|_| SwapError::PoolNotFound(pool_id.clone()))?
             .ok_or_else(|| SwapError::PoolNotFound(pool_id.clone()))?;
 
-        // Calculate output using constant product formula: x * y = k
-        // amount_out = (amount_in * reserve_out) / (reserve_in + amount_in)
-        let amount_out = (amount_in * pool.base_liquidity) / (pool.token_liquidity + amount_in);
+        let sells_token = token_in == pool.token_id;
+        let buyback = self.runtime.application_parameters().buyback;
+        let quote = Self::fill_exact_in(&mut pool, sells_token, amount_in, buyback)?;
+        let amount_out = quote.amount_out;
 
         // Check slippage protection
         if amount_out < min_amount_out {
@@ -255,18 +1120,41 @@ impl SwapContract {
                 min: min_amount_out,
             });
         }
-
-        // Update pool reserves
-        pool.token_liquidity = pool.token_liquidity + amount_in;
-        pool.base_liquidity = pool.base_liquidity - amount_out;
+        self.withhold_buyback_fee(&pool_id, sells_token, quote.fee).await?;
         pool.trade_count += 1;
 
-        // Update pool in state
+        // Volume is counted in base currency on either side of the trade
+        let (token_amount, base_volume) = if sells_token {
+            (amount_in, amount_out)
+        } else {
+            (amount_out, amount_in)
+        };
+        let trader = self.trader_account();
+        let now = self.runtime.system_time();
+        let trade = SwapTrade {
+            trader,
+            is_buy: !sells_token,
+            token_amount,
+            base_amount: base_volume,
+            price: pool.current_price(),
+            timestamp: now,
+        };
+        self.state.insert_pool_trade(&pool_id, pool.trade_count - 1, trade)?;
+        self.buy_back_if_due(&mut pool, now).await?;
+
+        // Update pool in state, revaluing its TVL at the new price
         self.state
-            .pools
-            .insert(&pool_id, pool)
+            .store_traded_pool(pool, now)
+            .await
             .expect("Failed to update pool");
 
+        // Update platform-wide volume counters
+        self.state
+            .record_swap(trader, base_volume, now)
+            .await
+            .expect("Failed to record swap volume");
+        self.notify_swap(trader, base_volume);
+
         // NOTE: Token transfers are handled through the token contract's
         // approve/transferFrom operations. Users must:
         // 1. Approve swap contract to spend their tokens
@@ -277,45 +1165,621 @@ impl SwapContract {
         Ok(())
     }
 
-    /// Send PoolCreated message back to token contract
-    fn send_pool_created_message(&mut self, token_id: String, pool_id: String, target_chain: ChainId) {
+    /// Execute a swap for an exact output amount
+    ///
+    /// `token_out` selects the direction: the pool's token ID buys tokens with
+    /// base currency, anything else sells tokens for base currency. The input
+    /// fee stays in the pool.
+    async fn execute_swap_exact_out(
+        &mut self,
+        pool_id: String,
+        token_out: String,
+        amount_out: U256,
+        max_in: U256,
+    ) -> Result<(), SwapError> {
+        if amount_out == U256::zero() {
+            return Err(SwapError::InvalidAmount);
+        }
+
+        let mut pool = self
+            .state
+            .get_pool(&pool_id)
+            .await
+            .map_err(|_| SwapError::PoolNotFound(pool_id.clone()))?
+            .ok_or_else(|| SwapError::PoolNotFound(pool_id.clone()))?;
+
+        let buys_token = token_out == pool.token_id;
+        let (reserve_in, reserve_out) = if buys_token {
+            (pool.base_liquidity, pool.token_liquidity)
+        } else {
+            (pool.token_liquidity, pool.base_liquidity)
+        };
+        if amount_out >= reserve_out {
+            return Err(SwapError::InsufficientLiquidity);
+        }
+
+        let amount_in = Self::constant_product_input(amount_out, reserve_in, reserve_out)?;
+        if amount_in > max_in {
+            return Err(SwapError::ExcessiveInput {
+                required: amount_in,
+                max: max_in,
+            });
+        }
+
+        // The fee is what the input exceeds a fee-free swap by
+        let fee_free_input = amm::constant_product_input(amount_out, reserve_in, reserve_out, 0)
+            .ok_or(SwapError::CurveOverflow)?;
+        let fee = amount_in.saturating_sub(fee_free_input);
+        let withheld = self.withhold_buyback_fee(&pool_id, !buys_token, fee).await?;
+
+        let reserve_in = reserve_in
+            .checked_add(amount_in - withheld)
+            .ok_or(SwapError::CurveOverflow)?;
+        let reserve_out = reserve_out - amount_out;
+        if buys_token {
+            pool.base_liquidity = reserve_in;
+            pool.token_liquidity = reserve_out;
+        } else {
+            pool.token_liquidity = reserve_in;
+            pool.base_liquidity = reserve_out;
+        }
+        pool.trade_count += 1;
+
+        // Volume is counted in base currency on either side of the trade
+        let (token_amount, base_volume) = if buys_token {
+            (amount_out, amount_in)
+        } else {
+            (amount_in, amount_out)
+        };
+        let trader = self.trader_account();
+        let now = self.runtime.system_time();
+        let trade = SwapTrade {
+            trader,
+            is_buy: buys_token,
+            token_amount,
+            base_amount: base_volume,
+            price: pool.current_price(),
+            timestamp: now,
+        };
+        self.state.insert_pool_trade(&pool_id, pool.trade_count - 1, trade)?;
+        self.buy_back_if_due(&mut pool, now).await?;
+
+        self.state
+            .store_traded_pool(pool, now)
+            .await
+            .expect("Failed to update pool");
+
+        self.state
+            .record_swap(trader, base_volume, now)
+            .await
+            .expect("Failed to record swap volume");
+        self.notify_swap(trader, base_volume);
+
+        Ok(())
+    }
+
+    /// Lend a pool's base currency to `callee` and require repayment before returning
+    ///
+    /// The loan is returned with the swap fee, which stays in the pool. The
+    /// repayment is checked against this application's balance of the pool's
+    /// base asset, so a short callee reverts the whole transaction. Tokens
+    /// cannot be borrowed: pools account for them without holding them.
+    async fn execute_flash_swap(
+        &mut self,
+        pool_id: String,
+        token_out: String,
+        amount_out: U256,
+        callee: ApplicationId,
+        data: Vec<u8>,
+    ) -> Result<Amount, SwapError> {
+        if amount_out == U256::zero() {
+            return Err(SwapError::InvalidAmount);
+        }
+
+        let mut pool = self
+            .state
+            .get_pool(&pool_id)
+            .await
+            .map_err(|_| SwapError::PoolNotFound(pool_id.clone()))?
+            .ok_or_else(|| SwapError::PoolNotFound(pool_id.clone()))?;
+        if token_out == pool.token_id {
+            return Err(SwapError::FlashSwapTokenBorrow(pool_id));
+        }
+        let quote = self
+            .state
+            .pool_quotes
+            .get(&pool_id)
+            .await?
+            .ok_or_else(|| SwapError::FlashSwapUnavailable(pool_id.clone()))?;
+        if amount_out >= pool.base_liquidity {
+            return Err(SwapError::InsufficientLiquidity);
+        }
+
+        let (lent, fee, amount_due) = Self::flash_loan_terms(amount_out, quote.base_decimals)?;
+        let balance_before = self.application_balance(quote.base_asset);
+        let callee_account = Account {
+            chain_id: self.runtime.chain_id(),
+            owner: AccountOwner::from(callee),
+        };
+        self.send_base(quote.base_asset, callee_account, lent);
+
+        let callback = FlashSwapCallback {
+            pool_id,
+            token_out,
+            amount_out,
+            base_asset: quote.base_asset,
+            amount_due,
+            data,
+        };
+        self.runtime
+            .call_application(true, callee.with_abi::<FlashSwapCalleeAbi>(), &callback);
+
+        let balance_after = self.application_balance(quote.base_asset);
+        Self::check_flash_repayment(balance_before, lent, balance_after, amount_due)?;
+
+        // The fee the pool kept now backs it
+        pool.base_liquidity = pool
+            .base_liquidity
+            .checked_add(fee)
+            .ok_or(SwapError::CurveOverflow)?;
+        let kept = units::to_amount(fee, quote.base_decimals)?;
+        self.state.commit_liquidity(quote.base_asset, kept).await?;
+
+        let now = self.runtime.system_time();
+        self.state
+            .store_traded_pool(pool, now)
+            .await
+            .expect("Failed to update pool");
+
+        Ok(amount_due)
+    }
+
+    /// A base currency flash loan of `amount_out` base units: what is sent,
+    /// the fee the pool keeps and what must come back
+    fn flash_loan_terms(
+        amount_out: U256,
+        base_decimals: u8,
+    ) -> Result<(Amount, U256, Amount), SwapError> {
+        let fee = wad::mul_bps(amount_out, amm::SWAP_FEE_BPS).ok_or(SwapError::CurveOverflow)?;
+        let due = amount_out.checked_add(fee).ok_or(SwapError::CurveOverflow)?;
+        Ok((
+            units::to_amount(amount_out, base_decimals)?,
+            fee,
+            units::to_amount(due, base_decimals)?,
+        ))
+    }
+
+    /// Check that the callee paid back at least `due` of a loan of `lent`
+    fn check_flash_repayment(
+        balance_before: Amount,
+        lent: Amount,
+        balance_after: Amount,
+        due: Amount,
+    ) -> Result<(), SwapError> {
+        let received = balance_after.saturating_sub(balance_before.saturating_sub(lent));
+        if received < due {
+            return Err(SwapError::FlashSwapNotRepaid { due, received });
+        }
+        Ok(())
+    }
+
+    /// Swap an exact input along `path`, hopping through the base currency
+    ///
+    /// The pools along the path must be quoted in the same base currency.
+    /// Nothing is written unless the whole route clears `min_out`.
+    async fn execute_swap_exact_tokens_for_tokens(
+        &mut self,
+        path: Vec<String>,
+        amount_in: U256,
+        min_out: U256,
+    ) -> Result<U256, SwapError> {
+        if amount_in == U256::zero() {
+            return Err(SwapError::InvalidAmount);
+        }
+        if path.len() < 2 {
+            return Err(SwapError::InvalidPath(
+                "at least two tokens are required".to_string(),
+            ));
+        }
+
+        let mut pools = BTreeMap::new();
+        let mut base_assets = BTreeMap::new();
+        for token_id in &path {
+            if pools.contains_key(token_id) {
+                continue;
+            }
+            let pool = self
+                .state
+                .get_pool_by_token(token_id)
+                .await
+                .map_err(|_| SwapError::PoolNotFound(token_id.clone()))?
+                .ok_or_else(|| SwapError::PoolNotFound(token_id.clone()))?;
+            let base_asset = self
+                .state
+                .pool_base_asset(&pool.pool_id)
+                .await
+                .map_err(|_| SwapError::PoolNotFound(token_id.clone()))?;
+            base_assets.insert(token_id.clone(), base_asset);
+            pools.insert(token_id.clone(), pool);
+        }
+
+        let trader = self.trader_account();
+        let now = self.runtime.system_time();
+        let buyback = self.runtime.application_parameters().buyback;
+        let route = Self::route_exact_in(
+            &mut pools,
+            &base_assets,
+            &path,
+            amount_in,
+            buyback,
+            trader,
+            now,
+        )?;
+        if route.amount_out < min_out {
+            return Err(SwapError::SlippageExceeded {
+                got: route.amount_out,
+                min: min_out,
+            });
+        }
+
+        for (pool_id, fee_in_tokens, fee) in route.fees {
+            self.withhold_buyback_fee(&pool_id, fee_in_tokens, fee).await?;
+        }
+        for (pool_id, sequence, trade) in route.trades {
+            self.state.insert_pool_trade(&pool_id, sequence, trade)?;
+        }
+        for mut pool in pools.into_values() {
+            self.buy_back_if_due(&mut pool, now).await?;
+            self.state
+                .store_traded_pool(pool, now)
+                .await
+                .expect("Failed to update pool");
+        }
+
+        // A routed swap counts once, with the base currency it moved
+        self.state
+            .record_swap(trader, route.base_volume, now)
+            .await
+            .expect("Failed to record swap volume");
+        self.notify_swap(trader, route.base_volume);
+
+        Ok(route.amount_out)
+    }
+
+    /// Price an exact-input swap against `pool` with the swap fee and apply it
+    /// to the pool's reserves, less the fee share withheld for buybacks
+    ///
+    /// Nothing is accrued for the buyback; the caller does so once the swap
+    /// is known to go through.
+    fn fill_exact_in(
+        pool: &mut PoolInfo,
+        sells_token: bool,
+        amount_in: U256,
+        buyback: Option<BuybackConfig>,
+    ) -> Result<amm::ExactInQuote, SwapError> {
+        let (reserve_in, reserve_out) = if sells_token {
+            (pool.token_liquidity, pool.base_liquidity)
+        } else {
+            (pool.base_liquidity, pool.token_liquidity)
+        };
+
+        // Calculate output using constant product formula: x * y = k
+        // amount_out = (amount_in * reserve_out) / (reserve_in + amount_in)
+        let quote = amm::quote_exact_in(amount_in, reserve_in, reserve_out, amm::EXACT_IN_FEE_BPS)
+            .ok_or(SwapError::CurveOverflow)?;
+        let withheld = buyback.map_or(U256::zero(), |config| buyback::share_of(&config, quote.fee));
+
+        let reserve_in = reserve_in
+            .checked_add(amount_in - withheld)
+            .ok_or(SwapError::CurveOverflow)?;
+        let reserve_out = reserve_out - quote.amount_out;
+        if sells_token {
+            pool.token_liquidity = reserve_in;
+            pool.base_liquidity = reserve_out;
+        } else {
+            pool.base_liquidity = reserve_in;
+            pool.token_liquidity = reserve_out;
+        }
+        Ok(quote)
+    }
+
+    /// Apply an exact-input route to working copies of its pools (keyed by token)
+    ///
+    /// Each hop sells into the first token's pool and buys the second token
+    /// with the proceeds, so a token visited twice sees its earlier trade.
+    /// Both pools of a hop must be quoted in the same base asset. Every leg
+    /// is priced as `execute_swap` prices it, fee and buyback share included.
+    fn route_exact_in(
+        pools: &mut BTreeMap<String, PoolInfo>,
+        base_assets: &BTreeMap<String, BaseAsset>,
+        path: &[String],
+        amount_in: U256,
+        buyback: Option<BuybackConfig>,
+        trader: Account,
+        now: Timestamp,
+    ) -> Result<Route, SwapError> {
+        let mut amount = amount_in;
+        let mut base_volume = U256::zero();
+        let mut trades = Vec::new();
+        let mut fees = Vec::new();
+        for hop in path.windows(2) {
+            let (from, to) = (&hop[0], &hop[1]);
+            if from == to {
+                return Err(SwapError::InvalidPath(format!("{} follows itself", from)));
+            }
+            let (from_asset, to_asset) = (base_assets.get(from), base_assets.get(to));
+            if from_asset != to_asset {
+                return Err(SwapError::InvalidPath(format!(
+                    "{} and {} are quoted in different base assets",
+                    from, to
+                )));
+            }
+
+            // tokenA → base
+            let pool = pools
+                .get_mut(from)
+                .ok_or_else(|| SwapError::PoolNotFound(from.clone()))?;
+            let quote = Self::fill_exact_in(pool, true, amount, buyback)?;
+            let base = quote.amount_out;
+            if base == U256::zero() {
+                return Err(SwapError::InsufficientLiquidity);
+            }
+            fees.push((pool.pool_id.clone(), true, quote.fee));
+            pool.trade_count += 1;
+            let trade = SwapTrade {
+                trader,
+                is_buy: false,
+                token_amount: amount,
+                base_amount: base,
+                price: pool.current_price(),
+                timestamp: now,
+            };
+            trades.push((pool.pool_id.clone(), pool.trade_count - 1, trade));
+
+            // base → tokenB
+            let pool = pools
+                .get_mut(to)
+                .ok_or_else(|| SwapError::PoolNotFound(to.clone()))?;
+            let quote = Self::fill_exact_in(pool, false, base, buyback)?;
+            let tokens = quote.amount_out;
+            if tokens == U256::zero() {
+                return Err(SwapError::InsufficientLiquidity);
+            }
+            fees.push((pool.pool_id.clone(), false, quote.fee));
+            pool.trade_count += 1;
+            let trade = SwapTrade {
+                trader,
+                is_buy: true,
+                token_amount: tokens,
+                base_amount: base,
+                price: pool.current_price(),
+                timestamp: now,
+            };
+            trades.push((pool.pool_id.clone(), pool.trade_count - 1, trade));
+
+            base_volume = base_volume.checked_add(base).ok_or(SwapError::CurveOverflow)?;
+            amount = tokens;
+        }
+        Ok(Route {
+            amount_out: amount,
+            base_volume,
+            trades,
+            fees,
+        })
+    }
+
+    /// Set aside the configured share of a swap fee for the pool's buyback,
+    /// returning the amount withheld from the pool
+    async fn withhold_buyback_fee(
+        &mut self,
+        pool_id: &str,
+        fee_in_tokens: bool,
+        fee: U256,
+    ) -> Result<U256, SwapError> {
+        let Some(config) = self.runtime.application_parameters().buyback else {
+            return Ok(U256::zero());
+        };
+        let share = buyback::share_of(&config, fee);
+        if share.is_zero() {
+            return Ok(share);
+        }
+        let (base, tokens) = if fee_in_tokens {
+            (U256::zero(), share)
+        } else {
+            (share, U256::zero())
+        };
+        self.state.accrue_buyback(pool_id, base, tokens).await?;
+        Ok(share)
+    }
+
+    /// Execute a pool's buyback on request once it is due
+    async fn execute_buyback(&mut self, pool_id: String) -> Result<(), SwapError> {
+        if self.runtime.application_parameters().buyback.is_none() {
+            return Err(SwapError::BuybackDisabled);
+        }
+        let mut pool = self
+            .state
+            .get_pool(&pool_id)
+            .await
+            .map_err(|_| SwapError::PoolNotFound(pool_id.clone()))?
+            .ok_or_else(|| SwapError::PoolNotFound(pool_id.clone()))?;
+
+        let now = self.runtime.system_time();
+        if !self.buy_back_if_due(&mut pool, now).await? {
+            return Err(SwapError::BuybackNotDue);
+        }
+        self.state
+            .store_traded_pool(pool, now)
+            .await
+            .expect("Failed to update pool");
+        Ok(())
+    }
+
+    /// Spend a pool's accrued base fees on its tokens and send them, with the
+    /// token fees, to be burned, if the buyback is due; returns whether it ran
+    ///
+    /// The purchase is recorded in the pool's trade history under this
+    /// application's account but does not count towards platform volume.
+    async fn buy_back_if_due(
+        &mut self,
+        pool: &mut PoolInfo,
+        now: Timestamp,
+    ) -> Result<bool, SwapError> {
+        let Some(config) = self.runtime.application_parameters().buyback else {
+            return Ok(false);
+        };
+        let PoolBuyback {
+            accrued_base,
+            pending_burn,
+            last_buyback_at,
+            ..
+        } = self.state.buybacks.get(&pool.pool_id).await?.unwrap_or_default();
+        let since = last_buyback_at.unwrap_or(pool.created_at);
+        if !buyback::is_due(&config, accrued_base, pending_burn, since, now) {
+            return Ok(false);
+        }
+
+        let bought = if accrued_base.is_zero() {
+            U256::zero()
+        } else {
+            Self::constant_product_output(accrued_base, pool.base_liquidity, pool.token_liquidity)?
+        };
+        if !bought.is_zero() {
+            pool.base_liquidity = pool
+                .base_liquidity
+                .checked_add(accrued_base)
+                .ok_or(SwapError::CurveOverflow)?;
+            pool.token_liquidity = pool.token_liquidity - bought;
+            pool.trade_count += 1;
+            let trade = SwapTrade {
+                trader: self.application_account(),
+                is_buy: true,
+                token_amount: bought,
+                base_amount: accrued_base,
+                price: pool.current_price(),
+                timestamp: now,
+            };
+            self.state.insert_pool_trade(&pool.pool_id, pool.trade_count - 1, trade)?;
+        }
+
+        let burned = bought.saturating_add(pending_burn);
+        let totals = self.state.record_buyback(&pool.pool_id, burned, now).await?;
+        self.logger.info(format!(
+            "Pool {} bought back {} tokens for {} base currency; {} burned in total",
+            pool.pool_id, bought, accrued_base, totals.tokens_burned
+        ));
+
+        if !burned.is_zero() {
+            let token_id = graduation::pool_key_token_id(&pool.token_id).to_string();
+            let chain_id = self.token_chain(&token_id).await?;
+            self.runtime
+                .prepare_message(Message::BurnTokens {
+                    token_id,
+                    pool_id: pool.pool_id.clone(),
+                    amount: burned,
+                })
+                .with_tracking()
+                .send_to(chain_id);
+        }
+        Ok(true)
+    }
+
+    /// Chain hosting `token_id`, recorded at graduation (this chain if none was)
+    async fn token_chain(&mut self, token_id: &str) -> Result<ChainId, SwapError> {
+        match self.state.token_chains.get(token_id).await? {
+            Some(token_chain) => Ok(token_chain),
+            None => {
+                self.logger.warn(format!(
+                    "No chain recorded for token {}; messaging this chain",
+                    token_id
+                ));
+                Ok(self.runtime.chain_id())
+            }
+        }
+    }
+
+    /// This application's own account on this chain
+    fn application_account(&mut self) -> Account {
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner: AccountOwner::from(self.runtime.application_id().forget_abi()),
+        }
+    }
+
+    /// Report a swap to the factory chain for platform statistics
+    fn notify_swap(&mut self, trader: Account, base_volume: U256) {
+        let Some(factory_chain) = self.runtime.application_parameters().factory_chain_id else {
+            return;
+        };
+
+        self.runtime
+            .prepare_message(Message::SwapExecuted {
+                trader,
+                base_volume,
+            })
+            .send_to(factory_chain);
+    }
+
+    /// Account of the signer trading on this chain
+    fn trader_account(&mut self) -> Account {
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner: self.runtime.authenticated_signer().unwrap_or(AccountOwner::CHAIN),
+        }
+    }
+
+    /// Input required for an exact output, including the swap fee
+    fn constant_product_input(
+        amount_out: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+    ) -> Result<U256, SwapError> {
+        amm::constant_product_input(amount_out, reserve_in, reserve_out, amm::SWAP_FEE_BPS)
+            .ok_or(SwapError::CurveOverflow)
+    }
+
+    /// Constant product output with overflow checks
+    fn constant_product_output(
+        amount_in: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+    ) -> Result<U256, SwapError> {
+        amm::constant_product_output(amount_in, reserve_in, reserve_out)
+            .ok_or(SwapError::CurveOverflow)
+    }
+
+    /// Send PoolCreated message back to token contract, attesting the pool's terms
+    fn send_pool_created_message(
+        &mut self,
+        token_id: String,
+        pool: &PoolInfo,
+        target_chain: ChainId,
+        is_stable_pool: bool,
+        excess_burned: U256,
+    ) {
+        let attestation = pool.attestation(self.runtime.system_time());
         self.runtime
             .prepare_message(Message::PoolCreated {
                 token_id: token_id.clone(),
-                pool_id: pool_id.clone(),
+                pool_id: pool.pool_id.clone(),
+                attestation,
+                is_stable_pool,
+                excess_burned,
             })
             .with_tracking()
             .send_to(target_chain);
 
-        self.log_event(&format!(
+        self.logger.debug(format!(
             "Sent PoolCreated message for token {} to chain {}",
             token_id, target_chain
         ));
     }
-
-    /// Log an event (would integrate with Linera logging in production)
-    fn log_event(&self, message: &str) {
-        #[cfg(debug_assertions)]
-        eprintln!("[SWAP-EVENT] {}", message);
-
-        // In production, this would use proper logging
-        let _ = message; // Suppress unused warning in release builds
-    }
-
-    /// Log an error (would integrate with Linera logging in production)
-    fn log_error(&self, message: &str) {
-        #[cfg(debug_assertions)]
-        eprintln!("[SWAP-ERROR] {}", message);
-
-        // In production, this would use proper error reporting
-        let _ = message; // Suppress unused warning in release builds
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use linera_sdk::linera_base_types::{ApplicationId, BytecodeId, ChainId};
+    use linera_sdk::linera_base_types::{ApplicationId, BytecodeId, ChainId, CryptoHash};
     use linera_views::memory::MemoryContext;
 
     // Helper to create a test runtime would go here
@@ -400,6 +1864,48 @@ mod tests {
         assert_eq!(pool1.token_liquidity, pool2.token_liquidity);
     }
 
+    #[tokio::test]
+    async fn test_stable_pool_alongside_native_pool() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+
+        let created_at = linera_sdk::linera_base_types::Timestamp::from(1234567890);
+        state.initialize(created_at).await.unwrap();
+
+        let token_id = "test-token-split".to_string();
+        let (native_supply, stable_supply) =
+            graduation::split_supply(U256::from(1_000_000), 3_000).unwrap();
+        state
+            .create_pool(token_id.clone(), native_supply, U256::from(10_000), created_at)
+            .await
+            .unwrap();
+        let stable_asset = BaseAsset::Fungible(ApplicationId::new(CryptoHash::from([1u64; 4])));
+        state
+            .create_pool_in(
+                graduation::stable_pool_key(&token_id),
+                PoolQuote {
+                    base_asset: stable_asset,
+                    base_decimals: 6,
+                },
+                stable_supply,
+                U256::from(6_000),
+                created_at,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(*state.total_pools.get(), 2);
+        let native = state.get_pool_by_token(&token_id).await.unwrap().unwrap();
+        let stable = state
+            .get_pool_by_token(&graduation::stable_pool_key(&token_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(native.pool_id, "pool-test-token-split");
+        assert_eq!(stable.pool_id, "pool-test-token-split:stable");
+        assert_eq!(native.token_liquidity + stable.token_liquidity, U256::from(1_000_000));
+    }
+
     #[tokio::test]
     async fn test_multiple_graduations() {
         let context = MemoryContext::default();
@@ -431,6 +1937,254 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_received_amount_conversion() {
+        assert_eq!(
+            SwapContract::amount_to_u256(Amount::from_tokens(69_000), 0).unwrap(),
+            U256::from(69_000)
+        );
+        assert_eq!(SwapContract::amount_to_u256(Amount::ZERO, 0).unwrap(), U256::zero());
+        assert_eq!(
+            SwapContract::amount_to_u256(Amount::from_tokens(1), 6).unwrap(),
+            U256::from(1_000_000)
+        );
+    }
+
+    #[test]
+    fn test_constant_product_output_overflow() {
+        assert_eq!(
+            SwapContract::constant_product_output(U256::from(100), U256::from(900), U256::from(1000))
+                .unwrap(),
+            U256::from(100)
+        );
+        assert!(matches!(
+            SwapContract::constant_product_output(U256::MAX, U256::one(), U256::from(2)),
+            Err(SwapError::CurveOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_constant_product_input_charges_fee() {
+        let input =
+            SwapContract::constant_product_input(U256::from(100), U256::from(900), U256::from(1000))
+                .unwrap();
+        // 900 * 100 * 10_000 / (900 * 9_970) + 1
+        assert_eq!(input, U256::from(101));
+        let input = SwapContract::constant_product_input(
+            U256::from(100_000),
+            U256::from(900_000),
+            U256::from(1_000_000),
+        )
+        .unwrap();
+        assert_eq!(input, U256::from(100_301));
+    }
+
+    #[test]
+    fn test_flash_loan_repaid_with_fee() {
+        let (lent, fee, due) = SwapContract::flash_loan_terms(U256::from(10_000), 0).unwrap();
+        assert_eq!(lent, Amount::from_tokens(10_000));
+        assert_eq!(fee, U256::from(30));
+        assert_eq!(due, Amount::from_tokens(10_030));
+
+        // The application held 50k, lent 10k and got 10,030 back
+        let before = Amount::from_tokens(50_000);
+        let after = Amount::from_tokens(50_030);
+        assert!(SwapContract::check_flash_repayment(before, lent, after, due).is_ok());
+    }
+
+    #[test]
+    fn test_flash_loan_short_repayment_reverts() {
+        let (lent, _, due) = SwapContract::flash_loan_terms(U256::from(10_000), 0).unwrap();
+        let before = Amount::from_tokens(50_000);
+
+        // Repaying the loan without the fee falls short
+        let after = Amount::from_tokens(50_000);
+        assert!(matches!(
+            SwapContract::check_flash_repayment(before, lent, after, due),
+            Err(SwapError::FlashSwapNotRepaid { received, .. })
+                if received == Amount::from_tokens(10_000)
+        ));
+
+        // Keeping the loan reverts too
+        let after = Amount::from_tokens(40_000);
+        assert!(SwapContract::check_flash_repayment(before, lent, after, due).is_err());
+    }
+
+    #[test]
+    fn test_flash_loan_in_fungible_base_asset() {
+        // 1,000 USDC at 6 decimals
+        let (lent, fee, due) =
+            SwapContract::flash_loan_terms(U256::from(1_000_000_000u64), 6).unwrap();
+        assert_eq!(lent, Amount::from_tokens(1_000));
+        assert_eq!(fee, U256::from(3_000_000));
+        assert_eq!(due, Amount::from_tokens(1_003));
+
+        let before = Amount::from_tokens(5_000);
+        let repaid = Amount::from_tokens(5_003);
+        assert!(SwapContract::check_flash_repayment(before, lent, repaid, due).is_ok());
+        let short = Amount::from_millis(5_002_999);
+        assert!(SwapContract::check_flash_repayment(before, lent, short, due).is_err());
+    }
+
+    #[test]
+    fn test_route_exact_in_hops_through_base() {
+        let created_at = linera_sdk::linera_base_types::Timestamp::from(0);
+        let pool_a =
+            PoolInfo::new("token-a".to_string(), U256::from(1_000_000), U256::from(10_000), created_at)
+                .unwrap();
+        let pool_b =
+            PoolInfo::new("token-b".to_string(), U256::from(2_000_000), U256::from(40_000), created_at)
+                .unwrap();
+        let amount_in = U256::from(5_000);
+        let fee = amm::EXACT_IN_FEE_BPS;
+        let base =
+            amm::quote_exact_in(amount_in, pool_a.token_liquidity, pool_a.base_liquidity, fee)
+                .unwrap()
+                .amount_out;
+        let expected =
+            amm::quote_exact_in(base, pool_b.base_liquidity, pool_b.token_liquidity, fee)
+                .unwrap()
+                .amount_out;
+
+        let mut pools = BTreeMap::from([
+            ("token-a".to_string(), pool_a.clone()),
+            ("token-b".to_string(), pool_b.clone()),
+        ]);
+        let path = ["token-a".to_string(), "token-b".to_string()];
+        let mut base_assets = BTreeMap::from([
+            ("token-a".to_string(), BaseAsset::Native),
+            ("token-b".to_string(), BaseAsset::Native),
+        ]);
+        let trader = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        let route = SwapContract::route_exact_in(
+            &mut pools,
+            &base_assets,
+            &path,
+            amount_in,
+            None,
+            trader,
+            created_at,
+        )
+        .unwrap();
+        assert_eq!(route.amount_out, expected);
+        assert_eq!(route.base_volume, base);
+        assert_eq!(route.trades.len(), 2);
+        assert!(!route.trades[0].2.is_buy && route.trades[1].2.is_buy);
+        assert_eq!(route.trades[1].2.token_amount, expected);
+
+        let (a, b) = (&pools["token-a"], &pools["token-b"]);
+        assert_eq!(a.token_liquidity, pool_a.token_liquidity + amount_in);
+        assert_eq!(a.base_liquidity, pool_a.base_liquidity - base);
+        assert_eq!(b.base_liquidity, pool_b.base_liquidity + base);
+        assert_eq!(b.token_liquidity, pool_b.token_liquidity - expected);
+        assert_eq!((a.trade_count, b.trade_count), (1, 1));
+
+        let looped = ["token-a".to_string(), "token-a".to_string()];
+        assert!(matches!(
+            SwapContract::route_exact_in(
+                &mut pools,
+                &base_assets,
+                &looped,
+                amount_in,
+                None,
+                trader,
+                created_at,
+            ),
+            Err(SwapError::InvalidPath(_))
+        ));
+
+        // Hopping between pools quoted in different assets is refused untouched
+        let usdc = BaseAsset::Fungible(ApplicationId::new(CryptoHash::from([1u64; 4])));
+        base_assets.insert("token-b".to_string(), usdc);
+        assert!(matches!(
+            SwapContract::route_exact_in(
+                &mut pools,
+                &base_assets,
+                &path,
+                amount_in,
+                None,
+                trader,
+                created_at,
+            ),
+            Err(SwapError::InvalidPath(_))
+        ));
+        assert_eq!(pools["token-a"].trade_count, 1);
+    }
+
+    #[test]
+    fn test_one_hop_route_matches_single_pool_swaps() {
+        let created_at = linera_sdk::linera_base_types::Timestamp::from(0);
+        let pool_a =
+            PoolInfo::new("token-a".to_string(), U256::from(1_000_000), U256::from(10_000), created_at)
+                .unwrap();
+        let pool_b =
+            PoolInfo::new("token-b".to_string(), U256::from(2_000_000), U256::from(40_000), created_at)
+                .unwrap();
+        let buyback = Some(BuybackConfig {
+            fee_share_bps: 5_000,
+            min_base: U256::from(1_000),
+            interval_micros: 0,
+        });
+        let amount_in = U256::from(50_000);
+
+        // The two swaps `execute_swap` would make: sell A, then buy B with the proceeds
+        let (mut single_a, mut single_b) = (pool_a.clone(), pool_b.clone());
+        let sold = SwapContract::fill_exact_in(&mut single_a, true, amount_in, buyback).unwrap();
+        let bought =
+            SwapContract::fill_exact_in(&mut single_b, false, sold.amount_out, buyback).unwrap();
+        assert!(!sold.fee.is_zero() && !bought.fee.is_zero());
+
+        let mut pools = BTreeMap::from([
+            ("token-a".to_string(), pool_a.clone()),
+            ("token-b".to_string(), pool_b.clone()),
+        ]);
+        let base_assets = BTreeMap::from([
+            ("token-a".to_string(), BaseAsset::Native),
+            ("token-b".to_string(), BaseAsset::Native),
+        ]);
+        let path = ["token-a".to_string(), "token-b".to_string()];
+        let trader = Account {
+            chain_id: ChainId::root(0),
+            owner: AccountOwner::CHAIN,
+        };
+        let route = SwapContract::route_exact_in(
+            &mut pools,
+            &base_assets,
+            &path,
+            amount_in,
+            buyback,
+            trader,
+            created_at,
+        )
+        .unwrap();
+
+        assert_eq!(route.amount_out, bought.amount_out);
+        assert_eq!(route.base_volume, sold.amount_out);
+        assert_eq!(
+            route.fees,
+            vec![
+                (pool_a.pool_id.clone(), true, sold.fee),
+                (pool_b.pool_id.clone(), false, bought.fee),
+            ]
+        );
+
+        // The buyback share of each fee is kept out of the reserves
+        let (a, b) = (&pools["token-a"], &pools["token-b"]);
+        assert_eq!(
+            (a.token_liquidity, a.base_liquidity),
+            (single_a.token_liquidity, single_a.base_liquidity)
+        );
+        assert_eq!(
+            (b.token_liquidity, b.base_liquidity),
+            (single_b.token_liquidity, single_b.base_liquidity)
+        );
+        let withheld = buyback::share_of(&buyback.unwrap(), sold.fee);
+        assert_eq!(a.token_liquidity, pool_a.token_liquidity + amount_in - withheld);
+    }
+
     #[tokio::test]
     async fn test_invalid_graduation_zero_supply() {
         let context = MemoryContext::default();
@@ -1,17 +1,27 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
 mod state;
-use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
-use fair_launch_abi::SwapAbi;
-use linera_sdk::{abi::WithServiceAbi, views::View, Service, ServiceRuntime};
+use async_graphql::{
+    connection::query, Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject,
+};
+use fair_launch_abi::{
+    pagination::{self, Page},
+    PoolInfoGQL, SwapAbi, SwapParameters,
+};
+use linera_sdk::{
+    abi::WithServiceAbi,
+    linera_base_types::{AccountOwner, Amount},
+    views::View,
+    Service, ServiceRuntime,
+};
 use primitive_types::U256;
 use std::sync::Arc;
 
-use crate::state::SwapState;
+use crate::state::{PoolBuyback, PoolInfo, SwapState, SwapTrade, TokenSupply, TVL_BUCKET_MICROS};
 
 /// GraphQL service for querying swap pools
 pub struct SwapService {
-    state: Arc<SwapState>,
+    runtime: Arc<ServiceRuntime<Self>>,
 }
 
 linera_sdk::service!(SwapService);
@@ -21,28 +31,39 @@ impl WithServiceAbi for SwapService {
 }
 
 impl Service for SwapService {
-    type Parameters = ();
+    type Parameters = SwapParameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
-        let state = SwapState::load(runtime.root_view_storage_context())
-            .await
-            .expect("Failed to load swap state");
         SwapService {
-            state: Arc::new(state),
+            runtime: Arc::new(runtime),
         }
     }
 
     async fn handle_query(&self, request: async_graphql::Request) -> async_graphql::Response {
+        // Load per query so a long-lived instance answers from the latest block
+        let state = Arc::new(
+            SwapState::load(self.runtime.root_view_storage_context())
+                .await
+                .expect("Failed to load swap state"),
+        );
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        let balance = ApplicationBalance(self.runtime.owner_balance(application_owner));
         let schema = Schema::build(
             QueryRoot {
-                state: self.state.clone(),
+                state: state.clone(),
             },
             EmptyMutation,
             EmptySubscription,
         )
+        .data(balance)
         .finish();
 
-        schema.execute(request).await
+        let mut response = schema.execute(request).await;
+        response.extensions.insert(
+            fair_launch_abi::STATE_VERSION_EXTENSION.to_string(),
+            async_graphql::Value::from(*state.state_version.get()),
+        );
+        response
     }
 }
 
@@ -50,6 +71,35 @@ pub struct QueryRoot {
     state: Arc<SwapState>,
 }
 
+/// One of a token's pools and the asset it is quoted in
+#[derive(SimpleObject)]
+pub struct TokenPool {
+    /// "native" or the fungible application ID
+    pub base_asset: String,
+    /// Whether this is the token's default pool, opened at graduation
+    pub is_default: bool,
+    pub pool: PoolInfoGQL,
+}
+
+/// Native balance of the application when the query was received
+struct ApplicationBalance(Amount);
+
+/// Protocol fees held by the application and their management
+#[derive(SimpleObject)]
+pub struct TreasuryBalance {
+    /// Native balance not backing any pool nor reserved for buybacks (None if
+    /// the balance is unavailable)
+    pub balance: Option<String>,
+    /// Native balance committed to pools, which the treasury cannot touch
+    pub committed_liquidity: String,
+    /// Native swap fees withheld for buybacks, which the treasury cannot touch
+    pub buyback_reserved: String,
+    /// Paid out of the treasury so far
+    pub total_withdrawn: String,
+    /// Account allowed to withdraw and hand the treasury over
+    pub admin: Option<String>,
+}
+
 #[derive(SimpleObject)]
 pub struct SwapStats {
     /// Total number of pools
@@ -60,6 +110,122 @@ pub struct SwapStats {
 
     /// Average pool size
     pub average_pool_tvl: String,
+
+    /// Swap volume in base currency over the last 24 hours
+    pub volume_24h: String,
+
+    /// Cumulative swap volume in base currency
+    pub total_volume: String,
+
+    /// Total swaps executed across all pools
+    pub total_swaps: u64,
+
+    /// Unique traders across all pools
+    pub unique_traders: u64,
+}
+
+/// Buyback-and-burn totals across all pools
+#[derive(SimpleObject)]
+pub struct BuybackStats {
+    /// Base currency spent buying tokens back
+    pub total_base_spent: String,
+    /// Tokens sent to their token chains for burning
+    pub total_tokens_burned: String,
+    pub total_buybacks: u64,
+}
+
+/// One pool's buyback-and-burn account
+#[derive(SimpleObject)]
+pub struct PoolBuybackView {
+    pub pool_id: String,
+    /// Base currency fees waiting for the next buyback
+    pub accrued_base: String,
+    /// Token fees waiting for the next burn
+    pub pending_burn: String,
+    pub base_spent: String,
+    pub tokens_burned: String,
+    pub buyback_count: u64,
+    /// Time of the last buyback, in microseconds
+    pub last_buyback_at: Option<String>,
+}
+
+impl PoolBuybackView {
+    fn new(pool_id: String, buyback: PoolBuyback) -> Self {
+        Self {
+            pool_id,
+            accrued_base: buyback.accrued_base.to_string(),
+            pending_burn: buyback.pending_burn.to_string(),
+            base_spent: buyback.base_spent.to_string(),
+            tokens_burned: buyback.tokens_burned.to_string(),
+            buyback_count: buyback.buyback_count,
+            last_buyback_at: buyback.last_buyback_at.map(|t| t.micros().to_string()),
+        }
+    }
+}
+
+/// Total TVL at the last change within one hour
+#[derive(SimpleObject)]
+pub struct TvlPoint {
+    /// Start of the hour, in microseconds
+    pub hour_start: String,
+    pub total_tvl: String,
+}
+
+/// One swap in a pool's trade history
+#[derive(SimpleObject)]
+pub struct SwapTradeView {
+    pub trader: String,
+    /// Whether tokens were bought with base currency (false: sold for it)
+    pub is_buy: bool,
+    pub token_amount: String,
+    pub base_amount: String,
+    /// Pool price after the trade, scaled by 10^price_decimals
+    pub price: String,
+    pub price_decimals: u8,
+    pub timestamp: String,
+}
+
+impl From<SwapTrade> for SwapTradeView {
+    fn from(trade: SwapTrade) -> Self {
+        Self {
+            trader: trade.trader.to_string(),
+            is_buy: trade.is_buy,
+            token_amount: trade.token_amount.to_string(),
+            base_amount: trade.base_amount.to_string(),
+            price: trade.price.raw().to_string(),
+            price_decimals: fair_launch_abi::price::PRICE_DECIMALS,
+            timestamp: trade.timestamp.micros().to_string(),
+        }
+    }
+}
+
+/// Preview of an exact-input swap
+#[derive(SimpleObject)]
+pub struct SwapQuote {
+    pub pool_id: String,
+    pub token_in: String,
+    pub amount_in: String,
+
+    /// Expected output at the current reserves
+    pub amount_out: String,
+
+    /// Base currency per token paid or received by this swap,
+    /// scaled by 10^price_decimals
+    pub execution_price: String,
+
+    /// Pool price before the swap, scaled by 10^price_decimals
+    pub spot_price: String,
+
+    pub price_decimals: u8,
+
+    /// Output shortfall against the spot price, in basis points (fee excluded)
+    pub price_impact_bps: u16,
+
+    /// Fee rate charged on the input
+    pub fee_bps: u16,
+
+    /// Part of the input kept by the pool as a fee
+    pub fee_amount: String,
 }
 
 #[derive(SimpleObject)]
@@ -72,10 +238,49 @@ pub struct PoolDetails {
 
     /// Pool age in seconds
     pub age_seconds: u64,
+
+    /// Supply outside time locks, as last reported by the token chain
+    pub circulating_supply: Option<String>,
+
+    /// Circulating supply valued at the pool price
+    pub market_cap: Option<String>,
+
+    /// Total supply valued at the pool price
+    pub fully_diluted_value: Option<String>,
+}
+
+impl PoolDetails {
+    fn new(
+        pool: &PoolInfo,
+        supply: Option<TokenSupply>,
+        is_active: bool,
+        age_seconds: u64,
+    ) -> Self {
+        let price = pool.current_price();
+        let value = |amount: U256| price.mul_amount(amount).map(|value| value.to_string());
+        Self {
+            pool: pool.into(),
+            is_active,
+            age_seconds,
+            circulating_supply: supply.map(|supply| supply.circulating_supply.to_string()),
+            market_cap: supply.and_then(|supply| value(supply.circulating_supply)),
+            fully_diluted_value: supply.and_then(|supply| value(supply.total_supply)),
+        }
+    }
 }
 
 #[Object]
 impl QueryRoot {
+    /// Version of the state these results were read from
+    async fn state_version(&self) -> u64 {
+        *self.state.state_version.get()
+    }
+
+    /// Version of the swap bytecode
+    async fn contract_version(&self) -> u32 {
+        *self.state.contract_version.get()
+    }
+
     /// Get overall swap statistics
     async fn stats(&self) -> SwapStats {
         let total_pools = *self.state.total_pools.get();
@@ -87,10 +292,20 @@ impl QueryRoot {
             U256::zero()
         };
 
+        let volume_24h = self
+            .state
+            .volume_24h(current_time())
+            .await
+            .unwrap_or_default();
+
         SwapStats {
             total_pools,
             total_tvl: total_tvl.to_string(),
             average_pool_tvl: average_pool_tvl.to_string(),
+            volume_24h: volume_24h.to_string(),
+            total_volume: self.state.total_volume.get().to_string(),
+            total_swaps: *self.state.total_swaps.get(),
+            unique_traders: *self.state.unique_traders.get(),
         }
     }
 
@@ -108,16 +323,158 @@ impl QueryRoot {
             .collect()
     }
 
+    /// All pools as a cursor-paginated connection with a total count
+    async fn pools_connection(
+        &self,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<Page<PoolInfoGQL>> {
+        query(after, before, first, last, |after, before, first, last| async move {
+            let total = self.state.pools.count().await?;
+            let (start, end) = pagination::window(after, before, first, last, total);
+            let pools = self.state.get_all_pools(start, end - start).await?;
+            let nodes = (start..).zip(pools.iter().map(PoolInfoGQL::from)).collect();
+            Ok::<_, async_graphql::Error>(pagination::page(nodes, start, end, total))
+        })
+        .await
+    }
+
+    /// A pool's trades, newest first
+    async fn pool_trades(
+        &self,
+        pool_id: String,
+        offset: Option<i32>,
+        limit: Option<i32>,
+    ) -> Vec<SwapTradeView> {
+        let offset = offset.unwrap_or(0).max(0) as u64;
+        let limit = limit.unwrap_or(20).clamp(1, 100) as u64;
+
+        let Ok(Some(pool)) = self.state.get_pool(&pool_id).await else {
+            return Vec::new();
+        };
+        self.state
+            .get_pool_trades(&pool, offset, limit)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Base currency traded in a pool over the last 24 hours
+    async fn pool_volume_24h(&self, pool_id: String) -> Option<String> {
+        let pool = self.state.get_pool(&pool_id).await.ok()??;
+        self.state
+            .pool_volume_24h(&pool, current_time())
+            .await
+            .ok()
+            .map(|volume| volume.to_string())
+    }
+
+    /// Total TVL over the last `hours` hours (default 24, at most 720), oldest first
+    ///
+    /// Only hours in which the TVL changed are listed; each value holds until
+    /// the next point.
+    async fn tvl_history(&self, hours: Option<i32>) -> Vec<TvlPoint> {
+        let hours = hours.unwrap_or(24).clamp(1, 720) as u64;
+        self.state
+            .get_tvl_history(current_time(), hours)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(bucket, tvl)| TvlPoint {
+                hour_start: (bucket * TVL_BUCKET_MICROS).to_string(),
+                total_tvl: tvl.to_string(),
+            })
+            .collect()
+    }
+
+    /// Preview swapping `amount_in` of `token_in` into a pool
+    ///
+    /// `token_in` is the pool's token ID to sell tokens, or its base asset
+    /// ("native" or the fungible application ID) to buy them; anything else
+    /// quotes nothing.
+    async fn swap_quote(
+        &self,
+        pool_id: String,
+        token_in: String,
+        amount_in: String,
+    ) -> Option<SwapQuote> {
+        use fair_launch_abi::{amm, Price};
+
+        let pool = self.state.get_pool(&pool_id).await.ok()??;
+        let amount_in = U256::from_dec_str(&amount_in).ok()?;
+        let base_asset = self.state.pool_base_asset(&pool_id).await.ok()?;
+        let sells_token = token_in == pool.token_id;
+        if !sells_token && token_in != base_asset.to_string() {
+            return None;
+        }
+        let (reserve_in, reserve_out) = if sells_token {
+            (pool.token_liquidity, pool.base_liquidity)
+        } else {
+            (pool.base_liquidity, pool.token_liquidity)
+        };
+        let quote =
+            amm::quote_exact_in(amount_in, reserve_in, reserve_out, amm::EXACT_IN_FEE_BPS)?;
+
+        let (base_amount, token_amount) = if sells_token {
+            (quote.amount_out, amount_in)
+        } else {
+            (amount_in, quote.amount_out)
+        };
+        let execution_price = Price::from_ratio(base_amount, token_amount).unwrap_or_default();
+
+        Some(SwapQuote {
+            pool_id,
+            token_in,
+            amount_in: amount_in.to_string(),
+            amount_out: quote.amount_out.to_string(),
+            execution_price: execution_price.raw().to_string(),
+            spot_price: pool.current_price().raw().to_string(),
+            price_decimals: fair_launch_abi::price::PRICE_DECIMALS,
+            price_impact_bps: quote.price_impact_bps,
+            fee_bps: amm::EXACT_IN_FEE_BPS,
+            fee_amount: quote.fee.to_string(),
+        })
+    }
+
+    /// Input required to receive exactly `amount_out` of `token_out`, including the swap fee
+    ///
+    /// `token_out` is the pool's token ID or its base asset, as in `swapQuote`.
+    async fn exact_out_quote(
+        &self,
+        pool_id: String,
+        token_out: String,
+        amount_out: String,
+    ) -> Option<String> {
+        let pool = self.state.get_pool(&pool_id).await.ok()??;
+        let amount_out = U256::from_dec_str(&amount_out).ok()?;
+        let base_asset = self.state.pool_base_asset(&pool_id).await.ok()?;
+        let buys_token = token_out == pool.token_id;
+        if !buys_token && token_out != base_asset.to_string() {
+            return None;
+        }
+        let (reserve_in, reserve_out) = if buys_token {
+            (pool.base_liquidity, pool.token_liquidity)
+        } else {
+            (pool.token_liquidity, pool.base_liquidity)
+        };
+        fair_launch_abi::amm::constant_product_input(
+            amount_out,
+            reserve_in,
+            reserve_out,
+            fair_launch_abi::amm::SWAP_FEE_BPS,
+        )
+        .map(|amount_in| amount_in.to_string())
+    }
+
     /// Get pool by pool ID
     async fn pool(&self, pool_id: String) -> Option<PoolDetails> {
         let pool = self.state.get_pool(&pool_id).await.ok()??;
 
-        let current_time = linera_sdk::linera_base_types::Timestamp::from(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_micros() as u64,
-        );
+        let current_time = current_time();
 
         let age_seconds = if current_time.micros() >= pool.created_at.micros() {
             (current_time.micros() - pool.created_at.micros()) / 1_000_000
@@ -125,23 +482,16 @@ impl QueryRoot {
             0
         };
 
-        Some(PoolDetails {
-            pool: (&pool).into(),
-            is_active: true, // All pools are always active (locked)
-            age_seconds,
-        })
+        let supply = self.state.pool_token_supply(&pool).await.ok()?;
+        // All pools are always active (locked)
+        Some(PoolDetails::new(&pool, supply, true, age_seconds))
     }
 
     /// Get pool by token ID
     async fn pool_by_token(&self, token_id: String) -> Option<PoolDetails> {
         let pool = self.state.get_pool_by_token(&token_id).await.ok()??;
 
-        let current_time = linera_sdk::linera_base_types::Timestamp::from(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_micros() as u64,
-        );
+        let current_time = current_time();
 
         let age_seconds = if current_time.micros() >= pool.created_at.micros() {
             (current_time.micros() - pool.created_at.micros()) / 1_000_000
@@ -149,11 +499,22 @@ impl QueryRoot {
             0
         };
 
-        Some(PoolDetails {
-            pool: (&pool).into(),
-            is_active: true,
-            age_seconds,
-        })
+        let supply = self.state.pool_token_supply(&pool).await.ok()?;
+        Some(PoolDetails::new(&pool, supply, true, age_seconds))
+    }
+
+    /// Every pool of a token, the default pool first
+    async fn pools_by_token(&self, token_id: String) -> Vec<TokenPool> {
+        let pools = self.state.pools_of_token(&token_id).await.unwrap_or_default();
+        let default_pool = self.state.token_to_pool.get(&token_id).await.ok().flatten();
+        pools
+            .into_iter()
+            .map(|(base_asset, pool)| TokenPool {
+                base_asset,
+                is_default: Some(&pool.pool_id) == default_pool.as_ref(),
+                pool: (&pool).into(),
+            })
+            .collect()
     }
 
     /// Check if token has graduated (has a pool)
@@ -198,21 +559,115 @@ impl QueryRoot {
         pools.iter().map(|p| p.into()).collect()
     }
 
+    /// List tracked messages that bounced and their resolution
+    async fn pending_messages(&self) -> Vec<fair_launch_abi::PendingMessageGQL> {
+        self.state
+            .get_pending_messages()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|p| p.into())
+            .collect()
+    }
+
+    /// Latest critical events logged by the contract, newest first
+    async fn critical_events(
+        &self,
+        limit: Option<i32>,
+    ) -> Vec<fair_launch_abi::logging::CriticalEventGQL> {
+        let limit = limit.unwrap_or(20).max(0) as u64;
+        self.state
+            .critical_events
+            .recent(limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|(sequence, event)| {
+                fair_launch_abi::logging::CriticalEventGQL::new(*sequence, event)
+            })
+            .collect()
+    }
+
+    /// Outbox events after sequence `after_seq` (from the first if omitted), oldest first
+    ///
+    /// Pass the last sequence processed to sync incrementally without gaps.
+    async fn events(
+        &self,
+        after_seq: Option<u64>,
+        limit: Option<i32>,
+    ) -> Vec<fair_launch_abi::outbox::OutboxEventGQL> {
+        let limit = limit.unwrap_or(100).clamp(1, 1000) as u64;
+        self.state
+            .outbox
+            .after(after_seq, limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|(sequence, event)| fair_launch_abi::outbox::OutboxEventGQL::new(*sequence, event))
+            .collect()
+    }
+
+    /// Collected protocol fees available to the treasury admin
+    async fn treasury_balance(&self, ctx: &Context<'_>) -> TreasuryBalance {
+        let committed = *self.state.committed_liquidity.get();
+        let reserved = *self.state.buyback_reserved.get();
+        TreasuryBalance {
+            balance: ctx.data_opt::<ApplicationBalance>().map(|ApplicationBalance(balance)| {
+                balance.saturating_sub(committed).saturating_sub(reserved).to_string()
+            }),
+            committed_liquidity: committed.to_string(),
+            buyback_reserved: reserved.to_string(),
+            total_withdrawn: self.state.treasury_withdrawn.get().to_string(),
+            admin: self.state.treasury_admin.get().as_ref().map(|admin| admin.to_string()),
+        }
+    }
+
+    /// Cumulative buyback-and-burn totals across all pools
+    async fn buyback_stats(&self) -> BuybackStats {
+        BuybackStats {
+            total_base_spent: self.state.total_buyback_spent.get().to_string(),
+            total_tokens_burned: self.state.total_buyback_burned.get().to_string(),
+            total_buybacks: *self.state.total_buybacks.get(),
+        }
+    }
+
+    /// A pool's buyback-and-burn account (None before any fee accrued)
+    async fn pool_buyback(&self, pool_id: String) -> Option<PoolBuybackView> {
+        let buyback = self.state.buybacks.get(&pool_id).await.ok()??;
+        Some(PoolBuybackView::new(pool_id, buyback))
+    }
+
     /// Get locked liquidity summary
+    ///
+    /// Time-locked pools count as locked until their creator withdraws,
+    /// even once the lock has expired.
     async fn locked_liquidity_summary(&self) -> LockedLiquiditySummary {
-        let total_pools = *self.state.total_pools.get();
-        let total_tvl = *self.state.total_tvl.get();
-
-        // All pools in Fair Launch are permanently locked
+        let total_locked_pools = *self.state.locked_pools.get();
+        let temporarily_locked_pools = *self.state.time_locked_pools.get();
         LockedLiquiditySummary {
-            total_locked_pools: total_pools,
-            total_locked_tvl: total_tvl.to_string(),
-            permanently_locked_pools: total_pools,
-            temporarily_locked_pools: 0,
+            total_locked_pools,
+            total_locked_tvl: self.state.locked_tvl.get().to_string(),
+            permanently_locked_pools: total_locked_pools.saturating_sub(temporarily_locked_pools),
+            temporarily_locked_pools,
+            withdrawable_pools: self
+                .state
+                .withdrawable_pool_count(current_time())
+                .await
+                .unwrap_or_default(),
         }
     }
 }
 
+/// Current wall-clock time as a Linera timestamp
+fn current_time() -> linera_sdk::linera_base_types::Timestamp {
+    linera_sdk::linera_base_types::Timestamp::from(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64,
+    )
+}
+
 #[derive(SimpleObject)]
 pub struct LockedLiquiditySummary {
     /// Total number of locked pools
@@ -226,6 +681,10 @@ pub struct LockedLiquiditySummary {
 
     /// Number of temporarily locked pools
     pub temporarily_locked_pools: u64,
+
+    /// Temporarily locked pools whose lock has expired but whose creator
+    /// has not withdrawn yet
+    pub withdrawable_pools: u64,
 }
 
 #[cfg(test)]
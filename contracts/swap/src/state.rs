@@ -1,9 +1,31 @@
+use fair_launch_abi::{
+    graduation, logging::CriticalEventLog, outbox::Outbox, units, BaseAsset, PendingMessage,
+    PoolAttestation, Price,
+};
 use linera_sdk::{
-    linera_base_types::Timestamp,
-    views::{MapView, RegisterView, RootView, ViewStorageContext},
+    linera_base_types::{
+        Account, AccountOwner, Amount, ApplicationId, BcsHashable, ChainId, CryptoHash,
+        Timestamp,
+    },
+    views::{MapView, RegisterView, RootView, View, ViewStorageContext},
 };
+use linera_views::ViewError;
 use primitive_types::U256;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Width of a volume bucket (one hour) in microseconds
+const VOLUME_BUCKET_MICROS: u64 = 3_600_000_000;
+
+/// Number of hourly buckets in the rolling volume window (24h)
+const VOLUME_WINDOW_BUCKETS: u64 = 24;
+
+/// Width of a TVL history bucket (one hour) in microseconds
+pub const TVL_BUCKET_MICROS: u64 = VOLUME_BUCKET_MICROS;
+
+/// Most pools a single `get_all_pools` call returns, so no query scans the
+/// whole registry within one block's fuel
+pub const MAX_POOLS_PER_QUERY: usize = 1_000;
 
 /// Pool information for a graduated token
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,13 +42,13 @@ pub struct PoolInfo {
     /// Total base currency raised during bonding curve
     pub base_liquidity: U256,
 
-    /// Initial liquidity ratio (base_per_token)
-    pub initial_ratio: U256,
+    /// Initial liquidity ratio (base per token)
+    pub initial_ratio: Price,
 
     /// Timestamp when pool was created
     pub created_at: Timestamp,
 
-    /// Whether liquidity is locked (always true for Fair Launch)
+    /// Whether liquidity is locked (false once a time-locked pool is withdrawn)
     pub is_locked: bool,
 
     /// Lock expiration timestamp (None = permanent lock)
@@ -47,49 +69,126 @@ impl PoolInfo {
         total_raised: U256,
         created_at: Timestamp,
     ) -> Result<Self, anyhow::Error> {
-        // Validate inputs
-        if total_supply == U256::zero() {
-            anyhow::bail!("Token supply must be greater than zero");
-        }
-        if total_raised == U256::zero() {
-            anyhow::bail!("Total raised must be greater than zero");
-        }
-
-        // Generate pool ID from token ID
-        let pool_id = format!("pool-{}", token_id);
-
-        // Calculate initial ratio: base_per_token = total_raised / total_supply
-        // Use scaled division to preserve precision
-        let initial_ratio = (total_raised * U256::from(1_000_000)) / total_supply;
-
-        // Calculate TVL (total value locked) = 2 * total_raised
-        // (accounts for both token and base currency sides)
-        let tvl = total_raised * U256::from(2);
+        // Validate inputs and derive reserves, ratio and TVL
+        // (shared with the token service's graduation preview)
+        let params = graduation::pool_parameters(&token_id, total_supply, total_raised)?;
 
         Ok(PoolInfo {
-            pool_id,
+            pool_id: params.pool_id,
             token_id,
-            token_liquidity: total_supply,
-            base_liquidity: total_raised,
-            initial_ratio,
+            token_liquidity: params.token_reserve,
+            base_liquidity: params.base_reserve,
+            initial_ratio: params.initial_ratio,
             created_at,
             is_locked: true,
             lock_expires_at: None, // Permanent lock
             trade_count: 0,
-            tvl,
+            tvl: params.tvl,
         })
     }
 
     /// Calculate current token price in base currency
     /// Uses constant product AMM formula: price = base_liquidity / token_liquidity
-    pub fn current_price(&self) -> U256 {
-        if self.token_liquidity == U256::zero() {
-            return U256::zero();
+    pub fn current_price(&self) -> Price {
+        Price::from_ratio(self.base_liquidity, self.token_liquidity).unwrap_or_default()
+    }
+
+    /// Value of both reserves in base currency, the token side at the current price
+    pub fn current_tvl(&self) -> U256 {
+        let token_value = self
+            .current_price()
+            .mul_amount(self.token_liquidity)
+            .unwrap_or_default();
+        self.base_liquidity.saturating_add(token_value)
+    }
+
+    /// Attest this pool's terms and lock status for the token chain
+    pub fn attestation(&self, attested_at: Timestamp) -> PoolAttestation {
+        PoolAttestation {
+            pool_id: self.pool_id.clone(),
+            token_liquidity: self.token_liquidity,
+            base_liquidity: self.base_liquidity,
+            initial_ratio: self.initial_ratio,
+            created_at: self.created_at,
+            is_locked: self.is_locked,
+            lock_expires_at: self.lock_expires_at,
+            pool_info_hash: CryptoHash::new(self),
+            attested_at,
         }
-        self.base_liquidity / self.token_liquidity
     }
 }
 
+impl BcsHashable<'_> for PoolInfo {}
+
+/// Liquidity behind a time-locked pool, returned to its creator after expiry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolLock {
+    /// Account the liquidity is returned to
+    pub creator: Account,
+    /// Asset the liquidity was received in
+    pub base_asset: BaseAsset,
+    /// Amount received at graduation and committed to the pool
+    pub amount: Amount,
+}
+
+/// What a time-locked pool held when its creator withdrew it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockedLiquidity {
+    /// Account the liquidity is returned to
+    pub creator: Account,
+    /// Asset the base side is paid in
+    pub base_asset: BaseAsset,
+    /// The pool's base reserve at withdrawal
+    pub base_amount: Amount,
+    /// The pool's token reserve at withdrawal
+    pub token_amount: U256,
+}
+
+/// Asset a pool's base side is held in, recorded when the pool opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolQuote {
+    pub base_asset: BaseAsset,
+    /// Decimals of the pool's base units
+    pub base_decimals: u8,
+}
+
+/// Latest supply a graduated token reported to the swap chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenSupply {
+    pub total_supply: U256,
+    /// Supply outside time locks
+    pub circulating_supply: U256,
+    pub reported_at: Timestamp,
+}
+
+/// Swap fees set aside for a pool's buyback-and-burn, and what it has burned
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolBuyback {
+    /// Base currency fees waiting for the next buyback
+    pub accrued_base: U256,
+    /// Token fees waiting for the next burn
+    pub pending_burn: U256,
+    /// Base currency spent on buybacks so far
+    pub base_spent: U256,
+    /// Tokens sent to the token chain for burning so far
+    pub tokens_burned: U256,
+    pub buyback_count: u64,
+    pub last_buyback_at: Option<Timestamp>,
+}
+
+/// One swap against a pool, as kept in the pool's trade history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapTrade {
+    pub trader: Account,
+    /// Whether tokens were bought with base currency (false: sold for it)
+    pub is_buy: bool,
+    pub token_amount: U256,
+    pub base_amount: U256,
+    /// Pool price after the trade
+    pub price: Price,
+    pub timestamp: Timestamp,
+}
+
 impl From<&PoolInfo> for fair_launch_abi::PoolInfoGQL {
     fn from(pool: &PoolInfo) -> Self {
         Self {
@@ -97,7 +196,8 @@ impl From<&PoolInfo> for fair_launch_abi::PoolInfoGQL {
             token_id: pool.token_id.clone(),
             token_liquidity: pool.token_liquidity.to_string(),
             base_liquidity: pool.base_liquidity.to_string(),
-            initial_ratio: pool.initial_ratio.to_string(),
+            initial_ratio: pool.initial_ratio.raw().to_string(),
+            price_decimals: fair_launch_abi::price::PRICE_DECIMALS,
             created_at: pool.created_at.micros().to_string(),
             is_locked: pool.is_locked,
             lock_expires_at: pool.lock_expires_at.map(|t| t.micros().to_string()),
@@ -114,9 +214,23 @@ pub struct SwapState {
     /// All pools: pool_id → PoolInfo
     pub pools: MapView<String, PoolInfo>,
 
-    /// Token to pool mapping: token_id → pool_id
+    /// Pools opened at graduation by pool key: token_id → the token's default
+    /// pool, its stable key → the stablecoin-quoted pool
     pub token_to_pool: MapView<String, String>,
 
+    /// Every pool of a token by quote asset: token_id → base asset → pool_id
+    ///
+    /// Absent for tokens graduated before it was kept, which read their
+    /// graduation pools instead until `migrate_asset_pools` indexes them.
+    pub asset_pools: MapView<String, BTreeMap<String, String>>,
+
+    /// Tokens deposited towards pools not yet opened: (token_id, depositor) → amount
+    pub pool_token_deposits: MapView<(String, AccountOwner), U256>,
+
+    /// Base asset of each pool: pool_id → PoolQuote
+    /// (absent for pools opened before it was recorded)
+    pub pool_quotes: MapView<String, PoolQuote>,
+
     /// Total number of pools created
     pub total_pools: RegisterView<u64>,
 
@@ -125,9 +239,109 @@ pub struct SwapState {
 
     /// Contract creation timestamp
     pub created_at: RegisterView<Timestamp>,
+
+    /// Native balance already committed to pools (verified graduation transfers)
+    pub committed_liquidity: RegisterView<Amount>,
+
+    /// Fungible balances already committed to pools: fungible app → amount
+    pub committed_fungible_liquidity: MapView<ApplicationId, Amount>,
+
+    /// Latest reported supply per graduated token: token_id → TokenSupply
+    pub token_supplies: MapView<String, TokenSupply>,
+
+    /// Chain hosting each graduated token: token_id → chain, recorded at
+    /// graduation (absent for tokens graduated before it was recorded)
+    pub token_chains: MapView<String, ChainId>,
+
+    /// Time-locked pools: pool_id → liquidity withdrawable after expiry
+    /// (permanently locked pools have no entry)
+    pub pool_locks: MapView<String, PoolLock>,
+
+    /// Number of pools still locked (permanently or until withdrawn)
+    pub locked_pools: RegisterView<u64>,
+
+    /// Number of locked pools under a time lock (a subset of `locked_pools`)
+    pub time_locked_pools: RegisterView<u64>,
+
+    /// TVL of the locked pools, maintained alongside `total_tvl`
+    pub locked_tvl: RegisterView<U256>,
+
+    /// Locked pools included in the three lock totals above; pools locked
+    /// before the totals join through `backfill_locked_totals`
+    pub counted_locked_pools: MapView<String, ()>,
+
+    /// Total swaps executed across all pools
+    pub total_swaps: RegisterView<u64>,
+
+    /// Cumulative swap volume across all pools (in base currency)
+    pub total_volume: RegisterView<U256>,
+
+    /// Total TVL at the last change within each hour: hour index → TVL
+    /// Hours without a change are absent (the TVL carried over unchanged)
+    pub tvl_history: MapView<u64, U256>,
+
+    /// Rolling hourly volume buckets: hour index → base currency volume
+    /// Buckets older than the 24h window are pruned on write
+    pub volume_buckets: MapView<u64, U256>,
+
+    /// Per-pool trade history: "{pool_id}:{sequence}" → SwapTrade
+    /// Sequences run from 0 up to the pool's `trade_count`
+    pub pool_trades: MapView<String, SwapTrade>,
+
+    /// Swap count per trader (presence marks a unique trader)
+    pub trader_swaps: MapView<Account, u64>,
+
+    /// Number of unique traders across all pools
+    pub unique_traders: RegisterView<u64>,
+
+    /// Buyback-and-burn accounts: pool_id → PoolBuyback
+    pub buybacks: MapView<String, PoolBuyback>,
+
+    /// Base currency spent on buybacks across all pools
+    pub total_buyback_spent: RegisterView<U256>,
+
+    /// Tokens burned by buybacks across all pools
+    pub total_buyback_burned: RegisterView<U256>,
+
+    /// Buybacks executed across all pools
+    pub total_buybacks: RegisterView<u64>,
+
+    /// Native base currency withheld for buybacks and not yet spent, kept
+    /// out of the treasury
+    pub buyback_reserved: RegisterView<Amount>,
+
+    /// Account managing the treasury (the account that instantiated the swap
+    /// application, until handed over)
+    pub treasury_admin: RegisterView<Option<Account>>,
+
+    /// Native currency paid out of the treasury so far
+    pub treasury_withdrawn: RegisterView<Amount>,
+
+    /// Bounced tracked messages: "{kind}:{token_id}" → PendingMessage
+    pub pending_messages: MapView<String, PendingMessage>,
+
+    /// Latest critical log lines, mirrored on-chain for operators
+    pub critical_events: CriticalEventLog,
+
+    /// Sequenced log of externally relevant events for indexers
+    pub outbox: Outbox,
+
+    /// Version of the swap bytecode that instantiated this state
+    pub contract_version: RegisterView<u32>,
+
+    /// Change counter reported under `STATE_VERSION_EXTENSION`
+    pub state_version: RegisterView<u64>,
 }
 
 impl SwapState {
+    /// Bump `state_version` if anything changed since the last save
+    pub async fn bump_version(&mut self) {
+        if self.has_pending_changes().await {
+            let version = *self.state_version.get();
+            self.state_version.set(version + 1);
+        }
+    }
+
     /// Initialize the swap contract
     pub async fn initialize(&mut self, created_at: Timestamp) -> Result<(), anyhow::Error> {
         self.total_pools.set(0);
@@ -136,30 +350,87 @@ impl SwapState {
         Ok(())
     }
 
-    /// Create a new pool for a graduated token
+    /// Create a new native-quoted pool for a graduated token
     pub async fn create_pool(
         &mut self,
         token_id: String,
         total_supply: U256,
         total_raised: U256,
         created_at: Timestamp,
+    ) -> Result<PoolInfo, anyhow::Error> {
+        let quote = PoolQuote {
+            base_asset: BaseAsset::Native,
+            base_decimals: units::NATIVE_DECIMALS,
+        };
+        self.create_pool_in(token_id, quote, total_supply, total_raised, created_at)
+            .await
+    }
+
+    /// Create a graduation pool quoted as `quote` under `pool_key`
+    /// (a token ID or its stable key)
+    pub async fn create_pool_in(
+        &mut self,
+        pool_key: String,
+        quote: PoolQuote,
+        total_supply: U256,
+        total_raised: U256,
+        created_at: Timestamp,
     ) -> Result<PoolInfo, anyhow::Error> {
         // Check if pool already exists for this token
-        if self.token_to_pool.get(&token_id).await?.is_some() {
-            anyhow::bail!("Pool already exists for token: {}", token_id);
+        if self.token_to_pool.contains_key(&pool_key).await? {
+            anyhow::bail!("Pool already exists for token: {}", pool_key);
+        }
+
+        let pool = self
+            .insert_pool(pool_key.clone(), quote, total_supply, total_raised, created_at)
+            .await?;
+        self.token_to_pool.insert(&pool_key, pool.pool_id.clone())?;
+        Ok(pool)
+    }
+
+    /// Open a pool pairing an already graduated token with another base asset
+    pub async fn create_asset_pool(
+        &mut self,
+        token_id: &str,
+        quote: PoolQuote,
+        token_amount: U256,
+        base_amount: U256,
+        created_at: Timestamp,
+    ) -> Result<PoolInfo, anyhow::Error> {
+        if !self.has_pool(token_id).await? {
+            anyhow::bail!("Token {} has not graduated", token_id);
+        }
+        let pool_key = graduation::asset_pool_key(token_id, &quote.base_asset);
+        self.insert_pool(pool_key, quote, token_amount, base_amount, created_at)
+            .await
+    }
+
+    /// Store a new pool and add it to the totals, unless the token already has
+    /// a pool in the quoted asset
+    async fn insert_pool(
+        &mut self,
+        pool_key: String,
+        quote: PoolQuote,
+        total_supply: U256,
+        total_raised: U256,
+        created_at: Timestamp,
+    ) -> Result<PoolInfo, anyhow::Error> {
+        let token_id = graduation::pool_key_token_id(&pool_key).to_string();
+        let base_asset = quote.base_asset.to_string();
+        let mut asset_pools = self.asset_pools_of(&token_id).await?;
+        if asset_pools.contains_key(&base_asset) {
+            anyhow::bail!("Token {} already has a pool in {}", token_id, base_asset);
         }
 
         // Create new pool
-        let pool = PoolInfo::new(
-            token_id.clone(),
-            total_supply,
-            total_raised,
-            created_at,
-        )?;
+        let pool = PoolInfo::new(pool_key, total_supply, total_raised, created_at)?;
 
         // Store pool
         self.pools.insert(&pool.pool_id, pool.clone())?;
-        self.token_to_pool.insert(&token_id, pool.pool_id.clone())?;
+        self.outbox.push("PoolCreated", Some(&pool.pool_id), &pool, created_at)?;
+        asset_pools.insert(base_asset, pool.pool_id.clone());
+        self.asset_pools.insert(&token_id, asset_pools)?;
+        self.pool_quotes.insert(&pool.pool_id, quote)?;
 
         // Update totals
         let current_pools = self.total_pools.get();
@@ -167,6 +438,10 @@ impl SwapState {
 
         let current_tvl = *self.total_tvl.get();
         self.total_tvl.set(current_tvl + pool.tvl);
+        if pool.is_locked {
+            self.count_locked_pool(&pool)?;
+        }
+        self.record_tvl(created_at)?;
 
         Ok(pool)
     }
@@ -176,7 +451,7 @@ impl SwapState {
         Ok(self.pools.get(pool_id).await?)
     }
 
-    /// Get pool by token ID
+    /// Get the graduation pool under a pool key (a token ID gives its default pool)
     pub async fn get_pool_by_token(&self, token_id: &str) -> Result<Option<PoolInfo>, anyhow::Error> {
         if let Some(pool_id) = self.token_to_pool.get(token_id).await? {
             Ok(self.pools.get(&pool_id).await?)
@@ -185,12 +460,156 @@ impl SwapState {
         }
     }
 
-    /// Get all pools (paginated)
+    /// Every pool of a token with its base asset, the default pool first
+    pub async fn pools_of_token(
+        &self,
+        token_id: &str,
+    ) -> Result<Vec<(String, PoolInfo)>, anyhow::Error> {
+        let default_pool = self.token_to_pool.get(token_id).await?;
+        let mut pools = Vec::new();
+        for (base_asset, pool_id) in self.asset_pools_of(token_id).await? {
+            if let Some(pool) = self.pools.get(&pool_id).await? {
+                pools.push((base_asset, pool));
+            }
+        }
+        pools.sort_by_key(|(_, pool)| Some(&pool.pool_id) != default_pool.as_ref());
+        Ok(pools)
+    }
+
+    /// A token's pools by base asset, read from its graduation pools if it
+    /// has not been indexed yet
+    /// Whether the token already has a pool quoted in `base_asset`
+    pub async fn has_asset_pool(
+        &self,
+        token_id: &str,
+        base_asset: &BaseAsset,
+    ) -> Result<bool, anyhow::Error> {
+        let asset_pools = self.asset_pools_of(token_id).await?;
+        Ok(asset_pools.contains_key(&base_asset.to_string()))
+    }
+
+    async fn asset_pools_of(
+        &self,
+        token_id: &str,
+    ) -> Result<BTreeMap<String, String>, anyhow::Error> {
+        if let Some(asset_pools) = self.asset_pools.get(token_id).await? {
+            return Ok(asset_pools);
+        }
+        let mut asset_pools = BTreeMap::new();
+        let stable_key = graduation::stable_pool_key(token_id);
+        for pool_key in [token_id, stable_key.as_str()] {
+            let Some(pool_id) = self.token_to_pool.get(pool_key).await? else {
+                continue;
+            };
+            let base_asset = self.pool_base_asset(&pool_id).await?;
+            asset_pools.insert(base_asset.to_string(), pool_id);
+        }
+        Ok(asset_pools)
+    }
+
+    /// Asset a pool's base side is held in
+    pub async fn pool_base_asset(&self, pool_id: &str) -> Result<BaseAsset, ViewError> {
+        // Pools without a quote predate stable pools and are native
+        Ok(self
+            .pool_quotes
+            .get(pool_id)
+            .await?
+            .map_or(BaseAsset::Native, |quote| quote.base_asset))
+    }
+
+    /// Index the pools of up to `max_entries` tokens graduated before
+    /// `asset_pools` was kept, returning how many were indexed
+    pub async fn migrate_asset_pools(
+        &mut self,
+        max_entries: usize,
+    ) -> Result<usize, anyhow::Error> {
+        let mut migrated = 0;
+        for pool_key in self.token_to_pool.indices().await? {
+            if migrated >= max_entries {
+                break;
+            }
+            if graduation::pool_key_token_id(&pool_key) != pool_key
+                || self.asset_pools.contains_key(&pool_key).await?
+            {
+                continue;
+            }
+            let asset_pools = self.asset_pools_of(&pool_key).await?;
+            self.asset_pools.insert(&pool_key, asset_pools)?;
+            migrated += 1;
+        }
+        Ok(migrated)
+    }
+
+    /// Add a locked pool to the lock totals
+    fn count_locked_pool(&mut self, pool: &PoolInfo) -> Result<(), ViewError> {
+        let locked_pools = *self.locked_pools.get();
+        self.locked_pools.set(locked_pools + 1);
+        if pool.lock_expires_at.is_some() {
+            let time_locked = *self.time_locked_pools.get();
+            self.time_locked_pools.set(time_locked + 1);
+        }
+        let locked_tvl = *self.locked_tvl.get();
+        self.locked_tvl.set(locked_tvl.saturating_add(pool.tvl));
+        self.counted_locked_pools.insert(&pool.pool_id, ())
+    }
+
+    /// Add up to `max_entries` pools locked before the lock totals were kept
+    /// to them, returning how many were added
+    pub async fn backfill_locked_totals(
+        &mut self,
+        max_entries: usize,
+    ) -> Result<usize, anyhow::Error> {
+        let mut added = 0;
+        for pool_id in self.pools.indices().await? {
+            if added >= max_entries {
+                break;
+            }
+            if self.counted_locked_pools.contains_key(&pool_id).await? {
+                continue;
+            }
+            let Some(pool) = self.pools.get(&pool_id).await? else {
+                continue;
+            };
+            if pool.is_locked {
+                self.count_locked_pool(&pool)?;
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Tokens `depositor` has deposited towards a new pool of `token_id`
+    pub async fn pool_tokens_deposited(
+        &self,
+        token_id: &str,
+        depositor: AccountOwner,
+    ) -> Result<U256, ViewError> {
+        let key = (token_id.to_string(), depositor);
+        Ok(self.pool_token_deposits.get(&key).await?.unwrap_or_default())
+    }
+
+    /// Set a depositor's unused pool tokens (removed once zero)
+    pub fn set_pool_tokens_deposited(
+        &mut self,
+        token_id: &str,
+        depositor: AccountOwner,
+        amount: U256,
+    ) -> Result<(), ViewError> {
+        let key = (token_id.to_string(), depositor);
+        if amount.is_zero() {
+            self.pool_token_deposits.remove(&key)
+        } else {
+            self.pool_token_deposits.insert(&key, amount)
+        }
+    }
+
+    /// Get all pools (paginated, at most `MAX_POOLS_PER_QUERY` per call)
     pub async fn get_all_pools(
         &self,
         offset: usize,
         limit: usize,
     ) -> Result<Vec<PoolInfo>, anyhow::Error> {
+        let limit = limit.min(MAX_POOLS_PER_QUERY);
         let mut pools = Vec::new();
         let mut count = 0;
         let mut skipped = 0;
@@ -214,9 +633,450 @@ impl SwapState {
         Ok(pools)
     }
 
+    /// Balance of a fungible base asset already committed to pools
+    pub async fn committed_fungible(&self, application_id: ApplicationId) -> Result<Amount, ViewError> {
+        Ok(self
+            .committed_fungible_liquidity
+            .get(&application_id)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Mark verified graduation funds as backing a pool
+    pub async fn commit_liquidity(&mut self, asset: BaseAsset, amount: Amount) -> Result<(), ViewError> {
+        match asset {
+            BaseAsset::Native => {
+                let committed = *self.committed_liquidity.get();
+                self.committed_liquidity.set(committed.saturating_add(amount));
+            }
+            BaseAsset::Fungible(application_id) => {
+                let committed = self.committed_fungible(application_id).await?;
+                self.committed_fungible_liquidity
+                    .insert(&application_id, committed.saturating_add(amount))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Release committed funds that leave the application
+    pub async fn release_liquidity(&mut self, asset: BaseAsset, amount: Amount) -> Result<(), ViewError> {
+        match asset {
+            BaseAsset::Native => {
+                let committed = *self.committed_liquidity.get();
+                self.committed_liquidity.set(committed.saturating_sub(amount));
+            }
+            BaseAsset::Fungible(application_id) => {
+                let committed = self.committed_fungible(application_id).await?;
+                self.committed_fungible_liquidity
+                    .insert(&application_id, committed.saturating_sub(amount))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Put a pool under a time lock expiring at `expires_at`, returning the updated pool
+    pub async fn lock_pool(
+        &mut self,
+        pool_id: &str,
+        lock: PoolLock,
+        expires_at: Timestamp,
+    ) -> Result<PoolInfo, anyhow::Error> {
+        let mut pool = self
+            .pools
+            .get(pool_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Pool not found: {}", pool_id))?;
+        let counted = self.counted_locked_pools.contains_key(pool_id).await?;
+        if pool.lock_expires_at.is_none() && counted {
+            let time_locked = *self.time_locked_pools.get();
+            self.time_locked_pools.set(time_locked + 1);
+        }
+        pool.lock_expires_at = Some(expires_at);
+        self.pools.insert(pool_id, pool.clone())?;
+        self.pool_locks.insert(pool_id, lock)?;
+        Ok(pool)
+    }
+
+    /// Empty a time-locked pool whose lock has expired, returning the
+    /// reserves it held when emptied
+    ///
+    /// The pool is kept for its history, unlocked and with zero reserves.
+    /// Swaps since graduation moved the reserves away from the locked amount,
+    /// so what is released is the current base reserve.
+    pub async fn unlock_pool(
+        &mut self,
+        pool_id: &str,
+        now: Timestamp,
+    ) -> Result<UnlockedLiquidity, anyhow::Error> {
+        let mut pool = self
+            .pools
+            .get(pool_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Pool not found: {}", pool_id))?;
+        let lock = self
+            .pool_locks
+            .get(pool_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Pool {} is permanently locked", pool_id))?;
+        match pool.lock_expires_at {
+            Some(expires_at) if now >= expires_at => {}
+            _ => anyhow::bail!("Pool {} is still locked", pool_id),
+        }
+        // Pools without a quote predate stable pools and are native
+        let base_decimals = self
+            .pool_quotes
+            .get(pool_id)
+            .await?
+            .map_or(units::NATIVE_DECIMALS, |quote| quote.base_decimals);
+        let unlocked = UnlockedLiquidity {
+            creator: lock.creator,
+            base_asset: lock.base_asset,
+            base_amount: units::to_amount(pool.base_liquidity, base_decimals)?,
+            token_amount: pool.token_liquidity,
+        };
+
+        let total_tvl = *self.total_tvl.get();
+        self.total_tvl.set(total_tvl.saturating_sub(pool.tvl));
+        self.record_tvl(now)?;
+        if pool.is_locked && self.counted_locked_pools.contains_key(pool_id).await? {
+            let locked_pools = *self.locked_pools.get();
+            self.locked_pools.set(locked_pools.saturating_sub(1));
+            let time_locked = *self.time_locked_pools.get();
+            self.time_locked_pools.set(time_locked.saturating_sub(1));
+            let locked_tvl = *self.locked_tvl.get();
+            self.locked_tvl.set(locked_tvl.saturating_sub(pool.tvl));
+            self.counted_locked_pools.remove(pool_id)?;
+        }
+        pool.is_locked = false;
+        pool.token_liquidity = U256::zero();
+        pool.base_liquidity = U256::zero();
+        pool.tvl = U256::zero();
+        self.pools.insert(pool_id, pool)?;
+        self.pool_locks.remove(pool_id)?;
+        self.release_liquidity(unlocked.base_asset, unlocked.base_amount).await?;
+        self.outbox.push("PoolUnlocked", Some(pool_id), &unlocked, now)?;
+        Ok(unlocked)
+    }
+
+    /// Store a token's reported supply and refresh the TVL of its pools
+    ///
+    /// Reports older than the stored one are ignored; returns whether the
+    /// report was applied.
+    pub async fn record_supply(
+        &mut self,
+        token_id: &str,
+        supply: TokenSupply,
+    ) -> Result<bool, anyhow::Error> {
+        if let Some(current) = self.token_supplies.get(token_id).await? {
+            if current.reported_at > supply.reported_at {
+                return Ok(false);
+            }
+        }
+        self.token_supplies.insert(token_id, supply)?;
+        self.outbox.push("SupplyChanged", Some(token_id), &supply, supply.reported_at)?;
+
+        for key in [token_id.to_string(), graduation::stable_pool_key(token_id)] {
+            if let Some(pool) = self.get_pool_by_token(&key).await? {
+                self.store_traded_pool(pool, supply.reported_at).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Store a pool whose reserves changed, revaluing its TVL at the current
+    /// price and adjusting `total_tvl` by the difference
+    pub async fn store_traded_pool(
+        &mut self,
+        mut pool: PoolInfo,
+        at: Timestamp,
+    ) -> Result<(), ViewError> {
+        let tvl = pool.current_tvl();
+        let total_tvl = *self.total_tvl.get();
+        self.total_tvl.set(total_tvl.saturating_sub(pool.tvl).saturating_add(tvl));
+        if pool.is_locked && self.counted_locked_pools.contains_key(&pool.pool_id).await? {
+            let locked_tvl = *self.locked_tvl.get();
+            self.locked_tvl.set(locked_tvl.saturating_sub(pool.tvl).saturating_add(tvl));
+        }
+        pool.tvl = tvl;
+        self.pools.insert(&pool.pool_id.clone(), pool)?;
+        self.record_tvl(at)
+    }
+
+    /// Number of time-locked pools whose lock expired by `now` (iterates
+    /// `pool_locks` only, so permanently locked pools cost nothing)
+    pub async fn withdrawable_pool_count(&self, now: Timestamp) -> Result<u64, ViewError> {
+        let mut withdrawable = 0;
+        for pool_id in self.pool_locks.indices().await? {
+            let expired = self
+                .pools
+                .get(&pool_id)
+                .await?
+                .and_then(|pool| pool.lock_expires_at)
+                .is_some_and(|expires_at| now >= expires_at);
+            if expired {
+                withdrawable += 1;
+            }
+        }
+        Ok(withdrawable)
+    }
+
+    /// Record the current total TVL in the history bucket for `at`
+    fn record_tvl(&mut self, at: Timestamp) -> Result<(), ViewError> {
+        let bucket = at.micros() / TVL_BUCKET_MICROS;
+        self.tvl_history.insert(&bucket, *self.total_tvl.get())
+    }
+
+    /// Recorded TVL buckets within the last `hours` hours before `now`,
+    /// oldest first: (hour index, total TVL)
+    pub async fn get_tvl_history(
+        &self,
+        now: Timestamp,
+        hours: u64,
+    ) -> Result<Vec<(u64, U256)>, ViewError> {
+        let current = now.micros() / TVL_BUCKET_MICROS;
+        let oldest = current.saturating_sub(hours.saturating_sub(1));
+        let mut history = Vec::new();
+        for bucket in oldest..=current {
+            if let Some(tvl) = self.tvl_history.get(&bucket).await? {
+                history.push((bucket, tvl));
+            }
+        }
+        Ok(history)
+    }
+
+    /// Latest reported supply of the token a pool trades
+    pub async fn pool_token_supply(
+        &self,
+        pool: &PoolInfo,
+    ) -> Result<Option<TokenSupply>, ViewError> {
+        let token_id = graduation::pool_key_token_id(&pool.token_id);
+        self.token_supplies.get(token_id).await
+    }
+
+    /// Record a swap in the platform-wide volume counters
+    pub async fn record_swap(
+        &mut self,
+        trader: Account,
+        base_volume: U256,
+        at: Timestamp,
+    ) -> Result<(), anyhow::Error> {
+        let swaps = *self.total_swaps.get();
+        self.total_swaps.set(swaps + 1);
+
+        let volume = *self.total_volume.get();
+        self.total_volume.set(volume + base_volume);
+
+        // Add to the current hourly bucket and prune buckets outside the window
+        let bucket = at.micros() / VOLUME_BUCKET_MICROS;
+        let bucket_volume = self.volume_buckets.get(&bucket).await?.unwrap_or_default();
+        self.volume_buckets.insert(&bucket, bucket_volume + base_volume)?;
+
+        let oldest = bucket.saturating_sub(VOLUME_WINDOW_BUCKETS - 1);
+        for stale in self.volume_buckets.indices().await? {
+            if stale < oldest {
+                self.volume_buckets.remove(&stale)?;
+            }
+        }
+
+        // Track unique traders
+        let trader_swaps = self.trader_swaps.get(&trader).await?;
+        if trader_swaps.is_none() {
+            let traders = *self.unique_traders.get();
+            self.unique_traders.set(traders + 1);
+        }
+        self.trader_swaps.insert(&trader, trader_swaps.unwrap_or(0) + 1)?;
+
+        Ok(())
+    }
+
+    /// Withhold swap fees for a pool's next buyback
+    pub async fn accrue_buyback(
+        &mut self,
+        pool_id: &str,
+        base: U256,
+        tokens: U256,
+    ) -> Result<(), ViewError> {
+        let mut buyback = self.buybacks.get(pool_id).await?.unwrap_or_default();
+        buyback.accrued_base = buyback.accrued_base.saturating_add(base);
+        buyback.pending_burn = buyback.pending_burn.saturating_add(tokens);
+        self.buybacks.insert(pool_id, buyback)?;
+
+        let reserved = *self.buyback_reserved.get();
+        let native = self.native_base_amount(pool_id, base).await?;
+        self.buyback_reserved.set(reserved.saturating_add(native));
+        Ok(())
+    }
+
+    /// `base` units of a pool's base side as native currency (zero for pools
+    /// quoted in another asset)
+    async fn native_base_amount(&self, pool_id: &str, base: U256) -> Result<Amount, ViewError> {
+        let Some(quote) = self.pool_quotes.get(pool_id).await? else {
+            // Pools without a quote predate stable pools and are native
+            return Ok(units::to_amount(base, units::NATIVE_DECIMALS).unwrap_or(Amount::MAX));
+        };
+        if quote.base_asset != BaseAsset::Native {
+            return Ok(Amount::ZERO);
+        }
+        Ok(units::to_amount(base, quote.base_decimals).unwrap_or(Amount::MAX))
+    }
+
+    /// Record a buyback that spent a pool's accrued base currency and burned
+    /// `tokens_burned`, clearing what had accrued
+    pub async fn record_buyback(
+        &mut self,
+        pool_id: &str,
+        tokens_burned: U256,
+        at: Timestamp,
+    ) -> Result<PoolBuyback, ViewError> {
+        let mut buyback = self.buybacks.get(pool_id).await?.unwrap_or_default();
+        let spent = buyback.accrued_base;
+        let reserved = *self.buyback_reserved.get();
+        let native = self.native_base_amount(pool_id, spent).await?;
+        self.buyback_reserved.set(reserved.saturating_sub(native));
+        buyback.base_spent = buyback.base_spent.saturating_add(spent);
+        buyback.tokens_burned = buyback.tokens_burned.saturating_add(tokens_burned);
+        buyback.buyback_count += 1;
+        buyback.last_buyback_at = Some(at);
+        buyback.accrued_base = U256::zero();
+        buyback.pending_burn = U256::zero();
+        self.buybacks.insert(pool_id, buyback.clone())?;
+        self.outbox.push("BuybackBurned", Some(pool_id), &buyback, at)?;
+
+        let total_spent = *self.total_buyback_spent.get();
+        self.total_buyback_spent.set(total_spent.saturating_add(spent));
+        let total_burned = *self.total_buyback_burned.get();
+        self.total_buyback_burned.set(total_burned.saturating_add(tokens_burned));
+        let total_buybacks = *self.total_buybacks.get();
+        self.total_buybacks.set(total_buybacks + 1);
+        Ok(buyback)
+    }
+
+    /// Hand treasury management to `account`
+    pub fn set_treasury_admin(&mut self, account: Account, at: Timestamp) -> Result<(), ViewError> {
+        self.treasury_admin.set(Some(account));
+        self.outbox.push("TreasuryAdminChanged", None, &account, at)?;
+        Ok(())
+    }
+
+    /// Record a payout from the treasury
+    pub fn record_treasury_withdrawal(
+        &mut self,
+        to: Account,
+        amount: Amount,
+        at: Timestamp,
+    ) -> Result<(), ViewError> {
+        let withdrawn = *self.treasury_withdrawn.get();
+        self.treasury_withdrawn.set(withdrawn.saturating_add(amount));
+        self.outbox.push("TreasuryWithdrawn", None, &(to, amount), at)?;
+        Ok(())
+    }
+
+    fn pool_trade_key(pool_id: &str, sequence: u64) -> String {
+        format!("{}:{}", pool_id, sequence)
+    }
+
+    /// Store the trade with the given sequence number in a pool's history
+    pub fn insert_pool_trade(
+        &mut self,
+        pool_id: &str,
+        sequence: u64,
+        trade: SwapTrade,
+    ) -> Result<(), ViewError> {
+        self.outbox.push("Swap", Some(pool_id), &trade, trade.timestamp)?;
+        self.pool_trades.insert(&Self::pool_trade_key(pool_id, sequence), trade)
+    }
+
+    /// A pool's recorded trades, newest first
+    pub async fn get_pool_trades(
+        &self,
+        pool: &PoolInfo,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<SwapTrade>, ViewError> {
+        let mut trades = Vec::new();
+        let newest = pool.trade_count.saturating_sub(offset);
+        for sequence in (0..newest).rev().take(limit as usize) {
+            let key = Self::pool_trade_key(&pool.pool_id, sequence);
+            if let Some(trade) = self.pool_trades.get(&key).await? {
+                trades.push(trade);
+            }
+        }
+        Ok(trades)
+    }
+
+    /// Base currency traded in a pool over the 24h ending at `now`
+    ///
+    /// Walks the history back from the newest trade, which is ordered by time.
+    pub async fn pool_volume_24h(&self, pool: &PoolInfo, now: Timestamp) -> Result<U256, ViewError> {
+        let since = now
+            .micros()
+            .saturating_sub(VOLUME_WINDOW_BUCKETS * VOLUME_BUCKET_MICROS);
+        let mut volume = U256::zero();
+        for sequence in (0..pool.trade_count).rev() {
+            let key = Self::pool_trade_key(&pool.pool_id, sequence);
+            match self.pool_trades.get(&key).await? {
+                Some(trade) if trade.timestamp.micros() >= since => volume += trade.base_amount,
+                _ => break,
+            }
+        }
+        Ok(volume)
+    }
+
+    /// Swap volume over the 24h window ending at `now`
+    pub async fn volume_24h(&self, now: Timestamp) -> Result<U256, anyhow::Error> {
+        let current = now.micros() / VOLUME_BUCKET_MICROS;
+        let oldest = current.saturating_sub(VOLUME_WINDOW_BUCKETS - 1);
+
+        let mut volume = U256::zero();
+        for bucket in oldest..=current {
+            if let Some(bucket_volume) = self.volume_buckets.get(&bucket).await? {
+                volume += bucket_volume;
+            }
+        }
+        Ok(volume)
+    }
+
+    /// Record a bounced message, returning how many times it has bounced
+    pub async fn record_bounce(
+        &mut self,
+        kind: &str,
+        token_id: &str,
+        bounced_at: Timestamp,
+        resolution: &str,
+    ) -> Result<u32, anyhow::Error> {
+        let key = PendingMessage::key(kind, token_id);
+        let bounces = self
+            .pending_messages
+            .get(&key)
+            .await?
+            .map(|pending| pending.bounces)
+            .unwrap_or(0)
+            + 1;
+        let pending = PendingMessage {
+            kind: kind.to_string(),
+            token_id: token_id.to_string(),
+            bounces,
+            last_bounced_at: bounced_at,
+            resolution: resolution.to_string(),
+        };
+        self.pending_messages.insert(&key, pending)?;
+        Ok(bounces)
+    }
+
+    /// Get all pending (bounced) messages
+    pub async fn get_pending_messages(&self) -> Result<Vec<PendingMessage>, anyhow::Error> {
+        let mut pending = Vec::new();
+        for key in self.pending_messages.indices().await? {
+            if let Some(message) = self.pending_messages.get(&key).await? {
+                pending.push(message);
+            }
+        }
+        Ok(pending)
+    }
+
     /// Check if token has graduated (has a pool)
     pub async fn has_pool(&self, token_id: &str) -> Result<bool, anyhow::Error> {
-        Ok(self.token_to_pool.get(token_id).await?.is_some())
+        Ok(self.token_to_pool.contains_key(token_id).await?)
     }
 }
 
@@ -382,6 +1242,92 @@ mod tests {
         assert_eq!(pools.len(), 5);
     }
 
+    #[tokio::test]
+    async fn test_state_version_bumps_on_change() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+
+        state.bump_version().await;
+        assert_eq!(*state.state_version.get(), 0);
+
+        state.total_swaps.set(1);
+        state.bump_version().await;
+        assert_eq!(*state.state_version.get(), 1);
+
+        // Nothing changed since the save
+        state.save().await.unwrap();
+        state.bump_version().await;
+        assert_eq!(*state.state_version.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rolling_volume_counters() {
+        use linera_sdk::linera_base_types::{AccountOwner, ChainId};
+
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        state.initialize(Timestamp::from(0)).await.unwrap();
+
+        let alice = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let bob = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        let hour = VOLUME_BUCKET_MICROS;
+
+        state.record_swap(alice, U256::from(100), Timestamp::from(0)).await.unwrap();
+        state.record_swap(alice, U256::from(50), Timestamp::from(hour)).await.unwrap();
+        state.record_swap(bob, U256::from(25), Timestamp::from(30 * hour)).await.unwrap();
+
+        assert_eq!(*state.total_swaps.get(), 3);
+        assert_eq!(*state.total_volume.get(), U256::from(175));
+        assert_eq!(*state.unique_traders.get(), 2);
+
+        // Only the last swap falls in the 24h window ending at hour 30
+        assert_eq!(state.volume_24h(Timestamp::from(30 * hour)).await.unwrap(), U256::from(25));
+
+        // Buckets outside the window were pruned
+        assert_eq!(state.volume_24h(Timestamp::from(hour)).await.unwrap(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_pool_trade_history() {
+        use linera_sdk::linera_base_types::{AccountOwner, ChainId};
+
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+
+        let mut pool = PoolInfo::new(
+            "token-0".to_string(),
+            U256::from(1_000_000),
+            U256::from(10_000),
+            Timestamp::from(0),
+        )
+        .unwrap();
+        let trader = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let hour = VOLUME_BUCKET_MICROS;
+        for (index, at) in [0, 10 * hour, 30 * hour].into_iter().enumerate() {
+            let trade = SwapTrade {
+                trader,
+                is_buy: index % 2 == 0,
+                token_amount: U256::from(1_000),
+                base_amount: U256::from(10 * (index as u64 + 1)),
+                price: pool.current_price(),
+                timestamp: Timestamp::from(at),
+            };
+            pool.trade_count += 1;
+            state.insert_pool_trade(&pool.pool_id, pool.trade_count - 1, trade).unwrap();
+        }
+
+        let trades = state.get_pool_trades(&pool, 0, 2).await.unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].timestamp, Timestamp::from(30 * hour));
+        let trades = state.get_pool_trades(&pool, 2, 10).await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].base_amount, U256::from(10));
+
+        // The 24h window ending at hour 30 starts at hour 6
+        let volume = state.pool_volume_24h(&pool, Timestamp::from(30 * hour)).await.unwrap();
+        assert_eq!(volume, U256::from(50));
+    }
+
     #[test]
     fn test_pool_price_calculation() {
         let token_id = "test-token".to_string();
@@ -397,10 +1343,370 @@ mod tests {
         ).unwrap();
 
         let price = pool.current_price();
-        assert!(price > U256::zero());
+        assert!(!price.is_zero());
+
+        // Price should be 69000 / 1000000000 = 0.000069
+        assert_eq!(price.to_string(), "0.000069");
+        assert_eq!(price, pool.initial_ratio);
+    }
+
+    #[test]
+    fn test_pool_attestation() {
+        let mut pool = PoolInfo::new(
+            "test-token".to_string(),
+            U256::from(1_000_000_000u64),
+            U256::from(69_000),
+            Timestamp::from(0),
+        ).unwrap();
+
+        let attestation = pool.attestation(Timestamp::from(5));
+        assert_eq!(attestation.pool_id, pool.pool_id);
+        assert_eq!(attestation.token_liquidity, pool.token_liquidity);
+        assert_eq!(attestation.base_liquidity, pool.base_liquidity);
+        assert!(attestation.is_locked);
+        assert_eq!(attestation.lock_expires_at, None);
+        assert_eq!(attestation.attested_at, Timestamp::from(5));
+
+        // The hash commits to the full pool record
+        assert_eq!(pool.attestation(Timestamp::from(9)).pool_info_hash, attestation.pool_info_hash);
+        pool.trade_count += 1;
+        assert_ne!(pool.attestation(Timestamp::from(5)).pool_info_hash, attestation.pool_info_hash);
+    }
+
+    #[tokio::test]
+    async fn test_time_locked_pool_unlocks_after_expiry() {
+        use linera_sdk::linera_base_types::{AccountOwner, ChainId};
+
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let pool = state
+            .create_pool(
+                "t".to_string(),
+                U256::from(1_000_000),
+                U256::from(10_000),
+                Timestamp::ZERO,
+            )
+            .await
+            .unwrap();
+        state.commit_liquidity(BaseAsset::Native, Amount::from_attos(10_000)).await.unwrap();
+        assert_eq!(*state.locked_pools.get(), 1);
+        assert_eq!(*state.locked_tvl.get(), pool.tvl);
+
+        // Permanently locked pools never unlock
+        assert!(state.unlock_pool(&pool.pool_id, Timestamp::from(u64::MAX)).await.is_err());
+
+        let lock = PoolLock {
+            creator: Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN },
+            base_asset: BaseAsset::Native,
+            amount: Amount::from_attos(10_000),
+        };
+        let expires_at = Timestamp::from(100);
+        let locked = state.lock_pool(&pool.pool_id, lock.clone(), expires_at).await.unwrap();
+        assert_eq!(locked.lock_expires_at, Some(expires_at));
+        assert_eq!(*state.time_locked_pools.get(), 1);
+        assert_eq!(state.withdrawable_pool_count(Timestamp::from(99)).await.unwrap(), 0);
+        assert_eq!(state.withdrawable_pool_count(expires_at).await.unwrap(), 1);
+        assert!(state.unlock_pool(&pool.pool_id, Timestamp::from(99)).await.is_err());
+
+        let expected = UnlockedLiquidity {
+            creator: lock.creator,
+            base_asset: BaseAsset::Native,
+            base_amount: Amount::from_attos(10_000),
+            token_amount: pool.token_liquidity,
+        };
+        assert_eq!(state.unlock_pool(&pool.pool_id, Timestamp::from(100)).await.unwrap(), expected);
+        let unlocked = state.get_pool(&pool.pool_id).await.unwrap().unwrap();
+        assert!(!unlocked.is_locked);
+        assert_eq!(unlocked.base_liquidity, U256::zero());
+        assert_eq!(*state.total_tvl.get(), U256::zero());
+        assert_eq!(*state.committed_liquidity.get(), Amount::ZERO);
+        assert_eq!(*state.locked_pools.get(), 0);
+        assert_eq!(*state.time_locked_pools.get(), 0);
+        assert_eq!(*state.locked_tvl.get(), U256::zero());
+
+        // The liquidity can only be withdrawn once
+        assert!(state.unlock_pool(&pool.pool_id, Timestamp::from(200)).await.is_err());
+
+        // A pool locked before the totals were kept joins them once backfilled
+        let legacy = state
+            .create_pool(
+                "u".to_string(),
+                U256::from(1_000_000),
+                U256::from(10_000),
+                Timestamp::ZERO,
+            )
+            .await
+            .unwrap();
+        state.counted_locked_pools.remove(&legacy.pool_id).unwrap();
+        state.locked_pools.set(0);
+        state.locked_tvl.set(U256::zero());
+        state.lock_pool(&legacy.pool_id, lock, expires_at).await.unwrap();
+        assert_eq!(*state.time_locked_pools.get(), 0);
+        assert_eq!(state.backfill_locked_totals(10).await.unwrap(), 1);
+        assert_eq!(state.backfill_locked_totals(10).await.unwrap(), 0);
+        assert_eq!(*state.locked_pools.get(), 1);
+        assert_eq!(*state.time_locked_pools.get(), 1);
+        assert_eq!(*state.locked_tvl.get(), legacy.tvl);
+    }
+
+    #[tokio::test]
+    async fn test_unlock_pays_out_reserves_after_swaps() {
+        use linera_sdk::linera_base_types::{AccountOwner, ChainId};
+
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let mut pool = state
+            .create_pool(
+                "t".to_string(),
+                U256::from(1_000_000),
+                U256::from(10_000),
+                Timestamp::ZERO,
+            )
+            .await
+            .unwrap();
+        state.commit_liquidity(BaseAsset::Native, Amount::from_attos(12_000)).await.unwrap();
+        let lock = PoolLock {
+            creator: Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN },
+            base_asset: BaseAsset::Native,
+            amount: Amount::from_attos(10_000),
+        };
+        state.lock_pool(&pool.pool_id, lock, Timestamp::from(100)).await.unwrap();
+
+        // A buy after graduation moved both reserves away from the locked amount
+        pool = state.get_pool(&pool.pool_id).await.unwrap().unwrap();
+        pool.base_liquidity = U256::from(12_000);
+        pool.token_liquidity = U256::from(833_334);
+        state.store_traded_pool(pool.clone(), Timestamp::from(50)).await.unwrap();
+
+        let unlocked = state.unlock_pool(&pool.pool_id, Timestamp::from(100)).await.unwrap();
+        assert_eq!(unlocked.base_amount, Amount::from_attos(12_000));
+        assert_eq!(unlocked.token_amount, U256::from(833_334));
+        assert_eq!(*state.committed_liquidity.get(), Amount::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_supply_change_refreshes_pool_tvl() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let mut pool = state
+            .create_pool(
+                "t".to_string(),
+                U256::from(1_000_000),
+                U256::from(10_000),
+                Timestamp::ZERO,
+            )
+            .await
+            .unwrap();
+
+        // Swaps move the reserves without touching the stored TVL
+        pool.base_liquidity = U256::from(12_000);
+        state.pools.insert(&pool.pool_id, pool.clone()).unwrap();
+
+        let supply = TokenSupply {
+            total_supply: U256::from(900_000),
+            circulating_supply: U256::from(800_000),
+            reported_at: Timestamp::from(10),
+        };
+        assert!(state.record_supply("t", supply).await.unwrap());
+        let refreshed = state.get_pool(&pool.pool_id).await.unwrap().unwrap();
+        assert_eq!(refreshed.tvl, U256::from(24_000));
+        assert_eq!(*state.total_tvl.get(), U256::from(24_000));
+        assert_eq!(state.pool_token_supply(&refreshed).await.unwrap(), Some(supply));
+
+        // An older report arriving late is ignored
+        let stale = TokenSupply { reported_at: Timestamp::from(5), ..supply };
+        assert!(!state.record_supply("t", stale).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_swap_revalues_tvl_and_records_history() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let mut pool = state
+            .create_pool(
+                "t".to_string(),
+                U256::from(1_000_000),
+                U256::from(10_000),
+                Timestamp::ZERO,
+            )
+            .await
+            .unwrap();
+        assert_eq!(*state.total_tvl.get(), U256::from(20_000));
+
+        // A buy adds base currency and removes tokens, raising the price
+        pool.base_liquidity = U256::from(12_500);
+        pool.token_liquidity = U256::from(800_000);
+        let hour = Timestamp::from(TVL_BUCKET_MICROS);
+        state.store_traded_pool(pool.clone(), hour).await.unwrap();
+
+        assert_eq!(pool.current_tvl(), U256::from(25_000));
+        assert_eq!(*state.total_tvl.get(), U256::from(25_000));
+        assert_eq!(
+            state.get_tvl_history(hour, 2).await.unwrap(),
+            vec![(0, U256::from(20_000)), (1, U256::from(25_000))]
+        );
+        assert_eq!(state.get_tvl_history(hour, 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_buyback_accrual_and_totals() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+
+        // Base and token fees accrue separately until a buyback clears them
+        state.accrue_buyback("p", U256::from(30), U256::zero()).await.unwrap();
+        state.accrue_buyback("p", U256::from(20), U256::from(7)).await.unwrap();
+        let buyback = state.buybacks.get("p").await.unwrap().unwrap();
+        assert_eq!(buyback.accrued_base, U256::from(50));
+        assert_eq!(buyback.pending_burn, U256::from(7));
+
+        let at = Timestamp::from(1_000);
+        let buyback = state.record_buyback("p", U256::from(107), at).await.unwrap();
+        assert_eq!(buyback.accrued_base, U256::zero());
+        assert_eq!(buyback.pending_burn, U256::zero());
+        assert_eq!(buyback.base_spent, U256::from(50));
+        assert_eq!(buyback.tokens_burned, U256::from(107));
+        assert_eq!(buyback.last_buyback_at, Some(at));
+
+        // Platform totals add up across pools
+        state.accrue_buyback("q", U256::from(10), U256::zero()).await.unwrap();
+        state.record_buyback("q", U256::from(20), at).await.unwrap();
+        assert_eq!(*state.total_buyback_spent.get(), U256::from(60));
+        assert_eq!(*state.total_buyback_burned.get(), U256::from(127));
+        assert_eq!(*state.total_buybacks.get(), 2);
+
+        // Native base fees stay reserved for the buyback until it spends them
+        state.accrue_buyback("q", U256::from(15), U256::zero()).await.unwrap();
+        assert_eq!(*state.buyback_reserved.get(), Amount::from_attos(15));
+        state.record_buyback("q", U256::zero(), at).await.unwrap();
+        assert_eq!(*state.buyback_reserved.get(), Amount::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_treasury_ledger() {
+        use linera_sdk::linera_base_types::{AccountOwner, ChainId};
+
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let account = |n: u32| Account {
+            chain_id: ChainId::root(n),
+            owner: AccountOwner::CHAIN,
+        };
+
+        state.set_treasury_admin(account(1), Timestamp::from(10)).unwrap();
+        assert_eq!(*state.treasury_admin.get(), Some(account(1)));
+
+        let at = Timestamp::from(20);
+        state.record_treasury_withdrawal(account(2), Amount::from_tokens(3), at).unwrap();
+        state.record_treasury_withdrawal(account(2), Amount::from_tokens(2), at).unwrap();
+        assert_eq!(*state.treasury_withdrawn.get(), Amount::from_tokens(5));
+
+        // Every change is visible to indexers
+        let events = state.outbox.after(None, 10).await.unwrap();
+        let kinds = events.iter().map(|(_, event)| event.kind.as_str()).collect::<Vec<_>>();
+        assert_eq!(kinds, ["TreasuryAdminChanged", "TreasuryWithdrawn", "TreasuryWithdrawn"]);
+    }
+
+    #[tokio::test]
+    async fn test_pools_per_base_asset() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let usdc = BaseAsset::Fungible(ApplicationId::new(CryptoHash::from([1u64; 4])));
+        let usdc_quote = PoolQuote {
+            base_asset: usdc,
+            base_decimals: 6,
+        };
+        let native_quote = PoolQuote {
+            base_asset: BaseAsset::Native,
+            base_decimals: units::NATIVE_DECIMALS,
+        };
+        let at = Timestamp::from(10);
+
+        // Only graduated tokens get more pools
+        assert!(state
+            .create_asset_pool("t", usdc_quote, U256::from(1_000), U256::from(500), at)
+            .await
+            .is_err());
+
+        let native = state
+            .create_pool("t".to_string(), U256::from(1_000_000), U256::from(10_000), at)
+            .await
+            .unwrap();
+        let extra = state
+            .create_asset_pool("t", usdc_quote, U256::from(1_000), U256::from(500), at)
+            .await
+            .unwrap();
+        assert_ne!(extra.pool_id, native.pool_id);
+        assert_eq!(graduation::pool_key_token_id(&extra.token_id), "t");
+        assert_eq!(*state.total_pools.get(), 2);
+        assert_eq!(state.pool_quotes.get(&extra.pool_id).await.unwrap(), Some(usdc_quote));
+
+        // One pool per asset; the default pool stays the graduation one
+        assert!(state
+            .create_asset_pool("t", usdc_quote, U256::from(1_000), U256::from(500), at)
+            .await
+            .is_err());
+        assert!(state
+            .create_asset_pool("t", native_quote, U256::from(1_000), U256::from(500), at)
+            .await
+            .is_err());
+        let default_pool = state.get_pool_by_token("t").await.unwrap().unwrap();
+        assert_eq!(default_pool.pool_id, native.pool_id);
+
+        let pools = state.pools_of_token("t").await.unwrap();
+        let listed = pools
+            .iter()
+            .map(|(base_asset, pool)| (base_asset.clone(), pool.pool_id.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            listed,
+            [("native".to_string(), native.pool_id), (usdc.to_string(), extra.pool_id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_legacy_pools_indexed_by_asset() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let at = Timestamp::from(10);
+        let native = state
+            .create_pool("t".to_string(), U256::from(1_000_000), U256::from(10_000), at)
+            .await
+            .unwrap();
+
+        // A token graduated before pools were indexed by asset
+        state.asset_pools.remove("t").unwrap();
+        state.pool_quotes.remove(&native.pool_id).unwrap();
+        let pools = state.pools_of_token("t").await.unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].0, "native");
+        let native_quote = PoolQuote {
+            base_asset: BaseAsset::Native,
+            base_decimals: units::NATIVE_DECIMALS,
+        };
+        assert!(state
+            .create_asset_pool("t", native_quote, U256::from(1_000), U256::from(500), at)
+            .await
+            .is_err());
+
+        assert_eq!(state.migrate_asset_pools(10).await.unwrap(), 1);
+        assert_eq!(state.migrate_asset_pools(10).await.unwrap(), 0);
+        let indexed = state.asset_pools.get("t").await.unwrap().unwrap();
+        assert_eq!(indexed.get("native"), Some(&native.pool_id));
+    }
+
+    #[tokio::test]
+    async fn test_pool_token_deposits() {
+        let context = MemoryContext::default();
+        let mut state = SwapState::load(context).await.unwrap();
+        let depositor = AccountOwner::CHAIN;
+
+        state.set_pool_tokens_deposited("t", depositor, U256::from(500)).unwrap();
+        assert_eq!(state.pool_tokens_deposited("t", depositor).await.unwrap(), U256::from(500));
+        assert!(state.pool_tokens_deposited("u", depositor).await.unwrap().is_zero());
 
-        // Price should be approximately 69000 / 1000000000 = 0.000069 (in scaled form)
-        let expected_ratio = (total_raised * U256::from(1_000_000)) / total_supply;
-        assert_eq!(price, expected_ratio);
+        state.set_pool_tokens_deposited("t", depositor, U256::zero()).unwrap();
+        let key = ("t".to_string(), depositor);
+        assert!(!state.pool_token_deposits.contains_key(&key).await.unwrap());
     }
 }
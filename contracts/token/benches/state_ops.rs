@@ -0,0 +1,145 @@
+//! Benchmarks for token state operations on large views
+//!
+//! Uses an in-memory view context. Save a baseline with
+//! `cargo bench -p fair-launch-token -- --save-baseline main` and compare
+//! against it with `--baseline main`.
+
+#[allow(dead_code)]
+#[path = "../src/auction.rs"]
+mod auction;
+#[allow(dead_code)]
+#[path = "../src/cocreators.rs"]
+mod cocreators;
+#[allow(dead_code)]
+#[path = "../src/recurring.rs"]
+mod recurring;
+#[allow(dead_code)]
+#[path = "../src/sale.rs"]
+mod sale;
+#[allow(dead_code)]
+#[path = "../src/snapshot.rs"]
+mod snapshot;
+#[allow(dead_code)]
+#[path = "../src/staking.rs"]
+mod staking;
+#[allow(dead_code)]
+#[path = "../src/state.rs"]
+mod state;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fair_launch_abi::{Price, Trade};
+use linera_sdk::linera_base_types::{Account, AccountOwner, ChainId, Timestamp};
+use linera_sdk::views::View;
+use linera_views::memory::MemoryContext;
+use primitive_types::U256;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use state::TokenState;
+
+/// Map sizes the state benchmarks are run against
+const SIZES: [u32; 3] = [100, 1_000, 10_000];
+
+/// Shared configuration: changes under 5% are treated as noise
+fn config() -> Criterion {
+    Criterion::default()
+        .noise_threshold(0.05)
+        .significance_level(0.01)
+        .measurement_time(Duration::from_secs(5))
+}
+
+fn account(index: u32) -> Account {
+    Account {
+        chain_id: ChainId::root(index),
+        owner: AccountOwner::CHAIN,
+    }
+}
+
+fn trade(index: u32) -> Trade {
+    Trade {
+        token_id: "bench-token".to_string(),
+        trader: account(index),
+        is_buy: true,
+        token_amount: U256::from(1_000),
+        currency_amount: U256::from(10),
+        price: Price::from_ratio(U256::from(10), U256::from(1_000)).unwrap(),
+        timestamp: Timestamp::from(u64::from(index)),
+        memo: None,
+        referrer: None,
+        flagged: false,
+    }
+}
+
+/// Load a fresh state with `holders` balances and `holders` trades
+async fn populated_state(holders: u32) -> TokenState {
+    let mut state = TokenState::load(MemoryContext::default())
+        .await
+        .expect("Failed to load state");
+    for index in 0..holders {
+        state
+            .set_balance(account(index), U256::from(1_000))
+            .await
+            .expect("Failed to set balance");
+        state
+            .record_trade(format!("trade-{}", index), trade(index))
+            .await
+            .expect("Failed to record trade");
+    }
+    state
+}
+
+fn set_balance(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("Failed to start runtime");
+    let mut group = c.benchmark_group("token_state/set_balance");
+    for size in SIZES {
+        let mut state = runtime.block_on(populated_state(size));
+        let target = account(size / 2);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| {
+                runtime
+                    .block_on(state.set_balance(target, black_box(U256::from(2_000))))
+                    .expect("Failed to set balance")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn record_trade(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("Failed to start runtime");
+    let mut group = c.benchmark_group("token_state/record_trade");
+    for size in SIZES {
+        let mut state = runtime.block_on(populated_state(size));
+        let mut next = size;
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| {
+                next += 1;
+                runtime
+                    .block_on(state.record_trade(format!("trade-{}", next), trade(next)))
+                    .expect("Failed to record trade")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn paginate_trades(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("Failed to start runtime");
+    let mut group = c.benchmark_group("token_state/get_trades");
+    for size in SIZES {
+        let state = runtime.block_on(populated_state(size));
+        // Last page is the worst case for offset-based pagination
+        let offset = (size as usize).saturating_sub(50);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| runtime.block_on(state.get_trades(black_box(offset), 50)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = set_balance, record_trade, paginate_trades
+}
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+use fair_launch_abi::{dutch_auction, AuctionBid, Price};
+use linera_sdk::{
+    linera_base_types::{Account, Amount, Timestamp},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+use primitive_types::U256;
+
+/// Bids and settlement of a Dutch auction launch
+///
+/// Bidders receive their tokens when the bid lands and pay the price at that
+/// time. Settlement fixes one clearing price for everyone and sets aside the
+/// overpayments, which bidders collect with `ClaimAuctionRefund`.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct AuctionState {
+    /// Combined bids per account
+    pub bids: MapView<Account, AuctionBid>,
+
+    /// Number of accounts that bid
+    pub bidder_count: RegisterView<u64>,
+
+    /// Base currency paid by all bids at their bid-time prices
+    pub total_paid: RegisterView<U256>,
+
+    /// Price every bidder is charged (None until settled)
+    pub clearing_price: RegisterView<Option<Price>>,
+
+    /// When the auction settled
+    pub settled_at: RegisterView<Option<Timestamp>>,
+
+    /// Base currency set aside for refunds not yet collected
+    pub refund_reserve: RegisterView<Amount>,
+}
+
+impl AuctionState {
+    /// Add a filled bid to the bidder's total
+    pub async fn record_bid(
+        &mut self,
+        bidder: Account,
+        amount: U256,
+        paid: U256,
+    ) -> Result<(), ViewError> {
+        let mut bid = match self.bids.get(&bidder).await? {
+            Some(bid) => bid,
+            None => {
+                let bidders = *self.bidder_count.get();
+                self.bidder_count.set(bidders + 1);
+                AuctionBid::default()
+            }
+        };
+        bid.amount = bid.amount.saturating_add(amount);
+        bid.paid = bid.paid.saturating_add(paid);
+        self.bids.insert(&bidder, bid)?;
+
+        let total_paid = *self.total_paid.get();
+        self.total_paid.set(total_paid.saturating_add(paid));
+        Ok(())
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.settled_at.get().is_some()
+    }
+
+    /// Fix the clearing price and the base currency held back for refunds
+    pub fn settle(&mut self, clearing_price: Price, at: Timestamp, refund_reserve: Amount) {
+        self.clearing_price.set(Some(clearing_price));
+        self.settled_at.set(Some(at));
+        self.refund_reserve.set(refund_reserve);
+    }
+
+    /// Overpayment a bidder can still collect (zero before settlement)
+    pub async fn refund_due(&self, bidder: &Account) -> Result<U256, ViewError> {
+        let Some(clearing_price) = *self.clearing_price.get() else {
+            return Ok(U256::zero());
+        };
+        Ok(match self.bids.get(bidder).await? {
+            Some(bid) if !bid.refunded => {
+                dutch_auction::refund(bid.paid, bid.amount, clearing_price)
+            }
+            _ => U256::zero(),
+        })
+    }
+
+    /// Mark a bidder's refund collected, returning what it was
+    pub async fn take_refund(&mut self, bidder: &Account) -> Result<U256, ViewError> {
+        let refund = self.refund_due(bidder).await?;
+        if let Some(mut bid) = self.bids.get(bidder).await? {
+            bid.refunded = true;
+            self.bids.insert(bidder, bid)?;
+        }
+        Ok(refund)
+    }
+
+    /// Release a paid-out refund from the reserve
+    pub fn release_refund(&mut self, amount: Amount) {
+        let reserve = *self.refund_reserve.get();
+        self.refund_reserve.set(reserve.saturating_sub(amount));
+    }
+}
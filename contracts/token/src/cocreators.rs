@@ -0,0 +1,122 @@
+use fair_launch_abi::{CreatorAction, CreatorProposal};
+use linera_sdk::{
+    linera_base_types::{Account, Amount, Timestamp},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+
+/// Co-creators sharing control of a token's creator-only actions
+///
+/// Until a set is configured the token creator is its only member and acts
+/// alone. With a threshold above one, creator actions go through proposals
+/// that run once enough members approve them. Changing the set bumps its
+/// version, which lapses every proposal still pending.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct CreatorSetState {
+    /// Co-creators (empty = the token creator alone)
+    pub members: RegisterView<Vec<Account>>,
+
+    /// Approvals a creator action needs (0 is treated as 1)
+    pub threshold: RegisterView<u16>,
+
+    /// Bumped on every change of the set
+    pub version: RegisterView<u64>,
+
+    /// Proposals: proposal_id → CreatorProposal
+    pub proposals: MapView<u64, CreatorProposal>,
+
+    /// Number of proposals made (next proposal_id)
+    pub proposal_count: RegisterView<u64>,
+}
+
+impl CreatorSetState {
+    /// Current co-creators, given the token creator
+    pub fn members_or(&self, creator: Account) -> Vec<Account> {
+        let members = self.members.get();
+        if members.is_empty() {
+            vec![creator]
+        } else {
+            members.clone()
+        }
+    }
+
+    /// Split a creator payout evenly over the co-creators, the first member
+    /// taking the remainder
+    pub fn payouts_or(&self, creator: Account, amount: Amount) -> Vec<(Account, Amount)> {
+        let members = self.members_or(creator);
+        let attos = u128::from(amount);
+        let share = attos / members.len() as u128;
+        let remainder = attos - share * members.len() as u128;
+        members
+            .into_iter()
+            .enumerate()
+            .map(|(i, member)| {
+                let payout = if i == 0 { share + remainder } else { share };
+                (member, Amount::from_attos(payout))
+            })
+            .filter(|(_, payout)| *payout > Amount::ZERO)
+            .collect()
+    }
+
+    /// Approvals a creator action needs
+    pub fn threshold(&self) -> u16 {
+        (*self.threshold.get()).max(1)
+    }
+
+    /// Replace the co-creators, lapsing pending proposals
+    pub fn set(&mut self, members: Vec<Account>, threshold: u16) {
+        self.members.set(members);
+        self.threshold.set(threshold);
+        let version = *self.version.get();
+        self.version.set(version + 1);
+    }
+
+    /// Store a proposal approved by its proposer
+    pub fn propose(
+        &mut self,
+        proposer: Account,
+        action: CreatorAction,
+        now: Timestamp,
+    ) -> Result<(u64, CreatorProposal), ViewError> {
+        let proposal_id = *self.proposal_count.get();
+        let proposal = CreatorProposal {
+            action,
+            proposer,
+            approvals: vec![proposer],
+            set_version: *self.version.get(),
+            created_at: now,
+            executed: false,
+        };
+        self.proposals.insert(&proposal_id, proposal.clone())?;
+        self.proposal_count.set(proposal_id + 1);
+        Ok((proposal_id, proposal))
+    }
+
+    /// Look up a proposal
+    pub async fn get(&self, proposal_id: u64) -> Result<Option<CreatorProposal>, ViewError> {
+        self.proposals.get(&proposal_id).await
+    }
+
+    /// Store an updated proposal
+    pub fn save(&mut self, proposal_id: u64, proposal: CreatorProposal) -> Result<(), ViewError> {
+        self.proposals.insert(&proposal_id, proposal)
+    }
+
+    /// Whether a proposal can still gather approvals
+    pub fn is_pending(&self, proposal: &CreatorProposal) -> bool {
+        !proposal.executed && proposal.set_version == *self.version.get()
+    }
+
+    /// Up to `limit` proposals, newest first
+    pub async fn recent(&self, limit: u64) -> Result<Vec<(u64, CreatorProposal)>, ViewError> {
+        let count = *self.proposal_count.get();
+        let mut proposals = Vec::new();
+        for proposal_id in (count.saturating_sub(limit)..count).rev() {
+            if let Some(proposal) = self.proposals.get(&proposal_id).await? {
+                proposals.push((proposal_id, proposal));
+            }
+        }
+        Ok(proposals)
+    }
+}
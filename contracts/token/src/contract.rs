@@ -1,14 +1,32 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod auction;
+mod cocreators;
+mod recurring;
+mod sale;
+mod snapshot;
+mod staking;
 mod state;
 
 use fair_launch_abi::{
-    bonding_curve, Message, TokenAbi, TokenOperation, Trade,
+    bonding_curve, dutch_auction, fixed_price_sale, graduation,
+    logging::{self, Logger},
+    merkle,
+    price_feed::{self, PriceUpdate, PRICE_FEED_STREAM},
+    units, wad, BalanceSnapshot, BaseAsset, CreatorAction, CreatorProposal, CurveState,
+    InitialBuy, LaunchFailure, LaunchMechanism, Message, Permit, Price, RecurringBuy,
+    SnapshotProof, SnipeTaxDestination, StableLeg, StablePoolConfig, StateChunk, TokenAbi,
+    TokenEvent, TokenOperation, TokenParameters, TokenResponse, Trade, TransferReceipt,
+    MAX_CO_CREATORS, MAX_MEMO_LENGTH, MAX_WHITELIST_BATCH, TOKEN_EVENTS_STREAM,
 };
 use linera_sdk::{
     abi::WithContractAbi,
-    linera_base_types::{Account, AccountOwner, Amount},
-    views::View,
+    abis::fungible::{FungibleOperation, FungibleResponse, FungibleTokenAbi},
+    linera_base_types::{
+        Account, AccountOwner, AccountSignature, Amount, ApplicationId, ChainId, CryptoHash,
+        StreamName, TimeDelta, Timestamp,
+    },
+    views::{RootView, View},
     Contract, ContractRuntime,
 };
 use primitive_types::U256;
@@ -16,6 +34,30 @@ use thiserror::Error;
 
 use crate::state::TokenState;
 
+/// Automatic resends of a bounced graduation before waiting for a manual `Graduate`
+const MAX_GRADUATION_RETRIES: u32 = 3;
+
+/// Minimum time between token summaries sent to the factory (5 minutes)
+const SUMMARY_SYNC_INTERVAL_MICROS: u64 = 300_000_000;
+
+/// Commitments a single `SettleSale` allocates
+const MAX_SALE_SETTLEMENTS_PER_CALL: u64 = 100;
+
+/// Dust thresholds are capped at max supply divided by this (one millionth)
+const MAX_DUST_THRESHOLD_DIVISOR: u64 = 1_000_000;
+
+/// Share of each creator fee routed to stakers (2000 = 20%)
+const STAKING_FEE_SHARE_BPS: u16 = 2_000;
+
+/// Shortest allowed interval between recurring buys (1 minute)
+const MIN_RECURRING_INTERVAL_MICROS: u64 = 60_000_000;
+
+/// Recurring buys executed per block, so one block never processes every schedule
+const MAX_RECURRING_BUYS_PER_BLOCK: usize = 10;
+
+/// Version of this token bytecode, announced to the factory on initialization
+const CONTRACT_VERSION: u32 = 1;
+
 #[derive(Debug, Error)]
 pub enum TokenError {
     #[error("Insufficient balance: have {have}, need {need}")]
@@ -27,25 +69,183 @@ pub enum TokenError {
     #[error("Slippage exceeded: cost {cost}, max allowed {max_cost}")]
     SlippageExceeded { cost: U256, max_cost: U256 },
 
+    #[error("Slippage exceeded: budget buys {tokens} tokens, min required {min_tokens_out}")]
+    SlippageExceededBudget { tokens: U256, min_tokens_out: U256 },
+
     #[error("Slippage exceeded: return {return_amount}, min required {min_return}")]
     SlippageExceededSell { return_amount: U256, min_return: U256 },
 
+    #[error("Rate limited: retry after {retry_after_micros} microseconds")]
+    RateLimited { retry_after_micros: u64 },
+
     #[error("Invalid amount: must be greater than zero")]
     InvalidAmount,
 
     #[error("Insufficient native token balance: have {have}, need {need}")]
     InsufficientNativeBalance { have: Amount, need: Amount },
 
+    #[error("Insufficient {asset} balance: have {have}, need {need}")]
+    InsufficientBaseBalance { asset: BaseAsset, have: Amount, need: Amount },
+
+    #[error("Quote is stale: expected supply {expected}, current supply {current}")]
+    StaleQuote { expected: U256, current: U256 },
+
+    #[error("Insufficient curve liquidity: {available} available for payouts, need {need}")]
+    InsufficientLiquidity { available: Amount, need: Amount },
+
     #[error("Amount conversion error")]
     AmountConversionError,
 
+    #[error("Bonding curve arithmetic overflow")]
+    CurveOverflow,
+
+    #[error("Unauthorized: only the token creator can perform this operation")]
+    Unauthorized,
+
+    #[error("Allowance revoked by owner")]
+    AllowanceRevoked,
+
+    #[error("Invalid permit: {0}")]
+    InvalidPermit(String),
+
+    #[error("Bonding curve closed after graduation; trade on DEX pool {pool_id}")]
+    CurveClosed { pool_id: String },
+
+    #[error("Memo too long: {length} bytes, max {max}")]
+    MemoTooLong { length: usize, max: usize },
+
+    #[error("Dust threshold {threshold} exceeds the maximum {max}")]
+    DustThresholdTooHigh { threshold: U256, max: U256 },
+
+    #[error("Dust sweeps open after graduation")]
+    DustSweepNotOpen,
+
+    #[error("Staking opens after graduation")]
+    StakingNotOpen,
+
+    #[error("Transfers are disabled until the token graduates")]
+    TransfersDisabled,
+
+    #[error("Pool tokens can be deposited once the token graduates to a swap application")]
+    PoolDepositUnavailable,
+
+    #[error("No stable pool is configured, or the curve has closed")]
+    StablePoolNotConfigured,
+
+    #[error("Chain already hosts token {0}")]
+    ChainHostsToken(String),
+
+    #[error("Snapshot {0} not found")]
+    SnapshotNotFound(u64),
+
+    #[error("There are no balances to snapshot")]
+    EmptySnapshot,
+
+    #[error("Invalid merkle proof for snapshot {0}")]
+    InvalidSnapshotProof(u64),
+
+    #[error("Reward for snapshot {0} already claimed")]
+    SnapshotRewardClaimed(u64),
+
+    #[error("Invalid recurring buy: {0}")]
+    InvalidRecurringBuy(String),
+
+    #[error("Recurring buy {0} not found")]
+    RecurringBuyNotFound(u64),
+
+    #[error("Unlock time {unlock_at} is not in the future (now {now})")]
+    InvalidUnlockTime { unlock_at: Timestamp, now: Timestamp },
+
+    #[error("No expired locks to claim")]
+    NothingUnlocked,
+
+    #[error("Creator actions need {threshold} co-creator approvals; propose the action instead")]
+    ApprovalRequired { threshold: u16 },
+
+    #[error("Creator proposal {0} not found")]
+    ProposalNotFound(u64),
+
+    #[error("Creator proposal {proposal_id} cannot be approved: {reason}")]
+    InvalidProposal { proposal_id: u64, reason: String },
+
+    #[error("Invalid creator set: {0}")]
+    InvalidCreatorSet(String),
+
+    #[error("State import is closed once the token has recorded its own trades")]
+    ImportClosed,
+
+    #[error("State chunk {0} was not approved for import by the factory")]
+    ImportNotApproved(CryptoHash),
+
+    #[error("Only whitelisted accounts may buy until {ends_at}")]
+    NotWhitelisted { ends_at: Timestamp },
+
+    #[error("No whitelist phase is running")]
+    WhitelistClosed,
+
+    #[error("Too many accounts to whitelist: {count}, max {max} per call")]
+    WhitelistBatchTooLarge { count: usize, max: usize },
+
+    #[error("This launch sells through a {0}; curve trading is unavailable")]
+    NotBondingCurve(LaunchMechanism),
+
+    #[error("This launch has no Dutch auction")]
+    NotAuction,
+
+    #[error("Auction opens at {0}")]
+    AuctionNotStarted(Timestamp),
+
+    #[error("Auction is closed to bids")]
+    AuctionClosed,
+
+    #[error("Auction takes bids until {0}")]
+    AuctionRunning(Timestamp),
+
+    #[error("No auction refund to claim")]
+    NothingToRefund,
+
+    #[error("This launch has no fixed-price sale")]
+    NotFixedPriceSale,
+
+    #[error("Sale opens at {0}")]
+    SaleNotStarted(Timestamp),
+
+    #[error("Sale is closed to commitments")]
+    SaleClosed,
+
+    #[error("Sale takes commitments until {0}")]
+    SaleRunning(Timestamp),
+
+    #[error("Launch failed to reach its minimum raise; redeem tokens with RedeemRefund")]
+    LaunchFailed,
+
+    #[error("Launch has not failed; refunds open if the minimum raise is missed")]
+    LaunchNotFailed,
+
     #[error("State error: {0}")]
     StateError(String),
 }
 
+impl From<bonding_curve::CurveError> for TokenError {
+    fn from(error: bonding_curve::CurveError) -> Self {
+        match error {
+            bonding_curve::CurveError::Overflow => TokenError::CurveOverflow,
+        }
+    }
+}
+
+/// Source of the base currency paying for a curve buy
+enum BuyPayment {
+    /// The authenticated signer of the current block
+    Signer,
+    /// Budget the application already holds in escrow
+    Escrow,
+}
+
 pub struct TokenContract {
     state: TokenState,
     runtime: ContractRuntime<Self>,
+    logger: Logger,
 }
 
 linera_sdk::contract!(TokenContract);
@@ -57,14 +257,18 @@ impl WithContractAbi for TokenContract {
 impl Contract for TokenContract {
     type Message = Message;
     type InstantiationArgument = ();
-    type Parameters = ();
-    type EventValue = ();
+    type Parameters = TokenParameters;
+    type EventValue = TokenEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = TokenState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
-        TokenContract { state, runtime }
+        TokenContract {
+            state,
+            runtime,
+            logger: Logger::new(logging::TOKEN_TARGET),
+        }
     }
 
     async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
@@ -85,48 +289,340 @@ impl Contract for TokenContract {
                     .initialize(token_id.clone(), creator, metadata.clone(), curve_config, created_at)
                     .await
                     .expect("Failed to initialize token");
+                self.announce_version();
             }
 
-            TokenOperation::Buy { amount, max_cost } => {
-                self.execute_buy(amount, max_cost).await
+            TokenOperation::Buy {
+                amount,
+                max_cost,
+                fill_or_cap,
+                memo,
+                referrer,
+                expected_supply,
+            } => {
+                self.check_expected_supply(expected_supply)
+                    .expect("Buy operation failed");
+                let buyer = self.owner_account();
+                self.ensure_may_buy(buyer).await
                     .expect("Buy operation failed");
+                self.execute_buy(amount, max_cost, fill_or_cap, memo, referrer).await
+                    .expect("Buy operation failed");
+            }
+
+            TokenOperation::BuyWithBudget { spend, min_tokens_out, memo, referrer } => {
+                let buyer = self.owner_account();
+                self.ensure_may_buy(buyer).await
+                    .expect("BuyWithBudget operation failed");
+                self.execute_buy_with_budget(spend, min_tokens_out, memo, referrer).await
+                    .expect("BuyWithBudget operation failed");
             }
 
-            TokenOperation::Sell { amount, min_return } => {
-                self.execute_sell(amount, min_return).await
+            TokenOperation::Sell { amount, min_return, memo, expected_supply } => {
+                self.check_expected_supply(expected_supply)
+                    .expect("Sell operation failed");
+                self.execute_sell(amount, min_return, memo).await
                     .expect("Sell operation failed");
             }
 
             TokenOperation::Graduate => {
+                // Manual retry of a graduation whose message bounced
+                let token_id = self.state.token_id.get().clone();
+                if !*self.state.is_graduated.get()
+                    && self.state.bounce_count("GraduateToken", &token_id).await > 0
+                {
+                    self.send_graduation_message();
+                    return TokenResponse::Ok;
+                }
+
+                let mut unmet = self.state.unmet_graduation_conditions();
+                if self.runtime.application_parameters().swap_application_id.is_none() {
+                    unmet.push("No swap application is configured".to_string());
+                }
+                if !unmet.is_empty() {
+                    panic!("Graduation conditions not met: {}", unmet.join("; "));
+                }
                 self.execute_graduation().await;
             }
 
-            TokenOperation::Approve { spender, amount } => {
-                self.execute_approve(spender, amount).await
+            TokenOperation::DepositStableLiquidity { amount } => {
+                self.execute_deposit_stable_liquidity(amount)
+                    .expect("DepositStableLiquidity operation failed");
+            }
+
+            TokenOperation::DepositPoolTokens { amount } => {
+                self.execute_deposit_pool_tokens(amount).await
+                    .expect("DepositPoolTokens operation failed");
+            }
+
+            TokenOperation::Approve { spender, amount, expires_at } => {
+                self.execute_approve(spender, amount, expires_at).await
                     .expect("Approve operation failed");
             }
 
-            TokenOperation::TransferFrom { from, to, amount } => {
-                self.execute_transfer_from(from, to, amount).await
+            TokenOperation::TransferFrom { from, to, amount, memo } => {
+                self.execute_transfer_from(from, to, amount, memo).await
                     .expect("TransferFrom operation failed");
             }
+
+            TokenOperation::IncreaseAllowance { spender, added } => {
+                self.execute_increase_allowance(spender, added).await
+                    .expect("IncreaseAllowance operation failed");
+            }
+
+            TokenOperation::DecreaseAllowance { spender, subtracted } => {
+                self.execute_decrease_allowance(spender, subtracted).await
+                    .expect("DecreaseAllowance operation failed");
+            }
+
+            TokenOperation::SetOperator { operator, approved } => {
+                let owner = self.owner_account();
+                self.state
+                    .set_operator(owner, operator, approved)
+                    .expect("SetOperator operation failed");
+            }
+
+            TokenOperation::Revoke { spender } => {
+                self.execute_revoke(spender).await
+                    .expect("Revoke operation failed");
+            }
+
+            TokenOperation::RevokeAll => {
+                self.execute_revoke_all().await
+                    .expect("RevokeAll operation failed");
+            }
+
+            TokenOperation::MigrateAllowances { max_entries } => {
+                self.state
+                    .migrate_legacy_allowances(max_entries as usize)
+                    .await
+                    .expect("MigrateAllowances operation failed");
+            }
+
+            TokenOperation::MigrateTrades { max_entries } => {
+                self.state
+                    .migrate_legacy_trades(max_entries as usize)
+                    .await
+                    .expect("MigrateTrades operation failed");
+            }
+
+            TokenOperation::BackfillTopHolders { max_entries } => {
+                self.state
+                    .backfill_top_holders(max_entries as usize)
+                    .await
+                    .expect("BackfillTopHolders operation failed");
+            }
+
+            TokenOperation::PermitApprove { permit, signature } => {
+                self.execute_permit_approve(permit, signature).await
+                    .expect("PermitApprove operation failed");
+            }
+
+            TokenOperation::UpdateMetadata {
+                description,
+                image_url,
+                twitter,
+                telegram,
+                website,
+            } => {
+                self.execute_update_metadata(description, image_url, twitter, telegram, website)
+                    .await
+                    .expect("UpdateMetadata operation failed");
+            }
+
+            TokenOperation::SweepDust { threshold } => {
+                self.execute_sweep_dust(threshold).await
+                    .expect("SweepDust operation failed");
+            }
+
+            TokenOperation::Stake { amount } => {
+                self.execute_stake(amount).await
+                    .expect("Stake operation failed");
+            }
+
+            TokenOperation::Unstake { amount } => {
+                self.execute_unstake(amount).await
+                    .expect("Unstake operation failed");
+            }
+
+            TokenOperation::ClaimRewards => {
+                self.execute_claim_rewards().await
+                    .expect("ClaimRewards operation failed");
+            }
+
+            TokenOperation::TakeSnapshot { reward } => {
+                self.execute_take_snapshot(reward).await
+                    .expect("TakeSnapshot operation failed");
+            }
+
+            TokenOperation::ClaimSnapshotReward { proof } => {
+                self.execute_claim_snapshot_reward(proof).await
+                    .expect("ClaimSnapshotReward operation failed");
+            }
+
+            TokenOperation::CreateRecurringBuy { amount_per_interval, interval, total_budget } => {
+                self.execute_create_recurring_buy(amount_per_interval, interval, total_budget).await
+                    .expect("CreateRecurringBuy operation failed");
+            }
+
+            TokenOperation::CancelRecurringBuy { schedule_id } => {
+                self.execute_cancel_recurring_buy(schedule_id).await
+                    .expect("CancelRecurringBuy operation failed");
+            }
+
+            TokenOperation::LockTokens { amount, unlock_at } => {
+                self.execute_lock_tokens(amount, unlock_at).await
+                    .expect("LockTokens operation failed");
+            }
+
+            TokenOperation::ClaimUnlocked => {
+                self.execute_claim_unlocked().await
+                    .expect("ClaimUnlocked operation failed");
+            }
+
+            TokenOperation::ProposeCreatorAction { action } => {
+                self.execute_propose_creator_action(action).await
+                    .expect("ProposeCreatorAction operation failed");
+            }
+
+            TokenOperation::ApproveCreatorAction { proposal_id } => {
+                self.execute_approve_creator_action(proposal_id).await
+                    .expect("ApproveCreatorAction operation failed");
+            }
+
+            TokenOperation::ImportState { chunk } => {
+                self.execute_import_state(chunk).await
+                    .expect("ImportState operation failed");
+            }
+
+            TokenOperation::PruneTrades { keep_last } => {
+                self.execute_prune_trades(keep_last).await
+                    .expect("PruneTrades operation failed");
+            }
+
+            TokenOperation::AddToWhitelist { accounts } => {
+                self.execute_add_to_whitelist(accounts).await
+                    .expect("AddToWhitelist operation failed");
+            }
+
+            TokenOperation::AuctionBid { amount, max_cost } => {
+                self.execute_auction_bid(amount, max_cost).await
+                    .expect("AuctionBid operation failed");
+            }
+
+            TokenOperation::SettleAuction => {
+                self.execute_settle_auction().await
+                    .expect("SettleAuction operation failed");
+            }
+
+            TokenOperation::ClaimAuctionRefund => {
+                self.execute_claim_auction_refund().await
+                    .expect("ClaimAuctionRefund operation failed");
+            }
+
+            TokenOperation::CommitToSale { amount } => {
+                self.execute_commit_to_sale(amount).await
+                    .expect("CommitToSale operation failed");
+            }
+
+            TokenOperation::SettleSale => {
+                self.execute_settle_sale().await
+                    .expect("SettleSale operation failed");
+            }
+
+            TokenOperation::RedeemRefund => {
+                self.execute_redeem_refund().await
+                    .expect("RedeemRefund operation failed");
+            }
+
+            // Reads answer right away and leave the token untouched
+            TokenOperation::QueryBalance { owner } => {
+                return TokenResponse::Balance(self.state.get_balance(&owner).await);
+            }
+
+            TokenOperation::QueryAllowance { owner, spender } => {
+                let now = self.runtime.system_time();
+                let allowance = self.state.get_active_allowance(&owner, &spender, now).await;
+                return TokenResponse::Allowance(allowance);
+            }
+
+            TokenOperation::QueryCurveState => {
+                let curve_state = self.curve_state().expect("QueryCurveState operation failed");
+                return TokenResponse::CurveState(curve_state);
+            }
         }
+
+        // Recurring buys run lazily in any block that touches the token
+        self.process_recurring_buys().await;
+        TokenResponse::Ok
     }
 
     async fn execute_message(&mut self, message: Self::Message) {
+        if self.runtime.message_is_bouncing() == Some(true) {
+            self.handle_bounced_message(message).await;
+            return;
+        }
+
         match message {
             Message::TokenCreated {
                 token_id,
                 creator,
                 metadata,
                 curve_config,
+                initial_buy,
             } => {
+                // A chain hosts one token, keyed by its ID: a redelivered launch
+                // is ignored and any other launch bounces so the factory rolls its
+                // registration back
+                let hosted = self.state.creator.get().is_some().then(|| self.state.token_id.get());
+                let is_new = Self::check_token_created(hosted.map(String::as_str), &token_id)
+                    .expect("TokenCreated rejected");
+                if !is_new {
+                    return;
+                }
+
                 // Initialize token when created by factory
                 let created_at = self.runtime.system_time();
                 self.state
                     .initialize(token_id, creator, metadata, curve_config, created_at)
                     .await
                     .expect("Failed to initialize token from message");
+                self.announce_version();
+
+                // The creator's first fill executes before the curve is visible to
+                // anyone else; a failure bounces the message so the launch rolls back
+                if let Some(InitialBuy { amount, max_cost }) = initial_buy {
+                    self.execute_buy(amount, max_cost, false, None, None)
+                        .await
+                        .expect("Initial buy failed");
+                }
+            }
+
+            Message::BatchBuyOrder {
+                batch_id,
+                order_index,
+                token_id,
+                amount,
+                max_cost,
+            } => {
+                // On a closed curve the buy would be escrowed and refunded; reject it
+                // instead so the order bounces back to the factory as failed
+                self.ensure_curve_open().expect("Batch buy order on a closed curve");
+                let buyer = self.owner_account();
+                self.ensure_may_buy(buyer).await.expect("Batch buy order failed");
+                self.execute_buy(amount, max_cost, false, None, None)
+                    .await
+                    .expect("Batch buy order failed");
+
+                let factory_chain = self.runtime.application_parameters().factory_chain_id;
+                if let Some(factory_chain) = factory_chain {
+                    self.runtime
+                        .prepare_message(Message::BatchOrderFilled {
+                            batch_id,
+                            order_index,
+                            token_id,
+                        })
+                        .send_to(factory_chain);
+                }
             }
 
             Message::TradeExecuted { .. } => {
@@ -134,9 +630,117 @@ impl Contract for TokenContract {
                 // This message is just for event tracking/notifications
             }
 
-            Message::PoolCreated { token_id: _, pool_id } => {
+            Message::PoolCreated { pool_id, attestation, is_stable_pool: true, .. } => {
+                self.state.stable_pool_id.set(Some(pool_id));
+                self.state.stable_pool_attestation.set(Some(attestation));
+            }
+
+            Message::PoolCreated { token_id, pool_id, attestation, excess_burned, .. } => {
+                let now = self.runtime.system_time();
+                self.state
+                    .outbox
+                    .push("Graduated", Some(&token_id), &attestation, now)
+                    .expect("Failed to record outbox event");
+                self.burn_pool_excess(&pool_id, excess_burned);
                 self.state.dex_pool_id.set(Some(pool_id));
+                self.state.pool_attestation.set(Some(attestation));
                 self.state.is_graduated.set(true);
+                self.state
+                    .clear_pending_message("GraduateToken", &token_id)
+                    .expect("Failed to clear pending graduation");
+            }
+
+            Message::BurnTokens { token_id, pool_id, amount } => {
+                // Anything not meant for this graduated token bounces back
+                assert!(
+                    *self.state.is_graduated.get() && token_id == *self.state.token_id.get(),
+                    "Burn for token {} not hosted or not graduated here",
+                    token_id
+                );
+                self.state.record_buyback_burn(amount);
+                self.logger.info(format!(
+                    "Burned {} tokens bought back by pool {}",
+                    amount, pool_id
+                ));
+                let now = self.runtime.system_time();
+                self.state
+                    .outbox
+                    .push("BuybackBurned", Some(&token_id), &amount, now)
+                    .expect("Failed to record outbox event");
+                self.notify_supply_changed();
+            }
+
+            Message::PoolTokensWithdrawn { token_id, pool_id, creator, amount } => {
+                // Anything not meant for this graduated token bounces back
+                assert!(
+                    *self.state.is_graduated.get() && token_id == *self.state.token_id.get(),
+                    "Pool tokens for token {} not hosted or not graduated here",
+                    token_id
+                );
+                let balance = self.state.get_balance(&creator).await;
+                self.state
+                    .set_balance(creator, balance + amount)
+                    .await
+                    .expect("Failed to credit withdrawn pool tokens");
+                self.logger.info(format!(
+                    "{} withdrew {} tokens from unlocked pool {}",
+                    creator, amount, pool_id
+                ));
+                let now = self.runtime.system_time();
+                self.state
+                    .outbox
+                    .push("PoolTokensWithdrawn", Some(&token_id), &amount, now)
+                    .expect("Failed to record outbox event");
+            }
+
+            Message::StateImportApproved {
+                token_id,
+                commitments,
+            } => {
+                let factory_chain = self.runtime.application_parameters().factory_chain_id;
+                assert!(
+                    factory_chain.is_some()
+                        && self.runtime.message_origin_chain_id() == factory_chain,
+                    "State imports are approved by the factory only"
+                );
+                assert_eq!(
+                    &token_id,
+                    self.state.token_id.get(),
+                    "State import approved for another token"
+                );
+                self.state
+                    .approve_imports(&commitments)
+                    .expect("Failed to record approved imports");
+                self.logger.info(format!(
+                    "{} state chunks approved for import",
+                    commitments.len()
+                ));
+            }
+
+            Message::ModerationUpdate { token_id, status } => {
+                self.logger.info(format!(
+                    "Moderation status of token {} updated: {:?}",
+                    token_id, status
+                ));
+                let now = self.runtime.system_time();
+                self.state
+                    .outbox
+                    .push("ModerationChanged", Some(&token_id), &status, now)
+                    .expect("Failed to record outbox event");
+                self.state.moderation_status.set(status);
+            }
+
+            Message::RefundIssued {
+                token_id,
+                buyer,
+                amount,
+                reason,
+            } => {
+                // Funds were already transferred by the token chain
+                self.logger.info(format!(
+                    "Refund of {} to {} for token {}: {}",
+                    amount, buyer, token_id, reason
+                ));
             }
 
             _ => {
@@ -145,18 +749,34 @@ impl Contract for TokenContract {
         }
     }
 
-    async fn store(self) {
-        // State is automatically persisted by linera-views
+    async fn store(mut self) {
+        for event in self.logger.flush(self.runtime.system_time()) {
+            self.state
+                .critical_events
+                .push(event)
+                .expect("Failed to mirror critical event");
+        }
+        self.state.bump_version().await;
+        self.state.save().await.expect("Failed to save state");
     }
 }
 
 impl TokenContract {
     /// Execute a buy operation
-    async fn execute_buy(&mut self, amount: U256, max_cost: U256) -> Result<(), TokenError> {
+    async fn execute_buy(
+        &mut self,
+        amount: U256,
+        max_cost: U256,
+        fill_or_cap: bool,
+        memo: Option<String>,
+        referrer: Option<Account>,
+    ) -> Result<(), TokenError> {
         // Validate input
         if amount == U256::zero() {
             return Err(TokenError::InvalidAmount);
         }
+        Self::validate_memo(&memo)?;
+        self.ensure_bonding_curve()?;
 
         // Get caller's account (includes chain_id and owner)
         let caller = self.owner_account();
@@ -164,17 +784,49 @@ impl TokenContract {
         let current_supply = *self.state.current_supply.get();
         let curve_config = self.state.curve_config.get().clone();
 
+        // A buy that lands after graduation started (e.g. behind the buy that
+        // completed the curve in the same block) has its payment escrowed and
+        // returned through the refund queue instead of failing outright
+        if let Err(error) = self.ensure_curve_open() {
+            let payment = Self::u256_to_amount(max_cost, curve_config.base_decimals)?;
+            if payment == Amount::ZERO {
+                return Err(error);
+            }
+            let application = self.application_account();
+            self.collect_payment(application, payment)?;
+            return self.refund_buyer(caller, payment, error.to_string()).await;
+        }
+
+        self.enforce_trade_interval(caller).await?;
+
+        // Cap the final buy at the remaining supply; the buyer is only charged
+        // for the filled amount, so the unfilled part is implicitly refunded
+        let requested = amount;
+        let amount = Self::capped_buy_amount(current_supply, requested, curve_config.max_supply, fill_or_cap);
+        if amount == U256::zero() {
+            return Err(TokenError::ExceedsMaxSupply {
+                current: current_supply,
+                adding: requested,
+                max: curve_config.max_supply,
+            });
+        }
+
         // Calculate cost using bonding curve
         let cost = bonding_curve::calculate_buy_cost(
             current_supply,
             amount,
             curve_config.k,
             curve_config.scale,
-        );
+        )?;
+
+        // Early buys pay the snipe tax on top, within the same slippage bound
+        let snipe_tax_bps = self.state.snipe_tax_bps(self.runtime.system_time());
+        let snipe_tax = wad::mul_bps(cost, snipe_tax_bps).ok_or(TokenError::CurveOverflow)?;
+        let total_cost = cost.checked_add(snipe_tax).ok_or(TokenError::CurveOverflow)?;
 
         // Check slippage protection
-        if cost > max_cost {
-            return Err(TokenError::SlippageExceeded { cost, max_cost });
+        if total_cost > max_cost {
+            return Err(TokenError::SlippageExceeded { cost: total_cost, max_cost });
         }
 
         // Check if curve would be completed
@@ -187,26 +839,140 @@ impl TokenContract {
             });
         }
 
-        // Calculate creator fee (e.g., 3% = 300 basis points)
-        let fee_amount = (cost * U256::from(curve_config.creator_fee_bps)) / U256::from(10000);
-        let creator = self.state.creator.get().clone().expect("Creator not set");
+        self.pay_snipe_tax(snipe_tax)?;
+        self.fill_buy(caller, amount, cost, memo, referrer, BuyPayment::Signer).await
+    }
+
+    /// Execute a buy of whatever `spend` affords, bounded by `min_tokens_out`
+    async fn execute_buy_with_budget(
+        &mut self,
+        spend: U256,
+        min_tokens_out: U256,
+        memo: Option<String>,
+        referrer: Option<Account>,
+    ) -> Result<(), TokenError> {
+        if spend == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        self.ensure_bonding_curve()?;
+        self.ensure_launch_live()?;
+        self.ensure_curve_open()?;
+
+        let (amount, _) = self.state.tokens_for_spend(spend, self.runtime.system_time())?;
+        if amount == U256::zero() || amount < min_tokens_out {
+            return Err(TokenError::SlippageExceededBudget { tokens: amount, min_tokens_out });
+        }
+
+        // The spend bounds the cost exactly as a buy's `max_cost` would
+        self.execute_buy(amount, spend, false, memo, referrer).await
+    }
+
+    /// Creator fee rate `account` pays on a curve trade now
+    async fn creator_fee_bps_for(&self, account: Account) -> Result<u16, TokenError> {
+        self.state
+            .creator_fee_bps_for(&account, self.runtime.system_time())
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))
+    }
+
+    /// Reject a curve trade that comes too soon after the account's last one,
+    /// otherwise start its interval
+    async fn enforce_trade_interval(&mut self, account: Account) -> Result<(), TokenError> {
+        let now = self.runtime.system_time();
+        let retry_after = self
+            .state
+            .trade_retry_after(&account, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if let Some(retry_after_micros) = retry_after {
+            return Err(TokenError::RateLimited { retry_after_micros });
+        }
+        self.state
+            .record_trade_at(account, now)
+            .map_err(|e| TokenError::StateError(e.to_string()))
+    }
+
+    /// Collect an early buy's snipe tax from the signer: paid to the
+    /// configured treasury, or kept by the application and never paid out
+    fn pay_snipe_tax(&mut self, snipe_tax: U256) -> Result<(), TokenError> {
+        let curve_config = self.state.curve_config.get().clone();
+        let Some(config) = curve_config.snipe_tax else {
+            return Ok(());
+        };
+        let amount = Self::u256_to_amount(snipe_tax, curve_config.base_decimals)?;
+        if amount == Amount::ZERO {
+            return Ok(());
+        }
+        let (to, burned) = match config.destination {
+            SnipeTaxDestination::Treasury(treasury) => (treasury, false),
+            SnipeTaxDestination::Burn => (self.application_account(), true),
+        };
+        self.collect_payment(to, amount)?;
+        self.state.record_snipe_tax(amount, burned);
+        Ok(())
+    }
+
+    /// Settle a priced curve buy: split the payment, credit the buyer and record the trade
+    async fn fill_buy(
+        &mut self,
+        caller: Account,
+        amount: U256,
+        cost: U256,
+        memo: Option<String>,
+        referrer: Option<Account>,
+        payment: BuyPayment,
+    ) -> Result<(), TokenError> {
+        // Every way into the curve settles here, scheduled buys included
+        self.ensure_launch_live()?;
+
+        let curve_config = self.state.curve_config.get().clone();
+        let new_supply = *self.state.current_supply.get() + amount;
+
+        // Calculate creator fee (e.g., 3% = 300 basis points), less the
+        // buyer's volume tier discount
+        let fee_bps = self.creator_fee_bps_for(caller).await?;
+        let fee_amount = wad::mul_bps(cost, fee_bps).ok_or(TokenError::CurveOverflow)?;
 
         // CRITICAL: Transfer cost from buyer
         // 1. Transfer fee to creator
         // 2. Transfer remaining to application
-        let native_cost = Self::u256_to_amount(cost)?;
-        let native_fee = Self::u256_to_amount(fee_amount)?;
+        let native_cost = Self::u256_to_amount(cost, curve_config.base_decimals)?;
+        let native_fee = Self::u256_to_amount(fee_amount, curve_config.base_decimals)?;
         let native_to_app = native_cost.saturating_sub(native_fee);
 
-        // Transfer fee to creator
-        if native_fee > Amount::ZERO {
-            self.fund_account(creator, native_fee)?;
-        }
+        // Transfer fee to the co-creators, keeping the stakers' share with the application
+        let staking_share = Self::staking_share(native_fee);
+        let creator_fee = native_fee.saturating_sub(staking_share);
+        match payment {
+            BuyPayment::Signer => {
+                if creator_fee > Amount::ZERO {
+                    for (member, share) in self.creator_payouts(creator_fee) {
+                        self.collect_payment(member, share)?;
+                    }
+                    self.state.record_fee_paid(creator_fee);
+                }
+                if staking_share > Amount::ZERO {
+                    let application = self.application_account();
+                    self.collect_payment(application, staking_share)?;
+                }
 
-        // Transfer remaining to application
-        let application = self.application_account();
-        if native_to_app > Amount::ZERO {
-            self.fund_account(application, native_to_app)?;
+                // Transfer remaining to application
+                let application = self.application_account();
+                if native_to_app > Amount::ZERO {
+                    self.collect_payment(application, native_to_app)?;
+                }
+            }
+            BuyPayment::Escrow => {
+                // The whole cost is already held by the application
+                if creator_fee > Amount::ZERO {
+                    self.pay_creators(creator_fee)?;
+                    self.state.record_fee_paid(creator_fee);
+                }
+            }
+        }
+        if staking_share > Amount::ZERO {
+            let now = self.runtime.system_time();
+            self.state.staking.fund_rewards(staking_share, now);
         }
 
         // Update state
@@ -222,8 +988,7 @@ impl TokenContract {
             .expect("Failed to update balance");
 
         // Record trade
-        let trade_id = format!("{}-{}", self.runtime.system_time().micros(), self.state.trade_count.get());
-        let new_price = bonding_curve::calculate_current_price(new_supply, curve_config.k, curve_config.scale);
+        let new_price = bonding_curve::calculate_current_price(new_supply, curve_config.k, curve_config.scale)?;
 
         let trade = Trade {
             token_id: self.state.token_id.get().clone(),
@@ -233,30 +998,584 @@ impl TokenContract {
             currency_amount: cost,
             price: new_price,
             timestamp: self.runtime.system_time(),
+            memo,
+            // Self-referrals are not credited
+            referrer: referrer.filter(|referrer| *referrer != caller),
+            flagged: false,
         };
 
         self.state
-            .record_trade(trade_id, trade.clone())
+            .record_trade(trade.clone())
             .await
             .expect("Failed to record trade");
+        self.notify_trade(&trade, fee_amount);
+        self.publish_price(&trade);
+        self.record_milestones().await;
 
-        // Check if curve is complete
-        if self.state.is_curve_complete() {
+        self.assert_invariants().await;
+        self.sync_summary(false).await;
+
+        // Check if curve is complete and all graduation conditions hold
+        if self.state.can_graduate() {
             self.execute_graduation().await;
         }
 
         Ok(())
     }
 
-    /// Execute a sell operation
-    async fn execute_sell(&mut self, amount: U256, min_return: U256) -> Result<(), TokenError> {
-        // Validate input
-        if amount == U256::zero() {
-            return Err(TokenError::InvalidAmount);
-        }
-
-        // Get caller's account (includes chain_id and owner)
-        let caller = self.owner_account();
+    /// Return an escrowed payment to a buyer and notify the buyer's chain
+    async fn refund_buyer(
+        &mut self,
+        buyer: Account,
+        amount: Amount,
+        reason: String,
+    ) -> Result<(), TokenError> {
+        let now = self.runtime.system_time();
+        let refund_id = self
+            .state
+            .queue_refund(buyer, amount, reason.clone(), now)
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        self.transfer_from_application(buyer, amount)?;
+        self.state.record_refund_paid(amount);
+        self.state
+            .mark_refund_issued(refund_id)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        let token_id = self.state.token_id.get().clone();
+        self.logger.info(format!(
+            "Refunded {} to {} for token {}: {}",
+            amount, buyer, token_id, reason
+        ));
+        self.runtime
+            .prepare_message(Message::RefundIssued {
+                token_id,
+                buyer,
+                amount,
+                reason,
+            })
+            .send_to(buyer.chain_id);
+
+        Ok(())
+    }
+
+    /// Snapshot of the curve for cross-application readers
+    fn curve_state(&self) -> Result<CurveState, TokenError> {
+        let curve_config = self.state.curve_config.get();
+        let current_supply = *self.state.current_supply.get();
+        let price =
+            bonding_curve::calculate_current_price(current_supply, curve_config.k, curve_config.scale)?;
+        Ok(CurveState {
+            current_supply,
+            max_supply: curve_config.max_supply,
+            total_raised: *self.state.total_raised.get(),
+            price,
+            base_asset: curve_config.base_asset,
+            base_decimals: curve_config.base_decimals,
+            is_graduated: *self.state.is_graduated.get(),
+            dex_pool_id: self.state.dex_pool_id.get().clone(),
+        })
+    }
+
+    /// Reject curve trades once graduation has started, pointing at the DEX pool
+    fn ensure_curve_open(&self) -> Result<(), TokenError> {
+        if self.state.is_curve_closed() {
+            let pool_id = self
+                .state
+                .dex_pool_id
+                .get()
+                .clone()
+                .unwrap_or_else(|| "(pending creation)".to_string());
+            return Err(TokenError::CurveClosed { pool_id });
+        }
+        Ok(())
+    }
+
+    /// Reject curve trades once the launch has missed its minimum raise
+    fn ensure_launch_live(&self) -> Result<(), TokenError> {
+        if !self.state.is_launch_live(self.runtime.system_time()) {
+            return Err(TokenError::LaunchFailed);
+        }
+        Ok(())
+    }
+
+    /// Record the failure of a launch that missed its minimum raise, fixing
+    /// the refund pool every holder's share is taken from
+    fn fail_launch_if_missed(&mut self) {
+        let now = self.runtime.system_time();
+        if self.state.is_failed() || !self.state.missed_min_raise(now) {
+            return;
+        }
+        let failure = LaunchFailure {
+            failed_at: now,
+            refund_pool: self.available_liquidity(),
+            supply: *self.state.current_supply.get(),
+            redeemed: Amount::ZERO,
+        };
+        self.state.launch_failure.set(Some(failure));
+
+        let token_id = self.state.token_id.get().clone();
+        let total_raised = *self.state.total_raised.get();
+        let min_raise = self.state.curve_config.get().min_raise.unwrap_or_default();
+        self.logger.warn(format!(
+            "Token {} failed: raised {} of the minimum {}; {} open for refunds",
+            token_id, total_raised, min_raise, failure.refund_pool
+        ));
+        self.emit_event(TokenEvent::LaunchFailed {
+            token_id,
+            total_raised,
+            min_raise,
+            failed_at: now,
+        });
+    }
+
+    /// Reject curve buys and sells on auction and sale launches
+    fn ensure_bonding_curve(&self) -> Result<(), TokenError> {
+        if !self.state.is_bonding_curve() {
+            let mechanism = self.state.curve_config.get().launch_mechanism;
+            return Err(TokenError::NotBondingCurve(mechanism));
+        }
+        Ok(())
+    }
+
+    /// Reject buys by accounts missing from the whitelist while its phase runs
+    async fn ensure_may_buy(&self, buyer: Account) -> Result<(), TokenError> {
+        let now = self.runtime.system_time();
+        let allowed = self
+            .state
+            .may_buy(&buyer, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        match self.state.curve_config.get().whitelist_ends_at {
+            Some(ends_at) if !allowed => Err(TokenError::NotWhitelisted { ends_at }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject a strictly quoted trade if the supply moved since the quote
+    ///
+    /// Any trade landing in between changes the supply, so a matching supply
+    /// means the trade executes at exactly the quoted price.
+    fn check_expected_supply(&self, expected_supply: Option<U256>) -> Result<(), TokenError> {
+        let current = *self.state.current_supply.get();
+        match expected_supply {
+            Some(expected) if expected != current => {
+                Err(TokenError::StaleQuote { expected, current })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject memos longer than `MAX_MEMO_LENGTH`
+    fn validate_memo(memo: &Option<String>) -> Result<(), TokenError> {
+        match memo {
+            Some(memo) if memo.len() > MAX_MEMO_LENGTH => Err(TokenError::MemoTooLong {
+                length: memo.len(),
+                max: MAX_MEMO_LENGTH,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Buy up to `amount` auctioned tokens at the current auction price
+    ///
+    /// Tokens are credited right away; the bid pays the current price and is
+    /// refunded down to the clearing price at settlement. The bid that sells
+    /// out the supply settles the auction.
+    async fn execute_auction_bid(
+        &mut self,
+        amount: U256,
+        max_cost: U256,
+    ) -> Result<(), TokenError> {
+        if amount == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        let curve_config = self.state.curve_config.get().clone();
+        let auction = *curve_config.launch_mechanism.auction().ok_or(TokenError::NotAuction)?;
+        self.ensure_curve_open()?;
+        let now = self.runtime.system_time();
+        if now < auction.starts_at {
+            return Err(TokenError::AuctionNotStarted(auction.starts_at));
+        }
+        if self.state.auction.is_settled() || now >= dutch_auction::ends_at(&auction) {
+            return Err(TokenError::AuctionClosed);
+        }
+
+        let caller = self.owner_account();
+        self.ensure_may_buy(caller).await?;
+
+        // Bids past the unsold supply are filled partially
+        let current_supply = *self.state.current_supply.get();
+        let amount = Self::capped_buy_amount(current_supply, amount, curve_config.max_supply, true);
+        if amount == U256::zero() {
+            return Err(TokenError::AuctionClosed);
+        }
+        let price = dutch_auction::price_at(&auction, now);
+        let cost = price.mul_amount(amount).ok_or(TokenError::CurveOverflow)?;
+        if cost > max_cost {
+            return Err(TokenError::SlippageExceeded { cost, max_cost });
+        }
+
+        let application = self.application_account();
+        let native_cost = Self::u256_to_amount(cost, curve_config.base_decimals)?;
+        self.collect_payment(application, native_cost)?;
+
+        let new_supply = current_supply + amount;
+        self.state.current_supply.set(new_supply);
+        let total_raised = *self.state.total_raised.get();
+        self.state.total_raised.set(total_raised + cost);
+        let balance = self.state.get_balance(&caller).await;
+        self.state
+            .set_balance(caller, balance + amount)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.state
+            .auction
+            .record_bid(caller, amount, cost)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        let trade = Trade {
+            token_id: self.state.token_id.get().clone(),
+            trader: caller,
+            is_buy: true,
+            token_amount: amount,
+            currency_amount: cost,
+            price,
+            timestamp: now,
+            memo: None,
+            referrer: None,
+            flagged: false,
+        };
+        self.state
+            .record_trade(trade.clone())
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        // The creator's fee is charged on the final raise at settlement
+        self.notify_trade(&trade, U256::zero());
+        self.publish_price(&trade);
+        self.assert_invariants().await;
+
+        if new_supply >= curve_config.max_supply {
+            self.settle_auction(price).await?;
+        } else {
+            self.sync_summary(false).await;
+        }
+        Ok(())
+    }
+
+    /// Settle an auction that ran out of time at its floor price
+    async fn execute_settle_auction(&mut self) -> Result<(), TokenError> {
+        let auction = *self
+            .state
+            .curve_config
+            .get()
+            .launch_mechanism
+            .auction()
+            .ok_or(TokenError::NotAuction)?;
+        if self.state.auction.is_settled() {
+            return Err(TokenError::AuctionClosed);
+        }
+        let ends_at = dutch_auction::ends_at(&auction);
+        if self.runtime.system_time() < ends_at {
+            return Err(TokenError::AuctionRunning(ends_at));
+        }
+        self.settle_auction(auction.floor_price).await
+    }
+
+    /// Fix the clearing price, charge the creator fee on the raise, set the
+    /// overpayments aside for refunds and graduate if the launch qualifies
+    async fn settle_auction(&mut self, clearing_price: Price) -> Result<(), TokenError> {
+        let now = self.runtime.system_time();
+        let curve_config = self.state.curve_config.get().clone();
+        let sold = *self.state.current_supply.get();
+        let raised = dutch_auction::charge(sold, clearing_price).ok_or(TokenError::CurveOverflow)?;
+        let overpaid = self.state.auction.total_paid.get().saturating_sub(raised);
+        let refund_reserve = Self::u256_to_amount(overpaid, curve_config.base_decimals)?;
+        self.state.auction.settle(clearing_price, now, refund_reserve);
+        self.state.total_raised.set(raised);
+        self.pay_settlement_fee(raised)?;
+
+        let token_id = self.state.token_id.get().clone();
+        self.logger.info(format!(
+            "Auction for {} settled at {}: {} tokens sold, {} raised, {} set aside for refunds",
+            token_id, clearing_price, sold, raised, refund_reserve
+        ));
+        self.emit_event(TokenEvent::AuctionSettled {
+            token_id,
+            clearing_price,
+            sold,
+            total_raised: raised,
+            settled_at: now,
+        });
+        self.finish_settlement().await;
+        Ok(())
+    }
+
+    /// Charge the creator fee on an auction or sale raise, with the same
+    /// creator / staker split as a curve buy's fee
+    fn pay_settlement_fee(&mut self, raised: U256) -> Result<(), TokenError> {
+        let curve_config = self.state.curve_config.get().clone();
+        let fee =
+            wad::mul_bps(raised, curve_config.creator_fee_bps).ok_or(TokenError::CurveOverflow)?;
+        let native_fee = Self::u256_to_amount(fee, curve_config.base_decimals)?;
+        let staking_share = Self::staking_share(native_fee);
+        let creator_fee = native_fee.saturating_sub(staking_share);
+        if creator_fee > Amount::ZERO {
+            self.pay_creators(creator_fee)?;
+            self.state.record_fee_paid(creator_fee);
+        }
+        if staking_share > Amount::ZERO {
+            let now = self.runtime.system_time();
+            self.state.staking.fund_rewards(staking_share, now);
+        }
+        Ok(())
+    }
+
+    /// Publish the final raise and graduate if the launch qualifies
+    async fn finish_settlement(&mut self) {
+        self.record_milestones().await;
+        if self.state.can_graduate() {
+            self.execute_graduation().await;
+        } else {
+            self.sync_summary(true).await;
+        }
+    }
+
+    /// Escrow a commitment to a fixed-price sale until its window closes
+    async fn execute_commit_to_sale(&mut self, amount: Amount) -> Result<(), TokenError> {
+        let curve_config = self.state.curve_config.get().clone();
+        let sale = *curve_config
+            .launch_mechanism
+            .fixed_price_sale()
+            .ok_or(TokenError::NotFixedPriceSale)?;
+        let now = self.runtime.system_time();
+        if now < sale.starts_at {
+            return Err(TokenError::SaleNotStarted(sale.starts_at));
+        }
+        if now >= fixed_price_sale::ends_at(&sale) {
+            return Err(TokenError::SaleClosed);
+        }
+
+        let buyer = self.owner_account();
+        self.ensure_may_buy(buyer).await?;
+
+        // Only whole base units are committed
+        let committed = units::from_amount(amount, curve_config.base_decimals)
+            .map_err(|_| TokenError::AmountConversionError)?;
+        if committed.is_zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        let payment = Self::u256_to_amount(committed, curve_config.base_decimals)?;
+        let application = self.application_account();
+        self.collect_payment(application, payment)?;
+        self.state
+            .sale
+            .commit(buyer, committed)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Allocate the next batch of commitments once the sale window closed
+    ///
+    /// Buyers are credited their tokens and refunded what their allocation
+    /// did not use; the call allocating the last buyer settles the sale.
+    async fn execute_settle_sale(&mut self) -> Result<(), TokenError> {
+        let curve_config = self.state.curve_config.get().clone();
+        let sale = *curve_config
+            .launch_mechanism
+            .fixed_price_sale()
+            .ok_or(TokenError::NotFixedPriceSale)?;
+        if self.state.sale.is_settled() {
+            return Err(TokenError::SaleClosed);
+        }
+        let now = self.runtime.system_time();
+        let ends_at = fixed_price_sale::ends_at(&sale);
+        if now < ends_at {
+            return Err(TokenError::SaleRunning(ends_at));
+        }
+
+        let total_committed = *self.state.sale.total_committed.get();
+        let batch = self
+            .state
+            .sale
+            .next_to_settle(MAX_SALE_SETTLEMENTS_PER_CALL)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        for (buyer, commitment) in batch {
+            let allocation = fixed_price_sale::allocate(
+                &sale,
+                commitment.committed,
+                total_committed,
+                curve_config.max_supply,
+            )
+            .ok_or(TokenError::CurveOverflow)?;
+            self.state
+                .sale
+                .record_allocation(buyer, commitment, allocation.tokens, allocation.refund)
+                .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+            if !allocation.tokens.is_zero() {
+                self.allocate_sale_tokens(buyer, allocation, sale.price, now).await?;
+            }
+            if !allocation.refund.is_zero() {
+                let refund = Self::u256_to_amount(allocation.refund, curve_config.base_decimals)?;
+                self.refund_buyer(buyer, refund, "Fixed-price sale allocation".to_string())
+                    .await?;
+            }
+        }
+        self.assert_invariants().await;
+
+        if !self.state.sale.all_allocated() {
+            return Ok(());
+        }
+        self.state.sale.mark_settled(now);
+        let raised = *self.state.total_raised.get();
+        self.pay_settlement_fee(raised)?;
+
+        let token_id = self.state.token_id.get().clone();
+        let sold = *self.state.current_supply.get();
+        self.logger.info(format!(
+            "Sale for {} settled: {} tokens sold, {} raised of {} committed",
+            token_id, sold, raised, total_committed
+        ));
+        self.emit_event(TokenEvent::SaleSettled {
+            token_id,
+            sold,
+            total_committed,
+            total_raised: raised,
+            settled_at: now,
+        });
+        self.finish_settlement().await;
+        Ok(())
+    }
+
+    /// Credit a buyer's sale allocation and record it as a trade
+    async fn allocate_sale_tokens(
+        &mut self,
+        buyer: Account,
+        allocation: fixed_price_sale::Allocation,
+        price: Price,
+        now: Timestamp,
+    ) -> Result<(), TokenError> {
+        let current_supply = *self.state.current_supply.get();
+        self.state.current_supply.set(current_supply + allocation.tokens);
+        let total_raised = *self.state.total_raised.get();
+        self.state.total_raised.set(total_raised + allocation.cost);
+        let balance = self.state.get_balance(&buyer).await;
+        self.state
+            .set_balance(buyer, balance + allocation.tokens)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        let trade = Trade {
+            token_id: self.state.token_id.get().clone(),
+            trader: buyer,
+            is_buy: true,
+            token_amount: allocation.tokens,
+            currency_amount: allocation.cost,
+            price,
+            timestamp: now,
+            memo: None,
+            referrer: None,
+            flagged: false,
+        };
+        self.state
+            .record_trade(trade.clone())
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        // The creator's fee is charged on the final raise
+        self.notify_trade(&trade, U256::zero());
+        self.publish_price(&trade);
+        Ok(())
+    }
+
+    /// Pay the caller what their bids paid above the clearing price
+    async fn execute_claim_auction_refund(&mut self) -> Result<(), TokenError> {
+        if !self.state.is_auction() {
+            return Err(TokenError::NotAuction);
+        }
+        let caller = self.owner_account();
+        let refund = self
+            .state
+            .auction
+            .take_refund(&caller)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if refund.is_zero() {
+            return Err(TokenError::NothingToRefund);
+        }
+
+        let base_decimals = self.state.curve_config.get().base_decimals;
+        let amount = Self::u256_to_amount(refund, base_decimals)?
+            .min(*self.state.auction.refund_reserve.get());
+        self.state.auction.release_refund(amount);
+        self.transfer_from_application(caller, amount)?;
+        self.state.record_refund_paid(amount);
+        Ok(())
+    }
+
+    /// Burn the caller's tokens for their share of a failed launch's raise
+    async fn execute_redeem_refund(&mut self) -> Result<(), TokenError> {
+        self.fail_launch_if_missed();
+        if !self.state.is_failed() {
+            return Err(TokenError::LaunchNotFailed);
+        }
+        let caller = self.owner_account();
+        let (burned, refund) = self
+            .state
+            .redeem_refund(caller)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if burned.is_zero() {
+            return Err(TokenError::NothingToRefund);
+        }
+
+        if refund > Amount::ZERO {
+            self.transfer_from_application(caller, refund)?;
+            self.state.record_refund_paid(refund);
+        }
+        self.logger.info(format!("{} redeemed {} tokens for {}", caller, burned, refund));
+        self.sync_summary(false).await;
+        Ok(())
+    }
+
+    /// Whether a `TokenCreated` for `token_id` initializes this chain, given
+    /// the token it already hosts: false for a redelivery of the same launch
+    fn check_token_created(hosted: Option<&str>, token_id: &str) -> Result<bool, TokenError> {
+        match hosted {
+            None => Ok(true),
+            Some(hosted) if hosted == token_id => Ok(false),
+            Some(hosted) => Err(TokenError::ChainHostsToken(hosted.to_string())),
+        }
+    }
+
+    /// Amount actually bought: the request, or the remaining supply when capping
+    fn capped_buy_amount(current_supply: U256, amount: U256, max_supply: U256, fill_or_cap: bool) -> U256 {
+        let remaining = max_supply.saturating_sub(current_supply);
+        if fill_or_cap && amount > remaining {
+            remaining
+        } else {
+            amount
+        }
+    }
+
+    /// Execute a sell operation
+    async fn execute_sell(&mut self, amount: U256, min_return: U256, memo: Option<String>) -> Result<(), TokenError> {
+        // Validate input
+        if amount == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        Self::validate_memo(&memo)?;
+        self.ensure_bonding_curve()?;
+        self.ensure_launch_live()?;
+        self.ensure_curve_open()?;
+
+        // Get caller's account (includes chain_id and owner)
+        let caller = self.owner_account();
+        self.enforce_trade_interval(caller).await?;
 
         // Check user has enough balance
         let current_balance = self.state.get_balance(&caller).await;
@@ -267,180 +1586,1545 @@ impl TokenContract {
             });
         }
 
-        let current_supply = *self.state.current_supply.get();
-        let curve_config = self.state.curve_config.get().clone();
+        let current_supply = *self.state.current_supply.get();
+        let curve_config = self.state.curve_config.get().clone();
+
+        // Calculate return using bonding curve
+        let return_amount = bonding_curve::calculate_sell_return(
+            current_supply,
+            amount,
+            curve_config.k,
+            curve_config.scale,
+        )?;
+
+        // Check slippage protection
+        if return_amount < min_return {
+            return Err(TokenError::SlippageExceededSell {
+                return_amount,
+                min_return,
+            });
+        }
+
+        // Calculate creator fee on sell, less the seller's volume tier discount
+        let fee_bps = self.creator_fee_bps_for(caller).await?;
+        let fee_amount =
+            wad::mul_bps(return_amount, fee_bps).ok_or(TokenError::CurveOverflow)?;
+        let net_return = return_amount.saturating_sub(fee_amount);
+
+        // CRITICAL: Transfer from application
+        // 1. Transfer fee to the co-creators
+        // 2. Transfer net return to seller
+        let native_fee = Self::u256_to_amount(fee_amount, curve_config.base_decimals)?;
+        let native_net_return = Self::u256_to_amount(net_return, curve_config.base_decimals)?;
+        let seller_account = self.owner_account();
+
+        // The whole return leaves the curve's liquidity (the stakers' share moves
+        // into the reward reserve), so check it can be paid before anything settles
+        let payout = native_fee.saturating_add(native_net_return);
+        let available = self.available_liquidity();
+        if available < payout {
+            return Err(TokenError::InsufficientLiquidity {
+                available,
+                need: payout,
+            });
+        }
+
+        // Settle the transfers before any state changes: fee to the co-creators,
+        // with the stakers' share staying with the application, then the seller's return
+        let staking_share = Self::staking_share(native_fee);
+        let creator_fee = native_fee.saturating_sub(staking_share);
+        if creator_fee > Amount::ZERO {
+            self.pay_creators(creator_fee)?;
+        }
+        if native_net_return > Amount::ZERO {
+            self.transfer_from_application(seller_account, native_net_return)?;
+        }
+
+        if creator_fee > Amount::ZERO {
+            self.state.record_fee_paid(creator_fee);
+        }
+        if staking_share > Amount::ZERO {
+            let now = self.runtime.system_time();
+            self.state.staking.fund_rewards(staking_share, now);
+        }
+
+        // Update state
+        let new_supply = current_supply - amount;
+        self.state.current_supply.set(new_supply);
+        let total_raised = *self.state.total_raised.get();
+        self.state.total_raised.set(total_raised.saturating_sub(return_amount));
+
+        // Update user balance
+        self.state
+            .set_balance(caller, current_balance - amount)
+            .await
+            .expect("Failed to update balance");
+
+        // Record trade
+        let new_price = bonding_curve::calculate_current_price(new_supply, curve_config.k, curve_config.scale)?;
+
+        let trade = Trade {
+            token_id: self.state.token_id.get().clone(),
+            trader: caller,
+            is_buy: false,
+            token_amount: amount,
+            currency_amount: return_amount,
+            price: new_price,
+            timestamp: self.runtime.system_time(),
+            memo,
+            referrer: None,
+            flagged: false,
+        };
+
+        self.state
+            .record_trade(trade.clone())
+            .await
+            .expect("Failed to record trade");
+        self.notify_trade(&trade, fee_amount);
+        self.publish_price(&trade);
+
+        self.assert_invariants().await;
+        self.sync_summary(false).await;
+
+        Ok(())
+    }
+
+    /// Panic if the accounting invariants no longer hold
+    ///
+    /// Only compiled into debug builds or with the `invariants` feature, since
+    /// summing every balance is too expensive for production trades.
+    #[cfg(any(debug_assertions, feature = "invariants"))]
+    async fn assert_invariants(&mut self) {
+        let native_balance = self.application_balance();
+        let reconciliation = self
+            .state
+            .reconcile()
+            .await
+            .expect("Failed to reconcile state");
+        let violations = reconciliation.violations(native_balance);
+        assert!(violations.is_empty(), "Invariant violated: {}", violations.join("; "));
+    }
+
+    #[cfg(not(any(debug_assertions, feature = "invariants")))]
+    async fn assert_invariants(&mut self) {}
+
+    /// Execute graduation to DEX
+    async fn execute_graduation(&mut self) {
+        if self.state.is_curve_closed() {
+            return; // Already graduated or graduation in flight
+        }
+
+        // Without a swap application the pool could never be seeded, so the
+        // launch stays on its curve rather than graduate into a pool that fails
+        let parameters = self.runtime.application_parameters();
+        let Some(swap_application_id) = parameters.swap_application_id else {
+            self.logger.warn(format!(
+                "Token {} cannot graduate: no swap application is configured",
+                self.state.token_id.get()
+            ));
+            return;
+        };
+
+        // Pay the creator and set the burn aside before the pool takes the rest
+        self.execute_graduation_plan();
+        self.burn_unsold_supply();
+
+        // Move the raised native balance to the swap application's account
+        let swap_chain = parameters
+            .swap_chain_id
+            .unwrap_or_else(|| self.runtime.chain_id());
+        let receipt = self.transfer_liquidity_to_swap(swap_application_id, swap_chain);
+        self.state.graduation_receipt.set(receipt);
+
+        // Seed a stablecoin-quoted pool too when one is configured and funded
+        let stable_leg = parameters.stable_pool.and_then(|config| {
+            self.transfer_stable_liquidity_to_swap(swap_application_id, swap_chain, config)
+        });
+        self.state.graduation_stable_leg.set(stable_leg);
+
+        self.send_graduation_message();
+        self.sync_summary(true).await;
+    }
+
+    /// Divide the raise according to the launch's `GraduationPlan`
+    ///
+    /// The creator's share is paid out and the burned share is added to the
+    /// reserved base, which leaves exactly the pool's share for
+    /// `transfer_liquidity_to_swap`.
+    fn execute_graduation_plan(&mut self) {
+        let plan = self.state.curve_config.get().graduation_plan;
+        let raised = self.available_liquidity();
+        let (to_pool, to_creator, burned) = plan.split(raised);
+
+        if to_creator > Amount::ZERO {
+            self.pay_creators(to_creator)
+                .expect("Failed to pay the creator's graduation share");
+            self.state.graduation_creator_payout.set(to_creator);
+        }
+        self.state.graduation_burned.set(burned);
+
+        let token_id = self.state.token_id.get().clone();
+        self.logger.info(format!(
+            "Token {} graduation split: {} to pool, {} to creator, {} burned",
+            token_id, to_pool, to_creator, burned
+        ));
+        let split_at = self.runtime.system_time();
+        self.emit_event(TokenEvent::GraduationFundsSplit {
+            token_id,
+            to_pool,
+            to_creator,
+            burned,
+            split_at,
+        });
+    }
+
+    /// Burn the supply left unsold when the launch is configured to, so it
+    /// never reaches the pool
+    fn burn_unsold_supply(&mut self) {
+        if !self.state.curve_config.get().burn_unsold_on_graduation {
+            return;
+        }
+        let amount = self.state.unsold_supply();
+        if amount.is_zero() {
+            return;
+        }
+        self.state.unsold_burned.set(amount);
+
+        let token_id = self.state.token_id.get().clone();
+        self.logger.info(format!(
+            "Token {} burned {} unsold tokens at graduation",
+            token_id, amount
+        ));
+        let burned_at = self.runtime.system_time();
+        self.emit_event(TokenEvent::UnsoldSupplyBurned {
+            token_id,
+            amount,
+            burned_at,
+        });
+    }
+
+    /// Burn the tokens the swap chain left out of the graduation pool to open
+    /// it at the final launch price
+    fn burn_pool_excess(&mut self, pool_id: &str, amount: U256) {
+        if amount.is_zero() {
+            return;
+        }
+        self.state.pool_excess_burned.set(amount);
+
+        let token_id = self.state.token_id.get().clone();
+        self.logger.info(format!(
+            "Token {} burned {} tokens left out of pool {}",
+            token_id, amount, pool_id
+        ));
+        let burned_at = self.runtime.system_time();
+        self.emit_event(TokenEvent::PoolExcessBurned {
+            token_id,
+            pool_id: pool_id.to_string(),
+            amount,
+            burned_at,
+        });
+    }
+
+    /// Record this bytecode's version and report it to the factory chain
+    fn announce_version(&mut self) {
+        self.state.contract_version.set(CONTRACT_VERSION);
+        let Some(factory_chain) = self.runtime.application_parameters().factory_chain_id else {
+            return;
+        };
+        self.runtime
+            .prepare_message(Message::VersionAnnouncement {
+                token_id: self.state.token_id.get().clone(),
+                version: CONTRACT_VERSION,
+            })
+            .send_to(factory_chain);
+    }
+
+    /// Send the listing summary to the factory chain, at most once per
+    /// `SUMMARY_SYNC_INTERVAL_MICROS` unless `force` is set
+    async fn sync_summary(&mut self, force: bool) {
+        let Some(factory_chain) = self.runtime.application_parameters().factory_chain_id else {
+            return;
+        };
+
+        let now = self.runtime.system_time();
+        if let Some(last_sync) = *self.state.last_summary_sync.get() {
+            let elapsed = now.micros().saturating_sub(last_sync.micros());
+            if !force && elapsed < SUMMARY_SYNC_INTERVAL_MICROS {
+                return;
+            }
+        }
+
+        let summary = self
+            .state
+            .summary(now)
+            .await
+            .expect("Failed to build token summary");
+        self.state.last_summary_sync.set(Some(now));
+
+        self.runtime
+            .prepare_message(Message::SyncTokenSummary {
+                token_id: self.state.token_id.get().clone(),
+                current_supply: *self.state.current_supply.get(),
+                total_raised: *self.state.total_raised.get(),
+                summary,
+            })
+            .send_to(factory_chain);
+    }
+
+    /// Report a curve trade to the factory chain, which caches per-account
+    /// portfolios and platform statistics
+    fn notify_trade(&mut self, trade: &Trade, creator_fee: U256) {
+        let Some(factory_chain) = self.runtime.application_parameters().factory_chain_id else {
+            return;
+        };
+
+        self.runtime
+            .prepare_message(Message::TradeExecuted {
+                token_id: trade.token_id.clone(),
+                trader: trade.trader,
+                is_buy: trade.is_buy,
+                token_amount: trade.token_amount,
+                currency_amount: trade.currency_amount,
+                new_price: trade.price,
+                creator_fee,
+            })
+            .send_to(factory_chain);
+    }
+
+    /// Publish the price a trade left the token at to `price_feed` subscribers,
+    /// when the trade interval or the move since the last update calls for it
+    fn publish_price(&mut self, trade: &Trade) {
+        let config = self.runtime.application_parameters().price_feed;
+        let trade_count = *self.state.trade_count.get();
+        let last = self.state.last_price_update.get().clone();
+        if !price_feed::should_publish(&config, last.as_ref(), trade.price, trade_count) {
+            return;
+        }
+
+        let update = PriceUpdate {
+            token_id: trade.token_id.clone(),
+            price: trade.price,
+            trade_count,
+            published_at: trade.timestamp,
+        };
+        self.state.last_price_update.set(Some(update.clone()));
+        self.runtime
+            .emit(StreamName::from(PRICE_FEED_STREAM), &TokenEvent::PriceUpdate(update));
+    }
+
+    /// Timestamp the raise milestones crossed by a buy and publish them
+    async fn record_milestones(&mut self) {
+        let now = self.runtime.system_time();
+        let reached = self
+            .state
+            .record_milestones(now)
+            .await
+            .expect("Failed to record milestones");
+
+        let token_id = self.state.token_id.get().clone();
+        let total_raised = *self.state.total_raised.get();
+        for milestone_bps in reached {
+            self.logger.info(format!(
+                "Token {} reached {}% of its target raise",
+                token_id,
+                milestone_bps / 100
+            ));
+            self.emit_event(TokenEvent::MilestoneReached {
+                token_id: token_id.clone(),
+                milestone_bps,
+                total_raised,
+                reached_at: now,
+            });
+        }
+    }
+
+    /// Publish a token event on the event stream and record it in the outbox
+    fn emit_event(&mut self, event: TokenEvent) {
+        let kind = match &event {
+            TokenEvent::MilestoneReached { .. } => "MilestoneReached",
+            TokenEvent::GraduationFundsSplit { .. } => "GraduationFundsSplit",
+            TokenEvent::AuctionSettled { .. } => "AuctionSettled",
+            TokenEvent::SaleSettled { .. } => "SaleSettled",
+            TokenEvent::PriceUpdate(_) => "PriceUpdate",
+            TokenEvent::UnsoldSupplyBurned { .. } => "UnsoldSupplyBurned",
+            TokenEvent::LaunchFailed { .. } => "LaunchFailed",
+        };
+        let token_id = self.state.token_id.get().clone();
+        let now = self.runtime.system_time();
+        self.state
+            .outbox
+            .push(kind, Some(&token_id), &event, now)
+            .expect("Failed to record outbox event");
+        self.runtime.emit(StreamName::from(TOKEN_EVENTS_STREAM), &event);
+    }
+
+    /// Tell the swap chain that a graduated token's supply changed, so its
+    /// pool statistics stay accurate (no-op before graduation)
+    fn notify_supply_changed(&mut self) {
+        if !*self.state.is_graduated.get() {
+            return;
+        }
+        let total_supply = self.state.supply_after_buybacks();
+        let circulating_supply = total_supply.saturating_sub(*self.state.total_locked.get());
+
+        let swap_chain = self
+            .runtime
+            .application_parameters()
+            .swap_chain_id
+            .unwrap_or_else(|| self.runtime.chain_id());
+        self.runtime
+            .prepare_message(Message::SupplyChanged {
+                token_id: self.state.token_id.get().clone(),
+                total_supply,
+                circulating_supply,
+                reported_at: self.runtime.system_time(),
+            })
+            .send_to(swap_chain);
+    }
+
+    /// Send (or resend) the tracked graduation message to the swap chain
+    fn send_graduation_message(&mut self) {
+        self.state.graduation_started.set(true);
+
+        let token_id = self.state.token_id.get().clone();
+        let total_supply = self.state.graduation_supply();
+        let total_raised = *self.state.total_raised.get();
+        let receipt = self.state.graduation_receipt.get().clone();
+        let base_decimals = self.state.curve_config.get().base_decimals;
+        let base_asset = self.state.curve_config.get().base_asset;
+        let stable_leg = self.state.graduation_stable_leg.get().clone();
+        let lock_duration_micros = self.state.curve_config.get().pool_lock_micros;
+        let creator = self.state.creator.get().clone();
+        let final_price = self.state.final_price();
+
+        // Send graduation message to swap chain (defaults to this chain)
+        let swap_chain = self
+            .runtime
+            .application_parameters()
+            .swap_chain_id
+            .unwrap_or_else(|| self.runtime.chain_id());
+
+        self.runtime
+            .prepare_message(Message::GraduateToken {
+                token_id,
+                total_supply,
+                total_raised,
+                receipt,
+                base_decimals,
+                base_asset,
+                stable_leg,
+                lock_duration_micros,
+                creator,
+                final_price,
+            })
+            .with_tracking()
+            .send_to(swap_chain);
+    }
+
+    /// Compensate for a tracked message that was rejected by its receiver
+    async fn handle_bounced_message(&mut self, message: Message) {
+        let now = self.runtime.system_time();
+        match message {
+            Message::GraduateToken { token_id, .. } => {
+                // Funds already moved stay with the swap app; the curve stays closed
+                let bounces = self.state.bounce_count("GraduateToken", &token_id).await + 1;
+                let retry = bounces <= MAX_GRADUATION_RETRIES;
+                let resolution = if retry {
+                    "Graduation resent automatically"
+                } else {
+                    "Retries exhausted; resend with the Graduate operation"
+                };
+                self.state
+                    .record_bounce("GraduateToken", &token_id, now, resolution)
+                    .await
+                    .expect("Failed to record bounced graduation");
+
+                if retry {
+                    self.send_graduation_message();
+                } else {
+                    self.logger.critical(format!(
+                        "Graduation of {} bounced {} times",
+                        token_id, bounces
+                    ));
+                }
+            }
+
+            Message::PoolTokensDeposited { token_id, depositor, amount } => {
+                // Return the deposit from the swap application's account
+                let swap_account = self.swap_application_account();
+                let result = match swap_account {
+                    Some(swap_account) => {
+                        self.move_balance(swap_account, depositor.clone(), amount).await
+                    }
+                    None => Err(TokenError::PoolDepositUnavailable),
+                };
+                match result {
+                    Ok(()) => self.logger.warn(format!(
+                        "Deposit of {} {} pool tokens bounced; returned to {}",
+                        amount, token_id, depositor
+                    )),
+                    Err(e) => self.logger.critical(format!(
+                        "Failed to return bounced deposit of {} tokens to {}: {}",
+                        amount, depositor, e
+                    )),
+                }
+            }
+
+            _ => {
+                // Only graduation and pool deposits are sent with tracking from
+                // the token contract
+            }
+        }
+    }
+
+    /// Move tokens to the swap application to open pools in other base assets
+    ///
+    /// The swap credits the deposit to the caller, who draws on it with
+    /// `CreatePool`; a bounced deposit is returned.
+    async fn execute_deposit_pool_tokens(&mut self, amount: U256) -> Result<(), TokenError> {
+        if amount.is_zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        if !*self.state.is_graduated.get() {
+            return Err(TokenError::PoolDepositUnavailable);
+        }
+        let swap_account = self
+            .swap_application_account()
+            .ok_or(TokenError::PoolDepositUnavailable)?;
+
+        let depositor = self.owner_account();
+        self.move_balance(depositor.clone(), swap_account.clone(), amount).await?;
+
+        let token_id = self.state.token_id.get().clone();
+        self.runtime
+            .prepare_message(Message::PoolTokensDeposited {
+                token_id,
+                depositor,
+                amount,
+            })
+            .with_tracking()
+            .send_to(swap_account.chain_id);
+        Ok(())
+    }
+
+    /// The swap application's account on the swap chain, if one is configured
+    fn swap_application_account(&mut self) -> Option<Account> {
+        let parameters = self.runtime.application_parameters();
+        let swap_application_id = parameters.swap_application_id?;
+        let chain_id = parameters
+            .swap_chain_id
+            .unwrap_or_else(|| self.runtime.chain_id());
+        Some(Account {
+            chain_id,
+            owner: AccountOwner::from(swap_application_id),
+        })
+    }
+
+    /// Move tokens between two accounts, failing if `from` holds too few
+    async fn move_balance(
+        &mut self,
+        from: Account,
+        to: Account,
+        amount: U256,
+    ) -> Result<(), TokenError> {
+        let from_balance = self.state.get_balance(&from).await;
+        if from_balance < amount {
+            return Err(TokenError::InsufficientBalance {
+                have: from_balance,
+                need: amount,
+            });
+        }
+        self.state
+            .set_balance(from, from_balance - amount)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        let to_balance = self.state.get_balance(&to).await;
+        self.state
+            .set_balance(to, to_balance + amount)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))
+    }
+
+    /// Transfer the application-held native balance to the swap application
+    fn transfer_liquidity_to_swap(
+        &mut self,
+        swap_application_id: ApplicationId,
+        swap_chain: ChainId,
+    ) -> Option<TransferReceipt> {
+        // Staking and snapshot rewards stay behind for their claimants
+        let reserved = self.state.reserved_base();
+        let amount = self.application_balance().saturating_sub(reserved);
+        if amount == Amount::ZERO {
+            return None;
+        }
+
+        let to = Account {
+            chain_id: swap_chain,
+            owner: AccountOwner::from(swap_application_id),
+        };
+        self.transfer_from_application(to, amount)
+            .expect("Failed to move liquidity to the swap application");
+
+        Some(TransferReceipt {
+            from: self.application_account(),
+            to,
+            amount,
+            transferred_at: self.runtime.system_time(),
+        })
+    }
+
+    /// Move the application's stablecoin balance to the swap application,
+    /// paired with the configured share of the graduating supply
+    fn transfer_stable_liquidity_to_swap(
+        &mut self,
+        swap_application_id: ApplicationId,
+        swap_chain: ChainId,
+        config: StablePoolConfig,
+    ) -> Option<StableLeg> {
+        // A curve raised in the stablecoin already graduates into a stable pool
+        if self.state.curve_config.get().base_asset == BaseAsset::Fungible(config.asset) {
+            return None;
+        }
+        let (_, token_liquidity) =
+            graduation::split_supply(*self.state.current_supply.get(), config.token_share_bps)?;
+        // Without a rate the stable pool could not open at the native pool's price
+        let opening_price = config.opening_price(self.state.final_price()?)?;
+
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        let amount = self.fungible_balance(config.asset, application_owner);
+        if amount == Amount::ZERO {
+            return None;
+        }
+
+        let to = Account {
+            chain_id: swap_chain,
+            owner: AccountOwner::from(swap_application_id),
+        };
+        self.fungible_transfer(config.asset, application_owner, to, amount)
+            .expect("Failed to move stable liquidity to the swap application");
+
+        Some(StableLeg {
+            asset: config.asset,
+            decimals: config.decimals,
+            token_liquidity,
+            opening_price,
+            receipt: TransferReceipt {
+                from: self.application_account(),
+                to,
+                amount,
+                transferred_at: self.runtime.system_time(),
+            },
+        })
+    }
+
+    /// Take the signer's stablecoin towards the configured stable pool
+    fn execute_deposit_stable_liquidity(&mut self, amount: Amount) -> Result<(), TokenError> {
+        let config = self
+            .runtime
+            .application_parameters()
+            .stable_pool
+            .ok_or(TokenError::StablePoolNotConfigured)?;
+        if self.state.is_curve_closed() {
+            return Err(TokenError::StablePoolNotConfigured);
+        }
+        let signer = self.runtime.authenticated_signer().ok_or(TokenError::Unauthorized)?;
+
+        let application = self.application_account();
+        self.fungible_transfer(config.asset, signer, application, amount)?;
+        let deposited = *self.state.stable_deposit.get();
+        self.state.stable_deposit.set(deposited.saturating_add(amount));
+        Ok(())
+    }
+
+    /// Execute approve operation - allows spender to transfer tokens on behalf of owner
+    async fn execute_approve(
+        &mut self,
+        spender: Account,
+        amount: U256,
+        expires_at: Option<Timestamp>,
+    ) -> Result<(), TokenError> {
+        let owner = self.owner_account();
+
+        // Set allowance
+        self.state
+            .set_allowance(owner, spender, amount, expires_at)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Raise the caller's allowance for a spender
+    async fn execute_increase_allowance(
+        &mut self,
+        spender: Account,
+        added: U256,
+    ) -> Result<(), TokenError> {
+        if added == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        let owner = self.owner_account();
+        let now = self.runtime.system_time();
+
+        self.state
+            .increase_allowance(owner, spender, added, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Lower the caller's allowance for a spender
+    async fn execute_decrease_allowance(
+        &mut self,
+        spender: Account,
+        subtracted: U256,
+    ) -> Result<(), TokenError> {
+        if subtracted == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        let owner = self.owner_account();
+        let now = self.runtime.system_time();
+
+        let allowance = self.state.get_active_allowance(&owner, &spender, now).await;
+        if allowance < subtracted {
+            return Err(TokenError::InsufficientBalance {
+                have: allowance,
+                need: subtracted,
+            });
+        }
+        self.state
+            .reduce_allowance(owner, spender, subtracted, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Set an allowance from a permit signed by the owner
+    ///
+    /// The submitter can be anyone (typically the spender), so the owner needs
+    /// no transaction of their own before a DEX interaction.
+    async fn execute_permit_approve(
+        &mut self,
+        permit: Permit,
+        signature: AccountSignature,
+    ) -> Result<(), TokenError> {
+        if permit.token_id != *self.state.token_id.get() {
+            return Err(TokenError::InvalidPermit("signed for another token".to_string()));
+        }
+        if self.runtime.system_time() > permit.deadline {
+            return Err(TokenError::InvalidPermit("deadline has passed".to_string()));
+        }
+        if signature.owner() != permit.owner.owner {
+            return Err(TokenError::InvalidPermit("not signed by the owner".to_string()));
+        }
+        signature
+            .verify(&permit)
+            .map_err(|e| TokenError::InvalidPermit(e.to_string()))?;
+
+        self.state
+            .use_permit_nonce(&permit.owner, permit.nonce)
+            .await
+            .map_err(|e| TokenError::InvalidPermit(e.to_string()))?;
+        self.state
+            .set_allowance(permit.owner, permit.spender, permit.amount, permit.expires_at)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))
+    }
+
+    /// Execute revoke operation - deletes the caller's allowance for a spender
+    async fn execute_revoke(&mut self, spender: Account) -> Result<(), TokenError> {
+        let owner = self.owner_account();
+        let now = self.runtime.system_time();
+
+        self.state
+            .revoke_allowance(owner, spender, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Execute revokeAll operation - deletes every allowance the caller granted
+    async fn execute_revoke_all(&mut self) -> Result<(), TokenError> {
+        let owner = self.owner_account();
+        let now = self.runtime.system_time();
+
+        self.state
+            .revoke_all_allowances(owner, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Execute transferFrom operation - transfer tokens from owner to recipient using allowance
+    async fn execute_transfer_from(
+        &mut self,
+        from: Account,
+        to: Account,
+        amount: U256,
+        memo: Option<String>,
+    ) -> Result<(), TokenError> {
+        // Validate input
+        if amount == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        Self::validate_memo(&memo)?;
+        if !self.state.transfers_allowed() {
+            return Err(TokenError::TransfersDisabled);
+        }
+
+        // Get spender (caller)
+        let spender = self.owner_account();
+
+        // Approved operators move any amount without touching allowances
+        let is_operator = self.state.is_operator(&from, &spender).await;
+        if !is_operator {
+            // Fail fast on allowances the owner explicitly revoked
+            if self.state.is_allowance_revoked(&from, &spender).await {
+                return Err(TokenError::AllowanceRevoked);
+            }
+
+            // Check allowance (expired allowances count as zero)
+            let now = self.runtime.system_time();
+            let allowance = self.state.get_active_allowance(&from, &spender, now).await;
+            if allowance < amount {
+                return Err(TokenError::InsufficientBalance {
+                    have: allowance,
+                    need: amount,
+                });
+            }
+        }
+
+        // Check from account has enough balance
+        let from_balance = self.state.get_balance(&from).await;
+        if from_balance < amount {
+            return Err(TokenError::InsufficientBalance {
+                have: from_balance,
+                need: amount,
+            });
+        }
+
+        // Decrease allowance
+        if !is_operator {
+            self.state
+                .decrease_allowance(&from, &spender, amount)
+                .await
+                .map_err(|e| TokenError::StateError(e.to_string()))?;
+        }
+
+        // Transfer tokens from -> to
+        self.state
+            .set_balance(from.clone(), from_balance - amount)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        let to_balance = self.state.get_balance(&to).await;
+        self.state
+            .set_balance(to, to_balance + amount)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        let now = self.runtime.system_time();
+        self.state
+            .record_transfer(from, to, amount, memo, now)
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        // New holders may satisfy a pending minimum-holders requirement
+        if self.state.can_graduate() {
+            self.execute_graduation().await;
+        }
+
+        Ok(())
+    }
+
+    /// Execute metadata update - creator only, name and symbol stay fixed
+    async fn execute_update_metadata(
+        &mut self,
+        description: String,
+        image_url: Option<String>,
+        twitter: Option<String>,
+        telegram: Option<String>,
+        website: Option<String>,
+    ) -> Result<(), TokenError> {
+        self.authorize_direct_creator_action()?;
+        self.update_metadata(description, image_url, twitter, telegram, website).await
+    }
+
+    async fn update_metadata(
+        &mut self,
+        description: String,
+        image_url: Option<String>,
+        twitter: Option<String>,
+        telegram: Option<String>,
+        website: Option<String>,
+    ) -> Result<(), TokenError> {
+        let updated_at = self.runtime.system_time();
+        self.state
+            .update_metadata(description, image_url, twitter, telegram, website, updated_at)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Execute dust sweep - creator only, burns balances below a tiny threshold
+    async fn execute_sweep_dust(&mut self, threshold: U256) -> Result<(), TokenError> {
+        self.authorize_direct_creator_action()?;
+        self.sweep_dust(threshold).await
+    }
+
+    async fn sweep_dust(&mut self, threshold: U256) -> Result<(), TokenError> {
+        if threshold == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        let max = self.state.curve_config.get().max_supply / U256::from(MAX_DUST_THRESHOLD_DIVISOR);
+        if threshold > max {
+            return Err(TokenError::DustThresholdTooHigh { threshold, max });
+        }
+        // Before graduation the supply sets the curve price, which a burn
+        // would move without paying anything out of the reserve
+        if !*self.state.is_graduated.get() {
+            return Err(TokenError::DustSweepNotOpen);
+        }
+
+        // Balances held by this application and the swap back other holders
+        let mut exempt = vec![self.application_account()];
+        exempt.extend(self.swap_application_account());
+        let (accounts, swept) = self
+            .state
+            .sweep_dust(threshold, &exempt)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.logger.info(format!("Swept {} dust balances totalling {} tokens", accounts, swept));
+        if !swept.is_zero() {
+            self.notify_supply_changed();
+        }
+
+        self.assert_invariants().await;
+
+        Ok(())
+    }
+
+    /// Stake tokens once the token has graduated
+    async fn execute_stake(&mut self, amount: U256) -> Result<(), TokenError> {
+        if amount == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+        if !*self.state.is_graduated.get() {
+            return Err(TokenError::StakingNotOpen);
+        }
+
+        let caller = self.owner_account();
+        let balance = self.state.get_balance(&caller).await;
+        if balance < amount {
+            return Err(TokenError::InsufficientBalance { have: balance, need: amount });
+        }
+
+        let now = self.runtime.system_time();
+        self.state
+            .stake(caller, amount, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))
+    }
+
+    /// Unstake tokens back into the caller's balance
+    async fn execute_unstake(&mut self, amount: U256) -> Result<(), TokenError> {
+        if amount == U256::zero() {
+            return Err(TokenError::InvalidAmount);
+        }
+
+        let caller = self.owner_account();
+        let staked = self
+            .state
+            .staking
+            .staked(&caller)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if staked < amount {
+            return Err(TokenError::InsufficientBalance { have: staked, need: amount });
+        }
+
+        let now = self.runtime.system_time();
+        self.state
+            .unstake(caller, amount, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))
+    }
+
+    /// Pay the caller's accrued staking rewards
+    async fn execute_claim_rewards(&mut self) -> Result<(), TokenError> {
+        let caller = self.owner_account();
+        let now = self.runtime.system_time();
+        let amount = self
+            .state
+            .staking
+            .claim(caller, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+
+        self.transfer_from_application(caller, amount)
+    }
+
+    /// Co-creators of the token (the creator alone until a set is configured)
+    fn creator_set_members(&self) -> Vec<Account> {
+        let creator = self.state.creator.get().clone().expect("Creator not set");
+        self.state.creator_set.members_or(creator)
+    }
+
+    /// A creator payout split over the co-creators
+    fn creator_payouts(&self, amount: Amount) -> Vec<(Account, Amount)> {
+        let creator = self.state.creator.get().clone().expect("Creator not set");
+        self.state.creator_set.payouts_or(creator, amount)
+    }
+
+    /// Pay a creator share held by the application to the co-creators
+    fn pay_creators(&mut self, amount: Amount) -> Result<(), TokenError> {
+        for (member, share) in self.creator_payouts(amount) {
+            self.transfer_from_application(member, share)?;
+        }
+        Ok(())
+    }
+
+    /// Allow a creator-only operation sent directly, which needs a co-creator
+    /// caller and a threshold of one
+    fn authorize_direct_creator_action(&mut self) -> Result<(), TokenError> {
+        let caller = self.owner_account();
+        if !self.creator_set_members().contains(&caller) {
+            return Err(TokenError::Unauthorized);
+        }
+        let threshold = self.state.creator_set.threshold();
+        if threshold > 1 {
+            return Err(TokenError::ApprovalRequired { threshold });
+        }
+        Ok(())
+    }
 
-        // Calculate return using bonding curve
-        let return_amount = bonding_curve::calculate_sell_return(
-            current_supply,
-            amount,
-            curve_config.k,
-            curve_config.scale,
-        );
+    /// Let accounts buy during the whitelist phase - creator only
+    async fn execute_add_to_whitelist(&mut self, accounts: Vec<Account>) -> Result<(), TokenError> {
+        self.authorize_direct_creator_action()?;
+        self.add_to_whitelist(accounts).await
+    }
 
-        // Check slippage protection
-        if return_amount < min_return {
-            return Err(TokenError::SlippageExceededSell {
-                return_amount,
-                min_return,
+    async fn add_to_whitelist(&mut self, accounts: Vec<Account>) -> Result<(), TokenError> {
+        if accounts.len() > MAX_WHITELIST_BATCH {
+            return Err(TokenError::WhitelistBatchTooLarge {
+                count: accounts.len(),
+                max: MAX_WHITELIST_BATCH,
             });
         }
+        let now = self.runtime.system_time();
+        if !self.state.whitelist_active(now) {
+            return Err(TokenError::WhitelistClosed);
+        }
 
-        // Calculate creator fee on sell
-        let fee_amount = (return_amount * U256::from(curve_config.creator_fee_bps)) / U256::from(10000);
-        let net_return = return_amount.saturating_sub(fee_amount);
-        let creator = self.state.creator.get().clone().expect("Creator not set");
+        let added = self
+            .state
+            .add_to_whitelist(&accounts)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.logger.info(format!("Whitelisted {} new accounts", added));
+        Ok(())
+    }
 
-        // CRITICAL: Transfer from application
-        // 1. Transfer fee to creator
-        // 2. Transfer net return to seller
-        let native_fee = Self::u256_to_amount(fee_amount)?;
-        let native_net_return = Self::u256_to_amount(net_return)?;
-        let seller_account = self.owner_account();
+    /// Load a chunk of state exported from an earlier token version
+    async fn execute_import_state(&mut self, chunk: StateChunk) -> Result<(), TokenError> {
+        self.authorize_direct_creator_action()?;
+        self.import_state(chunk).await
+    }
 
-        // Transfer fee to creator
-        if native_fee > Amount::ZERO {
-            self.transfer_from_application(creator, native_fee)?;
+    /// Load a chunk the factory approved, collecting the curve reserve
+    /// behind any supply it adds from the signer
+    async fn import_state(&mut self, chunk: StateChunk) -> Result<(), TokenError> {
+        if *self.state.import_sealed.get() {
+            return Err(TokenError::ImportClosed);
+        }
+        let approved = self
+            .state
+            .take_import_approval(&chunk)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if !approved {
+            return Err(TokenError::ImportNotApproved(chunk.commitment()));
         }
 
-        // Transfer net return to seller
-        if native_net_return > Amount::ZERO {
-            self.transfer_from_application(seller_account, native_net_return)?;
+        let supply_before = *self.state.current_supply.get();
+        let applied = self
+            .state
+            .import_chunk(chunk)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        let supply_after = *self.state.current_supply.get();
+
+        // Imported holders can sell back to the curve, so their reserve must be here
+        if supply_after > supply_before {
+            let curve_config = self.state.curve_config.get().clone();
+            let reserve = bonding_curve::calculate_buy_cost(
+                supply_before,
+                supply_after - supply_before,
+                curve_config.k,
+                curve_config.scale,
+            )?;
+            let native_reserve = Self::u256_to_amount(reserve, curve_config.base_decimals)?;
+            let application = self.application_account();
+            self.collect_payment(application, native_reserve)?;
+            let total_raised = *self.state.total_raised.get();
+            self.state.total_raised.set(total_raised + reserve);
         }
+        self.logger.info(format!("Imported {} state entries", applied));
+        Ok(())
+    }
 
-        // Update state
-        let new_supply = current_supply - amount;
-        self.state.current_supply.set(new_supply);
-        let total_raised = *self.state.total_raised.get();
-        self.state.total_raised.set(total_raised.saturating_sub(return_amount));
+    /// Delete the oldest trades, keeping the latest `keep_last`
+    async fn execute_prune_trades(&mut self, keep_last: u64) -> Result<(), TokenError> {
+        self.authorize_direct_creator_action()?;
+        self.prune_trades(keep_last).await
+    }
 
-        // Update user balance
-        self.state
-            .set_balance(caller, current_balance - amount)
+    async fn prune_trades(&mut self, keep_last: u64) -> Result<(), TokenError> {
+        let now = self.runtime.system_time();
+        let pruned = self
+            .state
+            .prune_trades(keep_last, now)
             .await
-            .expect("Failed to update balance");
-
-        // Record trade
-        let trade_id = format!("{}-{}", self.runtime.system_time().micros(), self.state.trade_count.get());
-        let new_price = bonding_curve::calculate_current_price(new_supply, curve_config.k, curve_config.scale);
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if let Some((from, to)) = pruned {
+            self.logger.info(format!("Pruned trades {} to {}", from, to - 1));
+        }
+        Ok(())
+    }
 
-        let trade = Trade {
-            token_id: self.state.token_id.get().clone(),
-            trader: caller,
-            is_buy: false,
-            token_amount: amount,
-            currency_amount: return_amount,
-            price: new_price,
-            timestamp: self.runtime.system_time(),
-        };
+    /// Propose a creator action, running it at once if the proposer's
+    /// approval meets the threshold
+    async fn execute_propose_creator_action(
+        &mut self,
+        action: CreatorAction,
+    ) -> Result<(), TokenError> {
+        let caller = self.owner_account();
+        if !self.creator_set_members().contains(&caller) {
+            return Err(TokenError::Unauthorized);
+        }
+        if let CreatorAction::SetCreatorSet { members, threshold } = &action {
+            Self::validate_creator_set(members, *threshold)?;
+        }
 
-        self.state
-            .record_trade(trade_id, trade.clone())
-            .await
-            .expect("Failed to record trade");
+        let now = self.runtime.system_time();
+        let (proposal_id, proposal) = self
+            .state
+            .creator_set
+            .propose(caller, action, now)
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.logger.info(format!("Creator action {} proposed by {}", proposal_id, caller));
 
-        Ok(())
+        self.run_proposal_if_approved(proposal_id, proposal).await
     }
 
-    /// Execute graduation to DEX
-    async fn execute_graduation(&mut self) {
-        if *self.state.is_graduated.get() {
-            return; // Already graduated
+    /// Add the caller's approval to a pending creator action
+    async fn execute_approve_creator_action(
+        &mut self,
+        proposal_id: u64,
+    ) -> Result<(), TokenError> {
+        let caller = self.owner_account();
+        if !self.creator_set_members().contains(&caller) {
+            return Err(TokenError::Unauthorized);
         }
 
-        let token_id = self.state.token_id.get().clone();
-        let total_supply = *self.state.current_supply.get();
-        let total_raised = *self.state.total_raised.get();
+        let mut proposal = self
+            .state
+            .creator_set
+            .get(proposal_id)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?
+            .ok_or(TokenError::ProposalNotFound(proposal_id))?;
+        let invalid = |reason: &str| TokenError::InvalidProposal {
+            proposal_id,
+            reason: reason.to_string(),
+        };
+        if proposal.executed {
+            return Err(invalid("already executed"));
+        }
+        if !self.state.creator_set.is_pending(&proposal) {
+            return Err(invalid("the creator set changed since it was proposed"));
+        }
+        if proposal.approvals.contains(&caller) {
+            return Err(invalid("already approved by the caller"));
+        }
 
-        // Send graduation message to swap chain
-        // In a real implementation, this would be the actual swap application ID
-        let swap_chain = self.runtime.chain_id();
+        proposal.approvals.push(caller);
+        self.state
+            .creator_set
+            .save(proposal_id, proposal.clone())
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.logger.info(format!("Creator action {} approved by {}", proposal_id, caller));
 
-        self.runtime
-            .prepare_message(Message::GraduateToken {
-                token_id,
-                total_supply,
-                total_raised,
-            })
-            .with_tracking()
-            .send_to(swap_chain);
+        self.run_proposal_if_approved(proposal_id, proposal).await
     }
 
-    /// Execute approve operation - allows spender to transfer tokens on behalf of owner
-    async fn execute_approve(&mut self, spender: Account, amount: U256) -> Result<(), TokenError> {
-        let owner = self.owner_account();
+    async fn run_proposal_if_approved(
+        &mut self,
+        proposal_id: u64,
+        mut proposal: CreatorProposal,
+    ) -> Result<(), TokenError> {
+        let threshold = usize::from(self.state.creator_set.threshold());
+        if proposal.approvals.len() < threshold {
+            return Ok(());
+        }
 
-        // Set allowance
+        // Mark first: a set change run by this proposal lapses the others, not itself
+        proposal.executed = true;
         self.state
-            .set_allowance(owner, spender, amount)
-            .await
+            .creator_set
+            .save(proposal_id, proposal.clone())
             .map_err(|e| TokenError::StateError(e.to_string()))?;
 
+        match proposal.action {
+            CreatorAction::UpdateMetadata {
+                description,
+                image_url,
+                twitter,
+                telegram,
+                website,
+            } => {
+                self.update_metadata(description, image_url, twitter, telegram, website).await?;
+            }
+            CreatorAction::SweepDust { threshold } => self.sweep_dust(threshold).await?,
+            CreatorAction::AddToWhitelist { accounts } => self.add_to_whitelist(accounts).await?,
+            CreatorAction::ImportState { chunk } => self.import_state(chunk).await?,
+            CreatorAction::TakeSnapshot { reward } => self.take_snapshot(reward).await?,
+            CreatorAction::PruneTrades { keep_last } => self.prune_trades(keep_last).await?,
+            CreatorAction::SetCreatorSet { members, threshold } => {
+                self.state.creator_set.set(members, threshold);
+            }
+        }
+        self.logger.info(format!("Creator action {} executed", proposal_id));
         Ok(())
     }
 
-    /// Execute transferFrom operation - transfer tokens from owner to recipient using allowance
-    async fn execute_transfer_from(&mut self, from: Account, to: Account, amount: U256) -> Result<(), TokenError> {
-        // Validate input
+    /// Check a proposed creator set: 1 to `MAX_CO_CREATORS` distinct members
+    /// and a threshold they can meet
+    fn validate_creator_set(members: &[Account], threshold: u16) -> Result<(), TokenError> {
+        if members.is_empty() || members.len() > MAX_CO_CREATORS {
+            return Err(TokenError::InvalidCreatorSet(format!(
+                "needs 1 to {} members, got {}",
+                MAX_CO_CREATORS,
+                members.len()
+            )));
+        }
+        if members.iter().enumerate().any(|(i, member)| members[..i].contains(member)) {
+            return Err(TokenError::InvalidCreatorSet("duplicate member".to_string()));
+        }
+        if threshold == 0 || usize::from(threshold) > members.len() {
+            return Err(TokenError::InvalidCreatorSet(format!(
+                "threshold {} must be between 1 and {}",
+                threshold,
+                members.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Escrow tokens from the caller's balance until `unlock_at`
+    async fn execute_lock_tokens(
+        &mut self,
+        amount: U256,
+        unlock_at: Timestamp,
+    ) -> Result<(), TokenError> {
         if amount == U256::zero() {
             return Err(TokenError::InvalidAmount);
         }
+        let now = self.runtime.system_time();
+        if unlock_at <= now {
+            return Err(TokenError::InvalidUnlockTime { unlock_at, now });
+        }
 
-        // Get spender (caller)
-        let spender = self.owner_account();
+        let caller = self.owner_account();
+        let balance = self.state.get_balance(&caller).await;
+        if balance < amount {
+            return Err(TokenError::InsufficientBalance { have: balance, need: amount });
+        }
 
-        // Check allowance
-        let allowance = self.state.get_allowance(&from, &spender).await;
-        if allowance < amount {
-            return Err(TokenError::InsufficientBalance {
-                have: allowance,
-                need: amount,
-            });
+        self.state
+            .lock_tokens(caller, amount, unlock_at)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.logger.info(format!(
+            "{} locked {} tokens until {}",
+            caller,
+            amount,
+            unlock_at.micros()
+        ));
+        self.notify_supply_changed();
+        Ok(())
+    }
+
+    /// Return the caller's expired locks to its balance
+    async fn execute_claim_unlocked(&mut self) -> Result<(), TokenError> {
+        let caller = self.owner_account();
+        let now = self.runtime.system_time();
+        let released = self
+            .state
+            .claim_unlocked(caller, now)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if released.is_zero() {
+            return Err(TokenError::NothingUnlocked);
         }
+        self.notify_supply_changed();
+        Ok(())
+    }
 
-        // Check from account has enough balance
-        let from_balance = self.state.get_balance(&from).await;
-        if from_balance < amount {
-            return Err(TokenError::InsufficientBalance {
-                have: from_balance,
-                need: amount,
-            });
+    /// Commit current balances to a merkle root, funding `reward` for its holders
+    async fn execute_take_snapshot(&mut self, reward: Amount) -> Result<(), TokenError> {
+        self.authorize_direct_creator_action()?;
+        self.take_snapshot(reward).await
+    }
+
+    async fn take_snapshot(&mut self, reward: Amount) -> Result<(), TokenError> {
+        let snapshot_id = *self.state.snapshots.snapshot_count.get();
+        let (leaves, total_balance) = self
+            .state
+            .balance_leaves(snapshot_id)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        let root = merkle::root(&leaves).ok_or(TokenError::EmptySnapshot)?;
+
+        if reward > Amount::ZERO {
+            let application = self.application_account();
+            self.collect_payment(application, reward)?;
         }
 
-        // Decrease allowance
+        let snapshot = BalanceSnapshot {
+            root,
+            block_height: self.runtime.block_height(),
+            taken_at: self.runtime.system_time(),
+            holder_count: leaves.len() as u64,
+            total_balance,
+            reward_pool: reward,
+            claimed: Amount::ZERO,
+        };
         self.state
-            .decrease_allowance(&from, &spender, amount)
+            .snapshots
+            .record(snapshot)
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.logger.info(format!(
+            "Snapshot {} taken over {} holders with reward {}",
+            snapshot_id, leaves.len(), reward
+        ));
+
+        Ok(())
+    }
+
+    /// Pay the caller's pro-rata share of a snapshot's reward pool
+    async fn execute_claim_snapshot_reward(&mut self, proof: SnapshotProof) -> Result<(), TokenError> {
+        let caller = self.owner_account();
+        let snapshot_id = proof.snapshot_id;
+        let snapshot = self
+            .state
+            .snapshots
+            .snapshots
+            .get(&snapshot_id)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?
+            .ok_or(TokenError::SnapshotNotFound(snapshot_id))?;
+
+        let leaf = merkle::leaf_hash(snapshot_id, caller, proof.balance);
+        if !merkle::verify(leaf, &proof.siblings, snapshot.root) {
+            return Err(TokenError::InvalidSnapshotProof(snapshot_id));
+        }
+        let claimed = self
+            .state
+            .snapshots
+            .has_claimed(snapshot_id, caller)
             .await
             .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if claimed {
+            return Err(TokenError::SnapshotRewardClaimed(snapshot_id));
+        }
+
+        let pool = U256::from(u128::from(snapshot.reward_pool));
+        let share = wad::mul_div(pool, proof.balance, snapshot.total_balance)
+            .ok_or(TokenError::CurveOverflow)?;
+        let amount = Amount::from_attos(share.as_u128());
 
-        // Transfer tokens from -> to
         self.state
-            .set_balance(from.clone(), from_balance - amount)
+            .snapshots
+            .record_claim(snapshot_id, caller, amount)
             .await
             .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if amount > Amount::ZERO {
+            self.transfer_from_application(caller, amount)?;
+        }
 
-        let to_balance = self.state.get_balance(&to).await;
-        self.state
-            .set_balance(to, to_balance + amount)
+        Ok(())
+    }
+
+    /// Escrow a budget and schedule recurring buys paid from it, starting now
+    async fn execute_create_recurring_buy(
+        &mut self,
+        amount_per_interval: Amount,
+        interval: TimeDelta,
+        total_budget: Amount,
+    ) -> Result<(), TokenError> {
+        if amount_per_interval == Amount::ZERO {
+            return Err(TokenError::InvalidAmount);
+        }
+        if total_budget < amount_per_interval {
+            return Err(TokenError::InvalidRecurringBuy(
+                "total_budget must cover at least one interval".to_string(),
+            ));
+        }
+        if interval.as_micros() < MIN_RECURRING_INTERVAL_MICROS {
+            return Err(TokenError::InvalidRecurringBuy(format!(
+                "interval must be at least {} seconds",
+                MIN_RECURRING_INTERVAL_MICROS / 1_000_000
+            )));
+        }
+        self.ensure_bonding_curve()?;
+        self.ensure_curve_open()?;
+
+        let owner = self.owner_account();
+        self.ensure_may_buy(owner).await?;
+        let application = self.application_account();
+        self.collect_payment(application, total_budget)?;
+
+        let now = self.runtime.system_time();
+        let schedule_id = self
+            .state
+            .recurring_buys
+            .create(RecurringBuy {
+                owner,
+                amount_per_interval,
+                interval,
+                remaining_budget: total_budget,
+                next_run_at: now,
+                created_at: now,
+                executions: 0,
+                tokens_bought: U256::zero(),
+                spent: Amount::ZERO,
+                active: true,
+            })
             .await
             .map_err(|e| TokenError::StateError(e.to_string()))?;
+        self.logger.info(format!(
+            "Recurring buy {} created by {} with budget {}",
+            schedule_id, owner, total_budget
+        ));
 
         Ok(())
     }
 
-    /// Convert U256 to Amount (native token amount)
-    fn u256_to_amount(value: U256) -> Result<Amount, TokenError> {
-        // Convert U256 to u128 for Amount
-        if value > U256::from(u128::MAX) {
-            return Err(TokenError::AmountConversionError);
+    /// Stop a recurring buy and refund its unspent budget
+    async fn execute_cancel_recurring_buy(&mut self, schedule_id: u64) -> Result<(), TokenError> {
+        let caller = self.owner_account();
+        let mut schedule = self
+            .state
+            .recurring_buys
+            .get(schedule_id)
+            .await
+            .map_err(|e| TokenError::StateError(e.to_string()))?
+            .ok_or(TokenError::RecurringBuyNotFound(schedule_id))?;
+        if schedule.owner != caller {
+            return Err(TokenError::Unauthorized);
+        }
+        if !schedule.active {
+            return Err(TokenError::InvalidRecurringBuy(format!(
+                "recurring buy {} is no longer active",
+                schedule_id
+            )));
+        }
+
+        self.close_recurring_buy(schedule_id, &mut schedule, "cancelled by owner")
+    }
+
+    /// Execute the recurring buys that are due, a bounded number per block
+    async fn process_recurring_buys(&mut self) {
+        let now = self.runtime.system_time();
+        let due = self
+            .state
+            .recurring_buys
+            .due(now, MAX_RECURRING_BUYS_PER_BLOCK)
+            .await
+            .expect("Failed to load due recurring buys");
+
+        for (schedule_id, mut schedule) in due {
+            let result = self.execute_recurring_buy(schedule_id, &mut schedule, now).await;
+            let reason = match result {
+                Ok(()) if schedule.remaining_budget >= schedule.amount_per_interval => continue,
+                Ok(()) => "budget exhausted".to_string(),
+                Err(error) => error.to_string(),
+            };
+            self.close_recurring_buy(schedule_id, &mut schedule, &reason)
+                .expect("Failed to close recurring buy");
+        }
+    }
+
+    /// Spend one interval of a schedule's escrowed budget on a curve buy
+    async fn execute_recurring_buy(
+        &mut self,
+        schedule_id: u64,
+        schedule: &mut RecurringBuy,
+        now: Timestamp,
+    ) -> Result<(), TokenError> {
+        self.ensure_curve_open()?;
+        self.ensure_may_buy(schedule.owner).await?;
+
+        let current_supply = *self.state.current_supply.get();
+        let curve_config = self.state.curve_config.get().clone();
+        let spend = schedule.amount_per_interval.min(schedule.remaining_budget);
+        let budget = units::from_amount(spend, curve_config.base_decimals)
+            .map_err(|_| TokenError::AmountConversionError)?;
+        let affordable =
+            bonding_curve::calculate_tokens_for_cost(current_supply, budget, curve_config.k, curve_config.scale)?;
+        let amount = Self::capped_buy_amount(current_supply, affordable, curve_config.max_supply, true);
+        if amount == U256::zero() {
+            return Err(TokenError::InvalidRecurringBuy("budget buys no tokens".to_string()));
+        }
+        let cost = bonding_curve::calculate_buy_cost(current_supply, amount, curve_config.k, curve_config.scale)?;
+        let native_cost = Self::u256_to_amount(cost, curve_config.base_decimals)?;
+
+        // Settle first: a buy that fails leaves the whole budget to refund
+        let memo = Some(format!("recurring buy #{}", schedule_id));
+        self.fill_buy(schedule.owner, amount, cost, memo, None, BuyPayment::Escrow).await?;
+        self.state
+            .recurring_buys
+            .record_execution(schedule_id, schedule, native_cost, amount, now)
+            .map_err(|e| TokenError::StateError(e.to_string()))
+    }
+
+    /// Deactivate a schedule and return its unspent budget to the owner
+    fn close_recurring_buy(
+        &mut self,
+        schedule_id: u64,
+        schedule: &mut RecurringBuy,
+        reason: &str,
+    ) -> Result<(), TokenError> {
+        let refund = self
+            .state
+            .recurring_buys
+            .close(schedule_id, schedule)
+            .map_err(|e| TokenError::StateError(e.to_string()))?;
+        if refund > Amount::ZERO {
+            self.transfer_from_application(schedule.owner, refund)?;
+            self.state.record_refund_paid(refund);
         }
-        Ok(Amount::from_tokens(value.as_u128()))
+        self.logger.info(format!(
+            "Recurring buy {} closed ({}), refunded {}",
+            schedule_id, reason, refund
+        ));
+        Ok(())
     }
 
-    /// Convert Amount to U256
-    fn amount_to_u256(amount: Amount) -> U256 {
-        // Amount is internally u128 units (attos)
-        U256::from(u128::from(amount))
+    /// Portion of a creator fee that funds staking rewards
+    fn staking_share(fee: Amount) -> Amount {
+        let attos = u128::from(fee).saturating_mul(u128::from(STAKING_FEE_SHARE_BPS)) / 10_000;
+        Amount::from_attos(attos)
+    }
+
+    /// Convert curve base units to a native Amount
+    fn u256_to_amount(value: U256, base_decimals: u8) -> Result<Amount, TokenError> {
+        units::to_amount(value, base_decimals).map_err(|_| TokenError::AmountConversionError)
     }
 
     /// Get the owner account (authenticated signer on current chain)
@@ -517,13 +3201,99 @@ impl TokenContract {
         Ok(())
     }
 
-    /// Transfer native tokens from application to user (for sells/refunds)
+    /// Pay `amount` of the curve's base asset from the signer to `to`
+    ///
+    /// Payments into the application account are added to the reserve ledger.
+    fn collect_payment(&mut self, to: Account, amount: Amount) -> Result<(), TokenError> {
+        match self.state.curve_config.get().base_asset {
+            BaseAsset::Native => self.fund_account(to, amount)?,
+            BaseAsset::Fungible(application_id) => {
+                let signer = self.runtime.authenticated_signer().ok_or(TokenError::Unauthorized)?;
+                self.fungible_transfer(application_id, signer, to, amount)?;
+            }
+        }
+        if to == self.application_account() {
+            self.state.credit_reserves(amount);
+        }
+        Ok(())
+    }
+
+    /// Base asset the application can pay out for sells: its balance minus
+    /// what is held for stakers, snapshot claimants and recurring buys
+    fn available_liquidity(&mut self) -> Amount {
+        let reserved = self.state.reserved_base();
+        self.application_balance().saturating_sub(reserved)
+    }
+
+    /// Balance of the curve's base asset held by this application
+    fn application_balance(&mut self) -> Amount {
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        match self.state.curve_config.get().base_asset {
+            BaseAsset::Native => self.runtime.owner_balance(application_owner),
+            BaseAsset::Fungible(application_id) => {
+                self.fungible_balance(application_id, application_owner)
+            }
+        }
+    }
+
+    /// Query an owner's balance on a fungible token application
+    fn fungible_balance(&mut self, application_id: ApplicationId, owner: AccountOwner) -> Amount {
+        let call = FungibleOperation::Balance { owner };
+        match self
+            .runtime
+            .call_application(true, application_id.with_abi::<FungibleTokenAbi>(), &call)
+        {
+            FungibleResponse::Balance(balance) => balance,
+            response => panic!("Unexpected fungible response: {:?}", response),
+        }
+    }
+
+    /// Transfer fungible tokens owned by `owner` (the signer or this application)
+    fn fungible_transfer(
+        &mut self,
+        application_id: ApplicationId,
+        owner: AccountOwner,
+        to: Account,
+        amount: Amount,
+    ) -> Result<(), TokenError> {
+        if amount <= Amount::ZERO {
+            return Err(TokenError::InvalidAmount);
+        }
+
+        let balance = self.fungible_balance(application_id, owner);
+        if balance < amount {
+            return Err(TokenError::InsufficientBaseBalance {
+                asset: BaseAsset::Fungible(application_id),
+                have: balance,
+                need: amount,
+            });
+        }
+
+        let call = FungibleOperation::Transfer {
+            owner,
+            amount,
+            target_account: to,
+        };
+        self.runtime
+            .call_application(true, application_id.with_abi::<FungibleTokenAbi>(), &call);
+        Ok(())
+    }
+
+    /// Transfer base currency from application to user (for sells/refunds)
+    ///
+    /// Every payment out of the application is taken off the reserve ledger.
     fn transfer_from_application(&mut self, to: Account, amount: Amount) -> Result<(), TokenError> {
         if amount <= Amount::ZERO {
             return Ok(());
         }
 
         let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        if let BaseAsset::Fungible(application_id) = self.state.curve_config.get().base_asset {
+            self.fungible_transfer(application_id, application_owner, to, amount)?;
+            self.state.debit_reserves(amount);
+            return Ok(());
+        }
+
         let application_balance = self.runtime.owner_balance(application_owner);
 
         if application_balance < amount {
@@ -535,7 +3305,88 @@ impl TokenContract {
 
         // Transfer from application to user
         self.runtime.transfer(application_owner, to, amount);
+        self.state.debit_reserves(amount);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_launches_on_one_chain() {
+        assert!(TokenContract::check_token_created(None, "token-a").unwrap());
+        // The same launch delivered again is ignored
+        assert!(!TokenContract::check_token_created(Some("token-a"), "token-a").unwrap());
+        // A second launch bounces back to the factory
+        assert!(matches!(
+            TokenContract::check_token_created(Some("token-a"), "token-b"),
+            Err(TokenError::ChainHostsToken(hosted)) if hosted == "token-a"
+        ));
+    }
+
+    #[test]
+    fn test_capped_buy_amount() {
+        let max_supply = U256::from(1_000);
+        let current_supply = U256::from(900);
+
+        // Without capping, the request passes through (and is rejected later)
+        assert_eq!(
+            TokenContract::capped_buy_amount(current_supply, U256::from(500), max_supply, false),
+            U256::from(500)
+        );
+
+        // With capping, only the remaining supply is filled
+        assert_eq!(
+            TokenContract::capped_buy_amount(current_supply, U256::from(500), max_supply, true),
+            U256::from(100)
+        );
+
+        // Requests within the remaining supply are untouched
+        assert_eq!(
+            TokenContract::capped_buy_amount(current_supply, U256::from(50), max_supply, true),
+            U256::from(50)
+        );
+
+        // Nothing left to fill
+        assert_eq!(
+            TokenContract::capped_buy_amount(max_supply, U256::from(50), max_supply, true),
+            U256::zero()
+        );
+    }
+
+    #[test]
+    fn test_memo_length_cap() {
+        assert!(TokenContract::validate_memo(&None).is_ok());
+        assert!(TokenContract::validate_memo(&Some("gm".to_string())).is_ok());
+        assert!(TokenContract::validate_memo(&Some("x".repeat(MAX_MEMO_LENGTH))).is_ok());
+        assert!(matches!(
+            TokenContract::validate_memo(&Some("x".repeat(MAX_MEMO_LENGTH + 1))),
+            Err(TokenError::MemoTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_staking_share() {
+        assert_eq!(TokenContract::staking_share(Amount::from_attos(1_000)), Amount::from_attos(200));
+        assert_eq!(TokenContract::staking_share(Amount::from_attos(4)), Amount::ZERO);
+        assert_eq!(TokenContract::staking_share(Amount::ZERO), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_validate_creator_set() {
+        let member = |index| Account {
+            chain_id: ChainId::root(index),
+            owner: AccountOwner::CHAIN,
+        };
+        let members = vec![member(0), member(1), member(2)];
+
+        assert!(TokenContract::validate_creator_set(&members, 2).is_ok());
+        assert!(TokenContract::validate_creator_set(&members, 0).is_err());
+        assert!(TokenContract::validate_creator_set(&members, 4).is_err());
+        assert!(TokenContract::validate_creator_set(&[], 1).is_err());
+        assert!(TokenContract::validate_creator_set(&[member(0), member(0)], 1).is_err());
+    }
+}
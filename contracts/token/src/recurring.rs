@@ -0,0 +1,168 @@
+use fair_launch_abi::RecurringBuy;
+use linera_sdk::{
+    linera_base_types::{Account, Amount, Timestamp},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+use primitive_types::U256;
+
+/// Recurring (dollar-cost-averaging) buy schedules
+///
+/// Budgets are escrowed with the application when a schedule is created
+/// and spent as its buys come due. Due schedules are processed lazily by
+/// the contract at the end of each operation, a bounded number per block.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct RecurringBuyState {
+    /// Schedules: schedule_id → RecurringBuy
+    pub schedules: MapView<u64, RecurringBuy>,
+
+    /// Number of schedules created (next schedule_id)
+    pub schedule_count: RegisterView<u64>,
+
+    /// IDs of schedules still running
+    pub active: MapView<u64, ()>,
+
+    /// Running schedules in the order they come due: `queue_key` → ()
+    pub queue: MapView<[u8; 16], ()>,
+
+    /// Whether the schedules created before `queue` existed have been queued
+    pub queue_backfilled: RegisterView<bool>,
+
+    /// Schedule IDs per owner
+    pub by_owner: MapView<Account, Vec<u64>>,
+
+    /// Base currency escrowed for unspent budgets
+    pub escrow: RegisterView<Amount>,
+}
+
+impl RecurringBuyState {
+    /// Store a new schedule whose budget has been escrowed
+    pub async fn create(&mut self, schedule: RecurringBuy) -> Result<u64, ViewError> {
+        let schedule_id = *self.schedule_count.get();
+        let escrow = *self.escrow.get();
+        self.escrow.set(escrow.saturating_add(schedule.remaining_budget));
+
+        let mut owned = self.by_owner.get(&schedule.owner).await?.unwrap_or_default();
+        owned.push(schedule_id);
+        self.by_owner.insert(&schedule.owner, owned)?;
+        self.active.insert(&schedule_id, ())?;
+        self.queue.insert(&Self::queue_key(schedule.next_run_at, schedule_id), ())?;
+        self.schedules.insert(&schedule_id, schedule)?;
+        self.schedule_count.set(schedule_id + 1);
+        Ok(schedule_id)
+    }
+
+    /// Look up a schedule
+    pub async fn get(&self, schedule_id: u64) -> Result<Option<RecurringBuy>, ViewError> {
+        self.schedules.get(&schedule_id).await
+    }
+
+    /// Active schedules due at `now`, at most `limit`, the longest overdue first
+    ///
+    /// Reads only the front of the queue, however many schedules are running.
+    pub async fn due(
+        &mut self,
+        now: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(u64, RecurringBuy)>, ViewError> {
+        self.backfill_queue().await?;
+
+        let mut due_ids = Vec::new();
+        self.queue
+            .for_each_index_while(|key| {
+                let (next_run_at, schedule_id) = Self::parse_queue_key(&key);
+                if due_ids.len() >= limit || next_run_at > now {
+                    return Ok(false);
+                }
+                due_ids.push(schedule_id);
+                Ok(true)
+            })
+            .await?;
+
+        let mut due = Vec::with_capacity(due_ids.len());
+        for schedule_id in due_ids {
+            if let Some(schedule) = self.schedules.get(&schedule_id).await? {
+                due.push((schedule_id, schedule));
+            }
+        }
+        Ok(due)
+    }
+
+    /// Queue the schedules created before the queue existed, once
+    async fn backfill_queue(&mut self) -> Result<(), ViewError> {
+        if *self.queue_backfilled.get() {
+            return Ok(());
+        }
+        for schedule_id in self.active.indices().await? {
+            if let Some(schedule) = self.schedules.get(&schedule_id).await? {
+                self.queue.insert(&Self::queue_key(schedule.next_run_at, schedule_id), ())?;
+            }
+        }
+        self.queue_backfilled.set(true);
+        Ok(())
+    }
+
+    /// Big-endian (next run, schedule ID), so key order is due order
+    fn queue_key(next_run_at: Timestamp, schedule_id: u64) -> [u8; 16] {
+        let mut key = [0; 16];
+        key[..8].copy_from_slice(&next_run_at.micros().to_be_bytes());
+        key[8..].copy_from_slice(&schedule_id.to_be_bytes());
+        key
+    }
+
+    fn parse_queue_key(key: &[u8; 16]) -> (Timestamp, u64) {
+        let mut micros = [0; 8];
+        let mut schedule_id = [0; 8];
+        micros.copy_from_slice(&key[..8]);
+        schedule_id.copy_from_slice(&key[8..]);
+        (Timestamp::from(u64::from_be_bytes(micros)), u64::from_be_bytes(schedule_id))
+    }
+
+    /// Record a buy paid from the schedule's escrow
+    pub fn record_execution(
+        &mut self,
+        schedule_id: u64,
+        schedule: &mut RecurringBuy,
+        spent: Amount,
+        tokens: U256,
+        now: Timestamp,
+    ) -> Result<(), ViewError> {
+        schedule.remaining_budget = schedule.remaining_budget.saturating_sub(spent);
+        schedule.spent = schedule.spent.saturating_add(spent);
+        schedule.tokens_bought += tokens;
+        schedule.executions += 1;
+        self.queue.remove(&Self::queue_key(schedule.next_run_at, schedule_id))?;
+        schedule.next_run_at = now.saturating_add(schedule.interval);
+        self.queue.insert(&Self::queue_key(schedule.next_run_at, schedule_id), ())?;
+        let escrow = *self.escrow.get();
+        self.escrow.set(escrow.saturating_sub(spent));
+        self.schedules.insert(&schedule_id, schedule.clone())
+    }
+
+    /// Deactivate a schedule, releasing its unspent budget from escrow
+    ///
+    /// Returns the amount to refund to the owner.
+    pub fn close(&mut self, schedule_id: u64, schedule: &mut RecurringBuy) -> Result<Amount, ViewError> {
+        let refund = schedule.remaining_budget;
+        schedule.remaining_budget = Amount::ZERO;
+        schedule.active = false;
+        let escrow = *self.escrow.get();
+        self.escrow.set(escrow.saturating_sub(refund));
+        self.active.remove(&schedule_id)?;
+        self.queue.remove(&Self::queue_key(schedule.next_run_at, schedule_id))?;
+        self.schedules.insert(&schedule_id, schedule.clone())?;
+        Ok(refund)
+    }
+
+    /// Schedules created by an account
+    pub async fn owned_by(&self, owner: &Account) -> Result<Vec<(u64, RecurringBuy)>, ViewError> {
+        let mut schedules = Vec::new();
+        for schedule_id in self.by_owner.get(owner).await?.unwrap_or_default() {
+            if let Some(schedule) = self.schedules.get(&schedule_id).await? {
+                schedules.push((schedule_id, schedule));
+            }
+        }
+        Ok(schedules)
+    }
+}
@@ -0,0 +1,101 @@
+use fair_launch_abi::SaleCommitment;
+use linera_sdk::{
+    linera_base_types::{Account, Timestamp},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+use primitive_types::U256;
+
+/// Commitments and settlement progress of a fixed-price sale launch
+///
+/// Commitments are escrowed with the application during the window. Once it
+/// closes they are allocated in commitment order, a bounded batch per
+/// `SettleSale`, so settlement never has to scan every buyer in one block.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct SaleState {
+    /// Commitment per buyer
+    pub commitments: MapView<Account, SaleCommitment>,
+
+    /// Buyers in the order they first committed: position → buyer
+    pub buyers: MapView<u64, Account>,
+
+    /// Number of buyers
+    pub buyer_count: RegisterView<u64>,
+
+    /// Base currency committed by all buyers
+    pub total_committed: RegisterView<U256>,
+
+    /// Buyers allocated so far (settlement cursor into `buyers`)
+    pub settled_count: RegisterView<u64>,
+
+    /// When the last commitment was allocated
+    pub settled_at: RegisterView<Option<Timestamp>>,
+}
+
+impl SaleState {
+    /// Add to a buyer's commitment
+    pub async fn commit(&mut self, buyer: Account, amount: U256) -> Result<(), ViewError> {
+        let mut commitment = match self.commitments.get(&buyer).await? {
+            Some(commitment) => commitment,
+            None => {
+                let position = *self.buyer_count.get();
+                self.buyers.insert(&position, buyer)?;
+                self.buyer_count.set(position + 1);
+                SaleCommitment::default()
+            }
+        };
+        commitment.committed = commitment.committed.saturating_add(amount);
+        self.commitments.insert(&buyer, commitment)?;
+
+        let total = *self.total_committed.get();
+        self.total_committed.set(total.saturating_add(amount));
+        Ok(())
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.settled_at.get().is_some()
+    }
+
+    /// Up to `limit` buyers not yet allocated, advancing the cursor past them
+    pub async fn next_to_settle(
+        &mut self,
+        limit: u64,
+    ) -> Result<Vec<(Account, SaleCommitment)>, ViewError> {
+        let start = *self.settled_count.get();
+        let end = (*self.buyer_count.get()).min(start.saturating_add(limit));
+        let mut batch = Vec::new();
+        for position in start..end {
+            if let Some(buyer) = self.buyers.get(&position).await? {
+                if let Some(commitment) = self.commitments.get(&buyer).await? {
+                    batch.push((buyer, commitment));
+                }
+            }
+        }
+        self.settled_count.set(end);
+        Ok(batch)
+    }
+
+    /// Whether every buyer has been allocated
+    pub fn all_allocated(&self) -> bool {
+        *self.settled_count.get() >= *self.buyer_count.get()
+    }
+
+    /// Store a buyer's allocation
+    pub fn record_allocation(
+        &mut self,
+        buyer: Account,
+        mut commitment: SaleCommitment,
+        tokens: U256,
+        refund: U256,
+    ) -> Result<(), ViewError> {
+        commitment.tokens = tokens;
+        commitment.refund = refund;
+        commitment.settled = true;
+        self.commitments.insert(&buyer, commitment)
+    }
+
+    pub fn mark_settled(&mut self, at: Timestamp) {
+        self.settled_at.set(Some(at));
+    }
+}
@@ -1,22 +1,36 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod auction;
+mod cocreators;
+mod recurring;
+mod sale;
+mod snapshot;
+mod staking;
 mod state;
 
-use async_graphql::{EmptySubscription, Object, Schema, SimpleObject};
-use fair_launch_abi::{bonding_curve, TokenAbi};
+use async_graphql::{
+    connection::query, ComplexObject, Context, EmptySubscription, Object, Schema, SimpleObject,
+};
+use fair_launch_abi::{
+    bonding_curve, dutch_auction, fee_tiers, fixed_price_sale, graduation, ipfs,
+    pagination::{self, Page},
+    price::PRICE_DECIMALS,
+    tax, units, wad, BaseAsset, StateChunk, TokenAbi, TokenOperation, TokenParameters,
+    RAISE_MILESTONES_BPS,
+};
 use linera_sdk::{
     abi::WithServiceAbi,
-    linera_base_types::Account,
+    linera_base_types::{Account, AccountOwner, Amount, Timestamp},
     views::View,
     Service, ServiceRuntime,
 };
 use primitive_types::U256;
 use std::sync::Arc;
 
-use crate::state::TokenState;
+use crate::state::{TokenState, HOLDER_BUCKET_LABELS};
 
 pub struct TokenService {
-    state: Arc<TokenState>,
+    runtime: Arc<ServiceRuntime<Self>>,
 }
 
 linera_sdk::service!(TokenService);
@@ -26,57 +40,512 @@ impl WithServiceAbi for TokenService {
 }
 
 impl Service for TokenService {
-    type Parameters = ();
+    type Parameters = TokenParameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
-        let state = TokenState::load(runtime.root_view_storage_context())
-            .await
-            .expect("Failed to load state");
         TokenService {
-            state: Arc::new(state),
+            runtime: Arc::new(runtime),
         }
     }
 
     async fn handle_query(&self, request: async_graphql::Request) -> async_graphql::Response {
+        // Load per query so a long-lived instance answers from the latest block
+        let state = Arc::new(
+            TokenState::load(self.runtime.root_view_storage_context())
+                .await
+                .expect("Failed to load state"),
+        );
         let schema = Schema::build(
             QueryRoot {
-                state: self.state.clone(),
+                state: state.clone(),
+                runtime: self.runtime.clone(),
+            },
+            MutationRoot {
+                runtime: self.runtime.clone(),
             },
-            EmptyMutation,
             EmptySubscription,
         )
+        .data(self.runtime.clone())
         .finish();
 
-        schema.execute(request).await
+        let mut response = schema.execute(request).await;
+        response.extensions.insert(
+            fair_launch_abi::STATE_VERSION_EXTENSION.to_string(),
+            async_graphql::Value::from(*state.state_version.get()),
+        );
+        response
     }
 }
 
 pub struct QueryRoot {
     state: Arc<TokenState>,
+    runtime: Arc<ServiceRuntime<TokenService>>,
 }
 
 #[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct TokenInfo {
     pub token_id: String,
     pub creator: String, // ChainId serialized as String for GraphQL
     pub name: String,
     pub symbol: String,
     pub description: String,
+    /// Image link as submitted
+    pub image_url: Option<String>,
     pub current_supply: String,
     pub total_raised: String,
+    /// Price as an integer scaled by 10^price_decimals
     pub current_price: String,
+    pub price_decimals: u8,
     pub holder_count: u64,
     pub trade_count: u64,
+    /// Approximate number of distinct accounts that traded on the curve
+    pub unique_traders: u64,
+    /// Base currency traded over the last 24 hours
+    pub volume_24h: String,
+    /// Base currency traded over the current day and the six before it
+    pub volume_7d: String,
     pub is_graduated: bool,
     pub progress_percentage: f64,
+    /// Graduation conditions not yet satisfied (empty when ready to graduate)
+    pub unmet_graduation_conditions: Vec<String>,
+    /// Whether curve trading is closed (graduation started or completed)
+    pub is_curve_closed: bool,
+    /// DEX pool to trade on once the curve is closed
+    pub dex_pool_id: Option<String>,
+    /// Stablecoin-quoted DEX pool, if one was seeded at graduation
+    pub stable_pool_id: Option<String>,
+    /// Whether the factory has flagged this token
+    pub is_flagged: bool,
+    pub moderation_reason: Option<String>,
+    /// Shares of the target raise, with when each was first reached
+    pub milestones: Vec<MilestoneInfo>,
+}
+
+#[ComplexObject]
+impl TokenInfo {
+    /// Image link rewritten through the configured IPFS gateway (None if it
+    /// is not served)
+    ///
+    /// Resolved only when selected, as IPFS links cost a gateway request each.
+    async fn resolved_image_url(&self, ctx: &Context<'_>) -> Option<String> {
+        let runtime = ctx.data::<Arc<ServiceRuntime<TokenService>>>().expect("Runtime not found");
+        let gateway = runtime.application_parameters().ipfs_gateway;
+        let url = self.image_url.as_deref()?;
+        ipfs::resolve(runtime, url, gateway.as_deref())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct MilestoneInfo {
+    /// Share of the target raise, in basis points
+    pub milestone_bps: u16,
+    /// First crossing time in microseconds, None while not reached
+    pub reached_at: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct GraduationPreview {
+    pub pool_id: String,
+    pub token_reserve: String,
+    pub base_reserve: String,
+    /// Opening price as base per token, scaled by 10^price_decimals
+    pub opening_price: String,
+    pub price_decimals: u8,
+    pub tvl: String,
+    /// Unsold supply burned by `burn_unsold_on_graduation`, plus the tokens
+    /// left out of the pool so it opens at the final launch price
+    pub burned_remainder: String,
+    pub creator_fee_bps: u16,
+    pub is_locked: bool,
+    /// Stablecoin-quoted pool seeded alongside, if configured
+    pub stable_pool_id: Option<String>,
+    /// Tokens moved into the stable pool instead of the native one, matched
+    /// to the stablecoin deposited so far
+    pub stable_token_reserve: Option<String>,
+    /// Graduation conditions not yet satisfied (empty when ready to graduate)
+    pub unmet_graduation_conditions: Vec<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ApprovalInfo {
+    pub owner: String,
+    pub spender: String,
+    pub amount: String,
+    pub expires_at: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct MetadataRevision {
+    pub revision: u64,
+    pub timestamp: String,
+    pub metadata: fair_launch_abi::TokenMetadata,
+}
+
+#[derive(SimpleObject)]
+pub struct StakingStats {
+    /// Total tokens staked
+    pub total_staked: String,
+    /// Base currency held for stakers, in attos
+    pub reward_reserve: String,
+    /// Total rewards funded from trading fees, in attos
+    pub total_funded: String,
+    /// Total rewards claimed, in attos
+    pub total_claimed: String,
+    /// Funded rewards still vesting to stakers, in attos
+    pub pending_emission: String,
+    /// Tokens staked by the requested account
+    pub staked: Option<String>,
+    /// Rewards claimable by the requested account, in attos
+    pub claimable: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct AuctionInfo {
+    /// Prices as integers scaled by 10^price_decimals
+    pub start_price: String,
+    pub floor_price: String,
+    /// Price a bid placed now pays (the clearing price once settled)
+    pub current_price: String,
+    pub price_decimals: u8,
+    /// Auction window in microseconds
+    pub starts_at: String,
+    pub ends_at: String,
+    /// Tokens offered and sold so far
+    pub supply: String,
+    pub sold: String,
+    pub bidder_count: u64,
+    pub is_settled: bool,
+    pub clearing_price: Option<String>,
+    /// Tokens the requested account bought
+    pub bid_amount: Option<String>,
+    /// Refund the requested account can claim after settlement
+    pub refund_due: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct SaleInfo {
+    /// Price as an integer scaled by 10^price_decimals
+    pub price: String,
+    pub price_decimals: u8,
+    /// Sale window in microseconds
+    pub starts_at: String,
+    pub ends_at: String,
+    /// Tokens offered
+    pub supply: String,
+    pub total_committed: String,
+    /// Commitments beyond this are pro-rated
+    pub supply_cost: String,
+    pub buyer_count: u64,
+    /// Buyers allocated so far
+    pub settled_count: u64,
+    pub is_settled: bool,
+    /// Base currency the requested account committed
+    pub committed: Option<String>,
+    /// Tokens allocated to the requested account (None until allocated)
+    pub allocated: Option<String>,
+    pub refund: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct WhitelistStatus {
+    /// End of the whitelist phase in microseconds (None = no whitelist phase)
+    pub ends_at: Option<String>,
+    /// Whether only whitelisted accounts may buy right now
+    pub is_active: bool,
+    pub whitelisted_count: u64,
+    /// Whether the requested account is whitelisted
+    pub is_whitelisted: Option<bool>,
+    /// Whether the requested account may buy right now
+    pub can_buy: Option<bool>,
+}
+
+#[derive(SimpleObject)]
+pub struct DustSweepStats {
+    /// Total tokens burned by dust sweeps
+    pub total_swept: String,
+    /// Total balances removed
+    pub accounts_swept: u64,
+    /// Number of sweeps executed
+    pub sweep_count: u64,
+}
+
+/// Last price published to `price_feed` subscribers
+#[derive(SimpleObject)]
+pub struct PublishedPrice {
+    /// Scaled by 10^price_decimals
+    pub price: String,
+    pub price_decimals: u8,
+    /// Trade count the price was taken at
+    pub trade_count: u64,
+    /// In microseconds
+    pub published_at: String,
+    /// Trades between updates (0: on price moves only)
+    pub every_trades: u64,
+    /// Price move that publishes early, in basis points (0: disabled)
+    pub move_bps: u16,
+}
+
+#[derive(SimpleObject)]
+pub struct LaunchFailureInfo {
+    pub failed_at: String,
+    /// Base currency shared pro rata among the holders at failure
+    pub refund_pool: String,
+    pub supply: String,
+    pub redeemed: String,
+}
+
+#[derive(SimpleObject)]
+pub struct BuybackBurnStats {
+    /// Total tokens bought back from the DEX pools and burned
+    pub total_burned: String,
+    /// Number of burns received from the swap chain
+    pub burn_count: u64,
+    /// Supply after the buyback burns, as reported to the swap chain
+    pub supply_after_burns: String,
+}
+
+#[derive(SimpleObject)]
+pub struct ReconciliationReport {
+    pub current_supply: String,
+    pub balances_total: String,
+    pub trade_count: u64,
+    pub trades_recorded: u64,
+    pub native_balance: String,
+    pub sell_out_value: Option<String>,
+    /// Human-readable description of each invariant that does not hold
+    pub violations: Vec<String>,
+    pub is_consistent: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct MaxSellable {
+    /// Largest sell the curve can pay out right now
+    pub token_amount: String,
+    /// Return for that sell, creator fee included, in curve base units
+    pub currency_amount: String,
+    /// Base asset available for sell payouts
+    pub available_liquidity: String,
+}
+
+#[derive(SimpleObject)]
+pub struct ReservesReport {
+    /// Base currency held by the application according to its ledger
+    pub native_reserves: String,
+    pub fees_paid_out: String,
+    pub refunds_issued: String,
+    /// Held for stakers, snapshot claimants and recurring buy budgets,
+    /// plus the share burned at graduation and the burned snipe tax
+    pub reserved_for_claims: String,
+    /// Share of the raise burned at graduation
+    pub burned_at_graduation: String,
+    /// Snipe tax charged on early buys, paid out or burned
+    pub snipe_tax_collected: String,
+    /// Snipe tax kept by the application as a burn
+    pub snipe_tax_burned: String,
+    /// Share of the raise paid to the creator at graduation
+    pub creator_graduation_payout: String,
+    /// Payout if the whole supply were sold back (None once the curve is closed)
+    pub sell_out_value: Option<String>,
+    /// Actual native balance of the application (None for fungible base assets)
+    pub application_balance: Option<String>,
+    /// Whether the ledger covers the sell-out value plus the reserved claims
+    pub is_fully_backed: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct CreatorSetInfo {
+    pub members: Vec<String>,
+    /// Approvals a creator action needs
+    pub threshold: u16,
+}
+
+#[derive(SimpleObject)]
+pub struct CreatorProposalInfo {
+    pub proposal_id: u64,
+    /// The proposed `CreatorAction`, as JSON
+    pub action: String,
+    pub proposer: String,
+    pub approvals: Vec<String>,
+    pub created_at: String,
+    /// Whether it can still gather approvals
+    pub is_pending: bool,
+    pub executed: bool,
+}
+
+/// One page of a `stateExport`
+#[derive(SimpleObject)]
+pub struct StateExportPage {
+    pub section: String,
+    pub offset: u64,
+    /// Offset of the next page (None once the section is exhausted)
+    pub next_offset: Option<u64>,
+    pub entries: u64,
+    /// The page as a JSON `StateChunk`, accepted as is by `ImportState`
+    pub chunk: String,
+    /// Commitment of the page, for the factory admin to approve its import
+    pub commitment: String,
+}
+
+/// A trade with its position in the history
+#[derive(SimpleObject)]
+pub struct SequencedTrade {
+    pub sequence: u64,
+    pub trader: String,
+    /// Trade time in microseconds
+    pub timestamp: String,
+    pub trade: fair_launch_abi::TradeGQL,
+}
+
+/// Part of a sell matched against one bought lot
+#[derive(SimpleObject)]
+pub struct TaxDisposal {
+    /// When the lot was bought, in microseconds (None if not bought on the curve)
+    pub acquired_at: Option<String>,
+    /// When it was sold, in microseconds
+    pub disposed_at: String,
+    pub amount: String,
+    pub cost_basis: String,
+    pub proceeds: String,
+    /// Proceeds minus cost basis, negative for a loss
+    pub gain: String,
+}
+
+impl From<&tax::Disposal> for TaxDisposal {
+    fn from(disposal: &tax::Disposal) -> Self {
+        Self {
+            acquired_at: disposal.acquired_at.map(|at| at.micros().to_string()),
+            disposed_at: disposal.disposed_at.micros().to_string(),
+            amount: disposal.amount.to_string(),
+            cost_basis: disposal.cost_basis.to_string(),
+            proceeds: disposal.proceeds.to_string(),
+            gain: signed(disposal.gain_magnitude(), disposal.is_loss()),
+        }
+    }
+}
+
+/// FIFO cost basis and realized gains of one trader's curve sells
+#[derive(SimpleObject)]
+pub struct TaxReport {
+    pub account: String,
+    /// Period start and end in microseconds (inclusive)
+    pub from: String,
+    pub to: String,
+    pub disposals: Vec<TaxDisposal>,
+    pub total_proceeds: String,
+    pub total_cost_basis: String,
+    /// Realized gain over the period, negative for a net loss
+    pub net_gain: String,
+    /// Tokens bought on the curve and still held at the end of the period
+    pub open_amount: String,
+    pub open_cost_basis: String,
+    /// The disposals as CSV with a header row, when requested
+    pub csv: Option<String>,
+    /// Set when the account has more trades than one report walks
+    /// (`MAX_TRADES_PER_QUERY`); the newest are left out until older trades
+    /// are pruned, which carries their lots forward, and `to` is moved back
+    /// to just before the first of them
+    pub truncated: bool,
+}
+
+/// Format a magnitude with a leading '-' when negative
+fn signed(magnitude: U256, negative: bool) -> String {
+    if negative && !magnitude.is_zero() {
+        format!("-{}", magnitude)
+    } else {
+        magnitude.to_string()
+    }
+}
+
+/// Tokens escrowed until `unlock_at`
+#[derive(SimpleObject)]
+pub struct TokenLock {
+    pub owner: String,
+    pub amount: String,
+    /// Unlock time in microseconds
+    pub unlock_at: String,
+    /// Whether the owner can claim it back now
+    pub is_unlocked: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct HolderBucket {
+    /// Share of max supply held, e.g. "0.1-1%"
+    pub label: String,
+    pub holders: u64,
+}
+
+/// A trader's standing in the launch's volume fee tiers
+#[derive(SimpleObject)]
+pub struct TraderFeeTier {
+    /// Base currency traded on the curve over the last 30 days, in base units
+    pub volume_30d: String,
+    /// Share of the creator fee waived, in basis points
+    pub discount_bps: u16,
+    /// Creator fee rate the trader pays, in basis points
+    pub fee_bps: u16,
+}
+
+/// An account whose trades were flagged as wash trading
+#[derive(SimpleObject)]
+pub struct SuspiciousAccount {
+    pub account: String,
+    pub flagged_trades: u64,
+    /// Currency volume of the flagged trades, in base units
+    pub flagged_volume: String,
+    /// Buy/sell switches in the account's current window
+    pub alternations: u64,
+    /// Latest flagged trade in microseconds
+    pub last_flagged_at: Option<String>,
+}
+
+/// One sample of the theoretical bonding curve
+#[derive(SimpleObject)]
+pub struct CurvePoint {
+    pub supply: String,
+    /// Marginal price at `supply`, scaled by 10^PRICE_DECIMALS
+    pub price: String,
+    /// Cost of buying from zero up to `supply`, in base units
+    pub cumulative_cost: String,
 }
 
 #[derive(SimpleObject)]
 pub struct BuySellQuote {
     pub token_amount: String,
+    /// Curve integral for the trade, in base units
     pub currency_amount: String,
+    /// Creator fee, in base units: part of a buy's `currency_amount`, taken
+    /// out of a sell's
+    pub fee_amount: String,
+    /// What the wallet is charged for a buy (`currency_amount` plus the snipe
+    /// tax) or receives for a sell (`currency_amount` minus the fee)
+    pub total_with_fee: String,
+    /// `total_with_fee` as the base asset amount actually transferred
+    /// (None if not representable)
+    pub native_amount: Option<String>,
     pub price_impact: f64,
+    /// Price after the trade, scaled by 10^price_decimals
     pub new_price: String,
+    pub price_decimals: u8,
+    /// Supply the quote was taken at, to pass as `expected_supply` for exact execution
+    pub quoted_supply: String,
+    /// Early-buy surcharge on top of `currency_amount` (always 0 for sells);
+    /// a buy's `max_cost` must cover both
+    pub snipe_tax: String,
+    pub snipe_tax_bps: u16,
+}
+
+impl QueryRoot {
+    /// Creator fee rate a quote charges: the tier `account_json` reached, or
+    /// the flat rate without an account
+    async fn quoted_fee_bps(&self, account_json: Option<String>) -> Option<u16> {
+        let Some(account_json) = account_json else {
+            return Some(self.state.curve_config.get().creator_fee_bps);
+        };
+        let account: Account = serde_json::from_str(&account_json).ok()?;
+        let now = self.runtime.system_time();
+        self.state.creator_fee_bps_for(&account, now).await.ok()
+    }
 }
 
 #[Object]
@@ -89,12 +558,13 @@ impl QueryRoot {
         let current_supply = *self.state.current_supply.get();
         let total_raised = *self.state.total_raised.get();
         let curve_config = self.state.curve_config.get().clone();
+        let now = self.runtime.system_time();
 
         let current_price = bonding_curve::calculate_current_price(
             current_supply,
             curve_config.k,
             curve_config.scale,
-        );
+        ).unwrap_or_default();
 
         let progress_percentage = if curve_config.max_supply > U256::zero() {
             let progress = (current_supply * U256::from(10000)) / curve_config.max_supply;
@@ -103,24 +573,181 @@ impl QueryRoot {
             0.0
         };
 
+        let mut milestones = Vec::new();
+        for milestone_bps in RAISE_MILESTONES_BPS {
+            let reached_at = self.state.milestones.get(&milestone_bps).await.unwrap_or_default();
+            milestones.push(MilestoneInfo {
+                milestone_bps,
+                reached_at: reached_at.map(|t| t.micros().to_string()),
+            });
+        }
+
         TokenInfo {
             token_id,
             creator: creator.to_string(),
             name: metadata.name,
             symbol: metadata.symbol,
             description: metadata.description,
+            image_url: metadata.image_url,
             current_supply: current_supply.to_string(),
             total_raised: total_raised.to_string(),
-            current_price: current_price.to_string(),
+            current_price: current_price.raw().to_string(),
+            price_decimals: PRICE_DECIMALS,
             holder_count: *self.state.holder_count.get(),
             trade_count: *self.state.trade_count.get(),
+            unique_traders: self.state.trader_sketch.get().estimate(),
+            volume_24h: self.state.volume_24h(now).await.unwrap_or_default().to_string(),
+            volume_7d: self.state.volume_7d(now).await.unwrap_or_default().to_string(),
             is_graduated: *self.state.is_graduated.get(),
             progress_percentage,
+            unmet_graduation_conditions: self.state.unmet_graduation_conditions(),
+            is_curve_closed: self.state.is_curve_closed(),
+            dex_pool_id: self.state.dex_pool_id.get().clone(),
+            stable_pool_id: self.state.stable_pool_id.get().clone(),
+            is_flagged: self.state.moderation_status.get().is_flagged(),
+            moderation_reason: self.state.moderation_status.get().reason().map(str::to_string),
+            milestones,
+        }
+    }
+
+    /// Preview the pool that graduation would create right now
+    async fn graduation_preview(&self) -> Option<GraduationPreview> {
+        let token_id = self.state.token_id.get().clone();
+        let current_supply = *self.state.current_supply.get();
+        let total_raised = *self.state.total_raised.get();
+        let curve_config = self.state.curve_config.get().clone();
+
+        // A funded stable pool takes the tokens its deposit matches at the final
+        // price off the native pool
+        let final_price = self.state.final_price();
+        let stable_deposit = *self.state.stable_deposit.get();
+        let stable_split = self
+            .runtime
+            .application_parameters()
+            .stable_pool
+            .filter(|config| curve_config.base_asset != BaseAsset::Fungible(config.asset))
+            .filter(|_| stable_deposit > Amount::ZERO)
+            .and_then(|config| {
+                let (_, share) = graduation::split_supply(current_supply, config.token_share_bps)?;
+                let price = config.opening_price(final_price?)?;
+                let deposit = units::from_amount(stable_deposit, config.decimals).ok()?;
+                let (stable, _) = graduation::price_matched_supply(share, deposit, price).ok()?;
+                Some(stable)
+            });
+        let pooled_supply = self.state.graduation_supply();
+        let stable_split = stable_split.filter(|stable| *stable < pooled_supply);
+        let native_supply = stable_split
+            .map_or(pooled_supply, |stable| pooled_supply - stable);
+
+        // The pool opens at the launch's last price; tokens beyond that are burned
+        let (native_supply, excess) = match final_price {
+            Some(price) => {
+                graduation::price_matched_supply(native_supply, total_raised, price).ok()?
+            }
+            None => (native_supply, U256::zero()),
+        };
+        let params = graduation::pool_parameters(&token_id, native_supply, total_raised).ok()?;
+
+        Some(GraduationPreview {
+            pool_id: params.pool_id,
+            token_reserve: params.token_reserve.to_string(),
+            base_reserve: params.base_reserve.to_string(),
+            opening_price: params.initial_ratio.raw().to_string(),
+            price_decimals: PRICE_DECIMALS,
+            tvl: params.tvl.to_string(),
+            burned_remainder: curve_config
+                .max_supply
+                .saturating_sub(pooled_supply)
+                .saturating_add(excess)
+                .to_string(),
+            creator_fee_bps: curve_config.creator_fee_bps,
+            is_locked: true,
+            stable_pool_id: stable_split
+                .map(|_| graduation::pool_id(&graduation::stable_pool_key(&token_id))),
+            stable_token_reserve: stable_split.map(|stable| stable.to_string()),
+            unmet_graduation_conditions: self.state.unmet_graduation_conditions(),
+        })
+    }
+
+    /// Compare tracked totals against balances, trades and the native reserve
+    async fn reconciliation(&self) -> Option<ReconciliationReport> {
+        let mut reconciliation = match self.state.reconcile().await {
+            Ok(reconciliation) => reconciliation,
+            Err(e) => {
+                log::error!("Failed to reconcile token state: {}", e);
+                return None;
+            }
+        };
+        let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+        let native_balance = self.runtime.owner_balance(application_owner);
+        if self.state.curve_config.get().base_asset != BaseAsset::Native {
+            // Fungible reserves live in another application; the contract checks them
+            reconciliation.holds_reserve = false;
         }
+        let violations = reconciliation.violations(native_balance);
+
+        Some(ReconciliationReport {
+            current_supply: reconciliation.current_supply.to_string(),
+            balances_total: reconciliation.balances_total.to_string(),
+            trade_count: reconciliation.trade_count,
+            trades_recorded: reconciliation.trades_recorded,
+            native_balance: native_balance.to_string(),
+            sell_out_value: reconciliation.sell_out_value.map(|value| value.to_string()),
+            is_consistent: violations.is_empty(),
+            violations,
+        })
     }
 
-    /// Get buy quote
-    async fn buy_quote(&self, amount: String) -> Option<BuySellQuote> {
+    /// Base currency ledger, for checking that the curve is fully backed
+    async fn reserves(&self) -> ReservesReport {
+        let native_reserves = *self.state.native_reserves.get();
+        let reserved = self.state.reserved_base();
+
+        let current_supply = *self.state.current_supply.get();
+        let curve_config = self.state.curve_config.get();
+        let sell_out_value = if self.state.is_curve_closed() {
+            None
+        } else {
+            bonding_curve::calculate_sell_return(
+                current_supply,
+                current_supply,
+                curve_config.k,
+                curve_config.scale,
+            )
+            .ok()
+            .and_then(|value| units::to_amount(value, curve_config.base_decimals).ok())
+        };
+
+        let application_balance = (curve_config.base_asset == BaseAsset::Native).then(|| {
+            let application_owner = AccountOwner::from(self.runtime.application_id().forget_abi());
+            self.runtime.owner_balance(application_owner)
+        });
+
+        let is_fully_backed = match sell_out_value {
+            Some(sell_out) => native_reserves >= reserved.saturating_add(sell_out),
+            None => self.state.is_curve_closed() && native_reserves >= reserved,
+        };
+        ReservesReport {
+            native_reserves: native_reserves.to_string(),
+            fees_paid_out: self.state.fees_paid_out.get().to_string(),
+            refunds_issued: self.state.refunds_issued.get().to_string(),
+            reserved_for_claims: reserved.to_string(),
+            burned_at_graduation: self.state.graduation_burned.get().to_string(),
+            snipe_tax_collected: self.state.snipe_tax_collected.get().to_string(),
+            snipe_tax_burned: self.state.snipe_tax_burned.get().to_string(),
+            creator_graduation_payout: self.state.graduation_creator_payout.get().to_string(),
+            sell_out_value: sell_out_value.map(|value| value.to_string()),
+            application_balance: application_balance.map(|balance| balance.to_string()),
+            is_fully_backed,
+        }
+    }
+
+    /// Get buy quote, at the fee tier of `account_json` when given
+    async fn buy_quote(
+        &self,
+        amount: String,
+        account_json: Option<String>,
+    ) -> Option<BuySellQuote> {
         let amount_u256 = U256::from_dec_str(&amount).ok()?;
         let current_supply = *self.state.current_supply.get();
         let curve_config = self.state.curve_config.get().clone();
@@ -130,38 +757,160 @@ impl QueryRoot {
             amount_u256,
             curve_config.k,
             curve_config.scale,
-        );
+        ).ok()?;
 
         let current_price = bonding_curve::calculate_current_price(
             current_supply,
             curve_config.k,
             curve_config.scale,
-        );
+        ).ok()?;
 
-        let new_supply = current_supply + amount_u256;
+        let new_supply = current_supply.checked_add(amount_u256)?;
         let new_price = bonding_curve::calculate_current_price(
             new_supply,
             curve_config.k,
             curve_config.scale,
-        );
+        ).ok()?;
 
-        let price_impact = if current_price > U256::zero() {
+        let price_impact = if !current_price.is_zero() {
+            let (current_price, new_price) = (current_price.raw(), new_price.raw());
             let impact = ((new_price.saturating_sub(current_price)) * U256::from(10000)) / current_price;
             impact.as_u64() as f64 / 100.0
         } else {
             0.0
         };
 
+        // Charged as in `execute_buy`: the cost, creator fee included, and the
+        // snipe tax, each converted on its own
+        let fee_bps = self.quoted_fee_bps(account_json).await?;
+        let fee_amount = wad::mul_bps(cost, fee_bps)?;
+        let snipe_tax_bps = self.state.snipe_tax_bps(self.runtime.system_time());
+        let snipe_tax = wad::mul_bps(cost, snipe_tax_bps)?;
+        let total_with_fee = cost.checked_add(snipe_tax)?;
+        let native_amount = units::to_amount(cost, curve_config.base_decimals)
+            .and_then(|cost| {
+                let tax = units::to_amount(snipe_tax, curve_config.base_decimals)?;
+                Ok(cost.saturating_add(tax))
+            })
+            .ok();
+
         Some(BuySellQuote {
             token_amount: amount,
             currency_amount: cost.to_string(),
+            fee_amount: fee_amount.to_string(),
+            total_with_fee: total_with_fee.to_string(),
+            native_amount: native_amount.map(|amount| amount.to_string()),
             price_impact,
-            new_price: new_price.to_string(),
+            new_price: new_price.raw().to_string(),
+            price_decimals: PRICE_DECIMALS,
+            quoted_supply: current_supply.to_string(),
+            snipe_tax: snipe_tax.to_string(),
+            snipe_tax_bps,
         })
     }
 
-    /// Get sell quote
-    async fn sell_quote(&self, amount: String) -> Option<BuySellQuote> {
+    /// Buy quote for spending `spend` base units, snipe tax included; pass the
+    /// spend as `BuyWithBudget`'s budget and `token_amount` (less slippage)
+    /// as its `min_tokens_out`
+    async fn buy_quote_by_spend(
+        &self,
+        spend: String,
+        account_json: Option<String>,
+    ) -> Option<BuySellQuote> {
+        let spend = U256::from_dec_str(&spend).ok()?;
+        let (amount, _) = self.state.tokens_for_spend(spend, self.runtime.system_time()).ok()?;
+        if amount.is_zero() {
+            return None;
+        }
+        self.buy_quote(amount.to_string(), account_json).await
+    }
+
+    /// The configured curve sampled evenly from zero to max supply, for charting
+    ///
+    /// `samples` counts both endpoints and defaults to 100 (at most 1000).
+    async fn curve_points(&self, samples: Option<i32>) -> Vec<CurvePoint> {
+        let samples = samples.unwrap_or(100).clamp(2, 1000) as u64;
+        let curve_config = self.state.curve_config.get();
+        let last = U256::from(samples - 1);
+        (0..samples)
+            .map_while(|index| {
+                let supply = wad::mul_div(curve_config.max_supply, U256::from(index), last)?;
+                let price = bonding_curve::calculate_current_price(
+                    supply,
+                    curve_config.k,
+                    curve_config.scale,
+                )
+                .ok()?;
+                let cumulative_cost = bonding_curve::calculate_buy_cost(
+                    U256::zero(),
+                    supply,
+                    curve_config.k,
+                    curve_config.scale,
+                )
+                .ok()?;
+                Some(CurvePoint {
+                    supply: supply.to_string(),
+                    price: price.raw().to_string(),
+                    cumulative_cost: cumulative_cost.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// How much can be sold right now without exceeding the curve's liquidity
+    ///
+    /// With an account, the amount is also capped at its balance. Native
+    /// liquidity is read from the application balance, fungible liquidity
+    /// from the reserve ledger.
+    async fn max_sellable_now(&self, account_json: Option<String>) -> Option<MaxSellable> {
+        if self.state.is_curve_closed() {
+            return None;
+        }
+        let curve_config = self.state.curve_config.get().clone();
+        let held = match curve_config.base_asset {
+            BaseAsset::Native => {
+                let application_owner =
+                    AccountOwner::from(self.runtime.application_id().forget_abi());
+                self.runtime.owner_balance(application_owner)
+            }
+            BaseAsset::Fungible(_) => *self.state.native_reserves.get(),
+        };
+        let available = held.saturating_sub(self.state.reserved_base());
+
+        let current_supply = *self.state.current_supply.get();
+        let budget = units::from_amount(available, curve_config.base_decimals).ok()?;
+        let mut token_amount = bonding_curve::calculate_max_sell_for_return(
+            current_supply,
+            budget,
+            curve_config.k,
+            curve_config.scale,
+        )
+        .ok()?;
+        if let Some(account_json) = account_json {
+            let account: Account = serde_json::from_str(&account_json).ok()?;
+            token_amount = token_amount.min(self.state.get_balance(&account).await);
+        }
+
+        let currency_amount = bonding_curve::calculate_sell_return(
+            current_supply,
+            token_amount,
+            curve_config.k,
+            curve_config.scale,
+        )
+        .ok()?;
+        Some(MaxSellable {
+            token_amount: token_amount.to_string(),
+            currency_amount: currency_amount.to_string(),
+            available_liquidity: available.to_string(),
+        })
+    }
+
+    /// Get sell quote, at the fee tier of `account_json` when given
+    async fn sell_quote(
+        &self,
+        amount: String,
+        account_json: Option<String>,
+    ) -> Option<BuySellQuote> {
         let amount_u256 = U256::from_dec_str(&amount).ok()?;
         let current_supply = *self.state.current_supply.get();
         let curve_config = self.state.curve_config.get().clone();
@@ -175,33 +924,48 @@ impl QueryRoot {
             amount_u256,
             curve_config.k,
             curve_config.scale,
-        );
+        ).ok()?;
 
         let current_price = bonding_curve::calculate_current_price(
             current_supply,
             curve_config.k,
             curve_config.scale,
-        );
+        ).ok()?;
 
         let new_supply = current_supply - amount_u256;
         let new_price = bonding_curve::calculate_current_price(
             new_supply,
             curve_config.k,
             curve_config.scale,
-        );
+        ).ok()?;
 
-        let price_impact = if current_price > U256::zero() {
+        let price_impact = if !current_price.is_zero() {
+            let (current_price, new_price) = (current_price.raw(), new_price.raw());
             let impact = ((current_price.saturating_sub(new_price)) * U256::from(10000)) / current_price;
             impact.as_u64() as f64 / 100.0
         } else {
             0.0
         };
 
+        // Paid out as in `execute_sell`: the return less the creator fee
+        let fee_bps = self.quoted_fee_bps(account_json).await?;
+        let fee_amount = wad::mul_bps(return_amount, fee_bps)?;
+        let net_return = return_amount.saturating_sub(fee_amount);
+
         Some(BuySellQuote {
             token_amount: amount,
             currency_amount: return_amount.to_string(),
+            fee_amount: fee_amount.to_string(),
+            total_with_fee: net_return.to_string(),
+            native_amount: units::to_amount(net_return, curve_config.base_decimals)
+                .ok()
+                .map(|amount| amount.to_string()),
             price_impact,
-            new_price: new_price.to_string(),
+            new_price: new_price.raw().to_string(),
+            price_decimals: PRICE_DECIMALS,
+            quoted_supply: current_supply.to_string(),
+            snipe_tax: "0".to_string(),
+            snipe_tax_bps: 0,
         })
     }
 
@@ -225,44 +989,768 @@ impl QueryRoot {
             .map(|p| p.into())
     }
 
-    /// Get recent trades
+    /// Volume fee tier of an account (JSON-encoded `Account`)
+    async fn trader_fee_tier(&self, account_json: String) -> Option<TraderFeeTier> {
+        let account: Account = serde_json::from_str(&account_json).ok()?;
+        let now = self.runtime.system_time();
+        let curve_config = self.state.curve_config.get();
+        let volume = self.state.trader_volume(&account, now).await.ok()?;
+        let discount_bps = fee_tiers::discount_bps(&curve_config.fee_tiers, volume);
+        Some(TraderFeeTier {
+            volume_30d: volume.to_string(),
+            discount_bps,
+            fee_bps: fee_tiers::discounted_fee_bps(curve_config.creator_fee_bps, discount_bps),
+        })
+    }
+
+    /// Accounts whose buys and sells net to near zero within short windows,
+    /// largest flagged volume first, so frontends can discount their volume
+    async fn suspicious_activity(&self, limit: Option<i32>) -> Vec<SuspiciousAccount> {
+        let limit = limit.unwrap_or(20).clamp(1, 100) as usize;
+        let accounts = match self.state.suspicious_accounts(limit).await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                log::error!("Failed to read wash trading stats: {}", e);
+                return Vec::new();
+            }
+        };
+        accounts
+            .into_iter()
+            .map(|(account, stats)| SuspiciousAccount {
+                account: account.to_string(),
+                flagged_trades: stats.flagged_trades,
+                flagged_volume: stats.flagged_volume.to_string(),
+                alternations: stats.alternations,
+                last_flagged_at: stats.last_flagged_at.map(|at| at.micros().to_string()),
+            })
+            .collect()
+    }
+
+    /// Get recent trades, newest first
     async fn recent_trades(&self, limit: Option<i32>) -> Vec<fair_launch_abi::TradeGQL> {
         let limit = limit.unwrap_or(20).max(1).min(100) as usize;
-        self.state.get_trades(0, limit).await.iter().map(|t| t.into()).collect()
+        let count = *self.state.trade_count.get() as usize;
+        let offset = count.saturating_sub(limit);
+        self.state.get_trades(offset, limit).await.iter().rev().map(|t| t.into()).collect()
     }
 
-    /// Get trades for specific user
-    async fn user_trades(&self, account_json: String, limit: Option<i32>) -> Vec<fair_launch_abi::TradeGQL> {
+    /// Trades with sequence numbers from `since` on, oldest first
+    ///
+    /// Sequence numbers are assigned in recording order without gaps, so an
+    /// indexer can tail the history by asking again from the last one + 1.
+    async fn trades_since(&self, since: u64, limit: Option<i32>) -> Vec<SequencedTrade> {
+        let limit = limit.unwrap_or(100).clamp(1, 1000) as u64;
+        self.state
+            .get_trades_since(since, limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|(sequence, trade)| SequencedTrade {
+                sequence: *sequence,
+                trader: trade.trader.to_string(),
+                timestamp: trade.timestamp.micros().to_string(),
+                trade: trade.into(),
+            })
+            .collect()
+    }
+
+    /// FIFO cost basis, proceeds and gains of an account's curve sells made
+    /// between `from` and `to` (microseconds, inclusive; default all time)
+    ///
+    /// Walks the account's trade history, oldest first and at most
+    /// `MAX_TRADES_PER_QUERY` trades, so earlier buys carry their basis into
+    /// the period; pruned trades contribute the lots they left open, though
+    /// their own sells are no longer reported. Tokens received by transfer
+    /// have no recorded cost and are reported with a zero basis.
+    async fn tax_report(
+        &self,
+        account_json: String,
+        from: Option<String>,
+        to: Option<String>,
+        csv: Option<bool>,
+    ) -> Option<TaxReport> {
+        let account: Account = serde_json::from_str(&account_json).ok()?;
+        let from = from.map_or(Some(0), |from| from.parse().ok())?;
+        let to = to.map_or(Some(u64::MAX), |to| to.parse().ok())?;
+
+        let (trades, left_out) = self.state.get_trader_history(&account).await.ok()?;
+        let truncated = left_out.is_some();
+        // The open lots are only right up to the trades that were read
+        let to = left_out.map_or(to, |at| to.min(at.micros().saturating_sub(1)));
+        let carried = self.state.carried_lots.get(&account).await.ok()?.unwrap_or_default();
+        let report =
+            tax::fifo_report_from(&carried, &trades, Timestamp::from(from), Timestamp::from(to));
+
+        let disposals: Vec<TaxDisposal> = report.disposals.iter().map(Into::into).collect();
+        let csv = csv.unwrap_or(false).then(|| {
+            let mut csv = "acquired_at,disposed_at,amount,cost_basis,proceeds,gain\n".to_string();
+            for disposal in &disposals {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    disposal.acquired_at.as_deref().unwrap_or(""),
+                    disposal.disposed_at,
+                    disposal.amount,
+                    disposal.cost_basis,
+                    disposal.proceeds,
+                    disposal.gain
+                ));
+            }
+            csv
+        });
+
+        let (open_amount, open_cost_basis) = report
+            .open_lots
+            .iter()
+            .fold((U256::zero(), U256::zero()), |(amount, cost), lot| {
+                (amount + lot.amount, cost + lot.cost)
+            });
+        let is_net_loss = report.total_proceeds < report.total_cost_basis;
+        let net_gain = if is_net_loss {
+            report.total_cost_basis - report.total_proceeds
+        } else {
+            report.total_proceeds - report.total_cost_basis
+        };
+
+        Some(TaxReport {
+            account: account.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            disposals,
+            total_proceeds: report.total_proceeds.to_string(),
+            total_cost_basis: report.total_cost_basis.to_string(),
+            net_gain: signed(net_gain, is_net_loss),
+            open_amount: open_amount.to_string(),
+            open_cost_basis: open_cost_basis.to_string(),
+            csv,
+            truncated,
+        })
+    }
+
+    /// All trades as a cursor-paginated connection with a total count
+    ///
+    /// Cursors are trade sequence numbers, so pruning never shifts them;
+    /// `totalCount` counts the trades that were not pruned.
+    async fn trades_connection(
+        &self,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<Page<fair_launch_abi::TradeGQL>> {
+        query(after, before, first, last, |after, before, first, last| async move {
+            // Window positions count from the oldest trade that was not pruned
+            let pruned = *self.state.pruned_trades.get() as usize;
+            let total = (*self.state.trade_count.get() as usize).saturating_sub(pruned);
+            let after = after.and_then(|sequence| sequence.checked_sub(pruned));
+            let before = before.map(|sequence| sequence.saturating_sub(pruned));
+            let (start, end) = pagination::window(after, before, first, last, total);
+            let nodes = self
+                .state
+                .get_trades_since((pruned + start) as u64, (end - start) as u64)
+                .await?
+                .into_iter()
+                .map(|(sequence, trade)| {
+                    (sequence as usize, fair_launch_abi::TradeGQL::from(&trade))
+                })
+                .collect();
+            Ok::<_, async_graphql::Error>(pagination::page(nodes, start, end, total))
+        })
+        .await
+    }
+
+    /// Get trades for specific user, newest first
+    async fn user_trades(
+        &self,
+        account_json: String,
+        offset: Option<i32>,
+        limit: Option<i32>,
+    ) -> Vec<fair_launch_abi::TradeGQL> {
         let account: Account = match serde_json::from_str(&account_json) {
             Ok(acc) => acc,
             Err(_) => return Vec::new(),
         };
-        let limit = limit.unwrap_or(20).max(1).min(100) as usize;
-        let all_trades = self.state.get_trades(0, 1000).await;
+        let offset = offset.unwrap_or(0).max(0) as u64;
+        let limit = limit.unwrap_or(20).max(1).min(100) as u64;
 
-        all_trades
-            .into_iter()
-            .filter(|t| t.trader == account)
-            .take(limit)
-            .map(|t| (&t).into())
+        self.state
+            .get_trades_by_trader(&account, offset, limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|t| t.into())
             .collect()
     }
 
-    /// Get allowance amount that spender can spend on behalf of owner
+    /// Get allowance amount that spender can spend on behalf of owner (zero once expired)
     async fn allowance(&self, owner_json: String, spender_json: String) -> Option<String> {
         let owner: Account = serde_json::from_str(&owner_json).ok()?;
         let spender: Account = serde_json::from_str(&spender_json).ok()?;
-        let allowance = self.state.get_allowance(&owner, &spender).await;
+        let now = self.runtime.system_time();
+        let allowance = self.state.get_active_allowance(&owner, &spender, now).await;
         Some(allowance.to_string())
     }
+
+    /// Whether `operator_json` may transfer any of the owner's tokens
+    async fn is_operator(&self, owner_json: String, operator_json: String) -> Option<bool> {
+        let owner: Account = serde_json::from_str(&owner_json).ok()?;
+        let operator: Account = serde_json::from_str(&operator_json).ok()?;
+        Some(self.state.is_operator(&owner, &operator).await)
+    }
+
+    /// Operators an owner has approved
+    async fn operators(&self, owner_json: String) -> Vec<String> {
+        let Ok(owner) = serde_json::from_str::<Account>(&owner_json) else {
+            return Vec::new();
+        };
+        self.state
+            .get_operators(&owner)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Nonce the owner's next permit must be signed with
+    async fn permit_nonce(&self, owner_json: String) -> Option<u64> {
+        let owner: Account = serde_json::from_str(&owner_json).ok()?;
+        self.state.permit_nonce(&owner).await.ok()
+    }
+
+    /// Allowances still stored under legacy string keys
+    async fn legacy_allowances_pending(&self) -> u64 {
+        self.state.legacy_allowance_count().await.unwrap_or_default() as u64
+    }
+
+    /// Trades still stored under legacy string keys
+    async fn legacy_trades_pending(&self) -> u64 {
+        self.state.legacy_trade_count().await.unwrap_or_default() as u64
+    }
+
+    /// List the unexpired allowances granted by an owner
+    async fn approvals_granted(&self, owner_json: String) -> Vec<ApprovalInfo> {
+        let owner: Account = match serde_json::from_str(&owner_json) {
+            Ok(acc) => acc,
+            Err(_) => return Vec::new(),
+        };
+        self.state
+            .get_approvals_granted(&owner, self.runtime.system_time())
+            .await
+            .into_iter()
+            .map(|(spender, amount, expires_at)| ApprovalInfo {
+                owner: owner.to_string(),
+                spender: spender.to_string(),
+                amount: amount.to_string(),
+                expires_at: expires_at.map(|t| t.micros().to_string()),
+            })
+            .collect()
+    }
+
+    /// List the unexpired allowances received by a spender
+    async fn approvals_received(&self, spender_json: String) -> Vec<ApprovalInfo> {
+        let spender: Account = match serde_json::from_str(&spender_json) {
+            Ok(acc) => acc,
+            Err(_) => return Vec::new(),
+        };
+        self.state
+            .get_approvals_received(&spender, self.runtime.system_time())
+            .await
+            .into_iter()
+            .map(|(owner, amount, expires_at)| ApprovalInfo {
+                owner: owner.to_string(),
+                spender: spender.to_string(),
+                amount: amount.to_string(),
+                expires_at: expires_at.map(|t| t.micros().to_string()),
+            })
+            .collect()
+    }
+
+    /// Version of the state these results were read from
+    async fn state_version(&self) -> u64 {
+        *self.state.state_version.get()
+    }
+
+    /// Holders grouped by share of max supply, smallest holders first
+    async fn holder_distribution(&self) -> Vec<HolderBucket> {
+        let distribution = self.state.holder_distribution().await.unwrap_or_default();
+        HOLDER_BUCKET_LABELS
+            .iter()
+            .zip(distribution)
+            .map(|(label, holders)| HolderBucket { label: label.to_string(), holders })
+            .collect()
+    }
+
+    /// Trades deleted by pruning; older sequence numbers no longer resolve
+    async fn pruned_trades(&self) -> u64 {
+        *self.state.pruned_trades.get()
+    }
+
+    /// Version of the token bytecode, as announced to the factory
+    async fn contract_version(&self) -> u32 {
+        *self.state.contract_version.get()
+    }
+
+    /// Staking totals, plus the position of `account_json` when given
+    async fn staking_stats(&self, account_json: Option<String>) -> StakingStats {
+        let staking = &self.state.staking;
+        let account = account_json.and_then(|json| serde_json::from_str::<Account>(&json).ok());
+        let (staked, claimable) = match account {
+            Some(account) => (
+                staking.staked(&account).await.ok().map(|staked| staked.to_string()),
+                staking.pending_rewards(&account).await.ok().map(|rewards| rewards.to_string()),
+            ),
+            None => (None, None),
+        };
+
+        StakingStats {
+            total_staked: staking.total_staked.get().to_string(),
+            reward_reserve: u128::from(*staking.reward_reserve.get()).to_string(),
+            total_funded: u128::from(*staking.total_funded.get()).to_string(),
+            total_claimed: u128::from(*staking.total_claimed.get()).to_string(),
+            pending_emission: staking.pending_emission.get().to_string(),
+            staked,
+            claimable,
+        }
+    }
+
+    /// Dutch auction state (None on bonding curve launches), plus the bid
+    /// and refund of `account_json` when given
+    async fn auction(&self, account_json: Option<String>) -> Option<AuctionInfo> {
+        let curve_config = self.state.curve_config.get();
+        let config = *curve_config.launch_mechanism.auction()?;
+        let auction = &self.state.auction;
+        let clearing_price = *auction.clearing_price.get();
+        let now = self.runtime.system_time();
+        let current_price = clearing_price.unwrap_or_else(|| dutch_auction::price_at(&config, now));
+
+        let account = account_json.and_then(|json| serde_json::from_str::<Account>(&json).ok());
+        let (bid_amount, refund_due) = match account {
+            Some(account) => (
+                auction
+                    .bids
+                    .get(&account)
+                    .await
+                    .ok()
+                    .map(|bid| bid.map_or(U256::zero(), |bid| bid.amount).to_string()),
+                auction.refund_due(&account).await.ok().map(|refund| refund.to_string()),
+            ),
+            None => (None, None),
+        };
+
+        Some(AuctionInfo {
+            start_price: config.start_price.raw().to_string(),
+            floor_price: config.floor_price.raw().to_string(),
+            current_price: current_price.raw().to_string(),
+            price_decimals: PRICE_DECIMALS,
+            starts_at: config.starts_at.micros().to_string(),
+            ends_at: dutch_auction::ends_at(&config).micros().to_string(),
+            supply: curve_config.max_supply.to_string(),
+            sold: self.state.current_supply.get().to_string(),
+            bidder_count: *auction.bidder_count.get(),
+            is_settled: auction.is_settled(),
+            clearing_price: clearing_price.map(|price| price.raw().to_string()),
+            bid_amount,
+            refund_due,
+        })
+    }
+
+    /// Fixed-price sale state (None on other launches), plus the commitment
+    /// and allocation of `account_json` when given
+    async fn sale(&self, account_json: Option<String>) -> Option<SaleInfo> {
+        let curve_config = self.state.curve_config.get();
+        let config = *curve_config.launch_mechanism.fixed_price_sale()?;
+        let sale = &self.state.sale;
+
+        let account = account_json.and_then(|json| serde_json::from_str::<Account>(&json).ok());
+        let commitment = match account {
+            Some(account) => sale.commitments.get(&account).await.ok().flatten(),
+            None => None,
+        };
+        let allocation = commitment.as_ref().filter(|commitment| commitment.settled);
+
+        Some(SaleInfo {
+            price: config.price.raw().to_string(),
+            price_decimals: PRICE_DECIMALS,
+            starts_at: config.starts_at.micros().to_string(),
+            ends_at: fixed_price_sale::ends_at(&config).micros().to_string(),
+            supply: curve_config.max_supply.to_string(),
+            total_committed: sale.total_committed.get().to_string(),
+            supply_cost: config
+                .price
+                .mul_amount(curve_config.max_supply)
+                .map(|cost| cost.to_string())
+                .unwrap_or_default(),
+            buyer_count: *sale.buyer_count.get(),
+            settled_count: *sale.settled_count.get(),
+            is_settled: sale.is_settled(),
+            committed: commitment.as_ref().map(|commitment| commitment.committed.to_string()),
+            allocated: allocation.map(|commitment| commitment.tokens.to_string()),
+            refund: allocation.map(|commitment| commitment.refund.to_string()),
+        })
+    }
+
+    /// Whitelist phase state, plus whether `account_json` may buy when given
+    async fn whitelist_status(&self, account_json: Option<String>) -> WhitelistStatus {
+        let now = self.runtime.system_time();
+        let account = account_json.and_then(|json| serde_json::from_str::<Account>(&json).ok());
+        let (is_whitelisted, can_buy) = match account {
+            Some(account) => (
+                self.state.is_whitelisted(&account).await.ok(),
+                self.state.may_buy(&account, now).await.ok(),
+            ),
+            None => (None, None),
+        };
+
+        WhitelistStatus {
+            ends_at: self
+                .state
+                .curve_config
+                .get()
+                .whitelist_ends_at
+                .map(|ends_at| ends_at.micros().to_string()),
+            is_active: self.state.whitelist_active(now),
+            whitelisted_count: *self.state.whitelist_count.get(),
+            is_whitelisted,
+            can_buy,
+        }
+    }
+
+    /// List whitelisted accounts
+    async fn whitelist(&self, offset: Option<u64>, limit: Option<u64>) -> Vec<String> {
+        let offset = offset.unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(100).min(1000) as usize;
+        self.state
+            .list_whitelist(offset, limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// List balance snapshots and their reward pools
+    async fn snapshots(&self, offset: Option<u64>, limit: Option<u64>) -> Vec<fair_launch_abi::BalanceSnapshotGQL> {
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(20).min(100);
+        self.state
+            .snapshots
+            .list(offset, limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|(snapshot_id, snapshot)| fair_launch_abi::BalanceSnapshotGQL::new(*snapshot_id, snapshot))
+            .collect()
+    }
+
+    /// Whether `account_json` has claimed its reward from a snapshot
+    async fn snapshot_claimed(&self, snapshot_id: u64, account_json: String) -> bool {
+        match serde_json::from_str::<Account>(&account_json) {
+            Ok(account) => self
+                .state
+                .snapshots
+                .has_claimed(snapshot_id, account)
+                .await
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    /// Last price published on the price feed stream (None before the first trade)
+    async fn published_price(&self) -> Option<PublishedPrice> {
+        let update = self.state.last_price_update.get().clone()?;
+        let config = self.runtime.application_parameters().price_feed;
+        Some(PublishedPrice {
+            price: update.price.raw().to_string(),
+            price_decimals: PRICE_DECIMALS,
+            trade_count: update.trade_count,
+            published_at: update.published_at.micros().to_string(),
+            every_trades: config.every_trades,
+            move_bps: config.move_bps,
+        })
+    }
+
+    /// Totals removed by creator dust sweeps
+    async fn dust_sweeps(&self) -> DustSweepStats {
+        DustSweepStats {
+            total_swept: self.state.dust_swept_total.get().to_string(),
+            accounts_swept: *self.state.dust_swept_accounts.get(),
+            sweep_count: *self.state.dust_sweep_count.get(),
+        }
+    }
+
+    /// Totals burned by the DEX pools' fee-funded buybacks
+    async fn buyback_burns(&self) -> BuybackBurnStats {
+        BuybackBurnStats {
+            total_burned: self.state.buyback_burned.get().to_string(),
+            burn_count: *self.state.buyback_burn_count.get(),
+            supply_after_burns: self.state.supply_after_buybacks().to_string(),
+        }
+    }
+
+    /// Failure of a launch that missed its minimum raise (None while live)
+    async fn launch_failure(&self) -> Option<LaunchFailureInfo> {
+        self.state.launch_failure.get().map(|failure| LaunchFailureInfo {
+            failed_at: failure.failed_at.micros().to_string(),
+            refund_pool: failure.refund_pool.to_string(),
+            supply: failure.supply.to_string(),
+            redeemed: failure.redeemed.to_string(),
+        })
+    }
+
+    /// Unsold supply burned at graduation instead of being pooled
+    async fn unsold_burned(&self) -> String {
+        self.state.unsold_burned.get().to_string()
+    }
+
+    /// Tokens left out of the graduation pool to open it at the final launch
+    /// price, and so burned
+    async fn pool_excess_burned(&self) -> String {
+        self.state.pool_excess_burned.get().to_string()
+    }
+
+    /// Recurring buy schedules created by `owner_json`
+    async fn recurring_buys(&self, owner_json: String) -> Vec<fair_launch_abi::RecurringBuyGQL> {
+        let Ok(owner) = serde_json::from_str::<Account>(&owner_json) else {
+            return Vec::new();
+        };
+        self.state
+            .recurring_buys
+            .owned_by(&owner)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|(schedule_id, schedule)| fair_launch_abi::RecurringBuyGQL::new(*schedule_id, schedule))
+            .collect()
+    }
+
+    /// Co-creators and the approvals a creator action needs
+    async fn creator_set(&self) -> CreatorSetInfo {
+        let creator = self.state.creator.get().clone().expect("Token creator not initialized");
+        CreatorSetInfo {
+            members: self
+                .state
+                .creator_set
+                .members_or(creator)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            threshold: self.state.creator_set.threshold(),
+        }
+    }
+
+    /// Latest creator action proposals, newest first
+    async fn creator_proposals(&self, limit: Option<u64>) -> Vec<CreatorProposalInfo> {
+        let limit = limit.unwrap_or(20).min(100);
+        let creator_set = &self.state.creator_set;
+        creator_set
+            .recent(limit)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(proposal_id, proposal)| CreatorProposalInfo {
+                proposal_id,
+                action: serde_json::to_string(&proposal.action).unwrap_or_default(),
+                proposer: proposal.proposer.to_string(),
+                approvals: proposal.approvals.iter().map(ToString::to_string).collect(),
+                created_at: proposal.created_at.micros().to_string(),
+                is_pending: creator_set.is_pending(&proposal),
+                executed: proposal.executed,
+            })
+            .collect()
+    }
+
+    /// Tokens held in escrow by `LockTokens`, optionally only those of `owner_json`
+    async fn locks(&self, owner_json: Option<String>) -> Vec<TokenLock> {
+        let owner = match owner_json.map(|json| serde_json::from_str::<Account>(&json)) {
+            Some(Ok(owner)) => Some(owner),
+            Some(Err(_)) => return Vec::new(),
+            None => None,
+        };
+        let now = self.runtime.system_time();
+        self.state
+            .get_locks(owner.as_ref())
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(owner, unlock_at, amount)| TokenLock {
+                owner: owner.to_string(),
+                amount: amount.to_string(),
+                unlock_at: unlock_at.micros().to_string(),
+                is_unlocked: unlock_at <= now,
+            })
+            .collect()
+    }
+
+    /// Total tokens held in escrow
+    async fn total_locked(&self) -> String {
+        self.state.total_locked.get().to_string()
+    }
+
+    /// List refunds issued for buys rejected after the curve closed
+    async fn refunds(&self, offset: Option<u64>, limit: Option<u64>) -> Vec<fair_launch_abi::RefundGQL> {
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(20).min(100);
+        self.state
+            .get_refunds(offset, limit)
+            .await
+            .iter()
+            .map(|(refund_id, refund)| fair_launch_abi::RefundGQL::new(*refund_id, refund))
+            .collect()
+    }
+
+    /// Pool terms and lock status attested by the swap chain at graduation
+    ///
+    /// Served from this chain's state, so it stays available when the swap
+    /// chain is unreachable.
+    async fn pool_attestation(&self) -> Option<fair_launch_abi::PoolAttestationGQL> {
+        self.state.pool_attestation.get().as_ref().map(Into::into)
+    }
+
+    /// Attestation of the stablecoin-quoted pool, if one was seeded
+    async fn stable_pool_attestation(&self) -> Option<fair_launch_abi::PoolAttestationGQL> {
+        self.state.stable_pool_attestation.get().as_ref().map(Into::into)
+    }
+
+    /// List tracked messages that bounced and their resolution
+    async fn pending_messages(&self) -> Vec<fair_launch_abi::PendingMessageGQL> {
+        self.state
+            .get_pending_messages()
+            .await
+            .iter()
+            .map(|p| p.into())
+            .collect()
+    }
+
+    /// Latest critical events logged by the contract, newest first
+    async fn critical_events(
+        &self,
+        limit: Option<i32>,
+    ) -> Vec<fair_launch_abi::logging::CriticalEventGQL> {
+        let limit = limit.unwrap_or(20).max(0) as u64;
+        self.state
+            .critical_events
+            .recent(limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|(sequence, event)| {
+                fair_launch_abi::logging::CriticalEventGQL::new(*sequence, event)
+            })
+            .collect()
+    }
+
+    /// Outbox events after sequence `after_seq` (from the first if omitted), oldest first
+    ///
+    /// Pass the last sequence processed to sync incrementally without gaps.
+    async fn events(
+        &self,
+        after_seq: Option<u64>,
+        limit: Option<i32>,
+    ) -> Vec<fair_launch_abi::outbox::OutboxEventGQL> {
+        let limit = limit.unwrap_or(100).clamp(1, 1000) as u64;
+        self.state
+            .outbox
+            .after(after_seq, limit)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|(sequence, event)| fair_launch_abi::outbox::OutboxEventGQL::new(*sequence, event))
+            .collect()
+    }
+
+    /// Export the token's state one page at a time, for analytics or for
+    /// `ImportState` on a new token version
+    ///
+    /// `section` is "balances", "positions" or "trades"; import the trades
+    /// before the positions.
+    async fn state_export(
+        &self,
+        section: String,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> async_graphql::Result<StateExportPage> {
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(500).clamp(1, 1000);
+        let chunk = match section.as_str() {
+            "balances" => StateChunk::Balances(
+                self.state.export_balances(offset as usize, limit as usize).await?,
+            ),
+            "positions" => StateChunk::Positions(
+                self.state.export_positions(offset as usize, limit as usize).await?,
+            ),
+            "trades" => StateChunk::Trades(self.state.get_trades_since(offset, limit).await?),
+            other => return Err(async_graphql::Error::new(format!("Unknown section: {}", other))),
+        };
+
+        // Trade offsets are sequence numbers, which skip any pruned trades
+        let entries = chunk.len() as u64;
+        let next_offset = match &chunk {
+            StateChunk::Trades(trades) => trades.last().map(|(sequence, _)| sequence + 1),
+            _ => Some(offset + entries),
+        };
+        Ok(StateExportPage {
+            section,
+            offset,
+            next_offset: next_offset.filter(|_| entries == limit),
+            entries,
+            chunk: serde_json::to_string(&chunk)?,
+            commitment: chunk.commitment().to_string(),
+        })
+    }
+
+    /// Get the metadata audit trail (oldest first)
+    async fn metadata_history(&self, offset: Option<i32>, limit: Option<i32>) -> Vec<MetadataRevision> {
+        let offset = offset.unwrap_or(0).max(0) as u64;
+        let limit = limit.unwrap_or(20).max(1).min(100) as u64;
+        self.state
+            .get_metadata_history(offset, limit)
+            .await
+            .into_iter()
+            .map(|(revision, timestamp, metadata)| MetadataRevision {
+                revision,
+                timestamp: timestamp.micros().to_string(),
+                metadata,
+            })
+            .collect()
+    }
 }
 
-pub struct EmptyMutation;
+pub struct MutationRoot {
+    runtime: Arc<ServiceRuntime<TokenService>>,
+}
 
 #[Object]
-impl EmptyMutation {
-    /// Placeholder mutation (operations are handled via execute_operation)
-    async fn _placeholder(&self) -> bool {
-        true
+impl MutationRoot {
+    /// Schedule a token operation given as JSON, for scripts and dev tooling
+    async fn schedule_operation(&self, operation_json: String) -> async_graphql::Result<bool> {
+        let operation: TokenOperation = serde_json::from_str(&operation_json)?;
+        self.runtime.schedule_operation(&operation);
+        Ok(true)
+    }
+
+    /// Schedule a relative allowance increase for `spender_json`
+    async fn increase_allowance(
+        &self,
+        spender_json: String,
+        added: String,
+    ) -> async_graphql::Result<bool> {
+        let spender: Account = serde_json::from_str(&spender_json)?;
+        let added = U256::from_dec_str(&added)?;
+        self.runtime
+            .schedule_operation(&TokenOperation::IncreaseAllowance { spender, added });
+        Ok(true)
+    }
+
+    /// Schedule a relative allowance decrease for `spender_json`
+    async fn decrease_allowance(
+        &self,
+        spender_json: String,
+        subtracted: String,
+    ) -> async_graphql::Result<bool> {
+        let spender: Account = serde_json::from_str(&spender_json)?;
+        let subtracted = U256::from_dec_str(&subtracted)?;
+        self.runtime
+            .schedule_operation(&TokenOperation::DecreaseAllowance { spender, subtracted });
+        Ok(true)
     }
 }
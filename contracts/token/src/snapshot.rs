@@ -0,0 +1,74 @@
+use fair_launch_abi::BalanceSnapshot;
+use linera_sdk::{
+    linera_base_types::{Account, Amount},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+
+/// Balance snapshots and the reward pools attached to them
+///
+/// A snapshot stores only the merkle root of the balances it covers, so
+/// claims are checked against a proof supplied by the claimant rather than
+/// by iterating holders. Proofs are built off-chain from the balances at
+/// the snapshot's block height with `fair_launch_abi::merkle`.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct SnapshotState {
+    /// Snapshots: snapshot_id → BalanceSnapshot
+    pub snapshots: MapView<u64, BalanceSnapshot>,
+
+    /// Number of snapshots taken (next snapshot_id)
+    pub snapshot_count: RegisterView<u64>,
+
+    /// Rewards paid: (snapshot_id, Account) → amount
+    pub claims: MapView<(u64, Account), Amount>,
+
+    /// Base currency held by the application for unclaimed snapshot rewards
+    pub reward_reserve: RegisterView<Amount>,
+}
+
+impl SnapshotState {
+    /// Store a new snapshot and reserve its reward pool
+    pub fn record(&mut self, snapshot: BalanceSnapshot) -> Result<u64, ViewError> {
+        let snapshot_id = *self.snapshot_count.get();
+        let reserve = *self.reward_reserve.get();
+        self.reward_reserve.set(reserve.saturating_add(snapshot.reward_pool));
+        self.snapshots.insert(&snapshot_id, snapshot)?;
+        self.snapshot_count.set(snapshot_id + 1);
+        Ok(snapshot_id)
+    }
+
+    /// Whether an account already claimed from a snapshot
+    pub async fn has_claimed(&self, snapshot_id: u64, account: Account) -> Result<bool, ViewError> {
+        Ok(self.claims.contains_key(&(snapshot_id, account)).await?)
+    }
+
+    /// Record a paid claim and release it from the reserve
+    pub async fn record_claim(
+        &mut self,
+        snapshot_id: u64,
+        account: Account,
+        amount: Amount,
+    ) -> Result<(), ViewError> {
+        if let Some(mut snapshot) = self.snapshots.get(&snapshot_id).await? {
+            snapshot.claimed = snapshot.claimed.saturating_add(amount);
+            self.snapshots.insert(&snapshot_id, snapshot)?;
+        }
+        self.claims.insert(&(snapshot_id, account), amount)?;
+        let reserve = *self.reward_reserve.get();
+        self.reward_reserve.set(reserve.saturating_sub(amount));
+        Ok(())
+    }
+
+    /// Snapshots in ID order, paginated
+    pub async fn list(&self, offset: u64, limit: u64) -> Result<Vec<(u64, BalanceSnapshot)>, ViewError> {
+        let count = *self.snapshot_count.get();
+        let mut snapshots = Vec::new();
+        for snapshot_id in offset..count.min(offset.saturating_add(limit)) {
+            if let Some(snapshot) = self.snapshots.get(&snapshot_id).await? {
+                snapshots.push((snapshot_id, snapshot));
+            }
+        }
+        Ok(snapshots)
+    }
+}
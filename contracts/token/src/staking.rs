@@ -0,0 +1,193 @@
+use fair_launch_abi::wad;
+use linera_sdk::{
+    linera_base_types::{Account, Amount, Timestamp},
+    views::{MapView, RegisterView, View, ViewStorageContext},
+};
+use linera_views::ViewError;
+use primitive_types::U256;
+
+/// Stake-to-earn bookkeeping for holders of a token
+///
+/// Rewards are base currency (in attos) funded by a slice of trading fees.
+/// They are emitted pro rata to stakers through a WAD-scaled accumulator:
+/// a staker is owed `staked * reward_per_token / WAD - reward_debt`. Funded
+/// rewards wait in `pending_emission` and vest into the accumulator linearly
+/// over `REWARD_VESTING_MICROS`, so fees collected during the curve, before
+/// anyone can stake, are not all taken by the first staker. Vesting pauses
+/// while nothing is staked.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct StakingState {
+    /// Staked tokens: Account → amount
+    pub staked_balances: MapView<Account, U256>,
+
+    /// Accumulator value already accounted for: Account → attos
+    pub reward_debts: MapView<Account, U256>,
+
+    /// Settled rewards not yet claimed: Account → attos
+    pub unclaimed_rewards: MapView<Account, U256>,
+
+    /// Total tokens staked
+    pub total_staked: RegisterView<U256>,
+
+    /// Accumulated rewards per staked token, WAD-scaled attos
+    pub reward_per_token: RegisterView<U256>,
+
+    /// Funded rewards not yet vested to stakers, in attos
+    pub pending_emission: RegisterView<U256>,
+
+    /// When `pending_emission` finishes vesting
+    pub emission_ends_at: RegisterView<Timestamp>,
+
+    /// When vesting was last applied
+    pub emission_updated_at: RegisterView<Timestamp>,
+
+    /// Base currency held by the application on behalf of stakers
+    pub reward_reserve: RegisterView<Amount>,
+
+    /// Total rewards funded from trading fees
+    pub total_funded: RegisterView<Amount>,
+
+    /// Total rewards claimed by stakers
+    pub total_claimed: RegisterView<Amount>,
+}
+
+/// Period over which funded rewards vest to stakers (7 days)
+pub const REWARD_VESTING_MICROS: u64 = 7 * 24 * 60 * 60 * 1_000_000;
+
+impl StakingState {
+    /// Add fee revenue to the reward pool, restarting the vesting period for
+    /// everything still pending
+    pub fn fund_rewards(&mut self, amount: Amount, now: Timestamp) {
+        self.emit_pending(now);
+        let reserve = *self.reward_reserve.get();
+        self.reward_reserve.set(reserve.saturating_add(amount));
+        let funded = *self.total_funded.get();
+        self.total_funded.set(funded.saturating_add(amount));
+
+        let pending = *self.pending_emission.get();
+        self.pending_emission.set(pending + U256::from(u128::from(amount)));
+        self.emission_ends_at.set(now.saturating_add_micros(REWARD_VESTING_MICROS));
+    }
+
+    /// Tokens staked by an account
+    pub async fn staked(&self, account: &Account) -> Result<U256, ViewError> {
+        Ok(self.staked_balances.get(account).await?.unwrap_or_default())
+    }
+
+    /// Rewards vested to an account so far, in attos
+    pub async fn pending_rewards(&self, account: &Account) -> Result<U256, ViewError> {
+        let unclaimed = self.unclaimed_rewards.get(account).await?.unwrap_or_default();
+        Ok(unclaimed + self.accrued(account).await?)
+    }
+
+    /// Add to an account's stake
+    pub async fn stake(
+        &mut self,
+        account: Account,
+        amount: U256,
+        now: Timestamp,
+    ) -> Result<(), ViewError> {
+        self.emit_pending(now);
+        self.settle(&account).await?;
+        let staked = self.staked(&account).await? + amount;
+        self.staked_balances.insert(&account, staked)?;
+        let total = *self.total_staked.get();
+        self.total_staked.set(total + amount);
+        self.reset_debt(&account, staked)
+    }
+
+    /// Remove from an account's stake; the caller checks the staked amount
+    pub async fn unstake(
+        &mut self,
+        account: Account,
+        amount: U256,
+        now: Timestamp,
+    ) -> Result<(), ViewError> {
+        self.emit_pending(now);
+        self.settle(&account).await?;
+        let staked = self.staked(&account).await?.saturating_sub(amount);
+        if staked.is_zero() {
+            self.staked_balances.remove(&account)?;
+        } else {
+            self.staked_balances.insert(&account, staked)?;
+        }
+        let total = *self.total_staked.get();
+        self.total_staked.set(total.saturating_sub(amount));
+        self.reset_debt(&account, staked)
+    }
+
+    /// Take an account's rewards out of the reserve
+    pub async fn claim(&mut self, account: Account, now: Timestamp) -> Result<Amount, ViewError> {
+        self.emit_pending(now);
+        self.settle(&account).await?;
+        let unclaimed = self.unclaimed_rewards.get(&account).await?.unwrap_or_default();
+        self.unclaimed_rewards.remove(&account)?;
+
+        let reserve = *self.reward_reserve.get();
+        let attos = u128::try_from(unclaimed).unwrap_or(u128::MAX);
+        let amount = Amount::from_attos(attos).min(reserve);
+        self.reward_reserve.set(reserve.saturating_sub(amount));
+        let claimed = *self.total_claimed.get();
+        self.total_claimed.set(claimed.saturating_add(amount));
+        Ok(amount)
+    }
+
+    /// Rewards accrued since the account's last settlement
+    async fn accrued(&self, account: &Account) -> Result<U256, ViewError> {
+        let staked = self.staked(account).await?;
+        let debt = self.reward_debts.get(account).await?.unwrap_or_default();
+        let earned = wad::mul_wad(staked, *self.reward_per_token.get()).unwrap_or(U256::MAX);
+        Ok(earned.saturating_sub(debt))
+    }
+
+    /// Move accrued rewards into the account's unclaimed balance
+    async fn settle(&mut self, account: &Account) -> Result<(), ViewError> {
+        let accrued = self.accrued(account).await?;
+        if !accrued.is_zero() {
+            let unclaimed = self.unclaimed_rewards.get(account).await?.unwrap_or_default();
+            self.unclaimed_rewards.insert(account, unclaimed + accrued)?;
+        }
+        Ok(())
+    }
+
+    /// Mark the current accumulator as accounted for
+    fn reset_debt(&mut self, account: &Account, staked: U256) -> Result<(), ViewError> {
+        if staked.is_zero() {
+            return self.reward_debts.remove(account);
+        }
+        let debt = wad::mul_wad(staked, *self.reward_per_token.get()).unwrap_or(U256::MAX);
+        self.reward_debts.insert(account, debt)
+    }
+
+    /// Spread the rewards vested since the last update over the current stakers
+    fn emit_pending(&mut self, now: Timestamp) {
+        let updated_at = *self.emission_updated_at.get();
+        let ends_at = *self.emission_ends_at.get();
+        let elapsed = now.delta_since(updated_at).as_micros();
+        self.emission_updated_at.set(now);
+
+        let total_staked = *self.total_staked.get();
+        let pending = *self.pending_emission.get();
+        if pending.is_zero() || elapsed == 0 {
+            return;
+        }
+        if total_staked.is_zero() {
+            // Nobody can earn the time that passed, so push the end back
+            self.emission_ends_at.set(ends_at.saturating_add_micros(elapsed));
+            return;
+        }
+
+        let remaining = ends_at.delta_since(updated_at).as_micros();
+        let vested = if elapsed >= remaining {
+            pending
+        } else {
+            pending * U256::from(elapsed) / U256::from(remaining)
+        };
+        if let Some(increment) = wad::div_wad(vested, total_staked) {
+            let reward_per_token = *self.reward_per_token.get();
+            self.reward_per_token.set(reward_per_token.saturating_add(increment));
+            self.pending_emission.set(pending - vested);
+        }
+    }
+}
@@ -1,9 +1,56 @@
-use fair_launch_abi::{BondingCurveConfig, TokenMetadata, Trade, UserPosition};
+use fair_launch_abi::{
+    bonding_curve, cardinality::TraderSketch, fee_tiers::{self, RollingVolume},
+    logging::CriticalEventLog, merkle, outbox::Outbox, price_feed::PriceUpdate, snipe_tax, tax,
+    units, wad,
+    BondingCurveConfig, LaunchFailure, LaunchMechanism, LegacyTrade, ModerationStatus,
+    PendingMessage, PoolAttestation, Price, Refund, StableLeg, StateChunk, TokenMetadata,
+    TokenSummary, Trade, TransferReceipt, UserPosition, WashTradeStats, RAISE_MILESTONES_BPS,
+};
 use linera_sdk::{
-    linera_base_types::{Account, Timestamp},
-    views::{MapView, RegisterView, RootView, ViewStorageContext},
+    linera_base_types::{Account, Amount, CryptoHash, Timestamp},
+    views::{MapView, RegisterView, RootView, View, ViewStorageContext},
 };
 use primitive_types::U256;
+use std::collections::BTreeMap;
+
+use crate::auction::AuctionState;
+use crate::cocreators::CreatorSetState;
+use crate::recurring::RecurringBuyState;
+use crate::sale::SaleState;
+use crate::snapshot::SnapshotState;
+use crate::staking::StakingState;
+
+/// Width of a volume bucket (one hour) in microseconds
+const VOLUME_BUCKET_MICROS: u64 = 3_600_000_000;
+
+/// Number of hourly buckets in the rolling volume window (24h)
+const VOLUME_WINDOW_BUCKETS: u64 = 24;
+
+/// Width of a daily volume bucket in microseconds
+const VOLUME_DAY_MICROS: u64 = 86_400_000_000;
+
+/// Number of daily buckets kept for the weekly volume (7d)
+const VOLUME_HISTORY_DAYS: u64 = 7;
+
+/// Most trades a single `get_trades_since` call reads, so no query scans the
+/// whole history within one block's fuel
+pub const MAX_TRADES_PER_QUERY: u64 = 1_000;
+
+/// Most trades a single `prune_trades` call deletes
+pub const MAX_TRADES_PRUNED_PER_CALL: u64 = 1_000;
+
+/// Most balances a single `sweep_dust` call examines
+pub const MAX_BALANCES_SWEPT_PER_CALL: usize = 1_000;
+
+/// Holder distribution buckets: a balance below max supply divided by the
+/// n-th divisor falls in bucket n (<0.01%, <0.1%, <1%); larger ones in the last
+const HOLDER_BUCKET_DIVISORS: [u64; 3] = [10_000, 1_000, 100];
+
+/// Largest balances kept in `top_holders`
+pub const TOP_HOLDERS_TRACKED: usize = 16;
+
+/// Labels of the holder distribution buckets, smallest holders first
+pub const HOLDER_BUCKET_LABELS: [&str; 4] = ["<0.01%", "0.01-0.1%", "0.1-1%", ">1%"];
 
 /// Token state - stores all token data on its microchain
 #[derive(RootView)]
@@ -30,33 +77,337 @@ pub struct TokenState {
     /// Whether token has graduated to DEX
     pub is_graduated: RegisterView<bool>,
 
+    /// Whether the graduation message has been sent (curve closes immediately)
+    pub graduation_started: RegisterView<bool>,
+
     /// Creation timestamp
     pub created_at: RegisterView<Timestamp>,
 
     /// DEX pool ID after graduation
     pub dex_pool_id: RegisterView<Option<String>>,
 
+    /// Swap chain's attestation of the pool's terms and liquidity lock
+    pub pool_attestation: RegisterView<Option<PoolAttestation>>,
+
+    /// Stablecoin-quoted DEX pool ID after graduation (if one was seeded)
+    pub stable_pool_id: RegisterView<Option<String>>,
+
+    /// Swap chain's attestation of the stablecoin-quoted pool
+    pub stable_pool_attestation: RegisterView<Option<PoolAttestation>>,
+
+    /// Moderation status mirrored from the factory
+    pub moderation_status: RegisterView<ModerationStatus>,
+
     /// User balances: Account → token balance
     pub balances: MapView<Account, U256>,
 
-    /// Trade history: trade_id → Trade
-    pub trades: MapView<String, Trade>,
+    /// Trades under the former "{micros}-{sequence}" keys, with integer prices
+    ///
+    /// Moved to `trades` by `migrate_legacy_trades`; never written to.
+    pub legacy_trades: MapView<String, LegacyTrade>,
+
+    /// Trade history: sequence number → Trade, numbered from 0 in recording order
+    pub trades: MapView<u64, Trade>,
+
+    /// Trade history per trader: (trader, n-th trade of that trader) → sequence number
+    pub trades_by_trader: MapView<(Account, u64), u64>,
 
     /// User positions: Account → UserPosition
     pub user_positions: MapView<Account, UserPosition>,
 
+    /// Every account that traded on the curve, as a fixed-size sketch
+    pub trader_sketch: RegisterView<TraderSketch>,
+
+    /// 30-day curve volume per trader, kept only while fee tiers are configured
+    pub trader_volumes: MapView<Account, RollingVolume>,
+
+    /// Buy/sell alternation per trader, behind `Trade::flagged`
+    pub wash_stats: MapView<Account, WashTradeStats>,
+
     /// Total number of holders
     pub holder_count: RegisterView<u64>,
 
+    /// Holders per distribution bucket (see `HOLDER_BUCKET_LABELS`), shares
+    /// taken of max supply so the buckets never shift as the curve fills
+    pub holder_buckets: MapView<u8, u64>,
+
+    /// Largest balances, largest first, at most `TOP_HOLDERS_TRACKED`, kept by
+    /// `set_balance` so holder concentration needs no scan of every balance
+    pub top_holders: RegisterView<Vec<(Account, U256)>>,
+
+    /// Balances, in index order, that `backfill_top_holders` has folded in
+    pub top_holders_backfilled: RegisterView<u64>,
+
     /// Total number of trades
     pub trade_count: RegisterView<u64>,
 
-    /// Allowances: "{owner}:{spender}" → amount approved
+    /// Trades deleted by pruning: sequences below this are gone, though they
+    /// still count in `trade_count`, the volumes and the positions
+    pub pruned_trades: RegisterView<u64>,
+
+    /// Per-trader index entries deleted by pruning: entries of a trader
+    /// below this are gone
+    pub pruned_trader_trades: MapView<Account, u64>,
+
+    /// Lots still open after a trader's pruned trades, so the tax report
+    /// keeps their cost basis
+    pub carried_lots: MapView<Account, Vec<tax::Lot>>,
+
+    /// Set by the first trade recorded here; `ImportState` is refused afterwards
+    pub import_sealed: RegisterView<bool>,
+
+    /// Commitments of the `StateChunk`s the factory admin allows `ImportState`
+    /// to load, each usable once
+    pub approved_imports: MapView<CryptoHash, ()>,
+
+    /// Version of the token bytecode that initialized this state
+    pub contract_version: RegisterView<u32>,
+
+    /// Allowances under the former "{owner JSON}:{spender JSON}" keys
+    ///
+    /// Read as a fallback and moved to `allowances` when an entry is next
+    /// written or by `migrate_legacy_allowances`; never written to.
+    pub legacy_allowances: MapView<String, U256>,
+
+    /// Allowance expiries under the former string keys
+    pub legacy_allowance_expiries: MapView<String, Timestamp>,
+
+    /// Revocation times under the former string keys
+    pub legacy_revoked_allowances: MapView<String, Timestamp>,
+
+    /// Allowances: (owner, spender) → amount approved
     /// Allows spenders to transfer tokens on behalf of owners (for DEX integration)
-    pub allowances: MapView<String, U256>,
+    pub allowances: MapView<(Account, Account), U256>,
+
+    /// Allowance expiries, keyed like `allowances` (absent = never expires)
+    pub allowance_expiries: MapView<(Account, Account), Timestamp>,
+
+    /// Revoked allowances, keyed like `allowances` → revocation time
+    /// Cleared when the owner approves the spender again
+    pub revoked_allowances: MapView<(Account, Account), Timestamp>,
+
+    /// Operator approvals: (owner, operator) → () for unlimited transfer rights
+    pub operators: MapView<(Account, Account), ()>,
+
+    /// Next permit nonce of each owner (absent = 0)
+    pub permit_nonces: MapView<Account, u64>,
+
+    /// Secondary index: owner → spenders with a non-zero allowance
+    pub approvals_by_owner: MapView<Account, Vec<Account>>,
+
+    /// Secondary index: spender → owners that granted a non-zero allowance
+    pub approvals_by_spender: MapView<Account, Vec<Account>>,
+
+    /// Metadata audit trail: revision → (timestamp, metadata)
+    /// Revision 0 is the metadata the token was created with
+    pub metadata_history: MapView<u64, (Timestamp, TokenMetadata)>,
+
+    /// Number of recorded metadata revisions
+    pub metadata_revision_count: RegisterView<u64>,
+
+    /// Receipt of the liquidity transfer made at graduation (reused on retries)
+    pub graduation_receipt: RegisterView<Option<TransferReceipt>>,
+
+    /// Stablecoin liquidity sent at graduation (reused on retries)
+    pub graduation_stable_leg: RegisterView<Option<StableLeg>>,
+
+    /// Stablecoin deposited with `DepositStableLiquidity` for the stable pool
+    pub stable_deposit: RegisterView<Amount>,
+
+    /// Share of the raise burned at graduation per the `GraduationPlan`;
+    /// stays in the application account and is never withdrawn
+    pub graduation_burned: RegisterView<Amount>,
+
+    /// Share of the raise paid to the creator at graduation
+    pub graduation_creator_payout: RegisterView<Amount>,
+
+    /// Unsold supply burned at graduation (`burn_unsold_on_graduation`)
+    pub unsold_burned: RegisterView<U256>,
+
+    /// Tokens the swap chain left out of the graduation pool so it opened at
+    /// the final launch price, burned on `PoolCreated`
+    pub pool_excess_burned: RegisterView<U256>,
+
+    /// Set once the launch missed `min_raise` by its deadline
+    pub launch_failure: RegisterView<Option<LaunchFailure>>,
+
+    /// Snipe tax charged on early buys, wherever it went
+    pub snipe_tax_collected: RegisterView<Amount>,
+
+    /// Snipe tax kept by the application as a burn; never paid out
+    pub snipe_tax_burned: RegisterView<Amount>,
+
+    /// Time of each account's latest curve trade, kept only while
+    /// `min_trade_interval_micros` is configured
+    pub last_trade_at: MapView<Account, Timestamp>,
+
+    /// Bounced tracked messages: "{kind}:{token_id}" → PendingMessage
+    pub pending_messages: MapView<String, PendingMessage>,
+
+    /// Latest critical log lines, mirrored on-chain for operators
+    pub critical_events: CriticalEventLog,
+
+    /// Sequenced log of externally relevant events for indexers
+    pub outbox: Outbox,
+
+    /// Refund queue for buys rejected after payment: refund_id → Refund
+    pub refunds: MapView<u64, Refund>,
+
+    /// Number of queued refunds (next refund_id)
+    pub refund_count: RegisterView<u64>,
+
+    /// Total native amount refunded to buyers
+    pub total_refunded: RegisterView<Amount>,
+
+    /// Rolling hourly trade volume buckets: hour index → base currency volume
+    /// Buckets older than the 24h window are pruned on write
+    pub volume_buckets: MapView<u64, U256>,
+
+    /// Rolling daily trade volume buckets: day index → base currency volume
+    /// Buckets older than the 7 day window are pruned on write
+    pub daily_volume: MapView<u64, U256>,
+
+    /// Raise milestones reached: share of the target raise in bps → first crossing time
+    pub milestones: MapView<u16, Timestamp>,
+
+    /// When a summary was last sent to the factory chain
+    pub last_summary_sync: RegisterView<Option<Timestamp>>,
+
+    /// Last price published on the price feed stream
+    pub last_price_update: RegisterView<Option<PriceUpdate>>,
+
+    /// Total tokens burned by dust sweeps
+    pub dust_swept_total: RegisterView<U256>,
+
+    /// Total balances removed by dust sweeps
+    pub dust_swept_accounts: RegisterView<u64>,
+
+    /// Number of dust sweeps executed
+    pub dust_sweep_count: RegisterView<u64>,
+
+    /// Position in `balances` the next dust sweep resumes from
+    pub dust_sweep_position: RegisterView<u64>,
+
+    /// Tokens bought back from the DEX pools with swap fees and burned
+    ///
+    /// They were pool liquidity rather than anyone's balance, so they come
+    /// off the supply reported to the swap chain, not `current_supply`.
+    pub buyback_burned: RegisterView<U256>,
+
+    /// Number of buyback burns received
+    pub buyback_burn_count: RegisterView<u64>,
+
+    /// Stake-to-earn positions and fee-funded rewards
+    pub staking: StakingState,
+
+    /// Escrowed tokens: (account, unlock time) → amount
+    pub locked_balances: MapView<(Account, Timestamp), U256>,
+
+    /// Total tokens held in escrow
+    pub total_locked: RegisterView<U256>,
+
+    /// Merkle balance snapshots and their reward pools
+    pub snapshots: SnapshotState,
+
+    /// Recurring buy schedules and their escrowed budgets
+    pub recurring_buys: RecurringBuyState,
+
+    /// Co-creators and their pending creator-action proposals
+    pub creator_set: CreatorSetState,
+
+    /// Accounts allowed to buy during the whitelist phase (presence marks an account)
+    pub whitelist: MapView<Account, ()>,
+
+    /// Number of whitelisted accounts
+    pub whitelist_count: RegisterView<u64>,
+
+    /// Bids and settlement of a Dutch auction launch (unused on the curve)
+    pub auction: AuctionState,
+
+    /// Commitments and settlement of a fixed-price sale launch (unused on the curve)
+    pub sale: SaleState,
+
+    /// Base currency the application holds by its own bookkeeping: every
+    /// payment into and out of the application account moves this ledger
+    pub native_reserves: RegisterView<Amount>,
+
+    /// Creator fees paid out on curve trades, whether by the buyer or the application
+    pub fees_paid_out: RegisterView<Amount>,
+
+    /// Base currency returned through refunds (rejected buys, closed recurring buys)
+    pub refunds_issued: RegisterView<Amount>,
+
+    /// Change counter reported under `STATE_VERSION_EXTENSION`
+    pub state_version: RegisterView<u64>,
+}
+
+/// Tracked totals compared against the records they summarize
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reconciliation {
+    pub current_supply: U256,
+    /// Sum of all holder balances
+    pub balances_total: U256,
+    pub trade_count: u64,
+    /// Number of entries in the trades maps, legacy keys included, plus the
+    /// pruned trades
+    pub trades_recorded: u64,
+    /// Native amount the application would pay out if all supply were sold
+    /// (None if it cannot be computed)
+    pub sell_out_value: Option<Amount>,
+    /// Base currency the application should hold by its own bookkeeping
+    pub ledger_reserves: Amount,
+    /// Whether the raise still sits with the application (false after graduation)
+    pub holds_reserve: bool,
+}
+
+impl Reconciliation {
+    /// Describe every invariant that does not hold, given the application's native balance
+    ///
+    /// The balance is held to the reserve ledger rather than to the sell-out
+    /// value: buys pay the creator fee out of their cost, so the application
+    /// never receives the full curve integral.
+    pub fn violations(&self, native_balance: Amount) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if self.balances_total != self.current_supply {
+            violations.push(format!(
+                "Sum of balances {} differs from current supply {}",
+                self.balances_total, self.current_supply
+            ));
+        }
+
+        if self.trades_recorded != self.trade_count {
+            violations.push(format!(
+                "Trade count {} differs from {} recorded trades",
+                self.trade_count, self.trades_recorded
+            ));
+        }
+
+        if self.holds_reserve {
+            if native_balance < self.ledger_reserves {
+                violations.push(format!(
+                    "Native balance {} is below the ledgered reserves {}",
+                    native_balance, self.ledger_reserves
+                ));
+            }
+            if self.sell_out_value.is_none() {
+                violations.push("Sell-out value is not representable".to_string());
+            }
+        }
+
+        violations
+    }
 }
 
 impl TokenState {
+    /// Bump `state_version` if anything changed since the last save
+    pub async fn bump_version(&mut self) {
+        if self.has_pending_changes().await {
+            let version = *self.state_version.get();
+            self.state_version.set(version + 1);
+        }
+    }
+
     /// Initialize new token
     pub async fn initialize(
         &mut self,
@@ -68,13 +419,19 @@ impl TokenState {
     ) -> Result<(), anyhow::Error> {
         self.token_id.set(token_id);
         self.creator.set(Some(creator));
+        self.metadata_revision_count.set(0);
+        self.record_metadata_revision(metadata.clone(), created_at)?;
         self.metadata.set(metadata);
         self.curve_config.set(curve_config);
         self.current_supply.set(U256::zero());
         self.total_raised.set(U256::zero());
         self.is_graduated.set(false);
+        self.graduation_started.set(false);
         self.created_at.set(created_at);
         self.dex_pool_id.set(None);
+        self.pool_attestation.set(None);
+        self.stable_pool_id.set(None);
+        self.stable_pool_attestation.set(None);
         self.holder_count.set(0);
         self.trade_count.set(0);
         Ok(())
@@ -87,6 +444,13 @@ impl TokenState {
 
     /// Set user balance
     pub async fn set_balance(&mut self, account: Account, balance: U256) -> Result<(), anyhow::Error> {  // Changed from ChainId to Account
+        if let Some(previous) = self.balances.get(&account).await? {
+            self.shift_holder_bucket(previous, false).await?;
+        }
+        if !balance.is_zero() {
+            self.shift_holder_bucket(balance, true).await?;
+        }
+
         if balance == U256::zero() {
             self.balances.remove(&account)?;
             // Decrement holder count if balance goes to zero
@@ -105,16 +469,322 @@ impl TokenState {
                 self.holder_count.set(*current_count + 1);
             }
         }
+        self.track_top_holder(account, balance);
+        Ok(())
+    }
+
+    /// Fold a balance change into `top_holders`
+    ///
+    /// A tracked holder whose balance falls is kept at its new balance while
+    /// it still ranks, so the largest tracked balance can trail an untracked
+    /// one only once all tracked holders have sold below it.
+    fn track_top_holder(&mut self, account: Account, balance: U256) {
+        let top_holders = self.top_holders.get_mut();
+        top_holders.retain(|(holder, _)| *holder != account);
+        if !balance.is_zero() {
+            top_holders.push((account, balance));
+            top_holders.sort_by(|a, b| b.1.cmp(&a.1));
+            top_holders.truncate(TOP_HOLDERS_TRACKED);
+        }
+    }
+
+    /// Fold up to `max_entries` balances recorded before `top_holders` was
+    /// kept into it, returning how many were folded
+    pub async fn backfill_top_holders(
+        &mut self,
+        max_entries: usize,
+    ) -> Result<usize, anyhow::Error> {
+        let done = *self.top_holders_backfilled.get() as usize;
+        let mut folded = 0;
+        for account in self.balances.indices().await?.into_iter().skip(done).take(max_entries) {
+            if let Some(balance) = self.balances.get(&account).await? {
+                self.track_top_holder(account, balance);
+            }
+            folded += 1;
+        }
+        self.top_holders_backfilled.set((done + folded) as u64);
+        Ok(folded)
+    }
+
+    /// Distribution bucket of a holder with `balance`
+    fn holder_bucket(&self, balance: U256) -> u8 {
+        let max_supply = self.curve_config.get().max_supply;
+        HOLDER_BUCKET_DIVISORS
+            .iter()
+            .position(|divisor| balance.saturating_mul(U256::from(*divisor)) < max_supply)
+            .unwrap_or(HOLDER_BUCKET_DIVISORS.len()) as u8
+    }
+
+    /// Count a holder with `balance` in or out of its distribution bucket
+    async fn shift_holder_bucket(
+        &mut self,
+        balance: U256,
+        added: bool,
+    ) -> Result<(), anyhow::Error> {
+        let bucket = self.holder_bucket(balance);
+        let holders = self.holder_buckets.get(&bucket).await?.unwrap_or(0);
+        let holders = if added { holders + 1 } else { holders.saturating_sub(1) };
+        self.holder_buckets.insert(&bucket, holders)?;
+        Ok(())
+    }
+
+    /// Holders per distribution bucket, smallest holders first
+    pub async fn holder_distribution(&self) -> Result<Vec<u64>, anyhow::Error> {
+        let mut distribution = Vec::new();
+        for bucket in 0..HOLDER_BUCKET_LABELS.len() as u8 {
+            distribution.push(self.holder_buckets.get(&bucket).await?.unwrap_or(0));
+        }
+        Ok(distribution)
+    }
+
+    /// Move tokens from an account's balance into its stake
+    pub async fn stake(
+        &mut self,
+        account: Account,
+        amount: U256,
+        now: Timestamp,
+    ) -> Result<(), anyhow::Error> {
+        let balance = self.get_balance(&account).await;
+        anyhow::ensure!(balance >= amount, "Insufficient balance: have {}, need {}", balance, amount);
+        self.set_balance(account, balance - amount).await?;
+        self.staking.stake(account, amount, now).await?;
         Ok(())
     }
 
-    /// Record a trade
-    pub async fn record_trade(
+    /// Move tokens from an account's stake back into its balance
+    pub async fn unstake(
         &mut self,
-        trade_id: String,
-        trade: Trade,
+        account: Account,
+        amount: U256,
+        now: Timestamp,
     ) -> Result<(), anyhow::Error> {
-        self.trades.insert(&trade_id, trade.clone())?;
+        let staked = self.staking.staked(&account).await?;
+        anyhow::ensure!(staked >= amount, "Insufficient stake: have {}, need {}", staked, amount);
+        self.staking.unstake(account, amount, now).await?;
+        let balance = self.get_balance(&account).await;
+        self.set_balance(account, balance + amount).await?;
+        Ok(())
+    }
+
+    /// Move tokens from an account's balance into escrow until `unlock_at`
+    ///
+    /// Locks with the same unlock time are merged.
+    pub async fn lock_tokens(
+        &mut self,
+        account: Account,
+        amount: U256,
+        unlock_at: Timestamp,
+    ) -> Result<(), anyhow::Error> {
+        let balance = self.get_balance(&account).await;
+        anyhow::ensure!(balance >= amount, "Insufficient balance: have {}, need {}", balance, amount);
+        self.set_balance(account, balance - amount).await?;
+
+        let key = (account, unlock_at);
+        let locked = self.locked_balances.get(&key).await?.unwrap_or_default();
+        self.locked_balances.insert(&key, locked + amount)?;
+        let total_locked = *self.total_locked.get();
+        self.total_locked.set(total_locked + amount);
+        Ok(())
+    }
+
+    /// Release every lock of an account that has expired by `now` back into
+    /// its balance, returning the amount released
+    pub async fn claim_unlocked(
+        &mut self,
+        account: Account,
+        now: Timestamp,
+    ) -> Result<U256, anyhow::Error> {
+        let mut released = U256::zero();
+        for key in self.locked_balances.indices().await? {
+            let (owner, unlock_at) = key;
+            if owner != account || unlock_at > now {
+                continue;
+            }
+            if let Some(amount) = self.locked_balances.get(&key).await? {
+                released += amount;
+            }
+            self.locked_balances.remove(&key)?;
+        }
+
+        if !released.is_zero() {
+            let total_locked = *self.total_locked.get();
+            self.total_locked.set(total_locked.saturating_sub(released));
+            let balance = self.get_balance(&account).await;
+            self.set_balance(account, balance + released).await?;
+        }
+        Ok(released)
+    }
+
+    /// Locks still held in escrow, optionally only those of one account
+    pub async fn get_locks(
+        &self,
+        account: Option<&Account>,
+    ) -> Result<Vec<(Account, Timestamp, U256)>, anyhow::Error> {
+        let mut locks = Vec::new();
+        for key in self.locked_balances.indices().await? {
+            if account.is_some_and(|account| *account != key.0) {
+                continue;
+            }
+            if let Some(amount) = self.locked_balances.get(&key).await? {
+                locks.push((key.0, key.1, amount));
+            }
+        }
+        Ok(locks)
+    }
+
+    /// Merkle leaves over every non-zero balance, in account order, and their total
+    pub async fn balance_leaves(
+        &self,
+        snapshot_id: u64,
+    ) -> Result<(Vec<CryptoHash>, U256), anyhow::Error> {
+        let mut leaves = Vec::new();
+        let mut total = U256::zero();
+        for account in self.balances.indices().await? {
+            if let Some(balance) = self.balances.get(&account).await? {
+                if !balance.is_zero() {
+                    leaves.push(merkle::leaf_hash(snapshot_id, account, balance));
+                    total += balance;
+                }
+            }
+        }
+        Ok((leaves, total))
+    }
+
+    /// Base currency held by the application for stakers, snapshot claimants
+    /// and recurring buy budgets, plus the share burned at graduation
+    pub fn reserved_base(&self) -> Amount {
+        self.staking
+            .reward_reserve
+            .get()
+            .saturating_add(*self.snapshots.reward_reserve.get())
+            .saturating_add(*self.recurring_buys.escrow.get())
+            .saturating_add(*self.auction.refund_reserve.get())
+            .saturating_add(*self.graduation_burned.get())
+            .saturating_add(*self.snipe_tax_burned.get())
+    }
+
+    /// Burn the non-zero balances below `threshold` among the next
+    /// `MAX_BALANCES_SWEPT_PER_CALL`, resuming where the last sweep stopped
+    ///
+    /// Balances of `exempt` accounts are left alone. Swept tokens leave the
+    /// supply. Returns the number of balances removed and the amount burned.
+    /// Once a sweep reaches the last balance the next one starts over.
+    pub async fn sweep_dust(
+        &mut self,
+        threshold: U256,
+        exempt: &[Account],
+    ) -> Result<(u64, U256), anyhow::Error> {
+        let start = *self.dust_sweep_position.get() as usize;
+        let mut position = 0;
+        let mut batch = Vec::new();
+        self.balances
+            .for_each_index_while(|account| {
+                if position >= start {
+                    batch.push(account);
+                }
+                position += 1;
+                Ok(batch.len() < MAX_BALANCES_SWEPT_PER_CALL)
+            })
+            .await?;
+
+        let mut dust = Vec::new();
+        for account in &batch {
+            if exempt.contains(account) {
+                continue;
+            }
+            if let Some(balance) = self.balances.get(account).await? {
+                if balance < threshold {
+                    dust.push((*account, balance));
+                }
+            }
+        }
+
+        let mut swept = U256::zero();
+        for (account, balance) in &dust {
+            self.set_balance(*account, U256::zero()).await?;
+            swept += *balance;
+        }
+
+        let current_supply = *self.current_supply.get();
+        self.current_supply.set(current_supply.saturating_sub(swept));
+
+        // Swept balances give up their positions, so the next batch starts
+        // that many places earlier
+        let next = if batch.len() < MAX_BALANCES_SWEPT_PER_CALL {
+            0
+        } else {
+            start + batch.len() - dust.len()
+        };
+        self.dust_sweep_position.set(next as u64);
+
+        let accounts = dust.len() as u64;
+        let total = *self.dust_swept_total.get();
+        self.dust_swept_total.set(total + swept);
+        let total_accounts = *self.dust_swept_accounts.get();
+        self.dust_swept_accounts.set(total_accounts + accounts);
+        let sweeps = *self.dust_sweep_count.get();
+        self.dust_sweep_count.set(sweeps + 1);
+
+        Ok((accounts, swept))
+    }
+
+    /// Record tokens a pool bought back and burned
+    pub fn record_buyback_burn(&mut self, amount: U256) {
+        let burned = *self.buyback_burned.get();
+        self.buyback_burned.set(burned.saturating_add(amount));
+        let burns = *self.buyback_burn_count.get();
+        self.buyback_burn_count.set(burns + 1);
+    }
+
+    /// Supply left once buyback burns are taken out
+    pub fn supply_after_buybacks(&self) -> U256 {
+        self.current_supply.get().saturating_sub(*self.buyback_burned.get())
+    }
+
+    /// Record a transfer and its memo in the outbox, returning the event's
+    /// sequence number
+    ///
+    /// Transfers are not trades, so the outbox is where their memos are kept.
+    pub fn record_transfer(
+        &mut self,
+        from: Account,
+        to: Account,
+        amount: U256,
+        memo: Option<String>,
+        now: Timestamp,
+    ) -> Result<u64, anyhow::Error> {
+        let transfer = serde_json::json!({
+            "from": from,
+            "to": to,
+            "amount": amount,
+            "memo": memo,
+        });
+        Ok(self.outbox.push("Transfer", Some(self.token_id.get()), &transfer, now)?)
+    }
+
+    /// Record a trade under the next sequence number, returning it
+    pub async fn record_trade(&mut self, mut trade: Trade) -> Result<u64, anyhow::Error> {
+        let sequence = *self.trade_count.get();
+
+        // Flag round trips before the trade is stored and published
+        let mut stats = self
+            .wash_stats
+            .get(&trade.trader)
+            .await?
+            .unwrap_or_else(|| WashTradeStats::new(trade.timestamp));
+        trade.flagged = stats.record(&trade);
+        self.wash_stats.insert(&trade.trader, stats)?;
+        self.trader_sketch.get_mut().insert(&trade.trader);
+        // Flagged round trips earn no tier discount
+        if !self.curve_config.get().fee_tiers.is_empty() && !trade.flagged {
+            let mut volume = self.trader_volumes.get(&trade.trader).await?.unwrap_or_default();
+            volume.record(trade.timestamp, trade.currency_amount);
+            self.trader_volumes.insert(&trade.trader, volume)?;
+        }
+
+        self.trades.insert(&sequence, trade.clone())?;
+        self.outbox.push("Trade", Some(&trade.token_id), &trade, trade.timestamp)?;
+        self.import_sealed.set(true);
 
         // Update user position
         let mut position = self.user_positions
@@ -133,90 +803,1323 @@ impl TokenState {
         } else {
             position.balance = position.balance.saturating_sub(trade.token_amount);
         }
+        self.trades_by_trader.insert(&(trade.trader, position.trades_count), sequence)?;
         position.trades_count += 1;
 
         self.user_positions.insert(&trade.trader, position)?;
 
         // Increment trade count
-        let count = self.trade_count.get();
-        self.trade_count.set(*count + 1);
+        self.trade_count.set(sequence + 1);
 
-        Ok(())
+        // Add to the current hourly volume bucket and prune buckets outside the window
+        let bucket = trade.timestamp.micros() / VOLUME_BUCKET_MICROS;
+        let bucket_volume = self.volume_buckets.get(&bucket).await?.unwrap_or_default();
+        self.volume_buckets.insert(&bucket, bucket_volume + trade.currency_amount)?;
+
+        let oldest = bucket.saturating_sub(VOLUME_WINDOW_BUCKETS - 1);
+        for stale in self.volume_buckets.indices().await? {
+            if stale < oldest {
+                self.volume_buckets.remove(&stale)?;
+            }
+        }
+
+        // Same for the daily buckets behind the weekly volume
+        let day = trade.timestamp.micros() / VOLUME_DAY_MICROS;
+        let day_volume = self.daily_volume.get(&day).await?.unwrap_or_default();
+        self.daily_volume.insert(&day, day_volume + trade.currency_amount)?;
+
+        let oldest_day = day.saturating_sub(VOLUME_HISTORY_DAYS - 1);
+        for stale in self.daily_volume.indices().await? {
+            if stale < oldest_day {
+                self.daily_volume.remove(&stale)?;
+            }
+        }
+
+        Ok(sequence)
     }
 
-    /// Check if bonding curve has completed
-    pub fn is_curve_complete(&self) -> bool {
-        let current_supply = *self.current_supply.get();
-        let max_supply = self.curve_config.get().max_supply;
-        current_supply >= max_supply
+    /// Base currency `account` traded on the curve over the 30 days up to `now`
+    pub async fn trader_volume(
+        &self,
+        account: &Account,
+        now: Timestamp,
+    ) -> Result<U256, anyhow::Error> {
+        let volume = self.trader_volumes.get(account).await?;
+        Ok(volume.map_or(U256::zero(), |volume| volume.total(now)))
     }
 
-    /// Get all trades (paginated)
-    pub async fn get_trades(&self, offset: usize, limit: usize) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let mut count = 0;
-        let mut skipped = 0;
+    /// Creator fee rate charged to `account` at `now`, after the discount of
+    /// the fee tier its volume reached
+    pub async fn creator_fee_bps_for(
+        &self,
+        account: &Account,
+        now: Timestamp,
+    ) -> Result<u16, anyhow::Error> {
+        let config = self.curve_config.get();
+        if config.fee_tiers.is_empty() {
+            return Ok(config.creator_fee_bps);
+        }
+        let volume = self.trader_volume(account, now).await?;
+        let discount_bps = fee_tiers::discount_bps(&config.fee_tiers, volume);
+        Ok(fee_tiers::discounted_fee_bps(config.creator_fee_bps, discount_bps))
+    }
 
-        for entry in self.trades.indices().await.unwrap() {
-            if skipped < offset {
-                skipped += 1;
-                continue;
+    /// Up to `limit` accounts with flagged trades, largest flagged volume first
+    pub async fn suspicious_accounts(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(Account, WashTradeStats)>, anyhow::Error> {
+        let mut accounts = Vec::new();
+        for account in self.wash_stats.indices().await? {
+            if let Some(stats) = self.wash_stats.get(&account).await? {
+                if stats.flagged_trades > 0 {
+                    accounts.push((account, stats));
+                }
             }
+        }
+        accounts.sort_by(|(_, a), (_, b)| b.flagged_volume.cmp(&a.flagged_volume));
+        accounts.truncate(limit);
+        Ok(accounts)
+    }
 
-            if count >= limit {
-                break;
+    /// Up to `limit` balances from the `offset`-th holder on, in key order
+    pub async fn export_balances(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(Account, U256)>, anyhow::Error> {
+        let mut entries = Vec::new();
+        for account in self.balances.indices().await?.into_iter().skip(offset).take(limit) {
+            if let Some(balance) = self.balances.get(&account).await? {
+                entries.push((account, balance));
             }
+        }
+        Ok(entries)
+    }
 
-            if let Ok(Some(trade)) = self.trades.get(&entry).await {
-                trades.push(trade);
-                count += 1;
+    /// Up to `limit` positions from the `offset`-th trader on, in key order
+    pub async fn export_positions(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(Account, UserPosition)>, anyhow::Error> {
+        let mut entries = Vec::new();
+        for account in self.user_positions.indices().await?.into_iter().skip(offset).take(limit) {
+            if let Some(position) = self.user_positions.get(&account).await? {
+                entries.push((account, position));
             }
         }
+        Ok(entries)
+    }
 
-        trades
+    /// Apply an exported chunk, returning the number of entries applied
+    ///
+    /// Trades must arrive in sequence order and before the positions, which
+    /// overwrite the ones rebuilt from the trades.
+    /// Allow the chunks with these commitments to be imported
+    pub fn approve_imports(&mut self, commitments: &[CryptoHash]) -> Result<(), anyhow::Error> {
+        for commitment in commitments {
+            self.approved_imports.insert(commitment, ())?;
+        }
+        Ok(())
     }
 
-    /// Create allowance key from owner and spender accounts
-    fn allowance_key(owner: &Account, spender: &Account) -> String {
-        format!("{}:{}",
-            serde_json::to_string(owner).unwrap_or_default(),
-            serde_json::to_string(spender).unwrap_or_default()
-        )
+    /// Use up the approval of `chunk`, returning whether there was one
+    pub async fn take_import_approval(
+        &mut self,
+        chunk: &StateChunk,
+    ) -> Result<bool, anyhow::Error> {
+        let commitment = chunk.commitment();
+        if !self.approved_imports.contains_key(&commitment).await? {
+            return Ok(false);
+        }
+        self.approved_imports.remove(&commitment)?;
+        Ok(true)
     }
 
-    /// Get allowance amount that spender can spend on behalf of owner
-    pub async fn get_allowance(&self, owner: &Account, spender: &Account) -> U256 {
-        let key = Self::allowance_key(owner, spender);
-        self.allowances.get(&key).await.unwrap_or(None).unwrap_or(U256::zero())
+    pub async fn import_chunk(&mut self, chunk: StateChunk) -> Result<usize, anyhow::Error> {
+        let applied = chunk.len();
+        match chunk {
+            StateChunk::Balances(entries) => {
+                for (account, balance) in entries {
+                    if balance.is_zero() {
+                        continue;
+                    }
+                    let previous = self.get_balance(&account).await;
+                    self.set_balance(account, balance).await?;
+                    let supply = *self.current_supply.get() - previous + balance;
+                    self.current_supply.set(supply);
+                }
+            }
+            StateChunk::Positions(entries) => {
+                for (account, position) in entries {
+                    self.user_positions.insert(&account, position)?;
+                }
+            }
+            StateChunk::Trades(entries) => {
+                for (sequence, trade) in entries {
+                    // A gap before the first trade is the source's pruned range
+                    let expected = *self.trade_count.get();
+                    let pruned_gap = expected == *self.pruned_trades.get() && sequence > expected;
+                    anyhow::ensure!(
+                        sequence == expected || pruned_gap,
+                        "Trade {} imported out of order, expected {}",
+                        sequence,
+                        expected
+                    );
+                    if pruned_gap {
+                        self.pruned_trades.set(sequence);
+                    }
+                    let mut position = self.user_positions
+                        .get(&trade.trader)
+                        .await?
+                        .unwrap_or(UserPosition {
+                            token_id: self.token_id.get().clone(),
+                            balance: U256::zero(),
+                            total_invested: U256::zero(),
+                            trades_count: 0,
+                        });
+                    self.trades_by_trader.insert(&(trade.trader, position.trades_count), sequence)?;
+                    position.trades_count += 1;
+                    self.user_positions.insert(&trade.trader, position)?;
+                    self.trader_sketch.get_mut().insert(&trade.trader);
+                    self.trades.insert(&sequence, trade)?;
+                    self.trade_count.set(sequence + 1);
+                }
+            }
+        }
+        Ok(applied)
     }
 
-    /// Set allowance amount (approve)
-    pub async fn set_allowance(&mut self, owner: Account, spender: Account, amount: U256) -> Result<(), anyhow::Error> {
-        let key = Self::allowance_key(&owner, &spender);
-        self.allowances.insert(&key, amount)?;
-        Ok(())
+    /// Delete the oldest trades so that at most `keep_last` remain, up to
+    /// `MAX_TRADES_PRUNED_PER_CALL` at a time
+    ///
+    /// Returns the pruned sequence range `[from, to)`, or None if nothing was
+    /// old enough. The range is recorded in the outbox for indexers.
+    pub async fn prune_trades(
+        &mut self,
+        keep_last: u64,
+        now: Timestamp,
+    ) -> Result<Option<(u64, u64)>, anyhow::Error> {
+        let from = *self.pruned_trades.get();
+        let to = self
+            .trade_count
+            .get()
+            .saturating_sub(keep_last)
+            .min(from.saturating_add(MAX_TRADES_PRUNED_PER_CALL));
+        // Legacy trades are not in the trader index yet, so their lots could
+        // not be carried over
+        if to <= from || self.legacy_trades.count().await? > 0 {
+            return Ok(None);
+        }
+
+        for sequence in from..to {
+            if let Some(trade) = self.trades.get(&sequence).await? {
+                self.prune_trader_entry(&trade, sequence).await?;
+            }
+            self.trades.remove(&sequence)?;
+        }
+        self.pruned_trades.set(to);
+        let range = serde_json::json!({ "from": from, "to": to });
+        self.outbox.push("TradesPruned", Some(self.token_id.get()), &range, now)?;
+        Ok(Some((from, to)))
     }
 
-    /// Decrease allowance amount (used in transferFrom)
-    pub async fn decrease_allowance(&mut self, owner: &Account, spender: &Account, amount: U256) -> Result<(), anyhow::Error> {
-        let current = self.get_allowance(owner, spender).await;
-        let new_allowance = current.saturating_sub(amount);
-        let key = Self::allowance_key(owner, spender);
-        self.allowances.insert(&key, new_allowance)?;
+    /// Drop a pruned trade from its trader's index, carrying its lots over
+    async fn prune_trader_entry(
+        &mut self,
+        trade: &Trade,
+        sequence: u64,
+    ) -> Result<(), anyhow::Error> {
+        let trader = trade.trader;
+        let mut lots = self.carried_lots.get(&trader).await?.unwrap_or_default();
+        tax::carry_lots(&mut lots, trade);
+        if lots.is_empty() {
+            self.carried_lots.remove(&trader)?;
+        } else {
+            self.carried_lots.insert(&trader, lots)?;
+        }
+
+        // Trades are pruned oldest first, so the trader's oldest remaining
+        // entry is this one, unless it predates the index
+        let position = self.pruned_trader_trades.get(&trader).await?.unwrap_or_default();
+        if self.trades_by_trader.get(&(trader, position)).await? == Some(sequence) {
+            self.trades_by_trader.remove(&(trader, position))?;
+            self.pruned_trader_trades.insert(&trader, position + 1)?;
+        }
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use linera_sdk::linera_base_types::ChainId;
-    use linera_views::memory::MemoryContext;
+    /// Trade volume over the 24h window ending at `now`
+    pub async fn volume_24h(&self, now: Timestamp) -> Result<U256, anyhow::Error> {
+        let current = now.micros() / VOLUME_BUCKET_MICROS;
+        let oldest = current.saturating_sub(VOLUME_WINDOW_BUCKETS - 1);
 
-    #[tokio::test]
-    async fn test_token_state_initialization() {
-        let context = MemoryContext::default();
-        let mut state = TokenState::load(context).await.unwrap();
+        let mut volume = U256::zero();
+        for bucket in oldest..=current {
+            if let Some(bucket_volume) = self.volume_buckets.get(&bucket).await? {
+                volume += bucket_volume;
+            }
+        }
+        Ok(volume)
+    }
+
+    /// Trade volume over the current day and the 6 before it
+    pub async fn volume_7d(&self, now: Timestamp) -> Result<U256, anyhow::Error> {
+        let current = now.micros() / VOLUME_DAY_MICROS;
+        let oldest = current.saturating_sub(VOLUME_HISTORY_DAYS - 1);
+
+        let mut volume = U256::zero();
+        for day in oldest..=current {
+            if let Some(day_volume) = self.daily_volume.get(&day).await? {
+                volume += day_volume;
+            }
+        }
+        Ok(volume)
+    }
+
+    /// Record the raise milestones crossed for the first time, returning them
+    ///
+    /// Milestones are sticky: a sell that pulls the raise back under one does
+    /// not clear it, and crossing it again is not reported twice.
+    pub async fn record_milestones(&mut self, now: Timestamp) -> Result<Vec<u16>, anyhow::Error> {
+        let target_raise = self.curve_config.get().target_raise;
+        if target_raise.is_zero() {
+            return Ok(Vec::new());
+        }
+        let progress_bps = wad::mul_div(*self.total_raised.get(), U256::from(10_000), target_raise)
+            .unwrap_or(U256::MAX);
+
+        let mut reached = Vec::new();
+        for milestone in RAISE_MILESTONES_BPS {
+            if progress_bps >= U256::from(milestone)
+                && !self.milestones.contains_key(&milestone).await?
+            {
+                self.milestones.insert(&milestone, now)?;
+                reached.push(milestone);
+            }
+        }
+        Ok(reached)
+    }
+
+    /// Largest tracked balance as a share of the current supply, in basis points
+    pub fn top_holder_bps(&self) -> u16 {
+        let current_supply = *self.current_supply.get();
+        if current_supply.is_zero() {
+            return 0;
+        }
+
+        let largest = self.top_holders.get().first().map_or(U256::zero(), |(_, balance)| *balance);
+        (largest.min(current_supply) * U256::from(10_000) / current_supply).as_u32() as u16
+    }
+
+    /// Snapshot of the listing metrics replicated to the factory
+    pub async fn summary(&self, now: Timestamp) -> Result<TokenSummary, anyhow::Error> {
+        let current_supply = *self.current_supply.get();
+        let config = self.curve_config.get();
+        let price = bonding_curve::calculate_current_price(current_supply, config.k, config.scale)
+            .unwrap_or_default();
+        let progress_bps = if config.max_supply.is_zero() {
+            0
+        } else {
+            let bps = current_supply.min(config.max_supply) * U256::from(10_000) / config.max_supply;
+            bps.as_u32() as u16
+        };
+
+        Ok(TokenSummary {
+            price,
+            progress_bps,
+            volume_24h: self.volume_24h(now).await?,
+            volume_7d: self.volume_7d(now).await?,
+            holder_count: *self.holder_count.get(),
+            top_holder_bps: self.top_holder_bps(),
+            updated_at: now,
+        })
+    }
+
+    /// Check if bonding curve has completed
+    pub fn is_curve_complete(&self) -> bool {
+        let current_supply = *self.current_supply.get();
+        let max_supply = self.curve_config.get().max_supply;
+        current_supply >= max_supply
+    }
+
+    /// Whether this launch trades on its bonding curve before graduating
+    pub fn is_bonding_curve(&self) -> bool {
+        self.curve_config.get().launch_mechanism == LaunchMechanism::BondingCurve
+    }
+
+    /// Whether this launch sells its supply through a Dutch auction
+    pub fn is_auction(&self) -> bool {
+        self.curve_config.get().launch_mechanism.auction().is_some()
+    }
+
+    /// Whether this launch sells its supply in a fixed-price sale
+    pub fn is_fixed_price_sale(&self) -> bool {
+        self.curve_config.get().launch_mechanism.fixed_price_sale().is_some()
+    }
+
+    /// List graduation conditions that are not yet satisfied
+    pub fn unmet_graduation_conditions(&self) -> Vec<String> {
+        let mut unmet = Vec::new();
+
+        if self.is_auction() {
+            if !self.auction.is_settled() {
+                unmet.push("Dutch auction has not settled".to_string());
+            } else if self.current_supply.get().is_zero() {
+                unmet.push("Dutch auction sold no tokens".to_string());
+            }
+        } else if self.is_fixed_price_sale() {
+            if !self.sale.is_settled() {
+                unmet.push("Fixed-price sale has not settled".to_string());
+            } else if self.current_supply.get().is_zero() {
+                unmet.push("Fixed-price sale sold no tokens".to_string());
+            }
+        } else if !self.is_curve_complete() {
+            unmet.push("Bonding curve has not reached max supply".to_string());
+        }
+
+        if self.is_failed() {
+            unmet.push("Launch failed to reach its minimum raise by the deadline".to_string());
+        }
+
+        if let Some(min_holders) = self.curve_config.get().min_holders {
+            let holders = *self.holder_count.get();
+            if holders < min_holders {
+                unmet.push(format!(
+                    "Requires {} unique holders, currently {}",
+                    min_holders, holders
+                ));
+            }
+        }
+
+        unmet
+    }
+
+    /// Ledger a payment received by the application
+    pub fn credit_reserves(&mut self, amount: Amount) {
+        let reserves = *self.native_reserves.get();
+        self.native_reserves.set(reserves.saturating_add(amount));
+    }
+
+    /// Ledger a payment sent by the application
+    pub fn debit_reserves(&mut self, amount: Amount) {
+        let reserves = *self.native_reserves.get();
+        self.native_reserves.set(reserves.saturating_sub(amount));
+    }
+
+    /// Count a creator fee payment
+    pub fn record_fee_paid(&mut self, amount: Amount) {
+        let fees = *self.fees_paid_out.get();
+        self.fees_paid_out.set(fees.saturating_add(amount));
+    }
+
+    /// Count a refund payment
+    pub fn record_refund_paid(&mut self, amount: Amount) {
+        let refunds = *self.refunds_issued.get();
+        self.refunds_issued.set(refunds.saturating_add(amount));
+    }
+
+    /// Whether curve trading is closed because graduation has started or completed
+    pub fn is_curve_closed(&self) -> bool {
+        *self.is_graduated.get() || *self.graduation_started.get()
+    }
+
+    /// Whether a curve launch reached its deadline short of `min_raise`
+    /// before graduating (always false without both configured)
+    pub fn missed_min_raise(&self, now: Timestamp) -> bool {
+        let config = self.curve_config.get();
+        let (Some(min_raise), Some(deadline)) = (config.min_raise, config.deadline) else {
+            return false;
+        };
+        self.is_bonding_curve()
+            && !self.is_curve_closed()
+            && now >= deadline
+            && *self.total_raised.get() < min_raise
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.launch_failure.get().is_some()
+    }
+
+    /// Whether the curve may still fill buys: the launch has neither failed
+    /// nor missed its minimum raise
+    pub fn is_launch_live(&self, now: Timestamp) -> bool {
+        !self.is_failed() && !self.missed_min_raise(now)
+    }
+
+    /// Burn `holder`'s whole balance against a failed launch's refund pool,
+    /// returning the tokens burned and the refund owed
+    pub async fn redeem_refund(
+        &mut self,
+        holder: Account,
+    ) -> Result<(U256, Amount), anyhow::Error> {
+        let Some(mut failure) = *self.launch_failure.get() else {
+            anyhow::bail!("Launch has not failed");
+        };
+        let balance = self.get_balance(&holder).await;
+        if balance.is_zero() {
+            return Ok((balance, Amount::ZERO));
+        }
+        let refund = failure.refund_for(balance);
+
+        self.set_balance(holder, U256::zero()).await?;
+        let current_supply = *self.current_supply.get();
+        self.current_supply.set(current_supply.saturating_sub(balance));
+        failure.redeemed = failure.redeemed.saturating_add(refund);
+        self.launch_failure.set(Some(failure));
+        Ok((balance, refund))
+    }
+
+    /// Supply still unsold against `max_supply`
+    pub fn unsold_supply(&self) -> U256 {
+        let current_supply = *self.current_supply.get();
+        self.curve_config.get().max_supply.saturating_sub(current_supply)
+    }
+
+    /// Supply the graduation pool is seeded from: the sold supply, plus the
+    /// unsold remainder unless the launch burns it
+    pub fn graduation_supply(&self) -> U256 {
+        let current_supply = *self.current_supply.get();
+        if self.curve_config.get().burn_unsold_on_graduation {
+            current_supply
+        } else {
+            current_supply.saturating_add(self.unsold_supply())
+        }
+    }
+
+    /// Last price the launch sold at: the curve's marginal price at the current
+    /// supply, the auction's clearing price or the sale price
+    pub fn final_price(&self) -> Option<Price> {
+        let config = self.curve_config.get();
+        let supply = *self.current_supply.get();
+        match config.launch_mechanism {
+            LaunchMechanism::BondingCurve => {
+                bonding_curve::calculate_current_price(supply, config.k, config.scale).ok()
+            }
+            LaunchMechanism::DutchAuction(_) => *self.auction.clearing_price.get(),
+            LaunchMechanism::FixedPriceSale(sale) => Some(sale.price),
+        }
+    }
+
+    /// When the curve opened to every buyer: the end of the whitelist phase,
+    /// or the launch itself without one
+    pub fn curve_opened_at(&self) -> Timestamp {
+        self.curve_config
+            .get()
+            .whitelist_ends_at
+            .unwrap_or(*self.created_at.get())
+    }
+
+    /// Snipe tax rate on a buy at `now`, in basis points
+    pub fn snipe_tax_bps(&self, now: Timestamp) -> u16 {
+        self.curve_config
+            .get()
+            .snipe_tax
+            .map_or(0, |config| snipe_tax::surcharge_bps(&config, self.curve_opened_at(), now))
+    }
+
+    /// Tokens a spend of `spend` base units buys at `now`, and their curve
+    /// cost; the snipe tax comes out of the spend and the buy is capped at
+    /// the unsold supply
+    pub fn tokens_for_spend(
+        &self,
+        spend: U256,
+        now: Timestamp,
+    ) -> Result<(U256, U256), bonding_curve::CurveError> {
+        let config = self.curve_config.get();
+        let tax_bps = u64::from(self.snipe_tax_bps(now));
+        let budget = wad::mul_div(spend, U256::from(10_000), U256::from(10_000 + tax_bps))
+            .ok_or(bonding_curve::CurveError::Overflow)?;
+        let supply = *self.current_supply.get();
+        let affordable =
+            bonding_curve::calculate_tokens_for_cost(supply, budget, config.k, config.scale)?;
+        let amount = affordable.min(config.max_supply.saturating_sub(supply));
+        let cost = bonding_curve::calculate_buy_cost(supply, amount, config.k, config.scale)?;
+        Ok((amount, cost))
+    }
+
+    /// Count a snipe tax payment, setting it aside when it is burned
+    pub fn record_snipe_tax(&mut self, amount: Amount, burned: bool) {
+        let collected = *self.snipe_tax_collected.get();
+        self.snipe_tax_collected.set(collected.saturating_add(amount));
+        if burned {
+            let total = *self.snipe_tax_burned.get();
+            self.snipe_tax_burned.set(total.saturating_add(amount));
+        }
+    }
+
+    /// Whether token transfers are currently permitted by the launch config
+    pub fn transfers_allowed(&self) -> bool {
+        *self.is_graduated.get() || self.curve_config.get().transfers_enabled_during_curve
+    }
+
+    /// Whether the whitelist phase is still running at `now`
+    pub fn whitelist_active(&self, now: Timestamp) -> bool {
+        self.curve_config.get().whitelist_ends_at.is_some_and(|ends_at| now < ends_at)
+    }
+
+    /// Whether `account` was added to the whitelist
+    pub async fn is_whitelisted(&self, account: &Account) -> Result<bool, anyhow::Error> {
+        Ok(self.whitelist.contains_key(account).await?)
+    }
+
+    /// Whether `account` may buy at `now`: anyone once the whitelist phase
+    /// is over, only whitelisted accounts before
+    pub async fn may_buy(&self, account: &Account, now: Timestamp) -> Result<bool, anyhow::Error> {
+        if !self.whitelist_active(now) {
+            return Ok(true);
+        }
+        self.is_whitelisted(account).await
+    }
+
+    /// Microseconds `account` must still wait at `now` before trading again,
+    /// or None if it may trade
+    pub async fn trade_retry_after(
+        &self,
+        account: &Account,
+        now: Timestamp,
+    ) -> Result<Option<u64>, anyhow::Error> {
+        let Some(interval) = self.curve_config.get().min_trade_interval_micros else {
+            return Ok(None);
+        };
+        let Some(last) = self.last_trade_at.get(account).await? else {
+            return Ok(None);
+        };
+        let elapsed = now.delta_since(last).as_micros();
+        Ok((elapsed < interval).then(|| interval - elapsed))
+    }
+
+    /// Remember `account` traded at `now`, if trades are rate limited
+    pub fn record_trade_at(
+        &mut self,
+        account: Account,
+        now: Timestamp,
+    ) -> Result<(), anyhow::Error> {
+        if self.curve_config.get().min_trade_interval_micros.is_some() {
+            self.last_trade_at.insert(&account, now)?;
+        }
+        Ok(())
+    }
+
+    /// Whitelist `accounts`, returning how many were not already on it
+    pub async fn add_to_whitelist(&mut self, accounts: &[Account]) -> Result<u64, anyhow::Error> {
+        let mut added = 0;
+        for account in accounts {
+            if !self.whitelist.contains_key(account).await? {
+                self.whitelist.insert(account, ())?;
+                added += 1;
+            }
+        }
+        let count = *self.whitelist_count.get();
+        self.whitelist_count.set(count + added);
+        Ok(added)
+    }
+
+    /// Up to `limit` whitelisted accounts from the `offset`-th on, in key order
+    pub async fn list_whitelist(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Account>, anyhow::Error> {
+        Ok(self.whitelist.indices().await?.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Check if all graduation conditions are met
+    pub fn can_graduate(&self) -> bool {
+        self.unmet_graduation_conditions().is_empty()
+    }
+
+    /// Queue a refund for a buyer, returning its refund_id
+    pub fn queue_refund(
+        &mut self,
+        buyer: Account,
+        amount: Amount,
+        reason: String,
+        queued_at: Timestamp,
+    ) -> Result<u64, anyhow::Error> {
+        let refund_id = *self.refund_count.get();
+        self.refunds.insert(
+            &refund_id,
+            Refund {
+                buyer,
+                amount,
+                reason,
+                queued_at,
+                issued: false,
+            },
+        )?;
+        self.refund_count.set(refund_id + 1);
+        Ok(refund_id)
+    }
+
+    /// Mark a queued refund as paid out
+    pub async fn mark_refund_issued(&mut self, refund_id: u64) -> Result<(), anyhow::Error> {
+        if let Some(mut refund) = self.refunds.get(&refund_id).await? {
+            if !refund.issued {
+                refund.issued = true;
+                let total = *self.total_refunded.get();
+                self.total_refunded.set(total.saturating_add(refund.amount));
+                self.refunds.insert(&refund_id, refund)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get refunds (paginated, oldest first)
+    pub async fn get_refunds(&self, offset: u64, limit: u64) -> Vec<(u64, Refund)> {
+        let end = offset.saturating_add(limit).min(*self.refund_count.get());
+        let mut refunds = Vec::new();
+        for refund_id in offset..end {
+            if let Ok(Some(refund)) = self.refunds.get(&refund_id).await {
+                refunds.push((refund_id, refund));
+            }
+        }
+        refunds
+    }
+
+    /// Recompute the totals that the accounting invariants compare
+    pub async fn reconcile(&self) -> Result<Reconciliation, anyhow::Error> {
+        // Staked and locked tokens are still part of the supply
+        let mut balances_total =
+            self.staking.total_staked.get().saturating_add(*self.total_locked.get());
+        for account in self.balances.indices().await? {
+            if let Some(balance) = self.balances.get(&account).await? {
+                balances_total = balances_total.saturating_add(balance);
+            }
+        }
+
+        let current_supply = *self.current_supply.get();
+        let curve_config = self.curve_config.get();
+        let sell_out_value = bonding_curve::calculate_sell_return(
+            current_supply,
+            current_supply,
+            curve_config.k,
+            curve_config.scale,
+        )
+        .ok()
+        .and_then(|value| units::to_amount(value, curve_config.base_decimals).ok());
+
+        Ok(Reconciliation {
+            current_supply,
+            balances_total,
+            trade_count: *self.trade_count.get(),
+            trades_recorded: (self.trades.count().await? + self.legacy_trades.count().await?)
+                as u64
+                + *self.pruned_trades.get(),
+            sell_out_value,
+            ledger_reserves: *self.native_reserves.get(),
+            // Auction and sale payments are not backed by curve sells
+            holds_reserve: !self.is_curve_closed() && self.is_bonding_curve(),
+        })
+    }
+
+    /// Up to `limit` of a trader's trades (capped at `MAX_TRADES_PER_QUERY`),
+    /// newest first, read through the per-trader index
+    pub async fn get_trades_by_trader(
+        &self,
+        trader: &Account,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Trade>, anyhow::Error> {
+        let limit = limit.min(MAX_TRADES_PER_QUERY);
+        let count = self
+            .user_positions
+            .get(trader)
+            .await?
+            .map_or(0, |position| position.trades_count);
+        let pruned = self.pruned_trader_trades.get(trader).await?.unwrap_or_default();
+        let newest = count.saturating_sub(offset);
+        let oldest = newest.saturating_sub(limit).max(pruned);
+
+        let mut trades = Vec::new();
+        for sequence in (oldest..newest).rev() {
+            let Some(trade_sequence) = self.trades_by_trader.get(&(*trader, sequence)).await? else {
+                continue;
+            };
+            if let Some(trade) = self.trades.get(&trade_sequence).await? {
+                trades.push(trade);
+            }
+        }
+        Ok(trades)
+    }
+
+    /// A trader's oldest unpruned trades, oldest first, up to
+    /// `MAX_TRADES_PER_QUERY`, and the time of the first newer trade left out
+    pub async fn get_trader_history(
+        &self,
+        trader: &Account,
+    ) -> Result<(Vec<Trade>, Option<Timestamp>), anyhow::Error> {
+        let count = self
+            .user_positions
+            .get(trader)
+            .await?
+            .map_or(0, |position| position.trades_count);
+        let pruned = self.pruned_trader_trades.get(trader).await?.unwrap_or_default();
+        let end = pruned.saturating_add(MAX_TRADES_PER_QUERY).min(count);
+        let mut trades = self
+            .get_trades_by_trader(trader, count - end, end.saturating_sub(pruned))
+            .await?;
+        trades.reverse();
+        let left_out = if end < count {
+            let next = self.get_trades_by_trader(trader, count - end - 1, 1).await?;
+            next.first().map(|trade| trade.timestamp)
+        } else {
+            None
+        };
+        Ok((trades, left_out))
+    }
+
+    /// Get all trades (paginated), oldest first
+    pub async fn get_trades(&self, offset: usize, limit: usize) -> Vec<Trade> {
+        self.get_trades_since(offset as u64, limit as u64)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, trade)| trade)
+            .collect()
+    }
+
+    /// Up to `limit` trades (capped at `MAX_TRADES_PER_QUERY`) with sequence
+    /// numbers from `since` on, in order, starting after any pruned trades
+    pub async fn get_trades_since(
+        &self,
+        since: u64,
+        limit: u64,
+    ) -> Result<Vec<(u64, Trade)>, anyhow::Error> {
+        let since = since.max(*self.pruned_trades.get());
+        let limit = limit.min(MAX_TRADES_PER_QUERY);
+        let end = since.saturating_add(limit).min(*self.trade_count.get());
+        let mut trades = Vec::new();
+        for sequence in since..end {
+            if let Some(trade) = self.trades.get(&sequence).await? {
+                trades.push((sequence, trade));
+            }
+        }
+        Ok(trades)
+    }
+
+    /// Sequence number of a former "{micros}-{sequence}" trade key
+    fn parse_legacy_trade_key(key: &str) -> Option<u64> {
+        key.rsplit_once('-')?.1.parse().ok()
+    }
+
+    /// Move up to `max_entries` legacy trades, oldest first, to `trades` under
+    /// their sequence numbers, returning how many left the legacy keys
+    ///
+    /// Each trade also takes its trader's first free index entry: the legacy
+    /// trades were counted in `trades_count`, so newer trades were indexed
+    /// after them. Keys that do not parse are left in place and skipped.
+    /// Trades in the pruned range are dropped.
+    pub async fn migrate_legacy_trades(
+        &mut self,
+        max_entries: usize,
+    ) -> Result<usize, anyhow::Error> {
+        let mut keys: Vec<(u64, String)> = self
+            .legacy_trades
+            .indices()
+            .await?
+            .into_iter()
+            .filter_map(|key| Some((Self::parse_legacy_trade_key(&key)?, key)))
+            .collect();
+        keys.sort();
+
+        let pruned = *self.pruned_trades.get();
+        let mut next_positions = BTreeMap::new();
+        let mut migrated = 0;
+        for (sequence, key) in keys.into_iter().take(max_entries) {
+            if let Some(trade) = self.legacy_trades.get(&key).await? {
+                if sequence >= pruned {
+                    let trader = trade.trader;
+                    let mut position = match next_positions.get(&trader) {
+                        Some(position) => *position,
+                        None => self.pruned_trader_trades.get(&trader).await?.unwrap_or_default(),
+                    };
+                    while self.trades_by_trader.contains_key(&(trader, position)).await? {
+                        position += 1;
+                    }
+                    self.trades_by_trader.insert(&(trader, position), sequence)?;
+                    next_positions.insert(trader, position + 1);
+                    self.trades.insert(&sequence, Trade::from(trade))?;
+                }
+            }
+            self.legacy_trades.remove(&key)?;
+            migrated += 1;
+        }
+        Ok(migrated)
+    }
+
+    /// Number of trades still stored under legacy keys
+    pub async fn legacy_trade_count(&self) -> Result<usize, anyhow::Error> {
+        Ok(self.legacy_trades.count().await?)
+    }
+
+    /// Former allowance key: owner and spender JSON joined by ':'
+    fn legacy_allowance_key(owner: &Account, spender: &Account) -> String {
+        format!("{}:{}",
+            serde_json::to_string(owner).unwrap_or_default(),
+            serde_json::to_string(spender).unwrap_or_default()
+        )
+    }
+
+    /// Split a former allowance key back into (owner, spender)
+    ///
+    /// Account JSON contains ':' itself, so the owner is parsed as a JSON
+    /// value first and the separator is expected right after it.
+    fn parse_legacy_allowance_key(key: &str) -> Option<(Account, Account)> {
+        let mut values = serde_json::Deserializer::from_str(key).into_iter::<Account>();
+        let owner = values.next()?.ok()?;
+        let spender = key[values.byte_offset()..].strip_prefix(':')?;
+        Some((owner, serde_json::from_str(spender).ok()?))
+    }
+
+    /// Get allowance amount that spender can spend on behalf of owner
+    pub async fn get_allowance(&self, owner: &Account, spender: &Account) -> U256 {
+        if let Ok(Some(amount)) = self.allowances.get(&(*owner, *spender)).await {
+            return amount;
+        }
+        let key = Self::legacy_allowance_key(owner, spender);
+        self.legacy_allowances.get(&key).await.unwrap_or(None).unwrap_or(U256::zero())
+    }
+
+    /// Get allowance expiry, if any
+    pub async fn get_allowance_expiry(&self, owner: &Account, spender: &Account) -> Option<Timestamp> {
+        if let Ok(Some(expires_at)) = self.allowance_expiries.get(&(*owner, *spender)).await {
+            return Some(expires_at);
+        }
+        let key = Self::legacy_allowance_key(owner, spender);
+        self.legacy_allowance_expiries.get(&key).await.unwrap_or(None)
+    }
+
+    /// Move one allowance's legacy entries to the structured maps
+    ///
+    /// Called before every allowance write, so a write never leaves a stale
+    /// legacy entry behind to be read as a fallback.
+    async fn migrate_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+    ) -> Result<(), anyhow::Error> {
+        let key = Self::legacy_allowance_key(&owner, &spender);
+        self.migrate_legacy_key(&key, (owner, spender)).await
+    }
+
+    /// Move the legacy entries under `key` to `(owner, spender)`
+    async fn migrate_legacy_key(
+        &mut self,
+        key: &str,
+        accounts: (Account, Account),
+    ) -> Result<(), anyhow::Error> {
+        if let Some(amount) = self.legacy_allowances.get(key).await? {
+            self.allowances.insert(&accounts, amount)?;
+            self.legacy_allowances.remove(key)?;
+        }
+        if let Some(expires_at) = self.legacy_allowance_expiries.get(key).await? {
+            self.allowance_expiries.insert(&accounts, expires_at)?;
+            self.legacy_allowance_expiries.remove(key)?;
+        }
+        if let Some(revoked_at) = self.legacy_revoked_allowances.get(key).await? {
+            self.revoked_allowances.insert(&accounts, revoked_at)?;
+            self.legacy_revoked_allowances.remove(key)?;
+        }
+        Ok(())
+    }
+
+    /// Move up to `max_entries` legacy allowances to the structured maps,
+    /// returning how many moved
+    ///
+    /// Keys that do not parse are left in place and skipped.
+    pub async fn migrate_legacy_allowances(
+        &mut self,
+        max_entries: usize,
+    ) -> Result<usize, anyhow::Error> {
+        let mut keys = self.legacy_allowances.indices().await?;
+        for key in self.legacy_allowance_expiries.indices().await? {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        for key in self.legacy_revoked_allowances.indices().await? {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let mut migrated = 0;
+        for key in keys {
+            if migrated == max_entries {
+                break;
+            }
+            if let Some(accounts) = Self::parse_legacy_allowance_key(&key) {
+                self.migrate_legacy_key(&key, accounts).await?;
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// Number of allowances still stored under legacy keys
+    pub async fn legacy_allowance_count(&self) -> Result<usize, anyhow::Error> {
+        Ok(self.legacy_allowances.count().await?)
+    }
+
+    /// Get allowance amount usable at `now` (zero once expired)
+    pub async fn get_active_allowance(&self, owner: &Account, spender: &Account, now: Timestamp) -> U256 {
+        match self.get_allowance_expiry(owner, spender).await {
+            Some(expires_at) if now >= expires_at => U256::zero(),
+            _ => self.get_allowance(owner, spender).await,
+        }
+    }
+
+    /// Set allowance amount (approve)
+    pub async fn set_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+        amount: U256,
+        expires_at: Option<Timestamp>,
+    ) -> Result<(), anyhow::Error> {
+        self.migrate_allowance(owner, spender).await?;
+        let key = (owner, spender);
+        self.revoked_allowances.remove(&key)?;
+        match expires_at {
+            Some(expires_at) => self.allowance_expiries.insert(&key, expires_at)?,
+            None => self.allowance_expiries.remove(&key)?,
+        }
+        self.write_allowance(owner, spender, amount).await
+    }
+
+    /// Raise an allowance by `added`, returning the new amount
+    ///
+    /// Starts from the allowance usable at `now`: an expired or revoked one
+    /// counts as zero and the increase replaces it without an expiry.
+    pub async fn increase_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+        added: U256,
+        now: Timestamp,
+    ) -> Result<U256, anyhow::Error> {
+        let expires_at = self
+            .get_allowance_expiry(&owner, &spender)
+            .await
+            .filter(|expires_at| now < *expires_at);
+        let current = self.get_active_allowance(&owner, &spender, now).await;
+        let amount = current
+            .checked_add(added)
+            .ok_or_else(|| anyhow::anyhow!("Allowance overflow"))?;
+        self.set_allowance(owner, spender, amount, expires_at).await?;
+        Ok(amount)
+    }
+
+    /// Lower an allowance by `subtracted`, returning the new amount
+    ///
+    /// Fails if the allowance usable at `now` is smaller than `subtracted`;
+    /// the expiry is kept.
+    pub async fn reduce_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+        subtracted: U256,
+        now: Timestamp,
+    ) -> Result<U256, anyhow::Error> {
+        let current = self.get_active_allowance(&owner, &spender, now).await;
+        if subtracted > current {
+            anyhow::bail!("Cannot decrease allowance {} by {}", current, subtracted);
+        }
+        let amount = current - subtracted;
+        self.write_allowance(owner, spender, amount).await?;
+        Ok(amount)
+    }
+
+    /// Grant or withdraw an operator's unlimited transfer rights over an owner's tokens
+    pub fn set_operator(
+        &mut self,
+        owner: Account,
+        operator: Account,
+        approved: bool,
+    ) -> Result<(), anyhow::Error> {
+        if approved {
+            self.operators.insert(&(owner, operator), ())?;
+        } else {
+            self.operators.remove(&(owner, operator))?;
+        }
+        Ok(())
+    }
+
+    /// Whether `operator` may transfer any of the owner's tokens
+    pub async fn is_operator(&self, owner: &Account, operator: &Account) -> bool {
+        self.operators.contains_key(&(*owner, *operator)).await.unwrap_or(false)
+    }
+
+    /// Operators an owner has approved
+    pub async fn get_operators(&self, owner: &Account) -> Result<Vec<Account>, anyhow::Error> {
+        Ok(self
+            .operators
+            .indices()
+            .await?
+            .into_iter()
+            .filter(|(approver, _)| approver == owner)
+            .map(|(_, operator)| operator)
+            .collect())
+    }
+
+    /// Next permit nonce an owner must sign
+    pub async fn permit_nonce(&self, owner: &Account) -> Result<u64, anyhow::Error> {
+        Ok(self.permit_nonces.get(owner).await?.unwrap_or(0))
+    }
+
+    /// Consume an owner's permit nonce, failing unless `nonce` is the current one
+    pub async fn use_permit_nonce(
+        &mut self,
+        owner: &Account,
+        nonce: u64,
+    ) -> Result<(), anyhow::Error> {
+        let current = self.permit_nonce(owner).await?;
+        if nonce != current {
+            anyhow::bail!("Permit nonce {} does not match the current nonce {}", nonce, current);
+        }
+        self.permit_nonces.insert(owner, current + 1)?;
+        Ok(())
+    }
+
+    /// Decrease allowance amount (used in transferFrom)
+    pub async fn decrease_allowance(&mut self, owner: &Account, spender: &Account, amount: U256) -> Result<(), anyhow::Error> {
+        let current = self.get_allowance(owner, spender).await;
+        let new_allowance = current.saturating_sub(amount);
+        self.write_allowance(*owner, *spender, new_allowance).await
+    }
+
+    /// Store an allowance amount and keep the owner/spender indexes in sync
+    async fn write_allowance(&mut self, owner: Account, spender: Account, amount: U256) -> Result<(), anyhow::Error> {
+        self.migrate_allowance(owner, spender).await?;
+        let key = (owner, spender);
+        let mut spenders = self.approvals_by_owner.get(&owner).await?.unwrap_or_default();
+        let mut owners = self.approvals_by_spender.get(&spender).await?.unwrap_or_default();
+
+        if amount == U256::zero() {
+            self.allowances.remove(&key)?;
+            self.allowance_expiries.remove(&key)?;
+            spenders.retain(|s| *s != spender);
+            owners.retain(|o| *o != owner);
+        } else {
+            self.allowances.insert(&key, amount)?;
+            if !spenders.contains(&spender) {
+                spenders.push(spender);
+            }
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+
+        if spenders.is_empty() {
+            self.approvals_by_owner.remove(&owner)?;
+        } else {
+            self.approvals_by_owner.insert(&owner, spenders)?;
+        }
+        if owners.is_empty() {
+            self.approvals_by_spender.remove(&spender)?;
+        } else {
+            self.approvals_by_spender.insert(&spender, owners)?;
+        }
+        Ok(())
+    }
+
+    /// Revoke a single allowance, deleting it immediately
+    pub async fn revoke_allowance(
+        &mut self,
+        owner: Account,
+        spender: Account,
+        revoked_at: Timestamp,
+    ) -> Result<(), anyhow::Error> {
+        self.write_allowance(owner, spender, U256::zero()).await?;
+        self.revoked_allowances.insert(&(owner, spender), revoked_at)?;
+        Ok(())
+    }
+
+    /// Revoke every allowance granted by an owner, returning how many were revoked
+    pub async fn revoke_all_allowances(&mut self, owner: Account, revoked_at: Timestamp) -> Result<usize, anyhow::Error> {
+        let spenders = self.approvals_by_owner.get(&owner).await?.unwrap_or_default();
+        for spender in &spenders {
+            self.revoke_allowance(owner, *spender, revoked_at).await?;
+        }
+        Ok(spenders.len())
+    }
+
+    /// Check whether an allowance was explicitly revoked
+    pub async fn is_allowance_revoked(&self, owner: &Account, spender: &Account) -> bool {
+        if matches!(self.revoked_allowances.get(&(*owner, *spender)).await, Ok(Some(_))) {
+            return true;
+        }
+        let key = Self::legacy_allowance_key(owner, spender);
+        matches!(self.legacy_revoked_allowances.get(&key).await, Ok(Some(_)))
+    }
+
+    /// List allowances granted by an owner and still usable at `now`:
+    /// (spender, amount, expiry)
+    pub async fn get_approvals_granted(
+        &self,
+        owner: &Account,
+        now: Timestamp,
+    ) -> Vec<(Account, U256, Option<Timestamp>)> {
+        let spenders = self.approvals_by_owner.get(owner).await.unwrap_or(None).unwrap_or_default();
+        let mut approvals = Vec::new();
+        for spender in spenders {
+            let expires_at = self.get_allowance_expiry(owner, &spender).await;
+            if expires_at.is_some_and(|expires_at| now >= expires_at) {
+                continue;
+            }
+            let amount = self.get_allowance(owner, &spender).await;
+            approvals.push((spender, amount, expires_at));
+        }
+        approvals
+    }
+
+    /// List allowances received by a spender and still usable at `now`:
+    /// (owner, amount, expiry)
+    pub async fn get_approvals_received(
+        &self,
+        spender: &Account,
+        now: Timestamp,
+    ) -> Vec<(Account, U256, Option<Timestamp>)> {
+        let owners = self.approvals_by_spender.get(spender).await.unwrap_or(None).unwrap_or_default();
+        let mut approvals = Vec::new();
+        for owner in owners {
+            let expires_at = self.get_allowance_expiry(&owner, spender).await;
+            if expires_at.is_some_and(|expires_at| now >= expires_at) {
+                continue;
+            }
+            let amount = self.get_allowance(&owner, spender).await;
+            approvals.push((owner, amount, expires_at));
+        }
+        approvals
+    }
+
+    /// Append a metadata revision to the audit trail
+    fn record_metadata_revision(
+        &mut self,
+        metadata: TokenMetadata,
+        timestamp: Timestamp,
+    ) -> Result<(), anyhow::Error> {
+        let revision = *self.metadata_revision_count.get();
+        self.metadata_history.insert(&revision, (timestamp, metadata))?;
+        self.metadata_revision_count.set(revision + 1);
+        Ok(())
+    }
+
+    /// Update mutable metadata fields, keeping name and symbol unchanged
+    pub async fn update_metadata(
+        &mut self,
+        description: String,
+        image_url: Option<String>,
+        twitter: Option<String>,
+        telegram: Option<String>,
+        website: Option<String>,
+        updated_at: Timestamp,
+    ) -> Result<TokenMetadata, anyhow::Error> {
+        if description.len() > 1000 {
+            anyhow::bail!("Token description too long (max 1000 characters)");
+        }
+
+        let current = self.metadata.get().clone();
+        let metadata = TokenMetadata {
+            name: current.name,
+            symbol: current.symbol,
+            description,
+            image_url,
+            twitter,
+            telegram,
+            website,
+        };
+
+        self.record_metadata_revision(metadata.clone(), updated_at)?;
+        self.metadata.set(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Number of times a message has bounced so far
+    pub async fn bounce_count(&self, kind: &str, token_id: &str) -> u32 {
+        let key = PendingMessage::key(kind, token_id);
+        self.pending_messages
+            .get(&key)
+            .await
+            .unwrap_or(None)
+            .map(|pending| pending.bounces)
+            .unwrap_or(0)
+    }
+
+    /// Record a bounced message, returning how many times it has bounced
+    pub async fn record_bounce(
+        &mut self,
+        kind: &str,
+        token_id: &str,
+        bounced_at: Timestamp,
+        resolution: &str,
+    ) -> Result<u32, anyhow::Error> {
+        let bounces = self.bounce_count(kind, token_id).await + 1;
+        let pending = PendingMessage {
+            kind: kind.to_string(),
+            token_id: token_id.to_string(),
+            bounces,
+            last_bounced_at: bounced_at,
+            resolution: resolution.to_string(),
+        };
+        self.pending_messages.insert(&PendingMessage::key(kind, token_id), pending)?;
+        Ok(bounces)
+    }
+
+    /// Clear a pending message once it has been delivered
+    pub fn clear_pending_message(&mut self, kind: &str, token_id: &str) -> Result<(), anyhow::Error> {
+        self.pending_messages.remove(&PendingMessage::key(kind, token_id))?;
+        Ok(())
+    }
+
+    /// Get all pending (bounced) messages
+    pub async fn get_pending_messages(&self) -> Vec<PendingMessage> {
+        let mut pending = Vec::new();
+        for key in self.pending_messages.indices().await.unwrap_or_default() {
+            if let Ok(Some(message)) = self.pending_messages.get(&key).await {
+                pending.push(message);
+            }
+        }
+        pending
+    }
+
+    /// Get metadata revisions (paginated, oldest first)
+    pub async fn get_metadata_history(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Vec<(u64, Timestamp, TokenMetadata)> {
+        let total = *self.metadata_revision_count.get();
+        let end = offset.saturating_add(limit).min(total);
+
+        let mut revisions = Vec::new();
+        for revision in offset..end {
+            if let Ok(Some((timestamp, metadata))) = self.metadata_history.get(&revision).await {
+                revisions.push((revision, timestamp, metadata));
+            }
+        }
+        revisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fair_launch_abi::{
+        DutchAuctionConfig, FeeTier, FixedPriceSaleConfig, Price, RecurringBuy, SaleCommitment,
+        SnipeTaxConfig, SnipeTaxDestination, WASH_TRADE_WINDOW_MICROS,
+    };
+    use linera_sdk::linera_base_types::{AccountOwner, ChainId, TimeDelta};
+    use linera_views::memory::MemoryContext;
+
+    use crate::staking::REWARD_VESTING_MICROS;
+
+    #[tokio::test]
+    async fn test_token_state_initialization() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
 
         let token_id = "test-token".to_string();
         let creator = AccountOwner::from(ChainId::root(0));
@@ -229,32 +2132,1452 @@ mod tests {
             telegram: None,
             website: None,
         };
-        let curve_config = BondingCurveConfig::default();
-        let created_at = Timestamp::from(0);
+        let curve_config = BondingCurveConfig::default();
+        let created_at = Timestamp::from(0);
+
+        state.initialize(token_id.clone(), creator, metadata, curve_config, created_at)
+            .await
+            .unwrap();
+
+        assert_eq!(state.token_id.get().as_str(), "test-token");
+        assert_eq!(*state.current_supply.get(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_balance_operations() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let account = AccountOwner::from(ChainId::root(0));
+        let balance = U256::from(1000);
+
+        // Set balance
+        state.set_balance(account, balance).await.unwrap();
+        assert_eq!(state.get_balance(&account).await, balance);
+
+        // Update balance
+        let new_balance = U256::from(2000);
+        state.set_balance(account, new_balance).await.unwrap();
+        assert_eq!(state.get_balance(&account).await, new_balance);
+    }
+
+    #[tokio::test]
+    async fn test_pending_message_tracking() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let bounces = state
+            .record_bounce("GraduateToken", "t", Timestamp::from(1), "retrying")
+            .await
+            .unwrap();
+        assert_eq!(bounces, 1);
+
+        let bounces = state
+            .record_bounce("GraduateToken", "t", Timestamp::from(2), "retrying")
+            .await
+            .unwrap();
+        assert_eq!(bounces, 2);
+
+        let pending = state.get_pending_messages().await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].last_bounced_at, Timestamp::from(2));
+
+        state.clear_pending_message("GraduateToken", "t").unwrap();
+        assert!(state.get_pending_messages().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_curve_closes_on_graduation() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        state
+            .initialize(
+                "test-token".to_string(),
+                AccountOwner::from(ChainId::root(0)),
+                TokenMetadata::default(),
+                BondingCurveConfig::default(),
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+        assert!(!state.is_curve_closed());
+
+        // Transition block: graduation message sent, pool not yet created
+        state.graduation_started.set(true);
+        assert!(state.is_curve_closed());
+        assert_eq!(*state.dex_pool_id.get(), None);
+
+        // Pool created on the swap chain
+        state.dex_pool_id.set(Some("pool-test-token".to_string()));
+        state.is_graduated.set(true);
+        assert!(state.is_curve_closed());
+    }
+
+    #[tokio::test]
+    async fn test_approval_indexes() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let owner = AccountOwner::from(ChainId::root(0));
+        let spender = AccountOwner::from(ChainId::root(1));
+
+        state
+            .set_allowance(owner, spender, U256::from(500), Some(Timestamp::from(100)))
+            .await
+            .unwrap();
+
+        let now = Timestamp::from(50);
+        let granted = state.get_approvals_granted(&owner, now).await;
+        assert_eq!(granted.len(), 1);
+        assert_eq!(granted[0].1, U256::from(500));
+        assert_eq!(granted[0].2, Some(Timestamp::from(100)));
+        assert_eq!(state.get_approvals_received(&spender, now).await.len(), 1);
+
+        // Expired allowances cannot be used and are no longer listed
+        assert_eq!(state.get_active_allowance(&owner, &spender, Timestamp::from(50)).await, U256::from(500));
+        assert_eq!(state.get_active_allowance(&owner, &spender, Timestamp::from(100)).await, U256::zero());
+        assert!(state.get_approvals_granted(&owner, Timestamp::from(100)).await.is_empty());
+        assert!(state.get_approvals_received(&spender, Timestamp::from(100)).await.is_empty());
+
+        // Spending the full allowance drops it from both indexes
+        state.decrease_allowance(&owner, &spender, U256::from(500)).await.unwrap();
+        assert!(state.get_approvals_granted(&owner, now).await.is_empty());
+        assert!(state.get_approvals_received(&spender, now).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_allowances() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let owner = AccountOwner::from(ChainId::root(0));
+        let spender1 = AccountOwner::from(ChainId::root(1));
+        let spender2 = AccountOwner::from(ChainId::root(2));
+
+        state.set_allowance(owner, spender1, U256::from(100), None).await.unwrap();
+        state.set_allowance(owner, spender2, U256::from(200), None).await.unwrap();
+
+        state.revoke_allowance(owner, spender1, Timestamp::from(1)).await.unwrap();
+        assert_eq!(state.get_allowance(&owner, &spender1).await, U256::zero());
+        assert!(state.is_allowance_revoked(&owner, &spender1).await);
+        assert!(!state.is_allowance_revoked(&owner, &spender2).await);
+
+        assert_eq!(state.revoke_all_allowances(owner, Timestamp::from(2)).await.unwrap(), 1);
+        assert!(state.is_allowance_revoked(&owner, &spender2).await);
+        assert!(state.get_approvals_granted(&owner, Timestamp::from(2)).await.is_empty());
+
+        // Approving again clears the revocation
+        state.set_allowance(owner, spender1, U256::from(50), None).await.unwrap();
+        assert!(!state.is_allowance_revoked(&owner, &spender1).await);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_allowance_migration() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let owner = AccountOwner::from(ChainId::root(0));
+        let spender1 = AccountOwner::from(ChainId::root(1));
+        let spender2 = AccountOwner::from(ChainId::root(2));
+        let key1 = TokenState::legacy_allowance_key(&owner, &spender1);
+        let key2 = TokenState::legacy_allowance_key(&owner, &spender2);
+        assert_eq!(
+            TokenState::parse_legacy_allowance_key(&key1),
+            Some((owner, spender1))
+        );
+
+        state.legacy_allowances.insert(&key1, U256::from(100)).unwrap();
+        state.legacy_allowance_expiries.insert(&key1, Timestamp::from(50)).unwrap();
+        state.legacy_allowances.insert(&key2, U256::from(200)).unwrap();
+        assert_eq!(state.get_allowance(&owner, &spender1).await, U256::from(100));
+        assert_eq!(state.legacy_allowance_count().await.unwrap(), 2);
+
+        // A write moves its own entry off the legacy key
+        state.decrease_allowance(&owner, &spender2, U256::from(20)).await.unwrap();
+        assert_eq!(state.get_allowance(&owner, &spender2).await, U256::from(180));
+        assert_eq!(state.legacy_allowance_count().await.unwrap(), 1);
+
+        assert_eq!(state.migrate_legacy_allowances(10).await.unwrap(), 1);
+        assert_eq!(state.legacy_allowance_count().await.unwrap(), 0);
+        assert_eq!(state.allowances.get(&(owner, spender1)).await.unwrap(), Some(U256::from(100)));
+        assert_eq!(state.get_allowance_expiry(&owner, &spender1).await, Some(Timestamp::from(50)));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_trade_migration() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let trader = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let legacy = |sequence: u64| LegacyTrade {
+            token_id: "token".to_string(),
+            trader,
+            is_buy: true,
+            token_amount: U256::from(sequence),
+            currency_amount: U256::from(10),
+            price: U256::from(2),
+            timestamp: Timestamp::from(100),
+        };
+        // Migrated by sequence number, though "100-10" sorts before "100-9"
+        for sequence in [9, 10, 11] {
+            let key = format!("100-{}", sequence);
+            state.legacy_trades.insert(&key, legacy(sequence)).unwrap();
+        }
+        state.trade_count.set(12);
+        state.pruned_trades.set(10);
+        assert_eq!(state.prune_trades(0, Timestamp::from(200)).await.unwrap(), None);
+
+        // The pruned trade is dropped, the rest keep their sequence numbers
+        assert_eq!(state.migrate_legacy_trades(2).await.unwrap(), 2);
+        assert!(state.trades.get(&9).await.unwrap().is_none());
+        let trade = state.trades.get(&10).await.unwrap().unwrap();
+        assert_eq!(trade.price, Price::from_integer(U256::from(2)).unwrap());
+        assert_eq!(state.migrate_legacy_trades(10).await.unwrap(), 1);
+        assert_eq!(state.legacy_trade_count().await.unwrap(), 0);
+        let sequences: Vec<u64> = state
+            .get_trades_since(0, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|(sequence, _)| *sequence)
+            .collect();
+        assert_eq!(sequences, vec![10, 11]);
+
+        // The trader's index picks them up in order
+        assert_eq!(state.trades_by_trader.get(&(trader, 0)).await.unwrap(), Some(10));
+        assert_eq!(state.trades_by_trader.get(&(trader, 1)).await.unwrap(), Some(11));
+    }
+
+    #[tokio::test]
+    async fn test_operators() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let owner = AccountOwner::from(ChainId::root(0));
+        let swap = AccountOwner::from(ChainId::root(1));
+        assert!(!state.is_operator(&owner, &swap).await);
+
+        state.set_operator(owner, swap, true).unwrap();
+        assert!(state.is_operator(&owner, &swap).await);
+        assert!(!state.is_operator(&swap, &owner).await);
+        assert_eq!(state.get_operators(&owner).await.unwrap(), vec![swap]);
+
+        state.set_operator(owner, swap, false).unwrap();
+        assert!(!state.is_operator(&owner, &swap).await);
+        assert!(state.get_operators(&owner).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_increase_and_reduce_allowance() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let owner = AccountOwner::from(ChainId::root(0));
+        let spender = AccountOwner::from(ChainId::root(1));
+        let now = Timestamp::from(10);
+
+        let expires_at = Some(Timestamp::from(100));
+        state.set_allowance(owner, spender, U256::from(100), expires_at).await.unwrap();
+        let amount = state.increase_allowance(owner, spender, U256::from(50), now).await.unwrap();
+        assert_eq!(amount, U256::from(150));
+        assert_eq!(state.get_allowance_expiry(&owner, &spender).await, expires_at);
+
+        let amount = state.reduce_allowance(owner, spender, U256::from(30), now).await.unwrap();
+        assert_eq!(amount, U256::from(120));
+        assert!(state.reduce_allowance(owner, spender, U256::from(121), now).await.is_err());
+        assert_eq!(state.get_allowance(&owner, &spender).await, U256::from(120));
+
+        // Once expired, an increase starts from zero and drops the old expiry
+        let later = Timestamp::from(200);
+        let amount = state.increase_allowance(owner, spender, U256::from(5), later).await.unwrap();
+        assert_eq!(amount, U256::from(5));
+        assert_eq!(state.get_allowance_expiry(&owner, &spender).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_min_holders_blocks_graduation() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let mut curve_config = BondingCurveConfig::default();
+        curve_config.min_holders = Some(2);
+        let max_supply = curve_config.max_supply;
+        state
+            .initialize(
+                "test-token".to_string(),
+                AccountOwner::from(ChainId::root(0)),
+                TokenMetadata::default(),
+                curve_config,
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+
+        // A single whale buys out the whole curve
+        state.current_supply.set(max_supply);
+        state.set_balance(AccountOwner::from(ChainId::root(1)), max_supply).await.unwrap();
+
+        assert!(state.is_curve_complete());
+        assert!(!state.can_graduate());
+        assert_eq!(state.unmet_graduation_conditions().len(), 1);
+
+        state.set_balance(AccountOwner::from(ChainId::root(2)), U256::from(1)).await.unwrap();
+        assert!(state.can_graduate());
+    }
+
+    #[tokio::test]
+    async fn test_metadata_update_keeps_name_and_records_history() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let creator = AccountOwner::from(ChainId::root(0));
+        let metadata = TokenMetadata {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            description: "A test token".to_string(),
+            image_url: None,
+            twitter: None,
+            telegram: None,
+            website: None,
+        };
+        state
+            .initialize("test-token".to_string(), creator, metadata, BondingCurveConfig::default(), Timestamp::from(0))
+            .await
+            .unwrap();
+
+        let updated = state
+            .update_metadata(
+                "New description".to_string(),
+                None,
+                Some("@test".to_string()),
+                None,
+                None,
+                Timestamp::from(10),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(updated.name, "Test Token");
+        assert_eq!(updated.symbol, "TEST");
+        assert_eq!(state.metadata.get().description, "New description");
+
+        let history = state.get_metadata_history(0, 10).await;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].2.description, "A test token");
+        assert_eq!(history[1].1, Timestamp::from(10));
+    }
+
+    #[tokio::test]
+    async fn test_reconciliation_reports_drift() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let alice = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let bob = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        state.set_balance(alice, U256::from(600)).await.unwrap();
+        state.set_balance(bob, U256::from(400)).await.unwrap();
+        state.current_supply.set(U256::from(1000));
+
+        let reconciliation = state.reconcile().await.unwrap();
+        assert_eq!(reconciliation.balances_total, U256::from(1000));
+        let sell_out = reconciliation.sell_out_value.unwrap();
+        assert!(reconciliation.violations(sell_out).is_empty());
+
+        // Supply and trade count mismatches are both reported
+        state.current_supply.set(U256::from(1001));
+        state.trade_count.set(1);
+        let reconciliation = state.reconcile().await.unwrap();
+        assert_eq!(reconciliation.violations(Amount::ZERO).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reconciliation_accepts_fee_bearing_buys() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+        state.curve_config.set(BondingCurveConfig::default());
+        let config = state.curve_config.get().clone();
+
+        // A buy whose creator fee went straight to the creator
+        let buyer = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let amount = U256::from(1_000_000);
+        let cost = bonding_curve::calculate_buy_cost(U256::zero(), amount, config.k, config.scale)
+            .unwrap();
+        let fee = wad::mul_bps(cost, config.creator_fee_bps).unwrap();
+        let received = units::to_amount(cost - fee, config.base_decimals).unwrap();
+        state.set_balance(buyer, amount).await.unwrap();
+        state.current_supply.set(amount);
+        state.credit_reserves(received);
+
+        let reconciliation = state.reconcile().await.unwrap();
+        assert!(reconciliation.sell_out_value.unwrap() > received);
+        assert!(reconciliation.violations(received).is_empty());
+
+        // Losing funds the ledger accounts for is still caught
+        let short = received.saturating_sub(Amount::from_attos(1));
+        assert_eq!(reconciliation.violations(short).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_summary_tracks_rolling_volume() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+        state.curve_config.set(BondingCurveConfig::default());
+
+        let trader = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let hour = 3_600_000_000u64;
+        let trade_at = |at: u64| Trade {
+            token_id: "token".to_string(),
+            trader,
+            is_buy: true,
+            token_amount: U256::from(10),
+            currency_amount: U256::from(100),
+            price: Price::default(),
+            timestamp: Timestamp::from(at),
+            memo: None,
+            referrer: None,
+            flagged: false,
+        };
+        for at in [0, hour, 30 * hour] {
+            state.record_trade(trade_at(at)).await.unwrap();
+        }
+
+        // Only the trade inside the 24h window counts
+        assert_eq!(state.volume_24h(Timestamp::from(30 * hour)).await.unwrap(), U256::from(100));
+        assert_eq!(state.volume_7d(Timestamp::from(30 * hour)).await.unwrap(), U256::from(300));
+
+        let max_supply = state.curve_config.get().max_supply;
+        state.current_supply.set(max_supply / 4);
+        state.set_balance(trader, max_supply / 8).await.unwrap();
+        let summary = state.summary(Timestamp::from(30 * hour)).await.unwrap();
+        assert_eq!(summary.progress_bps, 2_500);
+        assert_eq!(summary.volume_24h, U256::from(100));
+        assert_eq!(summary.volume_7d, U256::from(300));
+        assert_eq!(summary.top_holder_bps, 5_000);
+        assert_eq!(summary.updated_at, Timestamp::from(30 * hour));
+
+        // Day 7 only sees itself and the six days before it
+        state.record_trade(trade_at(150 * hour)).await.unwrap();
+        assert_eq!(state.volume_7d(Timestamp::from(170 * hour)).await.unwrap(), U256::from(200));
+        assert_eq!(state.daily_volume.indices().await.unwrap(), vec![0, 1, 6]);
+
+        // Writing on day 8 prunes days 0 and 1
+        state.record_trade(trade_at(200 * hour)).await.unwrap();
+        assert_eq!(state.daily_volume.indices().await.unwrap(), vec![6, 8]);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_dust() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let account = |index: u32| Account { chain_id: ChainId::root(index), owner: AccountOwner::CHAIN };
+        state.set_balance(account(0), U256::from(5)).await.unwrap();
+        state.set_balance(account(1), U256::from(9)).await.unwrap();
+        state.set_balance(account(2), U256::from(1_000)).await.unwrap();
+        state.set_balance(account(3), U256::from(3)).await.unwrap();
+        state.current_supply.set(U256::from(1_017));
+
+        // Exempt accounts keep their dust
+        let (accounts, swept) = state.sweep_dust(U256::from(10), &[account(3)]).await.unwrap();
+        assert_eq!((accounts, swept), (2, U256::from(14)));
+        assert_eq!(*state.current_supply.get(), U256::from(1_003));
+        assert_eq!(*state.holder_count.get(), 2);
+        assert_eq!(state.get_balance(&account(0)).await, U256::zero());
+        assert_eq!(state.get_balance(&account(2)).await, U256::from(1_000));
+        assert_eq!(state.get_balance(&account(3)).await, U256::from(3));
+        // The whole map fit in one batch, so the next sweep starts over
+        assert_eq!(*state.dust_sweep_position.get(), 0);
+
+        // Totals accumulate across sweeps
+        state.sweep_dust(U256::from(10), &[account(3)]).await.unwrap();
+        assert_eq!(*state.dust_swept_total.get(), U256::from(14));
+        assert_eq!(*state.dust_swept_accounts.get(), 2);
+        assert_eq!(*state.dust_sweep_count.get(), 2);
+
+        // A sweep resumes from the stored position
+        let last = *state.balances.indices().await.unwrap().last().unwrap();
+        state.dust_sweep_position.set(1);
+        let (accounts, _) = state.sweep_dust(U256::from(10), &[]).await.unwrap();
+        assert_eq!(accounts, u64::from(last == account(3)));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_dust_in_batches() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let holders = MAX_BALANCES_SWEPT_PER_CALL as u32 + 5;
+        let account = |index: u32| Account { chain_id: ChainId::root(index), owner: AccountOwner::CHAIN };
+        for index in 0..holders {
+            state.set_balance(account(index), U256::from(1 + index % 2)).await.unwrap();
+        }
+        state.current_supply.set(U256::from(2 * holders));
+
+        // Balances of 1 go, balances of 2 stay and keep their positions
+        let (first, _) = state.sweep_dust(U256::from(2), &[]).await.unwrap();
+        assert!(first > 0 && first < MAX_BALANCES_SWEPT_PER_CALL as u64);
+        assert_eq!(
+            *state.dust_sweep_position.get(),
+            MAX_BALANCES_SWEPT_PER_CALL as u64 - first
+        );
+
+        let (second, _) = state.sweep_dust(U256::from(2), &[]).await.unwrap();
+        assert_eq!(first + second, u64::from(holders.div_ceil(2)));
+        assert_eq!(*state.dust_sweep_position.get(), 0);
+        assert_eq!(state.balances.count().await.unwrap(), (holders / 2) as usize);
+    }
+
+    #[tokio::test]
+    async fn test_staking_rewards_split_pro_rata() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let alice = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let bob = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        state.set_balance(alice, U256::from(300)).await.unwrap();
+        state.set_balance(bob, U256::from(100)).await.unwrap();
+        state.current_supply.set(U256::from(400));
+
+        let at = |micros: u64| Timestamp::from(micros);
+        let period = REWARD_VESTING_MICROS;
+
+        // Rewards funded before anyone can stake wait; vesting only runs
+        // while something is staked, so the first staker does not take them all
+        state.staking.fund_rewards(Amount::from_attos(120), at(0));
+        state.stake(alice, U256::from(300), at(period)).await.unwrap();
+        assert_eq!(state.staking.pending_rewards(&alice).await.unwrap(), U256::zero());
+
+        // Bob joins halfway through vesting and shares the second half
+        state.stake(bob, U256::from(100), at(period * 3 / 2)).await.unwrap();
+        assert_eq!(state.staking.pending_rewards(&alice).await.unwrap(), U256::from(60));
+        state.staking.fund_rewards(Amount::from_attos(400), at(period * 2));
+        assert_eq!(state.staking.pending_rewards(&alice).await.unwrap(), U256::from(105));
+        assert_eq!(state.staking.pending_rewards(&bob).await.unwrap(), U256::from(15));
+
+        // Staked tokens still reconcile against supply
+        let reconciliation = state.reconcile().await.unwrap();
+        assert_eq!(reconciliation.balances_total, U256::from(400));
+        assert_eq!(state.get_balance(&alice).await, U256::zero());
+
+        // The new funding vests over a fresh period
+        assert!(state.unstake(bob, U256::from(101), at(period * 3)).await.is_err());
+        state.unstake(bob, U256::from(100), at(period * 3)).await.unwrap();
+        assert_eq!(state.get_balance(&bob).await, U256::from(100));
+        assert_eq!(state.staking.pending_rewards(&alice).await.unwrap(), U256::from(405));
+
+        let claimed = state.staking.claim(bob, at(period * 3)).await.unwrap();
+        assert_eq!(claimed, Amount::from_attos(115));
+        assert_eq!(state.staking.pending_rewards(&bob).await.unwrap(), U256::zero());
+        assert_eq!(*state.staking.reward_reserve.get(), Amount::from_attos(405));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_leaves_prove_balances() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let accounts: Vec<Account> = (0..5)
+            .map(|i| Account { chain_id: ChainId::root(i), owner: AccountOwner::CHAIN })
+            .collect();
+        for (i, account) in accounts.iter().enumerate() {
+            state.set_balance(*account, U256::from(100 * (i + 1))).await.unwrap();
+        }
+        state.set_balance(accounts[4], U256::zero()).await.unwrap();
+
+        let (leaves, total) = state.balance_leaves(0).await.unwrap();
+        assert_eq!(leaves.len(), 4);
+        assert_eq!(total, U256::from(1_000));
+
+        let root = merkle::root(&leaves).unwrap();
+        let leaf = merkle::leaf_hash(0, accounts[1], U256::from(200));
+        let index = leaves.iter().position(|l| *l == leaf).unwrap();
+        let siblings = merkle::proof(&leaves, index).unwrap();
+        assert!(merkle::verify(leaf, &siblings, root));
+        assert!(!merkle::verify(merkle::leaf_hash(0, accounts[1], U256::from(201)), &siblings, root));
+
+        let snapshot_id = state
+            .snapshots
+            .record(fair_launch_abi::BalanceSnapshot {
+                root,
+                block_height: 7.into(),
+                taken_at: Timestamp::from(0),
+                holder_count: leaves.len() as u64,
+                total_balance: total,
+                reward_pool: Amount::from_attos(1_000),
+                claimed: Amount::ZERO,
+            })
+            .unwrap();
+        assert_eq!(state.reserved_base(), Amount::from_attos(1_000));
+
+        state.snapshots.record_claim(snapshot_id, accounts[1], Amount::from_attos(200)).await.unwrap();
+        assert!(state.snapshots.has_claimed(snapshot_id, accounts[1]).await.unwrap());
+        assert!(!state.snapshots.has_claimed(snapshot_id, accounts[0]).await.unwrap());
+        assert_eq!(state.reserved_base(), Amount::from_attos(800));
+    }
+
+    #[tokio::test]
+    async fn test_recurring_buy_escrow() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let owner = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let schedule = fair_launch_abi::RecurringBuy {
+            owner,
+            amount_per_interval: Amount::from_tokens(1),
+            interval: linera_sdk::linera_base_types::TimeDelta::from_secs(60),
+            remaining_budget: Amount::from_tokens(3),
+            next_run_at: Timestamp::from(0),
+            created_at: Timestamp::from(0),
+            executions: 0,
+            tokens_bought: U256::zero(),
+            spent: Amount::ZERO,
+            active: true,
+        };
+        let schedule_id = state.recurring_buys.create(schedule).await.unwrap();
+        assert_eq!(state.reserved_base(), Amount::from_tokens(3));
+        assert_eq!(state.recurring_buys.due(Timestamp::from(0), 10).await.unwrap().len(), 1);
 
-        state.initialize(token_id.clone(), creator, metadata, curve_config, created_at)
-            .await
+        let mut schedule = state.recurring_buys.get(schedule_id).await.unwrap().unwrap();
+        state
+            .recurring_buys
+            .record_execution(schedule_id, &mut schedule, Amount::from_tokens(1), U256::from(500), Timestamp::from(0))
             .unwrap();
+        assert_eq!(schedule.remaining_budget, Amount::from_tokens(2));
+        assert_eq!(schedule.next_run_at, Timestamp::from(60_000_000));
+        assert_eq!(state.reserved_base(), Amount::from_tokens(2));
+        assert!(state.recurring_buys.due(Timestamp::from(59_999_999), 10).await.unwrap().is_empty());
 
-        assert_eq!(state.token_id.get().as_str(), "test-token");
-        assert_eq!(*state.current_supply.get(), U256::zero());
+        let refund = state.recurring_buys.close(schedule_id, &mut schedule).unwrap();
+        assert_eq!(refund, Amount::from_tokens(2));
+        assert_eq!(state.reserved_base(), Amount::ZERO);
+        assert!(state.recurring_buys.due(Timestamp::from(60_000_000), 10).await.unwrap().is_empty());
+
+        let owned = state.recurring_buys.owned_by(&owner).await.unwrap();
+        assert_eq!(owned.len(), 1);
+        assert!(!owned[0].1.active);
     }
 
     #[tokio::test]
-    async fn test_balance_operations() {
+    async fn test_recurring_buys_come_due_in_order() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let owner = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        for next_run_at in [300u64, 100, 200, 1_000] {
+            let schedule = RecurringBuy {
+                owner,
+                amount_per_interval: Amount::from_tokens(1),
+                interval: TimeDelta::from_micros(1_000),
+                remaining_budget: Amount::from_tokens(2),
+                next_run_at: Timestamp::from(next_run_at),
+                created_at: Timestamp::from(0),
+                executions: 0,
+                tokens_bought: U256::zero(),
+                spent: Amount::ZERO,
+                active: true,
+            };
+            state.recurring_buys.create(schedule).await.unwrap();
+        }
+
+        // Longest overdue first, within the limit, and none that are not due
+        let due = state.recurring_buys.due(Timestamp::from(300), 2).await.unwrap();
+        let ids: Vec<u64> = due.iter().map(|(schedule_id, _)| *schedule_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        let due = state.recurring_buys.due(Timestamp::from(300), 10).await.unwrap();
+        assert_eq!(due.len(), 3);
+
+        // An executed schedule moves to the back of the queue
+        let (schedule_id, mut schedule) = due[0].clone();
+        let (spent, now) = (Amount::from_tokens(1), Timestamp::from(300));
+        state
+            .recurring_buys
+            .record_execution(schedule_id, &mut schedule, spent, U256::one(), now)
+            .unwrap();
+        let due = state.recurring_buys.due(Timestamp::from(1_000), 10).await.unwrap();
+        let ids: Vec<u64> = due.iter().map(|(schedule_id, _)| *schedule_id).collect();
+        assert_eq!(ids, vec![2, 0, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_transfers_locked_until_graduation() {
         let context = MemoryContext::default();
         let mut state = TokenState::load(context).await.unwrap();
 
-        let account = AccountOwner::from(ChainId::root(0));
-        let balance = U256::from(1000);
+        state.curve_config.set(BondingCurveConfig::default());
+        assert!(state.transfers_allowed());
 
-        // Set balance
-        state.set_balance(account, balance).await.unwrap();
-        assert_eq!(state.get_balance(&account).await, balance);
+        state.curve_config.set(BondingCurveConfig {
+            transfers_enabled_during_curve: false,
+            ..BondingCurveConfig::default()
+        });
+        assert!(!state.transfers_allowed());
 
-        // Update balance
-        let new_balance = U256::from(2000);
-        state.set_balance(account, new_balance).await.unwrap();
-        assert_eq!(state.get_balance(&account).await, new_balance);
+        state.is_graduated.set(true);
+        assert!(state.transfers_allowed());
+    }
+
+    #[tokio::test]
+    async fn test_refund_queue() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let buyer = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let first = state
+            .queue_refund(buyer, Amount::from_tokens(2), "curve closed".to_string(), Timestamp::from(5))
+            .unwrap();
+        let second = state
+            .queue_refund(buyer, Amount::from_tokens(3), "curve closed".to_string(), Timestamp::from(6))
+            .unwrap();
+        assert_eq!((first, second), (0, 1));
+
+        state.mark_refund_issued(first).await.unwrap();
+        // Marking twice does not double count
+        state.mark_refund_issued(first).await.unwrap();
+        assert_eq!(*state.total_refunded.get(), Amount::from_tokens(2));
+
+        let refunds = state.get_refunds(0, 10).await;
+        assert_eq!(refunds.len(), 2);
+        assert!(refunds[0].1.issued);
+        assert!(!refunds[1].1.issued);
+        assert_eq!(state.get_refunds(1, 10).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_record_milestones() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+        // Target raise of 69_000
+        state.curve_config.set(BondingCurveConfig::default());
+
+        state.total_raised.set(U256::from(40_000));
+        let reached = state.record_milestones(Timestamp::from(10)).await.unwrap();
+        assert_eq!(reached, vec![2_500, 5_000]);
+
+        // Dropping back under a milestone keeps it, and nothing is reported again
+        state.total_raised.set(U256::from(20_000));
+        assert!(state.record_milestones(Timestamp::from(20)).await.unwrap().is_empty());
+
+        state.total_raised.set(U256::from(69_000));
+        let reached = state.record_milestones(Timestamp::from(30)).await.unwrap();
+        assert_eq!(reached, vec![7_500, 10_000]);
+        assert_eq!(state.milestones.get(&2_500).await.unwrap(), Some(Timestamp::from(10)));
+        assert_eq!(state.milestones.get(&10_000).await.unwrap(), Some(Timestamp::from(30)));
+    }
+
+    #[tokio::test]
+    async fn test_reserve_ledger() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        state.credit_reserves(Amount::from_tokens(10));
+        state.debit_reserves(Amount::from_tokens(3));
+        state.record_fee_paid(Amount::from_tokens(1));
+        state.record_refund_paid(Amount::from_tokens(2));
+        assert_eq!(*state.native_reserves.get(), Amount::from_tokens(7));
+        assert_eq!(*state.fees_paid_out.get(), Amount::from_tokens(1));
+        assert_eq!(*state.refunds_issued.get(), Amount::from_tokens(2));
+
+        // The ledger never goes negative
+        state.debit_reserves(Amount::from_tokens(100));
+        assert_eq!(*state.native_reserves.get(), Amount::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_permit_nonces() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let owner = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        assert_eq!(state.permit_nonce(&owner).await.unwrap(), 0);
+        assert!(state.use_permit_nonce(&owner, 1).await.is_err());
+        state.use_permit_nonce(&owner, 0).await.unwrap();
+
+        // A permit cannot be replayed
+        assert!(state.use_permit_nonce(&owner, 0).await.is_err());
+        assert_eq!(state.permit_nonce(&owner).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lock_and_claim_unlocked() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let team = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        state.set_balance(team, U256::from(1_000)).await.unwrap();
+        state.lock_tokens(team, U256::from(300), Timestamp::from(100)).await.unwrap();
+        state.lock_tokens(team, U256::from(200), Timestamp::from(200)).await.unwrap();
+        assert!(state.lock_tokens(team, U256::from(600), Timestamp::from(100)).await.is_err());
+        assert_eq!(state.get_balance(&team).await, U256::from(500));
+        assert_eq!(*state.total_locked.get(), U256::from(500));
+        assert_eq!(state.get_locks(Some(&team)).await.unwrap().len(), 2);
+
+        // Only the expired lock is released
+        let released = state.claim_unlocked(team, Timestamp::from(150)).await.unwrap();
+        assert_eq!(released, U256::from(300));
+        assert_eq!(state.get_balance(&team).await, U256::from(800));
+        assert_eq!(*state.total_locked.get(), U256::from(200));
+        let locks = state.get_locks(None).await.unwrap();
+        assert_eq!(locks, vec![(team, Timestamp::from(200), U256::from(200))]);
+    }
+
+    #[tokio::test]
+    async fn test_trades_by_trader() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let alice = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let bob = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        let trade_by = |trader: Account, at: u64| Trade {
+            token_id: "token".to_string(),
+            trader,
+            is_buy: true,
+            token_amount: U256::from(at),
+            currency_amount: U256::from(1),
+            price: Price::default(),
+            timestamp: Timestamp::from(at),
+            memo: None,
+            referrer: None,
+            flagged: false,
+        };
+        for (index, trader) in [alice, bob, alice, alice].into_iter().enumerate() {
+            let trade = trade_by(trader, index as u64);
+            state.record_trade(trade).await.unwrap();
+        }
+
+        let amounts = |trades: Vec<Trade>| -> Vec<u64> {
+            trades.iter().map(|trade| trade.token_amount.as_u64()).collect()
+        };
+        assert_eq!(amounts(state.get_trades_by_trader(&alice, 0, 10).await.unwrap()), vec![3, 2, 0]);
+        assert_eq!(amounts(state.get_trades_by_trader(&alice, 1, 1).await.unwrap()), vec![2]);
+        assert_eq!(amounts(state.get_trades_by_trader(&bob, 0, 10).await.unwrap()), vec![1]);
+        let (history, left_out) = state.get_trader_history(&alice).await.unwrap();
+        assert_eq!((amounts(history), left_out), (vec![0, 2, 3], None));
+
+        // The full history is numbered in recording order
+        let since: Vec<u64> = state
+            .get_trades_since(2, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|(sequence, _)| *sequence)
+            .collect();
+        assert_eq!(since, vec![2, 3]);
+        assert_eq!(amounts(state.get_trades(1, 2).await), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_round_trips_are_flagged_as_wash_trading() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let washer = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let holder = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        let trade = |trader: Account, is_buy: bool, amount: u64, at: u64| Trade {
+            token_id: "token".to_string(),
+            trader,
+            is_buy,
+            token_amount: U256::from(amount),
+            currency_amount: U256::from(amount),
+            price: Price::default(),
+            timestamp: Timestamp::from(at),
+            memo: None,
+            referrer: None,
+            flagged: false,
+        };
+        let trades = [
+            trade(washer, true, 1_000, 0),
+            trade(washer, false, 990, 10),
+            trade(holder, true, 1_000, 20),
+            trade(holder, false, 300, 30),
+            // Outside the window the earlier buy no longer nets out
+            trade(washer, true, 500, 40 + WASH_TRADE_WINDOW_MICROS),
+        ];
+        for trade in trades {
+            state.record_trade(trade).await.unwrap();
+        }
+
+        let flagged: Vec<bool> =
+            state.get_trades(0, 10).await.iter().map(|trade| trade.flagged).collect();
+        assert_eq!(flagged, vec![false, true, false, false, false]);
+
+        let suspicious = state.suspicious_accounts(10).await.unwrap();
+        assert_eq!(suspicious.len(), 1);
+        let (account, stats) = &suspicious[0];
+        assert_eq!(*account, washer);
+        assert_eq!(stats.flagged_trades, 1);
+        assert_eq!(stats.flagged_volume, U256::from(990));
+    }
+
+    #[tokio::test]
+    async fn test_creator_payouts_split_over_co_creators() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+        let members: Vec<Account> = (0..3)
+            .map(|i| Account { chain_id: ChainId::root(i), owner: AccountOwner::CHAIN })
+            .collect();
+
+        // The creator alone takes the whole payout
+        let payouts = state.creator_set.payouts_or(members[0], Amount::from_attos(100));
+        assert_eq!(payouts, vec![(members[0], Amount::from_attos(100))]);
+
+        state.creator_set.set(members.clone(), 2);
+        let payouts = state.creator_set.payouts_or(members[0], Amount::from_attos(100));
+        assert_eq!(
+            payouts,
+            vec![
+                (members[0], Amount::from_attos(34)),
+                (members[1], Amount::from_attos(33)),
+                (members[2], Amount::from_attos(33)),
+            ]
+        );
+
+        // Shares rounding to zero are skipped
+        let payouts = state.creator_set.payouts_or(members[0], Amount::from_attos(2));
+        assert_eq!(payouts, vec![(members[0], Amount::from_attos(2))]);
+    }
+
+    #[tokio::test]
+    async fn test_volume_fee_tiers_discount_the_creator_fee() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+        state.curve_config.set(BondingCurveConfig {
+            creator_fee_bps: 300,
+            fee_tiers: vec![FeeTier { min_volume: U256::from(1_000), discount_bps: 5_000 }],
+            ..BondingCurveConfig::default()
+        });
+
+        let trader = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let now = Timestamp::from(0);
+        assert_eq!(state.creator_fee_bps_for(&trader, now).await.unwrap(), 300);
+
+        let trade = Trade {
+            token_id: "token".to_string(),
+            trader,
+            is_buy: true,
+            token_amount: U256::from(10),
+            currency_amount: U256::from(1_000),
+            price: Price::default(),
+            timestamp: now,
+            memo: None,
+            referrer: None,
+            flagged: false,
+        };
+        state.record_trade(trade.clone()).await.unwrap();
+        assert_eq!(state.trader_volume(&trader, now).await.unwrap(), U256::from(1_000));
+        assert_eq!(state.creator_fee_bps_for(&trader, now).await.unwrap(), 150);
+
+        // Selling straight back is flagged and adds no volume
+        let round_trip = Trade { is_buy: false, ..trade };
+        state.record_trade(round_trip).await.unwrap();
+        assert_eq!(state.trader_volume(&trader, now).await.unwrap(), U256::from(1_000));
+
+        // The discount lapses once the volume leaves the 30-day window
+        let later = Timestamp::from(30 * fee_tiers::DAY_MICROS);
+        assert_eq!(state.creator_fee_bps_for(&trader, later).await.unwrap(), 300);
+    }
+
+    #[tokio::test]
+    async fn test_outbox_records_trades() {
+        let context = MemoryContext::default();
+        let mut state = TokenState::load(context).await.unwrap();
+
+        let trader = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        for at in 0..2u64 {
+            let trade = Trade {
+                token_id: "token".to_string(),
+                trader,
+                is_buy: at == 0,
+                token_amount: U256::from(10),
+                currency_amount: U256::from(1),
+                price: Price::default(),
+                timestamp: Timestamp::from(at),
+                memo: None,
+                referrer: None,
+                flagged: false,
+            };
+            state.record_trade(trade).await.unwrap();
+        }
+
+        let events = state.outbox.after(None, 10).await.unwrap();
+        let sequences: Vec<u64> = events.iter().map(|(sequence, _)| *sequence).collect();
+        assert_eq!(sequences, vec![0, 1]);
+        assert!(events.iter().all(|(_, event)| event.kind == "Trade"));
+        assert_eq!(events[1].1.recorded_at, Timestamp::from(1));
+
+        // Consumers resume after the last sequence they processed
+        assert_eq!(state.outbox.after(Some(0), 10).await.unwrap().len(), 1);
+        assert!(state.outbox.after(Some(1), 10).await.unwrap().is_empty());
+
+        // Transfers keep their memo there too
+        let memo = Some("otc #7".to_string());
+        let to = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        let sequence = state
+            .record_transfer(trader, to, U256::from(5), memo, Timestamp::from(2))
+            .unwrap();
+        let (_, event) = state.outbox.after(Some(1), 10).await.unwrap().remove(0);
+        assert_eq!((sequence, event.kind.as_str()), (2, "Transfer"));
+        assert!(event.payload.contains("otc #7"));
+    }
+
+    #[tokio::test]
+    async fn test_state_export_import_round_trip() {
+        let mut source = TokenState::load(MemoryContext::default()).await.unwrap();
+        let alice = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let bob = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        for (at, trader) in [alice, bob, alice].into_iter().enumerate() {
+            let trade = Trade {
+                token_id: "token".to_string(),
+                trader,
+                is_buy: true,
+                token_amount: U256::from(10),
+                currency_amount: U256::from(1),
+                price: Price::default(),
+                timestamp: Timestamp::from(at as u64),
+                memo: None,
+                referrer: None,
+                flagged: false,
+            };
+            source.record_trade(trade).await.unwrap();
+        }
+        source.set_balance(alice, U256::from(20)).await.unwrap();
+        source.set_balance(bob, U256::from(10)).await.unwrap();
+        assert!(*source.import_sealed.get());
+
+        // Pages of two, trades before positions
+        let mut target = TokenState::load(MemoryContext::default()).await.unwrap();
+        target.import_chunk(StateChunk::Balances(source.export_balances(0, 2).await.unwrap()))
+            .await
+            .unwrap();
+        for since in [0, 2] {
+            let trades = source.get_trades_since(since, 2).await.unwrap();
+            target.import_chunk(StateChunk::Trades(trades)).await.unwrap();
+        }
+        let positions = source.export_positions(0, 2).await.unwrap();
+        assert_eq!(target.import_chunk(StateChunk::Positions(positions)).await.unwrap(), 2);
+
+        assert_eq!(*target.current_supply.get(), U256::from(30));
+        assert_eq!(*target.holder_count.get(), 2);
+        assert_eq!(*target.trade_count.get(), 3);
+        assert!(!*target.import_sealed.get());
+        let alice_trades = target.get_trades_by_trader(&alice, 0, 10).await.unwrap();
+        assert_eq!(alice_trades.len(), 2);
+        let position = target.user_positions.get(&alice).await.unwrap().unwrap();
+        assert_eq!(position.trades_count, 2);
+        assert_eq!(position.balance, U256::from(20));
+
+        // Replayed or skipped trades are rejected
+        let replay = source.get_trades_since(1, 1).await.unwrap();
+        assert!(target.import_chunk(StateChunk::Trades(replay)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_approval_is_single_use() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let alice = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let approved = StateChunk::Balances(vec![(alice, U256::from(20))]);
+        let forged = StateChunk::Balances(vec![(alice, U256::from(20_000))]);
+
+        state.approve_imports(&[approved.commitment()]).unwrap();
+        assert!(!state.take_import_approval(&forged).await.unwrap());
+        assert!(state.take_import_approval(&approved).await.unwrap());
+        assert!(!state.take_import_approval(&approved).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_prune_trades_keeps_aggregates() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let trader = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        for at in 0..5u64 {
+            let trade = Trade {
+                token_id: "token".to_string(),
+                trader,
+                is_buy: true,
+                token_amount: U256::from(at),
+                currency_amount: U256::from(10),
+                price: Price::default(),
+                timestamp: Timestamp::from(at),
+                memo: None,
+                referrer: None,
+                flagged: false,
+            };
+            state.record_trade(trade).await.unwrap();
+        }
+        let now = Timestamp::from(5);
+        let volume = state.volume_24h(now).await.unwrap();
+
+        assert_eq!(state.prune_trades(2, now).await.unwrap(), Some((0, 3)));
+        assert_eq!(state.prune_trades(2, now).await.unwrap(), None);
+        assert_eq!(*state.trade_count.get(), 5);
+        assert_eq!(state.volume_24h(now).await.unwrap(), volume);
+        assert_eq!(state.user_positions.get(&trader).await.unwrap().unwrap().trades_count, 5);
+        let reconciliation = state.reconcile().await.unwrap();
+        assert_eq!(reconciliation.trades_recorded, reconciliation.trade_count);
+
+        // Reads start after the pruned range
+        let sequences: Vec<u64> = state
+            .get_trades_since(0, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|(sequence, _)| *sequence)
+            .collect();
+        assert_eq!(sequences, vec![3, 4]);
+        assert_eq!(state.get_trades_by_trader(&trader, 0, 10).await.unwrap().len(), 2);
+
+        // The trader's index drops the pruned trades and their lots carry over
+        assert!(state.trades_by_trader.get(&(trader, 2)).await.unwrap().is_none());
+        assert_eq!(state.trades_by_trader.get(&(trader, 3)).await.unwrap(), Some(3));
+        assert_eq!(state.pruned_trader_trades.get(&trader).await.unwrap(), Some(3));
+        let lots = state.carried_lots.get(&trader).await.unwrap().unwrap();
+        assert_eq!(lots.len(), 3);
+        assert_eq!(lots.iter().fold(U256::zero(), |cost, lot| cost + lot.cost), U256::from(30));
+
+        let events = state.outbox.after(Some(4), 10).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].1.kind, "TradesPruned");
+    }
+
+    #[tokio::test]
+    async fn test_top_holders() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let account = |index: u32| Account {
+            chain_id: ChainId::root(index),
+            owner: AccountOwner::CHAIN,
+        };
+        let last = TOP_HOLDERS_TRACKED as u32 + 2;
+        state.current_supply.set(U256::from(10_000));
+
+        // A balance stored before the top holders were kept
+        state.balances.insert(&account(0), U256::from(4_000)).unwrap();
+        for index in 1..=last {
+            state.set_balance(account(index), U256::from(index)).await.unwrap();
+        }
+        assert_eq!(state.top_holders.get().len(), TOP_HOLDERS_TRACKED);
+        assert_eq!(state.top_holder_bps(), 18);
+
+        assert_eq!(state.backfill_top_holders(100).await.unwrap(), TOP_HOLDERS_TRACKED + 3);
+        assert_eq!(state.backfill_top_holders(100).await.unwrap(), 0);
+        assert_eq!(state.top_holder_bps(), 4_000);
+
+        // A seller moves down the ranking and an emptied balance leaves it
+        state.set_balance(account(0), U256::from(1_000)).await.unwrap();
+        assert_eq!(state.top_holder_bps(), 1_000);
+        state.set_balance(account(0), U256::zero()).await.unwrap();
+        assert_eq!(state.top_holders.get()[0], (account(last), U256::from(u64::from(last))));
+    }
+
+    #[tokio::test]
+    async fn test_holder_distribution_buckets() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        state.curve_config.set(BondingCurveConfig {
+            max_supply: U256::from(1_000_000),
+            ..BondingCurveConfig::default()
+        });
+        let account = |n| Account { chain_id: ChainId::root(n), owner: AccountOwner::CHAIN };
+
+        state.set_balance(account(0), U256::from(50)).await.unwrap();
+        state.set_balance(account(1), U256::from(500)).await.unwrap();
+        state.set_balance(account(2), U256::from(5_000)).await.unwrap();
+        state.set_balance(account(3), U256::from(50_000)).await.unwrap();
+        assert_eq!(state.holder_distribution().await.unwrap(), vec![1, 1, 1, 1]);
+
+        // Growing a balance moves the holder up; emptying it drops the holder
+        state.set_balance(account(0), U256::from(20_000)).await.unwrap();
+        state.set_balance(account(1), U256::zero()).await.unwrap();
+        assert_eq!(state.holder_distribution().await.unwrap(), vec![0, 0, 1, 2]);
+        assert_eq!(*state.holder_count.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_whitelist_phase() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let listed = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let other = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        let (before, after) = (Timestamp::from(50), Timestamp::from(100));
+
+        // Without a phase everyone may buy
+        state.curve_config.set(BondingCurveConfig::default());
+        assert!(!state.whitelist_active(before));
+        assert!(state.may_buy(&other, before).await.unwrap());
+
+        state.curve_config.set(BondingCurveConfig {
+            whitelist_ends_at: Some(after),
+            ..BondingCurveConfig::default()
+        });
+        assert_eq!(state.add_to_whitelist(&[listed, listed]).await.unwrap(), 1);
+        assert_eq!(state.add_to_whitelist(&[listed]).await.unwrap(), 0);
+        assert_eq!(*state.whitelist_count.get(), 1);
+        assert_eq!(state.list_whitelist(0, 10).await.unwrap(), vec![listed]);
+
+        assert!(state.whitelist_active(before));
+        assert!(state.may_buy(&listed, before).await.unwrap());
+        assert!(!state.may_buy(&other, before).await.unwrap());
+
+        // The phase ends at `whitelist_ends_at`
+        assert!(!state.whitelist_active(after));
+        assert!(state.may_buy(&other, after).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_graduation_supply_burns_unsold() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        state.curve_config.set(BondingCurveConfig {
+            max_supply: U256::from(1_000),
+            ..BondingCurveConfig::default()
+        });
+        state.current_supply.set(U256::from(600));
+        assert_eq!(state.unsold_supply(), U256::from(400));
+        assert_eq!(state.graduation_supply(), U256::from(1_000));
+
+        state.curve_config.set(BondingCurveConfig {
+            max_supply: U256::from(1_000),
+            burn_unsold_on_graduation: true,
+            ..BondingCurveConfig::default()
+        });
+        assert_eq!(state.graduation_supply(), U256::from(600));
+
+        // Oversold launches have nothing left to burn
+        state.current_supply.set(U256::from(1_200));
+        assert_eq!(state.unsold_supply(), U256::zero());
+        assert_eq!(state.graduation_supply(), U256::from(1_200));
+    }
+
+    #[tokio::test]
+    async fn test_snipe_tax_starts_when_the_curve_opens() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        state.created_at.set(Timestamp::from(1_000));
+        state.curve_config.set(BondingCurveConfig {
+            snipe_tax: Some(SnipeTaxConfig {
+                initial_bps: 2_000,
+                duration_micros: 1_000,
+                destination: SnipeTaxDestination::Burn,
+            }),
+            ..BondingCurveConfig::default()
+        });
+        assert_eq!(state.snipe_tax_bps(Timestamp::from(1_000)), 2_000);
+        assert_eq!(state.snipe_tax_bps(Timestamp::from(1_500)), 1_000);
+        assert_eq!(state.snipe_tax_bps(Timestamp::from(2_000)), 0);
+
+        // A whitelist phase is untaxed and delays the clock until everyone may buy
+        let mut config = state.curve_config.get().clone();
+        config.whitelist_ends_at = Some(Timestamp::from(5_000));
+        state.curve_config.set(config);
+        assert_eq!(state.snipe_tax_bps(Timestamp::from(2_000)), 0);
+        assert_eq!(state.snipe_tax_bps(Timestamp::from(5_000)), 2_000);
+        assert_eq!(state.snipe_tax_bps(Timestamp::from(5_250)), 1_500);
+
+        state.record_snipe_tax(Amount::from_attos(30), false);
+        state.record_snipe_tax(Amount::from_attos(20), true);
+        assert_eq!(*state.snipe_tax_collected.get(), Amount::from_attos(50));
+        assert_eq!(state.reserved_base(), Amount::from_attos(20));
+    }
+
+    #[tokio::test]
+    async fn test_tokens_for_spend_stays_within_budget() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        state.created_at.set(Timestamp::from(1_000));
+        state.curve_config.set(BondingCurveConfig {
+            snipe_tax: Some(SnipeTaxConfig {
+                initial_bps: 2_000,
+                duration_micros: 1_000,
+                destination: SnipeTaxDestination::Burn,
+            }),
+            ..BondingCurveConfig::default()
+        });
+        let config = state.curve_config.get().clone();
+        let spend = U256::from(100_000);
+
+        let (taxed, taxed_cost) = state.tokens_for_spend(spend, Timestamp::from(1_000)).unwrap();
+        let tax = wad::mul_bps(taxed_cost, 2_000).unwrap();
+        assert!(taxed_cost + tax <= spend);
+        let next = taxed + U256::one();
+        let one_more =
+            bonding_curve::calculate_buy_cost(U256::zero(), next, config.k, config.scale).unwrap();
+        assert!(one_more + wad::mul_bps(one_more, 2_000).unwrap() > spend);
+
+        // Once the tax has decayed the same spend buys more
+        let (untaxed, cost) = state.tokens_for_spend(spend, Timestamp::from(2_000)).unwrap();
+        assert!(untaxed > taxed);
+        assert!(cost <= spend);
+
+        // The final buy is capped at the unsold supply
+        state.current_supply.set(config.max_supply - U256::from(10));
+        let (capped, _) =
+            state.tokens_for_spend(U256::from(u64::MAX), Timestamp::from(2_000)).unwrap();
+        assert_eq!(capped, U256::from(10));
+    }
+
+    #[tokio::test]
+    async fn test_trade_interval_rate_limits_accounts() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let trader = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let other = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+
+        // Without an interval nothing is tracked
+        state.record_trade_at(trader, Timestamp::from(1_000)).unwrap();
+        assert_eq!(state.last_trade_at.count().await.unwrap(), 0);
+
+        state.curve_config.set(BondingCurveConfig {
+            min_trade_interval_micros: Some(500),
+            ..BondingCurveConfig::default()
+        });
+        state.record_trade_at(trader, Timestamp::from(1_000)).unwrap();
+        let retry_after = state.trade_retry_after(&trader, Timestamp::from(1_200)).await.unwrap();
+        assert_eq!(retry_after, Some(300));
+        assert_eq!(state.trade_retry_after(&trader, Timestamp::from(1_500)).await.unwrap(), None);
+        assert_eq!(state.trade_retry_after(&other, Timestamp::from(1_200)).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_recurring_buys_stop_once_min_raise_is_missed() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let owner = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        state.curve_config.set(BondingCurveConfig {
+            min_raise: Some(U256::from(1_000)),
+            deadline: Some(Timestamp::from(100)),
+            ..BondingCurveConfig::default()
+        });
+        state
+            .recurring_buys
+            .create(RecurringBuy {
+                owner,
+                amount_per_interval: Amount::from_tokens(1),
+                interval: TimeDelta::from_micros(50),
+                remaining_budget: Amount::from_tokens(3),
+                next_run_at: Timestamp::from(50),
+                created_at: Timestamp::from(0),
+                executions: 0,
+                tokens_bought: U256::zero(),
+                spent: Amount::ZERO,
+                active: true,
+            })
+            .await
+            .unwrap();
+
+        // Still due after the deadline, but the fill refuses it
+        assert!(state.is_launch_live(Timestamp::from(50)));
+        let due = state.recurring_buys.due(Timestamp::from(100), 10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(!state.is_launch_live(Timestamp::from(100)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_launch_refunds_pro_rata() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let small = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let large = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        state.curve_config.set(BondingCurveConfig {
+            min_raise: Some(U256::from(1_000)),
+            deadline: Some(Timestamp::from(100)),
+            ..BondingCurveConfig::default()
+        });
+        state.total_raised.set(U256::from(400));
+        state.set_balance(small, U256::from(100)).await.unwrap();
+        state.set_balance(large, U256::from(300)).await.unwrap();
+        state.current_supply.set(U256::from(400));
+
+        assert!(!state.missed_min_raise(Timestamp::from(99)));
+        assert!(state.missed_min_raise(Timestamp::from(100)));
+        assert!(!state.is_launch_live(Timestamp::from(100)));
+        assert!(state.redeem_refund(small).await.is_err());
+
+        state.launch_failure.set(Some(LaunchFailure {
+            failed_at: Timestamp::from(100),
+            refund_pool: Amount::from_attos(800),
+            supply: U256::from(400),
+            redeemed: Amount::ZERO,
+        }));
+        let unmet = state.unmet_graduation_conditions();
+        assert!(unmet.iter().any(|condition| condition.contains("minimum raise")));
+
+        // Shares come from the supply at failure, whatever the order
+        let (burned, refund) = state.redeem_refund(large).await.unwrap();
+        assert_eq!((burned, refund), (U256::from(300), Amount::from_attos(600)));
+        let (burned, refund) = state.redeem_refund(small).await.unwrap();
+        assert_eq!((burned, refund), (U256::from(100), Amount::from_attos(200)));
+        assert_eq!(state.redeem_refund(small).await.unwrap(), (U256::zero(), Amount::ZERO));
+
+        assert_eq!(*state.current_supply.get(), U256::zero());
+        assert_eq!(*state.holder_count.get(), 0);
+        assert_eq!(state.launch_failure.get().unwrap().redeemed, Amount::from_attos(800));
+    }
+
+    #[tokio::test]
+    async fn test_auction_settlement_refunds() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let early = Account { chain_id: ChainId::root(0), owner: AccountOwner::CHAIN };
+        let late = Account { chain_id: ChainId::root(1), owner: AccountOwner::CHAIN };
+        state.curve_config.set(BondingCurveConfig {
+            launch_mechanism: LaunchMechanism::DutchAuction(DutchAuctionConfig {
+                start_price: Price::from_integer(U256::from(10)).unwrap(),
+                floor_price: Price::from_integer(U256::from(2)).unwrap(),
+                starts_at: Timestamp::from(0),
+                duration_micros: 1_000,
+            }),
+            ..BondingCurveConfig::default()
+        });
+        assert!(state.is_auction());
+
+        // Bought at 10 and 4 per token; both are charged the clearing price of 4
+        state.auction.record_bid(early, U256::from(100), U256::from(1_000)).await.unwrap();
+        state.auction.record_bid(late, U256::from(50), U256::from(200)).await.unwrap();
+        state.current_supply.set(U256::from(150));
+        assert_eq!(*state.auction.bidder_count.get(), 2);
+        assert_eq!(state.auction.refund_due(&early).await.unwrap(), U256::zero());
+        assert_eq!(
+            state.unmet_graduation_conditions(),
+            vec!["Dutch auction has not settled".to_string()]
+        );
+
+        let clearing_price = Price::from_integer(U256::from(4)).unwrap();
+        assert_eq!(state.final_price(), None);
+        state.auction.settle(clearing_price, Timestamp::from(500), Amount::from_attos(600));
+        assert_eq!(state.final_price(), Some(clearing_price));
+        assert!(state.can_graduate());
+        assert_eq!(state.reserved_base(), Amount::from_attos(600));
+
+        assert_eq!(state.auction.take_refund(&early).await.unwrap(), U256::from(600));
+        assert_eq!(state.auction.take_refund(&early).await.unwrap(), U256::zero());
+        assert_eq!(state.auction.refund_due(&late).await.unwrap(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_sale_settles_in_commitment_order() {
+        let mut state = TokenState::load(MemoryContext::default()).await.unwrap();
+        let buyer = |n| Account { chain_id: ChainId::root(n), owner: AccountOwner::CHAIN };
+        state.curve_config.set(BondingCurveConfig {
+            launch_mechanism: LaunchMechanism::FixedPriceSale(FixedPriceSaleConfig {
+                price: Price::from_integer(U256::from(2)).unwrap(),
+                starts_at: Timestamp::from(0),
+                duration_micros: 1_000,
+            }),
+            ..BondingCurveConfig::default()
+        });
+        assert!(state.is_fixed_price_sale() && !state.is_bonding_curve());
+
+        for n in 0..3 {
+            state.sale.commit(buyer(n), U256::from(100)).await.unwrap();
+        }
+        state.sale.commit(buyer(0), U256::from(50)).await.unwrap();
+        assert_eq!(*state.sale.buyer_count.get(), 3);
+        assert_eq!(*state.sale.total_committed.get(), U256::from(350));
+
+        let first = state.sale.next_to_settle(2).await.unwrap();
+        assert_eq!(first.len(), 2);
+        let committed = SaleCommitment { committed: U256::from(150), ..SaleCommitment::default() };
+        assert_eq!(first[0], (buyer(0), committed));
+        assert!(!state.sale.all_allocated());
+        let (account, commitment) = first[1].clone();
+        state.sale.record_allocation(account, commitment, U256::from(50), U256::zero()).unwrap();
+        assert!(state.sale.commitments.get(&account).await.unwrap().unwrap().settled);
+
+        assert_eq!(state.sale.next_to_settle(2).await.unwrap().len(), 1);
+        assert!(state.sale.all_allocated());
+        assert_eq!(
+            state.unmet_graduation_conditions(),
+            vec!["Fixed-price sale has not settled".to_string()]
+        );
+        state.sale.mark_settled(Timestamp::from(1_000));
+        assert_eq!(
+            state.unmet_graduation_conditions(),
+            vec!["Fixed-price sale sold no tokens".to_string()]
+        );
     }
 }
@@ -100,9 +100,12 @@ echo -e "   Deploying factory contract..."
 FACTORY_BYTECODE="${PROJECT_ROOT}/contracts/target/wasm32-unknown-unknown/release/factory.wasm"
 TOKEN_BYTECODE="${PROJECT_ROOT}/contracts/target/wasm32-unknown-unknown/release/token.wasm"
 
-# Deploy factory application
+# Deploy factory application: free launches, at most 3 per creator an hour
+FACTORY_PARAMETERS='{"creation_fee":"0","treasury":null,"max_launches_per_window":3,'\
+'"launch_window_micros":3600000000,"ipfs_gateway":null}'
 FACTORY_APP_ID=$(linera publish-and-create \
     "${FACTORY_BYTECODE}" \
+    --json-parameters "${FACTORY_PARAMETERS}" \
     --required-application-ids [] \
     | grep "Application ID" | awk '{print $3}')
 
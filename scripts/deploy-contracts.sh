@@ -36,9 +36,18 @@ fi
 
 echo "Factory Bytecode ID: $FACTORY_BYTECODE"
 
+# Factory parameters: launch fee and per-creator rate limit (override via env)
+CREATION_FEE="${CREATION_FEE:-0}"
+MAX_LAUNCHES_PER_WINDOW="${MAX_LAUNCHES_PER_WINDOW:-3}"
+LAUNCH_WINDOW_MICROS="${LAUNCH_WINDOW_MICROS:-3600000000}"
+FACTORY_PARAMETERS='{"creation_fee":"'"$CREATION_FEE"'","treasury":null,'\
+'"max_launches_per_window":'"$MAX_LAUNCHES_PER_WINDOW"','\
+'"launch_window_micros":'"$LAUNCH_WINDOW_MICROS"',"ipfs_gateway":null}'
+
 # Create Factory application
 FACTORY_APP=$(linera create-application "$FACTORY_BYTECODE" \
     --json-argument '{}' \
+    --json-parameters "$FACTORY_PARAMETERS" \
     2>/dev/null)
 
 if [ -z "$FACTORY_APP" ]; then
@@ -62,9 +71,14 @@ fi
 
 echo "Swap Bytecode ID: $SWAP_BYTECODE"
 
+# Swap parameters: report swaps to the factory's chain for its platform stats
+DEFAULT_CHAIN=$(linera wallet show | grep "default" | head -1 | awk '{print $2}')
+SWAP_PARAMETERS='{"factory_chain_id":"'"$DEFAULT_CHAIN"'","buyback":null}'
+
 # Create Swap application
 SWAP_APP=$(linera create-application "$SWAP_BYTECODE" \
     --json-argument '{}' \
+    --json-parameters "$SWAP_PARAMETERS" \
     2>/dev/null)
 
 if [ -z "$SWAP_APP" ]; then
@@ -110,10 +110,19 @@ echo -e "${YELLOW}Swap bytecode: $SWAP_BYTECODE${NC}"
 # Step 4: Create applications
 echo -e "${GREEN}🚀 Creating applications on Conway testnet...${NC}"
 
+# Factory parameters: launch fee and per-creator rate limit (override via env)
+CREATION_FEE="${CREATION_FEE:-0}"
+MAX_LAUNCHES_PER_WINDOW="${MAX_LAUNCHES_PER_WINDOW:-3}"
+LAUNCH_WINDOW_MICROS="${LAUNCH_WINDOW_MICROS:-3600000000}"
+FACTORY_PARAMETERS='{"creation_fee":"'"$CREATION_FEE"'","treasury":null,'\
+'"max_launches_per_window":'"$MAX_LAUNCHES_PER_WINDOW"','\
+'"launch_window_micros":'"$LAUNCH_WINDOW_MICROS"',"ipfs_gateway":null}'
+
 # Create Factory application
 echo "  → Creating Factory application..."
 FACTORY_APP=$(linera create-application "$FACTORY_BYTECODE" \
     --json-argument '{"token_bytecode_id":"'"$TOKEN_BYTECODE"'"}' \
+    --json-parameters "$FACTORY_PARAMETERS" \
     2>&1 | grep "application ID" | awk '{print $NF}')
 
 if [ -z "$FACTORY_APP" ]; then
@@ -122,10 +131,14 @@ if [ -z "$FACTORY_APP" ]; then
 fi
 echo -e "${YELLOW}Factory app: $FACTORY_APP${NC}"
 
+# Swap parameters: report swaps to the factory chain for its platform stats
+SWAP_PARAMETERS='{"factory_chain_id":"'"$DEFAULT_CHAIN"'","buyback":null}'
+
 # Create Swap application
 echo "  → Creating Swap application..."
 SWAP_APP=$(linera create-application "$SWAP_BYTECODE" \
     --json-argument '{}' \
+    --json-parameters "$SWAP_PARAMETERS" \
     2>&1 | grep "application ID" | awk '{print $NF}')
 
 if [ -z "$SWAP_APP" ]; then